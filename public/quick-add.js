@@ -0,0 +1,130 @@
+// 快速添加窗口：由托盘菜单或全局快捷键呼出，保存后自动隐藏
+(function () {
+  const invoke = window.__TAURI__.core.invoke;
+  const form = document.getElementById('capture');
+  const title = document.getElementById('title');
+  const project = document.getElementById('project');
+  const date = document.getElementById('date');
+  const status = document.getElementById('status');
+  const defaultHint = status.textContent;
+  let parseTimer = null;
+
+  async function loadProjects() {
+    try {
+      const projects = await invoke('get_capture_projects');
+      const selected = project.value;
+      project.length = 1;
+      for (const item of projects) {
+        project.add(new Option(item.name, item.id));
+      }
+      project.value = projects.some((item) => item.id === selected) ? selected : '';
+    } catch (error) {
+      console.error('Failed to load projects:', error);
+    }
+  }
+
+  // 与主窗口日期选择器一致：按本地日期零点存为 ISO 字符串
+  function deadline() {
+    if (!date.value) return null;
+    const [y, m, d] = date.value.split('-').map(Number);
+    return new Date(y, m - 1, d).toISOString();
+  }
+
+  // 输入框下方预览识别结果，识别出的片段高亮显示
+  function renderPreview(text, result) {
+    if (!result || result.spans.length === 0) {
+      status.textContent = defaultHint;
+      status.className = 'hint';
+      return;
+    }
+    status.textContent = '';
+    status.className = 'hint preview';
+    let from = 0;
+    for (const span of result.spans) {
+      status.append(text.slice(from, span.start));
+      const mark = document.createElement('mark');
+      mark.className = span.kind;
+      mark.textContent = text.slice(span.start, span.end);
+      status.append(mark);
+      from = span.end;
+    }
+    status.append(text.slice(from));
+    if (result.project && !result.project_id) {
+      status.append(`（没有名为「${result.project}」的项目）`);
+    }
+  }
+
+  async function parse(text) {
+    if (!text.trim()) return null;
+    try {
+      return await invoke('parse_quick_add', { text });
+    } catch (error) {
+      console.error('Failed to parse quick add:', error);
+      return null;
+    }
+  }
+
+  title.addEventListener('input', () => {
+    clearTimeout(parseTimer);
+    const text = title.value;
+    parseTimer = setTimeout(async () => {
+      const result = await parse(text);
+      if (title.value !== text) return;
+      renderPreview(text, result);
+    }, 150);
+  });
+
+  function reset() {
+    title.value = '';
+    date.value = '';
+    status.textContent = defaultHint;
+    status.className = 'hint';
+    title.focus();
+  }
+
+  form.addEventListener('submit', async (event) => {
+    event.preventDefault();
+    if (!title.value.trim()) return;
+    clearTimeout(parseTimer);
+    try {
+      // 文字里写了日期、#项目、@标签 时优先于下拉框和日期框
+      const result = await parse(title.value);
+      await invoke('capture_task', {
+        title: result?.title || title.value,
+        project: result?.project_id || project.value || null,
+        date: result?.date || deadline(),
+        tags: result?.tags ?? [],
+        flagged: result?.flagged ?? false,
+        rrule: result?.rrule ?? null,
+      });
+      reset();
+    } catch (error) {
+      status.textContent = `保存失败：${error}`;
+      status.className = 'hint error';
+    }
+  });
+
+  // 项目下拉框和日期框里按回车同样提交
+  for (const field of [project, date]) {
+    field.addEventListener('keydown', (event) => {
+      if (event.key === 'Enter') {
+        event.preventDefault();
+        form.requestSubmit();
+      }
+    });
+  }
+
+  document.addEventListener('keydown', (event) => {
+    if (event.key === 'Escape') {
+      reset();
+      invoke('hide_capture_window');
+    }
+  });
+
+  // 每次窗口重新显示时刷新项目列表并聚焦输入框
+  window.addEventListener('focus', () => {
+    loadProjects();
+    title.focus();
+  });
+  loadProjects();
+})();
@@ -0,0 +1,35 @@
+-- migration: add subtasks (task hierarchy)
+-- purpose : let a task sit under another task at any depth, and read a
+--           whole subtree in one call
+-- affects : table public.tasks, function public.get_task_subtree
+-- notes   : deleting a parent removes its subtasks through the foreign key;
+--           sort_order is compared among siblings (same parent_id) only
+
+alter table public.tasks
+  add column if not exists parent_id uuid null references public.tasks (id) on delete cascade;
+
+comment on column public.tasks.parent_id is
+  'Parent task of a subtask; null for a top-level task.';
+
+create index if not exists tasks_parent_idx on public.tasks (parent_id, sort_order);
+
+-- a task and all of its subtasks; runs as the caller, so row level security
+-- still decides which rows come back
+create or replace function public.get_task_subtree(root_id uuid)
+returns setof public.tasks
+language sql
+stable
+security invoker
+set search_path = public
+as $$
+  with recursive subtree as (
+    select t.* from public.tasks t where t.id = root_id
+    union
+    select child.*
+    from public.tasks child
+    join subtree parent on child.parent_id = parent.id
+  )
+  select * from subtree;
+$$;
+
+grant execute on function public.get_task_subtree(uuid) to authenticated, anon;
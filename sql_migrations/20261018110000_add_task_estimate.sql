@@ -0,0 +1,13 @@
+-- migration: add estimated duration to tasks
+-- purpose : let a task carry how long it is expected to take, so tracked
+--           time can be compared against it
+-- affects : table public.tasks
+-- notes   : time entries themselves are desktop-only for now; online mode
+--           only syncs the estimate
+
+alter table public.tasks
+  add column if not exists estimate_minutes integer null
+  check (estimate_minutes is null or estimate_minutes >= 0);
+
+comment on column public.tasks.estimate_minutes is
+  'Estimated duration in minutes; null when not estimated.';
@@ -0,0 +1,14 @@
+-- migration: add per-field merge state to tasks
+-- purpose : let desktop replicas that sync through supabase merge
+--           concurrent edits of a task field by field instead of keeping
+--           one whole row
+-- affects : table public.tasks
+-- notes   : written by the desktop sync engine only. the web app leaves it
+--           alone; a column whose value no longer matches the recorded one
+--           counts as changed at the row's updated_at
+
+alter table public.tasks
+  add column if not exists crdt jsonb null;
+
+comment on column public.tasks.crdt is
+  'Per-field values and clocks of the task as last pushed by a desktop replica; null for rows only the web app has written.';
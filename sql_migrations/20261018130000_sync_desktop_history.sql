@@ -0,0 +1,63 @@
+-- migration: sync desktop pomodoro history
+-- purpose : let the desktop sync engine push and pull pomodoro sessions and
+--           task activities without losing fields the web app never had
+-- affects : tables public.pomodoro_sessions, public.task_activities
+-- notes   : pomodoro sessions gain the desktop's task link, notes and
+--           cancellation time, plus an updated_at bumped on every update so
+--           finished sessions are pulled incrementally. the desktop upserts
+--           activities, which needs update and delete policies too. check-ins
+--           already have every column the desktop keeps
+
+alter table public.pomodoro_sessions
+  add column if not exists task_id uuid null,
+  add column if not exists notes text null,
+  add column if not exists cancelled_at timestamptz null,
+  add column if not exists updated_at timestamptz not null default now();
+
+comment on column public.pomodoro_sessions.task_id is
+  'Task the session was focused on, if any; not a foreign key, as sessions outlive their task.';
+comment on column public.pomodoro_sessions.cancelled_at is
+  'When the session was stopped early; null for sessions run to the end or still running.';
+
+create index if not exists pomodoro_sessions_updated_idx
+  on public.pomodoro_sessions (updated_at, id);
+
+-- keeps the desktop's own stamp, so its pushes do not come back as changes
+create or replace function public.bump_updated_at()
+returns trigger
+language plpgsql
+as $$
+begin
+  if new.updated_at is not distinct from old.updated_at then
+    new.updated_at := now();
+  end if;
+  return new;
+end;
+$$;
+
+drop trigger if exists trg_pomodoro_sessions_bump_updated_at on public.pomodoro_sessions;
+create trigger trg_pomodoro_sessions_bump_updated_at
+before update on public.pomodoro_sessions
+for each row execute procedure public.bump_updated_at();
+
+create index if not exists task_activities_created_idx
+  on public.task_activities (created_at, id);
+
+drop policy if exists "task_activities_update_owner" on public.task_activities;
+create policy "task_activities_update_owner"
+  on public.task_activities for update to authenticated
+  using (user_id = auth.uid())
+  with check (
+    exists (
+      select 1
+      from public.tasks t
+      left join public.project_members pm on pm.project_id = t.project
+      where t.id = task_activities.task_id
+        and (t.user_id = auth.uid() or pm.user_id = auth.uid())
+    )
+  );
+
+drop policy if exists "task_activities_delete_owner" on public.task_activities;
+create policy "task_activities_delete_owner"
+  on public.task_activities for delete to authenticated
+  using (user_id = auth.uid());
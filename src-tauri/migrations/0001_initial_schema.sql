@@ -0,0 +1,116 @@
+-- migration: initial desktop schema
+-- purpose : local copies of the supabase tables used by the StorageAdapter
+--           (tasks, projects, tags, task_tags, pomodoro_sessions,
+--           task_activities, checkin_records) plus offline-only
+--           user_profile and attachments
+-- notes   : uses "if not exists" so databases created before versioning
+--           was introduced adopt this version without changes
+
+create table if not exists projects (
+  id text primary key,
+  name text not null,
+  icon text not null default '',
+  is_fixed integer,
+  color text,
+  view_type text,
+  created_at text,
+  updated_at text,
+  sort_order real,
+  user_id text,
+  is_shared integer,
+  original_owner_id text
+);
+
+create table if not exists tasks (
+  id text primary key,
+  title text not null,
+  completed integer not null default 0,
+  date text,
+  project text,
+  description text,
+  icon text,
+  completed_at text,
+  created_at text,
+  updated_at text,
+  user_id text,
+  sort_order real,
+  deleted integer not null default 0,
+  deleted_at text,
+  abandoned integer not null default 0,
+  abandoned_at text,
+  flagged integer not null default 0,
+  attachments text not null default '[]'
+);
+create index if not exists tasks_project_idx on tasks (project);
+create index if not exists tasks_sort_order_idx on tasks (sort_order);
+
+create table if not exists tags (
+  id text primary key,
+  name text not null,
+  user_id text,
+  project_id text,
+  created_at text
+);
+create index if not exists tags_project_idx on tags (project_id);
+
+create table if not exists task_tags (
+  task_id text not null references tasks (id) on delete cascade,
+  tag_id text not null references tags (id) on delete cascade,
+  created_at text not null,
+  primary key (task_id, tag_id)
+);
+create index if not exists task_tags_tag_idx on task_tags (tag_id);
+
+create table if not exists pomodoro_sessions (
+  id text primary key,
+  task_id text,
+  user_id text,
+  duration integer not null,
+  type text not null,
+  started_at text not null,
+  completed_at text,
+  created_at text not null,
+  notes text,
+  title text
+);
+create index if not exists pomodoro_sessions_task_idx on pomodoro_sessions (task_id);
+
+create table if not exists task_activities (
+  id text primary key,
+  task_id text not null,
+  user_id text,
+  action text not null,
+  metadata text,
+  created_at text not null
+);
+create index if not exists task_activities_task_idx on task_activities (task_id, created_at);
+
+create table if not exists checkin_records (
+  id text primary key,
+  user_id text,
+  check_in_time text not null,
+  note text,
+  created_at text not null
+);
+create index if not exists checkin_records_time_idx on checkin_records (check_in_time);
+
+create table if not exists user_profile (
+  id text primary key,
+  username text not null,
+  avatar_url text,
+  avatar_data text,
+  settings text,
+  updated_at text not null
+);
+
+create table if not exists attachments (
+  id text primary key,
+  task_id text not null,
+  filename text not null,
+  original_name text not null,
+  type text not null,
+  size integer not null,
+  data blob not null,
+  uploaded_at text not null
+);
+create index if not exists attachments_task_idx on attachments (task_id);
@@ -0,0 +1,11 @@
+-- migration: deadline reminder log
+-- purpose : remembers which task deadlines already raised a desktop
+--           notification, so repeated checks and restarts stay quiet
+-- notes   : keyed by (task_id, deadline); moving a task's date re-arms it
+
+create table if not exists deadline_reminders (
+  task_id text not null references tasks(id) on delete cascade,
+  deadline text not null,
+  notified_at text not null,
+  primary key (task_id, deadline)
+);
@@ -0,0 +1,22 @@
+-- migration: webhook outbox
+-- purpose : deadline webhooks are queued here before delivery so pending
+--           and retrying deliveries survive restarts
+-- notes   : dedup_key ("<event>:<task_id>:<date>") makes enqueueing
+--           idempotent; status is pending, delivered or failed
+
+create table if not exists webhook_outbox (
+  id text primary key,
+  event text not null,
+  dedup_key text unique,
+  url text not null,
+  body text not null,
+  status text not null default 'pending',
+  attempts integer not null default 0,
+  next_attempt_at text not null,
+  last_error text,
+  created_at text not null,
+  delivered_at text
+);
+
+create index if not exists idx_webhook_outbox_due
+  on webhook_outbox (status, next_attempt_at);
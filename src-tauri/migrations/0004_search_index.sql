@@ -0,0 +1,13 @@
+-- migration: search index
+-- purpose : full-text index over task titles, descriptions and tag names
+-- notes   : rows are written by the storage layer, which flattens editor
+--           JSON and spaces out CJK characters first; the index is filled
+--           for existing tasks on the first open after this migration
+
+create virtual table if not exists task_search using fts5(
+  task_id unindexed,
+  title,
+  body,
+  tags,
+  tokenize = 'unicode61 remove_diacritics 2'
+);
@@ -0,0 +1,14 @@
+-- migration: smart lists
+-- purpose : saved task queries, shown in the sidebar as virtual projects
+-- notes   : query holds the query-language text and is parsed on every run,
+--           so relative dates like due:<7d follow the calendar
+
+create table if not exists smart_lists (
+  id text primary key,
+  name text not null,
+  query text not null,
+  icon text not null default 'filter',
+  sort_order real,
+  created_at text not null,
+  updated_at text not null
+);
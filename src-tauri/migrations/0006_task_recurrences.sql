@@ -0,0 +1,19 @@
+-- migration: recurring tasks
+-- purpose : attaches an RFC 5545 RRULE to a task; completing an occurrence
+--           creates the next one as a new task in the same series
+-- notes   : one row per task instance; dtstart and occurrence let COUNT
+--           span the whole series, next_task_id makes completion idempotent
+
+create table if not exists task_recurrences (
+  task_id text primary key references tasks(id) on delete cascade,
+  series_id text not null,
+  rrule text not null,
+  dtstart text not null,
+  exdates text not null default '[]',
+  occurrence integer not null default 1,
+  next_task_id text,
+  created_at text not null,
+  updated_at text not null
+);
+
+create index if not exists task_recurrences_series_idx on task_recurrences (series_id);
@@ -0,0 +1,9 @@
+-- migration: task hierarchy
+-- purpose : lets a task sit under another task, at any depth
+-- notes   : no foreign key, the same as `project`: a backup may list a
+--           subtask before its parent. Deleting a task removes its subtree
+--           in code; sort_order is compared among siblings only
+
+alter table tasks add column parent_id text;
+
+create index if not exists tasks_parent_idx on tasks (parent_id, sort_order);
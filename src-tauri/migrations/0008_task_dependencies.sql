@@ -0,0 +1,13 @@
+-- migration: task dependencies
+-- purpose : "blocked by" links between tasks; blocked is derived from the
+--           blockers' state and never stored
+-- notes   : cycles are rejected in code before a link is written
+
+create table if not exists task_dependencies (
+  task_id text not null references tasks(id) on delete cascade,
+  blocker_id text not null references tasks(id) on delete cascade,
+  created_at text not null,
+  primary key (task_id, blocker_id)
+);
+
+create index if not exists task_dependencies_blocker_idx on task_dependencies (blocker_id);
@@ -0,0 +1,12 @@
+-- migration: pomodoro timer
+-- purpose : lets the native timer tell cancelled sessions apart from ones
+--           still running, and keeps its state across restarts
+-- notes   : pomodoro_timer holds a single row with the timer as JSON
+
+alter table pomodoro_sessions add column cancelled_at text;
+
+create table if not exists pomodoro_timer (
+  id integer primary key check (id = 1),
+  state text not null,
+  updated_at text not null
+);
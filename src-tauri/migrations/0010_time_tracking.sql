@@ -0,0 +1,23 @@
+-- migration: time tracking
+-- purpose : per-task time logs, from a start/stop timer or entered by hand,
+--           and an estimate on each task
+-- notes   : ended_at is null while a timer runs; the partial unique index
+--           allows at most one running timer at a time. Focus pomodoros
+--           linked to a task count as tracked time without a row here
+
+alter table tasks add column estimate_minutes integer;
+
+create table if not exists time_entries (
+  id text primary key,
+  task_id text not null references tasks(id) on delete cascade,
+  started_at text not null,
+  ended_at text,
+  source text not null,
+  note text,
+  created_at text not null,
+  updated_at text not null
+);
+
+create index if not exists time_entries_task_idx on time_entries (task_id, started_at);
+create unique index if not exists time_entries_running_idx
+  on time_entries ((ended_at is null)) where ended_at is null;
@@ -0,0 +1,10 @@
+-- migration: iCalendar UIDs
+-- purpose : remember the UID a task was imported under, so importing the
+--           same .ics again updates the task instead of duplicating it
+-- notes   : tasks created here are exported with their own id as UID and
+--           need no row; the mapping goes away with the task
+
+create table if not exists ical_uids (
+  uid text primary key,
+  task_id text not null unique references tasks(id) on delete cascade
+);
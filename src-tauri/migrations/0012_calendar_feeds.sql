@@ -0,0 +1,13 @@
+-- migration: calendar feeds
+-- purpose : secret tokens for the read-only calendar feed served on
+--           localhost, one per subscribed client so each can be revoked
+-- notes   : whether the server runs, and on which port, lives in the
+--           `calendar_feed` user setting
+
+create table if not exists calendar_feeds (
+  id text primary key,
+  name text not null,
+  token text not null unique,
+  created_at text not null,
+  last_fetched_at text
+);
@@ -0,0 +1,9 @@
+-- migration: caldav resources
+-- purpose : the resource name a CalDAV client chose for a task when it
+--           differs from `<uid>.ics`, so the client finds it again
+-- notes   : tasks without a row here are served as `<uid>.ics`
+
+create table if not exists caldav_resources (
+  name text primary key,
+  task_id text not null unique references tasks(id) on delete cascade
+);
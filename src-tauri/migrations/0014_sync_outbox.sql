@@ -0,0 +1,113 @@
+-- migration: sync outbox
+-- purpose : change log of local writes to the synced tables (projects,
+--           tags, tasks, task_tags), recorded by triggers so every writer
+--           (app, CLI, CalDAV) is covered; the sync engine pushes and then
+--           clears it
+-- notes   : nothing is logged until sync is enabled (the `enabled` row in
+--           sync_state), and writes made while applying remote rows (the
+--           `applying_remote` row) are not logged back; sync_state also
+--           holds the per-table pull watermarks
+
+create table if not exists sync_outbox (
+  seq integer primary key autoincrement,
+  table_name text not null,
+  row_id text not null,
+  op text not null,
+  changed_at text not null
+);
+
+create index if not exists idx_sync_outbox_row
+  on sync_outbox (table_name, row_id);
+
+create table if not exists sync_state (
+  key text primary key,
+  value text not null
+);
+
+create trigger if not exists sync_projects_insert after insert on projects
+when exists (select 1 from sync_state where key = 'enabled')
+  and not exists (select 1 from sync_state where key = 'applying_remote')
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at)
+  values ('projects', new.id, 'upsert', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+end;
+
+create trigger if not exists sync_projects_update after update on projects
+when exists (select 1 from sync_state where key = 'enabled')
+  and not exists (select 1 from sync_state where key = 'applying_remote')
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at)
+  values ('projects', new.id, 'upsert', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+end;
+
+create trigger if not exists sync_projects_delete after delete on projects
+when exists (select 1 from sync_state where key = 'enabled')
+  and not exists (select 1 from sync_state where key = 'applying_remote')
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at)
+  values ('projects', old.id, 'delete', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+end;
+
+create trigger if not exists sync_tags_insert after insert on tags
+when exists (select 1 from sync_state where key = 'enabled')
+  and not exists (select 1 from sync_state where key = 'applying_remote')
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at)
+  values ('tags', new.id, 'upsert', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+end;
+
+create trigger if not exists sync_tags_update after update on tags
+when exists (select 1 from sync_state where key = 'enabled')
+  and not exists (select 1 from sync_state where key = 'applying_remote')
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at)
+  values ('tags', new.id, 'upsert', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+end;
+
+create trigger if not exists sync_tags_delete after delete on tags
+when exists (select 1 from sync_state where key = 'enabled')
+  and not exists (select 1 from sync_state where key = 'applying_remote')
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at)
+  values ('tags', old.id, 'delete', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+end;
+
+create trigger if not exists sync_tasks_insert after insert on tasks
+when exists (select 1 from sync_state where key = 'enabled')
+  and not exists (select 1 from sync_state where key = 'applying_remote')
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at)
+  values ('tasks', new.id, 'upsert', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+end;
+
+create trigger if not exists sync_tasks_update after update on tasks
+when exists (select 1 from sync_state where key = 'enabled')
+  and not exists (select 1 from sync_state where key = 'applying_remote')
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at)
+  values ('tasks', new.id, 'upsert', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+end;
+
+create trigger if not exists sync_tasks_delete after delete on tasks
+when exists (select 1 from sync_state where key = 'enabled')
+  and not exists (select 1 from sync_state where key = 'applying_remote')
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at)
+  values ('tasks', old.id, 'delete', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+end;
+
+create trigger if not exists sync_task_tags_insert after insert on task_tags
+when exists (select 1 from sync_state where key = 'enabled')
+  and not exists (select 1 from sync_state where key = 'applying_remote')
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at)
+  values ('task_tags', new.task_id || '/' || new.tag_id, 'upsert', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+end;
+
+create trigger if not exists sync_task_tags_delete after delete on task_tags
+when exists (select 1 from sync_state where key = 'enabled')
+  and not exists (select 1 from sync_state where key = 'applying_remote')
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at)
+  values ('task_tags', old.task_id || '/' || old.tag_id, 'delete', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+end;
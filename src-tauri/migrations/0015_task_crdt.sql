@@ -0,0 +1,121 @@
+-- migration: task crdt
+-- purpose : per-field clocks so that replicas of a task merge in any order
+--           and converge: a last-writer-wins stamp per task column, and
+--           add/remove dots per tag link (an observed-remove set)
+-- notes   : stamps come from the hybrid logical clock in crdt_clock; the
+--           triggers stamp local writes only, while the merge code sets
+--           crdt_clock.merging so rows it writes keep their remote stamps.
+--           a column without a stamp row has never changed since creation.
+--           existing tag links get a dot from this replica
+
+create table if not exists crdt_clock (
+  id integer primary key check (id = 1),
+  replica text not null,
+  at integer not null default 0,
+  merging integer not null default 0
+);
+
+insert or ignore into crdt_clock (id, replica) values (1, lower(hex(randomblob(8))));
+
+create table if not exists task_field_stamps (
+  task_id text not null,
+  field text not null,
+  at integer not null,
+  replica text not null,
+  primary key (task_id, field)
+);
+
+create table if not exists task_tag_dots (
+  task_id text not null,
+  tag_id text not null,
+  at integer not null,
+  replica text not null,
+  removed integer not null default 0,
+  primary key (task_id, tag_id, at, replica)
+);
+
+insert or ignore into task_tag_dots (task_id, tag_id, at, replica)
+select task_id, tag_id, 0, (select replica from crdt_clock) from task_tags;
+
+create trigger if not exists crdt_tasks_update after update on tasks
+when (select merging from crdt_clock) = 0
+begin
+  update crdt_clock
+  set at = max(at + 1, cast((julianday('now') - 2440587.5) * 86400000 as integer));
+  insert into task_field_stamps (task_id, field, at, replica)
+  select new.id, 'title', at, replica from crdt_clock where old.title is not new.title
+  on conflict (task_id, field) do update set at = excluded.at, replica = excluded.replica;
+  insert into task_field_stamps (task_id, field, at, replica)
+  select new.id, 'completed', at, replica from crdt_clock where old.completed is not new.completed
+  on conflict (task_id, field) do update set at = excluded.at, replica = excluded.replica;
+  insert into task_field_stamps (task_id, field, at, replica)
+  select new.id, 'date', at, replica from crdt_clock where old.date is not new.date
+  on conflict (task_id, field) do update set at = excluded.at, replica = excluded.replica;
+  insert into task_field_stamps (task_id, field, at, replica)
+  select new.id, 'project', at, replica from crdt_clock where old.project is not new.project
+  on conflict (task_id, field) do update set at = excluded.at, replica = excluded.replica;
+  insert into task_field_stamps (task_id, field, at, replica)
+  select new.id, 'description', at, replica from crdt_clock where old.description is not new.description
+  on conflict (task_id, field) do update set at = excluded.at, replica = excluded.replica;
+  insert into task_field_stamps (task_id, field, at, replica)
+  select new.id, 'icon', at, replica from crdt_clock where old.icon is not new.icon
+  on conflict (task_id, field) do update set at = excluded.at, replica = excluded.replica;
+  insert into task_field_stamps (task_id, field, at, replica)
+  select new.id, 'completed_at', at, replica from crdt_clock where old.completed_at is not new.completed_at
+  on conflict (task_id, field) do update set at = excluded.at, replica = excluded.replica;
+  insert into task_field_stamps (task_id, field, at, replica)
+  select new.id, 'created_at', at, replica from crdt_clock where old.created_at is not new.created_at
+  on conflict (task_id, field) do update set at = excluded.at, replica = excluded.replica;
+  insert into task_field_stamps (task_id, field, at, replica)
+  select new.id, 'updated_at', at, replica from crdt_clock where old.updated_at is not new.updated_at
+  on conflict (task_id, field) do update set at = excluded.at, replica = excluded.replica;
+  insert into task_field_stamps (task_id, field, at, replica)
+  select new.id, 'sort_order', at, replica from crdt_clock where old.sort_order is not new.sort_order
+  on conflict (task_id, field) do update set at = excluded.at, replica = excluded.replica;
+  insert into task_field_stamps (task_id, field, at, replica)
+  select new.id, 'deleted', at, replica from crdt_clock where old.deleted is not new.deleted
+  on conflict (task_id, field) do update set at = excluded.at, replica = excluded.replica;
+  insert into task_field_stamps (task_id, field, at, replica)
+  select new.id, 'deleted_at', at, replica from crdt_clock where old.deleted_at is not new.deleted_at
+  on conflict (task_id, field) do update set at = excluded.at, replica = excluded.replica;
+  insert into task_field_stamps (task_id, field, at, replica)
+  select new.id, 'abandoned', at, replica from crdt_clock where old.abandoned is not new.abandoned
+  on conflict (task_id, field) do update set at = excluded.at, replica = excluded.replica;
+  insert into task_field_stamps (task_id, field, at, replica)
+  select new.id, 'abandoned_at', at, replica from crdt_clock where old.abandoned_at is not new.abandoned_at
+  on conflict (task_id, field) do update set at = excluded.at, replica = excluded.replica;
+  insert into task_field_stamps (task_id, field, at, replica)
+  select new.id, 'flagged', at, replica from crdt_clock where old.flagged is not new.flagged
+  on conflict (task_id, field) do update set at = excluded.at, replica = excluded.replica;
+  insert into task_field_stamps (task_id, field, at, replica)
+  select new.id, 'attachments', at, replica from crdt_clock where old.attachments is not new.attachments
+  on conflict (task_id, field) do update set at = excluded.at, replica = excluded.replica;
+  insert into task_field_stamps (task_id, field, at, replica)
+  select new.id, 'parent_id', at, replica from crdt_clock where old.parent_id is not new.parent_id
+  on conflict (task_id, field) do update set at = excluded.at, replica = excluded.replica;
+  insert into task_field_stamps (task_id, field, at, replica)
+  select new.id, 'estimate_minutes', at, replica from crdt_clock where old.estimate_minutes is not new.estimate_minutes
+  on conflict (task_id, field) do update set at = excluded.at, replica = excluded.replica;
+end;
+
+create trigger if not exists crdt_tasks_delete after delete on tasks
+begin
+  delete from task_field_stamps where task_id = old.id;
+  delete from task_tag_dots where task_id = old.id;
+end;
+
+create trigger if not exists crdt_task_tags_insert after insert on task_tags
+when (select merging from crdt_clock) = 0
+begin
+  update crdt_clock
+  set at = max(at + 1, cast((julianday('now') - 2440587.5) * 86400000 as integer));
+  insert or ignore into task_tag_dots (task_id, tag_id, at, replica)
+  select new.task_id, new.tag_id, at, replica from crdt_clock;
+end;
+
+create trigger if not exists crdt_task_tags_delete after delete on task_tags
+when (select merging from crdt_clock) = 0
+begin
+  update task_tag_dots set removed = 1
+  where task_id = old.task_id and tag_id = old.tag_id;
+end;
@@ -0,0 +1,113 @@
+-- migration: peer sync
+-- purpose : paired LAN peers, and a sync_outbox that several consumers
+--           read: Supabase takes the rows still marked `push`, each peer
+--           keeps the last seq it has received
+-- notes   : changes are logged while Supabase sync is enabled or any peer
+--           is paired. rows written while applying Supabase rows are logged
+--           with push = 0, so peers still hear about them. the sync engine
+--           prunes rows every consumer has seen; a peer that has never
+--           synced gets a full copy instead of the log
+
+alter table sync_outbox add column push integer not null default 1;
+
+create table if not exists peers (
+  id text primary key,
+  name text not null,
+  secret text not null,
+  address text,
+  sent_seq integer,
+  paired_at text not null,
+  last_synced_at text
+);
+
+create view if not exists sync_push as
+select exists (select 1 from sync_state where key = 'enabled')
+  and not exists (select 1 from sync_state where key = 'applying_remote') as push;
+
+drop trigger if exists sync_projects_insert;
+create trigger sync_projects_insert after insert on projects
+when exists (select 1 from sync_state where key = 'enabled') or exists (select 1 from peers)
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at, push)
+  values ('projects', new.id, 'upsert', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), (select push from sync_push));
+end;
+
+drop trigger if exists sync_projects_update;
+create trigger sync_projects_update after update on projects
+when exists (select 1 from sync_state where key = 'enabled') or exists (select 1 from peers)
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at, push)
+  values ('projects', new.id, 'upsert', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), (select push from sync_push));
+end;
+
+drop trigger if exists sync_projects_delete;
+create trigger sync_projects_delete after delete on projects
+when exists (select 1 from sync_state where key = 'enabled') or exists (select 1 from peers)
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at, push)
+  values ('projects', old.id, 'delete', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), (select push from sync_push));
+end;
+
+drop trigger if exists sync_tags_insert;
+create trigger sync_tags_insert after insert on tags
+when exists (select 1 from sync_state where key = 'enabled') or exists (select 1 from peers)
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at, push)
+  values ('tags', new.id, 'upsert', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), (select push from sync_push));
+end;
+
+drop trigger if exists sync_tags_update;
+create trigger sync_tags_update after update on tags
+when exists (select 1 from sync_state where key = 'enabled') or exists (select 1 from peers)
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at, push)
+  values ('tags', new.id, 'upsert', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), (select push from sync_push));
+end;
+
+drop trigger if exists sync_tags_delete;
+create trigger sync_tags_delete after delete on tags
+when exists (select 1 from sync_state where key = 'enabled') or exists (select 1 from peers)
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at, push)
+  values ('tags', old.id, 'delete', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), (select push from sync_push));
+end;
+
+drop trigger if exists sync_tasks_insert;
+create trigger sync_tasks_insert after insert on tasks
+when exists (select 1 from sync_state where key = 'enabled') or exists (select 1 from peers)
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at, push)
+  values ('tasks', new.id, 'upsert', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), (select push from sync_push));
+end;
+
+drop trigger if exists sync_tasks_update;
+create trigger sync_tasks_update after update on tasks
+when exists (select 1 from sync_state where key = 'enabled') or exists (select 1 from peers)
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at, push)
+  values ('tasks', new.id, 'upsert', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), (select push from sync_push));
+end;
+
+drop trigger if exists sync_tasks_delete;
+create trigger sync_tasks_delete after delete on tasks
+when exists (select 1 from sync_state where key = 'enabled') or exists (select 1 from peers)
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at, push)
+  values ('tasks', old.id, 'delete', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), (select push from sync_push));
+end;
+
+drop trigger if exists sync_task_tags_insert;
+create trigger sync_task_tags_insert after insert on task_tags
+when exists (select 1 from sync_state where key = 'enabled') or exists (select 1 from peers)
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at, push)
+  values ('task_tags', new.task_id || '/' || new.tag_id, 'upsert', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), (select push from sync_push));
+end;
+
+drop trigger if exists sync_task_tags_delete;
+create trigger sync_task_tags_delete after delete on task_tags
+when exists (select 1 from sync_state where key = 'enabled') or exists (select 1 from peers)
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at, push)
+  values ('task_tags', old.task_id || '/' || old.tag_id, 'delete', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), (select push from sync_push));
+end;
@@ -0,0 +1,86 @@
+-- migration: sync history
+-- purpose : sync pomodoro sessions, task activities and check-ins with
+--           Supabase too: log their changes to sync_outbox like the other
+--           synced tables, and stamp pomodoro sessions with `updated_at` so
+--           finished sessions are pulled incrementally
+-- notes   : activities and check-ins are only ever inserted and are pulled
+--           by `created_at`. peers do not exchange these tables, so they
+--           are only logged while Supabase sync is enabled. a task's
+--           activities now go with it, as they do in Supabase, where they
+--           could not be pushed without the task
+
+alter table pomodoro_sessions add column updated_at text;
+
+update pomodoro_sessions
+set updated_at = coalesce(completed_at, cancelled_at, created_at);
+
+delete from task_activities
+where task_id not in (select id from tasks);
+
+create trigger if not exists task_activities_task_delete after delete on tasks
+begin
+  delete from task_activities where task_id = old.id;
+end;
+
+create trigger if not exists sync_pomodoro_sessions_insert after insert on pomodoro_sessions
+when exists (select 1 from sync_state where key = 'enabled')
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at, push)
+  values ('pomodoro_sessions', new.id, 'upsert', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), (select push from sync_push));
+end;
+
+create trigger if not exists sync_pomodoro_sessions_update after update on pomodoro_sessions
+when exists (select 1 from sync_state where key = 'enabled')
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at, push)
+  values ('pomodoro_sessions', new.id, 'upsert', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), (select push from sync_push));
+end;
+
+create trigger if not exists sync_pomodoro_sessions_delete after delete on pomodoro_sessions
+when exists (select 1 from sync_state where key = 'enabled')
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at, push)
+  values ('pomodoro_sessions', old.id, 'delete', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), (select push from sync_push));
+end;
+
+create trigger if not exists sync_task_activities_insert after insert on task_activities
+when exists (select 1 from sync_state where key = 'enabled')
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at, push)
+  values ('task_activities', new.id, 'upsert', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), (select push from sync_push));
+end;
+
+create trigger if not exists sync_task_activities_update after update on task_activities
+when exists (select 1 from sync_state where key = 'enabled')
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at, push)
+  values ('task_activities', new.id, 'upsert', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), (select push from sync_push));
+end;
+
+create trigger if not exists sync_task_activities_delete after delete on task_activities
+when exists (select 1 from sync_state where key = 'enabled')
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at, push)
+  values ('task_activities', old.id, 'delete', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), (select push from sync_push));
+end;
+
+create trigger if not exists sync_checkin_records_insert after insert on checkin_records
+when exists (select 1 from sync_state where key = 'enabled')
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at, push)
+  values ('checkin_records', new.id, 'upsert', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), (select push from sync_push));
+end;
+
+create trigger if not exists sync_checkin_records_update after update on checkin_records
+when exists (select 1 from sync_state where key = 'enabled')
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at, push)
+  values ('checkin_records', new.id, 'upsert', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), (select push from sync_push));
+end;
+
+create trigger if not exists sync_checkin_records_delete after delete on checkin_records
+when exists (select 1 from sync_state where key = 'enabled')
+begin
+  insert into sync_outbox (table_name, row_id, op, changed_at, push)
+  values ('checkin_records', old.id, 'delete', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), (select push from sync_push));
+end;
@@ -0,0 +1,21 @@
+-- migration: task tombstones
+-- purpose : remember hard-deleted tasks, so that merging a state another
+--           replica still holds (edited or not) does not bring them back
+-- notes   : a delete wins over any concurrent edit, as it already did for
+--           peers while the delete was still in the log. tombstones are kept
+--           for good; ids are never reused
+
+create table if not exists task_tombstones (
+  task_id text primary key,
+  deleted_at text not null
+);
+
+drop trigger if exists crdt_tasks_delete;
+
+create trigger if not exists crdt_tasks_delete after delete on tasks
+begin
+  delete from task_field_stamps where task_id = old.id;
+  delete from task_tag_dots where task_id = old.id;
+  insert or ignore into task_tombstones (task_id, deleted_at)
+  values (old.id, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+end;
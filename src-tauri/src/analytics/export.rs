@@ -0,0 +1,233 @@
+//! Report exports: a flat CSV for spreadsheets, the raw JSON, and a
+//! single HTML file with inline styles that opens anywhere.
+
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Bucket, Report};
+use crate::error::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+  Csv,
+  Json,
+  Html,
+}
+
+impl ReportFormat {
+  pub fn extension(self) -> &'static str {
+    match self {
+      ReportFormat::Csv => "csv",
+      ReportFormat::Json => "json",
+      ReportFormat::Html => "html",
+    }
+  }
+}
+
+pub fn render(report: &Report, format: ReportFormat) -> Result<String> {
+  Ok(match format {
+    ReportFormat::Csv => to_csv(report),
+    ReportFormat::Json => serde_json::to_string_pretty(report)?,
+    ReportFormat::Html => to_html(report),
+  })
+}
+
+/// `snail-report-2026-09-01_2026-09-30.csv`
+pub fn file_name(report: &Report, format: ReportFormat) -> String {
+  format!(
+    "snail-report-{}_{}.{}",
+    report.from,
+    report.to,
+    format.extension()
+  )
+}
+
+/// Every breakdown with its section name, in the order they are exported.
+fn sections(report: &Report) -> [(&'static str, &'static str, &[Bucket]); 6] {
+  [
+    ("total", "合计", std::slice::from_ref(&report.totals)),
+    ("day", "每日", &report.days),
+    ("project", "项目", &report.projects),
+    ("tag", "标签", &report.tags),
+    ("weekday", "星期", &report.weekdays),
+    ("hour", "时段", &report.hours),
+  ]
+}
+
+const CSV_HEADER: &str = "section,key,label,focus_sessions,focus_minutes,break_sessions,\
+                          break_minutes,cancelled_sessions,tasks_completed,check_ins";
+
+pub(crate) fn csv_field(value: &str) -> String {
+  if value.contains([',', '"', '\n', '\r']) {
+    format!("\"{}\"", value.replace('"', "\"\""))
+  } else {
+    value.to_string()
+  }
+}
+
+/// One row per bucket, all sections in one table so a pivot table can
+/// filter on `section`.
+pub fn to_csv(report: &Report) -> String {
+  let mut out = String::from(CSV_HEADER);
+  out.push_str("\r\n");
+  for (section, _, buckets) in sections(report) {
+    for b in buckets {
+      let _ = write!(
+        out,
+        "{section},{},{},{},{},{},{},{},{},{}\r\n",
+        csv_field(&b.key),
+        csv_field(&b.label),
+        b.focus_sessions,
+        b.focus_minutes,
+        b.break_sessions,
+        b.break_minutes,
+        b.cancelled_sessions,
+        b.tasks_completed,
+        b.check_ins
+      );
+    }
+  }
+  out
+}
+
+fn escape(value: &str) -> String {
+  let mut out = String::with_capacity(value.len());
+  for c in value.chars() {
+    match c {
+      '&' => out.push_str("&amp;"),
+      '<' => out.push_str("&lt;"),
+      '>' => out.push_str("&gt;"),
+      '"' => out.push_str("&quot;"),
+      '\'' => out.push_str("&#39;"),
+      c => out.push(c),
+    }
+  }
+  out
+}
+
+const STYLE: &str = "body{font-family:-apple-system,BlinkMacSystemFont,'Segoe UI','PingFang SC',\
+sans-serif;margin:32px auto;max-width:960px;color:#1f2937;padding:0 16px}\
+h1{font-size:22px}h2{font-size:16px;margin-top:32px}.muted{color:#6b7280;font-size:13px}\
+.cards{display:grid;grid-template-columns:repeat(auto-fit,minmax(150px,1fr));gap:12px}\
+.card{border:1px solid #e5e7eb;border-radius:8px;padding:12px}.card b{display:block;font-size:24px}\
+table{border-collapse:collapse;width:100%;font-size:13px}\
+th,td{border-bottom:1px solid #f3f4f6;padding:6px 8px;text-align:right;white-space:nowrap}\
+th:first-child,td:first-child{text-align:left}td.bar{width:35%}\
+.bar span{display:block;height:10px;border-radius:5px;background:#ef4444}";
+
+fn card(out: &mut String, label: &str, value: impl std::fmt::Display) {
+  let _ = write!(out, "<div class=\"card\"><b>{value}</b>{label}</div>");
+}
+
+fn table(out: &mut String, title: &str, buckets: &[Bucket]) {
+  let _ = write!(out, "<h2>{title}</h2>");
+  if buckets.is_empty() {
+    out.push_str("<p class=\"muted\">暂无数据</p>");
+    return;
+  }
+  let max = buckets.iter().map(|b| b.focus_minutes).max().unwrap_or(0);
+  out.push_str(
+    "<table><tr><th></th><th>专注</th><th>专注分钟</th><th>休息分钟</th><th>中断</th>\
+     <th>完成任务</th><th>打卡</th><th>专注分布</th></tr>",
+  );
+  for b in buckets {
+    let width = if max > 0 {
+      b.focus_minutes as f64 * 100.0 / max as f64
+    } else {
+      0.0
+    };
+    let _ = write!(
+      out,
+      "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td>\
+       <td class=\"bar\"><span style=\"width:{width:.1}%\"></span></td></tr>",
+      escape(&b.label),
+      b.focus_sessions,
+      b.focus_minutes,
+      b.break_minutes,
+      b.cancelled_sessions,
+      b.tasks_completed,
+      b.check_ins
+    );
+  }
+  out.push_str("</table>");
+}
+
+pub fn to_html(report: &Report) -> String {
+  let title = format!("效率报告 {} 至 {}", report.from, report.to);
+  let mut out = format!(
+    "<!DOCTYPE html><html lang=\"zh-CN\"><head><meta charset=\"utf-8\">\
+     <meta name=\"viewport\" content=\"width=device-width,initial-scale=1\">\
+     <title>{title}</title><style>{STYLE}</style></head><body><h1>{title}</h1>\
+     <p class=\"muted\">蜗牛待办 · 生成于 {}</p><div class=\"cards\">",
+    escape(&report.generated_at)
+  );
+  let t = &report.totals;
+  card(&mut out, "专注分钟", t.focus_minutes);
+  card(&mut out, "完成番茄", t.focus_sessions);
+  card(&mut out, "中断番茄", t.cancelled_sessions);
+  card(&mut out, "完成任务", t.tasks_completed);
+  card(&mut out, "打卡", t.check_ins);
+  out.push_str("</div>");
+  for (_, title, buckets) in sections(report).into_iter().skip(1) {
+    table(&mut out, title, buckets);
+  }
+  out.push_str("</body></html>\n");
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn report() -> Report {
+    Report {
+      from: "2026-09-01".parse().unwrap(),
+      to: "2026-09-30".parse().unwrap(),
+      totals: Bucket {
+        key: "total".into(),
+        label: "合计".into(),
+        focus_sessions: 3,
+        focus_minutes: 75,
+        ..Default::default()
+      },
+      projects: vec![Bucket {
+        key: "p1".into(),
+        label: "Q3, \"launch\" <b>".into(),
+        focus_minutes: 75,
+        ..Default::default()
+      }],
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn csv_quotes_labels_and_lists_every_section() {
+    let csv = to_csv(&report());
+    let lines: Vec<&str> = csv.split("\r\n").collect();
+    assert_eq!(lines[0], CSV_HEADER);
+    assert_eq!(lines[1], "total,total,合计,3,75,0,0,0,0,0");
+    assert_eq!(
+      lines[2],
+      "project,p1,\"Q3, \"\"launch\"\" <b>\",0,75,0,0,0,0,0"
+    );
+  }
+
+  #[test]
+  fn html_is_escaped_and_json_round_trips() {
+    let report = report();
+    let html = render(&report, ReportFormat::Html).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("Q3, &quot;launch&quot; &lt;b&gt;"));
+    assert!(html.contains("width:100.0%"));
+    assert!(!html.contains("<script"));
+
+    let json = render(&report, ReportFormat::Json).unwrap();
+    assert_eq!(serde_json::from_str::<Report>(&json).unwrap(), report);
+    assert_eq!(
+      file_name(&report, ReportFormat::Csv),
+      "snail-report-2026-09-01_2026-09-30.csv"
+    );
+  }
+}
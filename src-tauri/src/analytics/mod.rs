@@ -0,0 +1,477 @@
+//! Productivity analytics over an arbitrary date range.
+//!
+//! Finished pomodoro sessions, task completions (`completed_at`) and
+//! check-ins are counted per day, project, tag, weekday and hour of the day.
+//! Everything is bucketed by the local day and hour it happened in; callers
+//! pass the time zone so a report is reproducible. [`export`] turns a
+//! [`Report`] into CSV, JSON or a self-contained HTML page, and
+//! [`timesheet`] lists tracked time entries one per row.
+
+pub mod export;
+pub mod timesheet;
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Timelike, Utc};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::storage::{now_iso, Store};
+
+/// Longest range a report may cover, about ten years.
+const MAX_DAYS: i64 = 3660;
+
+const WEEKDAYS: [&str; 7] = ["周一", "周二", "周三", "周四", "周五", "周六", "周日"];
+
+/// Label of the bucket for tasks and sessions without a project.
+pub const NO_PROJECT: &str = "无项目";
+
+/// Counters shared by every breakdown. Minutes are actual minutes between
+/// start and completion, falling back to the planned duration.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bucket {
+  pub key: String,
+  pub label: String,
+  pub focus_sessions: u32,
+  pub focus_minutes: i64,
+  pub break_sessions: u32,
+  pub break_minutes: i64,
+  /// Sessions reset or skipped before they finished.
+  pub cancelled_sessions: u32,
+  pub tasks_completed: u32,
+  pub check_ins: u32,
+}
+
+impl Bucket {
+  fn new(key: impl Into<String>, label: impl Into<String>) -> Self {
+    Self {
+      key: key.into(),
+      label: label.into(),
+      ..Default::default()
+    }
+  }
+
+  fn add(&mut self, event: &Event) {
+    match *event {
+      Event::Focus(minutes) => {
+        self.focus_sessions += 1;
+        self.focus_minutes += minutes;
+      }
+      Event::Break(minutes) => {
+        self.break_sessions += 1;
+        self.break_minutes += minutes;
+      }
+      Event::Cancelled => self.cancelled_sessions += 1,
+      Event::TaskCompleted => self.tasks_completed += 1,
+      Event::CheckIn => self.check_ins += 1,
+    }
+  }
+
+  fn is_empty(&self) -> bool {
+    self.focus_sessions == 0
+      && self.break_sessions == 0
+      && self.cancelled_sessions == 0
+      && self.tasks_completed == 0
+      && self.check_ins == 0
+  }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Report {
+  /// First and last local day covered, inclusive.
+  pub from: NaiveDate,
+  pub to: NaiveDate,
+  pub generated_at: String,
+  pub totals: Bucket,
+  /// Every day of the range, including empty ones.
+  pub days: Vec<Bucket>,
+  /// Projects with any activity, busiest first.
+  pub projects: Vec<Bucket>,
+  /// Tags with any activity, busiest first. A task with several tags
+  /// counts once for each of them.
+  pub tags: Vec<Bucket>,
+  /// Monday to Sunday.
+  pub weekdays: Vec<Bucket>,
+  /// 00 to 23.
+  pub hours: Vec<Bucket>,
+}
+
+enum Event {
+  Focus(i64),
+  Break(i64),
+  Cancelled,
+  TaskCompleted,
+  CheckIn,
+}
+
+/// Something that happened at `at`, optionally on a task.
+struct Activity {
+  at: DateTime<Utc>,
+  task_id: Option<String>,
+  event: Event,
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+  DateTime::parse_from_rfc3339(value)
+    .ok()
+    .map(|t| t.with_timezone(&Utc))
+}
+
+fn session_activities(conn: &Connection) -> Result<Vec<Activity>> {
+  let mut stmt = conn.prepare(
+    "select task_id, type, duration, started_at, completed_at, cancelled_at
+     from pomodoro_sessions",
+  )?;
+  let rows = stmt
+    .query_map([], |row| {
+      Ok((
+        row.get::<_, Option<String>>(0)?,
+        row.get::<_, String>(1)?,
+        row.get::<_, i64>(2)?,
+        row.get::<_, String>(3)?,
+        row.get::<_, Option<String>>(4)?,
+        row.get::<_, Option<String>>(5)?,
+      ))
+    })?
+    .collect::<rusqlite::Result<Vec<_>>>()?;
+  let mut activities = Vec::new();
+  for (task_id, kind, duration, started_at, completed_at, cancelled_at) in rows {
+    let Some(at) = parse_time(&started_at) else {
+      continue;
+    };
+    let event = match completed_at.as_deref().and_then(parse_time) {
+      Some(end) => {
+        let minutes = ((end - at).num_seconds() as f64 / 60.0).round() as i64;
+        let minutes = if minutes > 0 { minutes } else { duration };
+        if kind == "work" {
+          Event::Focus(minutes)
+        } else {
+          Event::Break(minutes)
+        }
+      }
+      None if cancelled_at.is_some() => Event::Cancelled,
+      // Still running, or abandoned without a trace.
+      None => continue,
+    };
+    activities.push(Activity { at, task_id, event });
+  }
+  Ok(activities)
+}
+
+fn completion_activities(conn: &Connection) -> Result<Vec<Activity>> {
+  let mut stmt = conn.prepare(
+    "select id, completed_at from tasks
+     where completed = 1 and deleted = 0 and completed_at is not null",
+  )?;
+  let rows = stmt
+    .query_map([], |row| {
+      Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?
+    .collect::<rusqlite::Result<Vec<_>>>()?;
+  Ok(
+    rows
+      .into_iter()
+      .filter_map(|(id, completed_at)| {
+        Some(Activity {
+          at: parse_time(&completed_at)?,
+          task_id: Some(id),
+          event: Event::TaskCompleted,
+        })
+      })
+      .collect(),
+  )
+}
+
+fn check_in_activities(conn: &Connection) -> Result<Vec<Activity>> {
+  let mut stmt = conn.prepare("select check_in_time from checkin_records")?;
+  let rows = stmt
+    .query_map([], |row| row.get::<_, String>(0))?
+    .collect::<rusqlite::Result<Vec<_>>>()?;
+  Ok(
+    rows
+      .iter()
+      .filter_map(|time| {
+        Some(Activity {
+          at: parse_time(time)?,
+          task_id: None,
+          event: Event::CheckIn,
+        })
+      })
+      .collect(),
+  )
+}
+
+fn pairs(conn: &Connection, sql: &str) -> Result<Vec<(String, Option<String>)>> {
+  let mut stmt = conn.prepare(sql)?;
+  let rows = stmt
+    .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+    .collect::<rusqlite::Result<Vec<_>>>()?;
+  Ok(rows)
+}
+
+/// Busiest first: focus minutes, then completed tasks, then name.
+fn ranked(buckets: HashMap<String, Bucket>) -> Vec<Bucket> {
+  let mut buckets: Vec<Bucket> = buckets.into_values().filter(|b| !b.is_empty()).collect();
+  buckets.sort_by(|a, b| {
+    b.focus_minutes
+      .cmp(&a.focus_minutes)
+      .then(b.tasks_completed.cmp(&a.tasks_completed))
+      .then_with(|| a.label.cmp(&b.label))
+  });
+  buckets
+}
+
+/// Number of days after `from` that `to` falls, if the range is valid.
+fn check_range(from: NaiveDate, to: NaiveDate) -> Result<i64> {
+  let span = (to - from).num_days();
+  if span < 0 {
+    return Err(Error::InvalidInput(format!(
+      "report range ends before it starts: {from} to {to}"
+    )));
+  }
+  if span >= MAX_DAYS {
+    return Err(Error::InvalidInput(format!(
+      "report range is longer than {MAX_DAYS} days"
+    )));
+  }
+  Ok(span)
+}
+
+impl Store {
+  /// Report on the local days `from..=to` in time zone `tz`.
+  pub fn analytics_report<Tz: TimeZone>(
+    &self,
+    from: NaiveDate,
+    to: NaiveDate,
+    tz: &Tz,
+  ) -> Result<Report> {
+    let span = check_range(from, to)?;
+
+    let conn = self.conn();
+    let mut activities = session_activities(&conn)?;
+    activities.extend(completion_activities(&conn)?);
+    activities.extend(check_in_activities(&conn)?);
+    let task_projects: HashMap<String, Option<String>> =
+      pairs(&conn, "select id, project from tasks")?
+        .into_iter()
+        .collect();
+    let project_names: HashMap<String, Option<String>> =
+      pairs(&conn, "select id, name from projects")?
+        .into_iter()
+        .collect();
+    let tag_names: HashMap<String, Option<String>> = pairs(&conn, "select id, name from tags")?
+      .into_iter()
+      .collect();
+    let mut task_tags: HashMap<String, Vec<String>> = HashMap::new();
+    for (task_id, tag_id) in pairs(&conn, "select task_id, tag_id from task_tags")? {
+      task_tags.entry(task_id).or_default().extend(tag_id);
+    }
+    drop(conn);
+
+    let mut totals = Bucket::new("total", "合计");
+    let mut days: Vec<Bucket> = from
+      .iter_days()
+      .take(span as usize + 1)
+      .map(|d| Bucket::new(d.to_string(), d.format("%m-%d").to_string()))
+      .collect();
+    let mut weekdays: Vec<Bucket> = WEEKDAYS
+      .iter()
+      .enumerate()
+      .map(|(i, label)| Bucket::new((i + 1).to_string(), *label))
+      .collect();
+    let mut hours: Vec<Bucket> = (0..24)
+      .map(|h| Bucket::new(format!("{h:02}"), format!("{h:02}:00")))
+      .collect();
+    let mut projects: HashMap<String, Bucket> = HashMap::new();
+    let mut tags: HashMap<String, Bucket> = HashMap::new();
+
+    for activity in &activities {
+      let local = activity.at.with_timezone(tz);
+      let day = local.date_naive();
+      if day < from || day > to {
+        continue;
+      }
+      let event = &activity.event;
+      totals.add(event);
+      days[(day - from).num_days() as usize].add(event);
+      weekdays[local.weekday().num_days_from_monday() as usize].add(event);
+      hours[local.hour() as usize].add(event);
+
+      let Some(task_id) = activity.task_id.as_deref() else {
+        continue;
+      };
+      let project = task_projects.get(task_id).cloned().flatten();
+      let key = project.clone().unwrap_or_default();
+      projects
+        .entry(key.clone())
+        .or_insert_with(|| {
+          let label = match &project {
+            Some(id) => project_names
+              .get(id)
+              .cloned()
+              .flatten()
+              .unwrap_or_else(|| id.clone()),
+            None => NO_PROJECT.to_string(),
+          };
+          Bucket::new(key, label)
+        })
+        .add(event);
+      for tag in task_tags.get(task_id).into_iter().flatten() {
+        tags
+          .entry(tag.clone())
+          .or_insert_with(|| {
+            let label = tag_names.get(tag).cloned().flatten();
+            Bucket::new(tag.clone(), label.unwrap_or_else(|| tag.clone()))
+          })
+          .add(event);
+      }
+    }
+
+    Ok(Report {
+      from,
+      to,
+      generated_at: now_iso(),
+      totals,
+      days,
+      projects: ranked(projects),
+      tags: ranked(tags),
+      weekdays,
+      hours,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::storage::models::{PomodoroSession, Project, Task};
+  use chrono::FixedOffset;
+  use serde_json::json;
+
+  fn day(s: &str) -> NaiveDate {
+    s.parse().unwrap()
+  }
+
+  fn session(store: &Store, task: Option<&Task>, kind: &str, start: &str, end: Option<&str>) {
+    let session = store
+      .create_pomodoro_session(PomodoroSession {
+        task_id: task.map(|t| t.id.clone()),
+        duration: 25,
+        kind: kind.into(),
+        started_at: start.into(),
+        ..Default::default()
+      })
+      .unwrap();
+    let patch = match end {
+      Some(end) => json!({ "completed_at": end }),
+      None => json!({ "cancelled_at": start }),
+    };
+    store
+      .update_pomodoro_session(&session.id, patch.as_object().unwrap())
+      .unwrap();
+  }
+
+  #[test]
+  fn buckets_by_local_day_project_tag_weekday_and_hour() {
+    let store = Store::open_in_memory().unwrap();
+    let work = store
+      .create_project(Project {
+        name: "Work".into(),
+        ..Default::default()
+      })
+      .unwrap();
+    let task = store
+      .create_task(Task {
+        title: "Report".into(),
+        project: Some(work.id.clone()),
+        ..Default::default()
+      })
+      .unwrap();
+    let tag = store.create_tag("deep", None).unwrap();
+    store.attach_tag_to_task(&task.id, &tag.id).unwrap();
+    let loose = store
+      .create_task(Task {
+        title: "Errand".into(),
+        ..Default::default()
+      })
+      .unwrap();
+
+    // UTC+8: 2026-10-04 23:30Z is Monday 10-05 07:30 locally.
+    session(
+      &store,
+      Some(&task),
+      "work",
+      "2026-10-04T23:30:00Z",
+      Some("2026-10-04T23:58:00Z"),
+    );
+    session(
+      &store,
+      Some(&task),
+      "work",
+      "2026-10-05T01:00:00Z",
+      Some("2026-10-05T01:00:00Z"),
+    );
+    session(
+      &store,
+      None,
+      "short_break",
+      "2026-10-05T01:30:00Z",
+      Some("2026-10-05T01:35:00Z"),
+    );
+    session(&store, Some(&task), "work", "2026-10-06T01:00:00Z", None);
+    // Outside the range.
+    session(
+      &store,
+      Some(&task),
+      "work",
+      "2026-09-30T01:00:00Z",
+      Some("2026-09-30T01:25:00Z"),
+    );
+    for (t, at) in [
+      (&task, "2026-10-05T02:00:00Z"),
+      (&loose, "2026-10-07T12:00:00Z"),
+    ] {
+      let done = json!({ "completed": true, "completed_at": at });
+      store.update_task(&t.id, done.as_object().unwrap()).unwrap();
+    }
+    store
+      .conn()
+      .execute(
+        "insert into checkin_records (id, check_in_time, created_at)
+         values ('c1', '2026-10-05T00:10:00Z', '2026-10-05T00:10:00Z')",
+        [],
+      )
+      .unwrap();
+
+    let tz = FixedOffset::east_opt(8 * 3600).unwrap();
+    let report = store
+      .analytics_report(day("2026-10-05"), day("2026-10-11"), &tz)
+      .unwrap();
+    let totals = &report.totals;
+    assert_eq!((totals.focus_sessions, totals.focus_minutes), (2, 53));
+    assert_eq!((totals.break_sessions, totals.break_minutes), (1, 5));
+    assert_eq!(totals.cancelled_sessions, 1);
+    assert_eq!((totals.tasks_completed, totals.check_ins), (2, 1));
+
+    assert_eq!(report.days.len(), 7);
+    assert_eq!(report.days[0].focus_sessions, 2);
+    assert_eq!(report.days[2].tasks_completed, 1);
+    assert_eq!(report.weekdays[0].label, "周一");
+    assert_eq!(report.weekdays[0].focus_minutes, 53);
+    assert_eq!(report.hours[7].focus_sessions, 1);
+    assert_eq!(report.hours[9].focus_sessions, 1);
+
+    assert_eq!(report.projects[0].label, "Work");
+    assert_eq!(report.projects[0].focus_minutes, 53);
+    assert_eq!(report.projects[1].label, NO_PROJECT);
+    assert_eq!(report.projects[1].tasks_completed, 1);
+    assert_eq!(report.tags.len(), 1);
+    assert_eq!(report.tags[0].label, "deep");
+    assert_eq!(report.tags[0].cancelled_sessions, 1);
+
+    assert!(store
+      .analytics_report(day("2026-10-05"), day("2026-10-01"), &tz)
+      .is_err());
+  }
+}
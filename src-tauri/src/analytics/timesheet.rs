@@ -0,0 +1,171 @@
+//! Timesheet export: every tracked span in a range, one CSV row each, with
+//! local date and clock times so it pastes straight into a timesheet.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+
+use super::export::csv_field;
+use super::{check_range, pairs, NO_PROJECT};
+use crate::error::Result;
+use crate::storage::time_tracking::TimeEntry;
+use crate::storage::Store;
+
+pub const TIMESHEET_HEADER: &str = "date,start,end,minutes,project,task,tags,source,note";
+
+/// `snail-timesheet-2026-09-01_2026-09-30.csv`
+pub fn file_name(from: NaiveDate, to: NaiveDate) -> String {
+  format!("snail-timesheet-{from}_{to}.csv")
+}
+
+impl Store {
+  /// Finished time entries and focus pomodoros that started on the local
+  /// days `from..=to` in `tz`, oldest first.
+  pub fn timesheet_csv<Tz: TimeZone>(
+    &self,
+    from: NaiveDate,
+    to: NaiveDate,
+    tz: &Tz,
+  ) -> Result<String>
+  where
+    Tz::Offset: std::fmt::Display,
+  {
+    check_range(from, to)?;
+    // A day wider on each side covers every offset; exact filtering below.
+    let start = from.and_time(Default::default()).and_utc() - Duration::days(1);
+    let end = to.and_time(Default::default()).and_utc() + Duration::days(2);
+    let entries = self.time_entries_between(start, end)?;
+
+    let conn = self.conn();
+    let tasks: HashMap<String, (String, Option<String>)> = {
+      let mut stmt = conn.prepare("select id, title, project from tasks")?;
+      let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+      rows.into_iter().collect()
+    };
+    let projects: HashMap<String, Option<String>> = pairs(&conn, "select id, name from projects")?
+      .into_iter()
+      .collect();
+    let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+    for (task_id, name) in pairs(
+      &conn,
+      "select task_tags.task_id, tags.name from task_tags \
+       join tags on tags.id = task_tags.tag_id order by tags.name",
+    )? {
+      tags.entry(task_id).or_default().extend(name);
+    }
+    drop(conn);
+
+    let mut out = String::from(TIMESHEET_HEADER);
+    out.push_str("\r\n");
+    for entry in &entries {
+      let Some((started, ended)) = span(entry, tz) else {
+        continue;
+      };
+      let day = started.date_naive();
+      if day < from || day > to {
+        continue;
+      }
+      let (title, project) = tasks
+        .get(&entry.task_id)
+        .cloned()
+        .unwrap_or_else(|| (entry.task_id.clone(), None));
+      let project = match project {
+        Some(id) => projects.get(&id).cloned().flatten().unwrap_or(id),
+        None => NO_PROJECT.to_string(),
+      };
+      let task_tags = tags.get(&entry.task_id).map(|t| t.join(";"));
+      let _ = write!(
+        out,
+        "{day},{},{},{},{},{},{},{},{}\r\n",
+        started.format("%H:%M"),
+        ended.format("%H:%M"),
+        entry.minutes(Utc::now()),
+        csv_field(&project),
+        csv_field(&title),
+        csv_field(task_tags.as_deref().unwrap_or("")),
+        entry.source.as_str(),
+        csv_field(entry.note.as_deref().unwrap_or(""))
+      );
+    }
+    Ok(out)
+  }
+}
+
+fn span<Tz: TimeZone>(entry: &TimeEntry, tz: &Tz) -> Option<(DateTime<Tz>, DateTime<Tz>)> {
+  let parse = |value: &str| {
+    DateTime::parse_from_rfc3339(value)
+      .ok()
+      .map(|t| t.with_timezone(tz))
+  };
+  Some((
+    parse(&entry.started_at)?,
+    parse(entry.ended_at.as_deref()?)?,
+  ))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::storage::models::{Project, Task};
+  use chrono::FixedOffset;
+
+  #[test]
+  fn lists_entries_by_local_day_with_project_and_tags() {
+    let store = Store::open_in_memory().unwrap();
+    let project = store
+      .create_project(Project {
+        name: "Client, Inc".into(),
+        ..Default::default()
+      })
+      .unwrap();
+    let task = store
+      .create_task(Task {
+        title: "audit".into(),
+        project: Some(project.id),
+        ..Default::default()
+      })
+      .unwrap();
+    for name in ["billable", "deep"] {
+      let tag = store.create_tag(name, None).unwrap();
+      store.attach_tag_to_task(&task.id, &tag.id).unwrap();
+    }
+    let entry = |start: &str, end: &str, note: Option<&str>| {
+      store
+        .add_time_entry(TimeEntry {
+          task_id: task.id.clone(),
+          started_at: start.into(),
+          ended_at: Some(end.into()),
+          note: note.map(Into::into),
+          ..Default::default()
+        })
+        .unwrap();
+    };
+    // 23:30 UTC on the 30th is already October 1st at UTC+8.
+    entry(
+      "2026-09-30T23:30:00Z",
+      "2026-10-01T00:15:00Z",
+      Some("kickoff \"call\""),
+    );
+    entry("2026-10-01T16:30:00Z", "2026-10-01T17:00:00Z", None);
+
+    let tz = FixedOffset::east_opt(8 * 3600).unwrap();
+    let day: NaiveDate = "2026-10-01".parse().unwrap();
+    let csv = store.timesheet_csv(day, day, &tz).unwrap();
+    let lines: Vec<&str> = csv.trim_end().split("\r\n").collect();
+    assert_eq!(lines[0], TIMESHEET_HEADER);
+    assert_eq!(
+      lines[1..],
+      ["2026-10-01,07:30,08:15,45,\"Client, Inc\",audit,billable;deep,manual,\"kickoff \"\"call\"\"\""]
+    );
+    assert!(store
+      .timesheet_csv(day, day.pred_opt().unwrap(), &tz)
+      .is_err());
+    assert_eq!(
+      file_name(day, day),
+      "snail-timesheet-2026-10-01_2026-10-01.csv"
+    );
+  }
+}
@@ -0,0 +1,511 @@
+//! Backup archives compatible with `src/services/dataTransferService.ts`.
+//!
+//! An archive is a ZIP holding `manifest.json`, `projects.json`,
+//! `tasks.json`, `tags.json` and `task_tags.json`, each pretty-printed with
+//! two-space indentation like `JSON.stringify(data, null, 2)`. Archives
+//! written here open in the web import dialog and vice versa.
+
+pub mod schedule;
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, Write};
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::error::{Error, Result};
+use crate::storage::models::{
+  CheckInRecord, PomodoroSession, Project, Tag, Task, TaskActivity, TaskFilter, TaskTagLink,
+  UserProfile,
+};
+use crate::storage::{
+  now_iso, search, write_activity, write_checkin, write_project, write_session, write_task,
+  ProfileUpdate, Store,
+};
+
+/// Format version written to `manifest.json`.
+pub const BACKUP_VERSION: &str = "1.0";
+
+const MANIFEST_FILE: &str = "manifest.json";
+const PROJECTS_FILE: &str = "projects.json";
+const TASKS_FILE: &str = "tasks.json";
+const TAGS_FILE: &str = "tags.json";
+const TASK_TAGS_FILE: &str = "task_tags.json";
+const REQUIRED_FILES: [&str; 5] = [
+  MANIFEST_FILE,
+  PROJECTS_FILE,
+  TASKS_FILE,
+  TAGS_FILE,
+  TASK_TAGS_FILE,
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupCounts {
+  pub projects: usize,
+  pub tasks: usize,
+  pub tags: usize,
+  pub task_tags: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupManifest {
+  pub version: String,
+  pub created_at: String,
+  pub app_version: String,
+  pub counts: BackupCounts,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+  Merge,
+  Replace,
+}
+
+/// Mirrors `ImportResult.stats` on the TypeScript side.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportStats {
+  pub projects: usize,
+  pub tasks: usize,
+  pub tags: usize,
+}
+
+/// Progress callback: percentage (0-100) and a user-facing message.
+pub type Progress<'a> = &'a mut dyn FnMut(u8, &str);
+
+/// Default archive name, `snailtodo-backup-YYYY-MM-DD.zip`.
+pub fn default_file_name() -> String {
+  format!(
+    "snailtodo-backup-{}.zip",
+    chrono::Utc::now().format("%Y-%m-%d")
+  )
+}
+
+/// Write a backup of the whole store to `path`.
+pub fn export_to(
+  store: &Store,
+  path: &Path,
+  app_version: &str,
+  progress: Progress<'_>,
+) -> Result<BackupManifest> {
+  let file = BufWriter::new(File::create(path)?);
+  let manifest = write_archive(store, file, app_version, progress)?;
+  progress(100, "导出完成");
+  Ok(manifest)
+}
+
+fn write_archive<W: Write + Seek>(
+  store: &Store,
+  writer: W,
+  app_version: &str,
+  progress: Progress<'_>,
+) -> Result<BackupManifest> {
+  progress(0, "开始导出...");
+
+  progress(10, "正在收集清单数据...");
+  let projects = store.get_projects()?;
+  progress(30, "正在收集任务数据...");
+  let tasks = store.get_tasks(&TaskFilter::default(), &[])?;
+  progress(50, "正在收集标签数据...");
+  let tags = store.get_tags(None)?;
+  progress(60, "正在收集标签关联...");
+  let task_tags = store.get_task_tag_links()?;
+
+  let manifest = BackupManifest {
+    version: BACKUP_VERSION.to_string(),
+    created_at: now_iso(),
+    app_version: app_version.to_string(),
+    counts: BackupCounts {
+      projects: projects.len(),
+      tasks: tasks.len(),
+      tags: tags.len(),
+      task_tags: task_tags.len(),
+    },
+  };
+
+  progress(70, "正在创建备份文件...");
+  let mut zip = ZipWriter::new(writer);
+  let options = SimpleFileOptions::default()
+    .compression_method(CompressionMethod::Deflated)
+    .compression_level(Some(6));
+  write_entry(&mut zip, options, MANIFEST_FILE, &manifest)?;
+  write_entry(&mut zip, options, PROJECTS_FILE, &projects)?;
+  progress(80, "正在压缩数据...");
+  write_entry(&mut zip, options, TASKS_FILE, &tasks)?;
+  write_entry(&mut zip, options, TAGS_FILE, &tags)?;
+  write_entry(&mut zip, options, TASK_TAGS_FILE, &task_tags)?;
+  zip.finish().map_err(zip_error)?.flush()?;
+
+  Ok(manifest)
+}
+
+fn write_entry<W: Write + Seek, T: Serialize>(
+  zip: &mut ZipWriter<W>,
+  options: SimpleFileOptions,
+  name: &str,
+  value: &T,
+) -> Result<()> {
+  zip.start_file(name, options).map_err(zip_error)?;
+  serde_json::to_writer_pretty(&mut *zip, value)?;
+  Ok(())
+}
+
+fn zip_error(e: zip::result::ZipError) -> Error {
+  Error::InvalidInput(format!("backup archive: {e}"))
+}
+
+/// Check an archive without importing it, like `validateBackupFile`.
+pub fn validate(path: &Path) -> Result<BackupManifest> {
+  let mut zip = open_archive(path)?;
+  read_manifest(&mut zip)
+}
+
+fn open_archive(path: &Path) -> Result<ZipArchive<BufReader<File>>> {
+  let file = BufReader::new(File::open(path)?);
+  ZipArchive::new(file)
+    .map_err(|_| Error::InvalidInput("无法读取备份文件，文件可能已损坏".to_string()))
+}
+
+fn read_manifest<R: Read + Seek>(zip: &mut ZipArchive<R>) -> Result<BackupManifest> {
+  for name in REQUIRED_FILES {
+    if zip.index_for_name(name).is_none() {
+      return Err(Error::InvalidInput(format!("备份文件缺少必要文件: {name}")));
+    }
+  }
+  let manifest: BackupManifest = read_entry(zip, MANIFEST_FILE)?;
+  if manifest.version.is_empty() || manifest.created_at.is_empty() {
+    return Err(Error::InvalidInput("备份文件格式无效".to_string()));
+  }
+  Ok(manifest)
+}
+
+fn read_entry<R: Read + Seek, T: DeserializeOwned>(
+  zip: &mut ZipArchive<R>,
+  name: &str,
+) -> Result<T> {
+  let entry = zip.by_name(name).map_err(zip_error)?;
+  serde_json::from_reader(BufReader::new(entry))
+    .map_err(|_| Error::InvalidInput("备份文件数据格式错误".to_string()))
+}
+
+/// Import the archive at `path` into `store`.
+///
+/// Unlike the webview version the whole import runs in one transaction: a
+/// corrupt archive leaves the existing data untouched even in replace mode.
+pub fn import_from(
+  store: &Store,
+  path: &Path,
+  mode: ImportMode,
+  progress: Progress<'_>,
+) -> Result<ImportStats> {
+  progress(0, "开始导入...");
+  progress(5, "正在验证备份文件...");
+  let mut zip = open_archive(path)?;
+  read_manifest(&mut zip)?;
+
+  progress(10, "正在解析备份数据...");
+  let projects: Vec<Project> = read_entry(&mut zip, PROJECTS_FILE)?;
+  let tasks: Vec<Task> = read_entry(&mut zip, TASKS_FILE)?;
+  let tags: Vec<Tag> = read_entry(&mut zip, TAGS_FILE)?;
+  let task_tags: Vec<TaskTagLink> = read_entry(&mut zip, TASK_TAGS_FILE)?;
+
+  let stats = store.import_records(&projects, &tasks, &tags, &task_tags, mode, progress)?;
+  progress(100, "导入完成");
+  Ok(stats)
+}
+
+/// Offline data the webview kept in IndexedDB before the desktop app stored
+/// it here, as read by `IndexedDBAdapter.exportAll()`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WebviewData {
+  pub projects: Vec<Project>,
+  pub tasks: Vec<Task>,
+  pub tags: Vec<Tag>,
+  pub task_tags: Vec<TaskTagLink>,
+  pub pomodoro_sessions: Vec<PomodoroSession>,
+  pub activities: Vec<TaskActivity>,
+  pub check_ins: Vec<CheckInRecord>,
+  pub profile: Option<UserProfile>,
+}
+
+/// Copy the webview's IndexedDB data into `store`, keeping ids so that
+/// links between records survive. It merges like a merge import; the
+/// profile's settings are laid over the ones already here. Attachment
+/// files need no copy: tasks carry them as `data:` URLs.
+pub fn import_webview_data(store: &Store, data: &WebviewData) -> Result<ImportStats> {
+  let stats = store.import_records(
+    &data.projects,
+    &data.tasks,
+    &data.tags,
+    &data.task_tags,
+    ImportMode::Merge,
+    &mut |_, _| {},
+  )?;
+  store.transaction(|tx| {
+    for session in &data.pomodoro_sessions {
+      write_session(tx, session)?;
+    }
+    for activity in &data.activities {
+      write_activity(tx, activity)?;
+    }
+    for record in &data.check_ins {
+      write_checkin(tx, record)?;
+    }
+    Ok(())
+  })?;
+  if let Some(profile) = &data.profile {
+    if let Some(settings) = &profile.settings {
+      store.save_user_settings(settings.clone())?;
+    }
+    store.save_user_profile(ProfileUpdate {
+      username: Some(profile.username.clone()),
+      avatar_url: profile.avatar_url.clone(),
+      avatar_data: profile.avatar_data.clone(),
+      settings: None,
+    })?;
+  }
+  Ok(stats)
+}
+
+/// Step `i` of `len` mapped onto the `start..start + span` progress range.
+fn step(start: u8, span: u8, i: usize, len: usize) -> u8 {
+  start + (i * span as usize / len.max(1)) as u8
+}
+
+impl Store {
+  fn import_records(
+    &self,
+    projects: &[Project],
+    tasks: &[Task],
+    tags: &[Tag],
+    task_tags: &[TaskTagLink],
+    mode: ImportMode,
+    progress: Progress<'_>,
+  ) -> Result<ImportStats> {
+    self.transaction(|tx| {
+      if mode == ImportMode::Replace {
+        progress(15, "正在清除现有数据...");
+        tx.execute_batch(
+          "delete from task_tags; delete from tasks; delete from projects; delete from tags;",
+        )?;
+      }
+
+      let now = now_iso();
+      for (i, project) in projects.iter().enumerate() {
+        progress(
+          step(30, 20, i, projects.len()),
+          &format!("正在导入清单 ({}/{})...", i + 1, projects.len()),
+        );
+        let mut project = project.clone();
+        project.updated_at.get_or_insert_with(|| now.clone());
+        write_project(tx, &project)?;
+      }
+
+      for (i, task) in tasks.iter().enumerate() {
+        progress(
+          step(50, 25, i, tasks.len()),
+          &format!("正在导入任务 ({}/{})...", i + 1, tasks.len()),
+        );
+        write_task(tx, task)?;
+      }
+
+      for (i, tag) in tags.iter().enumerate() {
+        progress(
+          step(75, 15, i, tags.len()),
+          &format!("正在导入标签 ({}/{})...", i + 1, tags.len()),
+        );
+        // Merge keeps the local copy of a tag that already exists.
+        tx.execute(
+          "insert or ignore into tags (id, name, user_id, project_id, created_at) \
+           values (?1, ?2, ?3, ?4, ?5)",
+          rusqlite::params![
+            tag.id,
+            tag.name,
+            tag.user_id,
+            tag.project_id,
+            tag.created_at
+          ],
+        )?;
+      }
+
+      progress(90, "正在恢复标签关联...");
+      for link in task_tags {
+        // Links to tasks or tags missing from the archive are skipped.
+        tx.execute(
+          "insert or ignore into task_tags (task_id, tag_id, created_at) \
+           select ?1, ?2, ?3 \
+           where exists (select 1 from tasks where id = ?1) \
+             and exists (select 1 from tags where id = ?2)",
+          rusqlite::params![
+            link.task_id,
+            link.tag_id,
+            link.created_at.clone().unwrap_or_else(|| now.clone())
+          ],
+        )?;
+      }
+      // Tasks were indexed before their tags arrived, and Replace left
+      // entries of the removed tasks behind.
+      search::rebuild(tx)?;
+
+      Ok(ImportStats {
+        projects: projects.len(),
+        tasks: tasks.len(),
+        tags: tags.len(),
+      })
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn seeded_store() -> Store {
+    let store = Store::open_in_memory().unwrap();
+    let project = store
+      .create_project(Project {
+        name: "工作".into(),
+        icon: "folder".into(),
+        ..Default::default()
+      })
+      .unwrap();
+    let task = store
+      .create_task(Task {
+        title: "周报".into(),
+        project: Some(project.id.clone()),
+        ..Default::default()
+      })
+      .unwrap();
+    let tag = store.create_tag("urgent", None).unwrap();
+    store.attach_tag_to_task(&task.id, &tag.id).unwrap();
+    store
+  }
+
+  fn no_progress() -> impl FnMut(u8, &str) {
+    |_, _| {}
+  }
+
+  #[test]
+  fn round_trips_through_replace_import() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(default_file_name());
+    let source = seeded_store();
+    let mut seen = Vec::new();
+    let manifest = export_to(&source, &path, "1.0.6", &mut |p, _| seen.push(p)).unwrap();
+    assert_eq!(manifest.counts.task_tags, 1);
+    assert_eq!(seen.last(), Some(&100));
+    assert_eq!(validate(&path).unwrap(), manifest);
+
+    let target = seeded_store();
+    let stats = import_from(&target, &path, ImportMode::Replace, &mut no_progress()).unwrap();
+    assert_eq!(
+      stats,
+      ImportStats {
+        projects: 1,
+        tasks: 1,
+        tags: 1
+      }
+    );
+    let tasks = target.get_tasks(&TaskFilter::default(), &[]).unwrap();
+    assert_eq!(
+      tasks,
+      source.get_tasks(&TaskFilter::default(), &[]).unwrap()
+    );
+    let tags = target.get_tags_by_task_ids(&[tasks[0].id.clone()]).unwrap();
+    assert_eq!(tags[&tasks[0].id][0].name, "urgent");
+  }
+
+  #[test]
+  fn merge_import_does_not_duplicate_existing_records() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("backup.zip");
+    let store = seeded_store();
+    export_to(&store, &path, "1.0.6", &mut no_progress()).unwrap();
+
+    import_from(&store, &path, ImportMode::Merge, &mut no_progress()).unwrap();
+    assert_eq!(
+      store.get_tasks(&TaskFilter::default(), &[]).unwrap().len(),
+      1
+    );
+    assert_eq!(store.get_projects().unwrap().len(), 1);
+    assert_eq!(store.get_tags(None).unwrap().len(), 1);
+  }
+
+  #[test]
+  fn imports_webview_data_with_its_ids() {
+    let data: WebviewData = serde_json::from_value(serde_json::json!({
+      "projects": [{ "id": "p1", "name": "工作", "icon": "folder" }],
+      "tasks": [{ "id": "t1", "title": "周报", "project": "p1", "date": "2025-01-02" }],
+      "tags": [{ "id": "g1", "name": "urgent", "created_at": "2025-01-01T00:00:00Z" }],
+      "taskTags": [{ "task_id": "t1", "tag_id": "g1" }],
+      "pomodoroSessions": [{
+        "id": "s1", "task_id": "t1", "duration": 25, "type": "work",
+        "started_at": "2025-01-02T09:00:00Z", "created_at": "2025-01-02T09:00:00Z"
+      }],
+      "activities": [{
+        "id": "a1", "task_id": "t1", "action": "created", "created_at": "2025-01-02T08:00:00Z"
+      }],
+      "checkIns": [{
+        "id": "c1", "check_in_time": "2025-01-02T08:00:00Z", "created_at": "2025-01-02T08:00:00Z"
+      }],
+      "profile": {
+        "id": "local", "username": "蜗牛", "settings": { "deadline_notification_days": 3 }
+      }
+    }))
+    .unwrap();
+    let store = Store::open_in_memory().unwrap();
+    store
+      .save_user_settings(
+        serde_json::from_value(serde_json::json!({ "backup_schedule": "daily" })).unwrap(),
+      )
+      .unwrap();
+
+    let stats = import_webview_data(&store, &data).unwrap();
+    assert_eq!(stats.tasks, 1);
+    let task = store.get_task_by_id("t1").unwrap().unwrap();
+    assert_eq!(task.project.as_deref(), Some("p1"));
+    assert_eq!(
+      store.get_tags_by_task_ids(&["t1".into()]).unwrap()["t1"][0].id,
+      "g1"
+    );
+    assert_eq!(store.get_pomodoro_sessions(Some("t1")).unwrap()[0].id, "s1");
+    assert_eq!(store.get_task_activities("t1").unwrap()[0].id, "a1");
+    assert_eq!(store.get_check_in_history(1, 10).unwrap().total, 1);
+    let profile = store.get_user_profile().unwrap().unwrap();
+    assert_eq!(profile.username, "蜗牛");
+    let settings = store.get_user_settings().unwrap();
+    assert_eq!(settings.i64("deadline_notification_days"), Some(3));
+    assert_eq!(settings.str("backup_schedule"), Some("daily"));
+
+    // Running it again changes nothing.
+    import_webview_data(&store, &data).unwrap();
+    assert_eq!(
+      store.get_tasks(&TaskFilter::default(), &[]).unwrap().len(),
+      1
+    );
+  }
+
+  #[test]
+  fn rejects_archives_missing_required_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("partial.zip");
+    let mut zip = ZipWriter::new(File::create(&path).unwrap());
+    zip
+      .start_file(MANIFEST_FILE, SimpleFileOptions::default())
+      .unwrap();
+    zip.write_all(b"{}").unwrap();
+    zip.finish().unwrap();
+
+    let err = validate(&path).unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "invalid input: 备份文件缺少必要文件: projects.json"
+    );
+  }
+}
@@ -0,0 +1,347 @@
+//! Automatic local backups.
+//!
+//! Snapshots are ordinary backup archives written to `<app data>/backups`
+//! and named after their UTC creation time, so listing and pruning never
+//! needs to open them. The schedule and retention policy are read from the
+//! user settings on every check, so changes in Settings apply without a
+//! restart.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{export_to, import_from, ImportMode, ImportStats};
+use crate::error::{Error, Result};
+use crate::storage::models::UserSettings;
+use crate::storage::Store;
+
+const FILE_PREFIX: &str = "snailtodo-backup-";
+const FILE_SUFFIX: &str = ".zip";
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%3fZ";
+/// Names written before snapshots carried milliseconds.
+const LEGACY_TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// How often the scheduler wakes up to see whether a snapshot is due.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupInterval {
+  Hourly,
+  Daily,
+}
+
+impl BackupInterval {
+  fn as_delta(self) -> TimeDelta {
+    match self {
+      BackupInterval::Hourly => TimeDelta::hours(1),
+      BackupInterval::Daily => TimeDelta::days(1),
+    }
+  }
+}
+
+/// Settings keys: `auto_backup_enabled`, `auto_backup_interval`
+/// (`hourly`/`daily`), `auto_backup_keep_last` and
+/// `auto_backup_keep_daily_days`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AutoBackupPolicy {
+  pub enabled: bool,
+  pub interval: BackupInterval,
+  /// Always keep this many of the newest snapshots.
+  pub keep_last: usize,
+  /// Additionally keep the newest snapshot of each of the last N local days.
+  pub keep_daily_days: i64,
+}
+
+impl Default for AutoBackupPolicy {
+  fn default() -> Self {
+    Self {
+      enabled: true,
+      interval: BackupInterval::Daily,
+      keep_last: 10,
+      keep_daily_days: 30,
+    }
+  }
+}
+
+impl AutoBackupPolicy {
+  pub fn from_settings(settings: &UserSettings) -> Self {
+    let defaults = Self::default();
+    Self {
+      enabled: settings
+        .bool("auto_backup_enabled")
+        .unwrap_or(defaults.enabled),
+      interval: match settings.str("auto_backup_interval") {
+        Some("hourly") => BackupInterval::Hourly,
+        Some("daily") => BackupInterval::Daily,
+        _ => defaults.interval,
+      },
+      keep_last: settings
+        .i64("auto_backup_keep_last")
+        .map_or(defaults.keep_last, |n| n.max(1) as usize),
+      keep_daily_days: settings
+        .i64("auto_backup_keep_daily_days")
+        .map_or(defaults.keep_daily_days, |n| n.max(0)),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+  pub file_name: String,
+  pub created_at: DateTime<Utc>,
+  pub size: u64,
+}
+
+/// Directory holding the snapshots, under the app data directory.
+pub fn snapshot_dir(app_data_dir: &Path) -> PathBuf {
+  app_data_dir.join("backups")
+}
+
+fn file_name_for(created_at: DateTime<Utc>) -> String {
+  format!(
+    "{FILE_PREFIX}{}{FILE_SUFFIX}",
+    created_at.format(TIMESTAMP_FORMAT)
+  )
+}
+
+fn parse_file_name(name: &str) -> Option<DateTime<Utc>> {
+  let stamp = name.strip_prefix(FILE_PREFIX)?.strip_suffix(FILE_SUFFIX)?;
+  NaiveDateTime::parse_from_str(stamp, TIMESTAMP_FORMAT)
+    .or_else(|_| NaiveDateTime::parse_from_str(stamp, LEGACY_TIMESTAMP_FORMAT))
+    .ok()
+    .map(|t| t.and_utc())
+}
+
+/// Snapshots in `dir`, newest first. Unrelated files are ignored.
+pub fn list_snapshots(dir: &Path) -> Result<Vec<Snapshot>> {
+  let entries = match fs::read_dir(dir) {
+    Ok(entries) => entries,
+    Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+    Err(e) => return Err(e.into()),
+  };
+  let mut snapshots = Vec::new();
+  for entry in entries {
+    let entry = entry?;
+    let file_name = entry.file_name().to_string_lossy().into_owned();
+    if let Some(created_at) = parse_file_name(&file_name) {
+      snapshots.push(Snapshot {
+        file_name,
+        created_at,
+        size: entry.metadata()?.len(),
+      });
+    }
+  }
+  snapshots.sort_by_key(|s| std::cmp::Reverse(s.created_at));
+  Ok(snapshots)
+}
+
+/// Write a new snapshot now. An existing snapshot is never replaced: a
+/// name already taken moves the new one on by a millisecond.
+pub fn create_snapshot(store: &Store, dir: &Path, app_version: &str) -> Result<Snapshot> {
+  fs::create_dir_all(dir)?;
+  let mut created_at = Utc::now();
+  while dir.join(file_name_for(created_at)).exists() {
+    created_at += TimeDelta::milliseconds(1);
+  }
+  let file_name = file_name_for(created_at);
+  let path = dir.join(&file_name);
+  // Write under a temporary name so a crash never leaves a truncated
+  // archive that looks like a valid snapshot.
+  let partial = path.with_extension("zip.partial");
+  export_to(store, &partial, app_version, &mut |_, _| {})?;
+  fs::rename(&partial, &path)?;
+  Ok(Snapshot {
+    file_name,
+    created_at,
+    size: fs::metadata(&path)?.len(),
+  })
+}
+
+/// Snapshots (newest first) the policy no longer wants to keep.
+pub fn select_for_removal<'a>(
+  snapshots: &'a [Snapshot],
+  policy: &AutoBackupPolicy,
+  today: NaiveDate,
+) -> Vec<&'a Snapshot> {
+  let mut kept_days: Vec<NaiveDate> = Vec::new();
+  snapshots
+    .iter()
+    .enumerate()
+    .filter(|(i, snapshot)| {
+      if *i < policy.keep_last {
+        return false;
+      }
+      let day = snapshot.created_at.with_timezone(&Local).date_naive();
+      let within_window = (today - day).num_days() < policy.keep_daily_days;
+      if within_window && !kept_days.contains(&day) {
+        kept_days.push(day);
+        return false;
+      }
+      true
+    })
+    .map(|(_, snapshot)| snapshot)
+    .collect()
+}
+
+/// Delete snapshots outside the retention policy; returns what was removed.
+pub fn prune(dir: &Path, policy: &AutoBackupPolicy) -> Result<Vec<Snapshot>> {
+  let snapshots = list_snapshots(dir)?;
+  let doomed: Vec<Snapshot> = select_for_removal(&snapshots, policy, Local::now().date_naive())
+    .into_iter()
+    .cloned()
+    .collect();
+  for snapshot in &doomed {
+    fs::remove_file(dir.join(&snapshot.file_name))?;
+  }
+  Ok(doomed)
+}
+
+/// Whether a new snapshot is due given the newest existing one.
+pub fn is_due(latest: Option<&Snapshot>, policy: &AutoBackupPolicy, now: DateTime<Utc>) -> bool {
+  policy.enabled && latest.map_or(true, |s| now - s.created_at >= policy.interval.as_delta())
+}
+
+/// One scheduler tick: snapshot and prune if the policy says so.
+pub fn run_due(store: &Store, dir: &Path, app_version: &str) -> Result<Option<Snapshot>> {
+  let policy = AutoBackupPolicy::from_settings(&store.get_user_settings()?);
+  let snapshots = list_snapshots(dir)?;
+  if !is_due(snapshots.first(), &policy, Utc::now()) {
+    return Ok(None);
+  }
+  let snapshot = create_snapshot(store, dir, app_version)?;
+  prune(dir, &policy)?;
+  Ok(Some(snapshot))
+}
+
+/// Replace the current data with a snapshot.
+///
+/// The current state is snapshotted first, so a restore can itself be undone.
+pub fn restore_snapshot(
+  store: &Store,
+  dir: &Path,
+  file_name: &str,
+  app_version: &str,
+) -> Result<ImportStats> {
+  if parse_file_name(file_name).is_none() {
+    return Err(Error::InvalidInput(format!(
+      "not a backup snapshot: {file_name}"
+    )));
+  }
+  let path = dir.join(file_name);
+  if !path.is_file() {
+    return Err(Error::NotFound("backup snapshot", file_name.to_string()));
+  }
+  create_snapshot(store, dir, app_version)?;
+  import_from(store, &path, ImportMode::Replace, &mut |_, _| {})
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::storage::models::{Task, TaskFilter};
+  use chrono::TimeZone;
+
+  fn snapshot_at(t: DateTime<Utc>) -> Snapshot {
+    Snapshot {
+      file_name: file_name_for(t),
+      created_at: t,
+      size: 0,
+    }
+  }
+
+  #[test]
+  fn file_names_round_trip() {
+    let t = Utc.with_ymd_and_hms(2026, 10, 18, 9, 30, 5).unwrap() + TimeDelta::milliseconds(42);
+    assert_eq!(file_name_for(t), "snailtodo-backup-20261018T093005042Z.zip");
+    assert_eq!(parse_file_name(&file_name_for(t)), Some(t));
+    assert_eq!(
+      parse_file_name("snailtodo-backup-20261018T093005Z.zip"),
+      Some(t - TimeDelta::milliseconds(42))
+    );
+    assert_eq!(parse_file_name("snailtodo-backup-2026-10-18.zip"), None);
+  }
+
+  #[test]
+  fn retention_keeps_newest_and_one_per_recent_day() {
+    let now = Local::now();
+    let hours_ago = |h: i64| (now - TimeDelta::hours(h)).with_timezone(&Utc);
+    // Hourly snapshots over the last four days, newest first.
+    let snapshots: Vec<_> = (0..96).map(|h| snapshot_at(hours_ago(h))).collect();
+    let policy = AutoBackupPolicy {
+      keep_last: 3,
+      keep_daily_days: 2,
+      ..Default::default()
+    };
+
+    let removed = select_for_removal(&snapshots, &policy, now.date_naive());
+    let kept: Vec<_> = snapshots.iter().filter(|s| !removed.contains(s)).collect();
+
+    assert!(kept.starts_with(&[&snapshots[0], &snapshots[1], &snapshots[2]]));
+    let older_days: Vec<_> = kept[3..]
+      .iter()
+      .map(|s| s.created_at.with_timezone(&Local).date_naive())
+      .collect();
+    // Only yesterday/today may contribute an extra daily snapshot.
+    assert!(older_days.len() <= 2);
+    assert!(older_days
+      .iter()
+      .all(|d| (now.date_naive() - *d).num_days() < 2));
+  }
+
+  #[test]
+  fn due_after_interval_elapses() {
+    let policy = AutoBackupPolicy {
+      interval: BackupInterval::Hourly,
+      ..Default::default()
+    };
+    let now = Utc::now();
+    assert!(is_due(None, &policy, now));
+    let recent = snapshot_at(now - TimeDelta::minutes(30));
+    assert!(!is_due(Some(&recent), &policy, now));
+    let old = snapshot_at(now - TimeDelta::minutes(61));
+    assert!(is_due(Some(&old), &policy, now));
+    let disabled = AutoBackupPolicy {
+      enabled: false,
+      ..policy
+    };
+    assert!(!is_due(None, &disabled, now));
+  }
+
+  #[test]
+  fn restore_replaces_data_and_keeps_a_safety_snapshot() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = Store::open_in_memory().unwrap();
+    store
+      .create_task(Task {
+        title: "before".into(),
+        ..Default::default()
+      })
+      .unwrap();
+    let snapshot = create_snapshot(&store, dir.path(), "1.0.6").unwrap();
+    store
+      .create_task(Task {
+        title: "after".into(),
+        ..Default::default()
+      })
+      .unwrap();
+    restore_snapshot(&store, dir.path(), &snapshot.file_name, "1.0.6").unwrap();
+
+    let titles: Vec<_> = store
+      .get_tasks(&TaskFilter::default(), &[])
+      .unwrap()
+      .into_iter()
+      .map(|t| t.title)
+      .collect();
+    assert_eq!(titles, ["before"]);
+    assert_eq!(list_snapshots(dir.path()).unwrap().len(), 2);
+    // Restoring again right away still leaves the chosen snapshot intact.
+    restore_snapshot(&store, dir.path(), &snapshot.file_name, "1.0.6").unwrap();
+    assert_eq!(list_snapshots(dir.path()).unwrap().len(), 3);
+    assert!(restore_snapshot(&store, dir.path(), "../snail_todo.db", "1.0.6").is_err());
+  }
+}
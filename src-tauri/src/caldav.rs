@@ -0,0 +1,988 @@
+//! A minimal CalDAV (RFC 4791) server for two-way task sync.
+//!
+//! It shares the local server with the read-only feed, so it is reachable
+//! from phones only with [`crate::feed::FeedSettings::lan`] set, over plain
+//! HTTP. It exposes every project as a calendar collection holding one
+//! `VTODO` resource per task:
+//!
+//! - `/dav/` is the principal and the calendar home;
+//! - `/dav/<project id>/` is a collection;
+//! - `/dav/<project id>/<uid>.ics` is a task, or whatever name the client
+//!   chose when it created it (kept in `caldav_resources`).
+//!
+//! Supported: `OPTIONS`, `PROPFIND` (depth 0 and 1), `REPORT`
+//! (`calendar-query` by component and `calendar-multiget`), `GET`, `PUT`
+//! and `DELETE`, with `If-Match`/`If-None-Match` on ETags that hash the
+//! rendered `VTODO`. Writes go through the `.ics` importer, so a task
+//! edited on a phone updates in place. `DELETE` moves the task to the trash.
+//!
+//! Clients sign in with HTTP Basic: any user name and a calendar feed token
+//! as the password, so revoking the feed also locks the client out. The
+//! server thread answers one request at a time, which keeps the ETag check
+//! and the write of a `PUT` together.
+
+use std::collections::HashMap;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use rusqlite::params;
+use sha2::{Digest, Sha256};
+
+use crate::error::{Error, Result};
+use crate::feed::{self, HttpRequest, HttpResponse};
+use crate::ical::tasks::{calendar, uid_of, vtodo};
+use crate::ical::Component;
+use crate::storage::models::{Project, TaskFilter};
+use crate::storage::Store;
+
+pub const DAV_ROOT: &str = "/dav/";
+const WELL_KNOWN: &str = "/.well-known/caldav";
+
+const DAV: &str = "DAV:";
+const CALDAV: &str = "urn:ietf:params:xml:ns:caldav";
+const CALSERVER: &str = "http://calendarserver.org/ns/";
+
+/// Characters kept as-is in a resource name inside an href.
+const NAME_SET: &AsciiSet = &NON_ALPHANUMERIC
+  .remove(b'-')
+  .remove(b'_')
+  .remove(b'.')
+  .remove(b'~')
+  .remove(b'@');
+
+const XML: &str = "application/xml; charset=utf-8";
+const ICS: &str = "text/calendar; charset=utf-8";
+
+/// Answer a request on the local server: CalDAV under [`DAV_ROOT`],
+/// the read-only feed everywhere else.
+pub fn handle(store: &Store, request: &HttpRequest) -> HttpResponse {
+  let Some(url) = parse_url(&request.url) else {
+    return HttpResponse::text(400, "bad request");
+  };
+  if url.path() == WELL_KNOWN {
+    return HttpResponse::text(301, "moved").header("Location", DAV_ROOT);
+  }
+  if url.path() != DAV_ROOT.trim_end_matches('/') && !url.path().starts_with(DAV_ROOT) {
+    return feed::respond(store, request);
+  }
+  match authorized(store, request) {
+    Ok(true) => {}
+    Ok(false) => {
+      return HttpResponse::text(401, "unauthorized").header(
+        "WWW-Authenticate",
+        "Basic realm=\"Snail\", charset=\"UTF-8\"",
+      )
+    }
+    Err(e) => return failed(&e),
+  }
+  match dav(store, request, url.path()) {
+    Ok(response) => response,
+    Err(Error::InvalidInput(message)) => HttpResponse::text(400, &message),
+    Err(e) => failed(&e),
+  }
+}
+
+fn failed(e: &Error) -> HttpResponse {
+  log::warn!("caldav request failed: {e}");
+  HttpResponse::text(500, "internal error")
+}
+
+fn parse_url(url: &str) -> Option<url::Url> {
+  url::Url::parse("http://127.0.0.1")
+    .and_then(|base| base.join(url))
+    .ok()
+}
+
+/// Basic auth whose password is a calendar feed token.
+fn authorized(store: &Store, request: &HttpRequest) -> Result<bool> {
+  let password = request
+    .header("authorization")
+    .and_then(|value| value.strip_prefix("Basic "))
+    .and_then(|encoded| STANDARD.decode(encoded.trim()).ok())
+    .and_then(|decoded| String::from_utf8(decoded).ok())
+    .and_then(|pair| pair.split_once(':').map(|(_, p)| p.to_string()));
+  match password {
+    Some(password) => Ok(store.fetch_calendar_feed(&password)?.is_some()),
+    None => Ok(false),
+  }
+}
+
+/// A task as a calendar object resource.
+#[derive(Debug, Clone)]
+struct Object {
+  name: String,
+  task_id: String,
+  uid: String,
+  etag: String,
+  /// The `VCALENDAR` holding the task's `VTODO`.
+  data: String,
+}
+
+enum Target {
+  Root,
+  Collection(Project),
+  Object(Project, String),
+}
+
+fn href_of(project_id: &str, name: &str) -> String {
+  format!(
+    "{DAV_ROOT}{}/{}",
+    utf8_percent_encode(project_id, NAME_SET),
+    utf8_percent_encode(name, NAME_SET)
+  )
+}
+
+fn collection_href(project: &Project) -> String {
+  format!("{DAV_ROOT}{}/", utf8_percent_encode(&project.id, NAME_SET))
+}
+
+fn hash(value: &str) -> String {
+  hex::encode(Sha256::digest(value.as_bytes()))[..32].to_string()
+}
+
+impl Store {
+  /// What a `/dav/...` path points at, `None` for an unknown project.
+  fn caldav_target(&self, path: &str) -> Result<Option<Target>> {
+    let segments: Vec<String> = path
+      .trim_start_matches(DAV_ROOT.trim_end_matches('/'))
+      .split('/')
+      .filter(|s| !s.is_empty())
+      .map(|s| percent_decode_str(s).decode_utf8_lossy().into_owned())
+      .collect();
+    let project = match segments.first() {
+      None => return Ok(Some(Target::Root)),
+      Some(id) => match self.get_project_by_id(id)? {
+        Some(project) => project,
+        None => return Ok(None),
+      },
+    };
+    Ok(match &segments[1..] {
+      [] => Some(Target::Collection(project)),
+      [name] => Some(Target::Object(project, name.clone())),
+      _ => None,
+    })
+  }
+
+  /// Every task of `project` outside the trash as a resource.
+  fn caldav_objects(&self, project: &Project) -> Result<Vec<Object>> {
+    let filter = TaskFilter {
+      project_id: Some(project.id.clone()),
+      deleted: Some(false),
+      ..Default::default()
+    };
+    let tasks = self.get_tasks(&filter, &[])?;
+    let extras = self.ical_extras(&tasks)?;
+    let names = self.caldav_names()?;
+    Ok(
+      tasks
+        .iter()
+        .zip(extras)
+        .map(|(task, extras)| {
+          let mut todo = vtodo(task, &extras);
+          // DTSTAMP is the time of rendering; leave it out of the ETag.
+          let stamp = todo
+            .properties
+            .iter()
+            .position(|p| p.name == "DTSTAMP")
+            .map(|i| (i, todo.properties.remove(i)));
+          let etag = format!("\"{}\"", hash(&todo.to_string()));
+          if let Some((i, stamp)) = stamp {
+            todo.properties.insert(i, stamp);
+          }
+          Object {
+            name: names
+              .get(&task.id)
+              .cloned()
+              .unwrap_or_else(|| format!("{}.ics", extras.uid)),
+            task_id: task.id.clone(),
+            uid: extras.uid,
+            etag,
+            data: calendar(&project.name, vec![todo]).to_string(),
+          }
+        })
+        .collect(),
+    )
+  }
+
+  /// Resource names chosen by clients, by task id.
+  fn caldav_names(&self) -> Result<HashMap<String, String>> {
+    let conn = self.conn();
+    let mut stmt = conn.prepare("select task_id, name from caldav_resources")?;
+    let rows = stmt
+      .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(rows.into_iter().collect())
+  }
+
+  fn caldav_object(&self, project: &Project, name: &str) -> Result<Option<Object>> {
+    Ok(
+      self
+        .caldav_objects(project)?
+        .into_iter()
+        .find(|o| o.name == name),
+    )
+  }
+}
+
+/// Collection tag: changes whenever any resource in it does.
+fn ctag(objects: &[Object]) -> String {
+  let mut all = String::new();
+  for object in objects {
+    all.push_str(&object.name);
+    all.push_str(&object.etag);
+  }
+  hash(&all)
+}
+
+fn dav(store: &Store, request: &HttpRequest, path: &str) -> Result<HttpResponse> {
+  if request.method == "OPTIONS" {
+    return Ok(options());
+  }
+  let Some(target) = store.caldav_target(path)? else {
+    return Ok(HttpResponse::text(404, "not found"));
+  };
+  match (request.method.as_str(), target) {
+    ("PROPFIND", target) => propfind(store, request, &target),
+    ("REPORT", Target::Collection(project)) => report(store, request, &project),
+    ("GET" | "HEAD", Target::Object(project, name)) => {
+      Ok(match store.caldav_object(&project, &name)? {
+        Some(object) => HttpResponse::new(200, ICS, object.data).header("ETag", object.etag),
+        None => HttpResponse::text(404, "not found"),
+      })
+    }
+    ("PUT", Target::Object(project, name)) => put(store, request, &project, &name),
+    ("DELETE", Target::Object(project, name)) => {
+      let Some(object) = store.caldav_object(&project, &name)? else {
+        return Ok(HttpResponse::text(404, "not found"));
+      };
+      if !if_match(request, Some(&object)) {
+        return Ok(HttpResponse::text(412, "precondition failed"));
+      }
+      store.trash_task(&object.task_id)?;
+      Ok(HttpResponse::text(204, ""))
+    }
+    _ => Ok(HttpResponse::text(405, "method not allowed").header("Allow", ALLOW)),
+  }
+}
+
+const ALLOW: &str = "OPTIONS, GET, HEAD, PUT, DELETE, PROPFIND, REPORT";
+
+fn options() -> HttpResponse {
+  HttpResponse::text(200, "")
+    .header("DAV", "1, 3, calendar-access")
+    .header("Allow", ALLOW)
+}
+
+/// `If-Match` and `If-None-Match` against the resource's current state.
+fn if_match(request: &HttpRequest, current: Option<&Object>) -> bool {
+  let listed = |header: &str, etag: &str| {
+    header
+      .split(',')
+      .map(str::trim)
+      .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+  };
+  if let Some(header) = request.header("if-match") {
+    if !current.is_some_and(|o| listed(header, &o.etag)) {
+      return false;
+    }
+  }
+  if let Some(header) = request.header("if-none-match") {
+    if current.is_some_and(|o| listed(header, &o.etag)) {
+      return false;
+    }
+  }
+  true
+}
+
+/// A `403` naming the CalDAV precondition that failed.
+fn precondition(condition: &str) -> HttpResponse {
+  HttpResponse::new(
+    403,
+    XML,
+    format!(
+      "<?xml version=\"1.0\" encoding=\"utf-8\"?>\
+       <d:error xmlns:d=\"DAV:\" xmlns:c=\"{CALDAV}\">{condition}</d:error>"
+    ),
+  )
+}
+
+fn put(
+  store: &Store,
+  request: &HttpRequest,
+  project: &Project,
+  name: &str,
+) -> Result<HttpResponse> {
+  let existing = store.caldav_object(project, name)?;
+  if !if_match(request, existing.as_ref()) {
+    return Ok(HttpResponse::text(412, "precondition failed"));
+  }
+  let body = String::from_utf8_lossy(&request.body);
+  let roots = match Component::parse_all(&body) {
+    Ok(roots) => roots,
+    Err(_) => return Ok(precondition("<c:valid-calendar-data/>")),
+  };
+  let components: Vec<&Component> = roots
+    .iter()
+    .filter(|c| c.name == "VCALENDAR")
+    .flat_map(|c| &c.components)
+    .filter(|c| c.name != "VTIMEZONE")
+    .collect();
+  // Overrides of a recurring to-do carry a RECURRENCE-ID; only the master
+  // maps to a task.
+  let todos: Vec<&Component> = components
+    .iter()
+    .copied()
+    .filter(|c| c.name == "VTODO" && c.get("RECURRENCE-ID").is_none())
+    .collect();
+  if components.iter().any(|c| c.name != "VTODO") || todos.len() != 1 {
+    return Ok(precondition("<c:supported-calendar-component/>"));
+  }
+  let todo = todos[0];
+  let Some(uid) = todo.text("UID").filter(|u| !u.trim().is_empty()) else {
+    return Ok(precondition("<c:valid-calendar-object-resource/>"));
+  };
+  let owner = uid_of(&store.conn(), &uid)?;
+  let conflict = match &existing {
+    Some(object) => object.uid != uid,
+    None => owner.is_some(),
+  };
+  if conflict {
+    let names = store.caldav_names()?;
+    let href = owner
+      .and_then(|id| store.get_task_by_id(&id).ok().flatten())
+      .and_then(|task| {
+        let name = names
+          .get(&task.id)
+          .cloned()
+          .unwrap_or_else(|| format!("{uid}.ics"));
+        Some(href_of(&task.project?, &name))
+      })
+      .unwrap_or_default();
+    return Ok(precondition(&format!(
+      "<c:no-uid-conflict><d:href>{}</d:href></c:no-uid-conflict>",
+      escape(&href)
+    )));
+  }
+
+  let single = calendar(&project.name, vec![todo.clone()]).to_string();
+  store.import_ics(&single, Some(&project.id))?;
+  if existing.is_none() && name != format!("{uid}.ics") {
+    let task_id = uid_of(&store.conn(), &uid)?;
+    if let Some(task_id) = task_id {
+      store.conn().execute(
+        "insert or replace into caldav_resources (name, task_id) values (?1, ?2)",
+        params![name, task_id],
+      )?;
+    }
+  }
+  // The stored object is re-rendered, not the client's bytes, so no ETag
+  // here (RFC 4791 5.3.4); the client fetches it again.
+  Ok(HttpResponse::text(
+    if existing.is_some() { 204 } else { 201 },
+    "",
+  ))
+}
+
+type PropName = (String, String);
+
+/// What a `PROPFIND`/`REPORT` asks for: `None` for `allprop`.
+fn requested(parent: Option<roxmltree::Node<'_, '_>>) -> Option<Vec<PropName>> {
+  let prop = parent?
+    .children()
+    .find(|n| n.is_element() && n.tag_name().name() == "prop")?;
+  Some(
+    prop
+      .children()
+      .filter(|n| n.is_element())
+      .map(|n| {
+        (
+          n.tag_name().namespace().unwrap_or_default().to_string(),
+          n.tag_name().name().to_string(),
+        )
+      })
+      .collect(),
+  )
+}
+
+fn parse_xml(body: &str) -> Result<roxmltree::Document<'_>> {
+  roxmltree::Document::parse(body)
+    .map_err(|e| Error::InvalidInput(format!("invalid XML body: {e}")))
+}
+
+/// A resource as `PROPFIND` and `REPORT` see it.
+enum Resource<'a> {
+  Root,
+  Collection(&'a Project, String),
+  Object(&'a Object),
+}
+
+impl Resource<'_> {
+  fn all_props(&self) -> &'static [(&'static str, &'static str)] {
+    match self {
+      Resource::Root => &[
+        (DAV, "resourcetype"),
+        (DAV, "displayname"),
+        (DAV, "current-user-principal"),
+        (DAV, "principal-URL"),
+        (CALDAV, "calendar-home-set"),
+      ],
+      Resource::Collection(..) => &[
+        (DAV, "resourcetype"),
+        (DAV, "displayname"),
+        (DAV, "current-user-principal"),
+        (DAV, "current-user-privilege-set"),
+        (DAV, "supported-report-set"),
+        (CALDAV, "supported-calendar-component-set"),
+        (CALSERVER, "getctag"),
+      ],
+      Resource::Object(_) => &[
+        (DAV, "resourcetype"),
+        (DAV, "getetag"),
+        (DAV, "getcontenttype"),
+      ],
+    }
+  }
+
+  /// Inner XML of a property, `None` if the resource has no such property.
+  fn value(&self, ns: &str, name: &str) -> Option<String> {
+    let principal = format!("<d:href>{DAV_ROOT}</d:href>");
+    match (self, ns, name) {
+      (_, DAV, "current-user-principal") => Some(principal),
+      (Resource::Root, DAV, "resourcetype") => Some("<d:collection/><d:principal/>".into()),
+      (Resource::Root, DAV, "displayname") => Some("蜗牛待办".into()),
+      (Resource::Root, DAV, "principal-URL") | (Resource::Root, CALDAV, "calendar-home-set") => {
+        Some(principal)
+      }
+      (Resource::Collection(..), DAV, "resourcetype") => {
+        Some("<d:collection/><c:calendar/>".into())
+      }
+      (Resource::Collection(project, _), DAV, "displayname") => Some(escape(&project.name)),
+      (Resource::Collection(..), DAV, "current-user-privilege-set") => Some(
+        ["read", "write", "write-content", "bind", "unbind"]
+          .iter()
+          .map(|p| format!("<d:privilege><d:{p}/></d:privilege>"))
+          .collect(),
+      ),
+      (Resource::Collection(..), DAV, "supported-report-set") => Some(
+        ["calendar-query", "calendar-multiget"]
+          .iter()
+          .map(|r| {
+            format!("<d:supported-report><d:report><c:{r}/></d:report></d:supported-report>")
+          })
+          .collect(),
+      ),
+      (Resource::Collection(..), CALDAV, "supported-calendar-component-set") => {
+        Some("<c:comp name=\"VTODO\"/>".into())
+      }
+      (Resource::Collection(_, ctag), CALSERVER, "getctag") => Some(escape(ctag)),
+      (Resource::Object(_), DAV, "resourcetype") => Some(String::new()),
+      (Resource::Object(object), DAV, "getetag") => Some(escape(&object.etag)),
+      (Resource::Object(_), DAV, "getcontenttype") => {
+        Some("text/calendar; charset=utf-8; component=vtodo".into())
+      }
+      (Resource::Object(object), CALDAV, "calendar-data") => Some(escape(&object.data)),
+      _ => None,
+    }
+  }
+}
+
+fn escape(value: &str) -> String {
+  value
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+}
+
+/// A `207 Multi-Status` body.
+struct Multistatus(String);
+
+impl Multistatus {
+  fn new() -> Self {
+    Self(String::new())
+  }
+
+  fn resource(&mut self, href: &str, resource: &Resource<'_>, props: Option<&[PropName]>) {
+    let wanted: Vec<PropName> = match props {
+      Some(props) => props.to_vec(),
+      None => resource
+        .all_props()
+        .iter()
+        .map(|(ns, name)| (ns.to_string(), name.to_string()))
+        .collect(),
+    };
+    let (mut found, mut missing) = (String::new(), String::new());
+    for (ns, name) in &wanted {
+      match resource.value(ns, name) {
+        Some(value) => found.push_str(&element(ns, name, &value)),
+        None => missing.push_str(&element(ns, name, "")),
+      }
+    }
+    self
+      .0
+      .push_str(&format!("<d:response><d:href>{}</d:href>", escape(href)));
+    for (props, status) in [(found, "200 OK"), (missing, "404 Not Found")] {
+      if !props.is_empty() {
+        self.0.push_str(&format!(
+          "<d:propstat><d:prop>{props}</d:prop><d:status>HTTP/1.1 {status}</d:status></d:propstat>"
+        ));
+      }
+    }
+    self.0.push_str("</d:response>");
+  }
+
+  fn not_found(&mut self, href: &str) {
+    self.0.push_str(&format!(
+      "<d:response><d:href>{}</d:href><d:status>HTTP/1.1 404 Not Found</d:status></d:response>",
+      escape(href)
+    ));
+  }
+
+  fn finish(self) -> HttpResponse {
+    HttpResponse::new(
+      207,
+      XML,
+      format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\
+         <d:multistatus xmlns:d=\"DAV:\" xmlns:c=\"{CALDAV}\" xmlns:cs=\"{CALSERVER}\">{}</d:multistatus>",
+        self.0
+      ),
+    )
+  }
+}
+
+fn element(ns: &str, name: &str, value: &str) -> String {
+  let (prefix, declare) = match ns {
+    DAV => ("d", String::new()),
+    CALDAV => ("c", String::new()),
+    CALSERVER => ("cs", String::new()),
+    other => ("x", format!(" xmlns:x=\"{}\"", escape(other))),
+  };
+  if value.is_empty() {
+    format!("<{prefix}:{name}{declare}/>")
+  } else {
+    format!("<{prefix}:{name}{declare}>{value}</{prefix}:{name}>")
+  }
+}
+
+fn propfind(store: &Store, request: &HttpRequest, target: &Target) -> Result<HttpResponse> {
+  let body = String::from_utf8_lossy(&request.body);
+  let document = if body.trim().is_empty() {
+    None
+  } else {
+    Some(parse_xml(&body)?)
+  };
+  let props = document
+    .as_ref()
+    .and_then(|d| requested(Some(d.root_element())));
+  let props = props.as_deref();
+  let children = request.header("depth").map(str::trim) != Some("0");
+
+  let mut status = Multistatus::new();
+  match target {
+    Target::Root => {
+      status.resource(DAV_ROOT, &Resource::Root, props);
+      if children {
+        for project in store.get_projects()? {
+          let objects = store.caldav_objects(&project)?;
+          let resource = Resource::Collection(&project, ctag(&objects));
+          status.resource(&collection_href(&project), &resource, props);
+        }
+      }
+    }
+    Target::Collection(project) => {
+      let objects = store.caldav_objects(project)?;
+      let resource = Resource::Collection(project, ctag(&objects));
+      status.resource(&collection_href(project), &resource, props);
+      if children {
+        for object in &objects {
+          status.resource(
+            &href_of(&project.id, &object.name),
+            &Resource::Object(object),
+            props,
+          );
+        }
+      }
+    }
+    Target::Object(project, name) => match store.caldav_object(project, name)? {
+      Some(object) => status.resource(
+        &href_of(&project.id, name),
+        &Resource::Object(&object),
+        props,
+      ),
+      None => return Ok(HttpResponse::text(404, "not found")),
+    },
+  }
+  Ok(status.finish())
+}
+
+/// Whether a `calendar-query` filter can match a `VTODO`: only the
+/// component filters are honoured, property filters match everything.
+fn matches_todo(filter: Option<roxmltree::Node<'_, '_>>) -> bool {
+  let Some(filter) = filter else {
+    return true;
+  };
+  let Some(calendar) = filter
+    .children()
+    .find(|n| n.is_element() && n.tag_name().name() == "comp-filter")
+  else {
+    return true;
+  };
+  calendar
+    .children()
+    .filter(|n| n.is_element() && n.tag_name().name() == "comp-filter")
+    .all(|c| {
+      c.attribute("name")
+        .is_some_and(|n| n.eq_ignore_ascii_case("VTODO"))
+    })
+}
+
+fn report(store: &Store, request: &HttpRequest, project: &Project) -> Result<HttpResponse> {
+  let body = String::from_utf8_lossy(&request.body);
+  let document = parse_xml(&body)?;
+  let root = document.root_element();
+  let props = requested(Some(root));
+  let props = props.as_deref();
+  let objects = store.caldav_objects(project)?;
+  let mut status = Multistatus::new();
+  match (root.tag_name().namespace(), root.tag_name().name()) {
+    (Some(CALDAV), "calendar-query") => {
+      let filter = root
+        .children()
+        .find(|n| n.is_element() && n.tag_name().name() == "filter");
+      if matches_todo(filter) {
+        for object in &objects {
+          status.resource(
+            &href_of(&project.id, &object.name),
+            &Resource::Object(object),
+            props,
+          );
+        }
+      }
+    }
+    (Some(CALDAV), "calendar-multiget") => {
+      for href in root
+        .children()
+        .filter(|n| n.is_element() && n.tag_name().name() == "href")
+        .filter_map(|n| n.text())
+      {
+        let object = parse_url(href.trim())
+          .and_then(|url| match store.caldav_target(url.path()).ok()?? {
+            Target::Object(p, name) if p.id == project.id => Some(name),
+            _ => None,
+          })
+          .and_then(|name| objects.iter().find(|o| o.name == name));
+        match object {
+          Some(object) => status.resource(href.trim(), &Resource::Object(object), props),
+          None => status.not_found(href.trim()),
+        }
+      }
+    }
+    _ => return Ok(precondition("<d:supported-report/>")),
+  }
+  Ok(status.finish())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::feed::LocalServer;
+  use crate::storage::models::Task;
+  use http::{Request, StatusCode, Uri};
+  use hyper_util::client::legacy::connect::HttpConnector;
+  use hyper_util::client::legacy::Client;
+  use hyper_util::rt::TokioExecutor;
+  use libdav::dav::{mime_types, WebDavClient, WebDavError};
+  use libdav::{names, CalDavClient};
+  use std::net::Ipv4Addr;
+  use std::sync::Arc;
+  use tower_http::auth::AddAuthorization;
+
+  type Peer = CalDavClient<AddAuthorization<Client<HttpConnector, String>>>;
+
+  /// The server with one project holding one task, and a peer signed in
+  /// with a feed token.
+  struct Fixture {
+    store: Arc<Store>,
+    work: Project,
+    ship: Task,
+    peer: Peer,
+    server: LocalServer,
+  }
+
+  impl Fixture {
+    fn new() -> Self {
+      let store = Arc::new(Store::open_in_memory().unwrap());
+      let work = store
+        .create_project(Project {
+          name: "Work".into(),
+          ..Default::default()
+        })
+        .unwrap();
+      let ship = store
+        .create_task(Task {
+          title: "ship".into(),
+          project: Some(work.id.clone()),
+          ..Default::default()
+        })
+        .unwrap();
+      let token = store.create_calendar_feed("phone").unwrap().token;
+      let shared = store.clone();
+      let server = LocalServer::start(Ipv4Addr::LOCALHOST.into(), 0, move |request| {
+        handle(&shared, request)
+      })
+      .unwrap();
+      let peer = peer(server.port(), &token);
+      Self {
+        store,
+        work,
+        ship,
+        peer,
+        server,
+      }
+    }
+
+    fn collection(&self) -> String {
+      collection_href(&self.work)
+    }
+
+    /// Current ETag of `href`, as the peer lists it.
+    async fn etag(&self, href: &str) -> Option<String> {
+      self
+        .peer
+        .list_resources(&self.collection())
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|r| r.href == href)
+        .and_then(|r| r.details.etag)
+    }
+
+    async fn put(&self, href: &str, data: String) -> Result<(), WebDavError> {
+      self
+        .peer
+        .create_resource(href, data.into_bytes(), mime_types::CALENDAR)
+        .await
+        .map(drop)
+    }
+
+    async fn update(&self, href: &str, data: String, etag: &str) -> Result<(), WebDavError> {
+      self
+        .peer
+        .update_resource(href, data.into_bytes(), etag, mime_types::CALENDAR)
+        .await
+        .map(drop)
+    }
+
+    /// Status and body of a raw `PUT`, for answers the client library
+    /// reduces to a status code.
+    async fn put_raw(&self, href: &str, data: String) -> (StatusCode, String) {
+      let request = Request::put(self.peer.relative_uri(href).unwrap())
+        .header("Content-Type", "text/calendar")
+        .body(data)
+        .unwrap();
+      let (head, body) = self.peer.request(request).await.unwrap();
+      (head.status, String::from_utf8_lossy(&body).into_owned())
+    }
+  }
+
+  fn peer(port: u16, password: &str) -> Peer {
+    let http = Client::builder(TokioExecutor::new()).build_http();
+    let base: Uri = format!("http://127.0.0.1:{port}{DAV_ROOT}")
+      .parse()
+      .unwrap();
+    CalDavClient::new(WebDavClient::new(
+      base,
+      AddAuthorization::basic(http, "me", password),
+    ))
+  }
+
+  /// The status a request was refused with, `None` if it succeeded.
+  fn refusal(result: Result<(), WebDavError>) -> Option<StatusCode> {
+    match result {
+      Ok(()) => None,
+      Err(WebDavError::BadStatusCode(status)) => Some(status),
+      Err(e) => panic!("{e}"),
+    }
+  }
+
+  fn calendar_of(uid: &str, components: &[&str]) -> String {
+    let mut data = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//Test//Peer//EN\r\n".to_string();
+    for component in components {
+      data.push_str(&format!(
+        "BEGIN:{component}\r\nUID:{uid}\r\nDTSTAMP:20261018T080000Z\r\nSUMMARY:call mom\r\n\
+         DUE;VALUE=DATE:20261019\r\nEND:{component}\r\n"
+      ));
+    }
+    data + "END:VCALENDAR\r\n"
+  }
+
+  fn todo(status: &str) -> String {
+    calendar_of("call-mom@phone", &["VTODO"])
+      .replace("END:VTODO", &format!("STATUS:{status}\r\nEND:VTODO"))
+  }
+
+  #[tokio::test]
+  async fn a_caldav_client_discovers_calendars_and_todos() {
+    let f = Fixture::new();
+    let anonymous = peer(f.server.port(), "guess");
+    assert!(matches!(
+      anonymous.find_calendars(anonymous.base_url()).await,
+      Err(WebDavError::BadStatusCode(StatusCode::UNAUTHORIZED))
+    ));
+
+    f.peer.check_support(f.peer.base_url()).await.unwrap();
+    let principal = f.peer.find_current_user_principal().await.unwrap().unwrap();
+    assert_eq!(principal.path(), DAV_ROOT);
+    let homes = f.peer.find_calendar_home_set(&principal).await.unwrap();
+    assert_eq!(homes.iter().map(Uri::path).collect::<Vec<_>>(), [DAV_ROOT]);
+    let calendars = f.peer.find_calendars(&homes[0]).await.unwrap();
+    assert_eq!(
+      calendars
+        .iter()
+        .map(|c| c.href.as_str())
+        .collect::<Vec<_>>(),
+      [f.collection()]
+    );
+
+    let collection = f.collection();
+    let name = f.peer.get_property(&collection, &names::DISPLAY_NAME).await;
+    assert_eq!(name.unwrap().as_deref(), Some("Work"));
+    // Properties the server does not keep come back as 404 propstats.
+    let colour = f
+      .peer
+      .get_property(&collection, &names::CALENDAR_COLOUR)
+      .await;
+    assert_eq!(colour.unwrap(), None);
+
+    let href = format!("{}{}.ics", f.collection(), f.ship.id);
+    let missing = format!("{}missing.ics", f.collection());
+    let fetched = f
+      .peer
+      .get_calendar_resources(&f.collection(), [&href, &missing])
+      .await
+      .unwrap();
+    let content = fetched[0].content.as_ref().unwrap();
+    assert!(content.data.contains("SUMMARY:ship"));
+    assert_eq!(Some(&content.etag), f.etag(&href).await.as_ref());
+    assert_eq!(fetched[1].content, Err(StatusCode::NOT_FOUND));
+
+    // A local edit shows up under a new ETag.
+    let before = f.etag(&href).await;
+    let mut patch = serde_json::Map::new();
+    patch.insert("title".into(), "ship it".into());
+    f.store.update_task(&f.ship.id, &patch).unwrap();
+    assert_ne!(f.etag(&href).await, before);
+  }
+
+  #[tokio::test]
+  async fn writes_with_a_stale_etag_are_refused_with_412() {
+    let f = Fixture::new();
+    // The peer creates a to-do under a name of its own choosing.
+    let href = format!("{}call-mom.ics", f.collection());
+    f.put(&href, todo("NEEDS-ACTION")).await.unwrap();
+    assert_eq!(
+      refusal(f.put(&href, todo("NEEDS-ACTION")).await),
+      Some(StatusCode::PRECONDITION_FAILED)
+    );
+    let task_id = uid_of(&f.store.conn(), "call-mom@phone").unwrap().unwrap();
+    let task = f.store.get_task_by_id(&task_id).unwrap().unwrap();
+    assert_eq!(
+      (task.title.as_str(), task.project.as_deref()),
+      ("call mom", Some(f.work.id.as_str()))
+    );
+
+    let stale = f.etag(&href).await.unwrap();
+    f.update(&href, todo("COMPLETED"), &stale).await.unwrap();
+    assert!(f.store.get_task_by_id(&task_id).unwrap().unwrap().completed);
+    assert_eq!(
+      refusal(f.update(&href, todo("NEEDS-ACTION"), &stale).await),
+      Some(StatusCode::PRECONDITION_FAILED)
+    );
+    assert!(f.store.get_task_by_id(&task_id).unwrap().unwrap().completed);
+  }
+
+  #[tokio::test]
+  async fn a_uid_already_in_use_is_refused_with_403() {
+    let f = Fixture::new();
+    let first = format!("{}call-mom.ics", f.collection());
+    f.put(&first, todo("NEEDS-ACTION")).await.unwrap();
+
+    let (status, body) = f
+      .put_raw(
+        &format!("{}again.ics", f.collection()),
+        todo("NEEDS-ACTION"),
+      )
+      .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert!(
+      body.contains(&format!("<c:no-uid-conflict><d:href>{first}</d:href>")),
+      "{body}"
+    );
+
+    // Nor can an existing resource take over another task's UID.
+    let ship = format!("{}{}.ics", f.collection(), f.ship.id);
+    let etag = f.etag(&ship).await.unwrap();
+    let result = f.update(&ship, todo("NEEDS-ACTION"), &etag).await;
+    assert_eq!(refusal(result), Some(StatusCode::FORBIDDEN));
+    assert_eq!(
+      f.store.get_task_by_id(&f.ship.id).unwrap().unwrap().title,
+      "ship"
+    );
+    assert_eq!(
+      f.peer.list_resources(&f.collection()).await.unwrap().len(),
+      2
+    );
+  }
+
+  #[tokio::test]
+  async fn components_other_than_vtodo_are_refused_with_403() {
+    let f = Fixture::new();
+    for components in [
+      &["VEVENT"][..],
+      &["VJOURNAL"],
+      &["VTODO", "VEVENT"],
+      &["VTODO", "VTODO"],
+    ] {
+      let (status, body) = f
+        .put_raw(
+          &format!("{}other.ics", f.collection()),
+          calendar_of("other@phone", components),
+        )
+        .await;
+      assert_eq!(status, StatusCode::FORBIDDEN, "{components:?}");
+      assert!(body.contains("<c:supported-calendar-component/>"), "{body}");
+    }
+    assert_eq!(uid_of(&f.store.conn(), "other@phone").unwrap(), None);
+    assert_eq!(
+      f.peer.list_resources(&f.collection()).await.unwrap().len(),
+      1
+    );
+  }
+
+  #[tokio::test]
+  async fn delete_moves_the_task_to_the_trash() {
+    let f = Fixture::new();
+    let href = format!("{}{}.ics", f.collection(), f.ship.id);
+    let result = f.peer.delete(&href, "\"stale\"").await;
+    assert_eq!(refusal(result), Some(StatusCode::PRECONDITION_FAILED));
+    assert!(!f.store.get_task_by_id(&f.ship.id).unwrap().unwrap().deleted);
+
+    let etag = f.etag(&href).await.unwrap();
+    f.peer.delete(&href, etag).await.unwrap();
+    let trashed = f.store.get_task_by_id(&f.ship.id).unwrap().unwrap();
+    assert!(trashed.deleted && trashed.deleted_at.is_some());
+    assert_eq!(f.etag(&href).await, None);
+    let fetched = f
+      .peer
+      .get_calendar_resources(&f.collection(), [&href])
+      .await
+      .unwrap();
+    assert_eq!(fetched[0].content, Err(StatusCode::NOT_FOUND));
+
+    // Restoring it from the trash brings the resource back.
+    f.store.restore_task(&f.ship.id).unwrap();
+    assert!(f.etag(&href).await.is_some());
+  }
+}
@@ -0,0 +1,192 @@
+//! Quick-capture window.
+//!
+//! A small frameless, always-on-top window for adding a task without
+//! bringing the main window forward. The page is `public/quick-add.html`;
+//! it hands the task to the `capture_task` command and the window hides
+//! itself afterwards, so it is created once and reused.
+//!
+//! The task is not written here: it is queued for the main window, which
+//! saves it through its storage adapter like one typed into its own input,
+//! so it lands wherever the main window reads from (the local database,
+//! IndexedDB or Supabase). The queue covers a main window still loading.
+//!
+//! Besides the tray menu, the window is toggled by a global shortcut stored
+//! in the `quick_add_shortcut` setting.
+
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::error::{Error, Result};
+use crate::storage::models::UserSettings;
+use crate::storage::Store;
+
+pub const WINDOW_LABEL: &str = "quick-add";
+
+/// Settings key holding the shortcut, e.g. `CommandOrControl+Shift+Space`.
+pub const SHORTCUT_SETTING: &str = "quick_add_shortcut";
+pub const DEFAULT_SHORTCUT: &str = "CommandOrControl+Shift+Space";
+
+/// Emitted when a captured task is queued for the main window.
+pub const CAPTURED_EVENT: &str = "capture://task";
+
+/// A task from the capture window, fields as the main window stores them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapturedTask {
+  pub title: String,
+  pub project: Option<String>,
+  /// ISO timestamp, as the main window's date picker stores it.
+  pub date: Option<String>,
+  /// Tag names; the main window reuses or creates the tags.
+  pub tags: Vec<String>,
+  pub flagged: bool,
+  pub rrule: Option<String>,
+}
+
+/// A project the capture window can file tasks under.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CaptureProject {
+  pub id: String,
+  pub name: String,
+}
+
+#[derive(Default)]
+pub struct Captures {
+  pending: Mutex<Vec<CapturedTask>>,
+  /// The main window's projects, `None` until it has published them.
+  projects: Mutex<Option<Vec<CaptureProject>>>,
+}
+
+impl Captures {
+  pub fn push(&self, task: CapturedTask) {
+    self
+      .pending
+      .lock()
+      .unwrap_or_else(|e| e.into_inner())
+      .push(task);
+  }
+
+  /// Tasks captured since the last call, oldest first.
+  pub fn take(&self) -> Vec<CapturedTask> {
+    std::mem::take(&mut *self.pending.lock().unwrap_or_else(|e| e.into_inner()))
+  }
+
+  pub fn set_projects(&self, projects: Vec<CaptureProject>) {
+    *self.projects.lock().unwrap_or_else(|e| e.into_inner()) = Some(projects);
+  }
+
+  /// The projects the main window shows, or the local database's before it
+  /// has published them.
+  pub fn projects(&self, store: &Store) -> Result<Vec<CaptureProject>> {
+    if let Some(projects) = &*self.projects.lock().unwrap_or_else(|e| e.into_inner()) {
+      return Ok(projects.clone());
+    }
+    Ok(
+      store
+        .get_projects()?
+        .into_iter()
+        .map(|p| CaptureProject {
+          id: p.id,
+          name: p.name,
+        })
+        .collect(),
+    )
+  }
+}
+
+/// The shortcut currently registered, so it can be swapped out.
+#[derive(Default)]
+struct ActiveShortcut(Mutex<Option<Shortcut>>);
+
+pub fn show(app: &AppHandle) -> tauri::Result<()> {
+  if let Some(window) = app.get_webview_window(WINDOW_LABEL) {
+    window.show()?;
+    window.set_focus()?;
+    return Ok(());
+  }
+  WebviewWindowBuilder::new(app, WINDOW_LABEL, WebviewUrl::App("quick-add.html".into()))
+    .title("快速添加任务")
+    .inner_size(480.0, 132.0)
+    .resizable(false)
+    .decorations(false)
+    .always_on_top(true)
+    .skip_taskbar(true)
+    .center()
+    .focused(true)
+    .build()?;
+  Ok(())
+}
+
+pub fn hide(app: &AppHandle) {
+  if let Some(window) = app.get_webview_window(WINDOW_LABEL) {
+    let _ = window.hide();
+  }
+}
+
+fn toggle(app: &AppHandle) {
+  let visible = app
+    .get_webview_window(WINDOW_LABEL)
+    .and_then(|w| w.is_visible().ok())
+    .unwrap_or(false);
+  if visible {
+    hide(app);
+  } else if let Err(e) = show(app) {
+    log::warn!("failed to open quick add window: {e}");
+  }
+}
+
+/// Shortcut configured in `settings`, or the default.
+pub fn configured_shortcut(settings: &UserSettings) -> &str {
+  settings
+    .str(SHORTCUT_SETTING)
+    .filter(|s| !s.trim().is_empty())
+    .unwrap_or(DEFAULT_SHORTCUT)
+}
+
+/// Register the configured shortcut at startup. A shortcut already taken by
+/// another application is logged rather than failing startup.
+pub fn init_shortcut(app: &AppHandle) -> tauri::Result<()> {
+  app.plugin(tauri_plugin_global_shortcut::Builder::new().build())?;
+  app.manage(ActiveShortcut::default());
+  let configured = match app.state::<Store>().get_user_settings() {
+    Ok(settings) => configured_shortcut(&settings).to_string(),
+    Err(e) => {
+      log::warn!("failed to read quick add shortcut setting: {e}");
+      DEFAULT_SHORTCUT.to_string()
+    }
+  };
+  if let Err(e) = register_shortcut(app, &configured) {
+    log::warn!("failed to register quick add shortcut {configured}: {e}");
+  }
+  Ok(())
+}
+
+/// Replace the active shortcut with `accelerator`; returns its normalized form.
+///
+/// The old shortcut stays registered if the new one cannot be.
+pub fn register_shortcut(app: &AppHandle, accelerator: &str) -> Result<String> {
+  let shortcut: Shortcut = accelerator
+    .parse()
+    .map_err(|e| Error::InvalidInput(format!("invalid shortcut {accelerator}: {e}")))?;
+  let state = app.state::<ActiveShortcut>();
+  let mut active = state.0.lock().unwrap_or_else(|e| e.into_inner());
+  if active.as_ref() == Some(&shortcut) {
+    return Ok(shortcut.into_string());
+  }
+
+  let global = app.global_shortcut();
+  global
+    .on_shortcut(shortcut, |app, _, event| {
+      if event.state() == ShortcutState::Pressed {
+        toggle(app);
+      }
+    })
+    .map_err(|e| Error::InvalidInput(format!("cannot register shortcut {accelerator}: {e}")))?;
+  if let Some(old) = active.replace(shortcut) {
+    let _ = global.unregister(old);
+  }
+  Ok(shortcut.into_string())
+}
@@ -0,0 +1,1017 @@
+//! `snail`, a command-line front end to the desktop database.
+//!
+//! Opens the same `snail_todo.db` as the app (or `--db` / `SNAIL_DB`), so the
+//! two can be used side by side. Writes record the same task activities as
+//! the UI. Every command prints plain text by default and JSON with `--json`.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{Datelike, Local, NaiveDate, SecondsFormat, TimeDelta};
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::analytics::export::{self, ReportFormat};
+use crate::error::{Error, Result};
+use crate::ical::tasks::IcsExportOptions;
+use crate::peer::{discovery, Peer, PeerReport, PeerServer, PeerSettings, PAIRING_TTL};
+use crate::reminders::deadline_of;
+use crate::storage::models::{SearchOptions, Task, TaskActivity, TaskFilter};
+use crate::storage::{due_day, now_iso, Store, DB_FILE_NAME, OFFLINE_USER_ID};
+
+/// Must match `identifier` in `tauri.conf.json`; Tauri puts the app data
+/// directory under it.
+const APP_IDENTIFIER: &str = "com.snail.todolist";
+
+/// Length of the id prefix shown in plain output and accepted by `done`.
+const SHORT_ID: usize = 8;
+
+#[derive(Debug, Parser)]
+#[command(name = "snail", version, about = "Snail TodoList from the terminal")]
+pub struct Cli {
+  /// Print JSON instead of plain text.
+  #[arg(long, global = true)]
+  pub json: bool,
+  /// Database file; defaults to the desktop app's.
+  #[arg(long, global = true, env = "SNAIL_DB")]
+  pub db: Option<PathBuf>,
+  #[command(subcommand)]
+  pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+  /// Add a task.
+  Add {
+    title: String,
+    /// Project name or id.
+    #[arg(long, short)]
+    project: Option<String>,
+    /// Due date: YYYY-MM-DD, `today` or `tomorrow`.
+    #[arg(long, short)]
+    due: Option<String>,
+    /// Flag the task.
+    #[arg(long)]
+    flag: bool,
+  },
+  /// List open tasks.
+  List {
+    /// Only tasks due today or overdue.
+    #[arg(long)]
+    today: bool,
+    /// Project name or id.
+    #[arg(long, short)]
+    project: Option<String>,
+    /// Only flagged tasks.
+    #[arg(long)]
+    flagged: bool,
+    /// Include completed tasks.
+    #[arg(long)]
+    all: bool,
+  },
+  /// Mark a task as completed.
+  Done {
+    /// Task id, or an unambiguous prefix of it.
+    id: String,
+  },
+  /// Mark a task as blocked by another one.
+  Block {
+    /// Task id, or an unambiguous prefix of it.
+    id: String,
+    /// Id or prefix of the task it waits on.
+    #[arg(long)]
+    by: String,
+    /// Remove the link instead.
+    #[arg(long)]
+    remove: bool,
+  },
+  /// List open tasks in the order their dependencies allow.
+  Next {
+    /// Project name or id.
+    #[arg(long, short)]
+    project: Option<String>,
+  },
+  /// Pomodoro, completion and check-in numbers over a date range.
+  Report {
+    /// First day, YYYY-MM-DD; defaults to the first of this month.
+    #[arg(long)]
+    from: Option<String>,
+    /// Last day, YYYY-MM-DD; defaults to today.
+    #[arg(long)]
+    to: Option<String>,
+    /// Ignored with `--json`.
+    #[arg(long, short, value_enum, default_value_t = ReportFormat::Csv)]
+    format: ReportFormat,
+    /// Write to this file instead of stdout.
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+  },
+  /// Tracked time entries over a date range, as a timesheet CSV.
+  Timesheet {
+    /// First day, YYYY-MM-DD; defaults to the first of this month.
+    #[arg(long)]
+    from: Option<String>,
+    /// Last day, YYYY-MM-DD; defaults to today.
+    #[arg(long)]
+    to: Option<String>,
+    /// Write to this file instead of stdout.
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+  },
+  /// Write tasks to an iCalendar (.ics) file.
+  ExportIcs {
+    /// Project name or id.
+    #[arg(long, short)]
+    project: Option<String>,
+    /// Tag name or id.
+    #[arg(long, short)]
+    tag: Option<String>,
+    /// Deadlines as calendar events instead of to-dos.
+    #[arg(long)]
+    events: bool,
+    /// Write to this file instead of stdout.
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+  },
+  /// Import to-dos and events from an iCalendar (.ics) file; importing the
+  /// same file again updates the tasks it created.
+  ImportIcs {
+    file: PathBuf,
+    /// Put every task in this project (name or id).
+    #[arg(long, short)]
+    project: Option<String>,
+  },
+  /// Search titles and descriptions.
+  Search {
+    query: String,
+    /// Include completed tasks.
+    #[arg(long)]
+    all: bool,
+    #[arg(long, default_value_t = 50)]
+    limit: usize,
+  },
+  /// Sync with other Snail instances on the local network.
+  Peer {
+    #[command(subcommand)]
+    command: PeerCommand,
+  },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PeerCommand {
+  /// Accept pairing and sync from other instances until interrupted; turns
+  /// peer sync on in the settings.
+  Serve {
+    /// Defaults to the port in the settings.
+    #[arg(long)]
+    port: Option<u16>,
+    /// Print a one-time code another instance can pair with.
+    #[arg(long)]
+    pair: bool,
+  },
+  /// Pair with the instance at `host:port` using the code it shows.
+  Pair { address: String, code: String },
+  /// Sync with one paired instance, or with every one.
+  Sync {
+    /// Id, id prefix or name.
+    peer: Option<String>,
+    /// `host:port` to reach it at, instead of where it was last reached.
+    #[arg(long, requires = "peer")]
+    address: Option<String>,
+  },
+  /// List paired instances.
+  List,
+  /// Forget a paired instance.
+  Unpair {
+    /// Id, id prefix or name.
+    peer: String,
+  },
+  /// Look for instances on the local network.
+  Discover {
+    #[arg(long, default_value_t = 3)]
+    seconds: u64,
+  },
+}
+
+/// The desktop app's database, `<data dir>/<identifier>/snail_todo.db`.
+pub fn default_db_path() -> Option<PathBuf> {
+  Some(dirs::data_dir()?.join(APP_IDENTIFIER).join(DB_FILE_NAME))
+}
+
+/// Entry point of the `snail` binary.
+pub fn main() -> ExitCode {
+  let cli = Cli::parse();
+  let result = (|| {
+    let path = match &cli.db {
+      Some(path) => path.clone(),
+      None => default_db_path()
+        .ok_or_else(|| Error::InvalidInput("cannot locate the app data directory".into()))?,
+    };
+    let store = Store::open(path)?;
+    execute(
+      &store,
+      &cli.command,
+      cli.json,
+      &mut std::io::stdout().lock(),
+    )
+  })();
+  match result {
+    Ok(()) => ExitCode::SUCCESS,
+    Err(e) => {
+      eprintln!("snail: {e}");
+      ExitCode::FAILURE
+    }
+  }
+}
+
+/// Run one command against `store`, writing its output to `out`.
+pub fn execute(store: &Store, command: &Command, json: bool, out: &mut impl Write) -> Result<()> {
+  match command {
+    Command::Add {
+      title,
+      project,
+      due,
+      flag,
+    } => {
+      let task = add(store, title, project.as_deref(), due.as_deref(), *flag)?;
+      print_task(store, out, json, &task)
+    }
+    Command::List {
+      today,
+      project,
+      flagged,
+      all,
+    } => {
+      let filter = TaskFilter {
+        project_id: project
+          .as_deref()
+          .map(|p| find_project(store, p))
+          .transpose()?,
+        completed: (!all).then_some(false),
+        deleted: Some(false),
+        abandoned: Some(false),
+        flagged: flagged.then_some(true),
+        ..Default::default()
+      };
+      let mut tasks = store.get_tasks(&filter, &[])?;
+      if *today {
+        let today = Local::now().date_naive();
+        tasks.retain(|t| {
+          t.date
+            .as_deref()
+            .and_then(due_day)
+            .is_some_and(|d| d <= today)
+        });
+      }
+      sort_for_listing(&mut tasks);
+      print_tasks(store, out, json, &tasks)
+    }
+    Command::Done { id } => {
+      let task = complete(store, id)?;
+      print_task(store, out, json, &task)?;
+      let next = store
+        .get_task_recurrence(&task.id)?
+        .and_then(|r| r.next_task_id)
+        .and_then(|id| store.get_task_by_id(&id).transpose())
+        .transpose()?;
+      if json {
+        return Ok(());
+      }
+      if let Some(next) = next {
+        writeln!(out, "next occurrence:")?;
+        print_task(store, out, false, &next)?;
+      }
+      let unblocked = store.unblocked_by(&task.id)?;
+      if !unblocked.is_empty() {
+        writeln!(out, "unblocked:")?;
+        print_tasks(store, out, false, &unblocked)?;
+      }
+      Ok(())
+    }
+    Command::Block { id, by, remove } => {
+      let task = find_task(store, id)?;
+      let blocker = find_task(store, by)?;
+      if *remove {
+        store.remove_task_dependency(&task.id, &blocker.id)?;
+      } else {
+        store.add_task_dependency(&task.id, &blocker.id)?;
+      }
+      let dependencies = store.get_task_dependencies(&task.id)?;
+      if json {
+        return write_json(out, &dependencies);
+      }
+      print_task(store, out, false, &task)?;
+      if !dependencies.blocked_by.is_empty() {
+        writeln!(out, "blocked by:")?;
+        print_tasks(store, out, false, &dependencies.blocked_by)?;
+      }
+      Ok(())
+    }
+    Command::Next { project } => {
+      let project = project
+        .as_deref()
+        .map(|p| find_project(store, p))
+        .transpose()?;
+      let actions = store.next_actions(project.as_deref())?;
+      if json {
+        return write_json(out, &actions);
+      }
+      if actions.is_empty() {
+        writeln!(out, "No tasks.")?;
+        return Ok(());
+      }
+      let projects: HashMap<String, String> = store
+        .get_projects()?
+        .into_iter()
+        .map(|p| (p.id, p.name))
+        .collect();
+      for action in &actions {
+        let indent = "  ".repeat(action.level);
+        let mut line = format!("{indent}{}", plain_line(&action.task, &projects));
+        if action.blocked {
+          line.push_str("  (blocked)");
+        }
+        writeln!(out, "{line}")?;
+      }
+      Ok(())
+    }
+    Command::Report {
+      from,
+      to,
+      format,
+      output,
+    } => {
+      let (from, to) = day_range(from.as_deref(), to.as_deref())?;
+      let report = store.analytics_report(from, to, &Local)?;
+      let format = if json { ReportFormat::Json } else { *format };
+      write_output(out, output.as_ref(), &export::render(&report, format)?)
+    }
+    Command::Timesheet { from, to, output } => {
+      let (from, to) = day_range(from.as_deref(), to.as_deref())?;
+      write_output(
+        out,
+        output.as_ref(),
+        &store.timesheet_csv(from, to, &Local)?,
+      )
+    }
+    Command::ExportIcs {
+      project,
+      tag,
+      events,
+      output,
+    } => {
+      let options = IcsExportOptions {
+        project_id: project
+          .as_deref()
+          .map(|p| find_project(store, p))
+          .transpose()?,
+        tag_id: tag.as_deref().map(|t| find_tag(store, t)).transpose()?,
+        events: *events,
+        ..Default::default()
+      };
+      write_output(out, output.as_ref(), &store.export_ics(&options)?)
+    }
+    Command::ImportIcs { file, project } => {
+      let project = project
+        .as_deref()
+        .map(|p| find_project(store, p))
+        .transpose()?;
+      let summary = store.import_ics(&std::fs::read_to_string(file)?, project.as_deref())?;
+      if json {
+        return write_json(out, &summary);
+      }
+      writeln!(
+        out,
+        "created {}, updated {}, skipped {}",
+        summary.created, summary.updated, summary.skipped
+      )?;
+      Ok(())
+    }
+    Command::Search { query, all, limit } => {
+      let options = SearchOptions {
+        include_completed: *all,
+        limit: Some(*limit),
+        ..Default::default()
+      };
+      let result = store.search_tasks(query, &options)?;
+      if json {
+        return write_json(out, &result);
+      }
+      print_tasks(store, out, false, &result.tasks)?;
+      if result.total_count > result.tasks.len() {
+        writeln!(
+          out,
+          "… {} more, use --limit to see them",
+          result.total_count - result.tasks.len()
+        )?;
+      }
+      Ok(())
+    }
+    Command::Peer { command } => peer(store, command, json, out),
+  }
+}
+
+fn peer(store: &Store, command: &PeerCommand, json: bool, out: &mut impl Write) -> Result<()> {
+  match command {
+    PeerCommand::Serve { port, pair } => {
+      // The server threads need a store of their own.
+      let path = store
+        .conn()
+        .path()
+        .filter(|p| !p.is_empty())
+        .map(PathBuf::from)
+        .ok_or_else(|| Error::InvalidInput("serving needs a database file".into()))?;
+      let settings = store.peer_settings()?;
+      let settings = store.save_peer_settings(PeerSettings {
+        enabled: true,
+        port: port.unwrap_or(settings.port),
+        ..settings
+      })?;
+      let (reports, received) = std::sync::mpsc::channel();
+      let server = PeerServer::start(
+        Arc::new(Store::open(path)?),
+        settings.port,
+        move |peer, report| {
+          let _ = reports.send((peer.clone(), *report));
+        },
+      )?;
+      let _advertisement =
+        discovery::advertise(&store.replica_id()?, &store.peer_name()?, server.port())
+          .inspect_err(|e| eprintln!("snail: not advertised on the network: {e}"))
+          .ok();
+      let code = pair.then(|| server.new_pairing_code());
+      if json {
+        write_json(out, &json!({ "port": server.port(), "code": code }))?;
+      } else {
+        writeln!(out, "listening on port {}", server.port())?;
+        if let Some(code) = code {
+          writeln!(
+            out,
+            "pairing code {code}, valid for {} minutes",
+            PAIRING_TTL.as_secs() / 60
+          )?;
+        }
+      }
+      out.flush()?;
+      // Syncs run on the server's threads; report them here, which owns `out`.
+      for (peer, report) in received {
+        if json {
+          writeln!(out, "{}", json!({ "peer": peer, "report": report }))?;
+        } else {
+          print_peer_report(out, &peer, &report)?;
+        }
+        out.flush()?;
+      }
+      Ok(())
+    }
+    PeerCommand::Pair { address, code } => {
+      let peer = store.pair_with_peer(address, code)?;
+      let report = store.sync_with_peer(&peer.id, None)?;
+      if json {
+        return write_json(out, &json!({ "peer": peer, "report": report }));
+      }
+      writeln!(out, "paired with {}", peer.name)?;
+      print_peer_report(out, &peer, &report)
+    }
+    PeerCommand::Sync { peer, address } => {
+      let peers = match peer {
+        Some(peer) => vec![find_peer(store, peer)?],
+        None => store.peers()?,
+      };
+      let mut reports = Vec::new();
+      for peer in peers {
+        let report = store.sync_with_peer(&peer.id, address.as_deref());
+        if !json {
+          match &report {
+            Ok(report) => print_peer_report(out, &peer, report)?,
+            Err(e) => writeln!(out, "{}: {e}", peer.name)?,
+          }
+        }
+        reports.push(json!({
+          "peer": peer,
+          "report": report.as_ref().ok(),
+          "error": report.as_ref().err().map(ToString::to_string),
+        }));
+      }
+      if json {
+        return write_json(out, &reports);
+      }
+      Ok(())
+    }
+    PeerCommand::List => {
+      let peers = store.peers()?;
+      if json {
+        return write_json(out, &peers);
+      }
+      for peer in peers {
+        writeln!(
+          out,
+          "{}  {}  {}  last synced {}",
+          peer.id.get(..SHORT_ID).unwrap_or(&peer.id),
+          peer.name,
+          peer.address.as_deref().unwrap_or("-"),
+          peer.last_synced_at.as_deref().unwrap_or("never")
+        )?;
+      }
+      Ok(())
+    }
+    PeerCommand::Unpair { peer } => {
+      let peer = find_peer(store, peer)?;
+      store.unpair_peer(&peer.id)?;
+      if json {
+        return write_json(out, &peer);
+      }
+      writeln!(out, "unpaired {}", peer.name)?;
+      Ok(())
+    }
+    PeerCommand::Discover { seconds } => {
+      let found = discovery::discover(&store.replica_id()?, Duration::from_secs(*seconds))?;
+      if json {
+        return write_json(out, &found);
+      }
+      let paired: Vec<String> = store.peers()?.into_iter().map(|p| p.id).collect();
+      for instance in found {
+        writeln!(
+          out,
+          "{}  {}  {}{}",
+          instance
+            .replica
+            .get(..SHORT_ID)
+            .unwrap_or(&instance.replica),
+          instance.name,
+          instance.addresses.join(", "),
+          if paired.contains(&instance.replica) {
+            "  (paired)"
+          } else {
+            ""
+          }
+        )?;
+      }
+      Ok(())
+    }
+  }
+}
+
+fn print_peer_report(out: &mut impl Write, peer: &Peer, report: &PeerReport) -> Result<()> {
+  writeln!(
+    out,
+    "synced with {}: sent {}, received {}",
+    peer.name, report.sent, report.received
+  )?;
+  Ok(())
+}
+
+fn add(
+  store: &Store,
+  title: &str,
+  project: Option<&str>,
+  due: Option<&str>,
+  flagged: bool,
+) -> Result<Task> {
+  let title = title.trim();
+  if title.is_empty() {
+    return Err(Error::InvalidInput("task title is empty".into()));
+  }
+  let task = store.create_task(Task {
+    title: title.to_string(),
+    project: project.map(|p| find_project(store, p)).transpose()?,
+    date: due.map(parse_due).transpose()?,
+    flagged,
+    user_id: Some(OFFLINE_USER_ID.to_string()),
+    ..Default::default()
+  })?;
+  record(
+    store,
+    &task.id,
+    "task_created",
+    json!({ "title": task.title }),
+  )?;
+  Ok(task)
+}
+
+fn complete(store: &Store, id: &str) -> Result<Task> {
+  let task = find_task(store, id)?;
+  if task.completed {
+    return Ok(task);
+  }
+  let updates = json!({ "completed": true, "completed_at": now_iso() });
+  let task = store
+    .update_task(&task.id, updates.as_object().unwrap())?
+    .ok_or_else(|| Error::NotFound("task", id.to_string()))?;
+  record(
+    store,
+    &task.id,
+    "status_updated",
+    json!({ "from": "active", "to": "completed" }),
+  )?;
+  Ok(task)
+}
+
+fn record(store: &Store, task_id: &str, action: &str, metadata: Value) -> Result<()> {
+  let metadata: Map<String, Value> = serde_json::from_value(metadata)?;
+  store.create_task_activity(TaskActivity {
+    task_id: task_id.to_string(),
+    user_id: Some(OFFLINE_USER_ID.to_string()),
+    action: action.to_string(),
+    metadata: Some(metadata),
+    ..Default::default()
+  })?;
+  Ok(())
+}
+
+/// Due dates are stored like the date picker stores them: local midnight as
+/// an ISO timestamp.
+fn parse_due(input: &str) -> Result<String> {
+  let today = Local::now().date_naive();
+  let day = match input.trim().to_lowercase().as_str() {
+    "today" => today,
+    "tomorrow" => today + TimeDelta::days(1),
+    other => NaiveDate::parse_from_str(other, "%Y-%m-%d")
+      .map_err(|_| Error::InvalidInput(format!("invalid due date: {input}")))?,
+  };
+  let deadline = deadline_of(&day.format("%Y-%m-%d").to_string())
+    .ok_or_else(|| Error::InvalidInput(format!("invalid due date: {input}")))?;
+  Ok(deadline.to_rfc3339_opts(SecondsFormat::Millis, true))
+}
+
+fn parse_day(input: &str) -> Result<NaiveDate> {
+  NaiveDate::parse_from_str(input.trim(), "%Y-%m-%d")
+    .map_err(|_| Error::InvalidInput(format!("invalid date: {input}")))
+}
+
+/// `--from`/`--to`, defaulting to the first of this month through today.
+fn day_range(from: Option<&str>, to: Option<&str>) -> Result<(NaiveDate, NaiveDate)> {
+  let today = Local::now().date_naive();
+  let from = match from {
+    Some(from) => parse_day(from)?,
+    None => today.with_day(1).unwrap_or(today),
+  };
+  let to = to.map(parse_day).transpose()?.unwrap_or(today);
+  Ok((from, to))
+}
+
+/// Save a rendered export to `output`, or print it.
+fn write_output(out: &mut impl Write, output: Option<&PathBuf>, rendered: &str) -> Result<()> {
+  match output {
+    Some(path) => {
+      std::fs::write(path, rendered)?;
+      writeln!(out, "wrote {}", path.display())?;
+    }
+    None => writeln!(out, "{}", rendered.trim_end())?,
+  }
+  Ok(())
+}
+
+/// Resolve a project by id or case-insensitive name.
+fn find_project(store: &Store, name_or_id: &str) -> Result<String> {
+  let projects = store.get_projects()?;
+  projects
+    .iter()
+    .find(|p| p.id == name_or_id)
+    .or_else(|| {
+      projects
+        .iter()
+        .find(|p| p.name.eq_ignore_ascii_case(name_or_id.trim()))
+    })
+    .map(|p| p.id.clone())
+    .ok_or_else(|| Error::NotFound("project", name_or_id.to_string()))
+}
+
+/// Resolve a tag by id or case-insensitive name.
+fn find_tag(store: &Store, name_or_id: &str) -> Result<String> {
+  let tags = store.get_tags(None)?;
+  tags
+    .iter()
+    .find(|t| t.id == name_or_id)
+    .or_else(|| {
+      tags
+        .iter()
+        .find(|t| t.name.eq_ignore_ascii_case(name_or_id.trim()))
+    })
+    .map(|t| t.id.clone())
+    .ok_or_else(|| Error::NotFound("tag", name_or_id.to_string()))
+}
+
+/// Resolve a paired peer by id, unique id prefix or case-insensitive name.
+fn find_peer(store: &Store, peer: &str) -> Result<Peer> {
+  let peers = store.peers()?;
+  if let Some(found) = peers
+    .iter()
+    .find(|p| p.id == peer || p.name.eq_ignore_ascii_case(peer.trim()))
+  {
+    return Ok(found.clone());
+  }
+  let mut matches: Vec<&Peer> = peers.iter().filter(|p| p.id.starts_with(peer)).collect();
+  match matches.len() {
+    1 => Ok(matches.remove(0).clone()),
+    0 => Err(Error::NotFound("peer", peer.to_string())),
+    n => Err(Error::InvalidInput(format!(
+      "peer id prefix {peer} matches {n} peers"
+    ))),
+  }
+}
+
+/// Resolve a task by full id or unique prefix, ignoring trashed tasks.
+fn find_task(store: &Store, id: &str) -> Result<Task> {
+  if let Some(task) = store.get_task_by_id(id)? {
+    return Ok(task);
+  }
+  let filter = TaskFilter {
+    deleted: Some(false),
+    ..Default::default()
+  };
+  let mut matches: Vec<Task> = store
+    .get_tasks(&filter, &[])?
+    .into_iter()
+    .filter(|t| t.id.starts_with(id))
+    .collect();
+  match matches.len() {
+    1 => Ok(matches.remove(0)),
+    0 => Err(Error::NotFound("task", id.to_string())),
+    n => Err(Error::InvalidInput(format!(
+      "task id prefix {id} matches {n} tasks"
+    ))),
+  }
+}
+
+/// Open before completed, then by due date (undated last), then by title.
+fn sort_for_listing(tasks: &mut [Task]) {
+  tasks.sort_by(|a, b| {
+    let due = |t: &Task| t.date.as_deref().and_then(due_day);
+    a.completed
+      .cmp(&b.completed)
+      .then_with(|| match (due(a), due(b)) {
+        (Some(x), Some(y)) => x.cmp(&y),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+      })
+      .then_with(|| a.title.cmp(&b.title))
+  });
+}
+
+fn write_json(out: &mut impl Write, value: &impl Serialize) -> Result<()> {
+  serde_json::to_writer_pretty(&mut *out, value)?;
+  writeln!(out)?;
+  Ok(())
+}
+
+fn print_tasks(store: &Store, out: &mut impl Write, json: bool, tasks: &[Task]) -> Result<()> {
+  if json {
+    return write_json(out, &tasks);
+  }
+  if tasks.is_empty() {
+    writeln!(out, "No tasks.")?;
+    return Ok(());
+  }
+  let projects: HashMap<String, String> = store
+    .get_projects()?
+    .into_iter()
+    .map(|p| (p.id, p.name))
+    .collect();
+  for task in tasks {
+    writeln!(out, "{}", plain_line(task, &projects))?;
+  }
+  Ok(())
+}
+
+fn print_task(store: &Store, out: &mut impl Write, json: bool, task: &Task) -> Result<()> {
+  if json {
+    return write_json(out, task);
+  }
+  print_tasks(store, out, false, std::slice::from_ref(task))
+}
+
+/// `[ ] 1a2b3c4d  Title  #Project  due 2026-10-20  !`
+fn plain_line(task: &Task, projects: &HashMap<String, String>) -> String {
+  let mut line = format!(
+    "[{}] {}  {}",
+    if task.completed { "x" } else { " " },
+    task.id.get(..SHORT_ID).unwrap_or(&task.id),
+    task.title
+  );
+  if let Some(project) = task.project.as_ref() {
+    line.push_str("  #");
+    line.push_str(projects.get(project).unwrap_or(project));
+  }
+  if let Some(day) = task.date.as_deref().and_then(due_day) {
+    line.push_str(&format!("  due {day}"));
+  }
+  if task.flagged {
+    line.push_str("  !");
+  }
+  line
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::storage::models::Project;
+
+  fn run(store: &Store, args: &[&str]) -> String {
+    let cli = Cli::try_parse_from(std::iter::once("snail").chain(args.iter().copied())).unwrap();
+    let mut out = Vec::new();
+    execute(store, &cli.command, cli.json, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+  }
+
+  #[test]
+  fn add_list_and_complete() {
+    let store = Store::open_in_memory().unwrap();
+    let work = store
+      .create_project(Project {
+        name: "Work".into(),
+        ..Default::default()
+      })
+      .unwrap();
+
+    let added: Task = serde_json::from_str(&run(
+      &store,
+      &[
+        "add",
+        "Ship report",
+        "--project",
+        "work",
+        "--due",
+        "today",
+        "--flag",
+        "--json",
+      ],
+    ))
+    .unwrap();
+    assert_eq!(added.project.as_deref(), Some(work.id.as_str()));
+    assert!(added.flagged);
+    assert_eq!(
+      added.date.as_deref().and_then(due_day),
+      Some(Local::now().date_naive())
+    );
+    run(&store, &["add", "Someday"]);
+
+    let today: Vec<Task> =
+      serde_json::from_str(&run(&store, &["list", "--today", "--json"])).unwrap();
+    assert_eq!(today.len(), 1);
+    let today = run(&store, &["list", "--today"]);
+    assert!(today.contains("Ship report  #Work  due "), "{today}");
+    assert!(!today.contains("Someday"));
+
+    let short = &added.id[..SHORT_ID];
+    assert!(run(&store, &["done", short]).starts_with("[x] "));
+    assert!(!run(&store, &["list"]).contains("Ship report"));
+    assert!(run(&store, &["list", "--all"]).contains("[x]"));
+
+    let actions: Vec<_> = store
+      .get_task_activities(&added.id)
+      .unwrap()
+      .into_iter()
+      .map(|a| a.action)
+      .collect();
+    assert!(actions.contains(&"status_updated".to_string()));
+  }
+
+  #[test]
+  fn search_prints_json_results() {
+    let store = Store::open_in_memory().unwrap();
+    run(&store, &["add", "Buy milk"]);
+    run(&store, &["add", "Call mom"]);
+    let result: Value = serde_json::from_str(&run(&store, &["search", "milk", "--json"])).unwrap();
+    assert_eq!(result["totalCount"], 1);
+    assert_eq!(result["tasks"][0]["title"], "Buy milk");
+    assert_eq!(run(&store, &["search", "bread"]), "No tasks.\n");
+  }
+
+  #[test]
+  fn blocks_tasks_and_lists_next_actions() {
+    let store = Store::open_in_memory().unwrap();
+    let id = |out: String| serde_json::from_str::<Task>(&out).unwrap().id;
+    let write = id(run(&store, &["add", "Write draft", "--json"]));
+    let send = id(run(&store, &["add", "Send draft", "--json"]));
+    run(&store, &["block", &send[..SHORT_ID], "--by", &write]);
+    assert!(store.add_task_dependency(&write, &send).is_err());
+
+    let next = run(&store, &["next"]);
+    let lines: Vec<&str> = next.lines().collect();
+    assert!(lines[0].starts_with("[ ] ") && lines[0].contains("Write draft"));
+    assert!(lines[1].starts_with("  [ ] ") && lines[1].ends_with("(blocked)"));
+
+    let done = run(&store, &["done", &write]);
+    assert!(done.contains("unblocked:\n[ ] "), "{done}");
+    assert!(done.contains("Send draft"));
+    run(&store, &["block", &send, "--by", &write, "--remove"]);
+    assert!(store
+      .get_task_dependencies(&send)
+      .unwrap()
+      .blocked_by
+      .is_empty());
+  }
+
+  #[test]
+  fn writes_reports_in_each_format() {
+    let store = Store::open_in_memory().unwrap();
+    let task = serde_json::from_str::<Task>(&run(&store, &["add", "Plan", "--json"])).unwrap();
+    run(&store, &["done", &task.id]);
+    let today = Local::now().date_naive().to_string();
+    let range = ["--from", today.as_str(), "--to", today.as_str()];
+
+    let csv = run(&store, &[&["report"][..], &range].concat());
+    assert!(csv.starts_with("section,key,label,"), "{csv}");
+    assert!(csv.contains(&format!("day,{today},")));
+
+    let report: Value =
+      serde_json::from_str(&run(&store, &[&["report", "--json"][..], &range].concat())).unwrap();
+    assert_eq!(report["totals"]["tasks_completed"], 1);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("report.html");
+    let path_arg = path.to_str().unwrap();
+    run(
+      &store,
+      &[&["report", "-f", "html", "-o", path_arg][..], &range].concat(),
+    );
+    assert!(std::fs::read_to_string(&path)
+      .unwrap()
+      .contains("<h1>效率报告"));
+    assert!(store
+      .analytics_report(NaiveDate::MAX, NaiveDate::MIN, &Local)
+      .is_err());
+
+    let sheet = run(&store, &[&["timesheet"][..], &range].concat());
+    assert_eq!(
+      sheet.trim_end(),
+      crate::analytics::timesheet::TIMESHEET_HEADER
+    );
+  }
+
+  #[test]
+  fn exports_and_reimports_ics() {
+    let store = Store::open_in_memory().unwrap();
+    run(&store, &["add", "Ship it", "--due", "2026-10-20"]);
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tasks.ics");
+    let path_arg = path.to_str().unwrap();
+    run(&store, &["export-ics", "-o", path_arg]);
+    assert!(std::fs::read_to_string(&path)
+      .unwrap()
+      .contains("DUE;VALUE=DATE:20261020\r\n"));
+
+    assert_eq!(
+      run(&store, &["import-ics", path_arg]).trim(),
+      "created 0, updated 1, skipped 0"
+    );
+  }
+
+  #[test]
+  fn pairs_syncs_and_unpairs_peers() {
+    let store = Store::open_in_memory().unwrap();
+    let other = Arc::new(Store::open_in_memory().unwrap());
+    other
+      .save_peer_settings(PeerSettings {
+        name: "Laptop".into(),
+        ..Default::default()
+      })
+      .unwrap();
+    other
+      .create_task(Task {
+        title: "From the laptop".into(),
+        ..Default::default()
+      })
+      .unwrap();
+    let server = PeerServer::start(other.clone(), 0, |_, _| {}).unwrap();
+    let address = format!("127.0.0.1:{}", server.port());
+
+    let paired = run(
+      &store,
+      &["peer", "pair", &address, &server.new_pairing_code()],
+    );
+    assert_eq!(
+      paired,
+      "paired with Laptop\nsynced with Laptop: sent 0, received 1\n"
+    );
+    assert!(run(&store, &["list"]).contains("From the laptop"));
+    run(&store, &["add", "From the desk"]);
+    assert_eq!(
+      run(&store, &["peer", "sync", "laptop"]),
+      "synced with Laptop: sent 1, received 0\n"
+    );
+    assert!(run(&store, &["peer", "list"]).contains(&format!("Laptop  {address}")));
+    assert_eq!(
+      run(&store, &["peer", "unpair", "Laptop"]),
+      "unpaired Laptop\n"
+    );
+    assert!(find_peer(&store, "Laptop").is_err());
+  }
+
+  #[test]
+  fn rejects_bad_input() {
+    let store = Store::open_in_memory().unwrap();
+    assert!(parse_due("20/10/2026").is_err());
+    assert!(find_project(&store, "Nope").is_err());
+    assert!(find_task(&store, "missing").is_err());
+    assert!(find_tag(&store, "missing").is_err());
+    assert!(parse_day("2026-13-01").is_err());
+  }
+}
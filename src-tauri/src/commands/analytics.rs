@@ -0,0 +1,46 @@
+//! Productivity reports for the statistics page.
+
+use chrono::{Local, NaiveDate};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use super::blocking;
+use crate::analytics::export::{self, ReportFormat};
+use crate::analytics::Report;
+use crate::error::Result;
+use crate::storage::Store;
+
+/// A rendered export and the file name to save it under.
+#[derive(Serialize)]
+pub struct RenderedReport {
+  pub file_name: String,
+  pub content: String,
+}
+
+#[tauri::command]
+pub async fn get_analytics_report(
+  app: AppHandle,
+  from: NaiveDate,
+  to: NaiveDate,
+) -> Result<Report> {
+  blocking(move || app.state::<Store>().analytics_report(from, to, &Local)).await
+}
+
+/// The report as CSV, JSON or a self-contained HTML page; the webview
+/// saves it as a download.
+#[tauri::command]
+pub async fn export_analytics_report(
+  app: AppHandle,
+  from: NaiveDate,
+  to: NaiveDate,
+  format: ReportFormat,
+) -> Result<RenderedReport> {
+  blocking(move || {
+    let report = app.state::<Store>().analytics_report(from, to, &Local)?;
+    Ok(RenderedReport {
+      file_name: export::file_name(&report, format),
+      content: export::render(&report, format)?,
+    })
+  })
+  .await
+}
@@ -0,0 +1,139 @@
+//! Backup export/import commands.
+//!
+//! Progress is reported through the `backup://progress` event with the same
+//! `(progress, message)` pair `dataTransferService` hands to its callback.
+//! Automatic snapshots run on a background thread started from `setup`.
+
+use std::path::PathBuf;
+use std::thread;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::{blocking, tasks_changed};
+use crate::backup::schedule::{self, Snapshot};
+use crate::backup::{self, BackupManifest, ImportMode, ImportStats, WebviewData};
+use crate::error::Result;
+use crate::storage::Store;
+
+pub const PROGRESS_EVENT: &str = "backup://progress";
+/// Emitted after a snapshot restore so the webview reloads its data.
+pub const RESTORED_EVENT: &str = "backup://restored";
+
+#[derive(Clone, Serialize)]
+struct ProgressPayload<'a> {
+  progress: u8,
+  message: &'a str,
+}
+
+/// Forward progress to the webview, at most once per percentage point.
+fn progress_emitter(app: &AppHandle) -> impl FnMut(u8, &str) + '_ {
+  let mut last = None;
+  move |progress, message| {
+    if last != Some(progress) {
+      last = Some(progress);
+      let _ = app.emit(PROGRESS_EVENT, ProgressPayload { progress, message });
+    }
+  }
+}
+
+/// Suggested file name for the save dialog.
+#[tauri::command]
+pub fn default_backup_file_name() -> String {
+  backup::default_file_name()
+}
+
+#[tauri::command]
+pub async fn export_backup(app: AppHandle, path: PathBuf) -> Result<BackupManifest> {
+  blocking(move || {
+    let version = app.package_info().version.to_string();
+    let store = app.state::<Store>();
+    backup::export_to(&store, &path, &version, &mut progress_emitter(&app))
+  })
+  .await
+}
+
+#[tauri::command]
+pub async fn validate_backup(path: PathBuf) -> Result<BackupManifest> {
+  blocking(move || backup::validate(&path)).await
+}
+
+#[tauri::command]
+pub async fn import_backup(app: AppHandle, path: PathBuf, mode: ImportMode) -> Result<ImportStats> {
+  blocking(move || {
+    let store = app.state::<Store>();
+    let stats = backup::import_from(&store, &path, mode, &mut progress_emitter(&app))?;
+    tasks_changed(&app);
+    Ok(stats)
+  })
+  .await
+}
+
+/// One-time copy of the webview's IndexedDB data, made the first time the
+/// desktop app runs offline on this database.
+#[tauri::command]
+pub async fn import_webview_data(app: AppHandle, data: WebviewData) -> Result<ImportStats> {
+  blocking(move || {
+    let stats = backup::import_webview_data(&app.state::<Store>(), &data)?;
+    tasks_changed(&app);
+    Ok(stats)
+  })
+  .await
+}
+
+fn snapshot_dir(app: &AppHandle) -> Result<PathBuf> {
+  let data_dir = app.path().app_data_dir().map_err(std::io::Error::other)?;
+  Ok(schedule::snapshot_dir(&data_dir))
+}
+
+/// Start the automatic backup loop. Failures are logged and retried on the
+/// next check rather than stopping the loop.
+pub fn spawn_scheduler(app: AppHandle) {
+  thread::spawn(move || loop {
+    let result = snapshot_dir(&app).and_then(|dir| {
+      let version = app.package_info().version.to_string();
+      schedule::run_due(&app.state::<Store>(), &dir, &version)
+    });
+    match result {
+      Ok(Some(snapshot)) => log::info!("wrote automatic backup {}", snapshot.file_name),
+      Ok(None) => {}
+      Err(e) => log::warn!("automatic backup failed: {e}"),
+    }
+    thread::sleep(schedule::CHECK_INTERVAL);
+  });
+}
+
+#[tauri::command]
+pub async fn list_backup_snapshots(app: AppHandle) -> Result<Vec<Snapshot>> {
+  blocking(move || schedule::list_snapshots(&snapshot_dir(&app)?)).await
+}
+
+/// Take a snapshot immediately, outside the schedule.
+#[tauri::command]
+pub async fn create_backup_snapshot(app: AppHandle) -> Result<Snapshot> {
+  blocking(move || {
+    let dir = snapshot_dir(&app)?;
+    let version = app.package_info().version.to_string();
+    let store = app.state::<Store>();
+    let snapshot = schedule::create_snapshot(&store, &dir, &version)?;
+    schedule::prune(
+      &dir,
+      &schedule::AutoBackupPolicy::from_settings(&store.get_user_settings()?),
+    )?;
+    Ok(snapshot)
+  })
+  .await
+}
+
+#[tauri::command]
+pub async fn restore_backup_snapshot(app: AppHandle, file_name: String) -> Result<ImportStats> {
+  blocking(move || {
+    let dir = snapshot_dir(&app)?;
+    let version = app.package_info().version.to_string();
+    let stats = schedule::restore_snapshot(&app.state::<Store>(), &dir, &file_name, &version)?;
+    let _ = app.emit(RESTORED_EVENT, &stats);
+    tasks_changed(&app);
+    Ok(stats)
+  })
+  .await
+}
@@ -0,0 +1,135 @@
+//! Quick-capture window commands.
+
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use super::blocking;
+
+use crate::capture::{self, CaptureProject, CapturedTask, Captures};
+use crate::error::{Error, Result};
+use crate::quick_add::{self, QuickAdd};
+use crate::rrule::RRule;
+use crate::storage::models::UserSettings;
+use crate::storage::Store;
+use crate::tray;
+
+/// Parse a quick-add line into task fields, with the spans it recognised
+/// so the input can highlight them. `#project` is looked up among the
+/// projects the main window shows.
+#[tauri::command]
+pub async fn parse_quick_add(app: AppHandle, text: String) -> Result<QuickAdd> {
+  blocking(move || {
+    let mut parsed = quick_add::parse(&text, chrono::Local::now().naive_local());
+    if let Some(name) = parsed.project.as_deref() {
+      parsed.project_id = app
+        .state::<Captures>()
+        .projects(&app.state::<Store>())?
+        .into_iter()
+        .find(|p| p.name.eq_ignore_ascii_case(name))
+        .map(|p| p.id);
+    }
+    Ok(parsed)
+  })
+  .await
+}
+
+/// Hand a task from the quick-capture window to the main window, then hide
+/// the capture window. The main window saves it through its storage
+/// adapter, see [`capture`].
+///
+/// `date` is an ISO timestamp, as the main window's date picker stores it.
+/// `tags` are names: tags of the task's project or global ones are reused,
+/// missing ones are created as global tags.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn capture_task(
+  app: AppHandle,
+  captures: State<'_, Captures>,
+  title: String,
+  project: Option<String>,
+  date: Option<String>,
+  tags: Option<Vec<String>>,
+  flagged: Option<bool>,
+  rrule: Option<String>,
+) -> Result<CapturedTask> {
+  let title = title.trim();
+  if title.is_empty() {
+    return Err(Error::InvalidInput("task title is empty".into()));
+  }
+  // Check the rule here, so a bad one is reported in the capture window.
+  let date = date.filter(|d| !d.is_empty());
+  let rrule = rrule.filter(|r| !r.is_empty());
+  if let Some(rule) = rrule.as_deref() {
+    rule.parse::<RRule>()?;
+    if date.is_none() {
+      return Err(Error::InvalidInput(
+        "a recurring task needs a due date".into(),
+      ));
+    }
+  }
+  let task = CapturedTask {
+    title: title.to_string(),
+    project: project.filter(|p| !p.is_empty()),
+    date,
+    tags: tags
+      .unwrap_or_default()
+      .iter()
+      .map(|n| n.trim().to_string())
+      .filter(|n| !n.is_empty())
+      .collect(),
+    flagged: flagged.unwrap_or(false),
+    rrule,
+  };
+  captures.push(task.clone());
+  let _ = app.emit_to(tray::MAIN_WINDOW, capture::CAPTURED_EVENT, ());
+  capture::hide(&app);
+  Ok(task)
+}
+
+/// Tasks captured since the last call, oldest first; the main window saves
+/// them.
+#[tauri::command]
+pub fn take_captured_tasks(captures: State<'_, Captures>) -> Vec<CapturedTask> {
+  captures.take()
+}
+
+#[tauri::command]
+pub async fn get_capture_projects(app: AppHandle) -> Result<Vec<CaptureProject>> {
+  blocking(move || app.state::<Captures>().projects(&app.state::<Store>())).await
+}
+
+/// The main window publishes its projects whenever they change, so the
+/// capture window offers the same ones whatever the storage mode.
+#[tauri::command]
+pub fn set_capture_projects(captures: State<'_, Captures>, projects: Vec<CaptureProject>) {
+  captures.set_projects(projects);
+}
+
+#[tauri::command]
+pub fn hide_capture_window(app: AppHandle) {
+  capture::hide(&app);
+}
+
+#[tauri::command]
+pub async fn get_quick_add_shortcut(app: AppHandle) -> Result<String> {
+  let settings = blocking(move || app.state::<Store>().get_user_settings()).await?;
+  Ok(capture::configured_shortcut(&settings).to_string())
+}
+
+/// Switch the global shortcut and remember it in the user settings.
+#[tauri::command]
+pub async fn set_quick_add_shortcut(app: AppHandle, shortcut: String) -> Result<String> {
+  let normalized = capture::register_shortcut(&app, shortcut.trim())?;
+  let mut settings = Map::new();
+  settings.insert(
+    capture::SHORTCUT_SETTING.to_string(),
+    Value::String(shortcut.trim().to_string()),
+  );
+  blocking(move || {
+    app
+      .state::<Store>()
+      .save_user_settings(UserSettings(settings))
+  })
+  .await?;
+  Ok(normalized)
+}
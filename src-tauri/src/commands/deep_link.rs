@@ -0,0 +1,71 @@
+//! Routing `snailtodo://` links to the webview.
+//!
+//! Links can arrive before the webview has loaded (the app was launched by
+//! the link), so they are queued in Rust. The webview drains the queue on
+//! startup and again whenever `deep-link://open` fires.
+
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Emitter, Manager, State, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+use crate::deep_link::{self, DeepLink};
+use crate::tray;
+
+/// Emitted after a link was queued; the payload is empty.
+pub const OPEN_EVENT: &str = "deep-link://open";
+
+#[derive(Default)]
+pub struct PendingLinks(Mutex<Vec<DeepLink>>);
+
+fn open(app: &AppHandle, urls: Vec<Url>) {
+  let links: Vec<DeepLink> = urls
+    .iter()
+    .filter_map(|url| match deep_link::parse(url.as_str()) {
+      Ok(link) => Some(link),
+      Err(e) => {
+        log::warn!("ignoring deep link: {e}");
+        None
+      }
+    })
+    .collect();
+  if links.is_empty() {
+    return;
+  }
+  app
+    .state::<PendingLinks>()
+    .0
+    .lock()
+    .unwrap_or_else(|e| e.into_inner())
+    .extend(links);
+  tray::show_main_window(app);
+  let _ = app.emit(OPEN_EVENT, ());
+}
+
+/// Hook up the deep-link plugin. A second launch is forwarded here by the
+/// single-instance plugin, so links always reach the running app.
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+  app.manage(PendingLinks::default());
+
+  // Installed builds register the scheme from the bundle; dev builds on
+  // Linux and Windows have to do it at runtime.
+  #[cfg(all(debug_assertions, any(target_os = "linux", windows)))]
+  if let Err(e) = app.deep_link().register_all() {
+    log::warn!("failed to register deep link scheme: {e}");
+  }
+
+  if let Ok(Some(urls)) = app.deep_link().get_current() {
+    open(app, urls);
+  }
+  let handle = app.clone();
+  app
+    .deep_link()
+    .on_open_url(move |event| open(&handle, event.urls()));
+  Ok(())
+}
+
+/// Links received since the last call, oldest first.
+#[tauri::command]
+pub fn take_pending_deep_links(pending: State<'_, PendingLinks>) -> Vec<DeepLink> {
+  std::mem::take(&mut *pending.0.lock().unwrap_or_else(|e| e.into_inner()))
+}
@@ -0,0 +1,57 @@
+//! "Blocked by" links between tasks.
+
+use tauri::{AppHandle, Manager};
+
+use super::blocking;
+use crate::error::Result;
+use crate::storage::dependencies::{NextAction, TaskDependencies};
+use crate::storage::Store;
+
+/// Emitted with the tasks a completed blocker was the last open blocker of.
+pub const TASKS_UNBLOCKED_EVENT: &str = "tasks://unblocked";
+
+#[tauri::command]
+pub async fn get_task_dependencies(app: AppHandle, task_id: String) -> Result<TaskDependencies> {
+  blocking(move || app.state::<Store>().get_task_dependencies(&task_id)).await
+}
+
+#[tauri::command]
+pub async fn add_task_dependency(
+  app: AppHandle,
+  task_id: String,
+  blocker_id: String,
+) -> Result<TaskDependencies> {
+  blocking(move || {
+    app
+      .state::<Store>()
+      .add_task_dependency(&task_id, &blocker_id)
+  })
+  .await
+}
+
+#[tauri::command]
+pub async fn remove_task_dependency(
+  app: AppHandle,
+  task_id: String,
+  blocker_id: String,
+) -> Result<bool> {
+  blocking(move || {
+    app
+      .state::<Store>()
+      .remove_task_dependency(&task_id, &blocker_id)
+  })
+  .await
+}
+
+#[tauri::command]
+pub async fn get_blocked_task_ids(app: AppHandle) -> Result<Vec<String>> {
+  blocking(move || app.state::<Store>().blocked_task_ids()).await
+}
+
+#[tauri::command]
+pub async fn get_next_actions(
+  app: AppHandle,
+  project_id: Option<String>,
+) -> Result<Vec<NextAction>> {
+  blocking(move || app.state::<Store>().next_actions(project_id.as_deref())).await
+}
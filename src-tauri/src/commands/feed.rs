@@ -0,0 +1,107 @@
+//! The local calendar server: its settings, its subscriptions and the
+//! server behind them, which answers both the read-only feed and CalDAV.
+
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::caldav;
+use crate::error::Result;
+use crate::feed::{CalendarFeed, FeedSettings, LocalServer};
+use crate::storage::Store;
+
+pub struct FeedServer(Mutex<Option<LocalServer>>);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedOverview {
+  pub settings: FeedSettings,
+  /// Port the server listens on, `None` while stopped.
+  pub running_port: Option<u16>,
+  /// Host for the URLs clients are given; see [`FeedSettings::host`].
+  pub host: String,
+  pub feeds: Vec<CalendarFeed>,
+}
+
+/// Start the server if the feed is enabled. A port conflict is logged
+/// rather than failing startup; the settings card shows it as stopped.
+pub fn init(app: &AppHandle) -> Result<()> {
+  app.manage(FeedServer(Mutex::new(None)));
+  let settings = app.state::<Store>().feed_settings()?;
+  if let Err(e) = restart(app, settings) {
+    log::warn!("calendar feed not started: {e}");
+  }
+  Ok(())
+}
+
+/// Stop the running server, then start a new one if `settings` asks for it.
+fn restart(app: &AppHandle, settings: FeedSettings) -> Result<()> {
+  let state = app.state::<FeedServer>();
+  let mut server = state.0.lock().unwrap_or_else(|e| e.into_inner());
+  // Drop first so the same port can be bound again.
+  *server = None;
+  if settings.enabled {
+    if settings.lan {
+      log::warn!("calendar server open to the local network over plain HTTP");
+    }
+    let handle = app.clone();
+    *server = Some(LocalServer::start(
+      settings.address(),
+      settings.port,
+      move |request| {
+        let response = caldav::handle(&handle.state::<Store>(), request);
+        // A CalDAV client wrote a task.
+        if matches!(request.method.as_str(), "PUT" | "DELETE") && response.status < 300 {
+          super::tasks_changed(&handle);
+        }
+        response
+      },
+    )?);
+  }
+  Ok(())
+}
+
+fn overview(app: &AppHandle) -> Result<FeedOverview> {
+  let store = app.state::<Store>();
+  let running_port = app
+    .state::<FeedServer>()
+    .0
+    .lock()
+    .unwrap_or_else(|e| e.into_inner())
+    .as_ref()
+    .map(LocalServer::port);
+  let settings = store.feed_settings()?;
+  Ok(FeedOverview {
+    host: settings.host().to_string(),
+    settings,
+    running_port,
+    feeds: store.calendar_feeds()?,
+  })
+}
+
+#[tauri::command]
+pub fn get_calendar_feeds(app: AppHandle) -> Result<FeedOverview> {
+  overview(&app)
+}
+
+/// Save the settings and restart the server with them.
+#[tauri::command]
+pub fn set_calendar_feed_settings(app: AppHandle, settings: FeedSettings) -> Result<FeedOverview> {
+  let settings = app.state::<Store>().save_feed_settings(settings)?;
+  restart(&app, settings)?;
+  overview(&app)
+}
+
+#[tauri::command]
+pub fn create_calendar_feed(app: AppHandle, name: String) -> Result<FeedOverview> {
+  app.state::<Store>().create_calendar_feed(&name)?;
+  overview(&app)
+}
+
+/// Revoke a subscription; its URL stops working immediately.
+#[tauri::command]
+pub fn delete_calendar_feed(app: AppHandle, id: String) -> Result<FeedOverview> {
+  app.state::<Store>().delete_calendar_feed(&id)?;
+  overview(&app)
+}
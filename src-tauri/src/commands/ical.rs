@@ -0,0 +1,39 @@
+//! iCalendar (`.ics`) export and import.
+
+use tauri::{AppHandle, Manager};
+
+use super::analytics::RenderedReport;
+use super::{blocking, tasks_changed};
+use crate::error::Result;
+use crate::ical::tasks::{self, IcsExportOptions, IcsImportSummary};
+use crate::storage::Store;
+
+/// Tasks as a calendar file; the webview saves it as a download.
+#[tauri::command]
+pub async fn export_ics(app: AppHandle, options: IcsExportOptions) -> Result<RenderedReport> {
+  blocking(move || {
+    Ok(RenderedReport {
+      file_name: tasks::file_name(&options).to_string(),
+      content: app.state::<Store>().export_ics(&options)?,
+    })
+  })
+  .await
+}
+
+/// Import a calendar file's to-dos and events as tasks.
+#[tauri::command]
+pub async fn import_ics(
+  app: AppHandle,
+  content: String,
+  project_id: Option<String>,
+) -> Result<IcsImportSummary> {
+  let handle = app.clone();
+  let summary = blocking(move || {
+    handle
+      .state::<Store>()
+      .import_ics(&content, project_id.as_deref())
+  })
+  .await?;
+  tasks_changed(&app);
+  Ok(summary)
+}
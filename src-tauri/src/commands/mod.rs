@@ -0,0 +1,41 @@
+//! Tauri command handlers, grouped by subsystem.
+
+pub mod analytics;
+pub mod backup;
+pub mod capture;
+pub mod deep_link;
+pub mod dependencies;
+pub mod feed;
+pub mod ical;
+pub mod peers;
+pub mod pomodoro;
+pub mod query;
+pub mod recurrence;
+pub mod reminders;
+pub mod storage;
+pub mod sync;
+pub mod time_tracking;
+pub mod webhooks;
+
+use tauri::{AppHandle, Emitter};
+
+use crate::error::{Error, Result};
+use crate::tray;
+
+/// Tells the main window that tasks were written from outside it.
+pub const TASKS_CHANGED_EVENT: &str = "tasks://changed";
+
+/// Run blocking work (file I/O, large queries) off the async runtime.
+pub(crate) async fn blocking<T: Send + 'static>(
+  f: impl FnOnce() -> Result<T> + Send + 'static,
+) -> Result<T> {
+  tauri::async_runtime::spawn_blocking(f)
+    .await
+    .map_err(|e| Error::Background(e.to_string()))?
+}
+
+/// Notify the webview and the tray after the Rust side wrote tasks on its own.
+pub(crate) fn tasks_changed(app: &AppHandle) {
+  let _ = app.emit(TASKS_CHANGED_EVENT, ());
+  tray::refresh(app);
+}
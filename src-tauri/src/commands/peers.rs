@@ -0,0 +1,243 @@
+//! LAN peer sync: the server other instances pair and sync with, its mDNS
+//! advertisement, and a background thread that syncs with paired peers
+//! found on the network.
+
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::{blocking, tasks_changed};
+use crate::error::{Error, Result};
+use crate::peer::discovery::{self, Advertisement, Discovered};
+use crate::peer::{Peer, PeerReport, PeerServer, PeerSettings, PAIRING_TTL};
+use crate::storage::Store;
+
+const SYNC_INTERVAL: Duration = Duration::from_secs(60);
+/// How long each round listens for other instances.
+const DISCOVERY_WINDOW: Duration = Duration::from_secs(3);
+
+/// Emitted with the [`PeerOverview`] after every sync, either direction.
+pub const PEERS_EVENT: &str = "peers://synced";
+
+struct Running {
+  server: PeerServer,
+  _advertisement: Option<Advertisement>,
+}
+
+#[derive(Default)]
+pub struct PeerService {
+  running: Mutex<Option<Running>>,
+  /// Held while syncing so rounds never overlap.
+  syncing: Mutex<()>,
+}
+
+/// Lets the server threads reach the managed [`Store`].
+#[derive(Clone)]
+struct AppStore(AppHandle);
+
+impl AsRef<Store> for AppStore {
+  fn as_ref(&self) -> &Store {
+    self.0.state::<Store>().inner()
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerOverview {
+  pub settings: PeerSettings,
+  /// The name other instances see.
+  pub name: String,
+  /// Port the server listens on, `None` while stopped.
+  pub running_port: Option<u16>,
+  pub peers: Vec<Peer>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairingCode {
+  pub code: String,
+  pub port: u16,
+  pub expires_in_seconds: u64,
+}
+
+/// Start the server if peer sync is enabled, and the background sync.
+/// A port conflict is logged rather than failing startup.
+pub fn init(app: &AppHandle) -> Result<()> {
+  app.manage(PeerService::default());
+  let settings = app.state::<Store>().peer_settings()?;
+  if let Err(e) = restart(app, &settings) {
+    log::warn!("peer sync not started: {e}");
+  }
+  let app = app.clone();
+  thread::spawn(move || loop {
+    thread::sleep(SYNC_INTERVAL);
+    let enabled = app
+      .state::<Store>()
+      .peer_settings()
+      .is_ok_and(|s| s.enabled);
+    if !enabled {
+      continue;
+    }
+    if let Err(e) = sync_all(&app) {
+      log::warn!("peer sync failed: {e}");
+    }
+  });
+  Ok(())
+}
+
+/// Stop the running server, then start a new one if `settings` asks for it.
+fn restart(app: &AppHandle, settings: &PeerSettings) -> Result<()> {
+  let service = app.state::<PeerService>();
+  let mut running = service.running.lock().unwrap_or_else(|e| e.into_inner());
+  // Drop first so the same port can be bound again.
+  *running = None;
+  if !settings.enabled {
+    return Ok(());
+  }
+  let handle = app.clone();
+  let server = PeerServer::start(AppStore(app.clone()), settings.port, move |_, report| {
+    synced(&handle, report);
+  })?;
+  let store = app.state::<Store>();
+  let advertisement =
+    discovery::advertise(&store.replica_id()?, &store.peer_name()?, server.port())
+      .inspect_err(|e| log::warn!("peer sync not advertised: {e}"))
+      .ok();
+  *running = Some(Running {
+    server,
+    _advertisement: advertisement,
+  });
+  Ok(())
+}
+
+fn synced(app: &AppHandle, report: &PeerReport) {
+  if report.received > 0 {
+    tasks_changed(app);
+  }
+  if let Ok(overview) = overview(app) {
+    let _ = app.emit(PEERS_EVENT, overview);
+  }
+}
+
+/// Sync with every paired peer, at the address it is advertised at now if
+/// it is on the network, else where it was last reached.
+fn sync_all(app: &AppHandle) -> Result<()> {
+  let store = app.state::<Store>();
+  let peers = store.peers()?;
+  if peers.is_empty() {
+    return Ok(());
+  }
+  let service = app.state::<PeerService>();
+  let _syncing = service.syncing.lock().unwrap_or_else(|e| e.into_inner());
+  let found = discovery::discover(&store.replica_id()?, DISCOVERY_WINDOW).unwrap_or_default();
+  let mut failures = Vec::new();
+  for peer in peers {
+    let addresses = found
+      .iter()
+      .find(|d| d.replica == peer.id)
+      .map(|d| d.addresses.iter().map(|a| Some(a.as_str())).collect())
+      .unwrap_or_else(|| vec![None]);
+    let mut result: Result<PeerReport> = Err(Error::Peer("not reachable".into()));
+    for address in addresses {
+      result = store.sync_with_peer(&peer.id, address);
+      if result.is_ok() {
+        break;
+      }
+    }
+    match result {
+      Ok(report) => synced(app, &report),
+      Err(e) => failures.push(format!("{}: {e}", peer.name)),
+    }
+  }
+  if failures.is_empty() {
+    Ok(())
+  } else {
+    Err(Error::Peer(failures.join("; ")))
+  }
+}
+
+fn overview(app: &AppHandle) -> Result<PeerOverview> {
+  let store = app.state::<Store>();
+  let running_port = app
+    .state::<PeerService>()
+    .running
+    .lock()
+    .unwrap_or_else(|e| e.into_inner())
+    .as_ref()
+    .map(|r| r.server.port());
+  Ok(PeerOverview {
+    settings: store.peer_settings()?,
+    name: store.peer_name()?,
+    running_port,
+    peers: store.peers()?,
+  })
+}
+
+#[tauri::command]
+pub fn get_peers(app: AppHandle) -> Result<PeerOverview> {
+  overview(&app)
+}
+
+/// Save the settings and restart the server with them.
+#[tauri::command]
+pub fn set_peer_settings(app: AppHandle, settings: PeerSettings) -> Result<PeerOverview> {
+  let settings = app.state::<Store>().save_peer_settings(settings)?;
+  restart(&app, &settings)?;
+  overview(&app)
+}
+
+/// A one-time code for pairing another instance with this one.
+#[tauri::command]
+pub fn create_pairing_code(app: AppHandle) -> Result<PairingCode> {
+  let service = app.state::<PeerService>();
+  let running = service.running.lock().unwrap_or_else(|e| e.into_inner());
+  let server = &running
+    .as_ref()
+    .ok_or_else(|| Error::Peer("peer sync is not running".into()))?
+    .server;
+  Ok(PairingCode {
+    code: server.new_pairing_code(),
+    port: server.port(),
+    expires_in_seconds: PAIRING_TTL.as_secs(),
+  })
+}
+
+#[tauri::command]
+pub async fn discover_peers(app: AppHandle) -> Result<Vec<Discovered>> {
+  blocking(move || {
+    let replica = app.state::<Store>().replica_id()?;
+    discovery::discover(&replica, DISCOVERY_WINDOW)
+  })
+  .await
+}
+
+/// Pair with the instance at `address` using the code it shows, then sync.
+#[tauri::command]
+pub async fn pair_peer(app: AppHandle, address: String, code: String) -> Result<PeerOverview> {
+  blocking(move || {
+    let store = app.state::<Store>();
+    let peer = store.pair_with_peer(&address, &code)?;
+    let report = store.sync_with_peer(&peer.id, None)?;
+    synced(&app, &report);
+    overview(&app)
+  })
+  .await
+}
+
+#[tauri::command]
+pub async fn sync_peers(app: AppHandle) -> Result<PeerOverview> {
+  blocking(move || {
+    sync_all(&app)?;
+    overview(&app)
+  })
+  .await
+}
+
+#[tauri::command]
+pub fn unpair_peer(app: AppHandle, id: String) -> Result<PeerOverview> {
+  app.state::<Store>().unpair_peer(&id)?;
+  overview(&app)
+}
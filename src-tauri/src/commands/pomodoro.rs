@@ -0,0 +1,145 @@
+//! The native pomodoro timer: commands for the pomodoro page and a ticker
+//! thread that keeps counting while the window is hidden.
+
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::error::Result;
+use crate::pomodoro::{Phase, PhaseChange, PomodoroSettings, PomodoroState, Timer};
+use crate::storage::Store;
+use crate::tray;
+
+/// Emitted every second with the current [`PomodoroState`].
+pub const TICK_EVENT: &str = "pomodoro://tick";
+/// Emitted with a [`PhaseChange`] when a phase completes or is skipped.
+pub const PHASE_EVENT: &str = "pomodoro://phase";
+
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+pub struct PomodoroEngine(Mutex<Timer>);
+
+/// Restore the saved timer and start the ticker.
+pub fn init(app: &AppHandle) -> Result<()> {
+  let timer = Timer::load(&app.state::<Store>())?;
+  app.manage(PomodoroEngine(Mutex::new(timer)));
+  let handle = app.clone();
+  thread::spawn(move || loop {
+    if let Err(e) = run(&handle, |timer, store, now| timer.tick(store, now)) {
+      log::warn!("pomodoro tick failed: {e}");
+    }
+    thread::sleep(TICK_INTERVAL);
+  });
+  Ok(())
+}
+
+/// Apply `f` to the timer, then tell the UI and the tray about the result.
+fn run(
+  app: &AppHandle,
+  f: impl FnOnce(&mut Timer, &Store, DateTime<Utc>) -> Result<Option<PhaseChange>>,
+) -> Result<PomodoroState> {
+  let store = app.state::<Store>();
+  let engine = app.state::<PomodoroEngine>();
+  let now = Utc::now();
+  let (change, state) = {
+    let mut timer = engine.0.lock().unwrap_or_else(|e| e.into_inner());
+    let change = f(&mut timer, &store, now)?;
+    (change, timer.state(&store.pomodoro_settings()?, now))
+  };
+  if let Some(change) = change {
+    if change.completed {
+      notify(app, &change);
+    }
+    let _ = app.emit(PHASE_EVENT, &change);
+  }
+  let _ = app.emit(TICK_EVENT, &state);
+  tray::set_pomodoro(app, tray_label(&state));
+  Ok(state)
+}
+
+fn notify(app: &AppHandle, change: &PhaseChange) {
+  let (title, body) = match change.from {
+    Phase::Focus => ("专注完成", "休息一下吧"),
+    _ => ("休息结束", "开始下一个番茄吧"),
+  };
+  if let Err(e) = app.notification().builder().title(title).body(body).show() {
+    log::warn!("failed to show pomodoro notification: {e}");
+  }
+}
+
+/// "🍅 12:34" while a session is open, nothing otherwise.
+fn tray_label(state: &PomodoroState) -> Option<String> {
+  state.session.as_ref()?;
+  let icon = match state.phase {
+    Phase::Focus => "🍅",
+    _ => "☕",
+  };
+  let secs = state.remaining_seconds;
+  let paused = if state.running { "" } else { " ⏸" };
+  Some(format!("{icon} {:02}:{:02}{paused}", secs / 60, secs % 60))
+}
+
+/// Start from the tray menu, without the main window.
+pub(crate) fn start_from_tray(app: &AppHandle) {
+  if let Err(e) = run(app, |timer, store, now| {
+    timer.start(store, None, now).map(|()| None)
+  }) {
+    log::warn!("failed to start pomodoro: {e}");
+  }
+}
+
+#[tauri::command]
+pub async fn get_pomodoro_state(app: AppHandle) -> Result<PomodoroState> {
+  run(&app, |_, _, _| Ok(None))
+}
+
+/// Start a session for the current phase, or resume a paused one.
+#[tauri::command]
+pub async fn pomodoro_start(app: AppHandle, title: Option<String>) -> Result<PomodoroState> {
+  run(&app, |timer, store, now| {
+    timer.start(store, title.as_deref(), now).map(|()| None)
+  })
+}
+
+#[tauri::command]
+pub async fn pomodoro_pause(app: AppHandle) -> Result<PomodoroState> {
+  run(&app, |timer, store, now| {
+    timer.pause(store, now).map(|()| None)
+  })
+}
+
+#[tauri::command]
+pub async fn pomodoro_reset(app: AppHandle) -> Result<PomodoroState> {
+  run(&app, |timer, store, now| {
+    timer.reset(store, now).map(|()| None)
+  })
+}
+
+#[tauri::command]
+pub async fn pomodoro_skip(app: AppHandle) -> Result<PomodoroState> {
+  run(&app, |timer, store, now| timer.skip(store, now).map(Some))
+}
+
+#[tauri::command]
+pub async fn pomodoro_select(app: AppHandle, phase: Phase) -> Result<PomodoroState> {
+  run(&app, |timer, store, now| {
+    timer.select(store, phase, now).map(|()| None)
+  })
+}
+
+/// Mirror the pomodoro page's settings so the timer runs on them.
+#[tauri::command]
+pub async fn set_pomodoro_settings(
+  app: AppHandle,
+  settings: PomodoroSettings,
+) -> Result<PomodoroState> {
+  run(&app, |timer, store, _| {
+    let settings = store.save_pomodoro_settings(settings)?;
+    timer.apply_settings(store, &settings)?;
+    Ok(None)
+  })
+}
@@ -0,0 +1,51 @@
+//! Task queries and saved smart lists.
+
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Manager};
+
+use super::blocking;
+use crate::error::Result;
+use crate::query::smart_lists::SmartList;
+use crate::query::Query;
+use crate::storage::models::Task;
+use crate::storage::Store;
+
+#[tauri::command]
+pub async fn query_tasks(app: AppHandle, query: String) -> Result<Vec<Task>> {
+  blocking(move || app.state::<Store>().query_tasks(&query)).await
+}
+
+/// Check a query without running it; the error carries the parser message.
+#[tauri::command]
+pub async fn validate_query(query: String) -> Result<()> {
+  Query::parse(&query).map(|_| ())
+}
+
+#[tauri::command]
+pub async fn get_smart_lists(app: AppHandle) -> Result<Vec<SmartList>> {
+  blocking(move || app.state::<Store>().get_smart_lists()).await
+}
+
+#[tauri::command]
+pub async fn create_smart_list(app: AppHandle, list: SmartList) -> Result<SmartList> {
+  blocking(move || app.state::<Store>().create_smart_list(list)).await
+}
+
+#[tauri::command]
+pub async fn update_smart_list(
+  app: AppHandle,
+  id: String,
+  updates: Map<String, Value>,
+) -> Result<Option<SmartList>> {
+  blocking(move || app.state::<Store>().update_smart_list(&id, &updates)).await
+}
+
+#[tauri::command]
+pub async fn delete_smart_list(app: AppHandle, id: String) -> Result<bool> {
+  blocking(move || app.state::<Store>().delete_smart_list(&id)).await
+}
+
+#[tauri::command]
+pub async fn run_smart_list(app: AppHandle, id: String) -> Result<Vec<Task>> {
+  blocking(move || app.state::<Store>().run_smart_list(&id)).await
+}
@@ -0,0 +1,53 @@
+//! Recurring task rules.
+
+use chrono::NaiveDate;
+use tauri::{AppHandle, Manager};
+
+use super::blocking;
+use crate::error::Result;
+use crate::storage::recurrence::TaskRecurrence;
+use crate::storage::Store;
+
+const DEFAULT_PREVIEW: usize = 3;
+
+#[tauri::command]
+pub async fn get_task_recurrence(
+  app: AppHandle,
+  task_id: String,
+) -> Result<Option<TaskRecurrence>> {
+  blocking(move || app.state::<Store>().get_task_recurrence(&task_id)).await
+}
+
+#[tauri::command]
+pub async fn set_task_recurrence(
+  app: AppHandle,
+  task_id: String,
+  rrule: String,
+  exdates: Option<Vec<NaiveDate>>,
+) -> Result<TaskRecurrence> {
+  blocking(move || {
+    app
+      .state::<Store>()
+      .set_task_recurrence(&task_id, &rrule, exdates.unwrap_or_default())
+  })
+  .await
+}
+
+#[tauri::command]
+pub async fn clear_task_recurrence(app: AppHandle, task_id: String) -> Result<bool> {
+  blocking(move || app.state::<Store>().clear_task_recurrence(&task_id)).await
+}
+
+#[tauri::command]
+pub async fn get_upcoming_occurrences(
+  app: AppHandle,
+  task_id: String,
+  limit: Option<usize>,
+) -> Result<Vec<String>> {
+  blocking(move || {
+    app
+      .state::<Store>()
+      .upcoming_occurrences(&task_id, limit.unwrap_or(DEFAULT_PREVIEW))
+  })
+  .await
+}
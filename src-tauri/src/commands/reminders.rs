@@ -0,0 +1,56 @@
+//! Deadline reminders as native notifications.
+//!
+//! The check runs on a background thread started from `setup`, so it keeps
+//! going while the window is hidden in the tray.
+
+use std::thread;
+use std::time::Duration;
+
+use chrono::Utc;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use super::blocking;
+use crate::error::Result;
+use crate::storage::Store;
+
+/// Matches the once-a-minute granularity of the reminder settings.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Send every due reminder; returns how many were shown.
+fn notify_due(app: &AppHandle) -> Result<usize> {
+  let store = app.state::<Store>();
+  let mut sent = 0;
+  for reminder in store.due_reminders(Utc::now())? {
+    let shown = app
+      .notification()
+      .builder()
+      .title(reminder.notification_title())
+      .body(reminder.notification_body())
+      .show();
+    match shown {
+      Ok(()) => {
+        store.mark_reminded(&reminder)?;
+        sent += 1;
+      }
+      // Left unmarked so the next check tries again.
+      Err(e) => log::warn!("failed to show reminder for task {}: {e}", reminder.task_id),
+    }
+  }
+  Ok(sent)
+}
+
+pub fn spawn_scheduler(app: AppHandle) {
+  thread::spawn(move || loop {
+    if let Err(e) = notify_due(&app) {
+      log::warn!("deadline reminder check failed: {e}");
+    }
+    thread::sleep(CHECK_INTERVAL);
+  });
+}
+
+/// Run a check now, e.g. right after the reminder settings changed.
+#[tauri::command]
+pub async fn check_deadline_reminders(app: AppHandle) -> Result<usize> {
+  blocking(move || notify_due(&app)).await
+}
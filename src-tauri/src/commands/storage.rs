@@ -0,0 +1,463 @@
+//! `StorageAdapter` over IPC.
+//!
+//! One command per adapter method, named after it in snake_case
+//! (`getTasks` -> `get_tasks`). `initialize()`/`isReady()` stay on the
+//! webview side: the database is opened before the window is created. Every
+//! command runs its queries through [`blocking`], off the async runtime.
+
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::blocking;
+use super::dependencies::TASKS_UNBLOCKED_EVENT;
+use crate::error::Result;
+use crate::storage::hierarchy::{TaskMove, TaskProgress};
+use crate::storage::models::{
+  AppInfo, CheckInHistory, CheckInRecord, FileUploadResult, PomodoroSession, Project,
+  SearchOptions, SearchResult, SortOptions, SortOrderUpdate, Tag, Task, TaskActivity, TaskFilter,
+  UploadFile, UserProfile, UserSettings,
+};
+use crate::storage::{ProfileUpdate, Store};
+use crate::tray;
+
+// ============================================
+// Task Operations
+// ============================================
+
+#[tauri::command]
+pub async fn get_tasks(
+  app: AppHandle,
+  filter: Option<TaskFilter>,
+  sort: Option<Vec<SortOptions>>,
+) -> Result<Vec<Task>> {
+  blocking(move || {
+    app
+      .state::<Store>()
+      .get_tasks(&filter.unwrap_or_default(), &sort.unwrap_or_default())
+  })
+  .await
+}
+
+#[tauri::command]
+pub async fn get_task_by_id(app: AppHandle, id: String) -> Result<Option<Task>> {
+  blocking(move || app.state::<Store>().get_task_by_id(&id)).await
+}
+
+#[tauri::command]
+pub async fn create_task(app: AppHandle, task: Task) -> Result<Task> {
+  blocking(move || {
+    let store = app.state::<Store>();
+    let task = store.create_task(task)?;
+    tray::refresh(&app);
+    Ok(task)
+  })
+  .await
+}
+
+#[tauri::command]
+pub async fn update_task(
+  app: AppHandle,
+  id: String,
+  updates: Map<String, Value>,
+) -> Result<Option<Task>> {
+  blocking(move || {
+    let store = app.state::<Store>();
+    let was_done = store.get_task_by_id(&id)?.is_some_and(|t| t.completed);
+    let task = store.update_task(&id, &updates)?;
+    if !was_done && task.as_ref().is_some_and(|t| t.completed) {
+      let unblocked = store.unblocked_by(&id)?;
+      if !unblocked.is_empty() {
+        let _ = app.emit(TASKS_UNBLOCKED_EVENT, unblocked);
+      }
+    }
+    // Completing a recurring task writes its next instance behind the webview.
+    let next_created = task.as_ref().is_some_and(|t| t.completed)
+      && store
+        .get_task_recurrence(&id)?
+        .is_some_and(|r| r.next_task_id.is_some());
+    if next_created {
+      super::tasks_changed(&app);
+    } else {
+      tray::refresh(&app);
+    }
+    Ok(task)
+  })
+  .await
+}
+
+#[tauri::command]
+pub async fn delete_task(app: AppHandle, id: String) -> Result<bool> {
+  blocking(move || {
+    let store = app.state::<Store>();
+    let deleted = store.delete_task(&id)?;
+    tray::refresh(&app);
+    Ok(deleted)
+  })
+  .await
+}
+
+#[tauri::command]
+pub async fn batch_update_sort_order(
+  app: AppHandle,
+  updates: Vec<SortOrderUpdate>,
+) -> Result<bool> {
+  blocking(move || app.state::<Store>().batch_update_sort_order(&updates)).await
+}
+
+#[tauri::command]
+pub async fn get_subtree(app: AppHandle, root_id: String) -> Result<Vec<Task>> {
+  blocking(move || app.state::<Store>().get_subtree(&root_id)).await
+}
+
+#[tauri::command]
+pub async fn move_task(app: AppHandle, id: String, target: TaskMove) -> Result<Option<Task>> {
+  blocking(move || app.state::<Store>().move_task(&id, &target)).await
+}
+
+/// Reorder a task among its siblings; returns every task whose position
+/// changed, the moved one first.
+#[tauri::command]
+pub async fn place_task_between(
+  app: AppHandle,
+  id: String,
+  before: Option<String>,
+  after: Option<String>,
+) -> Result<Vec<Task>> {
+  blocking(move || {
+    app
+      .state::<Store>()
+      .place_task_between(&id, before.as_deref(), after.as_deref())
+  })
+  .await
+}
+
+#[tauri::command]
+pub async fn trash_task(app: AppHandle, id: String) -> Result<Vec<Task>> {
+  blocking(move || {
+    let store = app.state::<Store>();
+    let trashed = store.trash_task(&id)?;
+    tray::refresh(&app);
+    Ok(trashed)
+  })
+  .await
+}
+
+#[tauri::command]
+pub async fn restore_task(app: AppHandle, id: String) -> Result<Vec<Task>> {
+  blocking(move || {
+    let store = app.state::<Store>();
+    let restored = store.restore_task(&id)?;
+    tray::refresh(&app);
+    Ok(restored)
+  })
+  .await
+}
+
+#[tauri::command]
+pub async fn get_task_progress(app: AppHandle, id: String) -> Result<TaskProgress> {
+  blocking(move || app.state::<Store>().task_progress(&id)).await
+}
+
+// ============================================
+// Project Operations
+// ============================================
+
+#[tauri::command]
+pub async fn get_projects(app: AppHandle) -> Result<Vec<Project>> {
+  blocking(move || app.state::<Store>().get_projects()).await
+}
+
+#[tauri::command]
+pub async fn get_project_by_id(app: AppHandle, id: String) -> Result<Option<Project>> {
+  blocking(move || app.state::<Store>().get_project_by_id(&id)).await
+}
+
+#[tauri::command]
+pub async fn create_project(app: AppHandle, project: Project) -> Result<Project> {
+  blocking(move || app.state::<Store>().create_project(project)).await
+}
+
+#[tauri::command]
+pub async fn update_project(
+  app: AppHandle,
+  id: String,
+  updates: Map<String, Value>,
+) -> Result<Option<Project>> {
+  blocking(move || app.state::<Store>().update_project(&id, &updates)).await
+}
+
+#[tauri::command]
+pub async fn delete_project(app: AppHandle, id: String) -> Result<bool> {
+  blocking(move || app.state::<Store>().delete_project(&id)).await
+}
+
+#[tauri::command]
+pub async fn batch_update_project_sort_order(
+  app: AppHandle,
+  updates: Vec<SortOrderUpdate>,
+) -> Result<bool> {
+  blocking(move || {
+    app
+      .state::<Store>()
+      .batch_update_project_sort_order(&updates)
+  })
+  .await
+}
+
+// ============================================
+// Tag Operations
+// ============================================
+
+/// `getTags(null)` and `getTags()` look the same once serialized, so the
+/// global-only scope is requested with `globalOnly: true` instead.
+#[tauri::command]
+pub async fn get_tags(
+  app: AppHandle,
+  project_id: Option<String>,
+  global_only: Option<bool>,
+) -> Result<Vec<Tag>> {
+  blocking(move || {
+    let store = app.state::<Store>();
+    let scope = match (project_id.as_deref(), global_only.unwrap_or(false)) {
+      (Some(id), _) => Some(Some(id)),
+      (None, true) => Some(None),
+      (None, false) => None,
+    };
+    store.get_tags(scope)
+  })
+  .await
+}
+
+#[tauri::command]
+pub async fn get_tag_by_id(app: AppHandle, id: String) -> Result<Option<Tag>> {
+  blocking(move || app.state::<Store>().get_tag_by_id(&id)).await
+}
+
+#[tauri::command]
+pub async fn create_tag(app: AppHandle, name: String, project_id: Option<String>) -> Result<Tag> {
+  blocking(move || {
+    app
+      .state::<Store>()
+      .create_tag(&name, project_id.as_deref())
+  })
+  .await
+}
+
+#[tauri::command]
+pub async fn update_tag(
+  app: AppHandle,
+  id: String,
+  updates: Map<String, Value>,
+) -> Result<Option<Tag>> {
+  blocking(move || app.state::<Store>().update_tag(&id, &updates)).await
+}
+
+#[tauri::command]
+pub async fn delete_tag(app: AppHandle, id: String) -> Result<bool> {
+  blocking(move || app.state::<Store>().delete_tag(&id)).await
+}
+
+// ============================================
+// Task-Tag Operations
+// ============================================
+
+#[tauri::command]
+pub async fn get_tags_by_task_ids(
+  app: AppHandle,
+  task_ids: Vec<String>,
+) -> Result<HashMap<String, Vec<Tag>>> {
+  blocking(move || app.state::<Store>().get_tags_by_task_ids(&task_ids)).await
+}
+
+#[tauri::command]
+pub async fn attach_tag_to_task(app: AppHandle, task_id: String, tag_id: String) -> Result<()> {
+  blocking(move || app.state::<Store>().attach_tag_to_task(&task_id, &tag_id)).await
+}
+
+#[tauri::command]
+pub async fn detach_tag_from_task(app: AppHandle, task_id: String, tag_id: String) -> Result<()> {
+  blocking(move || app.state::<Store>().detach_tag_from_task(&task_id, &tag_id)).await
+}
+
+// ============================================
+// Pomodoro Operations
+// ============================================
+
+#[tauri::command]
+pub async fn get_pomodoro_sessions(
+  app: AppHandle,
+  task_id: Option<String>,
+) -> Result<Vec<PomodoroSession>> {
+  blocking(move || {
+    app
+      .state::<Store>()
+      .get_pomodoro_sessions(task_id.as_deref())
+  })
+  .await
+}
+
+#[tauri::command]
+pub async fn get_pomodoro_session_by_id(
+  app: AppHandle,
+  id: String,
+) -> Result<Option<PomodoroSession>> {
+  blocking(move || app.state::<Store>().get_pomodoro_session_by_id(&id)).await
+}
+
+#[tauri::command]
+pub async fn create_pomodoro_session(
+  app: AppHandle,
+  session: PomodoroSession,
+) -> Result<PomodoroSession> {
+  blocking(move || app.state::<Store>().create_pomodoro_session(session)).await
+}
+
+#[tauri::command]
+pub async fn update_pomodoro_session(
+  app: AppHandle,
+  id: String,
+  updates: Map<String, Value>,
+) -> Result<Option<PomodoroSession>> {
+  blocking(move || app.state::<Store>().update_pomodoro_session(&id, &updates)).await
+}
+
+#[tauri::command]
+pub async fn delete_pomodoro_session(app: AppHandle, id: String) -> Result<bool> {
+  blocking(move || app.state::<Store>().delete_pomodoro_session(&id)).await
+}
+
+// ============================================
+// Activity Operations
+// ============================================
+
+#[tauri::command]
+pub async fn get_task_activities(app: AppHandle, task_id: String) -> Result<Vec<TaskActivity>> {
+  blocking(move || app.state::<Store>().get_task_activities(&task_id)).await
+}
+
+#[tauri::command]
+pub async fn create_task_activity(app: AppHandle, activity: TaskActivity) -> Result<TaskActivity> {
+  blocking(move || app.state::<Store>().create_task_activity(activity)).await
+}
+
+// ============================================
+// Check-In Operations
+// ============================================
+
+#[tauri::command]
+pub async fn has_checked_in_today(app: AppHandle) -> Result<bool> {
+  blocking(move || app.state::<Store>().has_checked_in_today()).await
+}
+
+#[tauri::command]
+pub async fn create_check_in(app: AppHandle, note: Option<String>) -> Result<CheckInRecord> {
+  blocking(move || app.state::<Store>().create_check_in(note)).await
+}
+
+#[tauri::command]
+pub async fn get_check_in_history(
+  app: AppHandle,
+  page: Option<i64>,
+  page_size: Option<i64>,
+) -> Result<CheckInHistory> {
+  blocking(move || {
+    app
+      .state::<Store>()
+      .get_check_in_history(page.unwrap_or(1), page_size.unwrap_or(10))
+  })
+  .await
+}
+
+#[tauri::command]
+pub async fn get_check_in_streak(app: AppHandle) -> Result<i64> {
+  blocking(move || app.state::<Store>().get_check_in_streak()).await
+}
+
+// ============================================
+// File Storage Operations
+// ============================================
+
+#[tauri::command]
+pub async fn upload_attachment(
+  app: AppHandle,
+  task_id: String,
+  file: UploadFile,
+) -> Result<FileUploadResult> {
+  blocking(move || app.state::<Store>().upload_attachment(&task_id, file)).await
+}
+
+#[tauri::command]
+pub async fn delete_attachment(app: AppHandle, attachment_id: String) -> Result<bool> {
+  blocking(move || app.state::<Store>().delete_attachment(&attachment_id)).await
+}
+
+#[tauri::command]
+pub async fn upload_image(app: AppHandle, file: UploadFile) -> Result<FileUploadResult> {
+  blocking(move || app.state::<Store>().upload_image(file)).await
+}
+
+#[tauri::command]
+pub async fn upload_avatar(app: AppHandle, file: UploadFile) -> Result<FileUploadResult> {
+  blocking(move || app.state::<Store>().upload_avatar(file)).await
+}
+
+// ============================================
+// Search Operations
+// ============================================
+
+#[tauri::command]
+pub async fn search_tasks(
+  app: AppHandle,
+  query: String,
+  options: Option<SearchOptions>,
+) -> Result<SearchResult> {
+  blocking(move || {
+    app
+      .state::<Store>()
+      .search_tasks(&query, &options.unwrap_or_default())
+  })
+  .await
+}
+
+// ============================================
+// User Settings Operations
+// ============================================
+
+#[tauri::command]
+pub async fn get_user_settings(app: AppHandle) -> Result<UserSettings> {
+  blocking(move || app.state::<Store>().get_user_settings()).await
+}
+
+#[tauri::command]
+pub async fn save_user_settings(app: AppHandle, settings: UserSettings) -> Result<UserSettings> {
+  blocking(move || app.state::<Store>().save_user_settings(settings)).await
+}
+
+// ============================================
+// User Profile Operations
+// ============================================
+
+#[tauri::command]
+pub async fn get_user_profile(app: AppHandle) -> Result<Option<UserProfile>> {
+  blocking(move || app.state::<Store>().get_user_profile()).await
+}
+
+#[tauri::command]
+pub async fn save_user_profile(app: AppHandle, profile: ProfileUpdate) -> Result<UserProfile> {
+  blocking(move || app.state::<Store>().save_user_profile(profile)).await
+}
+
+// ============================================
+// App Info Operations
+// ============================================
+
+#[tauri::command]
+pub fn get_app_info(app: AppHandle) -> AppInfo {
+  AppInfo {
+    version: app.package_info().version.to_string(),
+    announcement: None,
+    maintenance_mode: false,
+  }
+}
@@ -0,0 +1,102 @@
+//! Supabase sync. The webview hands over its session; a background thread
+//! syncs on an interval and whenever the session or the user asks.
+
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::{blocking, tasks_changed};
+use crate::error::{Error, Result};
+use crate::storage::Store;
+use crate::sync::{SyncConfig, SyncReport, SyncStatus};
+
+const SYNC_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Emitted once per conflict with a [`crate::sync::SyncConflict`].
+pub const CONFLICT_EVENT: &str = "sync://conflict";
+/// Emitted with the [`SyncStatus`] after every run.
+pub const STATUS_EVENT: &str = "sync://status";
+
+#[derive(Default)]
+pub struct SyncEngine {
+  /// The signed-in session; `None` while signed out.
+  config: Mutex<Option<SyncConfig>>,
+  /// Held for the duration of a run so runs never overlap.
+  running: Mutex<()>,
+}
+
+pub fn init(app: &AppHandle) {
+  app.manage(SyncEngine::default());
+  let app = app.clone();
+  thread::spawn(move || loop {
+    thread::sleep(SYNC_INTERVAL);
+    if let Err(e) = run(&app) {
+      log::warn!("sync failed: {e}");
+    }
+  });
+}
+
+/// Sync once with the current session, if there is one.
+fn run(app: &AppHandle) -> Result<Option<SyncReport>> {
+  let engine = app.state::<SyncEngine>();
+  let Some(config) = engine
+    .config
+    .lock()
+    .unwrap_or_else(|e| e.into_inner())
+    .clone()
+  else {
+    return Ok(None);
+  };
+  let _running = engine.running.lock().unwrap_or_else(|e| e.into_inner());
+  let store = app.state::<Store>();
+  let result = store.sync(&config);
+  let _ = app.emit(STATUS_EVENT, store.sync_status()?);
+  let report = result?;
+  for conflict in &report.conflicts {
+    let _ = app.emit(CONFLICT_EVENT, conflict);
+  }
+  if report.pulled > 0 {
+    tasks_changed(app);
+  }
+  Ok(Some(report))
+}
+
+/// Set (or with `None`, clear) the session to sync with, then sync.
+#[tauri::command]
+pub async fn configure_sync(
+  app: AppHandle,
+  config: Option<SyncConfig>,
+) -> Result<Option<SyncReport>> {
+  let engine = app.state::<SyncEngine>();
+  let signed_in = config.is_some();
+  *engine.config.lock().unwrap_or_else(|e| e.into_inner()) = config;
+  if !signed_in {
+    return Ok(None);
+  }
+  blocking(move || run(&app)).await
+}
+
+#[tauri::command]
+pub async fn sync_now(app: AppHandle) -> Result<SyncReport> {
+  blocking(move || run(&app))
+    .await?
+    .ok_or_else(|| Error::Sync("not signed in".into()))
+}
+
+#[tauri::command]
+pub async fn get_sync_status(app: AppHandle) -> Result<SyncStatus> {
+  blocking(move || app.state::<Store>().sync_status()).await
+}
+
+/// Stop syncing and drop the pending changes; the next sign-in starts over.
+#[tauri::command]
+pub async fn disable_sync(app: AppHandle) -> Result<SyncStatus> {
+  let engine = app.state::<SyncEngine>();
+  *engine.config.lock().unwrap_or_else(|e| e.into_inner()) = None;
+  let _running = engine.running.lock().unwrap_or_else(|e| e.into_inner());
+  let store = app.state::<Store>();
+  store.disable_sync()?;
+  store.sync_status()
+}
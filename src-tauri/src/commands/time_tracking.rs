@@ -0,0 +1,76 @@
+//! Per-task timers, manual time entries and the timesheet export.
+
+use chrono::{Local, NaiveDate, Utc};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Manager};
+
+use super::analytics::RenderedReport;
+use super::blocking;
+use crate::analytics::timesheet;
+use crate::error::Result;
+use crate::storage::time_tracking::{TimeEntry, TimeSummary};
+use crate::storage::Store;
+
+#[tauri::command]
+pub async fn start_task_timer(
+  app: AppHandle,
+  task_id: String,
+  note: Option<String>,
+) -> Result<TimeEntry> {
+  blocking(move || app.state::<Store>().start_timer(&task_id, note)).await
+}
+
+#[tauri::command]
+pub async fn stop_task_timer(app: AppHandle) -> Result<Option<TimeEntry>> {
+  blocking(move || app.state::<Store>().stop_timer()).await
+}
+
+#[tauri::command]
+pub async fn get_running_timer(app: AppHandle) -> Result<Option<TimeEntry>> {
+  blocking(move || app.state::<Store>().running_timer()).await
+}
+
+#[tauri::command]
+pub async fn get_time_entries(app: AppHandle, task_id: String) -> Result<Vec<TimeEntry>> {
+  blocking(move || app.state::<Store>().get_time_entries(&task_id)).await
+}
+
+#[tauri::command]
+pub async fn add_time_entry(app: AppHandle, entry: TimeEntry) -> Result<TimeEntry> {
+  blocking(move || app.state::<Store>().add_time_entry(entry)).await
+}
+
+#[tauri::command]
+pub async fn update_time_entry(
+  app: AppHandle,
+  id: String,
+  updates: Map<String, Value>,
+) -> Result<Option<TimeEntry>> {
+  blocking(move || app.state::<Store>().update_time_entry(&id, &updates)).await
+}
+
+#[tauri::command]
+pub async fn delete_time_entry(app: AppHandle, id: String) -> Result<bool> {
+  blocking(move || app.state::<Store>().delete_time_entry(&id)).await
+}
+
+#[tauri::command]
+pub async fn get_time_summary(app: AppHandle) -> Result<TimeSummary> {
+  blocking(move || app.state::<Store>().time_summary(Utc::now())).await
+}
+
+/// Tracked time on the local days `from..=to` as a timesheet CSV.
+#[tauri::command]
+pub async fn export_timesheet(
+  app: AppHandle,
+  from: NaiveDate,
+  to: NaiveDate,
+) -> Result<RenderedReport> {
+  blocking(move || {
+    Ok(RenderedReport {
+      file_name: timesheet::file_name(from, to),
+      content: app.state::<Store>().timesheet_csv(from, to, &Local)?,
+    })
+  })
+  .await
+}
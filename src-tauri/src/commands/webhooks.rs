@@ -0,0 +1,97 @@
+//! Deadline webhook dispatcher.
+//!
+//! The outbox is drained on a background thread started from `setup`;
+//! deliveries queued before a restart are picked up on the first tick.
+//! When the webview keeps tasks and settings outside the local database
+//! (IndexedDB or Supabase) it hands them over with `queue_deadline_webhooks`,
+//! and the dispatcher stops reading them from SQLite.
+
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use chrono::{TimeDelta, Utc};
+use tauri::{AppHandle, Manager};
+
+use super::blocking;
+use crate::error::Result;
+use crate::storage::models::Task;
+use crate::storage::Store;
+use crate::webhooks::{self, DeliveryReport, OutboxEntry, WebhookConfig};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Where the dispatcher takes tasks and the webhook settings from.
+#[derive(Default)]
+enum Source {
+  #[default]
+  Database,
+  /// Last configuration handed over by the webview; `None` when disabled.
+  Webview(Option<WebhookConfig>),
+}
+
+#[derive(Default)]
+pub struct WebhookSource(Mutex<Source>);
+
+fn tick(app: &AppHandle) -> Result<DeliveryReport> {
+  let store = app.state::<Store>();
+  let source = app.state::<WebhookSource>();
+  let config = match &*source.0.lock().unwrap_or_else(|e| e.into_inner()) {
+    Source::Database => None,
+    Source::Webview(config) => Some(config.clone()),
+  };
+  match config {
+    None => webhooks::run_due(&store, Utc::now()),
+    Some(Some(config)) => store.deliver_webhooks(&config, Utc::now()),
+    Some(None) => Ok(DeliveryReport::default()),
+  }
+}
+
+pub fn spawn_dispatcher(app: AppHandle) {
+  app.manage(WebhookSource::default());
+  thread::spawn(move || loop {
+    match tick(&app) {
+      Ok(report) if report.failed > 0 => {
+        log::warn!("{} webhook deliveries gave up", report.failed)
+      }
+      Ok(_) => {}
+      Err(e) => log::warn!("webhook dispatch failed: {e}"),
+    }
+    thread::sleep(CHECK_INTERVAL);
+  });
+}
+
+/// Queue and deliver webhooks for tasks the webview keeps itself; `config`
+/// is `None` while webhooks are turned off.
+#[tauri::command]
+pub async fn queue_deadline_webhooks(
+  app: AppHandle,
+  config: Option<WebhookConfig>,
+  tasks: Vec<Task>,
+  reminder_minutes: i64,
+) -> Result<DeliveryReport> {
+  *app
+    .state::<WebhookSource>()
+    .0
+    .lock()
+    .unwrap_or_else(|e| e.into_inner()) = Source::Webview(config.clone());
+  let Some(config) = config else {
+    return Ok(DeliveryReport::default());
+  };
+  blocking(move || {
+    let lead = TimeDelta::minutes(reminder_minutes.max(1));
+    webhooks::run_with(&app.state::<Store>(), &config, &tasks, lead, Utc::now())
+  })
+  .await
+}
+
+/// Send a test message with the given (possibly unsaved) configuration.
+#[tauri::command]
+pub async fn send_test_webhook(config: WebhookConfig) -> Result<()> {
+  blocking(move || webhooks::send_test(&config)).await
+}
+
+#[tauri::command]
+pub async fn get_webhook_outbox(app: AppHandle) -> Result<Vec<OutboxEntry>> {
+  blocking(move || app.state::<Store>().webhook_outbox()).await
+}
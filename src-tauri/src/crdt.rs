@@ -0,0 +1,792 @@
+//! Conflict-free merging of concurrent task edits.
+//!
+//! Every database is a replica with its own id and a hybrid logical clock.
+//! Local writes are stamped by triggers (migration 15): each task column is
+//! a last-writer-wins register, and each tag link is an element of an
+//! observed-remove set, so removing a tag only removes the additions this
+//! replica had seen. `sort_order` is one of the registers; moving a task
+//! between two siblings writes the midpoint of their positions (a fractional
+//! index), so concurrent moves of different tasks never disturb each other.
+//! Once a gap is too narrow to halve, the siblings are renumbered; that
+//! renumbering is an ordinary edit of each of them, so it can override
+//! concurrent moves in the same list.
+//!
+//! Merging two [`TaskState`]s is commutative, associative and idempotent:
+//! replicas that have seen the same states hold the same task, whatever the
+//! order they saw them in. Deleting a task leaves a tombstone (migration 18)
+//! and wins over every edit: a state merged into a replica that deleted the
+//! task is dropped, so the deletion only has to reach the others. Tasks merge one by one, so the tree is not
+//! protected: two tasks moved under each other at once end up in a loop,
+//! which has to be untangled by moving one of them again.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::error::{Error, Result};
+use crate::storage::hierarchy::{spread_siblings, SORT_STEP};
+use crate::storage::models::Task;
+use crate::storage::{now_iso, read_task, search, write_task, Store};
+
+/// The task columns that merge as registers. `id` names the task and
+/// `user_id` is local to the database.
+pub const FIELDS: [&str; 18] = [
+  "title",
+  "completed",
+  "date",
+  "project",
+  "description",
+  "icon",
+  "completed_at",
+  "created_at",
+  "updated_at",
+  "sort_order",
+  "deleted",
+  "deleted_at",
+  "abandoned",
+  "abandoned_at",
+  "flagged",
+  "attachments",
+  "parent_id",
+  "estimate_minutes",
+];
+
+/// When and where a value was written. Ordered by clock, then replica id;
+/// the default stamp, for values unchanged since creation, is the oldest.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Stamp {
+  pub at: i64,
+  pub replica: String,
+}
+
+/// Last-writer-wins register.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Lww {
+  pub value: Value,
+  pub stamp: Stamp,
+}
+
+impl Lww {
+  /// Keep the later write. Equal stamps only meet when two replicas wrote
+  /// without a clock (before migration 15); the larger value wins those so
+  /// both still agree.
+  pub fn merge(&mut self, other: &Lww) {
+    let order = self
+      .stamp
+      .cmp(&other.stamp)
+      .then_with(|| self.value.to_string().cmp(&other.value.to_string()));
+    if order == Ordering::Less {
+      *self = other.clone();
+    }
+  }
+}
+
+/// One addition of `element` to an [`OrSet`], tombstoned once removed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dot {
+  pub element: String,
+  pub stamp: Stamp,
+  pub removed: bool,
+}
+
+/// Observed-remove set: an element is in the set while any of its additions
+/// is not tombstoned, so a concurrent add survives a remove.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrSet {
+  /// Sorted by element and stamp, one entry per addition.
+  dots: Vec<Dot>,
+}
+
+impl OrSet {
+  pub fn from_dots(dots: impl IntoIterator<Item = Dot>) -> Self {
+    let mut set = OrSet::default();
+    for dot in dots {
+      set.insert(dot);
+    }
+    set
+  }
+
+  fn insert(&mut self, dot: Dot) {
+    let key = |d: &Dot| (d.element.clone(), d.stamp.clone());
+    match self.dots.binary_search_by_key(&key(&dot), key) {
+      Ok(i) => self.dots[i].removed |= dot.removed,
+      Err(i) => self.dots.insert(i, dot),
+    }
+  }
+
+  pub fn add(&mut self, element: &str, stamp: Stamp) {
+    self.insert(Dot {
+      element: element.to_string(),
+      stamp,
+      removed: false,
+    });
+  }
+
+  /// Tombstone the additions of `element` seen so far.
+  pub fn remove(&mut self, element: &str) {
+    for dot in self.dots.iter_mut().filter(|d| d.element == element) {
+      dot.removed = true;
+    }
+  }
+
+  pub fn contains(&self, element: &str) -> bool {
+    self.dots.iter().any(|d| d.element == element && !d.removed)
+  }
+
+  pub fn elements(&self) -> BTreeSet<&str> {
+    self
+      .dots
+      .iter()
+      .filter(|d| !d.removed)
+      .map(|d| d.element.as_str())
+      .collect()
+  }
+
+  pub fn dots(&self) -> &[Dot] {
+    &self.dots
+  }
+
+  pub fn merge(&mut self, other: &OrSet) {
+    for dot in &other.dots {
+      self.insert(dot.clone());
+    }
+  }
+}
+
+/// Position between two neighbours, either of which may be missing (the
+/// start or end of the list). Ties between tasks moved to the same spot at
+/// once are broken by creation time, as everywhere else.
+pub fn rank_between(before: Option<f64>, after: Option<f64>) -> f64 {
+  match (before, after) {
+    (None, None) => 0.0,
+    (Some(before), None) => before + SORT_STEP,
+    (None, Some(after)) => after - SORT_STEP,
+    (Some(before), Some(after)) => before + (after - before) / 2.0,
+  }
+}
+
+/// [`rank_between`] the neighbours if it lands strictly between them;
+/// `None` once their gap has run out of precision (some fifty halvings of
+/// `SORT_STEP`) or when a neighbour has no position at all.
+fn fitting_rank(before: Option<&Task>, after: Option<&Task>) -> Option<f64> {
+  let position = |task: Option<&Task>| match task {
+    Some(task) => task.sort_order.map(Some),
+    None => Some(None),
+  };
+  let (before, after) = (position(before)?, position(after)?);
+  let rank = rank_between(before, after);
+  let fits = before.map_or(true, |b| b < rank) && after.map_or(true, |a| rank < a);
+  fits.then_some(rank)
+}
+
+/// Everything a replica knows about a task, enough to merge it anywhere.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskState {
+  pub id: String,
+  pub fields: BTreeMap<String, Lww>,
+  /// Tag ids.
+  pub tags: OrSet,
+}
+
+impl TaskState {
+  pub fn merge(&mut self, other: &TaskState) {
+    for (field, register) in &other.fields {
+      match self.fields.get_mut(field) {
+        Some(mine) => mine.merge(register),
+        None => {
+          self.fields.insert(field.clone(), register.clone());
+        }
+      }
+    }
+    self.tags.merge(&other.tags);
+  }
+
+  /// Latest clock reading in the state.
+  fn latest(&self) -> i64 {
+    let fields = self.fields.values().map(|r| r.stamp.at);
+    let dots = self.tags.dots.iter().map(|d| d.stamp.at);
+    fields.chain(dots).max().unwrap_or(0)
+  }
+}
+
+pub(crate) fn read_state(conn: &Connection, id: &str) -> Result<Option<TaskState>> {
+  let Some(task) = read_task(conn, id)? else {
+    return Ok(None);
+  };
+  let Value::Object(values) = serde_json::to_value(&task)? else {
+    unreachable!("a task serializes to an object");
+  };
+  let mut stamps: BTreeMap<String, Stamp> = {
+    let mut stmt =
+      conn.prepare("select field, at, replica from task_field_stamps where task_id = ?1")?;
+    let rows = stmt
+      .query_map([id], |row| {
+        Ok((
+          row.get(0)?,
+          Stamp {
+            at: row.get(1)?,
+            replica: row.get(2)?,
+          },
+        ))
+      })?
+      .collect::<rusqlite::Result<_>>()?;
+    rows
+  };
+  let fields = FIELDS
+    .iter()
+    .map(|field| {
+      let register = Lww {
+        value: values.get(*field).cloned().unwrap_or(Value::Null),
+        stamp: stamps.remove(*field).unwrap_or_default(),
+      };
+      (field.to_string(), register)
+    })
+    .collect();
+  let mut stmt =
+    conn.prepare("select tag_id, at, replica, removed from task_tag_dots where task_id = ?1")?;
+  let dots = stmt
+    .query_map([id], |row| {
+      Ok(Dot {
+        element: row.get(0)?,
+        stamp: Stamp {
+          at: row.get(1)?,
+          replica: row.get(2)?,
+        },
+        removed: row.get(3)?,
+      })
+    })?
+    .collect::<rusqlite::Result<Vec<_>>>()?;
+  Ok(Some(TaskState {
+    id: id.to_string(),
+    fields,
+    tags: OrSet::from_dots(dots),
+  }))
+}
+
+/// Write a merged state over the local task, its links and its clocks.
+fn write_state(conn: &Connection, state: &TaskState, local: Option<&Task>) -> Result<Task> {
+  let mut values = match local {
+    Some(task) => match serde_json::to_value(task)? {
+      Value::Object(values) => values,
+      _ => unreachable!("a task serializes to an object"),
+    },
+    None => Map::new(),
+  };
+  values.insert("id".into(), state.id.clone().into());
+  for (field, register) in &state.fields {
+    if FIELDS.contains(&field.as_str()) {
+      values.insert(field.clone(), register.value.clone());
+    }
+  }
+  let task: Task = serde_json::from_value(Value::Object(values))
+    .map_err(|e| Error::InvalidInput(format!("task {}: {e}", state.id)))?;
+  write_task(conn, &task)?;
+
+  for (field, register) in &state.fields {
+    conn.execute(
+      "insert or replace into task_field_stamps (task_id, field, at, replica)
+       values (?1, ?2, ?3, ?4)",
+      params![state.id, field, register.stamp.at, register.stamp.replica],
+    )?;
+  }
+  for dot in state.tags.dots() {
+    conn.execute(
+      "insert into task_tag_dots (task_id, tag_id, at, replica, removed)
+       values (?1, ?2, ?3, ?4, ?5)
+       on conflict (task_id, tag_id, at, replica) do update set removed = max(removed, excluded.removed)",
+      params![state.id, dot.element, dot.stamp.at, dot.stamp.replica, dot.removed],
+    )?;
+  }
+  let tags: BTreeSet<&str> = state
+    .tags
+    .dots()
+    .iter()
+    .map(|d| d.element.as_str())
+    .collect();
+  for tag_id in tags {
+    if state.tags.contains(tag_id) {
+      // A tag this database does not have (yet) stays in the set only.
+      conn.execute(
+        "insert or ignore into task_tags (task_id, tag_id, created_at)
+         select ?1, id, ?3 from tags where id = ?2",
+        params![state.id, tag_id, task.updated_at],
+      )?;
+    } else {
+      conn.execute(
+        "delete from task_tags where task_id = ?1 and tag_id = ?2",
+        params![state.id, tag_id],
+      )?;
+    }
+  }
+  search::reindex_task(conn, &state.id)?;
+  Ok(task)
+}
+
+/// Whether the task was deleted here.
+pub(crate) fn tombstoned(conn: &Connection, id: &str) -> Result<bool> {
+  Ok(
+    conn
+      .query_row(
+        "select 1 from task_tombstones where task_id = ?1",
+        [id],
+        |_| Ok(()),
+      )
+      .optional()?
+      .is_some(),
+  )
+}
+
+/// Merge `remote` into the local task. Returns the merged task and whether
+/// anything changed here; a merge that changes nothing writes nothing, so
+/// an echoed state does not count as a new change. `None` if the task was
+/// deleted here, which it stays.
+pub(crate) fn merge_state(conn: &Connection, remote: &TaskState) -> Result<Option<(Task, bool)>> {
+  if tombstoned(conn, &remote.id)? {
+    return Ok(None);
+  }
+  conn.execute("update crdt_clock set at = max(at, ?1)", [remote.latest()])?;
+  let local = read_task(conn, &remote.id)?;
+  let (merged, before) = match read_state(conn, &remote.id)? {
+    Some(state) => {
+      let mut merged = state.clone();
+      merged.merge(remote);
+      (merged, Some(state))
+    }
+    None => (remote.clone(), None),
+  };
+  if let (Some(task), Some(before)) = (&local, before) {
+    if before == merged {
+      return Ok(Some((task.clone(), false)));
+    }
+  }
+  conn.execute("update crdt_clock set merging = 1", [])?;
+  let task = write_state(conn, &merged, local.as_ref())?;
+  conn.execute("update crdt_clock set merging = 0", [])?;
+  Ok(Some((task, true)))
+}
+
+impl Store {
+  /// This database's replica id, stable for its lifetime.
+  pub fn replica_id(&self) -> Result<String> {
+    Ok(
+      self
+        .conn()
+        .query_row("select replica from crdt_clock", [], |row| row.get(0))?,
+    )
+  }
+
+  /// The mergeable state of a task, `None` if it does not exist here.
+  pub fn task_state(&self, id: &str) -> Result<Option<TaskState>> {
+    read_state(&self.conn(), id)
+  }
+
+  /// Merge a task's state from another replica into this one; the task
+  /// is created if it does not exist here. Returns the merged task, `None`
+  /// if it was deleted here.
+  pub fn merge_task_state(&self, remote: &TaskState) -> Result<Option<Task>> {
+    self.transaction(|tx| Ok(merge_state(tx, remote)?.map(|(task, _)| task)))
+  }
+
+  /// Place a task between two of its siblings (`None` for either end of
+  /// the list) by giving it the midpoint of their `sort_order`s. When the
+  /// midpoint no longer fits between them, the siblings are spread out
+  /// again first. Returns the tasks that changed, the placed one first;
+  /// empty if it does not exist.
+  pub fn place_task_between(
+    &self,
+    id: &str,
+    before: Option<&str>,
+    after: Option<&str>,
+  ) -> Result<Vec<Task>> {
+    self.transaction(|tx| {
+      let Some(mut task) = read_task(tx, id)? else {
+        return Ok(Vec::new());
+      };
+      let neighbour = |id: Option<&str>| -> Result<Option<Task>> {
+        match id {
+          Some(id) => Ok(Some(
+            read_task(tx, id)?.ok_or_else(|| Error::NotFound("task", id.to_string()))?,
+          )),
+          None => Ok(None),
+        }
+      };
+      let mut changed = Vec::new();
+      let rank = match fitting_rank(neighbour(before)?.as_ref(), neighbour(after)?.as_ref()) {
+        Some(rank) => rank,
+        None => {
+          changed = spread_siblings(tx, &task)?;
+          rank_between(
+            neighbour(before)?.and_then(|t| t.sort_order),
+            neighbour(after)?.and_then(|t| t.sort_order),
+          )
+        }
+      };
+      task.sort_order = Some(rank);
+      task.updated_at = Some(now_iso());
+      write_task(tx, &task)?;
+      changed.insert(0, task);
+      Ok(changed)
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use proptest::prelude::*;
+  use serde_json::json;
+
+  const REPLICAS: usize = 3;
+  const TAGS: [&str; 3] = ["tag-a", "tag-b", "tag-c"];
+
+  fn stamp(at: i64, replica: &str) -> Stamp {
+    Stamp {
+      at,
+      replica: replica.into(),
+    }
+  }
+
+  #[test]
+  fn a_concurrent_add_survives_a_remove() {
+    let mut here = OrSet::default();
+    here.add("urgent", stamp(1, "a"));
+    let mut there = here.clone();
+    there.remove("urgent");
+    here.add("urgent", stamp(2, "a"));
+
+    let mut merged = here.clone();
+    merged.merge(&there);
+    assert!(merged.contains("urgent"));
+    there.merge(&here);
+    assert_eq!(there, merged);
+
+    // Removing after seeing both additions removes it for good.
+    there.remove("urgent");
+    merged.merge(&there);
+    assert!(merged.elements().is_empty());
+  }
+
+  #[test]
+  fn ranks_fall_between_their_neighbours() {
+    assert_eq!(rank_between(None, None), 0.0);
+    assert_eq!(rank_between(Some(1000.0), None), 2000.0);
+    assert_eq!(rank_between(None, Some(-1000.0)), -2000.0);
+    let mut after = 1.0;
+    for _ in 0..40 {
+      let rank = rank_between(Some(0.0), Some(after));
+      assert!(0.0 < rank && rank < after);
+      after = rank;
+    }
+  }
+
+  #[test]
+  fn siblings_are_spread_out_once_a_gap_runs_out() {
+    let store = Store::open_in_memory().unwrap();
+    let [first, second, third] = ["first", "second", "third"].map(|title| {
+      store
+        .create_task(Task {
+          title: title.into(),
+          ..Default::default()
+        })
+        .unwrap()
+        .id
+    });
+    let position = |id: &str| {
+      store
+        .get_task_by_id(id)
+        .unwrap()
+        .unwrap()
+        .sort_order
+        .unwrap()
+    };
+    let (mut moving, mut other) = (second, third);
+    let mut spread = 0;
+    for _ in 0..200 {
+      let changed = store
+        .place_task_between(&moving, Some(&first), Some(&other))
+        .unwrap();
+      assert_eq!(changed[0].id, moving);
+      spread += usize::from(changed.len() > 1);
+      assert!(position(&first) < position(&moving));
+      assert!(position(&moving) < position(&other));
+      std::mem::swap(&mut moving, &mut other);
+    }
+    assert!(spread > 0);
+  }
+
+  #[test]
+  fn concurrent_edits_to_different_fields_both_survive() {
+    let (stores, ids) = replicas();
+    let task = &ids[0];
+    stores[0]
+      .update_task(task, json!({ "title": "renamed" }).as_object().unwrap())
+      .unwrap();
+    stores[1]
+      .update_task(task, json!({ "completed": true }).as_object().unwrap())
+      .unwrap();
+    stores[1].attach_tag_to_task(task, TAGS[0]).unwrap();
+    merge(&stores[0], &stores[1], &ids);
+    merge(&stores[1], &stores[0], &ids);
+
+    for store in &stores[..2] {
+      let merged = store.get_task_by_id(task).unwrap().unwrap();
+      assert_eq!(merged.title, "renamed");
+      assert!(merged.completed);
+      assert_eq!(linked_tags(store, task), [TAGS[0]]);
+    }
+  }
+
+  #[test]
+  fn a_deleted_task_stays_deleted_whatever_was_edited_elsewhere() {
+    let (stores, ids) = replicas();
+    let task = &ids[0];
+    stores[1]
+      .update_task(task, json!({ "title": "edited" }).as_object().unwrap())
+      .unwrap();
+    stores[0].delete_task(task).unwrap();
+    // Edited after the delete, too, by the clock.
+    stores[1]
+      .update_task(task, json!({ "completed": true }).as_object().unwrap())
+      .unwrap();
+
+    let edited = stores[1].task_state(task).unwrap().unwrap();
+    assert_eq!(stores[0].merge_task_state(&edited).unwrap(), None);
+    assert!(stores[0].get_task_by_id(task).unwrap().is_none());
+    assert!(stores[0].task_state(task).unwrap().is_none());
+  }
+
+  fn register() -> impl Strategy<Value = Lww> {
+    (0..4u8, 0..4i64, 0..2usize).prop_map(|(value, at, replica)| Lww {
+      value: json!(value),
+      stamp: stamp(at, ["a", "b"][replica]),
+    })
+  }
+
+  fn state() -> impl Strategy<Value = TaskState> {
+    let fields = prop::collection::btree_map(prop::sample::select(&FIELDS[..4]), register(), 0..4);
+    let dots = prop::collection::vec(
+      (prop::sample::select(&TAGS[..]), 0..4i64, any::<bool>()),
+      0..6,
+    );
+    (fields, dots).prop_map(|(fields, dots)| TaskState {
+      id: "task".into(),
+      fields: fields
+        .into_iter()
+        .map(|(f, r)| (f.to_string(), r))
+        .collect(),
+      tags: OrSet::from_dots(dots.into_iter().map(|(tag, at, removed)| Dot {
+        element: tag.into(),
+        stamp: stamp(at, "a"),
+        removed,
+      })),
+    })
+  }
+
+  fn merged(a: &TaskState, b: &TaskState) -> TaskState {
+    let mut a = a.clone();
+    a.merge(b);
+    a
+  }
+
+  #[derive(Debug, Clone)]
+  enum Op {
+    Edit {
+      replica: usize,
+      field: usize,
+      value: u8,
+    },
+    Tag {
+      replica: usize,
+      tag: usize,
+      add: bool,
+    },
+    /// Move the task between the two siblings, or after the second one.
+    Move {
+      replica: usize,
+      between: bool,
+    },
+    Delete {
+      replica: usize,
+    },
+    Merge {
+      from: usize,
+      to: usize,
+    },
+  }
+
+  fn op() -> impl Strategy<Value = Op> {
+    let replica = 0..REPLICAS;
+    prop_oneof![
+      4 => (replica.clone(), 0..3usize, any::<u8>()).prop_map(|(replica, field, value)| Op::Edit {
+        replica,
+        field,
+        value
+      }),
+      4 => (replica.clone(), 0..TAGS.len(), any::<bool>()).prop_map(|(replica, tag, add)| Op::Tag {
+        replica,
+        tag,
+        add
+      }),
+      4 => (replica.clone(), any::<bool>()).prop_map(|(replica, between)| Op::Move { replica, between }),
+      // Rare, so most runs still have a task left to edit.
+      1 => replica.clone().prop_map(|replica| Op::Delete { replica }),
+      4 => (replica.clone(), replica).prop_map(|(from, to)| Op::Merge { from, to }),
+    ]
+  }
+
+  /// Three replicas sharing a task, its two siblings and the tags.
+  fn replicas() -> (Vec<Store>, [String; 3]) {
+    let stores: Vec<Store> = (0..REPLICAS)
+      .map(|_| Store::open_in_memory().unwrap())
+      .collect();
+    let ids = ["task", "first", "second"].map(|title| {
+      stores[0]
+        .create_task(Task {
+          title: title.into(),
+          ..Default::default()
+        })
+        .unwrap()
+        .id
+    });
+    for store in &stores {
+      for tag in TAGS {
+        store
+          .conn()
+          .execute("insert into tags (id, name) values (?1, ?1)", [tag])
+          .unwrap();
+      }
+    }
+    for store in &stores[1..] {
+      merge(&stores[0], store, &ids);
+    }
+    (stores, ids)
+  }
+
+  /// Send every task over, or its deletion, as peers do.
+  fn merge(from: &Store, to: &Store, ids: &[String]) {
+    for id in ids {
+      match from.task_state(id).unwrap() {
+        Some(state) => {
+          to.merge_task_state(&state).unwrap();
+        }
+        None => {
+          to.delete_task(id).unwrap();
+        }
+      }
+    }
+  }
+
+  fn apply(stores: &[Store], ids: &[String; 3], op: &Op) {
+    let task = &ids[0];
+    let gone = |replica: usize| stores[replica].get_task_by_id(task).unwrap().is_none();
+    match *op {
+      Op::Edit { replica, .. } | Op::Tag { replica, .. } | Op::Move { replica, .. }
+        if gone(replica) => {}
+      Op::Edit {
+        replica,
+        field,
+        value,
+      } => {
+        let patch = match field {
+          0 => json!({ "title": format!("title {value}") }),
+          1 => json!({ "completed": value % 2 == 0 }),
+          _ => json!({ "description": format!("note {value}") }),
+        };
+        stores[replica]
+          .update_task(task, patch.as_object().unwrap())
+          .unwrap();
+      }
+      Op::Tag { replica, tag, add } => {
+        if add {
+          stores[replica].attach_tag_to_task(task, TAGS[tag]).unwrap();
+        } else {
+          stores[replica]
+            .detach_tag_from_task(task, TAGS[tag])
+            .unwrap();
+        }
+      }
+      Op::Move { replica, between } => {
+        let (before, after) = if between {
+          (Some(ids[1].as_str()), Some(ids[2].as_str()))
+        } else {
+          (Some(ids[2].as_str()), None)
+        };
+        stores[replica]
+          .place_task_between(task, before, after)
+          .unwrap();
+      }
+      Op::Delete { replica } => {
+        stores[replica].delete_task(task).unwrap();
+      }
+      Op::Merge { from, to } => merge(&stores[from], &stores[to], ids),
+    }
+  }
+
+  fn linked_tags(store: &Store, task: &str) -> Vec<String> {
+    let conn = store.conn();
+    let mut stmt = conn
+      .prepare("select tag_id from task_tags where task_id = ?1 order by tag_id")
+      .unwrap();
+    let tags = stmt
+      .query_map([task], |row| row.get(0))
+      .unwrap()
+      .collect::<rusqlite::Result<_>>()
+      .unwrap();
+    tags
+  }
+
+  proptest! {
+    #![proptest_config(ProptestConfig::with_cases(48))]
+
+    #[test]
+    fn merge_is_commutative_associative_and_idempotent(a in state(), b in state(), c in state()) {
+      prop_assert_eq!(merged(&a, &b), merged(&b, &a));
+      prop_assert_eq!(merged(&merged(&a, &b), &c), merged(&a, &merged(&b, &c)));
+      prop_assert_eq!(merged(&a, &a), a.clone());
+      prop_assert_eq!(merged(&merged(&a, &b), &b), merged(&a, &b));
+    }
+
+    #[test]
+    fn replicas_converge_under_any_interleaving(ops in prop::collection::vec(op(), 1..30)) {
+      let (stores, ids) = replicas();
+      for op in &ops {
+        apply(&stores, &ids, op);
+      }
+      let task = &ids[0];
+      let deleted = ops.iter().any(|op| matches!(op, Op::Delete { .. }));
+      let mut join: Option<TaskState> = None;
+      for state in stores.iter().filter_map(|store| store.task_state(task).unwrap()) {
+        match &mut join {
+          Some(join) => join.merge(&state),
+          None => join = Some(state),
+        }
+      }
+      for _ in 0..2 {
+        for from in 0..REPLICAS {
+          for to in (0..REPLICAS).filter(|to| *to != from) {
+            merge(&stores[from], &stores[to], &ids);
+          }
+        }
+      }
+
+      if deleted {
+        for store in &stores {
+          prop_assert!(store.get_task_by_id(task).unwrap().is_none());
+        }
+        return Ok(());
+      }
+      let expected = stores[0].task_state(task).unwrap().unwrap();
+      prop_assert_eq!(Some(&expected), join.as_ref());
+      let expected_task = stores[0].get_task_by_id(task).unwrap().unwrap();
+      let expected_tags: Vec<String> = expected.tags.elements().into_iter().map(String::from).collect();
+      for store in &stores {
+        prop_assert_eq!(&store.task_state(task).unwrap().unwrap(), &expected);
+        prop_assert_eq!(&store.get_task_by_id(task).unwrap().unwrap(), &expected_task);
+        prop_assert_eq!(&linked_tags(store, task), &expected_tags);
+      }
+    }
+  }
+}
@@ -0,0 +1,111 @@
+//! `snailtodo://` links.
+//!
+//! - `snailtodo://auth-callback?...#...`: OAuth redirect; query and fragment
+//!   parameters (`code`, `access_token`, `refresh_token`, ...) are passed on
+//!   as-is for the webview to finish the Supabase sign-in.
+//! - `snailtodo://task/<id>` and `snailtodo://project/<id>`: open an item.
+//! - `snailtodo://join/<share-code>`: the desktop twin of `/join/:code`.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use url::{form_urlencoded, Url};
+
+use crate::error::{Error, Result};
+
+pub const SCHEME: &str = "snailtodo";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum DeepLink {
+  AuthCallback { params: BTreeMap<String, String> },
+  Task { id: String },
+  Project { id: String },
+  Join { code: String },
+}
+
+fn invalid(link: &str, why: &str) -> Error {
+  Error::InvalidInput(format!("{why}: {link}"))
+}
+
+pub fn parse(link: &str) -> Result<DeepLink> {
+  let url = Url::parse(link).map_err(|_| invalid(link, "malformed link"))?;
+  if url.scheme() != SCHEME {
+    return Err(invalid(link, "unsupported scheme"));
+  }
+  let host = url.host_str().unwrap_or_default();
+
+  if host == "auth-callback" {
+    let mut params: BTreeMap<String, String> = url.query_pairs().into_owned().collect();
+    if let Some(fragment) = url.fragment() {
+      params.extend(form_urlencoded::parse(fragment.as_bytes()).into_owned());
+    }
+    return Ok(DeepLink::AuthCallback { params });
+  }
+
+  let segments: Vec<&str> = url
+    .path_segments()
+    .map(|s| s.filter(|s| !s.is_empty()).collect())
+    .unwrap_or_default();
+  let [target] = segments[..] else {
+    return Err(invalid(link, "expected exactly one path segment"));
+  };
+  let target = target.to_string();
+  match host {
+    "task" => Ok(DeepLink::Task { id: target }),
+    "project" => Ok(DeepLink::Project { id: target }),
+    "join" => Ok(DeepLink::Join { code: target }),
+    _ => Err(invalid(link, "unknown link")),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn auth_callback_merges_query_and_fragment() {
+    let link = parse(
+      "snailtodo://auth-callback?state=xyz#access_token=a.b.c&refresh_token=r1&token_type=bearer",
+    )
+    .unwrap();
+    let DeepLink::AuthCallback { params } = link else {
+      panic!("expected auth callback");
+    };
+    assert_eq!(params["access_token"], "a.b.c");
+    assert_eq!(params["refresh_token"], "r1");
+    assert_eq!(params["state"], "xyz");
+  }
+
+  #[test]
+  fn item_links() {
+    assert_eq!(
+      parse("snailtodo://task/6f1c").unwrap(),
+      DeepLink::Task { id: "6f1c".into() }
+    );
+    assert_eq!(
+      parse("snailtodo://project/p1/").unwrap(),
+      DeepLink::Project { id: "p1".into() }
+    );
+    assert_eq!(
+      parse("snailtodo://join/AB12CD").unwrap(),
+      DeepLink::Join {
+        code: "AB12CD".into()
+      }
+    );
+  }
+
+  #[test]
+  fn rejects_other_links() {
+    assert!(parse("https://task/1").is_err());
+    assert!(parse("snailtodo://task").is_err());
+    assert!(parse("snailtodo://task/1/2").is_err());
+    assert!(parse("snailtodo://settings/x").is_err());
+  }
+
+  #[test]
+  fn serializes_with_a_type_tag() {
+    let json = serde_json::to_value(DeepLink::Join { code: "X".into() }).unwrap();
+    assert_eq!(json, serde_json::json!({ "type": "join", "code": "X" }));
+  }
+}
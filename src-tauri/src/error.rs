@@ -0,0 +1,39 @@
+use serde::{Serialize, Serializer};
+
+/// Errors surfaced by the native backend.
+///
+/// Serialized as a plain message so `invoke()` rejects with a string on the
+/// webview side, the same shape the TypeScript adapters throw.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+  #[error(transparent)]
+  Sqlite(#[from] rusqlite::Error),
+  #[error(transparent)]
+  Json(#[from] serde_json::Error),
+  #[error(transparent)]
+  Io(#[from] std::io::Error),
+  #[error("{0} not found: {1}")]
+  NotFound(&'static str, String),
+  #[error("invalid input: {0}")]
+  InvalidInput(String),
+  #[error("background task failed: {0}")]
+  Background(String),
+  #[error("migration {0} failed: {1}")]
+  Migration(i64, String),
+  #[error("webhook delivery failed: {0}")]
+  Webhook(String),
+  #[error("sync failed: {0}")]
+  Sync(String),
+  #[error("peer sync failed: {0}")]
+  Peer(String),
+  #[error("database schema version {found} is newer than this app supports ({supported}); please update Snail TodoList")]
+  SchemaTooNew { found: i64, supported: i64 },
+}
+
+impl Serialize for Error {
+  fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_str(&self.to_string())
+  }
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -0,0 +1,521 @@
+//! Read-only calendar feed on localhost, or the local network.
+//!
+//! When enabled, a small HTTP server on `127.0.0.1` publishes tasks with a
+//! due date as an `.ics` calendar that Thunderbird, GNOME Calendar and
+//! similar clients can subscribe to:
+//!
+//! `webcal://127.0.0.1:<port>/calendar.ics?token=<secret>[&project=..][&tag=..][&type=event]`
+//!
+//! Only clients on this machine can reach it unless [`FeedSettings::lan`]
+//! is set, for phones and other devices. It speaks plain HTTP either way,
+//! so on the network the tokens and tasks travel unencrypted; the settings
+//! card says so where the option is turned on.
+//!
+//! Every subscribed client gets its own feed row with a secret token, so
+//! one can be revoked without touching the others. `project` and `tag`
+//! take an id or a name; `type=event` publishes deadlines as events for
+//! calendars that ignore to-dos. Unknown tokens get the same 404 as unknown
+//! paths.
+
+use std::net::{IpAddr, Ipv4Addr, UdpSocket};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use rusqlite::{params, Row};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::{Error, Result};
+use crate::ical::tasks::IcsExportOptions;
+use crate::storage::models::UserSettings;
+use crate::storage::{new_id, now_iso, Store};
+
+pub const SETTINGS_KEY: &str = "calendar_feed";
+pub const DEFAULT_PORT: u16 = 47_613;
+pub const FEED_PATH: &str = "/calendar.ics";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FeedSettings {
+  pub enabled: bool,
+  pub port: u16,
+  /// Listen on every interface rather than only `127.0.0.1`.
+  pub lan: bool,
+}
+
+impl Default for FeedSettings {
+  fn default() -> Self {
+    Self {
+      enabled: false,
+      port: DEFAULT_PORT,
+      lan: false,
+    }
+  }
+}
+
+impl FeedSettings {
+  pub fn from_settings(settings: &UserSettings) -> Self {
+    settings
+      .0
+      .get(SETTINGS_KEY)
+      .and_then(|v| serde_json::from_value(v.clone()).ok())
+      .unwrap_or_default()
+  }
+
+  /// Where the server listens.
+  pub fn address(&self) -> IpAddr {
+    if self.lan {
+      Ipv4Addr::UNSPECIFIED.into()
+    } else {
+      Ipv4Addr::LOCALHOST.into()
+    }
+  }
+
+  /// Host for the URLs handed to clients: this machine's address on the
+  /// network when listening there and it has one, else `127.0.0.1`.
+  pub fn host(&self) -> IpAddr {
+    let lan = || {
+      // Connecting a UDP socket sends nothing; it only picks the route.
+      let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+      socket.connect((Ipv4Addr::new(192, 0, 2, 1), 9)).ok()?;
+      Some(socket.local_addr().ok()?.ip())
+    };
+    self
+      .lan
+      .then(lan)
+      .flatten()
+      .unwrap_or(Ipv4Addr::LOCALHOST.into())
+  }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CalendarFeed {
+  pub id: String,
+  /// Which client the feed is for, e.g. "Thunderbird".
+  pub name: String,
+  pub token: String,
+  pub created_at: String,
+  pub last_fetched_at: Option<String>,
+}
+
+/// Subscription URL of a feed, without filters.
+pub fn feed_url(host: IpAddr, port: u16, token: &str) -> String {
+  format!("webcal://{host}:{port}{FEED_PATH}?token={token}")
+}
+
+fn feed_from_row(row: &Row<'_>) -> rusqlite::Result<CalendarFeed> {
+  Ok(CalendarFeed {
+    id: row.get("id")?,
+    name: row.get("name")?,
+    token: row.get("token")?,
+    created_at: row.get("created_at")?,
+    last_fetched_at: row.get("last_fetched_at")?,
+  })
+}
+
+/// Compare without an early exit, so response times do not leak how much
+/// of a guessed token was right.
+fn same_token(a: &str, b: &str) -> bool {
+  a.len() == b.len()
+    && a
+      .bytes()
+      .zip(b.bytes())
+      .fold(0, |acc, (x, y)| acc | (x ^ y))
+      == 0
+}
+
+impl Store {
+  pub fn feed_settings(&self) -> Result<FeedSettings> {
+    Ok(FeedSettings::from_settings(&self.get_user_settings()?))
+  }
+
+  pub fn save_feed_settings(&self, settings: FeedSettings) -> Result<FeedSettings> {
+    if settings.port < 1024 {
+      return Err(Error::InvalidInput(format!(
+        "port {} is reserved; use 1024 or above",
+        settings.port
+      )));
+    }
+    let mut map = serde_json::Map::new();
+    map.insert(SETTINGS_KEY.into(), serde_json::to_value(settings)?);
+    self.save_user_settings(UserSettings(map))?;
+    Ok(settings)
+  }
+
+  pub fn calendar_feeds(&self) -> Result<Vec<CalendarFeed>> {
+    let conn = self.conn();
+    let mut stmt = conn.prepare("select * from calendar_feeds order by created_at")?;
+    let feeds = stmt
+      .query_map([], feed_from_row)?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(feeds)
+  }
+
+  /// A new feed with a fresh random token.
+  pub fn create_calendar_feed(&self, name: &str) -> Result<CalendarFeed> {
+    let feed = CalendarFeed {
+      id: new_id(),
+      name: name.trim().to_string(),
+      token: uuid::Uuid::new_v4().simple().to_string(),
+      created_at: now_iso(),
+      last_fetched_at: None,
+    };
+    self.conn().execute(
+      "insert into calendar_feeds (id, name, token, created_at) values (?1, ?2, ?3, ?4)",
+      params![feed.id, feed.name, feed.token, feed.created_at],
+    )?;
+    Ok(feed)
+  }
+
+  pub fn delete_calendar_feed(&self, id: &str) -> Result<bool> {
+    let removed = self
+      .conn()
+      .execute("delete from calendar_feeds where id = ?1", [id])?;
+    Ok(removed > 0)
+  }
+
+  /// The feed `token` belongs to, marked as fetched now.
+  pub(crate) fn fetch_calendar_feed(&self, token: &str) -> Result<Option<CalendarFeed>> {
+    let Some(feed) = self
+      .calendar_feeds()?
+      .into_iter()
+      .find(|f| same_token(&f.token, token))
+    else {
+      return Ok(None);
+    };
+    self.conn().execute(
+      "update calendar_feeds set last_fetched_at = ?1 where id = ?2",
+      params![now_iso(), feed.id],
+    )?;
+    Ok(Some(feed))
+  }
+
+  /// Project or tag id from an id or a case-insensitive name.
+  fn feed_filter(&self, kind: &'static str, value: &str) -> Result<String> {
+    let candidates: Vec<(String, String)> = if kind == "project" {
+      self
+        .get_projects()?
+        .into_iter()
+        .map(|p| (p.id, p.name))
+        .collect()
+    } else {
+      self
+        .get_tags(None)?
+        .into_iter()
+        .map(|t| (t.id, t.name))
+        .collect()
+    };
+    candidates
+      .iter()
+      .find(|(id, _)| id == value)
+      .or_else(|| {
+        candidates
+          .iter()
+          .find(|(_, name)| name.eq_ignore_ascii_case(value.trim()))
+      })
+      .map(|(id, _)| id.clone())
+      .ok_or_else(|| Error::NotFound(kind, value.to_string()))
+  }
+}
+
+/// What the server hands to a handler.
+#[derive(Debug, Clone, Default)]
+pub struct HttpRequest {
+  pub method: String,
+  /// Path and query, e.g. `/calendar.ics?token=..`.
+  pub url: String,
+  /// Header names lower-cased.
+  pub headers: Vec<(String, String)>,
+  pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+  pub fn header(&self, name: &str) -> Option<&str> {
+    self
+      .headers
+      .iter()
+      .find(|(n, _)| n.eq_ignore_ascii_case(name))
+      .map(|(_, v)| v.as_str())
+  }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HttpResponse {
+  pub status: u16,
+  pub headers: Vec<(String, String)>,
+  pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+  pub fn new(status: u16, content_type: &str, body: impl Into<Vec<u8>>) -> Self {
+    Self {
+      status,
+      headers: vec![("Content-Type".into(), content_type.into())],
+      body: body.into(),
+    }
+  }
+
+  pub fn text(status: u16, body: &str) -> Self {
+    Self::new(status, "text/plain; charset=utf-8", body)
+  }
+
+  pub fn header(mut self, name: &str, value: impl Into<String>) -> Self {
+    self.headers.push((name.to_string(), value.into()));
+    self
+  }
+}
+
+/// Quoted hash of a response body, for `ETag` and `If-None-Match`.
+pub fn etag(body: &[u8]) -> String {
+  format!("\"{}\"", &hex::encode(Sha256::digest(body))[..32])
+}
+
+/// Answer a feed request.
+pub fn respond(store: &Store, request: &HttpRequest) -> HttpResponse {
+  let Ok(url) = url::Url::parse("http://127.0.0.1").and_then(|base| base.join(&request.url)) else {
+    return HttpResponse::text(400, "bad request");
+  };
+  if url.path() != FEED_PATH {
+    return HttpResponse::text(404, "not found");
+  }
+  if request.method != "GET" && request.method != "HEAD" {
+    return HttpResponse::text(405, "method not allowed").header("Allow", "GET, HEAD");
+  }
+  let query = |name: &str| {
+    url
+      .query_pairs()
+      .find(|(k, _)| k == name)
+      .map(|(_, v)| v.into_owned())
+  };
+  let result = (|| {
+    let Some(_) = store.fetch_calendar_feed(&query("token").unwrap_or_default())? else {
+      return Ok(None);
+    };
+    let options = IcsExportOptions {
+      project_id: query("project")
+        .map(|p| store.feed_filter("project", &p))
+        .transpose()?,
+      tag_id: query("tag")
+        .map(|t| store.feed_filter("tag", &t))
+        .transpose()?,
+      events: query("type").as_deref() == Some("event"),
+      due_only: true,
+    };
+    store.export_ics(&options).map(Some)
+  })();
+  let body = match result {
+    Ok(Some(body)) => body,
+    Ok(None) => return HttpResponse::text(404, "not found"),
+    Err(e @ Error::NotFound(..)) => return HttpResponse::text(400, &e.to_string()),
+    Err(e) => {
+      log::warn!("calendar feed failed: {e}");
+      return HttpResponse::text(500, "internal error");
+    }
+  };
+  let tag = etag(body.as_bytes());
+  if request.header("if-none-match") == Some(tag.as_str()) {
+    return HttpResponse::text(304, "").header("ETag", tag);
+  }
+  HttpResponse::new(200, "text/calendar; charset=utf-8", body)
+    .header("ETag", tag)
+    .header("Cache-Control", "no-cache")
+}
+
+/// A local HTTP server running `handler` on its own thread until dropped.
+pub struct LocalServer {
+  server: Arc<tiny_http::Server>,
+  thread: Option<JoinHandle<()>>,
+  port: u16,
+}
+
+impl LocalServer {
+  /// Listen on `address:port`; port 0 picks a free one.
+  pub fn start(
+    address: IpAddr,
+    port: u16,
+    handler: impl Fn(&HttpRequest) -> HttpResponse + Send + 'static,
+  ) -> Result<Self> {
+    let server = tiny_http::Server::http((address, port))
+      .map(Arc::new)
+      .map_err(|e| {
+        Error::Io(std::io::Error::other(format!(
+          "cannot listen on port {port}: {e}"
+        )))
+      })?;
+    let port = server
+      .server_addr()
+      .to_ip()
+      .map_or(port, |addr| addr.port());
+    let listener = server.clone();
+    let thread = thread::spawn(move || {
+      for mut request in listener.incoming_requests() {
+        let mut body = Vec::new();
+        if let Err(e) = request.as_reader().read_to_end(&mut body) {
+          log::warn!("failed to read request body: {e}");
+          continue;
+        }
+        let incoming = HttpRequest {
+          method: request.method().as_str().to_ascii_uppercase(),
+          url: request.url().to_string(),
+          headers: request
+            .headers()
+            .iter()
+            .map(|h| {
+              (
+                h.field.as_str().as_str().to_ascii_lowercase(),
+                h.value.to_string(),
+              )
+            })
+            .collect(),
+          body,
+        };
+        let response = handler(&incoming);
+        let head = incoming.method == "HEAD";
+        let length = response.body.len();
+        let mut reply =
+          tiny_http::Response::from_data(if head { Vec::new() } else { response.body })
+            .with_status_code(response.status);
+        for (name, value) in &response.headers {
+          if let Ok(header) = tiny_http::Header::from_bytes(name.as_bytes(), value.as_bytes()) {
+            reply.add_header(header);
+          }
+        }
+        if head {
+          if let Ok(header) = tiny_http::Header::from_bytes("Content-Length", length.to_string()) {
+            reply.add_header(header);
+          }
+        }
+        if let Err(e) = request.respond(reply) {
+          log::warn!("failed to answer {}: {e}", incoming.url);
+        }
+      }
+    });
+    Ok(Self {
+      server,
+      thread: Some(thread),
+      port,
+    })
+  }
+
+  pub fn port(&self) -> u16 {
+    self.port
+  }
+}
+
+impl Drop for LocalServer {
+  fn drop(&mut self) {
+    self.server.unblock();
+    if let Some(thread) = self.thread.take() {
+      let _ = thread.join();
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::storage::models::{Project, Task};
+
+  fn get(url: &str, etag: Option<&str>) -> (u16, String) {
+    let mut request = ureq::get(url);
+    if let Some(etag) = etag {
+      request = request.set("If-None-Match", etag);
+    }
+    match request.call() {
+      Ok(response) => (response.status(), response.into_string().unwrap()),
+      Err(ureq::Error::Status(status, response)) => (status, response.into_string().unwrap()),
+      Err(e) => panic!("{e}"),
+    }
+  }
+
+  #[test]
+  fn serves_filtered_feeds_to_token_holders_only() {
+    let store = Arc::new(Store::open_in_memory().unwrap());
+    let work = store
+      .create_project(Project {
+        name: "Work".into(),
+        ..Default::default()
+      })
+      .unwrap();
+    for (title, project, date) in [
+      (
+        "ship",
+        Some(work.id.clone()),
+        Some("2026-10-20T09:00:00.000Z"),
+      ),
+      ("groceries", None, Some("2026-10-21T09:00:00.000Z")),
+      ("someday", None, None),
+    ] {
+      store
+        .create_task(Task {
+          title: title.into(),
+          project,
+          date: date.map(Into::into),
+          ..Default::default()
+        })
+        .unwrap();
+    }
+    let feed = store.create_calendar_feed("Thunderbird").unwrap();
+    let shared = store.clone();
+    let settings = FeedSettings::default();
+    let server = LocalServer::start(settings.address(), 0, move |request| {
+      respond(&shared, request)
+    })
+    .unwrap();
+    let base = format!("http://127.0.0.1:{}{FEED_PATH}", server.port());
+    assert_eq!(
+      feed_url(settings.host(), server.port(), &feed.token).replacen("webcal", "http", 1),
+      format!("{base}?token={}", feed.token)
+    );
+
+    let (status, body) = get(&format!("{base}?token={}", feed.token), None);
+    assert_eq!(status, 200);
+    assert!(body.contains("SUMMARY:ship") && body.contains("SUMMARY:groceries"));
+    assert!(!body.contains("someday"));
+    assert!(store.calendar_feeds().unwrap()[0].last_fetched_at.is_some());
+
+    let (_, work_only) = get(
+      &format!("{base}?token={}&project=work&type=event", feed.token),
+      None,
+    );
+    assert!(work_only.contains("BEGIN:VEVENT") && !work_only.contains("groceries"));
+
+    let tag = etag(body.as_bytes());
+    assert_eq!(
+      get(&format!("{base}?token={}", feed.token), Some(&tag)).0,
+      304
+    );
+    assert_eq!(
+      get(&format!("{base}?token={}&tag=nope", feed.token), None).0,
+      400
+    );
+    assert_eq!(get(&format!("{base}?token=guess"), None).0, 404);
+    assert_eq!(
+      get(&format!("http://127.0.0.1:{}/other", server.port()), None).0,
+      404
+    );
+
+    store.delete_calendar_feed(&feed.id).unwrap();
+    assert_eq!(get(&format!("{base}?token={}", feed.token), None).0, 404);
+    assert!(store
+      .save_feed_settings(FeedSettings {
+        enabled: true,
+        port: 80,
+        lan: false,
+      })
+      .is_err());
+
+    let lan = FeedSettings {
+      enabled: true,
+      lan: true,
+      ..Default::default()
+    };
+    store.save_feed_settings(lan).unwrap();
+    assert_eq!(store.feed_settings().unwrap(), lan);
+    let open = LocalServer::start(lan.address(), 0, |_| HttpResponse::text(200, "ok")).unwrap();
+    assert_eq!(
+      get(&format!("http://127.0.0.1:{}/", open.port()), None),
+      (200, "ok".into())
+    );
+  }
+}
@@ -0,0 +1,255 @@
+//! Dependency graph between tasks.
+//!
+//! An edge `task -> blocker` reads "task is blocked by blocker". The graph
+//! only knows ids; whether a blocker is still open is the caller's to say,
+//! so the same graph answers for any snapshot of task states.
+
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt;
+
+/// A chain of "blocked by" links that returns to where it started.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle(pub Vec<String>);
+
+impl fmt::Display for Cycle {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.0.join(" -> "))
+  }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DependencyGraph {
+  blockers: HashMap<String, BTreeSet<String>>,
+  dependents: HashMap<String, BTreeSet<String>>,
+}
+
+impl DependencyGraph {
+  /// Build from `(task, blocker)` pairs that are already known to be valid.
+  pub fn from_edges<I, S>(edges: I) -> Self
+  where
+    I: IntoIterator<Item = (S, S)>,
+    S: Into<String>,
+  {
+    let mut graph = Self::default();
+    for (task, blocker) in edges {
+      graph.insert(task.into(), blocker.into());
+    }
+    graph
+  }
+
+  fn insert(&mut self, task: String, blocker: String) {
+    self
+      .dependents
+      .entry(blocker.clone())
+      .or_default()
+      .insert(task.clone());
+    self.blockers.entry(task).or_default().insert(blocker);
+  }
+
+  /// Add `task -> blocker`, unless it would close a cycle. A task blocking
+  /// itself is the shortest cycle.
+  pub fn add_edge(&mut self, task: &str, blocker: &str) -> Result<(), Cycle> {
+    if let Some(cycle) = self.cycle_with(task, blocker) {
+      return Err(cycle);
+    }
+    self.insert(task.to_string(), blocker.to_string());
+    Ok(())
+  }
+
+  pub fn remove_edge(&mut self, task: &str, blocker: &str) {
+    if let Some(set) = self.blockers.get_mut(task) {
+      set.remove(blocker);
+    }
+    if let Some(set) = self.dependents.get_mut(blocker) {
+      set.remove(task);
+    }
+  }
+
+  /// The cycle `task -> blocker` would close: `blocker` already waits on
+  /// `task`, directly or through other tasks.
+  pub fn cycle_with(&self, task: &str, blocker: &str) -> Option<Cycle> {
+    if task == blocker {
+      return Some(Cycle(vec![task.to_string(), task.to_string()]));
+    }
+    // Breadth first along "blocked by" links, so the reported cycle is the
+    // shortest one.
+    let mut came_from: HashMap<&str, &str> = HashMap::new();
+    let mut queue = VecDeque::from([blocker]);
+    while let Some(current) = queue.pop_front() {
+      for next in self.blockers_of(current) {
+        if came_from.contains_key(next) || next == blocker {
+          continue;
+        }
+        came_from.insert(next, current);
+        if next == task {
+          let mut path = vec![task.to_string()];
+          let mut at = task;
+          while at != blocker {
+            at = came_from[at];
+            path.push(at.to_string());
+          }
+          path.reverse();
+          path.insert(0, task.to_string());
+          return Some(Cycle(path));
+        }
+        queue.push_back(next);
+      }
+    }
+    None
+  }
+
+  pub fn blockers_of<'a>(&'a self, task: &str) -> impl Iterator<Item = &'a str> {
+    self
+      .blockers
+      .get(task)
+      .into_iter()
+      .flatten()
+      .map(String::as_str)
+  }
+
+  pub fn dependents_of<'a>(&'a self, task: &str) -> impl Iterator<Item = &'a str> {
+    self
+      .dependents
+      .get(task)
+      .into_iter()
+      .flatten()
+      .map(String::as_str)
+  }
+
+  /// Whether any of `task`'s blockers is still open.
+  pub fn is_blocked(&self, task: &str, is_open: impl Fn(&str) -> bool) -> bool {
+    self.blockers_of(task).any(is_open)
+  }
+
+  /// Open tasks that `finished` was the last open blocker of. Call it once
+  /// `finished` is no longer open.
+  pub fn unblocked_by(&self, finished: &str, is_open: impl Fn(&str) -> bool) -> Vec<String> {
+    self
+      .dependents_of(finished)
+      .filter(|task| is_open(task) && !self.is_blocked(task, &is_open))
+      .map(String::from)
+      .collect()
+  }
+
+  /// Layer `tasks` so that every task comes after its blockers: a task sits
+  /// one layer below the deepest of its blockers in `tasks`, and below the
+  /// first layer if an open blocker outside `tasks` holds it up. Within a
+  /// layer, tasks keep their order in `tasks`. Tasks caught in a cycle,
+  /// which [`DependencyGraph::add_edge`] never lets in, form a last layer.
+  pub fn layers(&self, tasks: &[String], is_open: impl Fn(&str) -> bool) -> Vec<Vec<String>> {
+    let members: HashSet<&str> = tasks.iter().map(String::as_str).collect();
+    let mut pending: HashMap<&str, usize> = HashMap::new();
+    let mut level: HashMap<&str, usize> = HashMap::new();
+    let mut queue = VecDeque::new();
+    for task in tasks {
+      let task = task.as_str();
+      let inside = self
+        .blockers_of(task)
+        .filter(|b| members.contains(b))
+        .count();
+      let outside = self
+        .blockers_of(task)
+        .any(|b| !members.contains(b) && is_open(b));
+      level.insert(task, usize::from(outside));
+      if inside == 0 {
+        queue.push_back(task);
+      } else {
+        pending.insert(task, inside);
+      }
+    }
+
+    let mut done = HashSet::new();
+    while let Some(task) = queue.pop_front() {
+      done.insert(task);
+      let below = level[task] + 1;
+      for dependent in self.dependents_of(task) {
+        let Some(count) = pending.get_mut(dependent) else {
+          continue;
+        };
+        let entry = level.entry(dependent).or_default();
+        *entry = (*entry).max(below);
+        *count -= 1;
+        if *count == 0 {
+          pending.remove(dependent);
+          queue.push_back(dependent);
+        }
+      }
+    }
+
+    let depth = level.values().copied().max().unwrap_or(0);
+    let mut layers = vec![Vec::new(); depth + 2];
+    for task in tasks {
+      let index = if done.contains(task.as_str()) {
+        level[task.as_str()]
+      } else {
+        depth + 1
+      };
+      layers[index].push(task.clone());
+    }
+    layers.retain(|layer| !layer.is_empty());
+    layers
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn ids(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+  }
+
+  #[test]
+  fn rejects_edges_that_close_a_cycle() {
+    let mut graph = DependencyGraph::default();
+    graph.add_edge("deploy", "test").unwrap();
+    graph.add_edge("test", "build").unwrap();
+    graph.add_edge("build", "checkout").unwrap();
+
+    assert_eq!(
+      graph.add_edge("checkout", "deploy"),
+      Err(Cycle(ids(&[
+        "checkout", "deploy", "test", "build", "checkout"
+      ])))
+    );
+    assert_eq!(
+      graph.add_edge("build", "build").unwrap_err().to_string(),
+      "build -> build"
+    );
+    // A diamond is not a cycle.
+    graph.add_edge("deploy", "build").unwrap();
+    graph.remove_edge("build", "checkout");
+    graph.add_edge("checkout", "deploy").unwrap();
+  }
+
+  #[test]
+  fn derives_blocked_state_and_unblocks() {
+    let graph = DependencyGraph::from_edges([("ship", "write"), ("ship", "review")]);
+    let open = |done: &'static [&'static str]| move |id: &str| !done.contains(&id);
+
+    assert!(graph.is_blocked("ship", open(&[])));
+    assert!(graph.unblocked_by("write", open(&["write"])).is_empty());
+    assert_eq!(
+      graph.unblocked_by("review", open(&["write", "review"])),
+      ["ship"]
+    );
+    assert!(!graph.is_blocked("ship", open(&["write", "review"])));
+  }
+
+  #[test]
+  fn layers_tasks_in_dependency_order() {
+    let graph = DependencyGraph::from_edges([
+      ("c", "b"),
+      ("b", "a"),
+      ("d", "a"),
+      ("e", "outside"),
+      ("f", "done"),
+    ]);
+    let tasks = ids(&["a", "b", "c", "d", "e", "f"]);
+    let layers = graph.layers(&tasks, |id| id != "done");
+    assert_eq!(
+      layers,
+      [ids(&["a", "f"]), ids(&["b", "d", "e"]), ids(&["c"])]
+    );
+  }
+}
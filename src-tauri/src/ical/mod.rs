@@ -0,0 +1,385 @@
+//! RFC 5545 iCalendar.
+//!
+//! This module is the content-line codec: folding, TEXT escaping and the
+//! `BEGIN`/`END` component tree. [`tasks`] maps tasks to `VTODO` (or
+//! `VEVENT`) components and back.
+
+pub mod tasks;
+
+use std::fmt::{self, Write};
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+use crate::error::{Error, Result};
+
+/// Longest content line in octets, excluding the line break.
+const LINE_LIMIT: usize = 75;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Property {
+  /// Upper-cased, e.g. `DUE`.
+  pub name: String,
+  /// Parameter names upper-cased, values without quotes.
+  pub params: Vec<(String, String)>,
+  /// Raw value, still escaped for TEXT properties.
+  pub value: String,
+}
+
+impl Property {
+  pub fn new(name: &str, value: impl Into<String>) -> Self {
+    Self {
+      name: name.to_string(),
+      params: Vec::new(),
+      value: value.into(),
+    }
+  }
+
+  /// A TEXT property, escaped.
+  pub fn text(name: &str, value: &str) -> Self {
+    Self::new(name, escape_text(value))
+  }
+
+  pub fn param(mut self, name: &str, value: &str) -> Self {
+    self.params.push((name.to_string(), value.to_string()));
+    self
+  }
+
+  pub fn get_param(&self, name: &str) -> Option<&str> {
+    self
+      .params
+      .iter()
+      .find(|(n, _)| n == name)
+      .map(|(_, v)| v.as_str())
+  }
+
+  /// The value as unescaped TEXT.
+  pub fn as_text(&self) -> String {
+    unescape_text(&self.value)
+  }
+
+  /// The value as a list of unescaped TEXT, split on unescaped commas.
+  pub fn as_list(&self) -> Vec<String> {
+    split_unescaped(&self.value, ',')
+      .into_iter()
+      .map(|v| unescape_text(&v))
+      .filter(|v| !v.is_empty())
+      .collect()
+  }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Component {
+  /// Upper-cased, e.g. `VTODO`.
+  pub name: String,
+  pub properties: Vec<Property>,
+  pub components: Vec<Component>,
+}
+
+impl Component {
+  pub fn new(name: &str) -> Self {
+    Self {
+      name: name.to_string(),
+      ..Default::default()
+    }
+  }
+
+  pub fn push(&mut self, property: Property) -> &mut Self {
+    self.properties.push(property);
+    self
+  }
+
+  pub fn get(&self, name: &str) -> Option<&Property> {
+    self.properties.iter().find(|p| p.name == name)
+  }
+
+  pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Property> + 'a {
+    self.properties.iter().filter(move |p| p.name == name)
+  }
+
+  /// Unescaped TEXT value of the first `name` property.
+  pub fn text(&self, name: &str) -> Option<String> {
+    self.get(name).map(Property::as_text)
+  }
+
+  /// Parse a stream that holds one or more top-level components, usually
+  /// a single `VCALENDAR`.
+  pub fn parse_all(input: &str) -> Result<Vec<Component>> {
+    let mut stack: Vec<Component> = Vec::new();
+    let mut roots = Vec::new();
+    for line in unfold(input) {
+      if line.trim().is_empty() {
+        continue;
+      }
+      let property = parse_line(&line)?;
+      match property.name.as_str() {
+        "BEGIN" => stack.push(Component::new(&property.value.to_ascii_uppercase())),
+        "END" => {
+          let component = stack
+            .pop()
+            .filter(|c| c.name.eq_ignore_ascii_case(&property.value))
+            .ok_or_else(|| invalid(format!("unexpected END:{}", property.value)))?;
+          match stack.last_mut() {
+            Some(parent) => parent.components.push(component),
+            None => roots.push(component),
+          }
+        }
+        _ => stack
+          .last_mut()
+          .ok_or_else(|| invalid(format!("{} outside of a component", property.name)))?
+          .properties
+          .push(property),
+      }
+    }
+    if let Some(open) = stack.last() {
+      return Err(invalid(format!("missing END:{}", open.name)));
+    }
+    Ok(roots)
+  }
+}
+
+impl fmt::Display for Component {
+  /// Folded content lines, each ending in CRLF.
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write_folded(f, &format!("BEGIN:{}", self.name))?;
+    for property in &self.properties {
+      let mut line = property.name.clone();
+      for (name, value) in &property.params {
+        let _ = write!(line, ";{name}=");
+        if value.contains([':', ';', ',']) {
+          let _ = write!(line, "\"{value}\"");
+        } else {
+          line.push_str(value);
+        }
+      }
+      line.push(':');
+      line.push_str(&property.value);
+      write_folded(f, &line)?;
+    }
+    for component in &self.components {
+      write!(f, "{component}")?;
+    }
+    write_folded(f, &format!("END:{}", self.name))
+  }
+}
+
+fn invalid(message: String) -> Error {
+  Error::InvalidInput(format!("invalid iCalendar: {message}"))
+}
+
+/// Split `line` into chunks of at most [`LINE_LIMIT`] octets without
+/// breaking a UTF-8 sequence; continuation lines start with a space.
+fn write_folded(f: &mut fmt::Formatter<'_>, line: &str) -> fmt::Result {
+  let mut rest = line;
+  let mut limit = LINE_LIMIT;
+  loop {
+    if rest.len() <= limit {
+      return write!(f, "{rest}\r\n");
+    }
+    let mut cut = limit;
+    while !rest.is_char_boundary(cut) {
+      cut -= 1;
+    }
+    write!(f, "{}\r\n ", &rest[..cut])?;
+    rest = &rest[cut..];
+    // The leading space counts toward the limit.
+    limit = LINE_LIMIT - 1;
+  }
+}
+
+/// Logical lines: a line break followed by a space or tab continues the
+/// previous line. Bare `\n` line ends are accepted too.
+fn unfold(input: &str) -> Vec<String> {
+  let mut lines: Vec<String> = Vec::new();
+  for raw in input.split('\n') {
+    let raw = raw.strip_suffix('\r').unwrap_or(raw);
+    match (raw.strip_prefix([' ', '\t']), lines.last_mut()) {
+      (Some(rest), Some(last)) => last.push_str(rest),
+      _ => lines.push(raw.to_string()),
+    }
+  }
+  lines
+}
+
+/// `NAME;PARAM=value;PARAM="quoted":value`
+fn parse_line(line: &str) -> Result<Property> {
+  let mut quoted = false;
+  let colon = line
+    .char_indices()
+    .find(|&(_, c)| {
+      if c == '"' {
+        quoted = !quoted;
+      }
+      c == ':' && !quoted
+    })
+    .map(|(i, _)| i)
+    .ok_or_else(|| invalid(format!("missing ':' in {line:?}")))?;
+  let (head, value) = (&line[..colon], &line[colon + 1..]);
+  let mut parts = split_outside_quotes(head, ';').into_iter();
+  let name = parts.next().unwrap_or_default().trim().to_ascii_uppercase();
+  if name.is_empty() {
+    return Err(invalid(format!("missing property name in {line:?}")));
+  }
+  let params = parts
+    .filter_map(|part| {
+      let (k, v) = part.split_once('=')?;
+      Some((
+        k.trim().to_ascii_uppercase(),
+        v.trim_matches('"').to_string(),
+      ))
+    })
+    .collect();
+  Ok(Property {
+    name,
+    params,
+    value: value.to_string(),
+  })
+}
+
+/// Split on `sep` outside double quotes.
+fn split_outside_quotes(value: &str, sep: char) -> Vec<String> {
+  let mut parts = vec![String::new()];
+  let mut quoted = false;
+  for c in value.chars() {
+    if c == '"' {
+      quoted = !quoted;
+    }
+    if c == sep && !quoted {
+      parts.push(String::new());
+    } else if let Some(last) = parts.last_mut() {
+      last.push(c);
+    }
+  }
+  parts
+}
+
+/// Split on `sep` where it is not escaped with a backslash; the parts stay
+/// escaped.
+fn split_unescaped(value: &str, sep: char) -> Vec<String> {
+  let mut parts = vec![String::new()];
+  let mut chars = value.chars();
+  while let Some(c) = chars.next() {
+    let last = parts.last_mut().expect("never empty");
+    if c == '\\' {
+      last.push(c);
+      last.extend(chars.next());
+    } else if c == sep {
+      parts.push(String::new());
+    } else {
+      last.push(c);
+    }
+  }
+  parts
+}
+
+pub fn escape_text(value: &str) -> String {
+  let mut out = String::with_capacity(value.len());
+  for c in value.chars() {
+    match c {
+      '\\' => out.push_str("\\\\"),
+      ';' => out.push_str("\\;"),
+      ',' => out.push_str("\\,"),
+      '\n' => out.push_str("\\n"),
+      '\r' => {}
+      c => out.push(c),
+    }
+  }
+  out
+}
+
+pub fn unescape_text(value: &str) -> String {
+  let mut out = String::with_capacity(value.len());
+  let mut chars = value.chars();
+  while let Some(c) = chars.next() {
+    if c != '\\' {
+      out.push(c);
+      continue;
+    }
+    match chars.next() {
+      Some('n' | 'N') => out.push('\n'),
+      Some(other) => out.push(other),
+      None => out.push('\\'),
+    }
+  }
+  out
+}
+
+/// A DATE, DATE-TIME in UTC, or floating (or `TZID`) local DATE-TIME value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IcalTime {
+  Date(NaiveDate),
+  Utc(DateTime<Utc>),
+  /// Wall-clock time; a `TZID` is read as the local zone.
+  Floating(NaiveDateTime),
+}
+
+impl IcalTime {
+  pub fn parse(property: &Property) -> Option<Self> {
+    let value = property.value.trim();
+    if property.get_param("VALUE") == Some("DATE") || value.len() == 8 {
+      return NaiveDate::parse_from_str(value, "%Y%m%d")
+        .ok()
+        .map(IcalTime::Date);
+    }
+    match value.strip_suffix('Z') {
+      Some(utc) => NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")
+        .ok()
+        .map(|t| IcalTime::Utc(t.and_utc())),
+      None => NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+        .ok()
+        .map(IcalTime::Floating),
+    }
+  }
+
+  /// A property named `name` holding this value.
+  pub fn property(self, name: &str) -> Property {
+    match self {
+      IcalTime::Date(d) => {
+        Property::new(name, d.format("%Y%m%d").to_string()).param("VALUE", "DATE")
+      }
+      IcalTime::Utc(t) => Property::new(name, format_utc(t)),
+      IcalTime::Floating(t) => Property::new(name, t.format("%Y%m%dT%H%M%S").to_string()),
+    }
+  }
+}
+
+/// `20261020T010000Z`
+pub fn format_utc(t: DateTime<Utc>) -> String {
+  t.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn folds_escapes_and_parses_back() {
+    let mut todo = Component::new("VTODO");
+    let long = "写周报，然后发给团队; 再检查一下 \\ 附件\n第二行".repeat(3);
+    todo.push(Property::text("SUMMARY", &long));
+    todo.push(Property::new("CATEGORIES", "a\\,b,c"));
+    todo.push(Property::new("DUE", "20261020").param("VALUE", "DATE"));
+    todo.push(Property::new("X-LINK", "x").param("ALTREP", "http://a;b"));
+    let mut calendar = Component::new("VCALENDAR");
+    calendar.components.push(todo.clone());
+
+    let text = calendar.to_string();
+    assert!(text
+      .lines()
+      .all(|l| l.trim_end_matches('\r').len() <= LINE_LIMIT));
+    assert!(text.contains("\r\n "));
+    assert!(text.contains("ALTREP=\"http://a;b\":x"));
+
+    let parsed = Component::parse_all(&text).unwrap();
+    assert_eq!(parsed, [calendar]);
+    let todo = &parsed[0].components[0];
+    assert_eq!(todo.text("SUMMARY").unwrap(), long);
+    assert_eq!(todo.get("CATEGORIES").unwrap().as_list(), ["a,b", "c"]);
+    assert_eq!(
+      IcalTime::parse(todo.get("DUE").unwrap()),
+      Some(IcalTime::Date("2026-10-20".parse().unwrap()))
+    );
+
+    assert!(Component::parse_all("BEGIN:VCALENDAR\nBEGIN:VTODO\nEND:VCALENDAR\n").is_err());
+    assert!(Component::parse_all("SUMMARY:loose\n").is_err());
+  }
+}
@@ -0,0 +1,657 @@
+//! Tasks as `VTODO` components, and `.ics` files back into tasks.
+//!
+//! A task is exported under its own id as `UID` unless it came from another
+//! calendar, in which case the original `UID` (kept in `ical_uids`) is used.
+//! Importing looks the `UID` up the same way, so a file can be imported
+//! again to update the tasks it created. Deadlines can also be exported as
+//! all-day or 30-minute `VEVENT`s for calendars that ignore to-dos.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, NaiveTime, SecondsFormat, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use super::{format_utc, Component, IcalTime, Property};
+use crate::error::Result;
+use crate::rrule::{format_task_date, parse_task_date, DateStyle, RRule};
+use crate::storage::models::{Project, Task, TaskFilter};
+use crate::storage::{now_iso, Store};
+
+pub const PRODID: &str = "-//Snail TodoList//Snail//ZH";
+/// Project name, so a round trip lands tasks back in their projects.
+const PROJECT_PROPERTY: &str = "X-SNAIL-PROJECT";
+/// Length of a timed deadline exported as an event without an estimate.
+const EVENT_MINUTES: i64 = 30;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IcsExportOptions {
+  #[serde(default)]
+  pub project_id: Option<String>,
+  #[serde(default)]
+  pub tag_id: Option<String>,
+  /// Deadlines as `VEVENT`s instead of every task as a `VTODO`.
+  #[serde(default)]
+  pub events: bool,
+  /// Only tasks with a due date.
+  #[serde(default)]
+  pub due_only: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IcsImportSummary {
+  pub created: usize,
+  pub updated: usize,
+  /// Components without a `SUMMARY`.
+  pub skipped: usize,
+}
+
+/// What a task's component needs besides the task row.
+#[derive(Debug, Clone, Default)]
+pub struct TaskExtras {
+  pub uid: String,
+  pub parent_uid: Option<String>,
+  pub project: Option<String>,
+  pub tags: Vec<String>,
+  pub rrule: Option<String>,
+}
+
+/// Export file name for `options`.
+pub fn file_name(options: &IcsExportOptions) -> &'static str {
+  if options.events {
+    "snail-deadlines.ics"
+  } else {
+    "snail-tasks.ics"
+  }
+}
+
+/// Wrap components in a `VCALENDAR` named `name`.
+pub fn calendar(name: &str, components: Vec<Component>) -> Component {
+  let mut calendar = Component::new("VCALENDAR");
+  calendar
+    .push(Property::new("VERSION", "2.0"))
+    .push(Property::new("PRODID", PRODID))
+    .push(Property::new("CALSCALE", "GREGORIAN"))
+    .push(Property::text("X-WR-CALNAME", name));
+  calendar.components = components;
+  calendar
+}
+
+fn timestamp(value: Option<&str>) -> Option<String> {
+  let t = DateTime::parse_from_rfc3339(value?).ok()?;
+  Some(format_utc(t.with_timezone(&Utc)))
+}
+
+/// A task `date` as DUE: local midnight, the date picker's "no time", is
+/// a DATE.
+fn due_time(date: &str) -> Option<IcalTime> {
+  let (at, style) = parse_task_date(date)?;
+  Some(match style {
+    DateStyle::Day => IcalTime::Date(at.date()),
+    _ if at.time() == NaiveTime::MIN => IcalTime::Date(at.date()),
+    DateStyle::Local => IcalTime::Floating(at),
+    DateStyle::Instant => IcalTime::Utc(DateTime::parse_from_rfc3339(date).ok()?.to_utc()),
+  })
+}
+
+/// A DUE value as a task `date`, stored like the date picker stores it.
+fn task_date(time: IcalTime) -> String {
+  match time {
+    IcalTime::Date(d) => format_task_date(d.and_time(NaiveTime::MIN), DateStyle::Instant),
+    IcalTime::Utc(t) => t.to_rfc3339_opts(SecondsFormat::Millis, true),
+    IcalTime::Floating(t) => format_task_date(t, DateStyle::Instant),
+  }
+}
+
+fn common(component: &mut Component, task: &Task, extras: &TaskExtras) {
+  component
+    .push(Property::text("UID", &extras.uid))
+    .push(Property::new("DTSTAMP", format_utc(Utc::now())));
+  if let Some(created) = timestamp(task.created_at.as_deref()) {
+    component.push(Property::new("CREATED", created));
+  }
+  if let Some(modified) = timestamp(task.updated_at.as_deref()) {
+    component.push(Property::new("LAST-MODIFIED", modified));
+  }
+  component.push(Property::text("SUMMARY", &task.title));
+  if let Some(description) = task.description.as_deref().filter(|d| !d.is_empty()) {
+    component.push(Property::text("DESCRIPTION", description));
+  }
+  if !extras.tags.is_empty() {
+    let tags: Vec<String> = extras.tags.iter().map(|t| super::escape_text(t)).collect();
+    component.push(Property::new("CATEGORIES", tags.join(",")));
+  }
+  if let Some(project) = &extras.project {
+    component.push(Property::text(PROJECT_PROPERTY, project));
+  }
+}
+
+/// The task as a `VTODO`.
+pub fn vtodo(task: &Task, extras: &TaskExtras) -> Component {
+  let mut todo = Component::new("VTODO");
+  common(&mut todo, task, extras);
+  if let Some(due) = task.date.as_deref().and_then(due_time) {
+    todo.push(due.property("DUE"));
+    if let Some(rrule) = &extras.rrule {
+      todo.push(Property::new("RRULE", rrule.as_str()));
+    }
+  }
+  let status = if task.completed {
+    "COMPLETED"
+  } else if task.abandoned {
+    "CANCELLED"
+  } else {
+    "NEEDS-ACTION"
+  };
+  todo.push(Property::new("STATUS", status));
+  if task.completed {
+    if let Some(completed) = timestamp(task.completed_at.as_deref()) {
+      todo.push(Property::new("COMPLETED", completed));
+    }
+    todo.push(Property::new("PERCENT-COMPLETE", "100"));
+  }
+  if task.flagged {
+    todo.push(Property::new("PRIORITY", "1"));
+  }
+  if let Some(parent) = &extras.parent_uid {
+    todo.push(Property::text("RELATED-TO", parent).param("RELTYPE", "PARENT"));
+  }
+  todo
+}
+
+/// The task's deadline as a `VEVENT`, or `None` without one.
+pub fn vevent(task: &Task, extras: &TaskExtras) -> Option<Component> {
+  let due = due_time(task.date.as_deref()?)?;
+  let mut event = Component::new("VEVENT");
+  common(&mut event, task, extras);
+  event.push(due.property("DTSTART"));
+  let minutes = task
+    .estimate_minutes
+    .filter(|m| *m > 0)
+    .unwrap_or(EVENT_MINUTES);
+  let end = match due {
+    IcalTime::Date(d) => IcalTime::Date(d.succ_opt()?),
+    IcalTime::Utc(t) => IcalTime::Utc(t + Duration::minutes(minutes)),
+    IcalTime::Floating(t) => IcalTime::Floating(t + Duration::minutes(minutes)),
+  };
+  event.push(end.property("DTEND"));
+  if let Some(rrule) = &extras.rrule {
+    event.push(Property::new("RRULE", rrule.as_str()));
+  }
+  let status = if task.abandoned {
+    "CANCELLED"
+  } else {
+    "CONFIRMED"
+  };
+  event
+    .push(Property::new("STATUS", status))
+    .push(Property::new("TRANSP", "TRANSPARENT"));
+  Some(event)
+}
+
+/// Task id for `uid`: a mapped foreign uid, or a task's own id.
+pub(crate) fn uid_of(conn: &Connection, uid: &str) -> Result<Option<String>> {
+  let mapped = conn
+    .query_row(
+      "select task_id from ical_uids where uid = ?1",
+      [uid],
+      |row| row.get(0),
+    )
+    .optional()?;
+  if mapped.is_some() {
+    return Ok(mapped);
+  }
+  Ok(
+    conn
+      .query_row("select id from tasks where id = ?1", [uid], |row| {
+        row.get(0)
+      })
+      .optional()?,
+  )
+}
+
+/// Task fields read from a `VTODO` or `VEVENT`, as an update patch.
+fn patch_of(
+  component: &Component,
+  existing: Option<&Task>,
+  project: Option<&str>,
+) -> Map<String, Value> {
+  let due_property = if component.name == "VEVENT" {
+    "DTSTART"
+  } else {
+    "DUE"
+  };
+  let date = component
+    .get(due_property)
+    .and_then(IcalTime::parse)
+    .map(task_date);
+  let status = component
+    .get("STATUS")
+    .map(|p| p.value.to_ascii_uppercase());
+  let completed = status.as_deref() == Some("COMPLETED")
+    || component
+      .get("PERCENT-COMPLETE")
+      .is_some_and(|p| p.value.trim() == "100");
+  let abandoned = status.as_deref() == Some("CANCELLED");
+  let completed_at = completed.then(|| {
+    component
+      .get("COMPLETED")
+      .and_then(IcalTime::parse)
+      .map(task_date)
+      .or_else(|| existing.and_then(|t| t.completed_at.clone()))
+      .unwrap_or_else(now_iso)
+  });
+  let abandoned_at = abandoned.then(|| {
+    existing
+      .and_then(|t| t.abandoned_at.clone())
+      .unwrap_or_else(now_iso)
+  });
+  let flagged = component
+    .get("PRIORITY")
+    .and_then(|p| p.value.trim().parse::<u8>().ok())
+    .is_some_and(|p| (1..=4).contains(&p));
+
+  let mut patch = Map::new();
+  let mut set = |key: &str, value: Value| patch.insert(key.to_string(), value);
+  set(
+    "title",
+    json!(component.text("SUMMARY").unwrap_or_default()),
+  );
+  set("description", json!(component.text("DESCRIPTION")));
+  set("date", json!(date));
+  set("completed", json!(completed));
+  set("completed_at", json!(completed_at));
+  set("abandoned", json!(abandoned));
+  set("abandoned_at", json!(abandoned_at));
+  set("flagged", json!(flagged));
+  if let Some(project) = project {
+    set("project", json!(project));
+  }
+  patch
+}
+
+impl Store {
+  /// Uid, parent uid, project name, tag names and rule of each task.
+  pub fn ical_extras(&self, tasks: &[Task]) -> Result<Vec<TaskExtras>> {
+    let ids: Vec<String> = tasks.iter().map(|t| t.id.clone()).collect();
+    let tags = self.get_tags_by_task_ids(&ids)?;
+    let projects: HashMap<String, String> = self
+      .get_projects()?
+      .into_iter()
+      .map(|p| (p.id, p.name))
+      .collect();
+    let uids: HashMap<String, String> = {
+      let conn = self.conn();
+      let mut stmt = conn.prepare("select task_id, uid from ical_uids")?;
+      let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+      rows.into_iter().collect()
+    };
+    let uid = |id: &str| uids.get(id).cloned().unwrap_or_else(|| id.to_string());
+    let mut extras = Vec::with_capacity(tasks.len());
+    for task in tasks {
+      extras.push(TaskExtras {
+        uid: uid(&task.id),
+        parent_uid: task.parent_id.as_deref().map(uid),
+        project: task.project.as_ref().and_then(|p| projects.get(p).cloned()),
+        tags: tags
+          .get(&task.id)
+          .map(|t| t.iter().map(|t| t.name.clone()).collect())
+          .unwrap_or_default(),
+        rrule: self.get_task_recurrence(&task.id)?.map(|r| r.rrule),
+      });
+    }
+    Ok(extras)
+  }
+
+  /// Tasks outside the trash as an `.ics` calendar.
+  pub fn export_ics(&self, options: &IcsExportOptions) -> Result<String> {
+    let filter = TaskFilter {
+      project_id: options.project_id.clone(),
+      deleted: Some(false),
+      ..Default::default()
+    };
+    let mut tasks = self.get_tasks(&filter, &[])?;
+    if options.due_only {
+      tasks.retain(|t| t.date.as_deref().and_then(due_time).is_some());
+    }
+    if let Some(tag_id) = &options.tag_id {
+      let ids: Vec<String> = tasks.iter().map(|t| t.id.clone()).collect();
+      let tags = self.get_tags_by_task_ids(&ids)?;
+      tasks.retain(|t| tags[&t.id].iter().any(|tag| &tag.id == tag_id));
+    }
+    tasks.sort_by(|a, b| {
+      a.sort_order
+        .unwrap_or(f64::MAX)
+        .total_cmp(&b.sort_order.unwrap_or(f64::MAX))
+        .then_with(|| a.created_at.cmp(&b.created_at))
+    });
+    let extras = self.ical_extras(&tasks)?;
+    let components = tasks
+      .iter()
+      .zip(&extras)
+      .filter_map(|(task, extras)| {
+        if options.events {
+          vevent(task, extras)
+        } else {
+          Some(vtodo(task, extras))
+        }
+      })
+      .collect();
+
+    let name = match &options.project_id {
+      Some(id) => self
+        .get_project_by_id(id)?
+        .map_or_else(|| "蜗牛待办".to_string(), |p| p.name),
+      None => "蜗牛待办".to_string(),
+    };
+    Ok(calendar(&name, components).to_string())
+  }
+
+  /// Import every `VTODO` and `VEVENT` in `input`. Tasks go to `project_id`
+  /// if given, otherwise to the project named in the file (created if
+  /// missing). A `UID` seen before updates its task.
+  pub fn import_ics(&self, input: &str, project_id: Option<&str>) -> Result<IcsImportSummary> {
+    let roots = Component::parse_all(input)?;
+    let components: Vec<&Component> = roots
+      .iter()
+      .flat_map(|root| {
+        if root.name == "VCALENDAR" {
+          root.components.iter().collect()
+        } else {
+          vec![root]
+        }
+      })
+      .filter(|c| c.name == "VTODO" || c.name == "VEVENT")
+      .collect();
+
+    let mut projects: HashMap<String, String> = self
+      .get_projects()?
+      .into_iter()
+      .map(|p| (p.name.to_lowercase(), p.id))
+      .collect();
+    let mut summary = IcsImportSummary::default();
+    let mut parents = Vec::new();
+    for component in components {
+      if component
+        .text("SUMMARY")
+        .map_or(true, |s| s.trim().is_empty())
+      {
+        summary.skipped += 1;
+        continue;
+      }
+      let project = match (project_id, component.text(PROJECT_PROPERTY)) {
+        (Some(id), _) => Some(id.to_string()),
+        (None, Some(name)) if !name.trim().is_empty() => {
+          let key = name.to_lowercase();
+          if !projects.contains_key(&key) {
+            let created = self.create_project(Project {
+              name: name.clone(),
+              ..Default::default()
+            })?;
+            projects.insert(key.clone(), created.id);
+          }
+          projects.get(&key).cloned()
+        }
+        _ => None,
+      };
+
+      let uid = component.text("UID").filter(|u| !u.is_empty());
+      let existing_id = match &uid {
+        Some(uid) => uid_of(&self.conn(), uid)?,
+        None => None,
+      };
+      let existing = match &existing_id {
+        Some(id) => self.get_task_by_id(id)?,
+        None => None,
+      };
+      let patch = patch_of(component, existing.as_ref(), project.as_deref());
+      let task = match existing {
+        Some(existing) => {
+          summary.updated += 1;
+          self.update_task(&existing.id, &patch)?.unwrap_or(existing)
+        }
+        None => {
+          summary.created += 1;
+          let mut task: Task = serde_json::from_value(Value::Object(patch))?;
+          task.user_id = Some(crate::storage::OFFLINE_USER_ID.to_string());
+          let task = self.create_task(task)?;
+          if let Some(uid) = uid.as_deref().filter(|u| *u != task.id) {
+            self.conn().execute(
+              "insert or replace into ical_uids (uid, task_id) values (?1, ?2)",
+              params![uid, task.id],
+            )?;
+          }
+          task
+        }
+      };
+
+      self.set_task_tag_names(
+        &task.id,
+        &component
+          .get("CATEGORIES")
+          .map(Property::as_list)
+          .unwrap_or_default(),
+      )?;
+      if let Some(rule) = component.get("RRULE") {
+        self.import_rrule(&task, &rule.value);
+      }
+      if let Some(parent) = component.get_all("RELATED-TO").find(|p| {
+        p.get_param("RELTYPE")
+          .map_or(true, |t| t.eq_ignore_ascii_case("PARENT"))
+      }) {
+        parents.push((task.id.clone(), task.parent_id.clone(), parent.as_text()));
+      }
+    }
+
+    for (task_id, current, parent_uid) in parents {
+      let Some(parent_id) = uid_of(&self.conn(), &parent_uid)? else {
+        continue;
+      };
+      if current.as_deref() == Some(parent_id.as_str()) || parent_id == task_id {
+        continue;
+      }
+      let patch = json!({ "parent_id": parent_id });
+      if let Err(e) = self.update_task(&task_id, patch.as_object().expect("object")) {
+        log::warn!("ignoring the parent of imported task {task_id}: {e}");
+      }
+    }
+    Ok(summary)
+  }
+
+  /// Give a task exactly the tags named, creating missing ones.
+  fn set_task_tag_names(&self, task_id: &str, names: &[String]) -> Result<()> {
+    let current = self
+      .get_tags_by_task_ids(&[task_id.to_string()])?
+      .remove(task_id)
+      .unwrap_or_default();
+    let wanted: Vec<String> = names.iter().map(|n| n.trim().to_lowercase()).collect();
+    for tag in &current {
+      if !wanted.contains(&tag.name.to_lowercase()) {
+        self.detach_tag_from_task(task_id, &tag.id)?;
+      }
+    }
+    let all = self.get_tags(None)?;
+    for name in names.iter().map(|n| n.trim()) {
+      if current.iter().any(|t| t.name.eq_ignore_ascii_case(name)) {
+        continue;
+      }
+      let tag = match all.iter().find(|t| t.name.eq_ignore_ascii_case(name)) {
+        Some(tag) => tag.clone(),
+        None => self.create_tag(name, None)?,
+      };
+      self.attach_tag_to_task(task_id, &tag.id)?;
+    }
+    Ok(())
+  }
+
+  /// Keep an imported `RRULE` if it parses and the task has a due date.
+  fn import_rrule(&self, task: &Task, rule: &str) {
+    let result = rule.parse::<RRule>().and_then(|_| {
+      let exdates = self
+        .get_task_recurrence(&task.id)?
+        .map(|r| r.exdates)
+        .unwrap_or_default();
+      self.set_task_recurrence(&task.id, rule, exdates)
+    });
+    if let Err(e) = result {
+      log::warn!("ignoring the RRULE of imported task {}: {e}", task.id);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::storage::models::Project;
+
+  const FOREIGN: &str = "BEGIN:VCALENDAR\r\n\
+VERSION:2.0\r\n\
+PRODID:-//Example//Other//EN\r\n\
+BEGIN:VTODO\r\n\
+UID:abc@example.com\r\n\
+SUMMARY:Pay rent\\, on time\r\n\
+DUE;VALUE=DATE:20261101\r\n\
+CATEGORIES:home,money\r\n\
+PRIORITY:2\r\n\
+RRULE:FREQ=MONTHLY\r\n\
+END:VTODO\r\n\
+BEGIN:VTODO\r\n\
+UID:child@example.com\r\n\
+SUMMARY:Transfer\r\n\
+RELATED-TO:abc@example.com\r\n\
+STATUS:COMPLETED\r\n\
+COMPLETED:20261020T080000Z\r\n\
+END:VTODO\r\n\
+BEGIN:VTODO\r\n\
+UID:nameless@example.com\r\n\
+END:VTODO\r\n\
+END:VCALENDAR\r\n";
+
+  #[test]
+  fn reimporting_updates_instead_of_duplicating() {
+    let store = Store::open_in_memory().unwrap();
+    let summary = store.import_ics(FOREIGN, None).unwrap();
+    assert_eq!(
+      (summary.created, summary.updated, summary.skipped),
+      (2, 0, 1)
+    );
+
+    let tasks = store.get_tasks(&TaskFilter::default(), &[]).unwrap();
+    let rent = tasks
+      .iter()
+      .find(|t| t.title == "Pay rent, on time")
+      .unwrap();
+    let child = tasks.iter().find(|t| t.title == "Transfer").unwrap();
+    assert!(rent.flagged);
+    assert_eq!(
+      due_time(rent.date.as_deref().unwrap()),
+      Some(IcalTime::Date("2026-11-01".parse().unwrap()))
+    );
+    assert_eq!(child.parent_id.as_deref(), Some(rent.id.as_str()));
+    assert!(child.completed);
+    assert_eq!(
+      child.completed_at.as_deref(),
+      Some("2026-10-20T08:00:00.000Z")
+    );
+    assert_eq!(
+      store.get_task_recurrence(&rent.id).unwrap().unwrap().rrule,
+      "FREQ=MONTHLY"
+    );
+
+    let edited = FOREIGN.replace("CATEGORIES:home,money", "CATEGORIES:home");
+    let summary = store.import_ics(&edited, None).unwrap();
+    assert_eq!((summary.created, summary.updated), (0, 2));
+    assert_eq!(
+      store.get_tasks(&TaskFilter::default(), &[]).unwrap().len(),
+      2
+    );
+    let tags = store
+      .get_tags_by_task_ids(std::slice::from_ref(&rent.id))
+      .unwrap();
+    assert_eq!(
+      tags[&rent.id]
+        .iter()
+        .map(|t| t.name.as_str())
+        .collect::<Vec<_>>(),
+      ["home"]
+    );
+
+    // The foreign UID survives an export.
+    let exported = store.export_ics(&IcsExportOptions::default()).unwrap();
+    assert!(exported.contains("UID:abc@example.com\r\n"));
+    assert!(exported.contains("RELATED-TO;RELTYPE=PARENT:abc@example.com\r\n"));
+  }
+
+  #[test]
+  fn exports_todos_and_events_that_round_trip() {
+    let store = Store::open_in_memory().unwrap();
+    let project = store
+      .create_project(Project {
+        name: "Work".into(),
+        ..Default::default()
+      })
+      .unwrap();
+    let report = store
+      .create_task(Task {
+        title: "Report; draft".into(),
+        description: Some("line one\nline two".into()),
+        project: Some(project.id.clone()),
+        date: Some("2026-10-20T09:30:00.000Z".into()),
+        estimate_minutes: Some(90),
+        ..Default::default()
+      })
+      .unwrap();
+    let tag = store.create_tag("deep", None).unwrap();
+    store.attach_tag_to_task(&report.id, &tag.id).unwrap();
+    store
+      .create_task(Task {
+        title: "undated".into(),
+        completed: true,
+        completed_at: Some("2026-10-01T10:00:00.000Z".into()),
+        ..Default::default()
+      })
+      .unwrap();
+
+    let ics = store.export_ics(&IcsExportOptions::default()).unwrap();
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(ics.contains(&format!("UID:{}\r\n", report.id)));
+    assert!(ics.contains("SUMMARY:Report\\; draft\r\n"));
+    assert!(ics.contains("DESCRIPTION:line one\\nline two\r\n"));
+    assert!(ics.contains("DUE:20261020T093000Z\r\n"));
+    assert!(ics.contains("CATEGORIES:deep\r\n"));
+    assert!(ics.contains("X-SNAIL-PROJECT:Work\r\n"));
+    assert!(ics.contains("STATUS:COMPLETED\r\nCOMPLETED:20261001T100000Z\r\n"));
+
+    let events = store
+      .export_ics(&IcsExportOptions {
+        tag_id: Some(tag.id.clone()),
+        events: true,
+        ..Default::default()
+      })
+      .unwrap();
+    assert_eq!(events.matches("BEGIN:VEVENT").count(), 1);
+    assert!(events.contains("DTSTART:20261020T093000Z\r\nDTEND:20261020T110000Z\r\n"));
+
+    // Into a fresh database: same tasks, same project, same UIDs.
+    let other = Store::open_in_memory().unwrap();
+    let summary = other.import_ics(&ics, None).unwrap();
+    assert_eq!(summary.created, 2);
+    let copy = other.get_tasks(&TaskFilter::default(), &[]).unwrap();
+    let copied = copy.iter().find(|t| t.title == "Report; draft").unwrap();
+    assert_eq!(copied.date, report.date);
+    assert_eq!(copied.description, report.description);
+    assert_eq!(other.get_projects().unwrap()[0].name, "Work");
+    assert_eq!(
+      other
+        .export_ics(&IcsExportOptions::default())
+        .unwrap()
+        .matches(&report.id)
+        .count(),
+      1
+    );
+    assert_eq!(other.import_ics(&ics, None).unwrap().updated, 2);
+  }
+}
@@ -1,6 +1,39 @@
+pub mod analytics;
+pub mod backup;
+pub mod caldav;
+pub mod capture;
+pub mod cli;
+pub mod commands;
+pub mod crdt;
+pub mod deep_link;
+pub mod error;
+pub mod feed;
+pub mod graph;
+pub mod ical;
+pub mod peer;
+pub mod pomodoro;
+pub mod query;
+pub mod quick_add;
+pub mod reminders;
+pub mod rrule;
+pub mod storage;
+pub mod sync;
+pub mod tray;
+pub mod webhooks;
+
+use tauri::{Manager, WindowEvent};
+
+use storage::Store;
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
   tauri::Builder::default()
+    // 单实例需最先注册：再次启动（包括点击 snailtodo:// 链接）时把参数交给已运行的实例
+    .plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+      tray::show_main_window(app);
+    }))
+    .plugin(tauri_plugin_deep_link::init())
+    .plugin(tauri_plugin_notification::init())
     .setup(|app| {
       if cfg!(debug_assertions) {
         app.handle().plugin(
@@ -9,7 +42,17 @@ pub fn run() {
             .build(),
         )?;
       }
-      
+
+      // 本地数据库放在应用数据目录下，清理 WebView 缓存不会影响它。
+      // 打开时按版本执行迁移；数据库版本高于当前程序时拒绝启动，避免旧版本写坏数据
+      let db_path = app.path().app_data_dir()?.join(storage::DB_FILE_NAME);
+      let store = Store::open(&db_path)?;
+      log::info!("database schema version {}", store.schema_version()?);
+      app.manage(store);
+
+      // 按设置定时在应用数据目录下生成本地备份，并按保留策略清理旧备份
+      commands::backup::spawn_scheduler(app.handle().clone());
+
       // 在 index.html 中注入脚本，确保内容加载完成后再显示窗口
       let html = r#"
         <script>
@@ -22,7 +65,7 @@ pub fn run() {
           });
         </script>
       "#;
-      
+
       // 将脚本注入到 index.html 中
       tauri::WebviewWindowBuilder::new(
         app,
@@ -32,9 +75,161 @@ pub fn run() {
       .initialization_script(html)
       .visible(false)
       .build()?;
-      
+
+      // 托盘显示今天的待办数量，并提供快速添加、番茄钟等入口
+      tray::init(app.handle())?;
+      // 番茄钟在 Rust 侧计时，窗口隐藏或被节流时不会变慢，托盘显示剩余时间
+      commands::pomodoro::init(app.handle())?;
+      // 本地日历订阅源（webcal），启用后系统日历可按令牌订阅带截止日期的任务
+      commands::feed::init(app.handle())?;
+      // 登录后本地写入先入发件箱，后台与 Supabase 双向同步，离线时修改不会丢失
+      commands::sync::init(app.handle());
+      // 局域网同步：通过 mDNS 发现其他实例，用一次性配对码配对，变更经加密通道交换
+      commands::peers::init(app.handle())?;
+      // 全局快捷键呼出快速添加窗口，快捷键可在设置中修改；
+      // 添加的任务交给主窗口按当前存储模式保存
+      app.manage(capture::Captures::default());
+      capture::init_shortcut(app.handle())?;
+      // 截止时间提醒在 Rust 侧检查，窗口隐藏到托盘时也能收到系统通知
+      commands::reminders::spawn_scheduler(app.handle().clone());
+      // 开启 Webhook 后，即将截止和已逾期的任务经发件箱推送，失败自动重试；
+      // 数据不在本地数据库时由页面把任务和配置交过来
+      commands::webhooks::spawn_dispatcher(app.handle().clone());
+      // snailtodo:// 链接：登录回调、打开任务/清单、加入共享清单
+      commands::deep_link::init(app.handle())?;
+
       Ok(())
     })
+    .on_window_event(|window, event| {
+      // 关闭主窗口和快速添加窗口时只隐藏，应用继续在托盘中运行
+      if let WindowEvent::CloseRequested { api, .. } = event {
+        if matches!(window.label(), "main" | capture::WINDOW_LABEL) {
+          api.prevent_close();
+          let _ = window.hide();
+        }
+      }
+    })
+    .invoke_handler(tauri::generate_handler![
+      commands::storage::get_tasks,
+      commands::storage::get_task_by_id,
+      commands::storage::create_task,
+      commands::storage::update_task,
+      commands::storage::delete_task,
+      commands::storage::batch_update_sort_order,
+      commands::storage::get_subtree,
+      commands::storage::move_task,
+      commands::storage::place_task_between,
+      commands::storage::trash_task,
+      commands::storage::restore_task,
+      commands::storage::get_task_progress,
+      commands::storage::get_projects,
+      commands::storage::get_project_by_id,
+      commands::storage::create_project,
+      commands::storage::update_project,
+      commands::storage::delete_project,
+      commands::storage::batch_update_project_sort_order,
+      commands::storage::get_tags,
+      commands::storage::get_tag_by_id,
+      commands::storage::create_tag,
+      commands::storage::update_tag,
+      commands::storage::delete_tag,
+      commands::storage::get_tags_by_task_ids,
+      commands::storage::attach_tag_to_task,
+      commands::storage::detach_tag_from_task,
+      commands::storage::get_pomodoro_sessions,
+      commands::storage::get_pomodoro_session_by_id,
+      commands::storage::create_pomodoro_session,
+      commands::storage::update_pomodoro_session,
+      commands::storage::delete_pomodoro_session,
+      commands::storage::get_task_activities,
+      commands::storage::create_task_activity,
+      commands::storage::has_checked_in_today,
+      commands::storage::create_check_in,
+      commands::storage::get_check_in_history,
+      commands::storage::get_check_in_streak,
+      commands::storage::upload_attachment,
+      commands::storage::delete_attachment,
+      commands::storage::upload_image,
+      commands::storage::upload_avatar,
+      commands::storage::search_tasks,
+      commands::storage::get_user_settings,
+      commands::storage::save_user_settings,
+      commands::storage::get_user_profile,
+      commands::storage::save_user_profile,
+      commands::storage::get_app_info,
+      commands::backup::default_backup_file_name,
+      commands::backup::export_backup,
+      commands::backup::validate_backup,
+      commands::backup::import_backup,
+      commands::backup::import_webview_data,
+      commands::backup::list_backup_snapshots,
+      commands::backup::create_backup_snapshot,
+      commands::backup::restore_backup_snapshot,
+      commands::capture::capture_task,
+      commands::capture::take_captured_tasks,
+      commands::capture::get_capture_projects,
+      commands::capture::set_capture_projects,
+      commands::capture::parse_quick_add,
+      commands::capture::hide_capture_window,
+      commands::capture::get_quick_add_shortcut,
+      commands::capture::set_quick_add_shortcut,
+      commands::reminders::check_deadline_reminders,
+      commands::webhooks::queue_deadline_webhooks,
+      commands::webhooks::send_test_webhook,
+      commands::webhooks::get_webhook_outbox,
+      commands::deep_link::take_pending_deep_links,
+      commands::query::query_tasks,
+      commands::query::validate_query,
+      commands::query::get_smart_lists,
+      commands::query::create_smart_list,
+      commands::query::update_smart_list,
+      commands::query::delete_smart_list,
+      commands::query::run_smart_list,
+      commands::recurrence::get_task_recurrence,
+      commands::recurrence::set_task_recurrence,
+      commands::recurrence::clear_task_recurrence,
+      commands::recurrence::get_upcoming_occurrences,
+      commands::dependencies::get_task_dependencies,
+      commands::dependencies::add_task_dependency,
+      commands::dependencies::remove_task_dependency,
+      commands::dependencies::get_blocked_task_ids,
+      commands::dependencies::get_next_actions,
+      commands::pomodoro::get_pomodoro_state,
+      commands::pomodoro::pomodoro_start,
+      commands::pomodoro::pomodoro_pause,
+      commands::pomodoro::pomodoro_reset,
+      commands::pomodoro::pomodoro_skip,
+      commands::pomodoro::pomodoro_select,
+      commands::pomodoro::set_pomodoro_settings,
+      commands::analytics::get_analytics_report,
+      commands::analytics::export_analytics_report,
+      commands::time_tracking::start_task_timer,
+      commands::time_tracking::stop_task_timer,
+      commands::time_tracking::get_running_timer,
+      commands::time_tracking::get_time_entries,
+      commands::time_tracking::add_time_entry,
+      commands::time_tracking::update_time_entry,
+      commands::time_tracking::delete_time_entry,
+      commands::time_tracking::get_time_summary,
+      commands::time_tracking::export_timesheet,
+      commands::ical::export_ics,
+      commands::ical::import_ics,
+      commands::feed::get_calendar_feeds,
+      commands::feed::set_calendar_feed_settings,
+      commands::feed::create_calendar_feed,
+      commands::feed::delete_calendar_feed,
+      commands::sync::configure_sync,
+      commands::sync::sync_now,
+      commands::sync::get_sync_status,
+      commands::sync::disable_sync,
+      commands::peers::get_peers,
+      commands::peers::set_peer_settings,
+      commands::peers::create_pairing_code,
+      commands::peers::discover_peers,
+      commands::peers::pair_peer,
+      commands::peers::sync_peers,
+      commands::peers::unpair_peer,
+    ])
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
 }
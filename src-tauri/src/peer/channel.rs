@@ -0,0 +1,342 @@
+//! The wire: length-prefixed frames over TCP, a plaintext handshake that
+//! runs [`super::pake`], then ChaCha20-Poly1305 with a key per direction.
+//! Nonces count frames, so a dropped, reordered or replayed frame fails to
+//! open and ends the session.
+
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use super::pake::{Pake, Role};
+use crate::error::{Error, Result};
+
+/// Larger frames are refused before anything is allocated for them.
+const MAX_FRAME: usize = 64 << 20;
+/// The same for the plaintext handshake, which anyone on the network can
+/// start: its messages carry two identities and a key exchange message.
+const MAX_HANDSHAKE_FRAME: usize = 4 << 10;
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+fn write_frame(stream: &mut TcpStream, bytes: &[u8], limit: usize) -> Result<()> {
+  let len = u32::try_from(bytes.len())
+    .ok()
+    .filter(|len| *len as usize <= limit)
+    .ok_or_else(|| Error::Peer("message too large".into()))?;
+  stream.write_all(&len.to_be_bytes())?;
+  stream.write_all(bytes)?;
+  stream.flush()?;
+  Ok(())
+}
+
+fn read_frame(stream: &mut TcpStream, limit: usize) -> Result<Vec<u8>> {
+  let mut len = [0u8; 4];
+  stream.read_exact(&mut len)?;
+  let len = u32::from_be_bytes(len) as usize;
+  if len > limit {
+    return Err(Error::Peer("message too large".into()));
+  }
+  let mut bytes = vec![0u8; len];
+  stream.read_exact(&mut bytes)?;
+  Ok(bytes)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Mode {
+  /// Prove knowledge of a one-time pairing code.
+  Pair,
+  /// Prove knowledge of the secret agreed when pairing.
+  Sync,
+}
+
+/// Who is on one end of a connection.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Identity {
+  /// The replica id of the database ([`crate::storage::Store::replica_id`]).
+  pub replica: String,
+  pub name: String,
+  /// Where this side accepts connections, if it does.
+  pub port: Option<u16>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum Handshake {
+  Hello {
+    from: Identity,
+    mode: Mode,
+    message: String,
+  },
+  Welcome {
+    from: Identity,
+    message: String,
+  },
+  Confirm {
+    mac: String,
+  },
+  Reject {
+    reason: String,
+  },
+}
+
+fn send_plain(stream: &mut TcpStream, message: &Handshake) -> Result<()> {
+  write_frame(stream, &serde_json::to_vec(message)?, MAX_HANDSHAKE_FRAME)
+}
+
+fn recv_plain(stream: &mut TcpStream) -> Result<Handshake> {
+  match serde_json::from_slice(&read_frame(stream, MAX_HANDSHAKE_FRAME)?)? {
+    Handshake::Reject { reason } => Err(Error::Peer(reason)),
+    message => Ok(message),
+  }
+}
+
+fn unexpected() -> Error {
+  Error::Peer("unexpected handshake message".into())
+}
+
+/// Everything the handshake said besides the key exchange itself, so
+/// neither side can be talked into a different session than the other.
+fn context(mode: Mode, client: &Identity, server: &Identity) -> Vec<u8> {
+  serde_json::to_vec(&(mode, client, server)).expect("plain data serializes")
+}
+
+/// An authenticated session.
+pub struct Session {
+  pub channel: Channel,
+  pub peer: Identity,
+  /// The peer's address as seen from here.
+  pub address: SocketAddr,
+  pub mode: Mode,
+  /// Agreed by both sides; stored as the peer's secret after pairing.
+  pub pairing_secret: [u8; 32],
+}
+
+/// Open a session with the peer at `address`.
+pub fn connect(address: &str, me: &Identity, mode: Mode, password: &[u8]) -> Result<Session> {
+  let target = address
+    .to_socket_addrs()?
+    .next()
+    .ok_or_else(|| Error::InvalidInput(format!("cannot resolve {address}")))?;
+  let mut stream = TcpStream::connect_timeout(&target, TIMEOUT)?;
+  stream.set_read_timeout(Some(TIMEOUT))?;
+  stream.set_write_timeout(Some(TIMEOUT))?;
+  let pake = Pake::start(Role::Client, password);
+  send_plain(
+    &mut stream,
+    &Handshake::Hello {
+      from: me.clone(),
+      mode,
+      message: hex::encode(pake.message()),
+    },
+  )?;
+  let Handshake::Welcome { from, message } = recv_plain(&mut stream)? else {
+    return Err(unexpected());
+  };
+  let theirs = hex::decode(message).map_err(|_| unexpected())?;
+  let keys = pake.finish(&theirs, &context(mode, me, &from))?;
+  send_plain(
+    &mut stream,
+    &Handshake::Confirm {
+      mac: hex::encode(keys.confirmation(Role::Client)),
+    },
+  )?;
+  let Handshake::Confirm { mac } = recv_plain(&mut stream)? else {
+    return Err(unexpected());
+  };
+  let mac = hex::decode(mac).map_err(|_| unexpected())?;
+  if !keys.verify(Role::Server, &mac) {
+    return Err(Error::Peer(
+      "the peer could not prove it knows the secret".into(),
+    ));
+  }
+  Ok(Session {
+    channel: Channel::new(stream, &keys.client_key, &keys.server_key),
+    peer: from,
+    address: target,
+    mode,
+    pairing_secret: keys.pairing_secret,
+  })
+}
+
+/// Answer a session on an accepted connection. `password` looks up what the
+/// client has to prove it knows, or says why it is turned away.
+pub fn accept(
+  mut stream: TcpStream,
+  me: &Identity,
+  password: impl FnOnce(Mode, &Identity) -> std::result::Result<Vec<u8>, String>,
+) -> Result<Session> {
+  stream.set_read_timeout(Some(TIMEOUT))?;
+  stream.set_write_timeout(Some(TIMEOUT))?;
+  let address = stream.peer_addr()?;
+  let Handshake::Hello {
+    from,
+    mode,
+    message,
+  } = recv_plain(&mut stream)?
+  else {
+    return Err(unexpected());
+  };
+  let password = match password(mode, &from) {
+    Ok(password) => password,
+    Err(reason) => {
+      let _ = send_plain(
+        &mut stream,
+        &Handshake::Reject {
+          reason: reason.clone(),
+        },
+      );
+      return Err(Error::Peer(reason));
+    }
+  };
+  let theirs = hex::decode(message).map_err(|_| unexpected())?;
+  let pake = Pake::start(Role::Server, &password);
+  send_plain(
+    &mut stream,
+    &Handshake::Welcome {
+      from: me.clone(),
+      message: hex::encode(pake.message()),
+    },
+  )?;
+  let keys = pake.finish(&theirs, &context(mode, &from, me))?;
+  let Handshake::Confirm { mac } = recv_plain(&mut stream)? else {
+    return Err(unexpected());
+  };
+  let mac = hex::decode(mac).map_err(|_| unexpected())?;
+  if !keys.verify(Role::Client, &mac) {
+    let reason = match mode {
+      Mode::Pair => "wrong pairing code",
+      Mode::Sync => "authentication failed; pair again",
+    };
+    let _ = send_plain(
+      &mut stream,
+      &Handshake::Reject {
+        reason: reason.into(),
+      },
+    );
+    return Err(Error::Peer(reason.into()));
+  }
+  send_plain(
+    &mut stream,
+    &Handshake::Confirm {
+      mac: hex::encode(keys.confirmation(Role::Server)),
+    },
+  )?;
+  Ok(Session {
+    channel: Channel::new(stream, &keys.server_key, &keys.client_key),
+    peer: from,
+    address,
+    mode,
+    pairing_secret: keys.pairing_secret,
+  })
+}
+
+/// Encrypted JSON messages in both directions.
+pub struct Channel {
+  stream: TcpStream,
+  sealer: ChaCha20Poly1305,
+  opener: ChaCha20Poly1305,
+  sent: u64,
+  received: u64,
+}
+
+fn nonce(counter: u64) -> Nonce {
+  let mut nonce = [0u8; 12];
+  nonce[4..].copy_from_slice(&counter.to_be_bytes());
+  nonce.into()
+}
+
+impl Channel {
+  fn new(stream: TcpStream, send_key: &[u8; 32], receive_key: &[u8; 32]) -> Self {
+    Channel {
+      stream,
+      sealer: ChaCha20Poly1305::new(Key::from_slice(send_key)),
+      opener: ChaCha20Poly1305::new(Key::from_slice(receive_key)),
+      sent: 0,
+      received: 0,
+    }
+  }
+
+  fn seal(&mut self, plain: &[u8]) -> Result<Vec<u8>> {
+    let sealed = self
+      .sealer
+      .encrypt(&nonce(self.sent), plain)
+      .map_err(|_| Error::Peer("encryption failed".into()))?;
+    self.sent += 1;
+    Ok(sealed)
+  }
+
+  pub fn send<T: Serialize>(&mut self, message: &T) -> Result<()> {
+    let sealed = self.seal(&serde_json::to_vec(message)?)?;
+    write_frame(&mut self.stream, &sealed, MAX_FRAME)
+  }
+
+  pub fn recv<T: DeserializeOwned>(&mut self) -> Result<T> {
+    let sealed = read_frame(&mut self.stream, MAX_FRAME)?;
+    let plain = self
+      .opener
+      .decrypt(&nonce(self.received), sealed.as_slice())
+      .map_err(|_| Error::Peer("a message failed authentication".into()))?;
+    self.received += 1;
+    Ok(serde_json::from_slice(&plain)?)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::net::TcpListener;
+
+  use super::*;
+
+  fn pair() -> (Channel, Channel) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (server, _) = listener.accept().unwrap();
+    let (a, b) = ([1u8; 32], [2u8; 32]);
+    (Channel::new(client, &a, &b), Channel::new(server, &b, &a))
+  }
+
+  #[test]
+  fn tampered_and_replayed_frames_are_refused() {
+    let (mut client, mut server) = pair();
+    client.send(&"hello").unwrap();
+    assert_eq!(server.recv::<String>().unwrap(), "hello");
+
+    let mut sealed = client.seal(br#""second""#).unwrap();
+    write_frame(&mut client.stream, &sealed, MAX_FRAME).unwrap();
+    assert_eq!(server.recv::<String>().unwrap(), "second");
+    // The same frame again is out of sequence.
+    write_frame(&mut client.stream, &sealed, MAX_FRAME).unwrap();
+    assert!(server.recv::<String>().is_err());
+
+    let (mut client, mut server) = pair();
+    sealed = client.seal(br#""hello""#).unwrap();
+    sealed[0] ^= 1;
+    write_frame(&mut client.stream, &sealed, MAX_FRAME).unwrap();
+    assert!(matches!(server.recv::<String>(), Err(Error::Peer(_))));
+  }
+
+  #[test]
+  fn a_stranger_cannot_announce_a_large_handshake() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (server, _) = listener.accept().unwrap();
+    client
+      .write_all(&(MAX_HANDSHAKE_FRAME as u32 + 1).to_be_bytes())
+      .unwrap();
+    let me = Identity {
+      replica: "replica".into(),
+      name: "me".into(),
+      port: None,
+    };
+    let Err(error) = accept(server, &me, |_, _| Ok(b"secret".to_vec())) else {
+      panic!("the handshake went through");
+    };
+    assert!(error.to_string().contains("too large"), "{error}");
+  }
+}
@@ -0,0 +1,97 @@
+//! Finding other Snail instances on the local network over mDNS / DNS-SD.
+//! Each instance advertises `_snail-sync._tcp` under its replica id, with
+//! its display name in the TXT record.
+
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+pub const SERVICE_TYPE: &str = "_snail-sync._tcp.local.";
+
+fn mdns_error(e: mdns_sd::Error) -> Error {
+  Error::Peer(format!("mDNS: {e}"))
+}
+
+/// Another instance seen on the network.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Discovered {
+  pub replica: String,
+  pub name: String,
+  /// `ip:port` pairs to try, IPv4 first.
+  pub addresses: Vec<String>,
+}
+
+/// Keeps this instance advertised until dropped.
+pub struct Advertisement {
+  daemon: ServiceDaemon,
+  fullname: String,
+}
+
+/// Announce this instance, listening on `port`.
+pub fn advertise(replica: &str, name: &str, port: u16) -> Result<Advertisement> {
+  let daemon = ServiceDaemon::new().map_err(mdns_error)?;
+  let host = format!("snail-{}.local.", &replica[..replica.len().min(12)]);
+  let properties = [("name", name)];
+  let info = ServiceInfo::new(SERVICE_TYPE, replica, &host, "", port, &properties[..])
+    .map_err(mdns_error)?
+    .enable_addr_auto();
+  let fullname = info.get_fullname().to_string();
+  daemon.register(info).map_err(mdns_error)?;
+  Ok(Advertisement { daemon, fullname })
+}
+
+impl Drop for Advertisement {
+  fn drop(&mut self) {
+    let _ = self.daemon.unregister(&self.fullname);
+    let _ = self.daemon.shutdown();
+  }
+}
+
+/// Listen for `timeout` and return every instance other than `own` that
+/// answered.
+pub fn discover(own: &str, timeout: Duration) -> Result<Vec<Discovered>> {
+  let daemon = ServiceDaemon::new().map_err(mdns_error)?;
+  let events = daemon.browse(SERVICE_TYPE).map_err(mdns_error)?;
+  let deadline = Instant::now() + timeout;
+  let mut found = BTreeMap::new();
+  while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+    let Ok(event) = events.recv_timeout(left) else {
+      break;
+    };
+    let ServiceEvent::ServiceResolved(info) = event else {
+      continue;
+    };
+    let replica = info
+      .get_fullname()
+      .strip_suffix(&format!(".{SERVICE_TYPE}"))
+      .unwrap_or_default()
+      .to_string();
+    if replica.is_empty() || replica == own {
+      continue;
+    }
+    let mut ips: Vec<IpAddr> = info.get_addresses().iter().copied().collect();
+    ips.sort_by_key(|ip| (ip.is_ipv6(), *ip));
+    found.insert(
+      replica.clone(),
+      Discovered {
+        name: info
+          .get_property_val_str("name")
+          .unwrap_or(&replica)
+          .to_string(),
+        replica,
+        addresses: ips
+          .into_iter()
+          .map(|ip| SocketAddr::new(ip, info.get_port()).to_string())
+          .collect(),
+      },
+    );
+  }
+  let _ = daemon.shutdown();
+  Ok(found.into_values().collect())
+}
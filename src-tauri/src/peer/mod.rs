@@ -0,0 +1,757 @@
+//! Sync between Snail instances on the same network, without Supabase.
+//!
+//! Instances find each other over mDNS ([`discovery`]) and pair once with
+//! a six-digit code shown by the instance being paired with. The code only
+//! seeds a password-authenticated key exchange ([`pake`]), so it never
+//! crosses the network and a wrong guess burns it; both sides then keep a
+//! secret that authenticates every later sync. Everything after the
+//! handshake is encrypted ([`channel`]).
+//!
+//! Changes come from the same `sync_outbox` log the Supabase engine reads
+//! ([`crate::sync`]): each peer keeps the last seq it has been sent, and a
+//! peer that has never synced gets a full copy. Tasks merge field by field
+//! through [`crate::crdt`]. A project or tag changed on both sides keeps
+//! the newer row by `updated_at`, or failing that the same row on both
+//! sides. A row deleted on one side while edited on the other stays
+//! deleted. What a sync writes is not sent back to the peer it came from.
+//!
+//! A sync session: the client sends its changes, the server applies them
+//! and answers with its own, the client applies those and says it is done.
+
+mod channel;
+pub mod discovery;
+mod pake;
+mod server;
+
+use std::collections::BTreeMap;
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::crdt::{self, TaskState};
+use crate::error::{Error, Result};
+use crate::storage::models::UserSettings;
+use crate::storage::{now_iso, Store, OFFLINE_USER_ID};
+use crate::sync;
+use channel::{Identity, Mode, Session};
+pub use server::{PeerServer, PAIRING_TTL};
+
+pub const SETTINGS_KEY: &str = "peer_sync";
+pub const DEFAULT_PORT: u16 = 47_614;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PeerSettings {
+  /// Accept connections and advertise this instance.
+  pub enabled: bool,
+  pub port: u16,
+  /// Shown to other instances; empty for a name made from the replica id.
+  pub name: String,
+}
+
+impl Default for PeerSettings {
+  fn default() -> Self {
+    Self {
+      enabled: false,
+      port: DEFAULT_PORT,
+      name: String::new(),
+    }
+  }
+}
+
+impl PeerSettings {
+  pub fn from_settings(settings: &UserSettings) -> Self {
+    settings
+      .0
+      .get(SETTINGS_KEY)
+      .and_then(|v| serde_json::from_value(v.clone()).ok())
+      .unwrap_or_default()
+  }
+}
+
+/// A paired instance.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Peer {
+  /// Its replica id.
+  pub id: String,
+  pub name: String,
+  /// Where it was last reached, `ip:port`.
+  pub address: Option<String>,
+  pub paired_at: String,
+  pub last_synced_at: Option<String>,
+}
+
+fn peer_from_row(row: &Row<'_>) -> rusqlite::Result<Peer> {
+  Ok(Peer {
+    id: row.get("id")?,
+    name: row.get("name")?,
+    address: row.get("address")?,
+    paired_at: row.get("paired_at")?,
+    last_synced_at: row.get("last_synced_at")?,
+  })
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerReport {
+  /// Rows and tasks sent to the peer.
+  pub sent: usize,
+  /// Incoming rows and tasks that changed something here.
+  pub received: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Deletion {
+  table: String,
+  id: String,
+}
+
+/// What one side has that the other may not.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ChangeSet {
+  projects: Vec<Map<String, Value>>,
+  tags: Vec<Map<String, Value>>,
+  tasks: Vec<TaskState>,
+  /// Children first.
+  deleted: Vec<Deletion>,
+}
+
+impl ChangeSet {
+  fn len(&self) -> usize {
+    self.projects.len() + self.tags.len() + self.tasks.len() + self.deleted.len()
+  }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "camelCase")]
+enum Message {
+  Changes(ChangeSet),
+  Done,
+}
+
+/// The last seq the peer was sent, `None` before its first sync.
+fn sent_seq(conn: &Connection, peer: &str) -> Result<Option<i64>> {
+  Ok(
+    conn
+      .query_row("select sent_seq from peers where id = ?1", [peer], |row| {
+        row.get(0)
+      })
+      .optional()?
+      .flatten(),
+  )
+}
+
+/// Whether the row was changed here (or only deleted, with `op`) after
+/// the peer was last sent changes, so the change is on its way to the peer.
+fn logged_since(
+  conn: &Connection,
+  table: &str,
+  id: &str,
+  after: i64,
+  op: Option<&str>,
+) -> Result<bool> {
+  Ok(
+    conn
+      .query_row(
+        "select 1 from sync_outbox
+         where table_name = ?1 and row_id = ?2 and seq > ?3 and (?4 is null or op = ?4)",
+        params![table, id, after, op],
+        |_| Ok(()),
+      )
+      .optional()?
+      .is_some(),
+  )
+}
+
+/// The newest seq logged so far.
+fn last_seq(conn: &Connection) -> Result<i64> {
+  Ok(conn.query_row(
+    "select coalesce((select seq from sqlite_sequence where name = 'sync_outbox'), 0)",
+    [],
+    |row| row.get(0),
+  )?)
+}
+
+/// Everything the peer has not been sent, and the seq it covers.
+fn export(conn: &Connection, peer: &str) -> Result<(ChangeSet, i64)> {
+  let upto = last_seq(conn)?;
+  let rows: Vec<(String, String)> = match sent_seq(conn, peer)? {
+    Some(after) => {
+      let mut stmt = conn.prepare(
+        "select table_name, row_id from sync_outbox where seq > ?1
+         group by table_name, row_id order by max(seq)",
+      )?;
+      let rows = stmt
+        .query_map([after], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+      rows
+    }
+    None => {
+      let mut stmt = conn.prepare(
+        "select 'projects', id from projects
+         union all select 'tags', id from tags
+         union all select 'tasks', id from tasks",
+      )?;
+      let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+      rows
+    }
+  };
+  // Tag links travel inside their task's state.
+  let mut tasks = BTreeMap::new();
+  let mut set = ChangeSet::default();
+  for (table, id) in rows {
+    match table.as_str() {
+      "tasks" | "task_tags" => {
+        let task = id.split('/').next().unwrap_or_default().to_string();
+        *tasks.entry(task).or_insert(false) |= table == "tasks";
+      }
+      "projects" | "tags" => {
+        let Some(spec) = sync::table(&table) else {
+          continue;
+        };
+        match sync::read_row(conn, spec, &id)? {
+          Some(row) if table == "projects" => set.projects.push(row),
+          Some(row) => set.tags.push(row),
+          None => set.deleted.push(Deletion { table, id }),
+        }
+      }
+      // Pomodoro sessions and other history only sync through Supabase.
+      _ => {}
+    }
+  }
+  for (id, logged) in tasks {
+    match crdt::read_state(conn, &id)? {
+      Some(state) => set.tasks.push(state),
+      // A link to a task that is gone says nothing; the task's own
+      // deletion is logged too.
+      None if logged => set.deleted.push(Deletion {
+        table: "tasks".into(),
+        id,
+      }),
+      None => {}
+    }
+  }
+  set.deleted.sort_by_key(|d| {
+    ["tasks", "tags", "projects"]
+      .iter()
+      .position(|t| *t == d.table)
+  });
+  Ok((set, upto))
+}
+
+/// Whether the peer's version of a row changed on both sides wins: the
+/// later `updated_at` for projects, otherwise (tags have no such column)
+/// the larger row, which both sides agree on.
+fn remote_wins(remote: &Map<String, Value>, local: &Map<String, Value>) -> bool {
+  let at = |row: &Map<String, Value>| {
+    row
+      .get("updated_at")
+      .and_then(Value::as_str)
+      .map(str::to_string)
+  };
+  let (ours, theirs) = (at(local), at(remote));
+  if ours != theirs {
+    return sync::later(theirs.as_deref(), ours.as_deref());
+  }
+  let key = |row: &Map<String, Value>| {
+    let mut row = row.clone();
+    row.remove("user_id");
+    Value::Object(row).to_string()
+  };
+  key(remote) > key(local)
+}
+
+/// Insert or update a project or tag from a peer; returns whether it did.
+/// A row deleted here stays deleted.
+fn apply_row(
+  conn: &Connection,
+  table: &str,
+  mut row: Map<String, Value>,
+  after: i64,
+) -> Result<bool> {
+  let spec = sync::table(table).expect("projects and tags are synced");
+  let Some(id) = row.get("id").and_then(Value::as_str).map(str::to_string) else {
+    return Ok(false);
+  };
+  match sync::read_row(conn, spec, &id)? {
+    None if logged_since(conn, table, &id, after, Some("delete"))? => return Ok(false),
+    None => {
+      row.insert("user_id".into(), OFFLINE_USER_ID.into());
+    }
+    Some(local) => {
+      if sync::same_row(spec, &local, &row)
+        || (logged_since(conn, table, &id, after, None)? && !remote_wins(&row, &local))
+      {
+        return Ok(false);
+      }
+      row.remove("user_id");
+    }
+  }
+  sync::write_row(conn, spec, &row)?;
+  Ok(true)
+}
+
+/// Apply what the peer sent, whose cursor is about to move to `upto`.
+/// Returns how much of it changed something here, and how far the cursor
+/// can move instead: the rows this logs only echo what the peer has, so
+/// unless something else was logged since `upto` they count as sent.
+fn apply(conn: &Connection, peer: &str, set: &ChangeSet, upto: i64) -> Result<(usize, i64)> {
+  let after = sent_seq(conn, peer)?.unwrap_or(0);
+  let before = last_seq(conn)?;
+  let mut applied = 0;
+  for row in &set.projects {
+    applied += usize::from(apply_row(conn, "projects", row.clone(), after)?);
+  }
+  for row in &set.tags {
+    applied += usize::from(apply_row(conn, "tags", row.clone(), after)?);
+  }
+  for state in &set.tasks {
+    let exists = crdt::read_state(conn, &state.id)?.is_some();
+    if !exists && logged_since(conn, "tasks", &state.id, after, Some("delete"))? {
+      continue;
+    }
+    applied += usize::from(crdt::merge_state(conn, state)?.is_some_and(|(_, changed)| changed));
+  }
+  for deletion in &set.deleted {
+    let Some(spec) = sync::table(&deletion.table) else {
+      continue;
+    };
+    if sync::read_row(conn, spec, &deletion.id)?.is_some() {
+      sync::delete_row(conn, spec, &deletion.id)?;
+      applied += 1;
+    }
+  }
+  let upto = if before == upto {
+    last_seq(conn)?
+  } else {
+    upto
+  };
+  Ok((applied, upto))
+}
+
+/// Record that the peer has everything up to `upto`.
+fn mark_synced(conn: &Connection, peer: &str, upto: i64, address: Option<&str>) -> Result<()> {
+  conn.execute(
+    "update peers set sent_seq = max(coalesce(sent_seq, 0), ?2), last_synced_at = ?3,
+       address = coalesce(?4, address)
+     where id = ?1",
+    params![peer, upto, now_iso(), address],
+  )?;
+  sync::prune_outbox(conn)
+}
+
+/// Where the peer listens: the address it connected from with the port it
+/// announced.
+fn listening_address(session: &Session) -> Option<String> {
+  session
+    .peer
+    .port
+    .map(|port| std::net::SocketAddr::new(session.address.ip(), port).to_string())
+}
+
+impl Store {
+  pub fn peer_settings(&self) -> Result<PeerSettings> {
+    Ok(PeerSettings::from_settings(&self.get_user_settings()?))
+  }
+
+  pub fn save_peer_settings(&self, settings: PeerSettings) -> Result<PeerSettings> {
+    if settings.port < 1024 {
+      return Err(Error::InvalidInput(format!(
+        "port {} is reserved; use 1024 or above",
+        settings.port
+      )));
+    }
+    let settings = PeerSettings {
+      name: settings.name.trim().to_string(),
+      ..settings
+    };
+    let mut map = serde_json::Map::new();
+    map.insert(SETTINGS_KEY.into(), serde_json::to_value(&settings)?);
+    self.save_user_settings(UserSettings(map))?;
+    Ok(settings)
+  }
+
+  /// The name other instances see for this one.
+  pub fn peer_name(&self) -> Result<String> {
+    let name = self.peer_settings()?.name;
+    if !name.is_empty() {
+      return Ok(name);
+    }
+    Ok(format!("Snail {}", &self.replica_id()?[..6]))
+  }
+
+  fn identity(&self) -> Result<Identity> {
+    let settings = self.peer_settings()?;
+    Ok(Identity {
+      replica: self.replica_id()?,
+      name: self.peer_name()?,
+      port: settings.enabled.then_some(settings.port),
+    })
+  }
+
+  pub fn peers(&self) -> Result<Vec<Peer>> {
+    let conn = self.conn();
+    let mut stmt = conn.prepare("select * from peers order by paired_at")?;
+    let peers = stmt
+      .query_map([], peer_from_row)?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(peers)
+  }
+
+  /// Forget a peer; it has to pair again to sync.
+  pub fn unpair_peer(&self, id: &str) -> Result<bool> {
+    self.transaction(|tx| {
+      let removed = tx.execute("delete from peers where id = ?1", [id])?;
+      sync::prune_outbox(tx)?;
+      Ok(removed > 0)
+    })
+  }
+
+  fn save_peer(&self, session: &Session, address: Option<String>) -> Result<Peer> {
+    let peer = Peer {
+      id: session.peer.replica.clone(),
+      name: session.peer.name.clone(),
+      address,
+      paired_at: now_iso(),
+      last_synced_at: None,
+    };
+    // Pairing again starts over with a full copy.
+    self.conn().execute(
+      "insert or replace into peers (id, name, secret, address, sent_seq, paired_at)
+       values (?1, ?2, ?3, ?4, null, ?5)",
+      params![
+        peer.id,
+        peer.name,
+        hex::encode(session.pairing_secret),
+        peer.address,
+        peer.paired_at
+      ],
+    )?;
+    Ok(peer)
+  }
+
+  /// Pair with the instance at `address` (`host:port`) using the code it
+  /// shows.
+  pub fn pair_with_peer(&self, address: &str, code: &str) -> Result<Peer> {
+    let code = code.trim();
+    if code.len() != server::CODE_DIGITS || !code.bytes().all(|b| b.is_ascii_digit()) {
+      return Err(Error::InvalidInput(format!(
+        "a pairing code has {} digits",
+        server::CODE_DIGITS
+      )));
+    }
+    let mut session = channel::connect(
+      address,
+      &self.identity()?,
+      Mode::Pair,
+      format!("code:{code}").as_bytes(),
+    )?;
+    // Keep the pairing only once the other side has kept it too.
+    let Message::Done = session.channel.recv()? else {
+      return Err(Error::Peer("the peer did not finish pairing".into()));
+    };
+    self.save_peer(&session, Some(session.address.to_string()))
+  }
+
+  /// Sync with a paired peer at `address`, or where it was last reached.
+  pub fn sync_with_peer(&self, id: &str, address: Option<&str>) -> Result<PeerReport> {
+    let (secret, known): (String, Option<String>) = self
+      .conn()
+      .query_row(
+        "select secret, address from peers where id = ?1",
+        [id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+      )
+      .optional()?
+      .ok_or_else(|| Error::NotFound("peer", id.to_string()))?;
+    let address = address
+      .map(str::to_string)
+      .or(known)
+      .ok_or_else(|| Error::Peer("no known address for this peer".into()))?;
+    let mut session = channel::connect(
+      &address,
+      &self.identity()?,
+      Mode::Sync,
+      format!("key:{secret}").as_bytes(),
+    )?;
+    if session.peer.replica != id {
+      return Err(Error::Peer(format!("{address} is a different instance")));
+    }
+    let (ours, upto) = self.transaction(|tx| export(tx, id))?;
+    session.channel.send(&Message::Changes(ours.clone()))?;
+    let Message::Changes(theirs) = session.channel.recv()? else {
+      return Err(Error::Peer("the peer ended the sync early".into()));
+    };
+    let received = self.transaction(|tx| {
+      let (received, upto) = apply(tx, id, &theirs, upto)?;
+      mark_synced(tx, id, upto, Some(&address))?;
+      Ok(received)
+    })?;
+    session.channel.send(&Message::Done)?;
+    Ok(PeerReport {
+      sent: ours.len(),
+      received,
+    })
+  }
+
+  /// The server's half of [`Store::sync_with_peer`].
+  fn answer_sync(&self, session: &mut Session) -> Result<PeerReport> {
+    let peer = session.peer.replica.clone();
+    let Message::Changes(theirs) = session.channel.recv()? else {
+      return Err(Error::Peer("the peer sent no changes".into()));
+    };
+    // Export before applying, so a row the client deleted and this side
+    // edited is still seen as deleted over there.
+    let (ours, upto, received) = self.transaction(|tx| {
+      let (ours, upto) = export(tx, &peer)?;
+      let (received, upto) = apply(tx, &peer, &theirs, upto)?;
+      Ok((ours, upto, received))
+    })?;
+    session.channel.send(&Message::Changes(ours.clone()))?;
+    let Message::Done = session.channel.recv()? else {
+      return Err(Error::Peer("the peer did not finish the sync".into()));
+    };
+    let address = listening_address(session);
+    self.transaction(|tx| mark_synced(tx, &peer, upto, address.as_deref()))?;
+    Ok(PeerReport {
+      sent: ours.len(),
+      received,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::io::{BufRead, BufReader, Write};
+  use std::net::TcpStream;
+  use std::process::{Command, Stdio};
+  use std::sync::Arc;
+  use std::thread;
+  use std::time::Duration;
+
+  use serde_json::json;
+
+  use super::*;
+  use crate::storage::models::{Project, Task};
+
+  /// Set for the child process of [`syncs_between_two_processes`].
+  const CHILD_DB: &str = "SNAIL_PEER_TEST_DB";
+
+  fn task(store: &Store, title: &str) -> Task {
+    store
+      .create_task(Task {
+        title: title.into(),
+        ..Default::default()
+      })
+      .unwrap()
+  }
+
+  fn title(store: &Store, id: &str) -> Option<String> {
+    store.get_task_by_id(id).unwrap().map(|t| t.title)
+  }
+
+  /// Serve `store` on a free port, advertising it in the settings so the
+  /// other side learns where to reach it.
+  fn serve(store: &Arc<Store>) -> PeerServer {
+    let server = PeerServer::start(store.clone(), 0, |_, _| {}).unwrap();
+    store
+      .save_peer_settings(PeerSettings {
+        enabled: true,
+        port: server.port(),
+        name: String::new(),
+      })
+      .unwrap();
+    server
+  }
+
+  #[test]
+  fn a_wrong_code_is_refused_and_burns_the_code() {
+    let here = Store::open_in_memory().unwrap();
+    let there = Arc::new(Store::open_in_memory().unwrap());
+    let server = serve(&there);
+    let address = format!("127.0.0.1:{}", server.port());
+
+    let code = server.new_pairing_code();
+    let wrong = format!("{:06}", (code.parse::<u32>().unwrap() + 1) % 1_000_000);
+    assert!(matches!(
+      here.pair_with_peer(&address, &wrong),
+      Err(Error::Peer(_))
+    ));
+    assert!(here.pair_with_peer(&address, &code).is_err());
+    assert!(here.peers().unwrap().is_empty() && there.peers().unwrap().is_empty());
+
+    let peer = here
+      .pair_with_peer(&address, &server.new_pairing_code())
+      .unwrap();
+    assert_eq!(peer.id, there.replica_id().unwrap());
+    assert_eq!(there.peers().unwrap()[0].id, here.replica_id().unwrap());
+  }
+
+  #[test]
+  fn a_flood_of_strangers_neither_piles_up_nor_burns_the_code() {
+    let here = Store::open_in_memory().unwrap();
+    let there = Arc::new(Store::open_in_memory().unwrap());
+    let server = serve(&there);
+    let address = format!("127.0.0.1:{}", server.port());
+    let code = server.new_pairing_code();
+
+    let idle: Vec<TcpStream> = (0..server::MAX_HANDSHAKES)
+      .map(|_| TcpStream::connect(&address).unwrap())
+      .collect();
+    thread::sleep(Duration::from_millis(200));
+    assert!(here.pair_with_peer(&address, &code).is_err());
+    drop(idle);
+    thread::sleep(Duration::from_millis(200));
+    here.pair_with_peer(&address, &code).unwrap();
+  }
+
+  #[test]
+  fn concurrent_edits_converge_and_a_deletion_wins() {
+    let here = Store::open_in_memory().unwrap();
+    let there = Arc::new(Store::open_in_memory().unwrap());
+    let server = serve(&there);
+    let address = format!("127.0.0.1:{}", server.port());
+    let peer = here
+      .pair_with_peer(&address, &server.new_pairing_code())
+      .unwrap();
+    let doomed = task(&here, "doomed");
+    let kept = task(&here, "kept");
+    let tag = here.create_tag("tag", None).unwrap();
+    let report = here.sync_with_peer(&peer.id, None).unwrap();
+    assert_eq!(report.sent, 3);
+    assert_eq!(title(&there, &doomed.id).as_deref(), Some("doomed"));
+
+    there.delete_task(&doomed.id).unwrap();
+    there
+      .update_task(&kept.id, json!({ "completed": true }).as_object().unwrap())
+      .unwrap();
+    here
+      .update_task(
+        &doomed.id,
+        json!({ "title": "edited" }).as_object().unwrap(),
+      )
+      .unwrap();
+    here
+      .update_task(&kept.id, json!({ "title": "renamed" }).as_object().unwrap())
+      .unwrap();
+    for (store, name) in [(&here, "ours"), (&*there, "theirs")] {
+      store
+        .update_tag(&tag.id, json!({ "name": name }).as_object().unwrap())
+        .unwrap();
+    }
+    here.sync_with_peer(&peer.id, None).unwrap();
+
+    let tag_name = |store: &Store| store.get_tag_by_id(&tag.id).unwrap().unwrap().name;
+    assert_eq!(tag_name(&here), tag_name(&there));
+    for store in [&here, &*there] {
+      assert_eq!(title(store, &doomed.id), None);
+      let kept = store.get_task_by_id(&kept.id).unwrap().unwrap();
+      assert_eq!(kept.title, "renamed");
+      assert!(kept.completed);
+    }
+    // Nothing is left to say once both sides agree.
+    assert_eq!(here.sync_with_peer(&peer.id, None).unwrap().received, 0);
+    assert_eq!(here.sync_with_peer(&peer.id, None).unwrap().sent, 0);
+  }
+
+  /// The other process of [`syncs_between_two_processes`]: serves its own
+  /// database and syncs back when told to on stdin.
+  #[test]
+  #[ignore = "run by syncs_between_two_processes"]
+  fn peer_child() {
+    let Ok(path) = std::env::var(CHILD_DB) else {
+      return;
+    };
+    let store = Arc::new(Store::open(&path).unwrap());
+    task(&store, "from the child");
+    let server = serve(&store);
+    println!("READY {} {}", server.port(), server.new_pairing_code());
+    for line in std::io::stdin().lock().lines() {
+      let line = line.unwrap();
+      let Some(peer) = line.strip_prefix("sync ") else {
+        break;
+      };
+      let report = store.sync_with_peer(peer, None).unwrap();
+      println!("SYNCED {}", report.received);
+    }
+  }
+
+  #[test]
+  fn syncs_between_two_processes() {
+    let dir = tempfile::tempdir().unwrap();
+    let child_db = dir.path().join("child.db");
+    let mut child = Command::new(std::env::current_exe().unwrap())
+      .args([
+        "peer::tests::peer_child",
+        "--exact",
+        "--ignored",
+        "--nocapture",
+      ])
+      .env(CHILD_DB, &child_db)
+      .stdin(Stdio::piped())
+      .stdout(Stdio::piped())
+      .spawn()
+      .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+    let mut expect = |prefix: &str| -> Vec<String> {
+      loop {
+        let line = lines.next().expect("child exited early").unwrap();
+        // The test harness may have started the line.
+        if let Some((_, rest)) = line.split_once(prefix) {
+          return rest.split_whitespace().map(String::from).collect();
+        }
+      }
+    };
+    let ready = expect("READY ");
+
+    let store = Arc::new(Store::open(dir.path().join("parent.db")).unwrap());
+    let _server = serve(&store);
+    let project = store
+      .create_project(Project {
+        name: "Shared".into(),
+        ..Default::default()
+      })
+      .unwrap();
+    let ours = task(&store, "from the parent");
+    let peer = store
+      .pair_with_peer(&format!("127.0.0.1:{}", ready[0]), &ready[1])
+      .unwrap();
+    let report = store.sync_with_peer(&peer.id, None).unwrap();
+    assert_eq!(report.received, 1);
+    let theirs = store
+      .get_tasks(&Default::default(), &[])
+      .unwrap()
+      .into_iter()
+      .find(|t| t.title == "from the child")
+      .expect("the child's task arrived");
+
+    // The child reaches back using the port announced while pairing.
+    store
+      .update_task(&ours.id, json!({ "title": "edited" }).as_object().unwrap())
+      .unwrap();
+    writeln!(stdin, "sync {}", store.replica_id().unwrap()).unwrap();
+    assert_eq!(expect("SYNCED "), ["1"]);
+    drop(stdin);
+    assert!(child.wait().unwrap().success());
+
+    let child_store = Store::open(&child_db).unwrap();
+    assert_eq!(title(&child_store, &ours.id).as_deref(), Some("edited"));
+    assert_eq!(
+      title(&child_store, &theirs.id).as_deref(),
+      Some("from the child")
+    );
+    assert!(child_store
+      .get_project_by_id(&project.id)
+      .unwrap()
+      .is_some());
+    assert_eq!(
+      child_store.peers().unwrap()[0].id,
+      store.replica_id().unwrap()
+    );
+  }
+}
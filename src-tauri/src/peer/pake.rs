@@ -0,0 +1,159 @@
+//! SPAKE2 (the `spake2` crate, over Ed25519): both sides prove they know
+//! the same password and agree on fresh keys, without the exchange
+//! revealing anything to guess the password from offline. That is what lets
+//! a short one-time code pair two devices: an attacker in the middle gets
+//! one guess per attempt, and a wrong guess burns the code. The keys used
+//! on the wire are derived from its shared secret with HKDF, and each side
+//! confirms it derived the same ones before anything else is sent.
+
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use spake2::{Ed25519Group, Identity, Password, Spake2};
+
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+  /// Opens the connection.
+  Client,
+  Server,
+}
+
+/// Keys shared after a successful exchange.
+pub struct SessionKeys {
+  /// Encrypts client-to-server messages.
+  pub client_key: [u8; 32],
+  /// Encrypts server-to-client messages.
+  pub server_key: [u8; 32],
+  /// Long-term secret stored by both sides after pairing.
+  pub pairing_secret: [u8; 32],
+  client_confirm: [u8; 32],
+  server_confirm: [u8; 32],
+  transcript: [u8; 32],
+}
+
+impl SessionKeys {
+  /// Proof that `role` derived the same keys.
+  pub fn confirmation(&self, role: Role) -> Vec<u8> {
+    let key = match role {
+      Role::Client => &self.client_confirm,
+      Role::Server => &self.server_confirm,
+    };
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("any key length");
+    mac.update(&self.transcript);
+    mac.finalize().into_bytes().to_vec()
+  }
+
+  /// Check the other side's [`SessionKeys::confirmation`] in constant time.
+  pub fn verify(&self, role: Role, proof: &[u8]) -> bool {
+    let key = match role {
+      Role::Client => &self.client_confirm,
+      Role::Server => &self.server_confirm,
+    };
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("any key length");
+    mac.update(&self.transcript);
+    mac.verify_slice(proof).is_ok()
+  }
+}
+
+/// One side of an exchange in progress.
+pub struct Pake {
+  role: Role,
+  state: Spake2<Ed25519Group>,
+  message: Vec<u8>,
+}
+
+impl Pake {
+  pub fn start(role: Role, password: &[u8]) -> Self {
+    let password = Password::new(password);
+    let client = Identity::new(b"snail peer sync client");
+    let server = Identity::new(b"snail peer sync server");
+    let (state, message) = match role {
+      Role::Client => Spake2::<Ed25519Group>::start_a(&password, &client, &server),
+      Role::Server => Spake2::<Ed25519Group>::start_b(&password, &client, &server),
+    };
+    Pake {
+      role,
+      state,
+      message,
+    }
+  }
+
+  /// What to send to the other side.
+  pub fn message(&self) -> &[u8] {
+    &self.message
+  }
+
+  /// Derive the session keys from the other side's message. `context`
+  /// binds everything else said in the handshake (ids, names, mode).
+  pub fn finish(self, theirs: &[u8], context: &[u8]) -> Result<SessionKeys> {
+    let (client, server) = match self.role {
+      Role::Client => (self.message.clone(), theirs.to_vec()),
+      Role::Server => (theirs.to_vec(), self.message.clone()),
+    };
+    let shared = self
+      .state
+      .finish(theirs)
+      .map_err(|_| Error::Peer("malformed key exchange message".into()))?;
+    let transcript: [u8; 32] = Sha256::new()
+      .chain_update(context)
+      .chain_update(&client)
+      .chain_update(&server)
+      .finalize()
+      .into();
+    let hkdf = Hkdf::<Sha256>::new(Some(&transcript), &shared);
+    let key = |label: &[u8]| {
+      let mut out = [0u8; 32];
+      hkdf
+        .expand(label, &mut out)
+        .expect("32 bytes is a valid length");
+      out
+    };
+    Ok(SessionKeys {
+      client_key: key(b"client to server"),
+      server_key: key(b"server to client"),
+      pairing_secret: key(b"pairing secret"),
+      client_confirm: key(b"client confirm"),
+      server_confirm: key(b"server confirm"),
+      transcript,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn exchange(client_password: &[u8], server_password: &[u8]) -> (SessionKeys, SessionKeys) {
+    let client = Pake::start(Role::Client, client_password);
+    let server = Pake::start(Role::Server, server_password);
+    let (to_server, to_client) = (client.message().to_vec(), server.message().to_vec());
+    (
+      client.finish(&to_client, b"ctx").unwrap(),
+      server.finish(&to_server, b"ctx").unwrap(),
+    )
+  }
+
+  #[test]
+  fn same_password_agrees_and_a_wrong_one_does_not() {
+    let (client, server) = exchange(b"123456", b"123456");
+    assert_eq!(client.client_key, server.client_key);
+    assert_eq!(client.pairing_secret, server.pairing_secret);
+    assert!(server.verify(Role::Client, &client.confirmation(Role::Client)));
+    assert!(client.verify(Role::Server, &server.confirmation(Role::Server)));
+
+    let (client, server) = exchange(b"123456", b"123457");
+    assert_ne!(client.client_key, server.client_key);
+    assert!(!server.verify(Role::Client, &client.confirmation(Role::Client)));
+
+    // Two clients cannot talk to each other, nor can a truncated message.
+    let (one, other) = (
+      Pake::start(Role::Client, b"123456"),
+      Pake::start(Role::Client, b"123456"),
+    );
+    assert!(one.finish(other.message(), b"ctx").is_err());
+    let server = Pake::start(Role::Server, b"123456");
+    assert!(server.finish(&[0u8; 5], b"ctx").is_err());
+  }
+}
@@ -0,0 +1,186 @@
+//! Accepting pairing and sync sessions from other instances.
+
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use rand_core::{OsRng, RngCore};
+
+use super::channel::{self, Mode};
+use super::{listening_address, Message, Peer, PeerReport};
+use crate::error::Result;
+use crate::storage::Store;
+
+pub(super) const CODE_DIGITS: usize = 6;
+/// How long a pairing code stays valid.
+pub const PAIRING_TTL: Duration = Duration::from_secs(5 * 60);
+/// Connections still in the handshake at once. More are closed right away,
+/// before they can take a pairing code or a thread.
+pub(super) const MAX_HANDSHAKES: usize = 8;
+
+struct PairingCode {
+  code: String,
+  expires: Instant,
+}
+
+type SyncHook = dyn Fn(&Peer, &PeerReport) + Send + Sync;
+
+/// One of the [`MAX_HANDSHAKES`], given back when dropped.
+struct HandshakeSlot(Arc<AtomicUsize>);
+
+impl HandshakeSlot {
+  fn claim(taken: &Arc<AtomicUsize>) -> Option<Self> {
+    taken
+      .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+        (n < MAX_HANDSHAKES).then_some(n + 1)
+      })
+      .ok()?;
+    Some(Self(taken.clone()))
+  }
+}
+
+impl Drop for HandshakeSlot {
+  fn drop(&mut self) {
+    self.0.fetch_sub(1, Ordering::SeqCst);
+  }
+}
+
+/// Listens on all interfaces so other machines can reach it. Dropping it
+/// stops the listener.
+pub struct PeerServer {
+  port: u16,
+  code: Arc<Mutex<Option<PairingCode>>>,
+  stop: Arc<AtomicBool>,
+  thread: Option<JoinHandle<()>>,
+}
+
+impl PeerServer {
+  /// Start listening on `port` (0 for any free port), serving the store
+  /// `store` leads to. `on_sync` runs after every sync a peer starts, e.g.
+  /// to refresh the UI.
+  pub fn start<S>(
+    store: S,
+    port: u16,
+    on_sync: impl Fn(&Peer, &PeerReport) + Send + Sync + 'static,
+  ) -> Result<Self>
+  where
+    S: AsRef<Store> + Clone + Send + 'static,
+  {
+    let listener = TcpListener::bind(("0.0.0.0", port))?;
+    let port = listener.local_addr()?.port();
+    let code: Arc<Mutex<Option<PairingCode>>> = Arc::default();
+    let stop = Arc::new(AtomicBool::new(false));
+    let on_sync: Arc<SyncHook> = Arc::new(on_sync);
+    let handshakes = Arc::new(AtomicUsize::new(0));
+    let thread = {
+      let (code, stop) = (code.clone(), stop.clone());
+      thread::spawn(move || {
+        for stream in listener.incoming() {
+          if stop.load(Ordering::SeqCst) {
+            break;
+          }
+          let Ok(stream) = stream else {
+            continue;
+          };
+          let Some(slot) = HandshakeSlot::claim(&handshakes) else {
+            log::warn!("too many peer handshakes at once; closing a connection");
+            continue;
+          };
+          let (store, code, on_sync) = (store.clone(), code.clone(), on_sync.clone());
+          thread::spawn(move || {
+            if let Err(e) = serve(store.as_ref(), stream, slot, &code, on_sync.as_ref()) {
+              log::warn!("peer session failed: {e}");
+            }
+          });
+        }
+      })
+    };
+    Ok(Self {
+      port,
+      code,
+      stop,
+      thread: Some(thread),
+    })
+  }
+
+  pub fn port(&self) -> u16 {
+    self.port
+  }
+
+  /// A fresh code for pairing one device, replacing any earlier one. It
+  /// expires after [`PAIRING_TTL`] or after a single attempt, right or
+  /// wrong.
+  pub fn new_pairing_code(&self) -> String {
+    let code = format!("{:06}", OsRng.next_u32() % 1_000_000);
+    *self.code.lock().unwrap_or_else(|e| e.into_inner()) = Some(PairingCode {
+      code: code.clone(),
+      expires: Instant::now() + PAIRING_TTL,
+    });
+    code
+  }
+}
+
+fn serve(
+  store: &Store,
+  stream: TcpStream,
+  slot: HandshakeSlot,
+  code: &Mutex<Option<PairingCode>>,
+  on_sync: &SyncHook,
+) -> Result<()> {
+  let me = store.identity()?;
+  let mut session = channel::accept(stream, &me, |mode, from| match mode {
+    Mode::Pair => {
+      let code = code.lock().unwrap_or_else(|e| e.into_inner()).take();
+      match code {
+        Some(code) if code.expires > Instant::now() => Ok(format!("code:{}", code.code).into()),
+        Some(_) => Err("the pairing code has expired".into()),
+        None => Err("no pairing code is active; create one first".into()),
+      }
+    }
+    Mode::Sync => {
+      let secret: Option<String> = store
+        .conn()
+        .query_row(
+          "select secret from peers where id = ?1",
+          [&from.replica],
+          |row| row.get(0),
+        )
+        .ok();
+      secret
+        .map(|secret| format!("key:{secret}").into())
+        .ok_or_else(|| "not paired with this instance".to_string())
+    }
+  })?;
+  drop(slot);
+  let address = listening_address(&session);
+  match session.mode {
+    Mode::Pair => {
+      store.save_peer(&session, address)?;
+      session.channel.send(&Message::Done)?;
+    }
+    Mode::Sync => {
+      let report = store.answer_sync(&mut session)?;
+      if let Some(peer) = store
+        .peers()?
+        .into_iter()
+        .find(|p| p.id == session.peer.replica)
+      {
+        on_sync(&peer, &report);
+      }
+    }
+  }
+  Ok(())
+}
+
+impl Drop for PeerServer {
+  fn drop(&mut self) {
+    self.stop.store(true, Ordering::SeqCst);
+    // Wake the accept loop so it sees the flag.
+    let _ = TcpStream::connect(("127.0.0.1", self.port));
+    if let Some(thread) = self.thread.take() {
+      let _ = thread.join();
+    }
+  }
+}
@@ -0,0 +1,546 @@
+//! Native pomodoro timer.
+//!
+//! The focus / short break / long break cycle used to run in the webview,
+//! where timers drift once the window is throttled in the background. Here
+//! the clock is wall time: a running phase remembers when it ends and each
+//! [`Timer::tick`] compares that with `now`, so a late tick never loses
+//! time. Sessions are written to `pomodoro_sessions` on start, complete and
+//! cancel, and the timer itself is kept in `pomodoro_timer` so a restart
+//! picks up where it left off.
+
+use chrono::{DateTime, SecondsFormat, TimeDelta, Utc};
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::error::Result;
+use crate::storage::models::{PomodoroSession, UserSettings};
+use crate::storage::{now_iso, Store, OFFLINE_USER_ID};
+
+/// User settings key the pomodoro page writes its settings to.
+pub const SETTINGS_KEY: &str = "pomodoro_settings";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+  #[default]
+  Focus,
+  ShortBreak,
+  LongBreak,
+}
+
+impl Phase {
+  /// `type` of the sessions this phase writes.
+  pub fn session_type(self) -> &'static str {
+    match self {
+      Phase::Focus => "work",
+      Phase::ShortBreak => "short_break",
+      Phase::LongBreak => "long_break",
+    }
+  }
+}
+
+/// Same fields, defaults and bounds as `usePomodoroSettings.ts`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PomodoroSettings {
+  /// Minutes.
+  pub focus_duration: i64,
+  pub short_break_duration: i64,
+  pub long_break_duration: i64,
+  pub cycles_before_long_break: u32,
+  pub auto_start_focus: bool,
+  pub auto_start_break: bool,
+  pub sound_enabled: bool,
+}
+
+impl Default for PomodoroSettings {
+  fn default() -> Self {
+    Self {
+      focus_duration: 25,
+      short_break_duration: 5,
+      long_break_duration: 15,
+      cycles_before_long_break: 4,
+      auto_start_focus: false,
+      auto_start_break: true,
+      sound_enabled: true,
+    }
+  }
+}
+
+impl PomodoroSettings {
+  /// Reads [`SETTINGS_KEY`]; missing or malformed fields fall back to the
+  /// defaults.
+  pub fn from_settings(settings: &UserSettings) -> Self {
+    settings
+      .0
+      .get(SETTINGS_KEY)
+      .and_then(|v| serde_json::from_value::<Self>(v.clone()).ok())
+      .unwrap_or_default()
+      .clamped()
+  }
+
+  pub fn clamped(self) -> Self {
+    Self {
+      focus_duration: self.focus_duration.clamp(1, 180),
+      short_break_duration: self.short_break_duration.clamp(1, 60),
+      long_break_duration: self.long_break_duration.clamp(1, 60),
+      cycles_before_long_break: self.cycles_before_long_break.clamp(1, 12),
+      ..self
+    }
+  }
+
+  pub fn minutes(&self, phase: Phase) -> i64 {
+    match phase {
+      Phase::Focus => self.focus_duration,
+      Phase::ShortBreak => self.short_break_duration,
+      Phase::LongBreak => self.long_break_duration,
+    }
+  }
+
+  fn auto_starts(&self, phase: Phase) -> bool {
+    match phase {
+      Phase::Focus => self.auto_start_focus,
+      _ => self.auto_start_break,
+    }
+  }
+
+  /// Phase and focus streak after `phase` ends. Only a completed focus
+  /// counts towards the long break; a skipped one goes to a short break.
+  pub fn next(&self, phase: Phase, focus_streak: u32, completed: bool) -> (Phase, u32) {
+    match phase {
+      Phase::Focus if completed => {
+        let streak = focus_streak + 1;
+        if streak >= self.cycles_before_long_break {
+          (Phase::LongBreak, 0)
+        } else {
+          (Phase::ShortBreak, streak)
+        }
+      }
+      Phase::Focus => (Phase::ShortBreak, focus_streak),
+      _ => (Phase::Focus, focus_streak),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+enum Clock {
+  #[default]
+  Idle,
+  Running {
+    ends_at: DateTime<Utc>,
+  },
+  Paused {
+    remaining_seconds: i64,
+  },
+}
+
+/// What the UI shows; emitted on every tick.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PomodoroState {
+  pub phase: Phase,
+  pub running: bool,
+  pub remaining_seconds: i64,
+  pub total_seconds: i64,
+  pub focus_streak: u32,
+  pub focus_target: u32,
+  pub upcoming_phase: Phase,
+  pub session: Option<PomodoroSession>,
+  pub sound_enabled: bool,
+}
+
+/// A move from one phase to the next, by completion or skip.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhaseChange {
+  pub from: Phase,
+  pub to: Phase,
+  /// False when the phase was skipped.
+  pub completed: bool,
+  /// The session that just ended.
+  pub session: Option<PomodoroSession>,
+  /// Whether the next phase started on its own.
+  pub auto_started: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Timer {
+  phase: Phase,
+  focus_streak: u32,
+  session: Option<PomodoroSession>,
+  clock: Clock,
+}
+
+fn iso(time: DateTime<Utc>) -> String {
+  time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn seconds_until(ends_at: DateTime<Utc>, now: DateTime<Utc>) -> i64 {
+  let ms = (ends_at - now).num_milliseconds().max(0);
+  (ms + 999) / 1000
+}
+
+impl Timer {
+  /// The saved timer, or a fresh one. A session that was finished or
+  /// removed elsewhere in the meantime is dropped.
+  pub fn load(store: &Store) -> Result<Self> {
+    let mut timer = store.load_pomodoro_timer()?.unwrap_or_default();
+    if let Some(session) = &timer.session {
+      let open = store
+        .get_pomodoro_session_by_id(&session.id)?
+        .is_some_and(|s| s.completed_at.is_none() && s.cancelled_at.is_none());
+      if !open {
+        timer.session = None;
+        timer.clock = Clock::Idle;
+      }
+    }
+    Ok(timer)
+  }
+
+  pub fn phase(&self) -> Phase {
+    self.phase
+  }
+
+  pub fn is_running(&self) -> bool {
+    matches!(self.clock, Clock::Running { .. })
+  }
+
+  pub fn state(&self, settings: &PomodoroSettings, now: DateTime<Utc>) -> PomodoroState {
+    let total_seconds = self
+      .session
+      .as_ref()
+      .map_or_else(|| settings.minutes(self.phase), |s| s.duration)
+      * 60;
+    let remaining_seconds = match &self.clock {
+      Clock::Idle => total_seconds,
+      Clock::Running { ends_at } => seconds_until(*ends_at, now),
+      Clock::Paused { remaining_seconds } => *remaining_seconds,
+    };
+    PomodoroState {
+      phase: self.phase,
+      running: self.is_running(),
+      remaining_seconds,
+      total_seconds,
+      focus_streak: self.focus_streak,
+      focus_target: settings.cycles_before_long_break,
+      upcoming_phase: settings.next(self.phase, self.focus_streak, true).0,
+      session: self.session.clone(),
+      sound_enabled: settings.sound_enabled,
+    }
+  }
+
+  /// Start a session for the current phase, or resume a paused one. The
+  /// title is only kept for focus sessions.
+  pub fn start(&mut self, store: &Store, title: Option<&str>, now: DateTime<Utc>) -> Result<()> {
+    match self.clock {
+      Clock::Running { .. } => return Ok(()),
+      Clock::Paused { remaining_seconds } if self.session.is_some() => {
+        self.clock = Clock::Running {
+          ends_at: now + TimeDelta::seconds(remaining_seconds),
+        };
+      }
+      _ => self.begin(store, &settings_of(store)?, title, now)?,
+    }
+    store.save_pomodoro_timer(self)
+  }
+
+  pub fn pause(&mut self, store: &Store, now: DateTime<Utc>) -> Result<()> {
+    if let Clock::Running { ends_at } = self.clock {
+      self.clock = Clock::Paused {
+        remaining_seconds: seconds_until(ends_at, now),
+      };
+      store.save_pomodoro_timer(self)?;
+    }
+    Ok(())
+  }
+
+  /// Cancel the current session and stay on the same phase.
+  pub fn reset(&mut self, store: &Store, now: DateTime<Utc>) -> Result<()> {
+    self.cancel(store, now)?;
+    store.save_pomodoro_timer(self)
+  }
+
+  /// Cancel the current session and move on without counting it.
+  pub fn skip(&mut self, store: &Store, now: DateTime<Utc>) -> Result<PhaseChange> {
+    let settings = settings_of(store)?;
+    let session = self.cancel(store, now)?;
+    let change = self.advance(store, &settings, session, false, now)?;
+    store.save_pomodoro_timer(self)?;
+    Ok(change)
+  }
+
+  /// Switch to `phase` by hand, cancelling whatever was running.
+  pub fn select(&mut self, store: &Store, phase: Phase, now: DateTime<Utc>) -> Result<()> {
+    self.cancel(store, now)?;
+    self.phase = phase;
+    store.save_pomodoro_timer(self)
+  }
+
+  /// Complete the running session once its time is up.
+  pub fn tick(&mut self, store: &Store, now: DateTime<Utc>) -> Result<Option<PhaseChange>> {
+    let Clock::Running { ends_at } = self.clock else {
+      return Ok(None);
+    };
+    if now < ends_at {
+      return Ok(None);
+    }
+    let settings = settings_of(store)?;
+    let session = match self.session.take() {
+      Some(session) => store.update_pomodoro_session(
+        &session.id,
+        json!({ "completed_at": iso(ends_at) }).as_object().unwrap(),
+      )?,
+      None => None,
+    };
+    self.clock = Clock::Idle;
+    let change = self.advance(store, &settings, session, true, now)?;
+    store.save_pomodoro_timer(self)?;
+    Ok(Some(change))
+  }
+
+  /// Bring the focus streak back in range after the cycle length changed.
+  pub fn apply_settings(&mut self, store: &Store, settings: &PomodoroSettings) -> Result<()> {
+    let max = settings.cycles_before_long_break.saturating_sub(1);
+    if self.focus_streak > max {
+      self.focus_streak = max;
+      store.save_pomodoro_timer(self)?;
+    }
+    Ok(())
+  }
+
+  fn begin(
+    &mut self,
+    store: &Store,
+    settings: &PomodoroSettings,
+    title: Option<&str>,
+    now: DateTime<Utc>,
+  ) -> Result<()> {
+    let minutes = settings.minutes(self.phase);
+    let title = title
+      .map(str::trim)
+      .filter(|t| self.phase == Phase::Focus && !t.is_empty());
+    let session = store.create_pomodoro_session(PomodoroSession {
+      user_id: Some(OFFLINE_USER_ID.to_string()),
+      duration: minutes,
+      kind: self.phase.session_type().to_string(),
+      started_at: iso(now),
+      title: title.map(Into::into),
+      ..Default::default()
+    })?;
+    self.session = Some(session);
+    self.clock = Clock::Running {
+      ends_at: now + TimeDelta::minutes(minutes),
+    };
+    Ok(())
+  }
+
+  fn cancel(&mut self, store: &Store, now: DateTime<Utc>) -> Result<Option<PomodoroSession>> {
+    self.clock = Clock::Idle;
+    let Some(session) = self.session.take() else {
+      return Ok(None);
+    };
+    store.update_pomodoro_session(
+      &session.id,
+      json!({ "cancelled_at": iso(now) }).as_object().unwrap(),
+    )
+  }
+
+  /// Move past the current phase; the next one starts on its own only when
+  /// a session actually ended and the settings ask for it.
+  fn advance(
+    &mut self,
+    store: &Store,
+    settings: &PomodoroSettings,
+    ended: Option<PomodoroSession>,
+    completed: bool,
+    now: DateTime<Utc>,
+  ) -> Result<PhaseChange> {
+    let from = self.phase;
+    let (to, streak) = settings.next(from, self.focus_streak, completed);
+    self.phase = to;
+    self.focus_streak = streak;
+    let auto_started = ended.is_some() && settings.auto_starts(to);
+    if auto_started {
+      self.begin(store, settings, None, now)?;
+    }
+    Ok(PhaseChange {
+      from,
+      to,
+      completed,
+      session: ended,
+      auto_started,
+    })
+  }
+}
+
+fn settings_of(store: &Store) -> Result<PomodoroSettings> {
+  Ok(PomodoroSettings::from_settings(&store.get_user_settings()?))
+}
+
+impl Store {
+  pub fn pomodoro_settings(&self) -> Result<PomodoroSettings> {
+    settings_of(self)
+  }
+
+  pub fn save_pomodoro_settings(&self, settings: PomodoroSettings) -> Result<PomodoroSettings> {
+    let settings = settings.clamped();
+    let mut map = serde_json::Map::new();
+    map.insert(SETTINGS_KEY.into(), serde_json::to_value(&settings)?);
+    self.save_user_settings(UserSettings(map))?;
+    Ok(settings)
+  }
+
+  fn load_pomodoro_timer(&self) -> Result<Option<Timer>> {
+    let state: Option<String> = self
+      .conn()
+      .query_row("select state from pomodoro_timer where id = 1", [], |row| {
+        row.get(0)
+      })
+      .optional()?;
+    // A timer saved by a different version is not worth failing over.
+    Ok(state.and_then(|s| serde_json::from_str(&s).ok()))
+  }
+
+  fn save_pomodoro_timer(&self, timer: &Timer) -> Result<()> {
+    self.conn().execute(
+      "insert into pomodoro_timer (id, state, updated_at) values (1, ?1, ?2)
+       on conflict (id) do update set state = excluded.state, updated_at = excluded.updated_at",
+      params![serde_json::to_string(timer)?, now_iso()],
+    )?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn store_with(settings: PomodoroSettings) -> Store {
+    let store = Store::open_in_memory().unwrap();
+    store.save_pomodoro_settings(settings).unwrap();
+    store
+  }
+
+  fn at(minutes: i64) -> DateTime<Utc> {
+    "2026-10-18T09:00:00Z".parse::<DateTime<Utc>>().unwrap() + TimeDelta::minutes(minutes)
+  }
+
+  #[test]
+  fn settings_are_clamped_and_default_when_missing() {
+    assert_eq!(
+      PomodoroSettings::from_settings(&UserSettings::default()),
+      PomodoroSettings::default()
+    );
+    let settings = UserSettings(
+      serde_json::from_value(json!({
+        SETTINGS_KEY: { "focusDuration": 500, "cyclesBeforeLongBreak": 0, "autoStartFocus": true }
+      }))
+      .unwrap(),
+    );
+    let parsed = PomodoroSettings::from_settings(&settings);
+    assert_eq!(parsed.focus_duration, 180);
+    assert_eq!(parsed.cycles_before_long_break, 1);
+    assert!(parsed.auto_start_focus);
+    assert_eq!(parsed.short_break_duration, 5);
+  }
+
+  #[test]
+  fn completes_on_wall_time_and_cycles_to_a_long_break() {
+    let store = store_with(PomodoroSettings {
+      cycles_before_long_break: 2,
+      auto_start_break: true,
+      auto_start_focus: false,
+      ..Default::default()
+    });
+    let mut timer = Timer::load(&store).unwrap();
+    timer.start(&store, Some(" write report "), at(0)).unwrap();
+    let session = timer.session.clone().unwrap();
+    assert_eq!(session.kind, "work");
+    assert_eq!(session.title.as_deref(), Some("write report"));
+
+    // Ticks may be late; remaining time still comes from the clock.
+    assert!(timer.tick(&store, at(10)).unwrap().is_none());
+    let state = timer.state(&store.pomodoro_settings().unwrap(), at(10));
+    assert_eq!(state.remaining_seconds, 15 * 60);
+    assert_eq!(state.upcoming_phase, Phase::ShortBreak);
+
+    let change = timer.tick(&store, at(26)).unwrap().unwrap();
+    assert_eq!((change.from, change.to), (Phase::Focus, Phase::ShortBreak));
+    assert!(change.completed && change.auto_started);
+    let done = store
+      .get_pomodoro_session_by_id(&session.id)
+      .unwrap()
+      .unwrap();
+    assert_eq!(
+      done.completed_at.as_deref(),
+      Some("2026-10-18T09:25:00.000Z")
+    );
+    assert_eq!(timer.session.as_ref().unwrap().kind, "short_break");
+
+    // The break ends and focus waits for the user.
+    let change = timer.tick(&store, at(31)).unwrap().unwrap();
+    assert_eq!(change.to, Phase::Focus);
+    assert!(!change.auto_started);
+    assert!(!timer.is_running());
+
+    timer.start(&store, None, at(40)).unwrap();
+    let change = timer.tick(&store, at(65)).unwrap().unwrap();
+    assert_eq!(change.to, Phase::LongBreak);
+    assert_eq!(timer.focus_streak, 0);
+  }
+
+  #[test]
+  fn pause_skip_and_reset_cancel_without_counting() {
+    let store = store_with(PomodoroSettings {
+      auto_start_break: false,
+      ..Default::default()
+    });
+    let mut timer = Timer::load(&store).unwrap();
+    timer.start(&store, None, at(0)).unwrap();
+    timer.pause(&store, at(5)).unwrap();
+    // Time spent paused does not count.
+    timer.start(&store, None, at(60)).unwrap();
+    let settings = store.pomodoro_settings().unwrap();
+    assert_eq!(timer.state(&settings, at(60)).remaining_seconds, 20 * 60);
+
+    let id = timer.session.clone().unwrap().id;
+    let change = timer.skip(&store, at(61)).unwrap();
+    assert_eq!(change.to, Phase::ShortBreak);
+    assert!(!change.completed && !change.auto_started);
+    assert_eq!(timer.focus_streak, 0);
+    let skipped = store.get_pomodoro_session_by_id(&id).unwrap().unwrap();
+    assert!(skipped.completed_at.is_none() && skipped.cancelled_at.is_some());
+
+    timer.start(&store, None, at(62)).unwrap();
+    timer.reset(&store, at(63)).unwrap();
+    assert_eq!(timer.phase(), Phase::ShortBreak);
+    assert!(timer.session.is_none());
+
+    timer.select(&store, Phase::LongBreak, at(64)).unwrap();
+    assert_eq!(timer.state(&settings, at(64)).total_seconds, 15 * 60);
+  }
+
+  #[test]
+  fn restores_after_a_restart() {
+    let store = store_with(PomodoroSettings::default());
+    let mut timer = Timer::load(&store).unwrap();
+    timer.start(&store, Some("deep work"), at(0)).unwrap();
+
+    let mut restored = Timer::load(&store).unwrap();
+    assert_eq!(restored, timer);
+    // The window was closed past the end of the session.
+    let change = restored.tick(&store, at(90)).unwrap().unwrap();
+    assert!(change.completed);
+
+    // A session finished elsewhere is not resumed.
+    let mut timer = Timer::load(&store).unwrap();
+    timer.select(&store, Phase::Focus, at(91)).unwrap();
+    timer.start(&store, None, at(91)).unwrap();
+    let id = timer.session.clone().unwrap().id;
+    store.delete_pomodoro_session(&id).unwrap();
+    let restored = Timer::load(&store).unwrap();
+    assert!(restored.session.is_none() && !restored.is_running());
+  }
+}
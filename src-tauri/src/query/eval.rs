@@ -0,0 +1,335 @@
+//! Running a parsed [`Query`] against the store.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use chrono::{Local, Months, NaiveDate, TimeDelta};
+
+use super::{Cmp, Cond, DateField, DateValue, Expr, Flag, HasField, Query, SortKey, TextField};
+use crate::error::Result;
+use crate::storage::models::{Project, SortField, Tag, Task, TaskFilter};
+use crate::storage::search::flatten_description;
+use crate::storage::{due_day, Store};
+
+/// What a query needs besides the task itself.
+pub struct Context<'a> {
+  pub today: NaiveDate,
+  pub projects: &'a [Project],
+  pub tags: &'a HashMap<String, Vec<Tag>>,
+}
+
+fn resolve(value: DateValue, today: NaiveDate) -> Option<NaiveDate> {
+  match value {
+    DateValue::Day(day) => Some(day),
+    DateValue::DaysFromToday(days) => today.checked_add_signed(TimeDelta::days(days)),
+    DateValue::MonthsFromToday(months) if months >= 0 => {
+      today.checked_add_months(Months::new(months.unsigned_abs()))
+    }
+    DateValue::MonthsFromToday(months) => {
+      today.checked_sub_months(Months::new(months.unsigned_abs()))
+    }
+  }
+}
+
+fn compare<T: PartialOrd>(left: T, cmp: Cmp, right: T) -> bool {
+  match cmp {
+    Cmp::Eq => left == right,
+    Cmp::Lt => left < right,
+    Cmp::Le => left <= right,
+    Cmp::Gt => left > right,
+    Cmp::Ge => left >= right,
+  }
+}
+
+fn date_of(task: &Task, field: DateField) -> Option<NaiveDate> {
+  let value = match field {
+    DateField::Due => &task.date,
+    DateField::Created => &task.created_at,
+    DateField::Updated => &task.updated_at,
+    DateField::Completed => &task.completed_at,
+    DateField::Deleted => &task.deleted_at,
+    DateField::Abandoned => &task.abandoned_at,
+  };
+  value.as_deref().and_then(due_day)
+}
+
+fn contains(haystack: &str, needle: &str) -> bool {
+  haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+fn description(task: &Task) -> String {
+  task
+    .description
+    .as_deref()
+    .map(flatten_description)
+    .unwrap_or_default()
+}
+
+impl Context<'_> {
+  fn tags_of(&self, task: &Task) -> &[Tag] {
+    self.tags.get(&task.id).map_or(&[], Vec::as_slice)
+  }
+
+  fn eval(&self, expr: &Expr, task: &Task) -> bool {
+    match expr {
+      Expr::And(items) => items.iter().all(|e| self.eval(e, task)),
+      Expr::Or(items) => items.iter().any(|e| self.eval(e, task)),
+      Expr::Not(inner) => !self.eval(inner, task),
+      Expr::Cond(cond) => self.test(cond, task),
+    }
+  }
+
+  fn test(&self, cond: &Cond, task: &Task) -> bool {
+    match cond {
+      Cond::Text(field, needle) => match field {
+        TextField::Any => {
+          contains(&task.title, needle)
+            || contains(&description(task), needle)
+            || self.tags_of(task).iter().any(|t| contains(&t.name, needle))
+        }
+        TextField::Title => contains(&task.title, needle),
+        TextField::Description => contains(&description(task), needle),
+        TextField::Icon => task.icon.as_deref().is_some_and(|i| contains(i, needle)),
+        TextField::Id => task.id.starts_with(needle.as_str()),
+        TextField::User => task.user_id.as_deref() == Some(needle.as_str()),
+      },
+      Cond::Project(None) => task.project.is_none(),
+      Cond::Project(Some(needle)) => task.project.as_deref().is_some_and(|id| {
+        id == needle
+          || self
+            .projects
+            .iter()
+            .any(|p| p.id == id && p.name.eq_ignore_ascii_case(needle))
+      }),
+      Cond::Tag(None) => self.tags_of(task).is_empty(),
+      Cond::Tag(Some(needle)) => self
+        .tags_of(task)
+        .iter()
+        .any(|t| t.id == *needle || t.name.eq_ignore_ascii_case(needle)),
+      Cond::Flag(flag) => match flag {
+        Flag::Completed => task.completed,
+        Flag::Flagged => task.flagged,
+        Flag::Deleted => task.deleted,
+        Flag::Abandoned => task.abandoned,
+      },
+      // Same rule as `isTaskExpired`: open and due before today.
+      Cond::Overdue => {
+        !task.completed && date_of(task, DateField::Due).is_some_and(|d| d < self.today)
+      }
+      Cond::Has(field) => match field {
+        HasField::Due => task.date.is_some(),
+        HasField::Description => !description(task).trim().is_empty(),
+        HasField::Attachments => !task.attachments.is_empty(),
+        HasField::Project => task.project.is_some(),
+        HasField::Tags => !self.tags_of(task).is_empty(),
+        HasField::Icon => task.icon.as_deref().is_some_and(|i| !i.is_empty()),
+      },
+      Cond::Date(field, cmp, value) => match (date_of(task, *field), resolve(*value, self.today)) {
+        (Some(day), Some(target)) => compare(day, *cmp, target),
+        _ => false,
+      },
+      Cond::NoDate(field) => date_of(task, *field).is_none(),
+      Cond::SortOrder(cmp, value) => task.sort_order.is_some_and(|o| compare(o, *cmp, *value)),
+    }
+  }
+}
+
+/// Missing values sort last in both directions, like `nullsFirst: false`.
+fn by_key<T: PartialOrd>(a: Option<T>, b: Option<T>, descending: bool) -> Ordering {
+  match (a, b) {
+    (Some(a), Some(b)) => {
+      let ord = a.partial_cmp(&b).unwrap_or(Ordering::Equal);
+      if descending {
+        ord.reverse()
+      } else {
+        ord
+      }
+    }
+    (Some(_), None) => Ordering::Less,
+    (None, Some(_)) => Ordering::Greater,
+    (None, None) => Ordering::Equal,
+  }
+}
+
+fn sort_value(task: &Task, field: SortField) -> Option<&str> {
+  match field {
+    SortField::SortOrder => None,
+    SortField::CreatedAt => task.created_at.as_deref(),
+    SortField::UpdatedAt => task.updated_at.as_deref(),
+    SortField::DeletedAt => task.deleted_at.as_deref(),
+    SortField::AbandonedAt => task.abandoned_at.as_deref(),
+    SortField::CompletedAt => task.completed_at.as_deref(),
+  }
+}
+
+fn order(a: &Task, b: &Task, key: SortKey, descending: bool) -> Ordering {
+  match key {
+    SortKey::Field(SortField::SortOrder) => by_key(a.sort_order, b.sort_order, descending),
+    SortKey::Field(field) => by_key(sort_value(a, field), sort_value(b, field), descending),
+    SortKey::Due => by_key(
+      date_of(a, DateField::Due),
+      date_of(b, DateField::Due),
+      descending,
+    ),
+    SortKey::Title => by_key(
+      Some(a.title.to_lowercase()),
+      Some(b.title.to_lowercase()),
+      descending,
+    ),
+  }
+}
+
+impl Query {
+  /// Whether `task` is in scope and passes the filter.
+  pub fn matches(&self, task: &Task, ctx: &Context<'_>) -> bool {
+    (!task.deleted || self.mentions(Flag::Deleted))
+      && (!task.abandoned || self.mentions(Flag::Abandoned))
+      && self.filter.as_ref().map_or(true, |e| ctx.eval(e, task))
+  }
+
+  /// Sort by the query's `sort:` keys, or by the list order (`sort_order`,
+  /// then newest first) when it has none.
+  pub fn sort_tasks(&self, tasks: &mut [Task]) {
+    let default = [
+      (SortKey::Field(SortField::SortOrder), false),
+      (SortKey::Field(SortField::CreatedAt), true),
+    ];
+    let keys = if self.sort.is_empty() {
+      &default[..]
+    } else {
+      &self.sort[..]
+    };
+    tasks.sort_by(|a, b| {
+      keys
+        .iter()
+        .map(|(key, descending)| order(a, b, *key, *descending))
+        .find(|o| o.is_ne())
+        .unwrap_or(Ordering::Equal)
+    });
+  }
+}
+
+impl Store {
+  /// Parse and run `query` with today's local date.
+  pub fn query_tasks(&self, query: &str) -> Result<Vec<Task>> {
+    self.run_query(&Query::parse(query)?, Local::now().date_naive())
+  }
+
+  pub fn run_query(&self, query: &Query, today: NaiveDate) -> Result<Vec<Task>> {
+    let filter = TaskFilter {
+      deleted: (!query.mentions(Flag::Deleted)).then_some(false),
+      abandoned: (!query.mentions(Flag::Abandoned)).then_some(false),
+      ..Default::default()
+    };
+    let tasks = self.get_tasks(&filter, &[])?;
+    let ids: Vec<String> = tasks.iter().map(|t| t.id.clone()).collect();
+    let tags = self.get_tags_by_task_ids(&ids)?;
+    let projects = self.get_projects()?;
+    let ctx = Context {
+      today,
+      projects: &projects,
+      tags: &tags,
+    };
+
+    let mut matched: Vec<Task> = tasks
+      .into_iter()
+      .filter(|t| query.matches(t, &ctx))
+      .collect();
+    query.sort_tasks(&mut matched);
+    if let Some(limit) = query.limit {
+      matched.truncate(limit);
+    }
+    Ok(matched)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn titles(tasks: &[Task]) -> Vec<&str> {
+    tasks.iter().map(|t| t.title.as_str()).collect()
+  }
+
+  #[test]
+  fn runs_queries_over_every_field() {
+    let store = Store::open_in_memory().unwrap();
+    let today = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+    let work = store
+      .create_project(Project {
+        name: "Work".into(),
+        ..Default::default()
+      })
+      .unwrap();
+    let urgent = store.create_tag("urgent", None).unwrap();
+    let add = |title: &str, fields: serde_json::Value| {
+      let task = store
+        .create_task(Task {
+          title: title.into(),
+          ..Default::default()
+        })
+        .unwrap();
+      store
+        .update_task(&task.id, fields.as_object().unwrap())
+        .unwrap()
+        .unwrap()
+    };
+
+    let report = add(
+      "Quarterly report",
+      json!({ "project": work.id, "date": "2026-10-20", "flagged": true, "sort_order": 2.0 }),
+    );
+    store.attach_tag_to_task(&report.id, &urgent.id).unwrap();
+    add(
+      "Old invoice",
+      json!({ "project": work.id, "date": "2026-10-01", "sort_order": 1.0 }),
+    );
+    add(
+      "周报",
+      json!({ "description": "[{\"type\":\"paragraph\",\"content\":[{\"type\":\"text\",\"text\":\"写完发给团队\"}]}]" }),
+    );
+    add(
+      "Done thing",
+      json!({ "completed": true, "date": "2026-10-17" }),
+    );
+    add("Gave up", json!({ "abandoned": true }));
+    add(
+      "Binned",
+      json!({ "deleted": true, "deleted_at": "2026-10-10T08:00:00.000Z" }),
+    );
+
+    let run = |q: &str| {
+      let query = Query::parse(q).unwrap();
+      titles(&store.run_query(&query, today).unwrap())
+        .into_iter()
+        .map(String::from)
+        .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+      run("project:work tag:URGENT due:<7d !completed flagged"),
+      ["Quarterly report"]
+    );
+    assert_eq!(run("project:Work"), ["Old invoice", "Quarterly report"]);
+    assert_eq!(
+      run("project:Work sort:-order"),
+      ["Quarterly report", "Old invoice"]
+    );
+    assert_eq!(run("overdue"), ["Old invoice"]);
+    assert_eq!(run("团队"), ["周报"]);
+    assert_eq!(
+      run("has:description OR is:abandoned sort:title"),
+      ["Gave up", "周报"]
+    );
+    assert_eq!(run("is:abandoned"), ["Gave up"]);
+    assert_eq!(run("deleted_at:2026-10-01..2026-10-15"), ["Binned"]);
+    assert_eq!(run("completed due:yesterday"), ["Done thing"]);
+    assert_eq!(
+      run("tag:none project:none -has:description"),
+      ["Done thing"]
+    );
+    assert_eq!(run("sort:title limit:2"), ["Done thing", "Old invoice"]);
+    assert!(store.query_tasks("due:<<7d").is_err());
+  }
+}
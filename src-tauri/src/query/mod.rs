@@ -0,0 +1,626 @@
+//! Task query language.
+//!
+//! A query is a list of terms that must all match; `OR` (or `|`) and
+//! parentheses combine them differently, and a leading `-` or `!` negates a
+//! term:
+//!
+//! ```text
+//! project:Work tag:urgent due:<7d !completed flagged "exact phrase"
+//! (tag:home OR tag:errand) -is:overdue sort:due limit:20
+//! ```
+//!
+//! - Bare words and `"quoted phrases"` search titles, descriptions and tag
+//!   names. `title:`, `desc:`, `icon:`, `id:` and `user:` search one field.
+//! - `project:` and `tag:` take a name or id, or `none`.
+//! - `completed`, `flagged`, `deleted`, `abandoned` and `overdue` work bare,
+//!   after `is:` (plus `is:open`), or as `completed:false` and so on.
+//! - `has:` checks for a `due` date, `description`, `attachments`,
+//!   `project`, `tags` or `icon`.
+//! - Dates (`due:`, `created:`, `updated:`, `completed_at:`, `deleted_at:`,
+//!   `abandoned_at:`) take `today`, `tomorrow`, `yesterday`, `YYYY-MM-DD`
+//!   or an offset from today such as `7d`, `-2w` or `1m`, optionally after
+//!   `<`, `<=`, `>` or `>=`; `a..b` is an inclusive range and `none` matches
+//!   a missing date. `order:` compares `sort_order` the same way.
+//! - `sort:` takes any `SortOptions` field (plus `due` and `title`), with a
+//!   `-` prefix or `:desc` suffix for descending order; `limit:` caps the
+//!   result.
+//!
+//! Trashed and abandoned tasks are left out unless the query mentions them.
+
+mod eval;
+pub mod smart_lists;
+
+use chrono::NaiveDate;
+
+use crate::error::{Error, Result};
+use crate::storage::models::SortField;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextField {
+  /// Title, description and tag names.
+  Any,
+  Title,
+  Description,
+  Icon,
+  Id,
+  User,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flag {
+  Completed,
+  Flagged,
+  Deleted,
+  Abandoned,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateField {
+  Due,
+  Created,
+  Updated,
+  Completed,
+  Deleted,
+  Abandoned,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HasField {
+  Due,
+  Description,
+  Attachments,
+  Project,
+  Tags,
+  Icon,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cmp {
+  Eq,
+  Lt,
+  Le,
+  Gt,
+  Ge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateValue {
+  Day(NaiveDate),
+  /// Days from today, resolved when the query runs.
+  DaysFromToday(i64),
+  /// Months from today, resolved when the query runs.
+  MonthsFromToday(i32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cond {
+  Text(TextField, String),
+  /// Project name or id; `None` for tasks without a project.
+  Project(Option<String>),
+  /// Tag name or id; `None` for tasks without tags.
+  Tag(Option<String>),
+  Flag(Flag),
+  Overdue,
+  Has(HasField),
+  Date(DateField, Cmp, DateValue),
+  NoDate(DateField),
+  SortOrder(Cmp, f64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+  And(Vec<Expr>),
+  Or(Vec<Expr>),
+  Not(Box<Expr>),
+  Cond(Cond),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+  Field(SortField),
+  Due,
+  Title,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+  /// `None` matches every task.
+  pub filter: Option<Expr>,
+  /// `(key, descending)`, most significant first.
+  pub sort: Vec<(SortKey, bool)>,
+  pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+  Open,
+  Close,
+  Or,
+  Not,
+  Word { text: String, quoted: bool },
+}
+
+fn syntax(message: impl Into<String>) -> Error {
+  Error::InvalidInput(format!("invalid query: {}", message.into()))
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+  let mut tokens = Vec::new();
+  let mut chars = input.chars().peekable();
+  while let Some(&c) = chars.peek() {
+    match c {
+      _ if c.is_whitespace() => {
+        chars.next();
+      }
+      '(' => {
+        chars.next();
+        tokens.push(Token::Open);
+      }
+      ')' => {
+        chars.next();
+        tokens.push(Token::Close);
+      }
+      '|' => {
+        chars.next();
+        tokens.push(Token::Or);
+      }
+      '-' | '!' => {
+        chars.next();
+        match chars.peek() {
+          Some(n) if n.is_whitespace() => return Err(syntax(format!("dangling `{c}`"))),
+          None => return Err(syntax(format!("dangling `{c}`"))),
+          _ => tokens.push(Token::Not),
+        }
+      }
+      _ => {
+        let mut text = String::new();
+        let mut quoted = false;
+        while let Some(&c) = chars.peek() {
+          if c.is_whitespace() || c == '(' || c == ')' {
+            break;
+          }
+          chars.next();
+          if c == '"' {
+            quoted = true;
+            loop {
+              match chars.next() {
+                Some('"') => break,
+                Some(c) => text.push(c),
+                None => return Err(syntax("unterminated quote")),
+              }
+            }
+          } else {
+            text.push(c);
+          }
+        }
+        if text == "OR" && !quoted {
+          tokens.push(Token::Or);
+        } else {
+          tokens.push(Token::Word { text, quoted });
+        }
+      }
+    }
+  }
+  Ok(tokens)
+}
+
+fn parse_cmp(value: &str) -> (Cmp, &str) {
+  for (prefix, cmp) in [
+    ("<=", Cmp::Le),
+    (">=", Cmp::Ge),
+    ("<", Cmp::Lt),
+    (">", Cmp::Gt),
+    ("=", Cmp::Eq),
+  ] {
+    if let Some(rest) = value.strip_prefix(prefix) {
+      return (cmp, rest);
+    }
+  }
+  (Cmp::Eq, value)
+}
+
+fn parse_date(value: &str) -> Result<DateValue> {
+  match value.to_lowercase().as_str() {
+    "today" => return Ok(DateValue::DaysFromToday(0)),
+    "tomorrow" => return Ok(DateValue::DaysFromToday(1)),
+    "yesterday" => return Ok(DateValue::DaysFromToday(-1)),
+    _ => {}
+  }
+  if let Ok(day) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+    return Ok(DateValue::Day(day));
+  }
+  let invalid = || syntax(format!("invalid date `{value}`"));
+  let split = value.len().checked_sub(1).ok_or_else(invalid)?;
+  let (amount, unit) = value.split_at(split);
+  let amount: i64 = amount
+    .strip_prefix('+')
+    .unwrap_or(amount)
+    .parse()
+    .map_err(|_| invalid())?;
+  match unit {
+    "d" => Ok(DateValue::DaysFromToday(amount)),
+    "w" => Ok(DateValue::DaysFromToday(amount * 7)),
+    "m" => Ok(DateValue::MonthsFromToday(
+      i32::try_from(amount).map_err(|_| invalid())?,
+    )),
+    _ => Err(invalid()),
+  }
+}
+
+fn parse_bool(key: &str, value: &str) -> Result<bool> {
+  match value.to_lowercase().as_str() {
+    "true" | "yes" | "1" => Ok(true),
+    "false" | "no" | "0" => Ok(false),
+    _ => Err(syntax(format!("`{key}:` expects true or false"))),
+  }
+}
+
+fn flag(flag: Flag, on: bool) -> Expr {
+  let cond = Expr::Cond(Cond::Flag(flag));
+  if on {
+    cond
+  } else {
+    Expr::Not(Box::new(cond))
+  }
+}
+
+fn state_keyword(word: &str) -> Option<Expr> {
+  Some(match word.to_lowercase().as_str() {
+    "completed" | "done" => flag(Flag::Completed, true),
+    "open" | "active" => flag(Flag::Completed, false),
+    "flagged" => flag(Flag::Flagged, true),
+    "deleted" | "trashed" => flag(Flag::Deleted, true),
+    "abandoned" => flag(Flag::Abandoned, true),
+    "overdue" => Expr::Cond(Cond::Overdue),
+    _ => return None,
+  })
+}
+
+fn date_field(key: &str) -> Option<DateField> {
+  Some(match key {
+    "due" | "date" => DateField::Due,
+    "created" | "created_at" => DateField::Created,
+    "updated" | "updated_at" => DateField::Updated,
+    "completed_at" => DateField::Completed,
+    "deleted_at" => DateField::Deleted,
+    "abandoned_at" => DateField::Abandoned,
+    _ => return None,
+  })
+}
+
+fn date_condition(field: DateField, value: &str) -> Result<Expr> {
+  if value.eq_ignore_ascii_case("none") {
+    return Ok(Expr::Cond(Cond::NoDate(field)));
+  }
+  if let Some((from, to)) = value.split_once("..") {
+    return Ok(Expr::And(vec![
+      Expr::Cond(Cond::Date(field, Cmp::Ge, parse_date(from)?)),
+      Expr::Cond(Cond::Date(field, Cmp::Le, parse_date(to)?)),
+    ]));
+  }
+  let (cmp, rest) = parse_cmp(value);
+  Ok(Expr::Cond(Cond::Date(field, cmp, parse_date(rest)?)))
+}
+
+fn sort_key(name: &str) -> Option<SortKey> {
+  Some(match name {
+    "sort_order" | "order" => SortKey::Field(SortField::SortOrder),
+    "created" | "created_at" => SortKey::Field(SortField::CreatedAt),
+    "updated" | "updated_at" => SortKey::Field(SortField::UpdatedAt),
+    "deleted_at" => SortKey::Field(SortField::DeletedAt),
+    "abandoned_at" => SortKey::Field(SortField::AbandonedAt),
+    "completed_at" => SortKey::Field(SortField::CompletedAt),
+    "due" | "date" => SortKey::Due,
+    "title" => SortKey::Title,
+    _ => return None,
+  })
+}
+
+struct Parser<'q> {
+  tokens: Vec<Token>,
+  pos: usize,
+  query: &'q mut Query,
+}
+
+impl Parser<'_> {
+  fn peek(&self) -> Option<&Token> {
+    self.tokens.get(self.pos)
+  }
+
+  fn next(&mut self) -> Option<Token> {
+    let token = self.tokens.get(self.pos).cloned();
+    self.pos += 1;
+    token
+  }
+
+  /// or := and ("OR" and)*
+  fn or(&mut self) -> Result<Option<Expr>> {
+    let mut branches = Vec::new();
+    loop {
+      match self.and()? {
+        Some(expr) => branches.push(expr),
+        None if branches.is_empty() && self.peek() != Some(&Token::Or) => return Ok(None),
+        None => return Err(syntax("`OR` needs a term on both sides")),
+      }
+      if self.peek() != Some(&Token::Or) {
+        break;
+      }
+      self.next();
+    }
+    Ok(Some(if branches.len() == 1 {
+      branches.remove(0)
+    } else {
+      Expr::Or(branches)
+    }))
+  }
+
+  /// and := unary*
+  fn and(&mut self) -> Result<Option<Expr>> {
+    let mut terms = Vec::new();
+    while !matches!(self.peek(), None | Some(Token::Or | Token::Close)) {
+      if let Some(term) = self.unary()? {
+        terms.push(term);
+      }
+    }
+    Ok(match terms.len() {
+      0 => None,
+      1 => Some(terms.remove(0)),
+      _ => Some(Expr::And(terms)),
+    })
+  }
+
+  /// unary := ("-" | "!") unary | "(" or ")" | word
+  ///
+  /// Returns `None` for terms that only set options, like `sort:`.
+  fn unary(&mut self) -> Result<Option<Expr>> {
+    match self.next() {
+      Some(Token::Not) => match self.unary()? {
+        Some(expr) => Ok(Some(Expr::Not(Box::new(expr)))),
+        None => Err(syntax("only filters can be negated")),
+      },
+      Some(Token::Open) => {
+        let inner = self.or()?;
+        if self.next() != Some(Token::Close) {
+          return Err(syntax("missing `)`"));
+        }
+        inner.map(Some).ok_or_else(|| syntax("empty parentheses"))
+      }
+      Some(Token::Word { text, quoted }) => self.word(&text, quoted),
+      Some(Token::Close) => Err(syntax("unexpected `)`")),
+      Some(Token::Or) | None => Err(syntax("unexpected end of query")),
+    }
+  }
+
+  fn word(&mut self, text: &str, quoted: bool) -> Result<Option<Expr>> {
+    if quoted && !text.contains(':') {
+      return Ok(Some(Expr::Cond(Cond::Text(
+        TextField::Any,
+        text.to_string(),
+      ))));
+    }
+    let field = text
+      .split_once(':')
+      .filter(|(key, _)| key.starts_with(|c: char| c.is_ascii_alphabetic()));
+    let Some((key, value)) = field else {
+      // Only the documented states work bare; `open` or `done` stay words.
+      let bare = ["completed", "flagged", "deleted", "abandoned", "overdue"]
+        .iter()
+        .any(|k| k.eq_ignore_ascii_case(text));
+      return Ok(Some(match state_keyword(text) {
+        Some(expr) if bare => expr,
+        _ => Expr::Cond(Cond::Text(TextField::Any, text.to_string())),
+      }));
+    };
+    let key = key.to_lowercase();
+    if value.is_empty() {
+      return Err(syntax(format!("`{key}:` needs a value")));
+    }
+
+    let cond = match key.as_str() {
+      "title" => Cond::Text(TextField::Title, value.into()),
+      "desc" | "description" => Cond::Text(TextField::Description, value.into()),
+      "icon" => Cond::Text(TextField::Icon, value.into()),
+      "id" => Cond::Text(TextField::Id, value.into()),
+      "user" | "user_id" => Cond::Text(TextField::User, value.into()),
+      "project" | "list" => {
+        Cond::Project((!value.eq_ignore_ascii_case("none")).then(|| value.to_string()))
+      }
+      "tag" => Cond::Tag((!value.eq_ignore_ascii_case("none")).then(|| value.to_string())),
+      "is" => {
+        return state_keyword(value)
+          .map(Some)
+          .ok_or_else(|| syntax(format!("unknown state `is:{value}`")))
+      }
+      "has" => Cond::Has(match value.to_lowercase().as_str() {
+        "due" | "date" => HasField::Due,
+        "desc" | "description" => HasField::Description,
+        "attachment" | "attachments" => HasField::Attachments,
+        "project" | "list" => HasField::Project,
+        "tag" | "tags" => HasField::Tags,
+        "icon" => HasField::Icon,
+        _ => return Err(syntax(format!("unknown `has:{value}`"))),
+      }),
+      "completed" => return Ok(Some(flag(Flag::Completed, parse_bool(&key, value)?))),
+      "flagged" => return Ok(Some(flag(Flag::Flagged, parse_bool(&key, value)?))),
+      "deleted" => return Ok(Some(flag(Flag::Deleted, parse_bool(&key, value)?))),
+      "abandoned" => return Ok(Some(flag(Flag::Abandoned, parse_bool(&key, value)?))),
+      "order" | "sort_order" => {
+        let (cmp, rest) = parse_cmp(value);
+        let number = rest
+          .parse()
+          .map_err(|_| syntax(format!("`{key}:` expects a number")))?;
+        Cond::SortOrder(cmp, number)
+      }
+      "sort" => {
+        let (name, descending) = match value.strip_prefix('-') {
+          Some(name) => (name, true),
+          None => match value.rsplit_once(':') {
+            Some((name, "desc")) => (name, true),
+            Some((name, "asc")) => (name, false),
+            _ => (value, false),
+          },
+        };
+        let key = sort_key(&name.to_lowercase())
+          .ok_or_else(|| syntax(format!("cannot sort by `{name}`")))?;
+        self.query.sort.push((key, descending));
+        return Ok(None);
+      }
+      "limit" => {
+        let limit = value
+          .parse()
+          .map_err(|_| syntax("`limit:` expects a number"))?;
+        self.query.limit = Some(limit);
+        return Ok(None);
+      }
+      other => match date_field(other) {
+        Some(field) => return date_condition(field, value).map(Some),
+        None => return Err(syntax(format!("unknown field `{other}`"))),
+      },
+    };
+    Ok(Some(Expr::Cond(cond)))
+  }
+}
+
+impl Query {
+  pub fn parse(input: &str) -> Result<Self> {
+    let mut query = Query {
+      filter: None,
+      sort: Vec::new(),
+      limit: None,
+    };
+    let mut parser = Parser {
+      tokens: tokenize(input)?,
+      pos: 0,
+      query: &mut query,
+    };
+    let filter = parser.or()?;
+    if parser.pos < parser.tokens.len() {
+      return Err(syntax("unexpected `)`"));
+    }
+    query.filter = filter;
+    Ok(query)
+  }
+
+  /// Whether the filter says anything about `flag`, which decides whether
+  /// trashed and abandoned tasks are in scope.
+  pub fn mentions(&self, flag: Flag) -> bool {
+    fn visit(expr: &Expr, flag: Flag) -> bool {
+      match expr {
+        Expr::And(items) | Expr::Or(items) => items.iter().any(|e| visit(e, flag)),
+        Expr::Not(inner) => visit(inner, flag),
+        Expr::Cond(Cond::Flag(f)) => *f == flag,
+        Expr::Cond(Cond::Date(field, ..) | Cond::NoDate(field)) => matches!(
+          (field, flag),
+          (DateField::Deleted, Flag::Deleted) | (DateField::Abandoned, Flag::Abandoned)
+        ),
+        Expr::Cond(_) => false,
+      }
+    }
+    self.filter.as_ref().is_some_and(|e| visit(e, flag))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn cond(c: Cond) -> Expr {
+    Expr::Cond(c)
+  }
+
+  #[test]
+  fn parses_the_documented_example() {
+    let query = Query::parse(
+      r#"project:Work tag:urgent due:<7d !completed flagged is:abandoned "exact phrase""#,
+    )
+    .unwrap();
+    assert_eq!(
+      query.filter,
+      Some(Expr::And(vec![
+        cond(Cond::Project(Some("Work".into()))),
+        cond(Cond::Tag(Some("urgent".into()))),
+        cond(Cond::Date(
+          DateField::Due,
+          Cmp::Lt,
+          DateValue::DaysFromToday(7)
+        )),
+        Expr::Not(Box::new(cond(Cond::Flag(Flag::Completed)))),
+        cond(Cond::Flag(Flag::Flagged)),
+        cond(Cond::Flag(Flag::Abandoned)),
+        cond(Cond::Text(TextField::Any, "exact phrase".into())),
+      ]))
+    );
+    assert!(query.mentions(Flag::Abandoned));
+    assert!(!query.mentions(Flag::Deleted));
+  }
+
+  #[test]
+  fn or_binds_looser_than_and() {
+    let query = Query::parse("a b OR -(c | d) sort:-due limit:5").unwrap();
+    let text = |s: &str| cond(Cond::Text(TextField::Any, s.into()));
+    assert_eq!(
+      query.filter,
+      Some(Expr::Or(vec![
+        Expr::And(vec![text("a"), text("b")]),
+        Expr::Not(Box::new(Expr::Or(vec![text("c"), text("d")]))),
+      ]))
+    );
+    assert_eq!(query.sort, [(SortKey::Due, true)]);
+    assert_eq!(query.limit, Some(5));
+  }
+
+  #[test]
+  fn parses_dates_and_ranges() {
+    assert_eq!(
+      Query::parse("created:2026-10-01..-1w").unwrap().filter,
+      Some(Expr::And(vec![
+        cond(Cond::Date(
+          DateField::Created,
+          Cmp::Ge,
+          DateValue::Day(NaiveDate::from_ymd_opt(2026, 10, 1).unwrap())
+        )),
+        cond(Cond::Date(
+          DateField::Created,
+          Cmp::Le,
+          DateValue::DaysFromToday(-7)
+        )),
+      ]))
+    );
+    assert_eq!(
+      Query::parse("due:none").unwrap().filter,
+      Some(cond(Cond::NoDate(DateField::Due)))
+    );
+    assert_eq!(
+      Query::parse("due:>=1m").unwrap().filter,
+      Some(cond(Cond::Date(
+        DateField::Due,
+        Cmp::Ge,
+        DateValue::MonthsFromToday(1)
+      )))
+    );
+  }
+
+  #[test]
+  fn reports_mistakes() {
+    for bad in [
+      "colour:red",
+      "due:soon",
+      "(a b",
+      "a)",
+      "\"open",
+      "OR a",
+      "is:sleeping",
+      "- a",
+      "sort:priority",
+      "-sort:due",
+    ] {
+      assert!(Query::parse(bad).is_err(), "{bad}");
+    }
+    assert_eq!(Query::parse("   ").unwrap().filter, None);
+    // Not a field: keys start with a letter.
+    assert_eq!(
+      Query::parse("12:30").unwrap().filter,
+      Some(cond(Cond::Text(TextField::Any, "12:30".into())))
+    );
+  }
+}
@@ -0,0 +1,218 @@
+//! Smart lists: named queries shown next to the projects.
+//!
+//! The sidebar addresses a smart list as the virtual project
+//! `smart:<id>`, the same way it uses `today` or `flagged`.
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::Query;
+use crate::error::{Error, Result};
+use crate::storage::models::Task;
+use crate::storage::{merge_patch, new_id, now_iso, Store};
+
+/// Prefix of the virtual project id of a smart list.
+pub const PROJECT_PREFIX: &str = "smart:";
+
+const DEFAULT_ICON: &str = "filter";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SmartList {
+  #[serde(default)]
+  pub id: String,
+  pub name: String,
+  pub query: String,
+  #[serde(default)]
+  pub icon: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub sort_order: Option<f64>,
+  #[serde(default)]
+  pub created_at: String,
+  #[serde(default)]
+  pub updated_at: String,
+}
+
+impl SmartList {
+  /// Id of the virtual project standing for this list.
+  pub fn project_id(&self) -> String {
+    format!("{PROJECT_PREFIX}{}", self.id)
+  }
+
+  fn validate(&self) -> Result<()> {
+    if self.name.trim().is_empty() {
+      return Err(Error::InvalidInput("smart list name is empty".into()));
+    }
+    Query::parse(&self.query).map(|_| ())
+  }
+}
+
+fn smart_list_from_row(row: &Row<'_>) -> rusqlite::Result<SmartList> {
+  Ok(SmartList {
+    id: row.get("id")?,
+    name: row.get("name")?,
+    query: row.get("query")?,
+    icon: row.get("icon")?,
+    sort_order: row.get("sort_order")?,
+    created_at: row.get("created_at")?,
+    updated_at: row.get("updated_at")?,
+  })
+}
+
+fn write_smart_list(conn: &Connection, list: &SmartList) -> Result<()> {
+  conn.execute(
+    "insert or replace into smart_lists
+       (id, name, query, icon, sort_order, created_at, updated_at)
+     values (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+    params![
+      list.id,
+      list.name,
+      list.query,
+      list.icon,
+      list.sort_order,
+      list.created_at,
+      list.updated_at
+    ],
+  )?;
+  Ok(())
+}
+
+fn read_smart_list(conn: &Connection, id: &str) -> Result<Option<SmartList>> {
+  Ok(
+    conn
+      .query_row(
+        "select * from smart_lists where id = ?1",
+        [id],
+        smart_list_from_row,
+      )
+      .optional()?,
+  )
+}
+
+impl Store {
+  pub fn get_smart_lists(&self) -> Result<Vec<SmartList>> {
+    let conn = self.conn();
+    let mut stmt =
+      conn.prepare("select * from smart_lists order by sort_order nulls last, created_at")?;
+    let lists = stmt
+      .query_map([], smart_list_from_row)?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(lists)
+  }
+
+  /// Save a new smart list; fails if its query does not parse.
+  pub fn create_smart_list(&self, input: SmartList) -> Result<SmartList> {
+    let now = now_iso();
+    let list = SmartList {
+      id: new_id(),
+      name: input.name.trim().to_string(),
+      icon: if input.icon.is_empty() {
+        DEFAULT_ICON.to_string()
+      } else {
+        input.icon
+      },
+      created_at: now.clone(),
+      updated_at: now,
+      ..input
+    };
+    list.validate()?;
+    write_smart_list(&self.conn(), &list)?;
+    Ok(list)
+  }
+
+  pub fn update_smart_list(
+    &self,
+    id: &str,
+    updates: &Map<String, Value>,
+  ) -> Result<Option<SmartList>> {
+    let conn = self.conn();
+    let Some(existing) = read_smart_list(&conn, id)? else {
+      return Ok(None);
+    };
+    let mut list = merge_patch(&existing, updates)?;
+    list.id = id.to_string();
+    list.created_at = existing.created_at;
+    list.updated_at = now_iso();
+    list.validate()?;
+    write_smart_list(&conn, &list)?;
+    Ok(Some(list))
+  }
+
+  pub fn delete_smart_list(&self, id: &str) -> Result<bool> {
+    self
+      .conn()
+      .execute("delete from smart_lists where id = ?1", [id])?;
+    Ok(true)
+  }
+
+  /// Tasks currently matching the list's query.
+  pub fn run_smart_list(&self, id: &str) -> Result<Vec<Task>> {
+    let id = id.strip_prefix(PROJECT_PREFIX).unwrap_or(id);
+    let list = read_smart_list(&self.conn(), id)?
+      .ok_or_else(|| Error::NotFound("smart list", id.to_string()))?;
+    self.query_tasks(&list.query)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn saves_validates_and_runs_lists() {
+    let store = Store::open_in_memory().unwrap();
+    store
+      .create_task(Task {
+        title: "Call bank".into(),
+        flagged: true,
+        ..Default::default()
+      })
+      .unwrap();
+    store
+      .create_task(Task {
+        title: "Water plants".into(),
+        ..Default::default()
+      })
+      .unwrap();
+
+    assert!(store
+      .create_smart_list(SmartList {
+        name: "Broken".into(),
+        query: "due:someday".into(),
+        ..Default::default()
+      })
+      .is_err());
+
+    let list = store
+      .create_smart_list(SmartList {
+        name: "Flagged, open".into(),
+        query: "flagged !completed".into(),
+        ..Default::default()
+      })
+      .unwrap();
+    assert_eq!(list.icon, "filter");
+    let titles = |id: &str| -> Vec<String> {
+      store
+        .run_smart_list(id)
+        .unwrap()
+        .into_iter()
+        .map(|t| t.title)
+        .collect()
+    };
+    assert_eq!(titles(&list.project_id()), ["Call bank"]);
+
+    let updates = json!({ "query": "plants" });
+    store
+      .update_smart_list(&list.id, updates.as_object().unwrap())
+      .unwrap();
+    assert_eq!(titles(&list.id), ["Water plants"]);
+    let bad = json!({ "query": "(plants" });
+    assert!(store
+      .update_smart_list(&list.id, bad.as_object().unwrap())
+      .is_err());
+
+    store.delete_smart_list(&list.id).unwrap();
+    assert!(store.get_smart_lists().unwrap().is_empty());
+  }
+}
@@ -0,0 +1,1072 @@
+//! Natural-language quick add.
+//!
+//! Turns a line such as `Ship release notes tomorrow 5pm #Work @urgent !flag`
+//! or `明天下午三点 写周报 #工作` into task fields:
+//!
+//! - `#project`, `@tag` and `!flag` (or a lone `!`) at the start of a word;
+//!   a name runs to the next space. Full-width `＃`, `＠` and `！` work too.
+//! - Days: `today`, `tomorrow`, `friday`, `next mon`, `in 3 days`, `+2w`,
+//!   `oct 20`, `10/20`, `2026-10-20`, `今天`, `明天`, `后天`, `周五`,
+//!   `下周一`, `3天后`, `下个月`, `10月20日`, `15号`.
+//! - Times: `5pm`, `at 17:30`, `noon`, `下午三点`, `晚上8点半`, `9点一刻`;
+//!   `tonight`, `今晚` and `明早` set a day and a time.
+//! - Repeats: `daily`, `every 2 weeks`, `every friday`, `every weekday`,
+//!   `每天`, `每周五`, `每个工作日`, `每月15号`, `每隔3天` become an RRULE.
+//!   Without a day of its own the task is due on the first occurrence.
+//!
+//! Only the first phrase of each kind is taken; later ones stay in the
+//! title. A time without a day means its next occurrence, today or
+//! tomorrow. Weeks start on Monday, so `next friday` and `下周五` are the
+//! Friday of next week while a bare `friday` is the coming one.
+
+use chrono::{Datelike, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::rrule::{format_task_date, DateStyle, RRule};
+
+/// Time given to `tonight`, `今晚` and `明晚`.
+const EVENING: (u32, u32) = (20, 0);
+/// Time given to `明早`.
+const MORNING: (u32, u32) = (9, 0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SpanKind {
+  Date,
+  Time,
+  Repeat,
+  Project,
+  Tag,
+  Flag,
+}
+
+/// A recognised piece of the input. Offsets count UTF-16 code units, the
+/// way JavaScript indexes strings, so the UI can highlight them as is.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+  pub kind: SpanKind,
+  pub start: usize,
+  pub end: usize,
+  pub text: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuickAdd {
+  /// The input without the recognised pieces.
+  pub title: String,
+  /// Local time as an ISO timestamp, as the date picker stores it.
+  pub date: Option<String>,
+  /// Whether `date` carries a time of day rather than local midnight.
+  pub has_time: bool,
+  pub rrule: Option<String>,
+  /// Project name as typed.
+  pub project: Option<String>,
+  /// The existing project `project` names; filled in by the command.
+  pub project_id: Option<String>,
+  pub tags: Vec<String>,
+  pub flagged: bool,
+  pub spans: Vec<Span>,
+}
+
+/// What one phrase contributes.
+#[derive(Debug, Default)]
+struct Found {
+  day: Option<NaiveDate>,
+  time: Option<NaiveTime>,
+  rule: Option<String>,
+}
+
+impl Found {
+  fn day(day: NaiveDate) -> Self {
+    Self {
+      day: Some(day),
+      ..Default::default()
+    }
+  }
+
+  fn time(time: NaiveTime) -> Self {
+    Self {
+      time: Some(time),
+      ..Default::default()
+    }
+  }
+
+  fn at(at: NaiveDateTime) -> Self {
+    Self {
+      day: Some(at.date()),
+      time: Some(at.time()),
+      rule: None,
+    }
+  }
+
+  fn rule(rule: impl Into<String>) -> Self {
+    Self {
+      rule: Some(rule.into()),
+      ..Default::default()
+    }
+  }
+
+  fn kind(&self) -> SpanKind {
+    if self.rule.is_some() {
+      SpanKind::Repeat
+    } else if self.day.is_some() {
+      SpanKind::Date
+    } else {
+      SpanKind::Time
+    }
+  }
+}
+
+/// Parse `input` relative to the local time `now`.
+pub fn parse(input: &str, now: NaiveDateTime) -> QuickAdd {
+  let mut result = QuickAdd::default();
+  let mut taken = Found::default();
+  let mut spans: Vec<(SpanKind, usize, usize)> = Vec::new();
+
+  let mut pos = 0;
+  while pos < input.len() {
+    let rest = &input[pos..];
+    let prev = input[..pos].chars().next_back();
+
+    if prev.map_or(true, char::is_whitespace) {
+      if let Some((kind, len, name)) = sigil(rest) {
+        if kind != SpanKind::Project || result.project.is_none() {
+          match kind {
+            SpanKind::Project => result.project = name,
+            SpanKind::Tag => {
+              let name = name.unwrap_or_default();
+              if !result.tags.contains(&name) {
+                result.tags.push(name);
+              }
+            }
+            _ => result.flagged = true,
+          }
+          spans.push((kind, pos, pos + len));
+          pos += len;
+          continue;
+        }
+      }
+    }
+
+    if !prev.is_some_and(|c| c.is_ascii_alphanumeric()) {
+      let found = chinese(rest, now).or_else(|| english(rest, now));
+      if let Some((len, found)) = found {
+        let free = (found.day.is_none() || taken.day.is_none())
+          && (found.time.is_none() || taken.time.is_none())
+          && (found.rule.is_none() || taken.rule.is_none());
+        if free {
+          spans.push((found.kind(), pos, pos + len));
+          taken.day = taken.day.or(found.day);
+          taken.time = taken.time.or(found.time);
+          taken.rule = taken.rule.or(found.rule);
+          pos += len;
+          continue;
+        }
+      }
+    }
+    pos += rest.chars().next().map_or(1, char::len_utf8);
+  }
+
+  let mut title = String::new();
+  let mut from = 0;
+  for &(_, start, end) in &spans {
+    title.push_str(&input[from..start]);
+    title.push(' ');
+    from = end;
+  }
+  title.push_str(&input[from..]);
+  result.title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+
+  let utf16 = |byte: usize| input[..byte].encode_utf16().count();
+  result.spans = spans
+    .into_iter()
+    .map(|(kind, start, end)| Span {
+      kind,
+      start: utf16(start),
+      end: utf16(end),
+      text: input[start..end].to_string(),
+    })
+    .collect();
+
+  let due = resolve(&taken, now);
+  result.date = due.map(|at| format_task_date(at, DateStyle::Instant));
+  result.has_time = due.is_some() && taken.time.is_some();
+  result.rrule = taken.rule;
+  result
+}
+
+/// Combine the phrases into a due time.
+fn resolve(taken: &Found, now: NaiveDateTime) -> Option<NaiveDateTime> {
+  let today = now.date();
+  let time = taken.time.unwrap_or(NaiveTime::MIN);
+  if let Some(day) = taken.day {
+    return Some(day.and_time(time));
+  }
+  if let Some(rule) = taken.rule.as_deref() {
+    let rule: RRule = rule.parse().ok()?;
+    return rule
+      .occurrences(today.and_time(time))
+      .find(|at| taken.time.is_none() || *at > now);
+  }
+  let at = today.and_time(taken.time?);
+  Some(if at > now {
+    at
+  } else {
+    at + TimeDelta::days(1)
+  })
+}
+
+/// `#project`, `@tag` or `!flag`: kind, length in bytes and name.
+fn sigil(s: &str) -> Option<(SpanKind, usize, Option<String>)> {
+  let first = s.chars().next()?;
+  let kind = match first {
+    '#' | '＃' => SpanKind::Project,
+    '@' | '＠' => SpanKind::Tag,
+    '!' | '！' => SpanKind::Flag,
+    _ => return None,
+  };
+  let len = s.find(char::is_whitespace).unwrap_or(s.len());
+  let name = &s[first.len_utf8()..len];
+  if kind == SpanKind::Flag {
+    return matches!(name.to_ascii_lowercase().as_str(), "" | "flag" | "flagged")
+      .then_some((kind, len, None));
+  }
+  (!name.is_empty()).then(|| (kind, len, Some(name.to_string())))
+}
+
+fn hm(hour: u32, minute: u32) -> Option<NaiveTime> {
+  NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+fn weekday_code(day: Weekday) -> &'static str {
+  match day {
+    Weekday::Mon => "MO",
+    Weekday::Tue => "TU",
+    Weekday::Wed => "WE",
+    Weekday::Thu => "TH",
+    Weekday::Fri => "FR",
+    Weekday::Sat => "SA",
+    Weekday::Sun => "SU",
+  }
+}
+
+fn weekly_on(days: &[Weekday]) -> String {
+  let codes: Vec<&str> = days.iter().map(|d| weekday_code(*d)).collect();
+  format!("FREQ=WEEKLY;BYDAY={}", codes.join(","))
+}
+
+fn every_n(freq: &str, interval: u32) -> String {
+  if interval > 1 {
+    format!("FREQ={freq};INTERVAL={interval}")
+  } else {
+    format!("FREQ={freq}")
+  }
+}
+
+const WORKDAYS: [Weekday; 5] = [
+  Weekday::Mon,
+  Weekday::Tue,
+  Weekday::Wed,
+  Weekday::Thu,
+  Weekday::Fri,
+];
+
+/// The next `day`, today included.
+fn coming(today: NaiveDate, day: Weekday) -> NaiveDate {
+  let ahead = (7 + day.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+  today + TimeDelta::days(ahead.into())
+}
+
+/// `day` in the week `weeks` after the current one.
+fn in_week(today: NaiveDate, weeks: i64, day: Weekday) -> NaiveDate {
+  let monday = today - TimeDelta::days(today.weekday().num_days_from_monday().into());
+  monday + TimeDelta::days(7 * weeks + i64::from(day.num_days_from_monday()))
+}
+
+/// `month`/`day` this year, or next year once it has passed.
+fn upcoming_date(today: NaiveDate, month: u32, day: u32) -> Option<NaiveDate> {
+  let this_year = NaiveDate::from_ymd_opt(today.year(), month, day)?;
+  if this_year >= today {
+    Some(this_year)
+  } else {
+    NaiveDate::from_ymd_opt(today.year() + 1, month, day)
+  }
+}
+
+fn first_of_next_month(today: NaiveDate) -> NaiveDate {
+  today.with_day(1).unwrap_or(today) + Months::new(1)
+}
+
+fn add_units(now: NaiveDateTime, n: u32, unit: Unit) -> Option<Found> {
+  Some(match unit {
+    Unit::Minute => Found::at(now + TimeDelta::minutes(n.into())),
+    Unit::Hour => Found::at(now + TimeDelta::hours(n.into())),
+    Unit::Day => Found::day(now.date() + TimeDelta::days(n.into())),
+    Unit::Week => Found::day(now.date() + TimeDelta::weeks(n.into())),
+    Unit::Month => Found::day(now.date().checked_add_months(Months::new(n))?),
+    Unit::Year => Found::day(
+      now
+        .date()
+        .checked_add_months(Months::new(n.checked_mul(12)?))?,
+    ),
+  })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+  Minute,
+  Hour,
+  Day,
+  Week,
+  Month,
+  Year,
+}
+
+impl Unit {
+  fn freq(self) -> Option<&'static str> {
+    match self {
+      Unit::Day => Some("DAILY"),
+      Unit::Week => Some("WEEKLY"),
+      Unit::Month => Some("MONTHLY"),
+      Unit::Year => Some("YEARLY"),
+      Unit::Minute | Unit::Hour => None,
+    }
+  }
+}
+
+// ============================================
+// English
+// ============================================
+
+fn is_word_char(c: char) -> bool {
+  c.is_ascii_alphanumeric() || matches!(c, ':' | '/' | '-' | '+')
+}
+
+/// Up to four lowercase words at the start of `s`, each with the byte
+/// offset where it ends. A phrase only continues across plain spaces.
+fn words(s: &str) -> Vec<(String, usize)> {
+  let mut words = Vec::new();
+  let mut pos = 0;
+  while words.len() < 4 {
+    let len = s[pos..]
+      .find(|c: char| !is_word_char(c))
+      .unwrap_or(s.len() - pos);
+    if len == 0 {
+      break;
+    }
+    words.push((s[pos..pos + len].to_ascii_lowercase(), pos + len));
+    pos += len;
+    let gap = s[pos..]
+      .find(|c: char| c != ' ' && c != '\t')
+      .unwrap_or(s.len() - pos);
+    if gap == 0 {
+      break;
+    }
+    pos += gap;
+  }
+  words
+}
+
+fn english(s: &str, now: NaiveDateTime) -> Option<(usize, Found)> {
+  let words = words(s);
+  let w: Vec<&str> = words.iter().map(|(w, _)| w.as_str()).collect();
+  // A preposition only goes with the phrase after it.
+  let (skip, (n, found)) = match *w.first()? {
+    "on" | "at" | "by" | "due" => (1, english_phrase(&w[1..], now)?),
+    _ => (0, english_phrase(&w, now)?),
+  };
+  Some((words[skip + n - 1].1, found))
+}
+
+fn english_phrase(w: &[&str], now: NaiveDateTime) -> Option<(usize, Found)> {
+  let today = now.date();
+  let first = *w.first()?;
+  let second = w.get(1).copied();
+  match first {
+    "daily" => return Some((1, Found::rule("FREQ=DAILY"))),
+    "weekly" => return Some((1, Found::rule("FREQ=WEEKLY"))),
+    "monthly" => return Some((1, Found::rule("FREQ=MONTHLY"))),
+    "yearly" | "annually" => return Some((1, Found::rule("FREQ=YEARLY"))),
+    "every" => return english_every(&w[1..]).map(|(n, rule)| (n + 1, Found::rule(rule))),
+    "today" => return Some((1, Found::day(today))),
+    "tonight" => {
+      return Some((1, Found::at(today.and_time(hm(EVENING.0, EVENING.1)?))));
+    }
+    "tomorrow" | "tmr" | "tmrw" => return Some((1, Found::day(today + TimeDelta::days(1)))),
+    "noon" => return Some((1, Found::time(hm(12, 0)?))),
+    "day" if w.get(1..3) == Some(&["after", "tomorrow"][..]) => {
+      return Some((3, Found::day(today + TimeDelta::days(2))));
+    }
+    "next" | "this" => {
+      let weeks = i64::from(first == "next");
+      if let Some(day) = second.and_then(|s| english_weekday(s, true)) {
+        return Some((2, Found::day(in_week(today, weeks, day))));
+      }
+      return match second? {
+        "week" if weeks == 1 => Some((2, Found::day(in_week(today, 1, Weekday::Mon)))),
+        "month" if weeks == 1 => Some((2, Found::day(first_of_next_month(today)))),
+        _ => None,
+      };
+    }
+    "in" => {
+      let n = english_number(second?)?;
+      let unit = english_unit(w.get(2)?)?;
+      return Some((3, add_units(now, n, unit)?));
+    }
+    _ => {}
+  }
+
+  if let Some(day) = english_weekday(first, false) {
+    return Some((1, Found::day(coming(today, day))));
+  }
+  if let Some(rest) = first.strip_prefix('+') {
+    let (n, unit) = rest.split_at(rest.len().checked_sub(1)?);
+    let unit = match unit {
+      "d" => Unit::Day,
+      "w" => Unit::Week,
+      "m" => Unit::Month,
+      "y" => Unit::Year,
+      _ => return None,
+    };
+    return Some((1, add_units(now, n.parse().ok()?, unit)?));
+  }
+  if let Ok(day) = NaiveDate::parse_from_str(first, "%Y-%m-%d") {
+    return Some((1, Found::day(day)));
+  }
+  if let Some((month, day)) = first.split_once('/') {
+    let day = upcoming_date(today, month.parse().ok()?, day.parse().ok()?)?;
+    return Some((1, Found::day(day)));
+  }
+  if let Some(month) = english_month(first) {
+    let day = upcoming_date(today, month, english_ordinal(second?)?)?;
+    return Some((2, Found::day(day)));
+  }
+  if let (Some(day), Some(month)) = (english_ordinal(first), second.and_then(english_month)) {
+    return Some((2, Found::day(upcoming_date(today, month, day)?)));
+  }
+  if let Some(time) = english_time(first, None) {
+    return Some((1, Found::time(time)));
+  }
+  let time = english_time(first, second)?;
+  Some((2, Found::time(time)))
+}
+
+/// The part of a repeat after `every`.
+fn english_every(w: &[&str]) -> Option<(usize, String)> {
+  let first = *w.first()?;
+  match first {
+    "weekday" | "weekdays" => return Some((1, weekly_on(&WORKDAYS))),
+    "other" => {
+      let freq = english_unit(w.get(1)?)?.freq()?;
+      return Some((2, every_n(freq, 2)));
+    }
+    _ => {}
+  }
+  if let Some(day) = english_weekday(first.strip_suffix('s').unwrap_or(first), true) {
+    return Some((1, weekly_on(&[day])));
+  }
+  if let Some(freq) = english_unit(first).and_then(Unit::freq) {
+    return Some((1, every_n(freq, 1)));
+  }
+  let n = english_number(first)?;
+  let freq = english_unit(w.get(1)?)?.freq()?;
+  Some((2, every_n(freq, n)))
+}
+
+fn english_number(word: &str) -> Option<u32> {
+  const WORDS: [&str; 12] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten", "eleven",
+    "twelve",
+  ];
+  match word {
+    "a" | "an" => Some(1),
+    _ => WORDS
+      .iter()
+      .position(|w| *w == word)
+      .map(|i| i as u32 + 1)
+      .or_else(|| word.parse().ok().filter(|n| *n > 0)),
+  }
+}
+
+fn english_unit(word: &str) -> Option<Unit> {
+  Some(match word.strip_suffix('s').unwrap_or(word) {
+    "min" | "minute" => Unit::Minute,
+    "hour" | "hr" => Unit::Hour,
+    "day" => Unit::Day,
+    "week" | "wk" => Unit::Week,
+    "month" => Unit::Month,
+    "year" | "yr" => Unit::Year,
+    _ => return None,
+  })
+}
+
+/// Full weekday names, and abbreviations when `short` allows them; a bare
+/// `sat` or `sun` is more often a word than a date.
+fn english_weekday(word: &str, short: bool) -> Option<Weekday> {
+  let day = match word {
+    "monday" => Weekday::Mon,
+    "tuesday" => Weekday::Tue,
+    "wednesday" => Weekday::Wed,
+    "thursday" => Weekday::Thu,
+    "friday" => Weekday::Fri,
+    "saturday" => Weekday::Sat,
+    "sunday" => Weekday::Sun,
+    _ if !short => return None,
+    "mon" => Weekday::Mon,
+    "tue" | "tues" => Weekday::Tue,
+    "wed" => Weekday::Wed,
+    "thu" | "thur" | "thurs" => Weekday::Thu,
+    "fri" => Weekday::Fri,
+    "sat" => Weekday::Sat,
+    "sun" => Weekday::Sun,
+    _ => return None,
+  };
+  Some(day)
+}
+
+fn english_month(word: &str) -> Option<u32> {
+  const MONTHS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+  ];
+  let word = if word == "sept" { "sep" } else { word };
+  MONTHS
+    .iter()
+    .position(|m| *m == word || (word.len() == 3 && m.starts_with(word)))
+    .map(|i| i as u32 + 1)
+}
+
+/// `20`, `20th`, `1st`.
+fn english_ordinal(word: &str) -> Option<u32> {
+  let digits = ["st", "nd", "rd", "th"]
+    .iter()
+    .find_map(|s| word.strip_suffix(s))
+    .unwrap_or(word);
+  digits.parse().ok().filter(|d| (1..=31).contains(d))
+}
+
+/// `5pm`, `5:30pm`, `17:30`, or `5` followed by `am`/`pm` in `next`.
+fn english_time(word: &str, next: Option<&str>) -> Option<NaiveTime> {
+  let (clock, suffix) = match next {
+    Some(suffix) => (word, suffix),
+    None => match word.strip_suffix("am").or_else(|| word.strip_suffix("pm")) {
+      Some(clock) => (clock, &word[clock.len()..]),
+      None if word.contains(':') => (word, ""),
+      None => return None,
+    },
+  };
+  let (hour, minute) = match clock.split_once(':') {
+    Some((h, m)) if m.len() == 2 => (h.parse().ok()?, m.parse().ok()?),
+    Some(_) => return None,
+    None => (clock.parse().ok()?, 0),
+  };
+  let hour: u32 = match suffix {
+    "" => hour,
+    "am" | "pm" if !(1..=12).contains(&hour) => return None,
+    "am" => hour % 12,
+    "pm" => hour % 12 + 12,
+    _ => return None,
+  };
+  hm(hour, minute)
+}
+
+// ============================================
+// Chinese
+// ============================================
+
+struct Cursor<'a> {
+  s: &'a str,
+  pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+  fn rest(&self) -> &'a str {
+    &self.s[self.pos..]
+  }
+
+  fn eat(&mut self, lit: &str) -> bool {
+    let found = self.rest().starts_with(lit);
+    if found {
+      self.pos += lit.len();
+    }
+    found
+  }
+
+  /// The first of `lits` that matches; list longer ones first.
+  fn eat_any<'b>(&mut self, lits: &[&'b str]) -> Option<&'b str> {
+    lits.iter().copied().find(|lit| self.eat(lit))
+  }
+
+  /// Arabic digits, or Chinese numerals up to 99 (`三`, `两`, `十五`, `二十`).
+  fn number(&mut self) -> Option<u32> {
+    let digits = self
+      .rest()
+      .find(|c: char| !c.is_ascii_digit())
+      .unwrap_or(self.rest().len());
+    if digits > 0 {
+      let n = self.rest()[..digits].parse().ok()?;
+      self.pos += digits;
+      return Some(n);
+    }
+    let start = self.pos;
+    let tens = self.digit();
+    if self.eat("十") {
+      return Some(tens.unwrap_or(1) * 10 + self.digit().unwrap_or(0));
+    }
+    if tens.is_none() {
+      self.pos = start;
+    }
+    tens
+  }
+
+  fn digit(&mut self) -> Option<u32> {
+    const DIGITS: [(&str, u32); 12] = [
+      ("零", 0),
+      ("〇", 0),
+      ("一", 1),
+      ("二", 2),
+      ("两", 2),
+      ("三", 3),
+      ("四", 4),
+      ("五", 5),
+      ("六", 6),
+      ("七", 7),
+      ("八", 8),
+      ("九", 9),
+    ];
+    DIGITS
+      .iter()
+      .find(|(name, _)| self.eat(name))
+      .map(|(_, n)| *n)
+  }
+
+  fn weekday(&mut self) -> Option<Weekday> {
+    const DAYS: [(&str, Weekday); 8] = [
+      ("一", Weekday::Mon),
+      ("二", Weekday::Tue),
+      ("三", Weekday::Wed),
+      ("四", Weekday::Thu),
+      ("五", Weekday::Fri),
+      ("六", Weekday::Sat),
+      ("日", Weekday::Sun),
+      ("天", Weekday::Sun),
+    ];
+    DAYS
+      .iter()
+      .find(|(name, _)| self.eat(name))
+      .map(|(_, day)| *day)
+  }
+
+  fn week(&mut self) -> bool {
+    self.eat_any(&["星期", "礼拜", "周"]).is_some()
+  }
+
+  fn unit(&mut self) -> Option<Unit> {
+    Some(
+      match self.eat_any(&[
+        "分钟",
+        "个小时",
+        "小时",
+        "天",
+        "个星期",
+        "个礼拜",
+        "星期",
+        "礼拜",
+        "周",
+        "个月",
+        "年",
+      ])? {
+        "分钟" => Unit::Minute,
+        "个小时" | "小时" => Unit::Hour,
+        "天" => Unit::Day,
+        "个月" => Unit::Month,
+        "年" => Unit::Year,
+        _ => Unit::Week,
+      },
+    )
+  }
+}
+
+/// Try `parse` on a fresh cursor over `s`; the phrase length on success.
+fn attempt(
+  s: &str,
+  parse: impl FnOnce(&mut Cursor<'_>) -> Option<Found>,
+) -> Option<(usize, Found)> {
+  let mut cursor = Cursor { s, pos: 0 };
+  let found = parse(&mut cursor)?;
+  Some((cursor.pos, found))
+}
+
+fn chinese(s: &str, now: NaiveDateTime) -> Option<(usize, Found)> {
+  attempt(s, chinese_repeat)
+    .or_else(|| attempt(s, |c| chinese_day(c, now)))
+    .or_else(|| attempt(s, |c| chinese_time(c, None).map(Found::time)))
+}
+
+fn chinese_repeat(c: &mut Cursor<'_>) -> Option<Found> {
+  if !c.eat("每") {
+    return None;
+  }
+  c.eat("隔");
+  if c.eat_any(&["天", "日"]).is_some() {
+    return Some(Found::rule("FREQ=DAILY"));
+  }
+  if c.eat_any(&["个工作日", "工作日"]).is_some() {
+    return Some(Found::rule(weekly_on(&WORKDAYS)));
+  }
+  let at = c.pos;
+  c.eat("个");
+  if c.week() {
+    if c.eat("末") {
+      return Some(Found::rule(weekly_on(&[Weekday::Sat, Weekday::Sun])));
+    }
+    // 每周一、三、五
+    let mut days = Vec::new();
+    while let Some(day) = c.weekday() {
+      days.push(day);
+      let at = c.pos;
+      if c.eat_any(&["、", "，", ",", "和"]).is_some() && !c.week() {
+        if c
+          .rest()
+          .starts_with(|ch: char| "一二三四五六日天".contains(ch))
+        {
+          continue;
+        }
+        c.pos = at;
+        break;
+      }
+    }
+    return Some(Found::rule(if days.is_empty() {
+      "FREQ=WEEKLY".to_string()
+    } else {
+      weekly_on(&days)
+    }));
+  }
+  c.pos = at;
+  if c.eat_any(&["个月", "月"]).is_some() {
+    let at = c.pos;
+    if let Some(day) = c.number().filter(|d| (1..=31).contains(d)) {
+      if c.eat_any(&["号", "日"]).is_some() {
+        return Some(Found::rule(format!("FREQ=MONTHLY;BYMONTHDAY={day}")));
+      }
+    }
+    c.pos = at;
+    return Some(Found::rule("FREQ=MONTHLY"));
+  }
+  if c.eat("年") {
+    return Some(Found::rule("FREQ=YEARLY"));
+  }
+  let n = c.number().filter(|n| *n > 0)?;
+  let freq = c.unit()?.freq()?;
+  Some(Found::rule(every_n(freq, n)))
+}
+
+fn chinese_day(c: &mut Cursor<'_>, now: NaiveDateTime) -> Option<Found> {
+  let today = now.date();
+  let days = |n: i64| today + TimeDelta::days(n);
+
+  const RELATIVE: [(&str, i64); 6] = [
+    ("大后天", 3),
+    ("后天", 2),
+    ("明天", 1),
+    ("明日", 1),
+    ("今天", 0),
+    ("今日", 0),
+  ];
+  if let Some((_, n)) = RELATIVE.iter().find(|(name, _)| c.eat(name)) {
+    return Some(Found::day(days(*n)));
+  }
+  // 今晚八点 takes the time that follows, 今晚 alone means the evening.
+  for (name, n, period, default) in [
+    ("今晚", 0, "晚上", EVENING),
+    ("明晚", 1, "晚上", EVENING),
+    ("明早", 1, "早上", MORNING),
+  ] {
+    if c.eat(name) {
+      let at = c.pos;
+      let time = chinese_time(c, Some(period)).or_else(|| {
+        c.pos = at;
+        hm(default.0, default.1)
+      })?;
+      return Some(Found::at(days(n).and_time(time)));
+    }
+  }
+
+  let start = c.pos;
+  let weeks = match c.eat_any(&["下下个", "下下", "下个", "下", "本", "这个", "这"]) {
+    Some("下下个" | "下下") => Some(2),
+    Some("下个" | "下") => Some(1),
+    Some(_) => Some(0),
+    None => None,
+  };
+  if c.week() {
+    if c.eat("末") {
+      let day = match weeks {
+        Some(n) => in_week(today, n, Weekday::Sat),
+        None => coming(today, Weekday::Sat),
+      };
+      return Some(Found::day(day));
+    }
+    return match (weeks, c.weekday()) {
+      (Some(n), Some(day)) => Some(Found::day(in_week(today, n, day))),
+      (None, Some(day)) => Some(Found::day(coming(today, day))),
+      (Some(n), None) if n > 0 => Some(Found::day(in_week(today, n, Weekday::Mon))),
+      _ => None,
+    };
+  }
+  if weeks == Some(1) && c.eat("月") {
+    let month = first_of_next_month(today);
+    let at = c.pos;
+    if let Some(day) = c.number() {
+      if c.eat_any(&["号", "日"]).is_some() {
+        return Some(Found::day(month.with_day(day)?));
+      }
+    }
+    c.pos = at;
+    return Some(Found::day(month));
+  }
+  c.pos = start;
+
+  let n = c.number()?;
+  let at = c.pos;
+  // 3天后, 两周以后
+  if let Some(unit) = c.unit() {
+    if c.eat_any(&["以后", "之后", "后"]).is_some() {
+      return add_units(now, n, unit);
+    }
+  }
+  c.pos = at;
+  if c.eat("月") {
+    let day = c.number()?;
+    c.eat_any(&["号", "日"])?;
+    return Some(Found::day(upcoming_date(today, n, day)?));
+  }
+  c.eat_any(&["号", "日"])?;
+  let this_month = today.with_day(n)?;
+  if this_month >= today {
+    Some(Found::day(this_month))
+  } else {
+    Some(Found::day(first_of_next_month(today).with_day(n)?))
+  }
+}
+
+/// `[时段] N点[半|一刻|三刻|N分]` or `[时段] HH:MM`. `period` is the time of
+/// day already given, as in 今晚八点.
+fn chinese_time(c: &mut Cursor<'_>, period: Option<&str>) -> Option<NaiveTime> {
+  const PERIODS: [&str; 9] = [
+    "凌晨", "早上", "早晨", "上午", "中午", "下午", "傍晚", "晚上", "夜里",
+  ];
+  let period = c.eat_any(&PERIODS).or(period);
+  let hour = c.number()?;
+  let minute = if c.eat(":") || c.eat("：") {
+    let digits = c
+      .rest()
+      .get(..2)
+      .filter(|m| m.bytes().all(|b| b.is_ascii_digit()))?;
+    c.pos += 2;
+    digits.parse().ok()?
+  } else {
+    c.eat_any(&["点", "时"])?;
+    if c.eat("半") {
+      30
+    } else if c.eat("一刻") {
+      15
+    } else if c.eat("三刻") {
+      45
+    } else {
+      let at = c.pos;
+      match c.number() {
+        Some(m) if m < 60 => {
+          c.eat("分");
+          m
+        }
+        _ => {
+          c.pos = at;
+          0
+        }
+      }
+    }
+  };
+  let hour = match period {
+    Some("下午" | "傍晚" | "晚上" | "夜里") if (1..12).contains(&hour) => hour + 12,
+    Some("中午") if (1..=3).contains(&hour) => hour + 12,
+    Some("凌晨") if hour == 12 => 0,
+    _ => hour,
+  };
+  hm(hour, minute)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Wednesday 2026-10-14, 10:00.
+  fn now() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2026, 10, 14)
+      .unwrap()
+      .and_hms_opt(10, 0, 0)
+      .unwrap()
+  }
+
+  /// Title and due time in local wall-clock time.
+  fn due(input: &str) -> (String, Option<String>) {
+    let parsed = parse(input, now());
+    let at = parsed.date.as_deref().map(|d| {
+      let (at, _) = crate::rrule::parse_task_date(d).unwrap();
+      if parsed.has_time {
+        at.format("%Y-%m-%d %H:%M").to_string()
+      } else {
+        at.format("%Y-%m-%d").to_string()
+      }
+    });
+    (parsed.title, at)
+  }
+
+  fn check(input: &str, title: &str, at: &str) {
+    assert_eq!(
+      due(input),
+      (title.to_string(), Some(at.to_string())),
+      "{input}"
+    );
+  }
+
+  #[test]
+  fn parses_fields_and_spans() {
+    let input = "Ship release notes tomorrow 5pm #Work @urgent !flag";
+    let parsed = parse(input, now());
+    assert_eq!(parsed.title, "Ship release notes");
+    assert_eq!(parsed.project.as_deref(), Some("Work"));
+    assert_eq!(parsed.tags, ["urgent"]);
+    assert!(parsed.flagged && parsed.has_time);
+    let kinds: Vec<(SpanKind, &str)> = parsed
+      .spans
+      .iter()
+      .map(|s| (s.kind, s.text.as_str()))
+      .collect();
+    assert_eq!(
+      kinds,
+      [
+        (SpanKind::Date, "tomorrow"),
+        (SpanKind::Time, "5pm"),
+        (SpanKind::Project, "#Work"),
+        (SpanKind::Tag, "@urgent"),
+        (SpanKind::Flag, "!flag"),
+      ]
+    );
+
+    // Offsets are in UTF-16 code units.
+    let parsed = parse("😀 明天下午三点 写周报 ＃工作", now());
+    assert_eq!(parsed.title, "😀 写周报");
+    assert_eq!(parsed.project.as_deref(), Some("工作"));
+    let span = &parsed.spans[0];
+    assert_eq!((span.start, span.end, span.text.as_str()), (3, 5, "明天"));
+  }
+
+  #[test]
+  fn understands_english_dates() {
+    check("Pay rent today", "Pay rent", "2026-10-14");
+    check("call mom at 17:30", "call mom", "2026-10-14 17:30");
+    check("standup 9am", "standup", "2026-10-15 09:00");
+    check("review on friday", "review", "2026-10-16");
+    check("plan next monday at 2:30pm", "plan", "2026-10-19 14:30");
+    check("retro this sun", "retro", "2026-10-18");
+    check("renew in 2 weeks", "renew", "2026-10-28");
+    check("ping in an hour", "ping", "2026-10-14 11:00");
+    check("dentist oct 20", "dentist", "2026-10-20");
+    check("taxes 15th april", "taxes", "2027-04-15");
+    check("launch 2026-11-02", "launch", "2026-11-02");
+    check("order cake 10/1", "order cake", "2027-10-01");
+    check("backup +3d", "backup", "2026-10-17");
+    check("movie tonight", "movie", "2026-10-14 20:00");
+    assert_eq!(
+      due("Sat down with Sun Microsystems"),
+      ("Sat down with Sun Microsystems".into(), None)
+    );
+  }
+
+  #[test]
+  fn understands_chinese_dates() {
+    check("明天下午三点开会", "开会", "2026-10-15 15:00");
+    check("后天交报告", "交报告", "2026-10-16");
+    check("下周一 晨会", "晨会", "2026-10-19");
+    check("周三 健身", "健身", "2026-10-14");
+    check("本周五上午10点半 复盘", "复盘", "2026-10-16 10:30");
+    check("两天后 取快递", "取快递", "2026-10-16");
+    check("11月11日 抢购", "抢购", "2026-11-11");
+    check("5号 交房租", "交房租", "2026-11-05");
+    check("今晚八点 看电影", "看电影", "2026-10-14 20:00");
+    check("明早 跑步", "跑步", "2026-10-15 09:00");
+    check("下午3点 喝茶", "喝茶", "2026-10-14 15:00");
+    check("中午12点吃饭", "吃饭", "2026-10-14 12:00");
+  }
+
+  #[test]
+  fn turns_repeats_into_rrules() {
+    let repeat = |input: &str| {
+      let parsed = parse(input, now());
+      (parsed.title.clone(), parsed.rrule.clone(), due(input).1)
+    };
+    let expect = |title: &str, rule: &str, at: &str| {
+      (
+        title.to_string(),
+        Some(rule.to_string()),
+        Some(at.to_string()),
+      )
+    };
+    assert_eq!(
+      repeat("每周五 周报"),
+      expect("周报", "FREQ=WEEKLY;BYDAY=FR", "2026-10-16")
+    );
+    assert_eq!(
+      repeat("每周一、三、五 晨跑"),
+      expect("晨跑", "FREQ=WEEKLY;BYDAY=MO,WE,FR", "2026-10-14")
+    );
+    assert_eq!(
+      repeat("每天早上9点 吃药"),
+      expect("吃药", "FREQ=DAILY", "2026-10-15 09:00")
+    );
+    assert_eq!(
+      repeat("每个工作日 打卡"),
+      expect("打卡", "FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR", "2026-10-14")
+    );
+    assert_eq!(
+      repeat("每月15号 还信用卡"),
+      expect("还信用卡", "FREQ=MONTHLY;BYMONTHDAY=15", "2026-10-15")
+    );
+    assert_eq!(
+      repeat("每隔3天 浇花"),
+      expect("浇花", "FREQ=DAILY;INTERVAL=3", "2026-10-14")
+    );
+    assert_eq!(
+      repeat("water plants next monday every other week"),
+      expect("water plants", "FREQ=WEEKLY;INTERVAL=2", "2026-10-19")
+    );
+    assert_eq!(
+      repeat("Team sync every thursday 4pm"),
+      expect("Team sync", "FREQ=WEEKLY;BYDAY=TH", "2026-10-15 16:00")
+    );
+  }
+
+  #[test]
+  fn keeps_later_phrases_in_the_title() {
+    check(
+      "tomorrow prepare friday demo",
+      "prepare friday demo",
+      "2026-10-15",
+    );
+    let parsed = parse("email #a #b ! @x @x", now());
+    assert_eq!(parsed.title, "email #b");
+    assert_eq!(parsed.tags, ["x"]);
+    assert!(parsed.flagged);
+  }
+}
@@ -0,0 +1,259 @@
+//! Deadline reminders.
+//!
+//! The same check as `deadlineService.checkAndNotify`, run from the Rust
+//! process so reminders still fire while the window is hidden in the tray.
+//! Each `(task, deadline)` pair is reminded once; the log lives in
+//! `deadline_reminders`, so restarts do not repeat notifications.
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeDelta, Utc};
+use rusqlite::{params, OptionalExtension};
+
+use crate::error::Result;
+use crate::storage::models::{Task, TaskFilter, UserSettings};
+use crate::storage::{now_iso, Store};
+
+/// `deadlineService` defaults: off, 30 minutes ahead.
+const DEFAULT_LEAD_MINUTES: i64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReminderPolicy {
+  pub enabled: bool,
+  pub lead: TimeDelta,
+}
+
+impl ReminderPolicy {
+  /// Reads `deadline_notification_enabled` and `deadline_notification_days`.
+  /// Despite its name the latter holds minutes, as written by the
+  /// notification settings page.
+  pub fn from_settings(settings: &UserSettings) -> Self {
+    Self {
+      enabled: settings
+        .bool("deadline_notification_enabled")
+        .unwrap_or(false),
+      lead: TimeDelta::minutes(
+        settings
+          .i64("deadline_notification_days")
+          .filter(|m| *m > 0)
+          .unwrap_or(DEFAULT_LEAD_MINUTES),
+      ),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reminder {
+  pub task_id: String,
+  pub title: String,
+  /// The task's `date` exactly as stored; part of the dedup key.
+  pub date: String,
+  pub deadline: DateTime<Utc>,
+}
+
+impl Reminder {
+  pub fn notification_title(&self) -> &'static str {
+    "任务即将截止"
+  }
+
+  pub fn notification_body(&self) -> String {
+    format!(
+      "{}\n截止时间: {}",
+      self.title,
+      self.deadline.with_timezone(&Local).format("%m/%d %H:%M")
+    )
+  }
+}
+
+/// Instant of a task `date`; bare dates mean local midnight, like `new Date()`
+/// on a date picked in the UI.
+pub(crate) fn deadline_of(date: &str) -> Option<DateTime<Utc>> {
+  if let Ok(t) = DateTime::parse_from_rfc3339(date) {
+    return Some(t.with_timezone(&Utc));
+  }
+  NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S")
+    .ok()
+    .or_else(|| {
+      NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .ok()?
+        .and_hms_opt(0, 0, 0)
+    })?
+    .and_local_timezone(Local)
+    .earliest()
+    .map(|t| t.with_timezone(&Utc))
+}
+
+/// Open tasks whose deadline falls in `(now, now + lead]`.
+pub fn upcoming(tasks: &[Task], lead: TimeDelta, now: DateTime<Utc>) -> Vec<Reminder> {
+  tasks
+    .iter()
+    .filter(|t| !t.completed && !t.deleted && !t.abandoned)
+    .filter_map(|t| {
+      let date = t.date.as_deref()?;
+      let deadline = deadline_of(date)?;
+      (deadline > now && deadline <= now + lead).then(|| Reminder {
+        task_id: t.id.clone(),
+        title: t.title.clone(),
+        date: date.to_string(),
+        deadline,
+      })
+    })
+    .collect()
+}
+
+impl Store {
+  /// Reminders due at `now` that have not been sent yet.
+  pub fn due_reminders(&self, now: DateTime<Utc>) -> Result<Vec<Reminder>> {
+    let policy = ReminderPolicy::from_settings(&self.get_user_settings()?);
+    if !policy.enabled {
+      return Ok(Vec::new());
+    }
+    let filter = TaskFilter {
+      completed: Some(false),
+      deleted: Some(false),
+      abandoned: Some(false),
+      ..Default::default()
+    };
+    let tasks = self.get_tasks(&filter, &[])?;
+    let mut candidates = upcoming(&tasks, policy.lead, now);
+    candidates.extend(self.recurring_reminders(policy.lead, now)?);
+    let conn = self.conn();
+    let mut stmt =
+      conn.prepare("select 1 from deadline_reminders where task_id = ?1 and deadline = ?2")?;
+    let mut due = Vec::new();
+    for reminder in candidates {
+      let sent = stmt
+        .query_row(params![reminder.task_id, reminder.date], |_| Ok(()))
+        .optional()?;
+      if sent.is_none() {
+        due.push(reminder);
+      }
+    }
+    Ok(due)
+  }
+
+  /// Later occurrences of recurring tasks whose deadline falls in the
+  /// window, reminded on the open instance so a series left unfinished
+  /// still announces its next due date.
+  fn recurring_reminders(&self, lead: TimeDelta, now: DateTime<Utc>) -> Result<Vec<Reminder>> {
+    let window = |t: DateTime<Utc>| t.with_timezone(&Local).naive_local();
+    let occurrences = self.recurring_occurrences_between(window(now), window(now + lead))?;
+    Ok(
+      occurrences
+        .into_iter()
+        .filter_map(|(task, date)| {
+          let deadline = deadline_of(&date)?;
+          (deadline > now && deadline <= now + lead).then_some(Reminder {
+            task_id: task.id,
+            title: task.title,
+            date,
+            deadline,
+          })
+        })
+        .collect(),
+    )
+  }
+
+  pub fn mark_reminded(&self, reminder: &Reminder) -> Result<()> {
+    self.conn().execute(
+      "insert or ignore into deadline_reminders (task_id, deadline, notified_at)
+       values (?1, ?2, ?3)",
+      params![reminder.task_id, reminder.date, now_iso()],
+    )?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::{json, Map, Value};
+
+  fn settings(value: Value) -> UserSettings {
+    UserSettings(serde_json::from_value::<Map<String, Value>>(value).unwrap())
+  }
+
+  fn task_due(store: &Store, title: &str, deadline: DateTime<Utc>) -> Task {
+    store
+      .create_task(Task {
+        title: title.into(),
+        date: Some(deadline.to_rfc3339()),
+        ..Default::default()
+      })
+      .unwrap()
+  }
+
+  #[test]
+  fn lead_time_comes_from_settings_in_minutes() {
+    let policy = ReminderPolicy::from_settings(&settings(json!({
+      "deadline_notification_enabled": true,
+      "deadline_notification_days": 1440,
+    })));
+    assert!(policy.enabled);
+    assert_eq!(policy.lead, TimeDelta::days(1));
+    let defaults = ReminderPolicy::from_settings(&UserSettings::default());
+    assert!(!defaults.enabled);
+    assert_eq!(defaults.lead, TimeDelta::minutes(30));
+  }
+
+  #[test]
+  fn reminds_each_deadline_once_and_rearms_when_it_moves() {
+    let store = Store::open_in_memory().unwrap();
+    let now = Utc::now();
+    let soon = task_due(&store, "soon", now + TimeDelta::minutes(10));
+    task_due(&store, "later", now + TimeDelta::hours(2));
+    task_due(&store, "past", now - TimeDelta::minutes(5));
+    assert!(store.due_reminders(now).unwrap().is_empty());
+
+    store
+      .save_user_settings(settings(json!({ "deadline_notification_enabled": true })))
+      .unwrap();
+    let due = store.due_reminders(now).unwrap();
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].task_id, soon.id);
+
+    store.mark_reminded(&due[0]).unwrap();
+    assert!(store.due_reminders(now).unwrap().is_empty());
+
+    let moved = (now + TimeDelta::minutes(20)).to_rfc3339();
+    store
+      .update_task(&soon.id, json!({ "date": moved }).as_object().unwrap())
+      .unwrap();
+    assert_eq!(store.due_reminders(now).unwrap().len(), 1);
+  }
+
+  #[test]
+  fn reminds_the_next_occurrence_of_an_unfinished_series() {
+    let store = Store::open_in_memory().unwrap();
+    store
+      .save_user_settings(settings(json!({ "deadline_notification_enabled": true })))
+      .unwrap();
+    let now = Utc::now();
+    let yesterday = (now + TimeDelta::minutes(10) - TimeDelta::days(1)).with_timezone(&Local);
+    let task = store
+      .create_task(Task {
+        title: "Daily log".into(),
+        date: Some(yesterday.format("%Y-%m-%d %H:%M:00").to_string()),
+        ..Default::default()
+      })
+      .unwrap();
+    store
+      .set_task_recurrence(&task.id, "FREQ=DAILY", vec![])
+      .unwrap();
+
+    let due = store.due_reminders(now).unwrap();
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].task_id, task.id);
+    assert!(due[0].deadline > now);
+    store.mark_reminded(&due[0]).unwrap();
+    assert!(store.due_reminders(now).unwrap().is_empty());
+  }
+
+  #[test]
+  fn bare_dates_are_local_midnight() {
+    let deadline = deadline_of("2026-10-20").unwrap();
+    let local = deadline.with_timezone(&Local);
+    assert_eq!(
+      local.format("%Y-%m-%d %H:%M").to_string(),
+      "2026-10-20 00:00"
+    );
+  }
+}
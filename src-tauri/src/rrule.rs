@@ -0,0 +1,639 @@
+//! RFC 5545 recurrence rules.
+//!
+//! Supports the `RRULE` parts a task list needs: `FREQ` (daily to yearly),
+//! `INTERVAL`, `COUNT`, `UNTIL`, `BYDAY` (with ordinals such as `-1FR` for
+//! monthly and yearly rules), `BYMONTHDAY`, `BYMONTH`, `BYSETPOS` and
+//! `WKST`. Occurrences are expanded in local wall-clock time, so a task due
+//! every Monday at 09:00 stays at 09:00 across daylight saving changes.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{
+  DateTime, Datelike, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Weekday,
+};
+
+use crate::error::{Error, Result};
+
+/// Periods scanned before giving up on a rule that never matches again,
+/// e.g. `FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=30`.
+const MAX_PERIODS: u32 = 50_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+  Daily,
+  Weekly,
+  Monthly,
+  Yearly,
+}
+
+/// A `BYDAY` entry: a weekday, optionally the nth (or nth from last) one in
+/// the month or year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WeekdayNum {
+  pub nth: Option<i32>,
+  pub weekday: Weekday,
+}
+
+/// End of a rule. A date-only `UNTIL` includes that whole day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Until {
+  Date(NaiveDate),
+  /// Floating local time.
+  Local(NaiveDateTime),
+  /// `...Z`, kept in UTC for serialization.
+  Utc(DateTime<chrono::Utc>),
+}
+
+impl Until {
+  fn allows(&self, at: NaiveDateTime) -> bool {
+    match self {
+      Until::Date(day) => at.date() <= *day,
+      Until::Local(until) => at <= *until,
+      Until::Utc(until) => at <= until.with_timezone(&Local).naive_local(),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RRule {
+  pub freq: Frequency,
+  pub interval: u32,
+  pub count: Option<u32>,
+  pub until: Option<Until>,
+  pub by_day: Vec<WeekdayNum>,
+  pub by_month_day: Vec<i32>,
+  pub by_month: Vec<u32>,
+  pub by_set_pos: Vec<i32>,
+  pub week_start: Weekday,
+}
+
+fn invalid(message: impl fmt::Display) -> Error {
+  Error::InvalidInput(format!("invalid rrule: {message}"))
+}
+
+fn parse_weekday(s: &str) -> Option<Weekday> {
+  Some(match s {
+    "MO" => Weekday::Mon,
+    "TU" => Weekday::Tue,
+    "WE" => Weekday::Wed,
+    "TH" => Weekday::Thu,
+    "FR" => Weekday::Fri,
+    "SA" => Weekday::Sat,
+    "SU" => Weekday::Sun,
+    _ => return None,
+  })
+}
+
+fn weekday_code(day: Weekday) -> &'static str {
+  match day {
+    Weekday::Mon => "MO",
+    Weekday::Tue => "TU",
+    Weekday::Wed => "WE",
+    Weekday::Thu => "TH",
+    Weekday::Fri => "FR",
+    Weekday::Sat => "SA",
+    Weekday::Sun => "SU",
+  }
+}
+
+fn parse_list<T>(value: &str, part: &str, f: impl Fn(&str) -> Option<T>) -> Result<Vec<T>> {
+  value
+    .split(',')
+    .map(|item| f(item.trim()).ok_or_else(|| invalid(format!("bad {part} value `{item}`"))))
+    .collect()
+}
+
+fn parse_ranged(value: &str, part: &str, max: i32) -> Result<Vec<i32>> {
+  parse_list(value, part, |item| {
+    item
+      .parse::<i32>()
+      .ok()
+      .filter(|n| *n != 0 && n.abs() <= max)
+  })
+}
+
+fn parse_until(value: &str) -> Result<Until> {
+  if let Ok(day) = NaiveDate::parse_from_str(value, "%Y%m%d") {
+    return Ok(Until::Date(day));
+  }
+  if let Some(utc) = value.strip_suffix('Z') {
+    return NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")
+      .map(|t| Until::Utc(t.and_utc()))
+      .map_err(|_| invalid(format!("bad UNTIL `{value}`")));
+  }
+  NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+    .map(Until::Local)
+    .map_err(|_| invalid(format!("bad UNTIL `{value}`")))
+}
+
+impl FromStr for RRule {
+  type Err = Error;
+
+  /// Parses `FREQ=WEEKLY;BYDAY=MO,WE`, with or without an `RRULE:` prefix.
+  fn from_str(s: &str) -> Result<Self> {
+    let s = s.trim();
+    let body = s
+      .get(..6)
+      .filter(|p| p.eq_ignore_ascii_case("RRULE:"))
+      .map_or(s, |_| &s[6..]);
+
+    let mut freq = None;
+    let mut rule = RRule {
+      freq: Frequency::Daily,
+      interval: 1,
+      count: None,
+      until: None,
+      by_day: Vec::new(),
+      by_month_day: Vec::new(),
+      by_month: Vec::new(),
+      by_set_pos: Vec::new(),
+      week_start: Weekday::Mon,
+    };
+    for part in body.split(';').filter(|p| !p.trim().is_empty()) {
+      let (key, value) = part
+        .split_once('=')
+        .ok_or_else(|| invalid(format!("expected KEY=VALUE, got `{part}`")))?;
+      let value = value.trim().to_ascii_uppercase();
+      match key.trim().to_ascii_uppercase().as_str() {
+        "FREQ" => {
+          freq = Some(match value.as_str() {
+            "DAILY" => Frequency::Daily,
+            "WEEKLY" => Frequency::Weekly,
+            "MONTHLY" => Frequency::Monthly,
+            "YEARLY" => Frequency::Yearly,
+            _ => return Err(invalid(format!("unsupported FREQ `{value}`"))),
+          })
+        }
+        "INTERVAL" => {
+          rule.interval = value
+            .parse()
+            .ok()
+            .filter(|n| *n > 0)
+            .ok_or_else(|| invalid(format!("bad INTERVAL `{value}`")))?
+        }
+        "COUNT" => {
+          rule.count = Some(
+            value
+              .parse()
+              .ok()
+              .filter(|n| *n > 0)
+              .ok_or_else(|| invalid(format!("bad COUNT `{value}`")))?,
+          )
+        }
+        "UNTIL" => rule.until = Some(parse_until(&value)?),
+        "BYDAY" => {
+          rule.by_day = parse_list(&value, "BYDAY", |item| {
+            let split = item.len().checked_sub(2)?;
+            let weekday = parse_weekday(item.get(split..)?)?;
+            let nth = match &item[..split] {
+              "" => None,
+              n => Some(
+                n.trim_start_matches('+')
+                  .parse::<i32>()
+                  .ok()
+                  .filter(|n| *n != 0 && n.abs() <= 53)?,
+              ),
+            };
+            Some(WeekdayNum { nth, weekday })
+          })?
+        }
+        "BYMONTHDAY" => rule.by_month_day = parse_ranged(&value, "BYMONTHDAY", 31)?,
+        "BYMONTH" => {
+          rule.by_month = parse_list(&value, "BYMONTH", |item| {
+            item.parse::<u32>().ok().filter(|m| (1..=12).contains(m))
+          })?
+        }
+        "BYSETPOS" => rule.by_set_pos = parse_ranged(&value, "BYSETPOS", 366)?,
+        "WKST" => {
+          rule.week_start =
+            parse_weekday(&value).ok_or_else(|| invalid(format!("bad WKST `{value}`")))?
+        }
+        other => return Err(invalid(format!("unsupported part `{other}`"))),
+      }
+    }
+
+    rule.freq = freq.ok_or_else(|| invalid("FREQ is required"))?;
+    if rule.count.is_some() && rule.until.is_some() {
+      return Err(invalid("COUNT and UNTIL cannot be combined"));
+    }
+    if matches!(rule.freq, Frequency::Daily | Frequency::Weekly)
+      && rule.by_day.iter().any(|d| d.nth.is_some())
+    {
+      return Err(invalid(
+        "BYDAY ordinals only apply to MONTHLY and YEARLY rules",
+      ));
+    }
+    if rule.freq == Frequency::Weekly && !rule.by_month_day.is_empty() {
+      return Err(invalid("BYMONTHDAY does not apply to WEEKLY rules"));
+    }
+    Ok(rule)
+  }
+}
+
+impl fmt::Display for RRule {
+  /// Canonical form, without the `RRULE:` prefix.
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let freq = match self.freq {
+      Frequency::Daily => "DAILY",
+      Frequency::Weekly => "WEEKLY",
+      Frequency::Monthly => "MONTHLY",
+      Frequency::Yearly => "YEARLY",
+    };
+    write!(f, "FREQ={freq}")?;
+    if self.interval != 1 {
+      write!(f, ";INTERVAL={}", self.interval)?;
+    }
+    if let Some(count) = self.count {
+      write!(f, ";COUNT={count}")?;
+    }
+    match self.until {
+      Some(Until::Date(day)) => write!(f, ";UNTIL={}", day.format("%Y%m%d"))?,
+      Some(Until::Local(t)) => write!(f, ";UNTIL={}", t.format("%Y%m%dT%H%M%S"))?,
+      Some(Until::Utc(t)) => write!(f, ";UNTIL={}", t.format("%Y%m%dT%H%M%SZ"))?,
+      None => {}
+    }
+    let join = |items: Vec<String>| items.join(",");
+    if !self.by_day.is_empty() {
+      let days = self
+        .by_day
+        .iter()
+        .map(|d| match d.nth {
+          Some(n) => format!("{n}{}", weekday_code(d.weekday)),
+          None => weekday_code(d.weekday).to_string(),
+        })
+        .collect();
+      write!(f, ";BYDAY={}", join(days))?;
+    }
+    if !self.by_month_day.is_empty() {
+      let days = self.by_month_day.iter().map(i32::to_string).collect();
+      write!(f, ";BYMONTHDAY={}", join(days))?;
+    }
+    if !self.by_month.is_empty() {
+      let months = self.by_month.iter().map(u32::to_string).collect();
+      write!(f, ";BYMONTH={}", join(months))?;
+    }
+    if !self.by_set_pos.is_empty() {
+      let positions = self.by_set_pos.iter().map(i32::to_string).collect();
+      write!(f, ";BYSETPOS={}", join(positions))?;
+    }
+    if self.week_start != Weekday::Mon {
+      write!(f, ";WKST={}", weekday_code(self.week_start))?;
+    }
+    Ok(())
+  }
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+  let first = NaiveDate::from_ymd_opt(year, month, 1).expect("valid month");
+  let next = first + Months::new(1);
+  (next - first).num_days() as u32
+}
+
+/// Resolve a 1-based position that may count from the end.
+fn position(n: i32, len: usize) -> Option<usize> {
+  let len = len as i32;
+  let index = if n > 0 { n - 1 } else { len + n };
+  (0..len).contains(&index).then_some(index as usize)
+}
+
+impl RRule {
+  /// Occurrences on or after `start`, which is itself the first one when
+  /// it matches the rule. `COUNT` counts from `start`.
+  pub fn occurrences(&self, start: NaiveDateTime) -> Occurrences<'_> {
+    Occurrences {
+      rule: self,
+      start,
+      period: 0,
+      buffer: VecDeque::new(),
+      emitted: 0,
+      done: false,
+    }
+  }
+
+  fn month_matches(&self, day: NaiveDate) -> bool {
+    self.by_month.is_empty() || self.by_month.contains(&day.month())
+  }
+
+  fn weekday_matches(&self, day: NaiveDate) -> bool {
+    self.by_day.is_empty() || self.by_day.iter().any(|d| d.weekday == day.weekday())
+  }
+
+  fn month_day_matches(&self, day: NaiveDate) -> bool {
+    let len = days_in_month(day.year(), day.month()) as i32;
+    let d = day.day() as i32;
+    self.by_month_day.is_empty()
+      || self
+        .by_month_day
+        .iter()
+        .any(|n| *n == d || len + 1 + *n == d)
+  }
+
+  /// `BYDAY` over a span (a month or a whole year), honouring ordinals.
+  fn by_day_in(&self, days: &[NaiveDate]) -> Vec<NaiveDate> {
+    let mut picked = Vec::new();
+    for spec in &self.by_day {
+      let matching: Vec<NaiveDate> = days
+        .iter()
+        .copied()
+        .filter(|d| d.weekday() == spec.weekday)
+        .collect();
+      match spec.nth {
+        None => picked.extend(matching),
+        Some(n) => picked.extend(position(n, matching.len()).map(|i| matching[i])),
+      }
+    }
+    picked
+  }
+
+  fn month_candidates(&self, year: i32, month: u32, start: NaiveDate) -> Vec<NaiveDate> {
+    let days: Vec<NaiveDate> = (1..=days_in_month(year, month))
+      .filter_map(|d| NaiveDate::from_ymd_opt(year, month, d))
+      .collect();
+    match (self.by_day.is_empty(), self.by_month_day.is_empty()) {
+      (true, true) => days
+        .into_iter()
+        .filter(|d| d.day() == start.day())
+        .collect(),
+      (true, false) => days
+        .into_iter()
+        .filter(|d| self.month_day_matches(*d))
+        .collect(),
+      (false, _) => self
+        .by_day_in(&days)
+        .into_iter()
+        .filter(|d| self.month_day_matches(*d))
+        .collect(),
+    }
+  }
+
+  fn candidates(&self, period: u32, start: NaiveDate) -> Vec<NaiveDate> {
+    let step = period.saturating_mul(self.interval);
+    let mut days = match self.freq {
+      Frequency::Daily => start
+        .checked_add_signed(TimeDelta::days(step as i64))
+        .filter(|d| {
+          self.month_matches(*d) && self.weekday_matches(*d) && self.month_day_matches(*d)
+        })
+        .into_iter()
+        .collect(),
+      Frequency::Weekly => {
+        let offset =
+          (7 + start.weekday().num_days_from_monday() - self.week_start.num_days_from_monday()) % 7;
+        let week = start - TimeDelta::days(offset as i64) + TimeDelta::weeks(step as i64);
+        (0..7)
+          .map(|i| week + TimeDelta::days(i))
+          .filter(|d| {
+            let weekday = if self.by_day.is_empty() {
+              d.weekday() == start.weekday()
+            } else {
+              self.weekday_matches(*d)
+            };
+            weekday && self.month_matches(*d)
+          })
+          .collect()
+      }
+      Frequency::Monthly => {
+        let first = start.with_day(1).expect("first of month");
+        let Some(month) = first.checked_add_months(Months::new(step)) else {
+          return Vec::new();
+        };
+        if !self.month_matches(month) {
+          return Vec::new();
+        }
+        self.month_candidates(month.year(), month.month(), start)
+      }
+      Frequency::Yearly => {
+        let year = start.year() + step as i32;
+        if !self.by_month.is_empty() {
+          self
+            .by_month
+            .iter()
+            .flat_map(|m| self.month_candidates(year, *m, start))
+            .collect()
+        } else if self.by_day.is_empty() && self.by_month_day.is_empty() {
+          NaiveDate::from_ymd_opt(year, start.month(), start.day())
+            .into_iter()
+            .collect()
+        } else if self.by_month_day.is_empty() {
+          // BYDAY alone on a yearly rule counts ordinals within the year.
+          let days: Vec<NaiveDate> = NaiveDate::from_ymd_opt(year, 1, 1)
+            .into_iter()
+            .flat_map(|d| d.iter_days())
+            .take_while(|d| d.year() == year)
+            .collect();
+          self.by_day_in(&days)
+        } else {
+          (1..=12)
+            .flat_map(|m| self.month_candidates(year, m, start))
+            .collect()
+        }
+      }
+    };
+    days.sort();
+    days.dedup();
+    if self.by_set_pos.is_empty() {
+      return days;
+    }
+    let mut picked: Vec<NaiveDate> = self
+      .by_set_pos
+      .iter()
+      .filter_map(|n| position(*n, days.len()).map(|i| days[i]))
+      .collect();
+    picked.sort();
+    picked.dedup();
+    picked
+  }
+}
+
+/// Iterator returned by [`RRule::occurrences`].
+pub struct Occurrences<'a> {
+  rule: &'a RRule,
+  start: NaiveDateTime,
+  period: u32,
+  buffer: VecDeque<NaiveDate>,
+  emitted: u32,
+  done: bool,
+}
+
+impl Iterator for Occurrences<'_> {
+  type Item = NaiveDateTime;
+
+  fn next(&mut self) -> Option<NaiveDateTime> {
+    while !self.done {
+      let Some(day) = self.buffer.pop_front() else {
+        if self.period >= MAX_PERIODS {
+          self.done = true;
+          break;
+        }
+        let candidates = self.rule.candidates(self.period, self.start.date());
+        self.buffer.extend(candidates);
+        self.period += 1;
+        continue;
+      };
+      let at = day.and_time(self.start.time());
+      if at < self.start {
+        continue;
+      }
+      if self.rule.until.is_some_and(|u| !u.allows(at))
+        || self.rule.count.is_some_and(|c| self.emitted >= c)
+      {
+        self.done = true;
+        break;
+      }
+      self.emitted += 1;
+      return Some(at);
+    }
+    None
+  }
+}
+
+/// How a task `date` was written, so the next occurrence is stored alike.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateStyle {
+  /// `YYYY-MM-DD`
+  Day,
+  /// `YYYY-MM-DD HH:mm:ss`, local time.
+  Local,
+  /// ISO 8601 instant, written back like `Date.toISOString()`.
+  Instant,
+}
+
+/// Local wall-clock time of a task `date`, and its style.
+pub fn parse_task_date(date: &str) -> Option<(NaiveDateTime, DateStyle)> {
+  if let Ok(t) = DateTime::parse_from_rfc3339(date) {
+    return Some((t.with_timezone(&Local).naive_local(), DateStyle::Instant));
+  }
+  if let Ok(t) = NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S") {
+    return Some((t, DateStyle::Local));
+  }
+  NaiveDate::parse_from_str(date, "%Y-%m-%d")
+    .ok()
+    .map(|d| (d.and_time(NaiveTime::MIN), DateStyle::Day))
+}
+
+pub fn format_task_date(at: NaiveDateTime, style: DateStyle) -> String {
+  match style {
+    DateStyle::Day => at.format("%Y-%m-%d").to_string(),
+    DateStyle::Local => at.format("%Y-%m-%d %H:%M:%S").to_string(),
+    DateStyle::Instant => at
+      .and_local_timezone(Local)
+      .earliest()
+      .unwrap_or_else(|| at.and_utc().with_timezone(&Local))
+      .with_timezone(&chrono::Utc)
+      .to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn at(s: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+  }
+
+  fn expand(rule: &str, start: &str, n: usize) -> Vec<String> {
+    let rule: RRule = rule.parse().unwrap();
+    rule
+      .occurrences(at(start))
+      .take(n)
+      .map(|t| t.format("%Y-%m-%d").to_string())
+      .collect()
+  }
+
+  #[test]
+  fn parses_and_prints_canonical_rules() {
+    let rule: RRule = "rrule:freq=monthly;byday=-1fr;interval=2;until=20271231"
+      .parse()
+      .unwrap();
+    assert_eq!(
+      rule.to_string(),
+      "FREQ=MONTHLY;INTERVAL=2;UNTIL=20271231;BYDAY=-1FR"
+    );
+    for bad in [
+      "BYDAY=MO",
+      "FREQ=HOURLY",
+      "FREQ=WEEKLY;BYDAY=1MO",
+      "FREQ=DAILY;COUNT=0",
+      "FREQ=DAILY;COUNT=2;UNTIL=20270101",
+      "FREQ=MONTHLY;BYMONTHDAY=32",
+    ] {
+      assert!(bad.parse::<RRule>().is_err(), "{bad}");
+    }
+  }
+
+  #[test]
+  fn expands_weekly_and_monthly_rules() {
+    // Weekly report every Friday, starting on a Wednesday.
+    assert_eq!(
+      expand("FREQ=WEEKLY;BYDAY=FR", "2026-10-14 17:00", 3),
+      ["2026-10-16", "2026-10-23", "2026-10-30"]
+    );
+    assert_eq!(
+      expand(
+        "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH;COUNT=4",
+        "2026-10-19 09:00",
+        10
+      ),
+      ["2026-10-19", "2026-10-22", "2026-11-02", "2026-11-05"]
+    );
+    // Monthly review on the last Friday.
+    assert_eq!(
+      expand("FREQ=MONTHLY;BYDAY=-1FR", "2026-10-01 10:00", 3),
+      ["2026-10-30", "2026-11-27", "2026-12-25"]
+    );
+    // The 31st skips shorter months; -1 is always the last day.
+    assert_eq!(
+      expand("FREQ=MONTHLY", "2026-01-31 08:00", 3),
+      ["2026-01-31", "2026-03-31", "2026-05-31"]
+    );
+    assert_eq!(
+      expand("FREQ=MONTHLY;BYMONTHDAY=-1", "2026-01-15 08:00", 3),
+      ["2026-01-31", "2026-02-28", "2026-03-31"]
+    );
+    // Last workday of the month.
+    assert_eq!(
+      expand(
+        "FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1",
+        "2026-10-01 08:00",
+        2
+      ),
+      ["2026-10-30", "2026-11-30"]
+    );
+  }
+
+  #[test]
+  fn expands_daily_and_yearly_rules_with_limits() {
+    assert_eq!(
+      expand("FREQ=DAILY;UNTIL=20261020", "2026-10-18 09:00", 10),
+      ["2026-10-18", "2026-10-19", "2026-10-20"]
+    );
+    assert_eq!(
+      expand("FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR", "2026-10-16 09:00", 3),
+      ["2026-10-16", "2026-10-19", "2026-10-20"]
+    );
+    assert_eq!(
+      expand("FREQ=YEARLY", "2024-02-29 09:00", 2),
+      ["2024-02-29", "2028-02-29"]
+    );
+    assert_eq!(
+      expand("FREQ=YEARLY;BYMONTH=11;BYDAY=4TH", "2026-01-01 09:00", 2),
+      ["2026-11-26", "2027-11-25"]
+    );
+    assert!(expand("FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=30", "2026-01-01 09:00", 1).is_empty());
+  }
+
+  #[test]
+  fn keeps_the_task_date_style() {
+    let (t, style) = parse_task_date("2026-10-18").unwrap();
+    assert_eq!(style, DateStyle::Day);
+    assert_eq!(format_task_date(t, style), "2026-10-18");
+    let (t, style) = parse_task_date("2026-10-18 09:30:00").unwrap();
+    assert_eq!(format_task_date(t, style), "2026-10-18 09:30:00");
+    let (t, style) = parse_task_date("2026-10-18T01:30:00.000Z").unwrap();
+    assert_eq!(style, DateStyle::Instant);
+    assert_eq!(format_task_date(t, style), "2026-10-18T01:30:00.000Z");
+  }
+}
@@ -0,0 +1,63 @@
+//! Task activity operations.
+
+use rusqlite::{params, Connection, Row};
+
+use super::models::TaskActivity;
+use super::{new_id, now_iso, Store};
+use crate::error::Result;
+
+pub(crate) fn activity_from_row(row: &Row<'_>) -> rusqlite::Result<TaskActivity> {
+  let metadata: Option<String> = row.get("metadata")?;
+  Ok(TaskActivity {
+    id: row.get("id")?,
+    task_id: row.get("task_id")?,
+    user_id: row.get("user_id")?,
+    action: row.get("action")?,
+    metadata: metadata.and_then(|m| serde_json::from_str(&m).ok()),
+    created_at: row.get("created_at")?,
+  })
+}
+
+pub(crate) fn write_activity(conn: &Connection, activity: &TaskActivity) -> Result<()> {
+  let metadata = activity
+    .metadata
+    .as_ref()
+    .map(serde_json::to_string)
+    .transpose()?;
+  conn.execute(
+    "insert or replace into task_activities (id, task_id, user_id, action, metadata, created_at) \
+     values (?1, ?2, ?3, ?4, ?5, ?6)",
+    params![
+      activity.id,
+      activity.task_id,
+      activity.user_id,
+      activity.action,
+      metadata,
+      activity.created_at,
+    ],
+  )?;
+  Ok(())
+}
+
+impl Store {
+  /// Activities for a task in chronological order.
+  pub fn get_task_activities(&self, task_id: &str) -> Result<Vec<TaskActivity>> {
+    let conn = self.conn();
+    let mut stmt =
+      conn.prepare("select * from task_activities where task_id = ?1 order by created_at asc")?;
+    let activities = stmt
+      .query_map([task_id], activity_from_row)?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(activities)
+  }
+
+  pub fn create_task_activity(&self, input: TaskActivity) -> Result<TaskActivity> {
+    let activity = TaskActivity {
+      id: new_id(),
+      created_at: now_iso(),
+      ..input
+    };
+    write_activity(&self.conn(), &activity)?;
+    Ok(activity)
+  }
+}
@@ -0,0 +1,92 @@
+//! File storage operations.
+//!
+//! Files are kept inside the database and handed back as `data:` URLs, the
+//! same representation `IndexedDBAdapter` uses, so the editor and task
+//! detail views render them without any extra plumbing.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::Utc;
+use rusqlite::params;
+
+use super::models::{FileUploadResult, UploadFile};
+use super::profile::ProfileUpdate;
+use super::{new_id, now_iso, Store};
+use crate::error::Result;
+
+fn data_url(file: &UploadFile) -> String {
+  format!("data:{};base64,{}", file.kind, STANDARD.encode(&file.data))
+}
+
+/// `<millis>_<random>.<ext>`, the naming scheme of the TypeScript adapters.
+fn stored_file_name(original: &str) -> String {
+  let ext = original.rsplit('.').next().unwrap_or_default();
+  let random = new_id().replace('-', "");
+  format!("{}_{}.{}", Utc::now().timestamp_millis(), &random[..8], ext)
+}
+
+impl Store {
+  pub fn upload_attachment(&self, task_id: &str, file: UploadFile) -> Result<FileUploadResult> {
+    let result = FileUploadResult {
+      id: new_id(),
+      filename: stored_file_name(&file.name),
+      original_name: file.name.clone(),
+      url: data_url(&file),
+      size: file.data.len() as i64,
+      kind: file.kind.clone(),
+      uploaded_at: now_iso(),
+    };
+    self.conn().execute(
+      "insert into attachments (id, task_id, filename, original_name, type, size, data, uploaded_at) \
+       values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+      params![
+        result.id,
+        task_id,
+        result.filename,
+        result.original_name,
+        result.kind,
+        result.size,
+        file.data,
+        result.uploaded_at,
+      ],
+    )?;
+    Ok(result)
+  }
+
+  pub fn delete_attachment(&self, id: &str) -> Result<bool> {
+    self
+      .conn()
+      .execute("delete from attachments where id = ?1", [id])?;
+    Ok(true)
+  }
+
+  /// Editor images are inlined into the document, nothing is persisted.
+  pub fn upload_image(&self, file: UploadFile) -> Result<FileUploadResult> {
+    Ok(FileUploadResult {
+      id: format!("local_{}", Utc::now().timestamp_millis()),
+      filename: stored_file_name(&file.name),
+      original_name: file.name.clone(),
+      url: data_url(&file),
+      size: file.data.len() as i64,
+      kind: file.kind,
+      uploaded_at: now_iso(),
+    })
+  }
+
+  pub fn upload_avatar(&self, file: UploadFile) -> Result<FileUploadResult> {
+    let url = data_url(&file);
+    self.save_user_profile(ProfileUpdate {
+      avatar_data: Some(url.clone()),
+      ..Default::default()
+    })?;
+    Ok(FileUploadResult {
+      id: "local_avatar".to_string(),
+      filename: file.name.clone(),
+      original_name: file.name,
+      url,
+      size: file.data.len() as i64,
+      kind: file.kind,
+      uploaded_at: now_iso(),
+    })
+  }
+}
@@ -0,0 +1,132 @@
+//! Daily check-in operations.
+
+use std::collections::BTreeSet;
+
+use chrono::{DateTime, Local, NaiveDate};
+use rusqlite::{params, Connection, Row};
+
+use super::models::{CheckInHistory, CheckInRecord};
+use super::{new_id, now_iso, Store};
+use crate::error::Result;
+
+pub(crate) fn checkin_from_row(row: &Row<'_>) -> rusqlite::Result<CheckInRecord> {
+  Ok(CheckInRecord {
+    id: row.get("id")?,
+    user_id: row.get("user_id")?,
+    check_in_time: row.get("check_in_time")?,
+    note: row.get("note")?,
+    created_at: row.get("created_at")?,
+  })
+}
+
+pub(crate) fn write_checkin(conn: &Connection, record: &CheckInRecord) -> Result<()> {
+  conn.execute(
+    "insert or replace into checkin_records (id, user_id, check_in_time, note, created_at) \
+     values (?1, ?2, ?3, ?4, ?5)",
+    params![
+      record.id,
+      record.user_id,
+      record.check_in_time,
+      record.note,
+      record.created_at,
+    ],
+  )?;
+  Ok(())
+}
+
+/// Local calendar day of a stored `check_in_time`.
+fn local_day(timestamp: &str) -> Option<NaiveDate> {
+  DateTime::parse_from_rfc3339(timestamp)
+    .ok()
+    .map(|t| t.with_timezone(&Local).date_naive())
+}
+
+/// Consecutive days ending today or yesterday, as in `IndexedDBAdapter.getCheckInStreak`.
+pub(crate) fn streak(days: &BTreeSet<NaiveDate>, today: NaiveDate) -> i64 {
+  let mut iter = days.iter().rev();
+  let Some(mut current) = iter.next().copied() else {
+    return 0;
+  };
+  if (today - current).num_days() > 1 {
+    return 0;
+  }
+  let mut streak = 1;
+  for day in iter {
+    if (current - *day).num_days() != 1 {
+      break;
+    }
+    streak += 1;
+    current = *day;
+  }
+  streak
+}
+
+impl Store {
+  fn checkin_days(&self) -> Result<BTreeSet<NaiveDate>> {
+    let conn = self.conn();
+    let mut stmt = conn.prepare("select check_in_time from checkin_records")?;
+    let days = stmt
+      .query_map([], |row| row.get::<_, String>(0))?
+      .filter_map(|t| t.ok().as_deref().and_then(local_day))
+      .collect();
+    Ok(days)
+  }
+
+  pub fn has_checked_in_today(&self) -> Result<bool> {
+    Ok(self.checkin_days()?.contains(&Local::now().date_naive()))
+  }
+
+  pub fn create_check_in(&self, note: Option<String>) -> Result<CheckInRecord> {
+    let now = now_iso();
+    let record = CheckInRecord {
+      id: new_id(),
+      user_id: None,
+      check_in_time: now.clone(),
+      note: note.filter(|n| !n.is_empty()),
+      created_at: now,
+    };
+    write_checkin(&self.conn(), &record)?;
+    Ok(record)
+  }
+
+  /// Newest first, `page` is 1-based.
+  pub fn get_check_in_history(&self, page: i64, page_size: i64) -> Result<CheckInHistory> {
+    let conn = self.conn();
+    let total = conn.query_row("select count(*) from checkin_records", [], |row| row.get(0))?;
+    let mut stmt = conn
+      .prepare("select * from checkin_records order by check_in_time desc limit ?1 offset ?2")?;
+    let records = stmt
+      .query_map(
+        params![page_size, (page.max(1) - 1) * page_size],
+        checkin_from_row,
+      )?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(CheckInHistory { records, total })
+  }
+
+  pub fn get_check_in_streak(&self) -> Result<i64> {
+    Ok(streak(&self.checkin_days()?, Local::now().date_naive()))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn day(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+  }
+
+  #[test]
+  fn streak_counts_back_from_yesterday_and_stops_at_gaps() {
+    let days: BTreeSet<_> = ["2026-10-10", "2026-10-12", "2026-10-13", "2026-10-14"]
+      .into_iter()
+      .map(day)
+      .collect();
+
+    assert_eq!(streak(&days, day("2026-10-14")), 3);
+    assert_eq!(streak(&days, day("2026-10-15")), 3);
+    assert_eq!(streak(&days, day("2026-10-16")), 0);
+    assert_eq!(streak(&BTreeSet::new(), day("2026-10-16")), 0);
+  }
+}
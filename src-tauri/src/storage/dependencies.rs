@@ -0,0 +1,259 @@
+//! Task dependencies: "blocked by" links between tasks.
+//!
+//! Links live in `task_dependencies`; cycle checks, the derived blocked
+//! state and the "what can I do next" order come from [`crate::graph`].
+//! A blocker counts as open while it is neither completed, trashed nor
+//! abandoned.
+
+use std::collections::HashSet;
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use super::models::Task;
+use super::tasks::{read_task, task_from_row, TASK_COLUMNS};
+use super::{due_day, now_iso, Store};
+use crate::error::{Error, Result};
+use crate::graph::DependencyGraph;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TaskDependencies {
+  /// Tasks this one waits on.
+  pub blocked_by: Vec<Task>,
+  /// Tasks waiting on this one.
+  pub blocking: Vec<Task>,
+  /// Whether any of `blocked_by` is still open.
+  pub blocked: bool,
+}
+
+/// One entry of [`Store::next_actions`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NextAction {
+  pub task: Task,
+  /// 0 for tasks that can be done now, n for tasks that wait on n rounds
+  /// of other work first.
+  pub level: usize,
+  pub blocked: bool,
+}
+
+fn load_graph(conn: &Connection) -> Result<DependencyGraph> {
+  let mut stmt = conn.prepare_cached("select task_id, blocker_id from task_dependencies")?;
+  let edges = stmt
+    .query_map([], |row| {
+      Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?
+    .collect::<rusqlite::Result<Vec<_>>>()?;
+  Ok(DependencyGraph::from_edges(edges))
+}
+
+fn open_tasks(conn: &Connection) -> Result<Vec<Task>> {
+  let mut stmt = conn.prepare(&format!(
+    "select {TASK_COLUMNS} from tasks where completed = 0 and deleted = 0 and abandoned = 0"
+  ))?;
+  let tasks = stmt
+    .query_map([], task_from_row)?
+    .collect::<rusqlite::Result<Vec<_>>>()?;
+  Ok(tasks)
+}
+
+fn open_ids(conn: &Connection) -> Result<HashSet<String>> {
+  Ok(open_tasks(conn)?.into_iter().map(|t| t.id).collect())
+}
+
+fn read_tasks<'a>(conn: &Connection, ids: impl Iterator<Item = &'a str>) -> Result<Vec<Task>> {
+  let mut tasks = Vec::new();
+  for id in ids {
+    tasks.extend(read_task(conn, id)?);
+  }
+  Ok(tasks)
+}
+
+fn dependencies_of(conn: &Connection, task_id: &str) -> Result<TaskDependencies> {
+  let graph = load_graph(conn)?;
+  let open = open_ids(conn)?;
+  Ok(TaskDependencies {
+    blocked_by: read_tasks(conn, graph.blockers_of(task_id))?,
+    blocking: read_tasks(conn, graph.dependents_of(task_id))?,
+    blocked: graph.is_blocked(task_id, |id| open.contains(id)),
+  })
+}
+
+impl Store {
+  /// Record that `task_id` is blocked by `blocker_id`. Fails if either task
+  /// is missing or the link would close a cycle.
+  pub fn add_task_dependency(&self, task_id: &str, blocker_id: &str) -> Result<TaskDependencies> {
+    self.transaction(|tx| {
+      for id in [task_id, blocker_id] {
+        if read_task(tx, id)?.is_none() {
+          return Err(Error::NotFound("task", id.to_string()));
+        }
+      }
+      if let Some(cycle) = load_graph(tx)?.cycle_with(task_id, blocker_id) {
+        let titles: Vec<String> = cycle
+          .0
+          .iter()
+          .map(|id| Ok(read_task(tx, id)?.map_or_else(|| id.clone(), |t| t.title)))
+          .collect::<Result<_>>()?;
+        return Err(Error::InvalidInput(format!(
+          "dependency cycle: {}",
+          titles.join(" -> ")
+        )));
+      }
+      tx.execute(
+        "insert or ignore into task_dependencies (task_id, blocker_id, created_at) \
+         values (?1, ?2, ?3)",
+        params![task_id, blocker_id, now_iso()],
+      )?;
+      dependencies_of(tx, task_id)
+    })
+  }
+
+  pub fn remove_task_dependency(&self, task_id: &str, blocker_id: &str) -> Result<bool> {
+    let removed = self.conn().execute(
+      "delete from task_dependencies where task_id = ?1 and blocker_id = ?2",
+      params![task_id, blocker_id],
+    )?;
+    Ok(removed > 0)
+  }
+
+  pub fn get_task_dependencies(&self, task_id: &str) -> Result<TaskDependencies> {
+    dependencies_of(&self.conn(), task_id)
+  }
+
+  /// Open tasks with at least one open blocker.
+  pub fn blocked_task_ids(&self) -> Result<Vec<String>> {
+    let conn = self.conn();
+    let graph = load_graph(&conn)?;
+    let open = open_ids(&conn)?;
+    let mut blocked: Vec<String> = open
+      .iter()
+      .filter(|id| graph.is_blocked(id, |b| open.contains(b)))
+      .cloned()
+      .collect();
+    blocked.sort();
+    Ok(blocked)
+  }
+
+  /// Open tasks whose last open blocker was `blocker_id`; call it after
+  /// `blocker_id` was completed.
+  pub fn unblocked_by(&self, blocker_id: &str) -> Result<Vec<Task>> {
+    let conn = self.conn();
+    let graph = load_graph(&conn)?;
+    let open = open_ids(&conn)?;
+    let ids = graph.unblocked_by(blocker_id, |id| open.contains(id));
+    read_tasks(&conn, ids.iter().map(String::as_str))
+  }
+
+  /// Open tasks, optionally of one project, in an order that respects their
+  /// dependencies: what can be done now first (level 0), then what that
+  /// work unblocks, and so on. Within a level, earlier due dates come first.
+  pub fn next_actions(&self, project_id: Option<&str>) -> Result<Vec<NextAction>> {
+    let conn = self.conn();
+    let graph = load_graph(&conn)?;
+    let all = open_tasks(&conn)?;
+    let open: HashSet<&str> = all.iter().map(|t| t.id.as_str()).collect();
+
+    let mut tasks: Vec<&Task> = all
+      .iter()
+      .filter(|t| project_id.map_or(true, |p| t.project.as_deref() == Some(p)))
+      .collect();
+    tasks.sort_by(|a, b| {
+      let due = |t: &Task| t.date.as_deref().and_then(due_day);
+      match (due(a), due(b)) {
+        (Some(x), Some(y)) => x.cmp(&y),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+      }
+      .then_with(|| {
+        a.sort_order
+          .unwrap_or(f64::MAX)
+          .total_cmp(&b.sort_order.unwrap_or(f64::MAX))
+      })
+    });
+
+    let ids: Vec<String> = tasks.iter().map(|t| t.id.clone()).collect();
+    let mut actions = Vec::with_capacity(ids.len());
+    for (level, layer) in graph
+      .layers(&ids, |id| open.contains(id))
+      .into_iter()
+      .enumerate()
+    {
+      for id in layer {
+        let task = tasks.iter().find(|t| t.id == id).map(|t| (*t).clone());
+        actions.extend(task.map(|task| NextAction {
+          blocked: graph.is_blocked(&task.id, |b| open.contains(b)),
+          task,
+          level,
+        }));
+      }
+    }
+    Ok(actions)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn links_block_until_done_and_order_next_actions() {
+    let store = Store::open_in_memory().unwrap();
+    let add = |title: &str, date: Option<&str>| {
+      store
+        .create_task(Task {
+          title: title.into(),
+          date: date.map(Into::into),
+          ..Default::default()
+        })
+        .unwrap()
+    };
+    let design = add("design", None);
+    let build = add("build", Some("2026-10-20"));
+    let launch = add("launch", Some("2026-10-19"));
+    add("email", Some("2026-10-25"));
+    store.add_task_dependency(&build.id, &design.id).unwrap();
+    let deps = store.add_task_dependency(&launch.id, &build.id).unwrap();
+    assert!(deps.blocked);
+    assert_eq!(deps.blocked_by[0].title, "build");
+
+    let err = store
+      .add_task_dependency(&design.id, &launch.id)
+      .unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "invalid input: dependency cycle: design -> launch -> build -> design"
+    );
+    assert!(store.add_task_dependency(&design.id, "missing").is_err());
+
+    let next: Vec<(String, usize)> = store
+      .next_actions(None)
+      .unwrap()
+      .into_iter()
+      .map(|a| (a.task.title, a.level))
+      .collect();
+    assert_eq!(
+      next,
+      [
+        ("email".to_string(), 0),
+        ("design".to_string(), 0),
+        ("build".to_string(), 1),
+        ("launch".to_string(), 2)
+      ]
+    );
+    assert_eq!(store.blocked_task_ids().unwrap().len(), 2);
+
+    let done = json!({ "completed": true });
+    store
+      .update_task(&design.id, done.as_object().unwrap())
+      .unwrap();
+    let unblocked = store.unblocked_by(&design.id).unwrap();
+    assert_eq!(unblocked.len(), 1);
+    assert_eq!(unblocked[0].id, build.id);
+    assert!(!store.get_task_dependencies(&build.id).unwrap().blocked);
+
+    assert!(store.remove_task_dependency(&launch.id, &build.id).unwrap());
+    assert!(store.blocked_task_ids().unwrap().is_empty());
+  }
+}
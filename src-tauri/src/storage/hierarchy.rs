@@ -0,0 +1,424 @@
+//! Subtasks.
+//!
+//! A task points at its parent through `parent_id`, to any depth. A subtree
+//! moves, goes to the trash and comes back as one piece, a subtask always
+//! lives in its parent's project, and `sort_order` only orders a task among
+//! its siblings.
+
+use std::collections::HashMap;
+
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use super::models::Task;
+use super::tasks::{read_task, task_from_row, write_task, TASK_COLUMNS};
+use super::{now_iso, Store};
+use crate::error::{Error, Result};
+
+/// Gap between neighbouring `sort_order` values, the same as the webview's.
+pub(crate) const SORT_STEP: f64 = 1000.0;
+
+/// Where [`Store::move_task`] puts a task.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskMove {
+  /// New parent, `None` for the top level.
+  #[serde(default)]
+  pub parent_id: Option<String>,
+  /// Project of a top-level task; a subtask always takes its parent's.
+  #[serde(default)]
+  pub project: Option<String>,
+  /// Position among the new siblings; first when left out.
+  #[serde(default)]
+  pub sort_order: Option<f64>,
+}
+
+/// Completion of a task's subtasks, at every depth.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskProgress {
+  pub total: usize,
+  pub completed: usize,
+  /// 0-100; a task without subtasks counts as 0 or 100 by itself.
+  pub percent: u8,
+}
+
+impl TaskProgress {
+  /// Roll up a subtree as returned by [`Store::get_subtree`], root first.
+  /// Trashed and abandoned subtasks are left out.
+  pub fn of(subtree: &[Task]) -> Self {
+    let Some((root, descendants)) = subtree.split_first() else {
+      return Self::default();
+    };
+    let counted: Vec<&Task> = descendants
+      .iter()
+      .filter(|t| !t.deleted && !t.abandoned)
+      .collect();
+    let total = counted.len();
+    let completed = counted.iter().filter(|t| t.completed).count();
+    let percent = match total {
+      0 if root.completed => 100,
+      0 => 0,
+      _ => (completed * 100 / total) as u8,
+    };
+    Self {
+      total,
+      completed,
+      percent,
+    }
+  }
+}
+
+/// Ids of `root` and everything under it. `UNION` rather than `UNION ALL`
+/// keeps a corrupt cycle from recursing forever.
+pub(crate) fn subtree_ids(conn: &Connection, root: &str) -> Result<Vec<String>> {
+  let mut stmt = conn.prepare_cached(
+    "with recursive subtree(id) as ( \
+       select ?1 \
+       union select tasks.id from tasks join subtree on tasks.parent_id = subtree.id \
+     ) \
+     select id from subtree",
+  )?;
+  let ids = stmt
+    .query_map([root], |row| row.get(0))?
+    .collect::<rusqlite::Result<Vec<String>>>()?;
+  Ok(ids)
+}
+
+/// `root` followed by its subtasks depth first, siblings by `sort_order`.
+fn read_subtree(conn: &Connection, root_id: &str) -> Result<Vec<Task>> {
+  let ids = subtree_ids(conn, root_id)?;
+  let placeholders = vec!["?"; ids.len()].join(", ");
+  let mut stmt = conn.prepare(&format!(
+    "select {TASK_COLUMNS} from tasks where id in ({placeholders}) \
+     order by sort_order nulls last, created_at"
+  ))?;
+  let tasks = stmt
+    .query_map(params_from_iter(&ids), task_from_row)?
+    .collect::<rusqlite::Result<Vec<_>>>()?;
+
+  let mut root = None;
+  let mut by_parent: HashMap<String, Vec<Task>> = HashMap::new();
+  for task in tasks {
+    if task.id == root_id {
+      root = Some(task);
+    } else if let Some(parent_id) = task.parent_id.clone() {
+      by_parent.entry(parent_id).or_default().push(task);
+    }
+  }
+  let Some(root) = root else {
+    return Ok(Vec::new());
+  };
+  let mut ordered = vec![root];
+  let mut i = 0;
+  while i < ordered.len() {
+    // Children go right after their parent, ahead of its later siblings.
+    let children = by_parent.remove(&ordered[i].id).unwrap_or_default();
+    i += 1;
+    ordered.splice(i..i, children);
+  }
+  Ok(ordered)
+}
+
+/// `sort_order` that puts `task` first among its siblings, the way new
+/// tasks go to the top of a list.
+fn first_sort_order(conn: &Connection, task: &Task) -> Result<f64> {
+  let min: Option<f64> = conn
+    .query_row(
+      "select min(sort_order) from tasks \
+       where parent_id is ?1 and project is ?2 and id != ?3",
+      params![task.parent_id, task.project, task.id],
+      |row| row.get(0),
+    )
+    .optional()?
+    .flatten();
+  Ok(min.unwrap_or(SORT_STEP) - SORT_STEP)
+}
+
+/// Spread the siblings of `task` (itself left out) `SORT_STEP` apart in
+/// their current order; returns the ones whose position changed.
+pub(crate) fn spread_siblings(conn: &Connection, task: &Task) -> Result<Vec<Task>> {
+  let mut stmt = conn.prepare(&format!(
+    "select {TASK_COLUMNS} from tasks \
+     where parent_id is ?1 and project is ?2 and id != ?3 \
+     order by sort_order nulls last, created_at"
+  ))?;
+  let siblings = stmt
+    .query_map(
+      params![task.parent_id, task.project, task.id],
+      task_from_row,
+    )?
+    .collect::<rusqlite::Result<Vec<_>>>()?;
+  let now = now_iso();
+  let mut moved = Vec::new();
+  for (i, mut sibling) in siblings.into_iter().enumerate() {
+    let rank = (i + 1) as f64 * SORT_STEP;
+    if sibling.sort_order != Some(rank) {
+      sibling.sort_order = Some(rank);
+      sibling.updated_at = Some(now.clone());
+      write_task(conn, &sibling)?;
+      moved.push(sibling);
+    }
+  }
+  Ok(moved)
+}
+
+/// Check `task.parent_id` and apply what follows from it; with `reorder`
+/// the task also goes first among its new siblings.
+pub(crate) fn place(conn: &Connection, task: &mut Task, reorder: bool) -> Result<()> {
+  if let Some(parent_id) = &task.parent_id {
+    if subtree_ids(conn, &task.id)?.contains(parent_id) {
+      return Err(Error::InvalidInput(
+        "a task cannot be moved under itself or one of its subtasks".into(),
+      ));
+    }
+    let parent = read_task(conn, parent_id)?
+      .ok_or_else(|| Error::NotFound("parent task", parent_id.clone()))?;
+    task.project = parent.project;
+  }
+  if reorder {
+    task.sort_order = Some(first_sort_order(conn, task)?);
+  }
+  Ok(())
+}
+
+/// Keep the tree consistent when `task` replaces `existing`.
+///
+/// Moving a subtask to another project on its own takes it out of its
+/// parent; a project change is carried down to every subtask.
+pub(crate) fn rehome(
+  conn: &Connection,
+  existing: &Task,
+  task: &mut Task,
+  reorder: bool,
+) -> Result<()> {
+  if task.parent_id.is_some()
+    && task.parent_id == existing.parent_id
+    && task.project != existing.project
+  {
+    task.parent_id = None;
+  }
+  if task.parent_id == existing.parent_id && task.project == existing.project {
+    return Ok(());
+  }
+  place(conn, task, reorder && task.parent_id != existing.parent_id)?;
+  if task.project != existing.project {
+    let now = now_iso();
+    let mut stmt =
+      conn.prepare_cached("update tasks set project = ?1, updated_at = ?2 where id = ?3")?;
+    for id in subtree_ids(conn, &task.id)?.iter().skip(1) {
+      stmt.execute(params![task.project, now, id])?;
+    }
+  }
+  Ok(())
+}
+
+impl Store {
+  /// A task and all of its subtasks in one call: the root first, then depth
+  /// first with siblings in list order. Empty if `root_id` does not exist.
+  pub fn get_subtree(&self, root_id: &str) -> Result<Vec<Task>> {
+    read_subtree(&self.conn(), root_id)
+  }
+
+  pub fn task_progress(&self, id: &str) -> Result<TaskProgress> {
+    Ok(TaskProgress::of(&self.get_subtree(id)?))
+  }
+
+  /// Put a task under another parent or at the top level of a project;
+  /// its subtasks come along.
+  pub fn move_task(&self, id: &str, target: &TaskMove) -> Result<Option<Task>> {
+    self.transaction(|tx| {
+      let Some(existing) = read_task(tx, id)? else {
+        return Ok(None);
+      };
+      let mut task = Task {
+        parent_id: target.parent_id.clone(),
+        project: target.project.clone(),
+        sort_order: target.sort_order,
+        updated_at: Some(now_iso()),
+        ..existing.clone()
+      };
+      rehome(tx, &existing, &mut task, target.sort_order.is_none())?;
+      if task.sort_order.is_none() {
+        task.sort_order = Some(first_sort_order(tx, &task)?);
+      }
+      write_task(tx, &task)?;
+      Ok(Some(task))
+    })
+  }
+
+  /// Move a task and its subtasks to the trash; returns the tasks that
+  /// changed. They share one `deleted_at`, which is how
+  /// [`Store::restore_task`] knows what went in together.
+  pub fn trash_task(&self, id: &str) -> Result<Vec<Task>> {
+    self.transaction(|tx| {
+      let now = now_iso();
+      let mut trashed = Vec::new();
+      for mut task in read_subtree(tx, id)? {
+        if task.deleted {
+          continue;
+        }
+        task.deleted = true;
+        task.deleted_at = Some(now.clone());
+        task.updated_at = Some(now.clone());
+        write_task(tx, &task)?;
+        trashed.push(task);
+      }
+      Ok(trashed)
+    })
+  }
+
+  /// Bring a task back from the trash with the subtasks trashed along with
+  /// it. If its parent is still in the trash, it comes back at the top level.
+  pub fn restore_task(&self, id: &str) -> Result<Vec<Task>> {
+    self.transaction(|tx| {
+      let subtree = read_subtree(tx, id)?;
+      let Some(root) = subtree.first().filter(|t| t.deleted) else {
+        return Ok(Vec::new());
+      };
+      let batch = root.deleted_at.clone();
+      let orphaned = match &root.parent_id {
+        Some(parent_id) => read_task(tx, parent_id)?.map_or(true, |p| p.deleted),
+        None => false,
+      };
+
+      let now = now_iso();
+      let mut restored = Vec::new();
+      for (i, mut task) in subtree.into_iter().enumerate() {
+        if i > 0 && !(task.deleted && task.deleted_at == batch) {
+          continue;
+        }
+        task.deleted = false;
+        task.deleted_at = None;
+        task.updated_at = Some(now.clone());
+        if i == 0 && orphaned {
+          task.parent_id = None;
+          task.sort_order = Some(first_sort_order(tx, &task)?);
+        }
+        write_task(tx, &task)?;
+        restored.push(task);
+      }
+      Ok(restored)
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn add(store: &Store, title: &str, parent: Option<&Task>) -> Task {
+    store
+      .create_task(Task {
+        title: title.into(),
+        project: Some("inbox".into()),
+        parent_id: parent.map(|p| p.id.clone()),
+        ..Default::default()
+      })
+      .unwrap()
+  }
+
+  fn titles(tasks: &[Task]) -> Vec<&str> {
+    tasks.iter().map(|t| t.title.as_str()).collect()
+  }
+
+  #[test]
+  fn moves_trashes_and_restores_whole_subtrees() {
+    let store = Store::open_in_memory().unwrap();
+    let trip = add(&store, "trip", None);
+    let pack = add(&store, "pack", Some(&trip));
+    let clothes = add(&store, "clothes", Some(&pack));
+    // New siblings go first, like new tasks in a list.
+    let tickets = add(&store, "tickets", Some(&trip));
+    assert_eq!(
+      titles(&store.get_subtree(&trip.id).unwrap()),
+      ["trip", "tickets", "pack", "clothes"]
+    );
+    assert_eq!(tickets.sort_order, Some(-1000.0));
+
+    let err = store.move_task(
+      &trip.id,
+      &TaskMove {
+        parent_id: Some(clothes.id.clone()),
+        ..Default::default()
+      },
+    );
+    assert!(matches!(err, Err(Error::InvalidInput(_))));
+
+    let moved = store
+      .move_task(
+        &trip.id,
+        &TaskMove {
+          project: Some("travel".into()),
+          ..Default::default()
+        },
+      )
+      .unwrap()
+      .unwrap();
+    assert_eq!(moved.project.as_deref(), Some("travel"));
+    for task in store.get_subtree(&trip.id).unwrap() {
+      assert_eq!(task.project.as_deref(), Some("travel"));
+    }
+
+    // A subtask moved to another project on its own leaves its parent.
+    let detached = store
+      .update_task(
+        &tickets.id,
+        json!({ "project": "inbox" }).as_object().unwrap(),
+      )
+      .unwrap()
+      .unwrap();
+    assert_eq!(detached.parent_id, None);
+
+    store.trash_task(&clothes.id).unwrap();
+    store
+      .update_task(
+        &clothes.id,
+        json!({ "deleted_at": "2026-01-01T00:00:00.000Z" })
+          .as_object()
+          .unwrap(),
+      )
+      .unwrap();
+    assert_eq!(store.trash_task(&trip.id).unwrap().len(), 2);
+    let restored = store.restore_task(&trip.id).unwrap();
+    // `clothes` was trashed on its own earlier and stays there.
+    assert_eq!(titles(&restored), ["trip", "pack"]);
+
+    store.trash_task(&pack.id).unwrap();
+    store.trash_task(&trip.id).unwrap();
+    let restored = store.restore_task(&pack.id).unwrap();
+    assert_eq!(restored[0].parent_id, None);
+
+    assert!(store.delete_task(&pack.id).unwrap());
+    assert!(store.get_task_by_id(&clothes.id).unwrap().is_none());
+  }
+
+  #[test]
+  fn rolls_completion_up_to_the_root() {
+    let store = Store::open_in_memory().unwrap();
+    let root = add(&store, "root", None);
+    let a = add(&store, "a", Some(&root));
+    let b = add(&store, "b", Some(&a));
+    add(&store, "c", Some(&a));
+    let dropped = add(&store, "dropped", Some(&root));
+    let done = json!({ "completed": true });
+    store.update_task(&b.id, done.as_object().unwrap()).unwrap();
+    store
+      .update_task(
+        &dropped.id,
+        json!({ "abandoned": true }).as_object().unwrap(),
+      )
+      .unwrap();
+
+    let progress = store.task_progress(&root.id).unwrap();
+    assert_eq!(
+      progress,
+      TaskProgress {
+        total: 3,
+        completed: 1,
+        percent: 33
+      }
+    );
+    assert_eq!(store.task_progress(&b.id).unwrap().percent, 100);
+  }
+}
@@ -0,0 +1,258 @@
+//! Versioned schema migrations for the desktop database.
+//!
+//! Migrations live in `src-tauri/migrations/` and are compiled into the
+//! binary. Applied versions are recorded in `schema_migrations`; every
+//! pending migration runs inside one transaction, so an upgrade either
+//! lands completely or leaves the file untouched.
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::now_iso;
+use crate::error::{Error, Result};
+
+pub struct Migration {
+  pub version: i64,
+  pub name: &'static str,
+  pub sql: &'static str,
+}
+
+/// Every migration known to this binary, in ascending version order.
+pub const MIGRATIONS: &[Migration] = &[
+  Migration {
+    version: 1,
+    name: "initial_schema",
+    sql: include_str!("../../migrations/0001_initial_schema.sql"),
+  },
+  Migration {
+    version: 2,
+    name: "deadline_reminders",
+    sql: include_str!("../../migrations/0002_deadline_reminders.sql"),
+  },
+  Migration {
+    version: 3,
+    name: "webhook_outbox",
+    sql: include_str!("../../migrations/0003_webhook_outbox.sql"),
+  },
+  Migration {
+    version: 4,
+    name: "search_index",
+    sql: include_str!("../../migrations/0004_search_index.sql"),
+  },
+  Migration {
+    version: 5,
+    name: "smart_lists",
+    sql: include_str!("../../migrations/0005_smart_lists.sql"),
+  },
+  Migration {
+    version: 6,
+    name: "task_recurrences",
+    sql: include_str!("../../migrations/0006_task_recurrences.sql"),
+  },
+  Migration {
+    version: 7,
+    name: "task_hierarchy",
+    sql: include_str!("../../migrations/0007_task_hierarchy.sql"),
+  },
+  Migration {
+    version: 8,
+    name: "task_dependencies",
+    sql: include_str!("../../migrations/0008_task_dependencies.sql"),
+  },
+  Migration {
+    version: 9,
+    name: "pomodoro_timer",
+    sql: include_str!("../../migrations/0009_pomodoro_timer.sql"),
+  },
+  Migration {
+    version: 10,
+    name: "time_tracking",
+    sql: include_str!("../../migrations/0010_time_tracking.sql"),
+  },
+  Migration {
+    version: 11,
+    name: "ical_uids",
+    sql: include_str!("../../migrations/0011_ical_uids.sql"),
+  },
+  Migration {
+    version: 12,
+    name: "calendar_feeds",
+    sql: include_str!("../../migrations/0012_calendar_feeds.sql"),
+  },
+  Migration {
+    version: 13,
+    name: "caldav_resources",
+    sql: include_str!("../../migrations/0013_caldav_resources.sql"),
+  },
+  Migration {
+    version: 14,
+    name: "sync_outbox",
+    sql: include_str!("../../migrations/0014_sync_outbox.sql"),
+  },
+  Migration {
+    version: 15,
+    name: "task_crdt",
+    sql: include_str!("../../migrations/0015_task_crdt.sql"),
+  },
+  Migration {
+    version: 16,
+    name: "peer_sync",
+    sql: include_str!("../../migrations/0016_peer_sync.sql"),
+  },
+  Migration {
+    version: 17,
+    name: "sync_history",
+    sql: include_str!("../../migrations/0017_sync_history.sql"),
+  },
+  Migration {
+    version: 18,
+    name: "task_tombstones",
+    sql: include_str!("../../migrations/0018_task_tombstones.sql"),
+  },
+];
+
+/// Highest schema version this binary understands.
+pub fn latest_version() -> i64 {
+  MIGRATIONS.last().map_or(0, |m| m.version)
+}
+
+fn ensure_metadata_table(conn: &Connection) -> Result<()> {
+  conn.execute_batch(
+    "create table if not exists schema_migrations (
+      version integer primary key,
+      name text not null,
+      applied_at text not null
+    );",
+  )?;
+  Ok(())
+}
+
+/// Version currently recorded in the database, 0 for a fresh file.
+pub fn current_version(conn: &Connection) -> Result<i64> {
+  ensure_metadata_table(conn)?;
+  let version: Option<i64> = conn
+    .query_row("select max(version) from schema_migrations", [], |row| {
+      row.get(0)
+    })
+    .optional()?
+    .flatten();
+  Ok(version.unwrap_or(0))
+}
+
+/// Versions that still need to run against `conn`.
+pub fn pending(conn: &Connection) -> Result<Vec<i64>> {
+  pending_in(conn, MIGRATIONS)
+}
+
+fn pending_in(conn: &Connection, migrations: &[Migration]) -> Result<Vec<i64>> {
+  let current = current_version(conn)?;
+  let supported = migrations.last().map_or(0, |m| m.version);
+  if current > supported {
+    return Err(Error::SchemaTooNew {
+      found: current,
+      supported,
+    });
+  }
+  Ok(
+    migrations
+      .iter()
+      .filter(|m| m.version > current)
+      .map(|m| m.version)
+      .collect(),
+  )
+}
+
+/// Bring the database up to [`latest_version`]; returns the versions applied.
+pub fn run(conn: &mut Connection) -> Result<Vec<i64>> {
+  run_migrations(conn, MIGRATIONS)
+}
+
+fn run_migrations(conn: &mut Connection, migrations: &[Migration]) -> Result<Vec<i64>> {
+  let pending = pending_in(conn, migrations)?;
+  if pending.is_empty() {
+    return Ok(pending);
+  }
+
+  let tx = conn.transaction()?;
+  for migration in migrations.iter().filter(|m| pending.contains(&m.version)) {
+    tx.execute_batch(migration.sql)
+      .map_err(|e| Error::Migration(migration.version, e.to_string()))?;
+    tx.execute(
+      "insert into schema_migrations (version, name, applied_at) values (?1, ?2, ?3)",
+      params![migration.version, migration.name, now_iso()],
+    )?;
+  }
+  tx.commit()?;
+  Ok(pending)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const FIXTURE: &[Migration] = &[
+    Migration {
+      version: 1,
+      name: "one",
+      sql: "create table a (id text primary key);",
+    },
+    Migration {
+      version: 2,
+      name: "two",
+      sql: "alter table a add column title text;",
+    },
+  ];
+
+  #[test]
+  fn applies_every_embedded_migration_once() {
+    let mut conn = Connection::open_in_memory().unwrap();
+    let applied = run(&mut conn).unwrap();
+    assert_eq!(applied.len(), MIGRATIONS.len());
+    assert_eq!(current_version(&conn).unwrap(), latest_version());
+    assert!(run(&mut conn).unwrap().is_empty());
+  }
+
+  #[test]
+  fn failed_migration_rolls_back_the_whole_batch() {
+    let mut conn = Connection::open_in_memory().unwrap();
+    let broken = [
+      Migration {
+        version: 1,
+        name: "one",
+        sql: FIXTURE[0].sql,
+      },
+      Migration {
+        version: 2,
+        name: "broken",
+        sql: "alter table missing add column x text;",
+      },
+    ];
+
+    let err = run_migrations(&mut conn, &broken).unwrap_err();
+    assert!(matches!(err, Error::Migration(2, _)));
+    assert_eq!(current_version(&conn).unwrap(), 0);
+    let table: Option<String> = conn
+      .query_row(
+        "select name from sqlite_master where name = 'a'",
+        [],
+        |row| row.get(0),
+      )
+      .optional()
+      .unwrap();
+    assert_eq!(table, None);
+  }
+
+  #[test]
+  fn refuses_a_database_newer_than_the_binary() {
+    let mut conn = Connection::open_in_memory().unwrap();
+    run_migrations(&mut conn, FIXTURE).unwrap();
+
+    let err = run_migrations(&mut conn, &FIXTURE[..1]).unwrap_err();
+    assert!(matches!(
+      err,
+      Error::SchemaTooNew {
+        found: 2,
+        supported: 1
+      }
+    ));
+  }
+}
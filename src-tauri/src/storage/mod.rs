@@ -0,0 +1,164 @@
+//! Native SQLite storage.
+//!
+//! Mirrors the TypeScript `StorageAdapter` interface so desktop offline mode
+//! no longer lives in the webview's IndexedDB. Each submodule adds one group
+//! of operations to [`Store`], in the same order as `src/storage/types.ts`.
+
+mod activities;
+mod attachments;
+mod checkins;
+pub mod dependencies;
+pub mod hierarchy;
+pub mod migrations;
+pub mod models;
+mod pomodoro;
+mod profile;
+mod projects;
+pub mod recurrence;
+pub mod search;
+mod tags;
+mod tasks;
+pub mod time_tracking;
+
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use chrono::{SecondsFormat, Utc};
+use rusqlite::Connection;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::error::Result;
+
+pub(crate) use activities::write_activity;
+pub(crate) use checkins::write_checkin;
+pub(crate) use pomodoro::write_session;
+pub use profile::ProfileUpdate;
+pub(crate) use profile::OFFLINE_USER_ID;
+pub(crate) use projects::write_project;
+pub(crate) use tasks::{due_day, read_task, write_task};
+
+/// File name of the database inside the app data directory.
+pub const DB_FILE_NAME: &str = "snail_todo.db";
+
+/// Handle to the local database, shared with every command through Tauri state.
+pub struct Store {
+  conn: Mutex<Connection>,
+}
+
+impl Store {
+  /// Open (or create) the database file at `path` and migrate it.
+  ///
+  /// Before an existing database is upgraded, a copy is written next to it
+  /// as `<file>.v<version>.bak` so a failed or unwanted upgrade can be undone
+  /// by hand.
+  pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+      std::fs::create_dir_all(parent)?;
+    }
+    let conn = Connection::open(path)?;
+    let current = migrations::current_version(&conn)?;
+    if current > 0 && !migrations::pending(&conn)?.is_empty() {
+      let backup = path.with_extension(format!("db.v{current}.bak"));
+      let _ = std::fs::remove_file(&backup);
+      conn.execute("vacuum into ?1", [backup.to_string_lossy()])?;
+    }
+    Self::init(conn)
+  }
+
+  /// In-memory database, used by tests.
+  pub fn open_in_memory() -> Result<Self> {
+    Self::init(Connection::open_in_memory()?)
+  }
+
+  fn init(mut conn: Connection) -> Result<Self> {
+    // The `snail` CLI may write to the same file while the app is running.
+    conn.busy_timeout(Duration::from_secs(5))?;
+    conn.pragma_update(None, "foreign_keys", true)?;
+    conn.pragma_update(None, "journal_mode", "WAL")?;
+    let applied = migrations::run(&mut conn)?;
+    if !applied.is_empty() {
+      log::info!("applied database migrations {applied:?}");
+    }
+    search::ensure_index(&conn)?;
+    Ok(Self {
+      conn: Mutex::new(conn),
+    })
+  }
+
+  /// Run `f` inside a single transaction, committing only if it succeeds.
+  pub(crate) fn transaction<T>(
+    &self,
+    f: impl FnOnce(&rusqlite::Transaction<'_>) -> Result<T>,
+  ) -> Result<T> {
+    let mut conn = self.conn();
+    let tx = conn.transaction()?;
+    let value = f(&tx)?;
+    tx.commit()?;
+    Ok(value)
+  }
+
+  /// Schema version of the open database.
+  pub fn schema_version(&self) -> Result<i64> {
+    migrations::current_version(&self.conn())
+  }
+
+  pub(crate) fn conn(&self) -> MutexGuard<'_, Connection> {
+    // A poisoned lock only means another command panicked mid-call; SQLite
+    // itself rolled back, so the connection is still usable.
+    self.conn.lock().unwrap_or_else(|e| e.into_inner())
+  }
+}
+
+/// Current time in the same format as JavaScript's `Date.toISOString()`.
+pub(crate) fn now_iso() -> String {
+  Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+pub(crate) fn new_id() -> String {
+  uuid::Uuid::new_v4().to_string()
+}
+
+/// Apply a `Partial<T>` coming from the webview on top of `existing`.
+///
+/// Behaves like `{ ...existing, ...updates }`: keys present in `updates`
+/// win, `null` clears an optional field, and unknown keys are ignored.
+pub(crate) fn merge_patch<T: Serialize + DeserializeOwned>(
+  existing: &T,
+  updates: &Map<String, Value>,
+) -> Result<T> {
+  let mut value = serde_json::to_value(existing)?;
+  if let Value::Object(fields) = &mut value {
+    for (key, v) in updates {
+      fields.insert(key.clone(), v.clone());
+    }
+  }
+  Ok(serde_json::from_value(value)?)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::storage::models::Task;
+  use serde_json::json;
+
+  #[test]
+  fn merge_patch_clears_nulls_and_keeps_untouched_fields() {
+    let task = Task {
+      id: "t1".into(),
+      title: "Write report".into(),
+      project: Some("p1".into()),
+      flagged: true,
+      ..Default::default()
+    };
+    let updates = json!({ "project": null, "title": "Ship report" });
+    let merged = merge_patch(&task, updates.as_object().unwrap()).unwrap();
+
+    assert_eq!(merged.title, "Ship report");
+    assert_eq!(merged.project, None);
+    assert!(merged.flagged);
+  }
+}
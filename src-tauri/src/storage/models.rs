@@ -0,0 +1,322 @@
+//! Record types shared with the webview.
+//!
+//! Field names mirror `src/types/*.ts` and `src/storage/types.ts` one to one,
+//! so the JSON produced here can be handed straight to the React code.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::search::SearchHit;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TaskAttachment {
+  pub id: String,
+  pub filename: String,
+  pub original_name: String,
+  pub url: String,
+  pub size: i64,
+  #[serde(rename = "type")]
+  pub kind: String,
+  pub uploaded_at: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Task {
+  #[serde(default)]
+  pub id: String,
+  pub title: String,
+  #[serde(default)]
+  pub completed: bool,
+  /// Deadline, `YYYY-MM-DD HH:mm:ss` or ISO 8601.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub date: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub project: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub description: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub icon: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub completed_at: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub created_at: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub updated_at: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub user_id: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub sort_order: Option<f64>,
+  #[serde(default)]
+  pub deleted: bool,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub deleted_at: Option<String>,
+  #[serde(default)]
+  pub abandoned: bool,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub abandoned_at: Option<String>,
+  #[serde(default)]
+  pub flagged: bool,
+  #[serde(default)]
+  pub attachments: Vec<TaskAttachment>,
+  /// Parent task; `None` for a top-level task.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub parent_id: Option<String>,
+  /// Planned effort in minutes.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub estimate_minutes: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Project {
+  #[serde(default)]
+  pub id: String,
+  pub name: String,
+  #[serde(default)]
+  pub icon: String,
+  /// Always reported as 0, like both TypeScript adapters do.
+  #[serde(default)]
+  pub count: i64,
+  #[serde(default, rename = "isFixed", skip_serializing_if = "Option::is_none")]
+  pub is_fixed: Option<bool>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub color: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub view_type: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub created_at: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub updated_at: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub sort_order: Option<f64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub user_id: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub is_shared: Option<bool>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub original_owner_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Tag {
+  #[serde(default)]
+  pub id: String,
+  pub name: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub user_id: Option<String>,
+  /// `None` means a global tag.
+  #[serde(default)]
+  pub project_id: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub created_at: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskTagLink {
+  pub task_id: String,
+  pub tag_id: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub created_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PomodoroSession {
+  #[serde(default)]
+  pub id: String,
+  #[serde(default)]
+  pub task_id: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub user_id: Option<String>,
+  pub duration: i64,
+  /// `work`, `short_break` or `long_break`.
+  #[serde(rename = "type")]
+  pub kind: String,
+  #[serde(default)]
+  pub started_at: String,
+  #[serde(default)]
+  pub completed_at: Option<String>,
+  /// Set when the session was reset or skipped before it finished.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub cancelled_at: Option<String>,
+  #[serde(default)]
+  pub created_at: String,
+  #[serde(default)]
+  pub notes: Option<String>,
+  #[serde(default)]
+  pub title: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TaskActivity {
+  #[serde(default)]
+  pub id: String,
+  pub task_id: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub user_id: Option<String>,
+  pub action: String,
+  #[serde(default)]
+  pub metadata: Option<Map<String, Value>>,
+  #[serde(default)]
+  pub created_at: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CheckInRecord {
+  pub id: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub user_id: Option<String>,
+  pub check_in_time: String,
+  #[serde(default)]
+  pub note: Option<String>,
+  pub created_at: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CheckInHistory {
+  pub records: Vec<CheckInRecord>,
+  pub total: i64,
+}
+
+/// Free-form settings bag; known keys are read through the helpers below.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct UserSettings(pub Map<String, Value>);
+
+impl UserSettings {
+  pub fn bool(&self, key: &str) -> Option<bool> {
+    self.0.get(key).and_then(Value::as_bool)
+  }
+
+  pub fn i64(&self, key: &str) -> Option<i64> {
+    self.0.get(key).and_then(Value::as_i64)
+  }
+
+  pub fn str(&self, key: &str) -> Option<&str> {
+    self.0.get(key).and_then(Value::as_str)
+  }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UserProfile {
+  pub id: String,
+  pub username: String,
+  #[serde(default)]
+  pub avatar_url: Option<String>,
+  #[serde(default)]
+  pub avatar_data: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub settings: Option<UserSettings>,
+  #[serde(default)]
+  pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AppInfo {
+  pub version: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub announcement: Option<String>,
+  #[serde(default)]
+  pub maintenance_mode: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FileUploadResult {
+  pub id: String,
+  pub filename: String,
+  pub original_name: String,
+  pub url: String,
+  pub size: i64,
+  #[serde(rename = "type")]
+  pub kind: String,
+  pub uploaded_at: String,
+}
+
+/// A file handed over from the webview; replaces the DOM `File` argument.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UploadFile {
+  pub name: String,
+  #[serde(rename = "type", default)]
+  pub kind: String,
+  pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskFilter {
+  pub project_id: Option<String>,
+  pub completed: Option<bool>,
+  pub deleted: Option<bool>,
+  pub abandoned: Option<bool>,
+  pub flagged: Option<bool>,
+  pub user_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortField {
+  SortOrder,
+  CreatedAt,
+  UpdatedAt,
+  DeletedAt,
+  AbandonedAt,
+  CompletedAt,
+}
+
+impl SortField {
+  pub fn column(self) -> &'static str {
+    match self {
+      SortField::SortOrder => "sort_order",
+      SortField::CreatedAt => "created_at",
+      SortField::UpdatedAt => "updated_at",
+      SortField::DeletedAt => "deleted_at",
+      SortField::AbandonedAt => "abandoned_at",
+      SortField::CompletedAt => "completed_at",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+  Asc,
+  Desc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SortOptions {
+  pub field: SortField,
+  pub direction: SortDirection,
+  #[serde(default)]
+  pub nulls_first: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SortOrderUpdate {
+  pub id: String,
+  pub sort_order: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchOptions {
+  #[serde(default)]
+  pub include_completed: bool,
+  #[serde(default)]
+  pub include_deleted: bool,
+  #[serde(default)]
+  pub include_abandoned: bool,
+  pub limit: Option<usize>,
+  pub project_filter: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResult {
+  pub tasks: Vec<Task>,
+  pub total_count: usize,
+  /// Milliseconds, like `performance.now()` deltas on the TypeScript side.
+  pub search_time: f64,
+  /// Highlighting for each of `tasks`, in the same order.
+  #[serde(default)]
+  pub hits: Vec<SearchHit>,
+}
@@ -0,0 +1,116 @@
+//! Pomodoro session operations.
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde_json::{Map, Value};
+
+use super::models::PomodoroSession;
+use super::{merge_patch, new_id, now_iso, Store};
+use crate::error::Result;
+
+pub(crate) fn session_from_row(row: &Row<'_>) -> rusqlite::Result<PomodoroSession> {
+  Ok(PomodoroSession {
+    id: row.get("id")?,
+    task_id: row.get("task_id")?,
+    user_id: row.get("user_id")?,
+    duration: row.get("duration")?,
+    kind: row.get("type")?,
+    started_at: row.get("started_at")?,
+    completed_at: row.get("completed_at")?,
+    cancelled_at: row.get("cancelled_at")?,
+    created_at: row.get("created_at")?,
+    notes: row.get("notes")?,
+    title: row.get("title")?,
+  })
+}
+
+/// Insert or replace a session, stamping `updated_at` for sync.
+pub(crate) fn write_session(conn: &Connection, session: &PomodoroSession) -> Result<()> {
+  conn.execute(
+    "insert or replace into pomodoro_sessions \
+     (id, task_id, user_id, duration, type, started_at, completed_at, cancelled_at, created_at, \
+     notes, title, updated_at) \
+     values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+    params![
+      session.id,
+      session.task_id,
+      session.user_id,
+      session.duration,
+      session.kind,
+      session.started_at,
+      session.completed_at,
+      session.cancelled_at,
+      session.created_at,
+      session.notes,
+      session.title,
+      now_iso(),
+    ],
+  )?;
+  Ok(())
+}
+
+fn read_session(conn: &Connection, id: &str) -> Result<Option<PomodoroSession>> {
+  Ok(
+    conn
+      .query_row(
+        "select * from pomodoro_sessions where id = ?1",
+        [id],
+        session_from_row,
+      )
+      .optional()?,
+  )
+}
+
+impl Store {
+  pub fn get_pomodoro_sessions(&self, task_id: Option<&str>) -> Result<Vec<PomodoroSession>> {
+    let conn = self.conn();
+    let mut stmt = conn.prepare(
+      "select * from pomodoro_sessions where ?1 is null or task_id = ?1 order by created_at",
+    )?;
+    let sessions = stmt
+      .query_map([task_id], session_from_row)?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(sessions)
+  }
+
+  pub fn get_pomodoro_session_by_id(&self, id: &str) -> Result<Option<PomodoroSession>> {
+    read_session(&self.conn(), id)
+  }
+
+  pub fn create_pomodoro_session(&self, input: PomodoroSession) -> Result<PomodoroSession> {
+    let now = now_iso();
+    let session = PomodoroSession {
+      id: new_id(),
+      started_at: if input.started_at.is_empty() {
+        now.clone()
+      } else {
+        input.started_at.clone()
+      },
+      created_at: now,
+      ..input
+    };
+    write_session(&self.conn(), &session)?;
+    Ok(session)
+  }
+
+  pub fn update_pomodoro_session(
+    &self,
+    id: &str,
+    updates: &Map<String, Value>,
+  ) -> Result<Option<PomodoroSession>> {
+    let conn = self.conn();
+    let Some(existing) = read_session(&conn, id)? else {
+      return Ok(None);
+    };
+    let mut session = merge_patch(&existing, updates)?;
+    session.id = id.to_string();
+    write_session(&conn, &session)?;
+    Ok(Some(session))
+  }
+
+  pub fn delete_pomodoro_session(&self, id: &str) -> Result<bool> {
+    self
+      .conn()
+      .execute("delete from pomodoro_sessions where id = ?1", [id])?;
+    Ok(true)
+  }
+}
@@ -0,0 +1,125 @@
+//! Offline user profile and settings.
+
+use rusqlite::{params, OptionalExtension, Row};
+
+use super::models::{UserProfile, UserSettings};
+use super::{now_iso, Store};
+use crate::error::Result;
+
+pub(crate) const OFFLINE_USER_ID: &str = "offline-user";
+const OFFLINE_USERNAME: &str = "离线用户";
+
+fn profile_from_row(row: &Row<'_>) -> rusqlite::Result<UserProfile> {
+  let settings: Option<String> = row.get("settings")?;
+  Ok(UserProfile {
+    id: row.get("id")?,
+    username: row.get("username")?,
+    avatar_url: row.get("avatar_url")?,
+    avatar_data: row.get("avatar_data")?,
+    settings: settings.and_then(|s| serde_json::from_str(&s).ok()),
+    updated_at: row.get("updated_at")?,
+  })
+}
+
+/// Profile fields the webview may change; absent fields keep their value.
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct ProfileUpdate {
+  pub username: Option<String>,
+  pub avatar_url: Option<String>,
+  pub avatar_data: Option<String>,
+  pub settings: Option<UserSettings>,
+}
+
+impl Store {
+  pub fn get_user_profile(&self) -> Result<Option<UserProfile>> {
+    Ok(
+      self
+        .conn()
+        .query_row("select * from user_profile limit 1", [], profile_from_row)
+        .optional()?,
+    )
+  }
+
+  pub fn save_user_profile(&self, update: ProfileUpdate) -> Result<UserProfile> {
+    let existing = self.get_user_profile()?;
+    let existing = existing.as_ref();
+    let profile = UserProfile {
+      id: existing.map_or_else(|| OFFLINE_USER_ID.to_string(), |p| p.id.clone()),
+      username: update
+        .username
+        .or_else(|| existing.map(|p| p.username.clone()))
+        .unwrap_or_else(|| OFFLINE_USERNAME.to_string()),
+      avatar_url: update
+        .avatar_url
+        .or_else(|| existing.and_then(|p| p.avatar_url.clone())),
+      avatar_data: update
+        .avatar_data
+        .or_else(|| existing.and_then(|p| p.avatar_data.clone())),
+      settings: update
+        .settings
+        .or_else(|| existing.and_then(|p| p.settings.clone())),
+      updated_at: now_iso(),
+    };
+    let settings = profile
+      .settings
+      .as_ref()
+      .map(serde_json::to_string)
+      .transpose()?;
+    self.conn().execute(
+      "insert or replace into user_profile (id, username, avatar_url, avatar_data, settings, updated_at) \
+       values (?1, ?2, ?3, ?4, ?5, ?6)",
+      params![
+        profile.id,
+        profile.username,
+        profile.avatar_url,
+        profile.avatar_data,
+        settings,
+        profile.updated_at,
+      ],
+    )?;
+    Ok(profile)
+  }
+
+  pub fn get_user_settings(&self) -> Result<UserSettings> {
+    Ok(
+      self
+        .get_user_profile()?
+        .and_then(|p| p.settings)
+        .unwrap_or_default(),
+    )
+  }
+
+  /// Shallow-merge `settings` into the stored settings.
+  pub fn save_user_settings(&self, settings: UserSettings) -> Result<UserSettings> {
+    let mut merged = self.get_user_settings()?;
+    merged.0.extend(settings.0);
+    self.save_user_profile(ProfileUpdate {
+      settings: Some(merged.clone()),
+      ..Default::default()
+    })?;
+    Ok(merged)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn settings_merge_into_the_offline_profile() {
+    let store = Store::open_in_memory().unwrap();
+    let first: UserSettings =
+      serde_json::from_value(json!({ "deadline_notification_enabled": true })).unwrap();
+    let second: UserSettings =
+      serde_json::from_value(json!({ "deadline_notification_days": 3 })).unwrap();
+    store.save_user_settings(first).unwrap();
+    let merged = store.save_user_settings(second).unwrap();
+
+    assert_eq!(merged.bool("deadline_notification_enabled"), Some(true));
+    assert_eq!(merged.i64("deadline_notification_days"), Some(3));
+    let profile = store.get_user_profile().unwrap().unwrap();
+    assert_eq!(profile.id, "offline-user");
+    assert_eq!(profile.username, "离线用户");
+  }
+}
@@ -0,0 +1,177 @@
+//! Project operations.
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde_json::{Map, Value};
+
+use super::models::{Project, SortOrderUpdate};
+use super::{merge_patch, new_id, now_iso, search, Store};
+use crate::error::Result;
+
+pub(crate) const PROJECT_COLUMNS: &str = "id, name, icon, is_fixed, color, view_type, created_at, \
+  updated_at, sort_order, user_id, is_shared, original_owner_id";
+
+pub(crate) fn project_from_row(row: &Row<'_>) -> rusqlite::Result<Project> {
+  Ok(Project {
+    id: row.get("id")?,
+    name: row.get("name")?,
+    icon: row.get("icon")?,
+    count: 0,
+    is_fixed: row.get("is_fixed")?,
+    color: row.get("color")?,
+    view_type: row.get("view_type")?,
+    created_at: row.get("created_at")?,
+    updated_at: row.get("updated_at")?,
+    sort_order: row.get("sort_order")?,
+    user_id: row.get("user_id")?,
+    is_shared: row.get("is_shared")?,
+    original_owner_id: row.get("original_owner_id")?,
+  })
+}
+
+pub(crate) fn write_project(conn: &Connection, project: &Project) -> Result<()> {
+  conn.execute(
+    &format!(
+      "insert or replace into projects ({PROJECT_COLUMNS}) \
+       values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)"
+    ),
+    params![
+      project.id,
+      project.name,
+      project.icon,
+      project.is_fixed,
+      project.color,
+      project.view_type,
+      project.created_at,
+      project.updated_at,
+      project.sort_order,
+      project.user_id,
+      project.is_shared,
+      project.original_owner_id,
+    ],
+  )?;
+  Ok(())
+}
+
+fn read_project(conn: &Connection, id: &str) -> Result<Option<Project>> {
+  Ok(
+    conn
+      .query_row(
+        &format!("select {PROJECT_COLUMNS} from projects where id = ?1"),
+        [id],
+        project_from_row,
+      )
+      .optional()?,
+  )
+}
+
+impl Store {
+  /// Projects ordered like the Supabase query: `sort_order` first, newest first on ties.
+  pub fn get_projects(&self) -> Result<Vec<Project>> {
+    let conn = self.conn();
+    let mut stmt = conn.prepare(&format!(
+      "select {PROJECT_COLUMNS} from projects \
+       order by sort_order asc nulls last, created_at desc"
+    ))?;
+    let projects = stmt
+      .query_map([], project_from_row)?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(projects)
+  }
+
+  pub fn get_project_by_id(&self, id: &str) -> Result<Option<Project>> {
+    read_project(&self.conn(), id)
+  }
+
+  pub fn create_project(&self, input: Project) -> Result<Project> {
+    let now = now_iso();
+    let project = Project {
+      id: new_id(),
+      count: 0,
+      created_at: Some(now.clone()),
+      updated_at: Some(now),
+      ..input
+    };
+    write_project(&self.conn(), &project)?;
+    Ok(project)
+  }
+
+  pub fn update_project(&self, id: &str, updates: &Map<String, Value>) -> Result<Option<Project>> {
+    let conn = self.conn();
+    let Some(existing) = read_project(&conn, id)? else {
+      return Ok(None);
+    };
+    let mut project = merge_patch(&existing, updates)?;
+    project.id = id.to_string();
+    project.updated_at = Some(now_iso());
+    write_project(&conn, &project)?;
+    Ok(Some(project))
+  }
+
+  /// Delete a project: its tasks become project-less and its scoped tags are removed.
+  pub fn delete_project(&self, id: &str) -> Result<bool> {
+    let mut conn = self.conn();
+    let tx = conn.transaction()?;
+    tx.execute(
+      "update tasks set project = null, updated_at = ?2 where project = ?1",
+      params![id, now_iso()],
+    )?;
+    let tagged = search::tasks_tagged(&tx, "project_id", id)?;
+    tx.execute("delete from tags where project_id = ?1", [id])?;
+    tx.execute("delete from projects where id = ?1", [id])?;
+    for task_id in tagged {
+      search::reindex_task(&tx, &task_id)?;
+    }
+    tx.commit()?;
+    Ok(true)
+  }
+
+  pub fn batch_update_project_sort_order(&self, updates: &[SortOrderUpdate]) -> Result<bool> {
+    let mut conn = self.conn();
+    let tx = conn.transaction()?;
+    {
+      let mut stmt = tx.prepare("update projects set sort_order = ?1 where id = ?2")?;
+      for update in updates {
+        stmt.execute(params![update.sort_order, update.id])?;
+      }
+    }
+    tx.commit()?;
+    Ok(true)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::storage::models::{Task, TaskFilter};
+
+  #[test]
+  fn deleting_a_project_detaches_its_tasks_and_drops_scoped_tags() {
+    let store = Store::open_in_memory().unwrap();
+    let project = store
+      .create_project(Project {
+        name: "Work".into(),
+        ..Default::default()
+      })
+      .unwrap();
+    let task = store
+      .create_task(Task {
+        title: "Plan".into(),
+        project: Some(project.id.clone()),
+        ..Default::default()
+      })
+      .unwrap();
+    let tag = store.create_tag("urgent", Some(&project.id)).unwrap();
+    store.attach_tag_to_task(&task.id, &tag.id).unwrap();
+
+    assert!(store.delete_project(&project.id).unwrap());
+
+    let task = store.get_task_by_id(&task.id).unwrap().unwrap();
+    assert_eq!(task.project, None);
+    assert!(store.get_tag_by_id(&tag.id).unwrap().is_none());
+    assert!(store.get_project_by_id(&project.id).unwrap().is_none());
+    assert_eq!(
+      store.get_tasks(&TaskFilter::default(), &[]).unwrap().len(),
+      1
+    );
+  }
+}
@@ -0,0 +1,476 @@
+//! Recurring tasks.
+//!
+//! Every instance of a series is an ordinary task with a row in
+//! `task_recurrences`. Completing an instance writes the next one as a new
+//! task and links the pair through `task_activities`, so the history of a
+//! weekly report is the chain of its completed instances.
+
+use chrono::{NaiveDate, NaiveDateTime};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::activities::write_activity;
+use super::models::{Task, TaskActivity};
+use super::tasks::{read_task, task_from_row, write_task, TASK_COLUMNS};
+use super::{new_id, now_iso, search, Store};
+use crate::error::{Error, Result};
+use crate::rrule::{format_task_date, parse_task_date, RRule};
+
+/// Activity written on a completed instance when its successor is created.
+pub const NEXT_CREATED_ACTION: &str = "recurrence_next_created";
+/// Activity written on the successor, pointing back at the completed instance.
+pub const CREATED_FROM_ACTION: &str = "recurrence_created";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TaskRecurrence {
+  pub task_id: String,
+  #[serde(default)]
+  pub series_id: String,
+  pub rrule: String,
+  /// Date of the first instance, in the task's own `date` format.
+  #[serde(default)]
+  pub dtstart: String,
+  /// Skipped occurrences (local days).
+  #[serde(default)]
+  pub exdates: Vec<NaiveDate>,
+  /// 1-based position of this instance in the series.
+  #[serde(default)]
+  pub occurrence: u32,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub next_task_id: Option<String>,
+  #[serde(default)]
+  pub created_at: String,
+  #[serde(default)]
+  pub updated_at: String,
+}
+
+impl TaskRecurrence {
+  pub fn rule(&self) -> Result<RRule> {
+    self.rrule.parse()
+  }
+
+  /// Occurrences of the series after `after`, with their 1-based position.
+  /// `COUNT` is applied before exceptions, as RFC 5545 does with `EXDATE`.
+  fn occurrences_after(&self, after: NaiveDateTime) -> Result<Vec<(u32, NaiveDateTime)>> {
+    let Some((start, _)) = parse_task_date(&self.dtstart) else {
+      return Ok(Vec::new());
+    };
+    let rule = self.rule()?;
+    Ok(
+      rule
+        .occurrences(start)
+        .zip(1..)
+        .skip_while(|(at, _)| *at <= after)
+        .filter(|(at, _)| !self.exdates.contains(&at.date()))
+        .map(|(at, n)| (n, at))
+        .take(MAX_LOOKAHEAD)
+        .collect(),
+    )
+  }
+}
+
+/// Upper bound on occurrences materialized per call; enough for previews
+/// and reminder windows, small enough for an endless daily rule.
+const MAX_LOOKAHEAD: usize = 400;
+
+fn recurrence_from_row(row: &Row<'_>) -> rusqlite::Result<TaskRecurrence> {
+  let exdates: String = row.get("exdates")?;
+  Ok(TaskRecurrence {
+    task_id: row.get("task_id")?,
+    series_id: row.get("series_id")?,
+    rrule: row.get("rrule")?,
+    dtstart: row.get("dtstart")?,
+    exdates: serde_json::from_str(&exdates).unwrap_or_default(),
+    occurrence: row.get("occurrence")?,
+    next_task_id: row.get("next_task_id")?,
+    created_at: row.get("created_at")?,
+    updated_at: row.get("updated_at")?,
+  })
+}
+
+fn write_recurrence(conn: &Connection, recurrence: &TaskRecurrence) -> Result<()> {
+  conn.execute(
+    "insert or replace into task_recurrences \
+     (task_id, series_id, rrule, dtstart, exdates, occurrence, next_task_id, created_at, updated_at) \
+     values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+    params![
+      recurrence.task_id,
+      recurrence.series_id,
+      recurrence.rrule,
+      recurrence.dtstart,
+      serde_json::to_string(&recurrence.exdates)?,
+      recurrence.occurrence,
+      recurrence.next_task_id,
+      recurrence.created_at,
+      recurrence.updated_at,
+    ],
+  )?;
+  Ok(())
+}
+
+fn read_recurrence(conn: &Connection, task_id: &str) -> Result<Option<TaskRecurrence>> {
+  Ok(
+    conn
+      .query_row(
+        "select * from task_recurrences where task_id = ?1",
+        [task_id],
+        recurrence_from_row,
+      )
+      .optional()?,
+  )
+}
+
+fn activity(task: &Task, action: &str, metadata: Value) -> TaskActivity {
+  TaskActivity {
+    id: new_id(),
+    task_id: task.id.clone(),
+    user_id: task.user_id.clone(),
+    action: action.to_string(),
+    metadata: metadata.as_object().cloned(),
+    created_at: now_iso(),
+  }
+}
+
+/// Create the instance following `task`, which was just completed.
+///
+/// Instances that fell due while the series was left open are skipped, so
+/// finishing a daily task a week late does not leave a week of copies;
+/// the next instance is the first occurrence after the completed one that
+/// is due `today` or later. Returns `None` when the task does not recur,
+/// already has a successor, or the series is over.
+pub(crate) fn advance(conn: &Connection, task: &Task, today: NaiveDate) -> Result<Option<Task>> {
+  let Some(mut current) = read_recurrence(conn, &task.id)? else {
+    return Ok(None);
+  };
+  if current.next_task_id.is_some() {
+    return Ok(None);
+  }
+  let Some((due, style)) = task.date.as_deref().and_then(parse_task_date) else {
+    return Ok(None);
+  };
+  let Some((occurrence, at)) = current
+    .occurrences_after(due)?
+    .into_iter()
+    .find(|(_, at)| at.date() >= today)
+  else {
+    return Ok(None);
+  };
+
+  let now = now_iso();
+  let next = Task {
+    id: new_id(),
+    title: task.title.clone(),
+    date: Some(format_task_date(at, style)),
+    project: task.project.clone(),
+    description: task.description.clone(),
+    icon: task.icon.clone(),
+    created_at: Some(now.clone()),
+    updated_at: Some(now.clone()),
+    user_id: task.user_id.clone(),
+    sort_order: task.sort_order,
+    flagged: task.flagged,
+    ..Default::default()
+  };
+  write_task(conn, &next)?;
+  conn.execute(
+    "insert into task_tags (task_id, tag_id, created_at) \
+     select ?1, tag_id, ?2 from task_tags where task_id = ?3",
+    params![next.id, now, task.id],
+  )?;
+  search::reindex_task(conn, &next.id)?;
+
+  write_recurrence(
+    conn,
+    &TaskRecurrence {
+      task_id: next.id.clone(),
+      occurrence,
+      next_task_id: None,
+      created_at: now.clone(),
+      updated_at: now.clone(),
+      ..current.clone()
+    },
+  )?;
+  current.next_task_id = Some(next.id.clone());
+  current.updated_at = now;
+  write_recurrence(conn, &current)?;
+
+  write_activity(
+    conn,
+    &activity(
+      task,
+      NEXT_CREATED_ACTION,
+      json!({
+        "series_id": current.series_id,
+        "next_task_id": next.id,
+        "next_date": next.date,
+        "occurrence": occurrence,
+      }),
+    ),
+  )?;
+  write_activity(
+    conn,
+    &activity(
+      &next,
+      CREATED_FROM_ACTION,
+      json!({
+        "series_id": current.series_id,
+        "previous_task_id": task.id,
+        "occurrence": occurrence,
+      }),
+    ),
+  )?;
+  Ok(Some(next))
+}
+
+impl Store {
+  pub fn get_task_recurrence(&self, task_id: &str) -> Result<Option<TaskRecurrence>> {
+    read_recurrence(&self.conn(), task_id)
+  }
+
+  /// Make a task recur. The task must have a due date, which becomes the
+  /// first occurrence; changing the rule restarts the series from the
+  /// task's current date, while changing only the exceptions keeps it.
+  pub fn set_task_recurrence(
+    &self,
+    task_id: &str,
+    rrule: &str,
+    exdates: Vec<NaiveDate>,
+  ) -> Result<TaskRecurrence> {
+    let rrule = rrule.parse::<RRule>()?.to_string();
+    let conn = self.conn();
+    let task =
+      read_task(&conn, task_id)?.ok_or_else(|| Error::NotFound("task", task_id.to_string()))?;
+    let date = task
+      .date
+      .filter(|d| parse_task_date(d).is_some())
+      .ok_or_else(|| Error::InvalidInput("a recurring task needs a due date".into()))?;
+
+    let now = now_iso();
+    let recurrence = match read_recurrence(&conn, task_id)? {
+      Some(existing) if existing.rrule == rrule => TaskRecurrence {
+        exdates,
+        updated_at: now,
+        ..existing
+      },
+      existing => TaskRecurrence {
+        task_id: task_id.to_string(),
+        series_id: existing.map_or_else(new_id, |e| e.series_id),
+        rrule,
+        dtstart: date,
+        exdates,
+        occurrence: 1,
+        next_task_id: None,
+        created_at: now.clone(),
+        updated_at: now,
+      },
+    };
+    write_recurrence(&conn, &recurrence)?;
+    Ok(recurrence)
+  }
+
+  /// Stop the series at this instance. Earlier instances keep their rows
+  /// as history.
+  pub fn clear_task_recurrence(&self, task_id: &str) -> Result<bool> {
+    let removed = self
+      .conn()
+      .execute("delete from task_recurrences where task_id = ?1", [task_id])?;
+    Ok(removed > 0)
+  }
+
+  /// The next `limit` due dates after the task's current one, formatted
+  /// like its `date`.
+  pub fn upcoming_occurrences(&self, task_id: &str, limit: usize) -> Result<Vec<String>> {
+    let conn = self.conn();
+    let (Some(task), Some(recurrence)) =
+      (read_task(&conn, task_id)?, read_recurrence(&conn, task_id)?)
+    else {
+      return Ok(Vec::new());
+    };
+    let Some((due, style)) = task.date.as_deref().and_then(parse_task_date) else {
+      return Ok(Vec::new());
+    };
+    Ok(
+      recurrence
+        .occurrences_after(due)?
+        .into_iter()
+        .take(limit)
+        .map(|(_, at)| format_task_date(at, style))
+        .collect(),
+    )
+  }
+
+  /// Future occurrences in `(from, to]` of every open series, beyond each
+  /// series' current instance, paired with that instance.
+  pub fn recurring_occurrences_between(
+    &self,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+  ) -> Result<Vec<(Task, String)>> {
+    let conn = self.conn();
+    let mut stmt = conn.prepare(&format!(
+      "select {TASK_COLUMNS} from tasks \
+       where id in (select task_id from task_recurrences where next_task_id is null) \
+       and completed = 0 and deleted = 0 and abandoned = 0"
+    ))?;
+    let tasks = stmt
+      .query_map([], task_from_row)?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut found = Vec::new();
+    for task in tasks {
+      let (Some(recurrence), Some((due, style))) = (
+        read_recurrence(&conn, &task.id)?,
+        task.date.as_deref().and_then(parse_task_date),
+      ) else {
+        continue;
+      };
+      for (_, at) in recurrence.occurrences_after(due.max(from))? {
+        if at > to {
+          break;
+        }
+        found.push((task.clone(), format_task_date(at, style)));
+      }
+    }
+    Ok(found)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn complete(store: &Store, id: &str) -> Task {
+    store
+      .update_task(id, json!({ "completed": true }).as_object().unwrap())
+      .unwrap()
+      .unwrap()
+  }
+
+  fn successor(store: &Store, id: &str) -> Task {
+    let next = store
+      .get_task_recurrence(id)
+      .unwrap()
+      .unwrap()
+      .next_task_id
+      .expect("a next instance");
+    store.get_task_by_id(&next).unwrap().unwrap()
+  }
+
+  #[test]
+  fn completing_an_instance_creates_the_next_one() {
+    let store = Store::open_in_memory().unwrap();
+    let tag = store.create_tag("work", None).unwrap();
+    let report = store
+      .create_task(Task {
+        title: "Weekly report".into(),
+        date: Some("2099-01-02".into()),
+        flagged: true,
+        ..Default::default()
+      })
+      .unwrap();
+    store.attach_tag_to_task(&report.id, &tag.id).unwrap();
+    assert!(store
+      .set_task_recurrence(&report.id, "FREQ=WEEKLY;COUNT=3", vec![])
+      .is_ok());
+    let skipped = NaiveDate::from_ymd_opt(2099, 1, 9).unwrap();
+    let recurrence = store
+      .set_task_recurrence(&report.id, "rrule:freq=weekly;count=3", vec![skipped])
+      .unwrap();
+    assert_eq!(recurrence.rrule, "FREQ=WEEKLY;COUNT=3");
+    assert_eq!(
+      store.upcoming_occurrences(&report.id, 5).unwrap(),
+      ["2099-01-16"]
+    );
+
+    complete(&store, &report.id);
+    let second = successor(&store, &report.id);
+    assert_eq!(second.date.as_deref(), Some("2099-01-16"));
+    assert!(!second.completed && second.flagged);
+    let tags = store
+      .get_tags_by_task_ids(std::slice::from_ref(&second.id))
+      .unwrap();
+    assert_eq!(tags[&second.id][0].name, "work");
+    assert_eq!(
+      store
+        .get_task_recurrence(&second.id)
+        .unwrap()
+        .unwrap()
+        .occurrence,
+      3
+    );
+
+    // Completing twice does not fork the series.
+    let updates = json!({ "completed": false });
+    store
+      .update_task(&report.id, updates.as_object().unwrap())
+      .unwrap();
+    complete(&store, &report.id);
+    assert_eq!(successor(&store, &report.id).id, second.id);
+
+    // COUNT=3 ends the series after the third occurrence.
+    complete(&store, &second.id);
+    let last = store.get_task_recurrence(&second.id).unwrap().unwrap();
+    assert_eq!(last.next_task_id, None);
+
+    let history = store.get_task_activities(&report.id).unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].action, NEXT_CREATED_ACTION);
+    let back = store.get_task_activities(&second.id).unwrap();
+    assert_eq!(back[0].action, CREATED_FROM_ACTION);
+    assert_eq!(
+      back[0].metadata.as_ref().unwrap()["previous_task_id"],
+      json!(report.id)
+    );
+  }
+
+  #[test]
+  fn late_completion_skips_missed_occurrences() {
+    let store = Store::open_in_memory().unwrap();
+    let task = store
+      .create_task(Task {
+        title: "Stand-up notes".into(),
+        date: Some("2020-03-02 09:30:00".into()),
+        ..Default::default()
+      })
+      .unwrap();
+    store
+      .set_task_recurrence(&task.id, "FREQ=DAILY", vec![])
+      .unwrap();
+    let today = NaiveDate::from_ymd_opt(2020, 3, 10).unwrap();
+    let conn = store.conn();
+    let done = Task {
+      completed: true,
+      ..task
+    };
+    let next = advance(&conn, &done, today).unwrap().unwrap();
+    assert_eq!(next.date.as_deref(), Some("2020-03-10 09:30:00"));
+    assert_eq!(
+      read_recurrence(&conn, &next.id)
+        .unwrap()
+        .unwrap()
+        .occurrence,
+      9
+    );
+  }
+
+  #[test]
+  fn recurrence_needs_a_valid_rule_and_a_due_date() {
+    let store = Store::open_in_memory().unwrap();
+    let task = store
+      .create_task(Task {
+        title: "No date".into(),
+        ..Default::default()
+      })
+      .unwrap();
+    assert!(store
+      .set_task_recurrence(&task.id, "FREQ=DAILY", vec![])
+      .is_err());
+    assert!(store
+      .set_task_recurrence("missing", "FREQ=DAILY", vec![])
+      .is_err());
+    assert!(store
+      .set_task_recurrence(&task.id, "FREQ=SOMETIMES", vec![])
+      .is_err());
+  }
+}
@@ -0,0 +1,612 @@
+//! Full-text search over tasks.
+//!
+//! `task_search` is an FTS5 table with one row per task: the title, the
+//! description flattened out of the editor JSON, and the names of its tags.
+//! FTS5's `unicode61` tokenizer keeps a run of Chinese characters as one
+//! token, so CJK characters are spaced out before indexing and every
+//! character becomes a token; a query like `周报` turns into the phrase
+//! `"周 报"`, which matches exactly where the two characters are adjacent.
+//!
+//! The index is maintained by the storage write paths themselves
+//! (`write_task`, task deletion, tag links and renames), and rebuilt on open
+//! if it ever falls out of step with `tasks`.
+
+use std::time::Instant;
+
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, params_from_iter, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::models::{SearchOptions, SearchResult, Task};
+use super::tasks::{read_task, task_from_row, TASK_COLUMNS};
+use super::Store;
+use crate::error::Result;
+
+/// Column weights for `bm25()`: task_id, title, body, tags.
+const WEIGHTS: &str = "0.0, 10.0, 1.0, 4.0";
+
+/// Characters of context kept around the first match in a snippet.
+const SNIPPET_BEFORE: usize = 24;
+const SNIPPET_LEN: usize = 120;
+
+/// A piece of highlighted text; matched parts have `highlight` set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fragment {
+  pub text: String,
+  #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+  pub highlight: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+  pub task_id: String,
+  /// Higher is better.
+  pub score: f64,
+  pub title: Vec<Fragment>,
+  /// Part of the description around the first match; empty when only the
+  /// title or tags matched.
+  pub snippet: Vec<Fragment>,
+  /// Tags whose names matched.
+  pub tags: Vec<String>,
+}
+
+fn is_cjk(c: char) -> bool {
+  matches!(c,
+    '\u{3040}'..='\u{30ff}'     // Hiragana, Katakana
+    | '\u{3400}'..='\u{4dbf}'   // CJK Extension A
+    | '\u{4e00}'..='\u{9fff}'   // CJK Unified Ideographs
+    | '\u{ac00}'..='\u{d7af}'   // Hangul syllables
+    | '\u{f900}'..='\u{faff}'   // CJK Compatibility Ideographs
+    | '\u{20000}'..='\u{2fa1f}' // Extensions B–F, compatibility supplement
+  )
+}
+
+/// Text as it is stored in the index: CJK characters become single tokens.
+fn segment(text: &str) -> String {
+  let mut out = String::with_capacity(text.len() + text.len() / 2);
+  for c in text.chars() {
+    if is_cjk(c) {
+      out.push(' ');
+      out.push(c);
+      out.push(' ');
+    } else {
+      out.push(c);
+    }
+  }
+  out
+}
+
+/// Tokens of one query term, split the way `unicode61` splits text.
+fn tokens(term: &str) -> Vec<String> {
+  let mut tokens = Vec::new();
+  let mut word = String::new();
+  for c in term.chars() {
+    if is_cjk(c) || !c.is_alphanumeric() {
+      if !word.is_empty() {
+        tokens.push(std::mem::take(&mut word));
+      }
+      if is_cjk(c) {
+        tokens.push(c.to_string());
+      }
+    } else {
+      word.push(c);
+    }
+  }
+  if !word.is_empty() {
+    tokens.push(word);
+  }
+  tokens
+}
+
+/// FTS5 query for user input: every whitespace-separated term must match,
+/// as a phrase, and Latin terms also match as a prefix ("repo" → "report").
+fn match_expression(query: &str) -> Option<String> {
+  let phrases: Vec<String> = query
+    .split_whitespace()
+    .filter_map(|term| {
+      let tokens = tokens(term);
+      let last = tokens.last()?;
+      let prefix = !last.chars().any(is_cjk);
+      Some(format!(
+        "\"{}\"{}",
+        tokens.join(" "),
+        if prefix { "*" } else { "" }
+      ))
+    })
+    .collect();
+  (!phrases.is_empty()).then(|| phrases.join(" "))
+}
+
+fn collect_text(value: &Value, out: &mut Vec<String>) {
+  match value {
+    Value::Array(items) => items.iter().for_each(|v| collect_text(v, out)),
+    Value::Object(fields) => {
+      for (key, v) in fields {
+        match (key.as_str(), v) {
+          // BlockNote inline content and Editor.js block data.
+          ("text" | "caption" | "code", Value::String(s)) => out.push(strip_tags(s)),
+          ("items", Value::Array(items)) => {
+            for item in items {
+              match item {
+                Value::String(s) => out.push(strip_tags(s)),
+                other => collect_text(other, out),
+              }
+            }
+          }
+          (_, Value::Array(_) | Value::Object(_)) => collect_text(v, out),
+          _ => {}
+        }
+      }
+    }
+    _ => {}
+  }
+}
+
+fn strip_tags(html: &str) -> String {
+  let mut out = String::with_capacity(html.len());
+  let mut in_tag = false;
+  for c in html.chars() {
+    match c {
+      '<' => in_tag = true,
+      '>' if in_tag => in_tag = false,
+      _ if !in_tag => out.push(c),
+      _ => {}
+    }
+  }
+  out
+}
+
+/// Plain text of a task description. Descriptions are BlockNote or Editor.js
+/// JSON from older versions, or Markdown from the current editor.
+pub(crate) fn flatten_description(description: &str) -> String {
+  match serde_json::from_str::<Value>(description) {
+    Ok(value @ (Value::Array(_) | Value::Object(_))) => {
+      let mut parts = Vec::new();
+      collect_text(&value, &mut parts);
+      parts.retain(|p| !p.trim().is_empty());
+      parts.join("\n")
+    }
+    _ => description.to_string(),
+  }
+}
+
+fn tag_names(conn: &Connection, task_id: &str) -> Result<Vec<String>> {
+  let mut stmt = conn.prepare_cached(
+    "select t.name from task_tags tt join tags t on t.id = tt.tag_id \
+     where tt.task_id = ?1 order by tt.created_at",
+  )?;
+  let names = stmt
+    .query_map([task_id], |row| row.get(0))?
+    .collect::<rusqlite::Result<Vec<String>>>()?;
+  Ok(names)
+}
+
+/// Replace the index entry of `task`.
+pub(crate) fn index_task(conn: &Connection, task: &Task) -> Result<()> {
+  remove_task(conn, &task.id)?;
+  let body = task
+    .description
+    .as_deref()
+    .map(flatten_description)
+    .unwrap_or_default();
+  conn
+    .prepare_cached("insert into task_search (task_id, title, body, tags) values (?1, ?2, ?3, ?4)")?
+    .execute(params![
+      task.id,
+      segment(&task.title),
+      segment(&body),
+      segment(&tag_names(conn, &task.id)?.join("\n")),
+    ])?;
+  Ok(())
+}
+
+pub(crate) fn remove_task(conn: &Connection, task_id: &str) -> Result<()> {
+  conn
+    .prepare_cached("delete from task_search where task_id = ?1")?
+    .execute([task_id])?;
+  Ok(())
+}
+
+/// Re-read a task and re-index it, e.g. after its tags changed.
+pub(crate) fn reindex_task(conn: &Connection, task_id: &str) -> Result<()> {
+  match read_task(conn, task_id)? {
+    Some(task) => index_task(conn, &task),
+    None => remove_task(conn, task_id),
+  }
+}
+
+/// Ids of the tasks carrying any of the tags selected by `tag_filter`
+/// (`id = ?1` or `project_id = ?1`), for re-indexing after tag changes.
+pub(crate) fn tasks_tagged(conn: &Connection, tag_filter: &str, key: &str) -> Result<Vec<String>> {
+  let mut stmt = conn.prepare(&format!(
+    "select distinct tt.task_id from task_tags tt join tags t on t.id = tt.tag_id \
+     where t.{tag_filter} = ?1"
+  ))?;
+  let ids = stmt
+    .query_map([key], |row| row.get(0))?
+    .collect::<rusqlite::Result<Vec<String>>>()?;
+  Ok(ids)
+}
+
+/// Drop and rebuild the whole index.
+pub(crate) fn rebuild(conn: &Connection) -> Result<usize> {
+  conn.execute("delete from task_search", [])?;
+  let mut stmt = conn.prepare(&format!("select {TASK_COLUMNS} from tasks"))?;
+  let tasks = stmt
+    .query_map([], task_from_row)?
+    .collect::<rusqlite::Result<Vec<_>>>()?;
+  for task in &tasks {
+    index_task(conn, task)?;
+  }
+  Ok(tasks.len())
+}
+
+/// Rebuild the index when it does not cover exactly the stored tasks, e.g.
+/// right after the migration that created it.
+pub(crate) fn ensure_index(conn: &Connection) -> Result<()> {
+  let (indexed, stored): (i64, i64) = conn.query_row(
+    "select (select count(*) from task_search), (select count(*) from tasks)",
+    [],
+    |row| Ok((row.get(0)?, row.get(1)?)),
+  )?;
+  if indexed != stored {
+    let count = rebuild(conn)?;
+    log::info!("rebuilt search index for {count} tasks");
+  }
+  Ok(())
+}
+
+/// Lowercased chars of `text` with the byte offset each one starts at.
+fn folded(text: &str) -> Vec<(usize, char)> {
+  text
+    .char_indices()
+    .map(|(i, c)| (i, c.to_lowercase().next().unwrap_or(c)))
+    .collect()
+}
+
+/// Byte ranges in `text` where any needle occurs, case-insensitively,
+/// sorted and non-overlapping.
+fn find_matches(text: &str, needles: &[Vec<char>]) -> Vec<(usize, usize)> {
+  let chars = folded(text);
+  let mut ranges = Vec::new();
+  let mut i = 0;
+  while i < chars.len() {
+    let hit = needles
+      .iter()
+      .filter(|n| !n.is_empty() && i + n.len() <= chars.len())
+      .filter(|n| n.iter().zip(&chars[i..]).all(|(a, (_, b))| a == b))
+      .map(Vec::len)
+      .max();
+    match hit {
+      Some(len) => {
+        let end = chars.get(i + len).map_or(text.len(), |(b, _)| *b);
+        ranges.push((chars[i].0, end));
+        i += len;
+      }
+      None => i += 1,
+    }
+  }
+  ranges
+}
+
+fn fragments(text: &str, ranges: &[(usize, usize)]) -> Vec<Fragment> {
+  let mut out = Vec::new();
+  let mut pos = 0;
+  for &(start, end) in ranges {
+    if start > pos {
+      out.push(Fragment {
+        text: text[pos..start].to_string(),
+        highlight: false,
+      });
+    }
+    out.push(Fragment {
+      text: text[start..end].to_string(),
+      highlight: true,
+    });
+    pos = end;
+  }
+  if pos < text.len() {
+    out.push(Fragment {
+      text: text[pos..].to_string(),
+      highlight: false,
+    });
+  }
+  out
+}
+
+/// Window of `text` around its first match, with ellipses where cut.
+fn snippet(text: &str, needles: &[Vec<char>]) -> Vec<Fragment> {
+  let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+  let ranges = find_matches(&text, needles);
+  let Some(&(first, _)) = ranges.first() else {
+    return Vec::new();
+  };
+  let starts: Vec<usize> = text.char_indices().map(|(i, _)| i).collect();
+  let first_char = starts.partition_point(|b| *b < first);
+  let from_char = first_char.saturating_sub(SNIPPET_BEFORE);
+  let to_char = (from_char + SNIPPET_LEN).min(starts.len());
+  let from = starts[from_char];
+  let to = starts.get(to_char).copied().unwrap_or(text.len());
+
+  let window: Vec<(usize, usize)> = ranges
+    .iter()
+    .filter(|(s, e)| *s >= from && *e <= to)
+    .map(|(s, e)| (s - from, e - from))
+    .collect();
+  let mut out = fragments(&text[from..to], &window);
+  if from > 0 {
+    out.insert(
+      0,
+      Fragment {
+        text: "…".into(),
+        highlight: false,
+      },
+    );
+  }
+  if to < text.len() {
+    out.push(Fragment {
+      text: "…".into(),
+      highlight: false,
+    });
+  }
+  out
+}
+
+impl Store {
+  /// Ranked full-text search over titles, descriptions and tag names.
+  ///
+  /// `tasks` holds the best `limit` matches in rank order, `hits` the
+  /// highlighting for each of them in the same order.
+  pub fn search_tasks(&self, query: &str, options: &SearchOptions) -> Result<SearchResult> {
+    let started = Instant::now();
+    let Some(expression) = match_expression(query) else {
+      return Ok(SearchResult::default());
+    };
+
+    let mut clauses = vec!["task_search match ?".to_string()];
+    let mut args: Vec<SqlValue> = vec![expression.into()];
+    if let Some(project) = &options.project_filter {
+      clauses.push("t.project = ?".into());
+      args.push(project.clone().into());
+    }
+    for (column, include) in [
+      ("completed", options.include_completed),
+      ("deleted", options.include_deleted),
+      ("abandoned", options.include_abandoned),
+    ] {
+      if !include {
+        clauses.push(format!("t.{column} = 0"));
+      }
+    }
+    let sql = format!(
+      "select t.id, bm25(task_search, {WEIGHTS}) as rank \
+       from task_search join tasks t on t.id = task_search.task_id \
+       where {} order by rank, t.completed, t.created_at desc",
+      clauses.join(" and ")
+    );
+
+    let conn = self.conn();
+    let ranked: Vec<(String, f64)> = conn
+      .prepare(&sql)?
+      .query_map(params_from_iter(args), |row| Ok((row.get(0)?, row.get(1)?)))?
+      .collect::<rusqlite::Result<_>>()?;
+    let total_count = ranked.len();
+    let limit = options.limit.filter(|l| *l > 0).unwrap_or(50);
+
+    let needles: Vec<Vec<char>> = query
+      .split_whitespace()
+      .map(|term| {
+        term
+          .trim_matches(|c: char| !c.is_alphanumeric())
+          .chars()
+          .flat_map(char::to_lowercase)
+          .collect()
+      })
+      .collect();
+    let mut tasks = Vec::new();
+    let mut hits = Vec::new();
+    for (id, rank) in ranked.into_iter().take(limit) {
+      let Some(task) = read_task(&conn, &id)? else {
+        continue;
+      };
+      let body = task
+        .description
+        .as_deref()
+        .map(flatten_description)
+        .unwrap_or_default();
+      hits.push(SearchHit {
+        task_id: id.clone(),
+        score: -rank,
+        title: fragments(&task.title, &find_matches(&task.title, &needles)),
+        snippet: snippet(&body, &needles),
+        tags: tag_names(&conn, &id)?
+          .into_iter()
+          .filter(|name| !find_matches(name, &needles).is_empty())
+          .collect(),
+      });
+      tasks.push(task);
+    }
+
+    Ok(SearchResult {
+      tasks,
+      total_count,
+      search_time: started.elapsed().as_secs_f64() * 1000.0,
+      hits,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn add(store: &Store, title: &str, description: Option<&str>) -> Task {
+    store
+      .create_task(Task {
+        title: title.into(),
+        description: description.map(Into::into),
+        ..Default::default()
+      })
+      .unwrap()
+  }
+
+  fn titles(result: &SearchResult) -> Vec<&str> {
+    result.tasks.iter().map(|t| t.title.as_str()).collect()
+  }
+
+  fn highlighted(fragments: &[Fragment]) -> Vec<&str> {
+    fragments
+      .iter()
+      .filter(|f| f.highlight)
+      .map(|f| f.text.as_str())
+      .collect()
+  }
+
+  #[test]
+  fn flattens_editor_json() {
+    let blocknote = json!([
+      { "type": "paragraph", "content": [{ "type": "text", "text": "准备季度汇报" }] },
+      { "type": "bulletListItem", "content": [], "children": [
+        { "type": "paragraph", "content": [{ "type": "text", "text": "collect numbers" }] }
+      ] }
+    ]);
+    assert_eq!(
+      flatten_description(&blocknote.to_string()),
+      "准备季度汇报\ncollect numbers"
+    );
+    let editorjs = json!({ "blocks": [
+      { "type": "paragraph", "data": { "text": "Call <b>Alice</b>" } },
+      { "type": "list", "data": { "items": ["one", "two"] } }
+    ] });
+    assert_eq!(
+      flatten_description(&editorjs.to_string()),
+      "Call Alice\none\ntwo"
+    );
+    assert_eq!(flatten_description("# plain markdown"), "# plain markdown");
+  }
+
+  #[test]
+  fn builds_phrase_queries() {
+    assert_eq!(
+      match_expression("周报 repo").as_deref(),
+      Some("\"周 报\" \"repo\"*")
+    );
+    assert_eq!(match_expression("e-mail\"").as_deref(), Some("\"e mail\"*"));
+    assert_eq!(match_expression(" !! "), None);
+  }
+
+  #[test]
+  fn ranks_title_matches_first_and_highlights() {
+    let store = Store::open_in_memory().unwrap();
+    add(&store, "整理会议记录", Some("下周的周报也要写"));
+    add(&store, "写周报", None);
+    add(&store, "周末报名", None);
+
+    let result = store
+      .search_tasks("周报", &SearchOptions::default())
+      .unwrap();
+    assert_eq!(titles(&result), ["写周报", "整理会议记录"]);
+    assert_eq!(highlighted(&result.hits[0].title), ["周报"]);
+    assert_eq!(highlighted(&result.hits[1].snippet), ["周报"]);
+    assert!(result.search_time >= 0.0);
+  }
+
+  #[test]
+  fn latin_terms_match_as_prefixes() {
+    let store = Store::open_in_memory().unwrap();
+    store
+      .create_task(Task {
+        title: "Weekly report".into(),
+        ..Default::default()
+      })
+      .unwrap();
+    store
+      .create_task(Task {
+        completed: true,
+        title: "Monthly report".into(),
+        ..Default::default()
+      })
+      .unwrap();
+
+    let open = store
+      .search_tasks("REPO", &SearchOptions::default())
+      .unwrap();
+    assert_eq!(titles(&open), ["Weekly report"]);
+    assert_eq!(highlighted(&open.hits[0].title), ["repo"]);
+
+    let all = store
+      .search_tasks(
+        "report",
+        &SearchOptions {
+          include_completed: true,
+          ..Default::default()
+        },
+      )
+      .unwrap();
+    assert_eq!(all.total_count, 2);
+  }
+
+  #[test]
+  fn index_follows_every_mutation() {
+    let store = Store::open_in_memory().unwrap();
+    let task = add(&store, "Buy milk", None);
+    let search = |q: &str| {
+      store
+        .search_tasks(q, &SearchOptions::default())
+        .unwrap()
+        .total_count
+    };
+
+    store
+      .update_task(
+        &task.id,
+        json!({ "title": "Buy bread" }).as_object().unwrap(),
+      )
+      .unwrap();
+    assert_eq!((search("milk"), search("bread")), (0, 1));
+
+    let tag = store.create_tag("杂货", None).unwrap();
+    store.attach_tag_to_task(&task.id, &tag.id).unwrap();
+    let result = store
+      .search_tasks("杂货", &SearchOptions::default())
+      .unwrap();
+    assert_eq!(result.hits[0].tags, ["杂货"]);
+
+    store
+      .update_tag(&tag.id, json!({ "name": "groceries" }).as_object().unwrap())
+      .unwrap();
+    assert_eq!((search("杂货"), search("groceries")), (0, 1));
+    store.delete_tag(&tag.id).unwrap();
+    assert_eq!(search("groceries"), 0);
+
+    store.delete_task(&task.id).unwrap();
+    assert_eq!(search("bread"), 0);
+  }
+
+  #[test]
+  fn rebuilds_a_stale_index_on_open() {
+    let store = Store::open_in_memory().unwrap();
+    add(&store, "Plan trip", None);
+    store.conn().execute("delete from task_search", []).unwrap();
+    ensure_index(&store.conn()).unwrap();
+    assert_eq!(
+      store
+        .search_tasks("trip", &SearchOptions::default())
+        .unwrap()
+        .total_count,
+      1
+    );
+  }
+
+  #[test]
+  fn snippets_are_cut_around_the_first_match() {
+    let text = format!("{} needle {}", "a ".repeat(40), "b ".repeat(80));
+    let parts = snippet(&text, &[vec!['n', 'e', 'e', 'd', 'l', 'e']]);
+    assert_eq!(parts.first().unwrap().text, "…");
+    assert_eq!(parts.last().unwrap().text, "…");
+    assert_eq!(highlighted(&parts), ["needle"]);
+  }
+}
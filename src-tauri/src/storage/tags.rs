@@ -0,0 +1,182 @@
+//! Tag and task-tag operations.
+
+use std::collections::HashMap;
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde_json::{Map, Value};
+
+use super::models::{Tag, TaskTagLink};
+use super::{merge_patch, new_id, now_iso, search, Store};
+use crate::error::Result;
+
+pub(crate) fn tag_from_row(row: &Row<'_>) -> rusqlite::Result<Tag> {
+  Ok(Tag {
+    id: row.get("id")?,
+    name: row.get("name")?,
+    user_id: row.get("user_id")?,
+    project_id: row.get("project_id")?,
+    created_at: row.get("created_at")?,
+  })
+}
+
+pub(crate) fn write_tag(conn: &Connection, tag: &Tag) -> Result<()> {
+  conn.execute(
+    "insert or replace into tags (id, name, user_id, project_id, created_at) \
+     values (?1, ?2, ?3, ?4, ?5)",
+    params![
+      tag.id,
+      tag.name,
+      tag.user_id,
+      tag.project_id,
+      tag.created_at
+    ],
+  )?;
+  Ok(())
+}
+
+fn read_tag(conn: &Connection, id: &str) -> Result<Option<Tag>> {
+  Ok(
+    conn
+      .query_row(
+        "select id, name, user_id, project_id, created_at from tags where id = ?1",
+        [id],
+        tag_from_row,
+      )
+      .optional()?,
+  )
+}
+
+impl Store {
+  /// `None` returns every tag, `Some(None)` only global tags and
+  /// `Some(Some(id))` the tags scoped to one project.
+  pub fn get_tags(&self, project_id: Option<Option<&str>>) -> Result<Vec<Tag>> {
+    let conn = self.conn();
+    let (sql, arg) = match project_id {
+      None => ("select * from tags order by created_at", None),
+      Some(None) => (
+        "select * from tags where project_id is null order by created_at",
+        None,
+      ),
+      Some(Some(id)) => (
+        "select * from tags where project_id = ?1 order by created_at",
+        Some(id),
+      ),
+    };
+    let mut stmt = conn.prepare(sql)?;
+    let rows = match arg {
+      Some(id) => stmt.query_map([id], tag_from_row)?,
+      None => stmt.query_map([], tag_from_row)?,
+    };
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+  }
+
+  pub fn get_tag_by_id(&self, id: &str) -> Result<Option<Tag>> {
+    read_tag(&self.conn(), id)
+  }
+
+  pub fn create_tag(&self, name: &str, project_id: Option<&str>) -> Result<Tag> {
+    let tag = Tag {
+      id: new_id(),
+      name: name.to_string(),
+      user_id: None,
+      project_id: project_id.map(str::to_string),
+      created_at: Some(now_iso()),
+    };
+    write_tag(&self.conn(), &tag)?;
+    Ok(tag)
+  }
+
+  pub fn update_tag(&self, id: &str, updates: &Map<String, Value>) -> Result<Option<Tag>> {
+    let conn = self.conn();
+    let Some(existing) = read_tag(&conn, id)? else {
+      return Ok(None);
+    };
+    let mut tag = merge_patch(&existing, updates)?;
+    tag.id = id.to_string();
+    conn.execute(
+      "update tags set name = ?2, user_id = ?3, project_id = ?4, created_at = ?5 where id = ?1",
+      params![
+        tag.id,
+        tag.name,
+        tag.user_id,
+        tag.project_id,
+        tag.created_at
+      ],
+    )?;
+    for task_id in search::tasks_tagged(&conn, "id", id)? {
+      search::reindex_task(&conn, &task_id)?;
+    }
+    Ok(Some(tag))
+  }
+
+  /// Delete a tag; its task links go with it through the foreign key.
+  pub fn delete_tag(&self, id: &str) -> Result<bool> {
+    let conn = self.conn();
+    let tagged = search::tasks_tagged(&conn, "id", id)?;
+    conn.execute("delete from tags where id = ?1", [id])?;
+    for task_id in tagged {
+      search::reindex_task(&conn, &task_id)?;
+    }
+    Ok(true)
+  }
+
+  /// Tags for each of `task_ids`; every requested id is present in the map.
+  pub fn get_tags_by_task_ids(&self, task_ids: &[String]) -> Result<HashMap<String, Vec<Tag>>> {
+    let mut result: HashMap<String, Vec<Tag>> =
+      task_ids.iter().map(|id| (id.clone(), Vec::new())).collect();
+    if task_ids.is_empty() {
+      return Ok(result);
+    }
+
+    let conn = self.conn();
+    let mut stmt = conn.prepare(
+      "select tt.task_id, t.id, t.name, t.user_id, t.project_id, t.created_at \
+       from task_tags tt join tags t on t.id = tt.tag_id \
+       where tt.task_id in (select value from json_each(?1)) \
+       order by tt.created_at",
+    )?;
+    let rows = stmt.query_map([serde_json::to_string(task_ids)?], |row| {
+      Ok((row.get::<_, String>("task_id")?, tag_from_row(row)?))
+    })?;
+    for row in rows {
+      let (task_id, tag) = row?;
+      result.entry(task_id).or_default().push(tag);
+    }
+    Ok(result)
+  }
+
+  /// Every task-tag link, oldest first.
+  pub fn get_task_tag_links(&self) -> Result<Vec<TaskTagLink>> {
+    let conn = self.conn();
+    let mut stmt =
+      conn.prepare("select task_id, tag_id, created_at from task_tags order by created_at")?;
+    let links = stmt
+      .query_map([], |row| {
+        Ok(TaskTagLink {
+          task_id: row.get("task_id")?,
+          tag_id: row.get("tag_id")?,
+          created_at: row.get("created_at")?,
+        })
+      })?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(links)
+  }
+
+  pub fn attach_tag_to_task(&self, task_id: &str, tag_id: &str) -> Result<()> {
+    let conn = self.conn();
+    conn.execute(
+      "insert or replace into task_tags (task_id, tag_id, created_at) values (?1, ?2, ?3)",
+      params![task_id, tag_id, now_iso()],
+    )?;
+    search::reindex_task(&conn, task_id)
+  }
+
+  pub fn detach_tag_from_task(&self, task_id: &str, tag_id: &str) -> Result<()> {
+    let conn = self.conn();
+    conn.execute(
+      "delete from task_tags where task_id = ?1 and tag_id = ?2",
+      params![task_id, tag_id],
+    )?;
+    search::reindex_task(&conn, task_id)
+  }
+}
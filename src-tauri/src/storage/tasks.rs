@@ -0,0 +1,345 @@
+//! Task operations.
+
+use chrono::{DateTime, Local, NaiveDate};
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row};
+use serde_json::{Map, Value};
+
+use super::models::{SortDirection, SortOptions, SortOrderUpdate, Task, TaskFilter};
+use super::{hierarchy, merge_patch, new_id, now_iso, recurrence, search, Store};
+use crate::error::Result;
+
+pub(crate) const TASK_COLUMNS: &str = "id, title, completed, date, project, description, icon, \
+  completed_at, created_at, updated_at, user_id, sort_order, deleted, deleted_at, abandoned, \
+  abandoned_at, flagged, attachments, parent_id, estimate_minutes";
+
+pub(crate) fn task_from_row(row: &Row<'_>) -> rusqlite::Result<Task> {
+  let attachments: String = row.get("attachments")?;
+  Ok(Task {
+    id: row.get("id")?,
+    title: row.get("title")?,
+    completed: row.get("completed")?,
+    date: row.get("date")?,
+    project: row.get("project")?,
+    description: row.get("description")?,
+    icon: row.get("icon")?,
+    completed_at: row.get("completed_at")?,
+    created_at: row.get("created_at")?,
+    updated_at: row.get("updated_at")?,
+    user_id: row.get("user_id")?,
+    sort_order: row.get("sort_order")?,
+    deleted: row.get("deleted")?,
+    deleted_at: row.get("deleted_at")?,
+    abandoned: row.get("abandoned")?,
+    abandoned_at: row.get("abandoned_at")?,
+    flagged: row.get("flagged")?,
+    attachments: serde_json::from_str(&attachments).unwrap_or_default(),
+    parent_id: row.get("parent_id")?,
+    estimate_minutes: row.get("estimate_minutes")?,
+  })
+}
+
+/// Insert a full task row, or overwrite the existing one in place.
+///
+/// An upsert rather than `insert or replace`: replacing deletes the old row
+/// first, and the foreign keys would cascade that delete to the task's tag
+/// links, reminder log and recurrence.
+pub(crate) fn write_task(conn: &Connection, task: &Task) -> Result<()> {
+  let updates = TASK_COLUMNS
+    .split(", ")
+    .skip(1)
+    .map(|c| format!("{c} = excluded.{c}"))
+    .collect::<Vec<_>>()
+    .join(", ");
+  conn.execute(
+    &format!(
+      "insert into tasks ({TASK_COLUMNS}) \
+       values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, \
+       ?19, ?20) \
+       on conflict (id) do update set {updates}"
+    ),
+    params![
+      task.id,
+      task.title,
+      task.completed,
+      task.date,
+      task.project,
+      task.description,
+      task.icon,
+      task.completed_at,
+      task.created_at,
+      task.updated_at,
+      task.user_id,
+      task.sort_order,
+      task.deleted,
+      task.deleted_at,
+      task.abandoned,
+      task.abandoned_at,
+      task.flagged,
+      serde_json::to_string(&task.attachments)?,
+      task.parent_id,
+      task.estimate_minutes,
+    ],
+  )?;
+  search::index_task(conn, task)
+}
+
+pub(crate) fn read_task(conn: &Connection, id: &str) -> Result<Option<Task>> {
+  Ok(
+    conn
+      .query_row(
+        &format!("select {TASK_COLUMNS} from tasks where id = ?1"),
+        [id],
+        task_from_row,
+      )
+      .optional()?,
+  )
+}
+
+/// Local calendar day of a task `date`, read the way date-fns `parseISO`
+/// does: full timestamps are converted to local time, bare `YYYY-MM-DD`
+/// dates are taken as-is.
+pub(crate) fn due_day(date: &str) -> Option<NaiveDate> {
+  DateTime::parse_from_rfc3339(date)
+    .map(|t| t.with_timezone(&Local).date_naive())
+    .or_else(|_| NaiveDate::parse_from_str(date, "%Y-%m-%d"))
+    .ok()
+}
+
+fn query_tasks(conn: &Connection, filter: &TaskFilter, sort: &[SortOptions]) -> Result<Vec<Task>> {
+  let mut clauses = Vec::new();
+  let mut args: Vec<SqlValue> = Vec::new();
+
+  if let Some(project_id) = &filter.project_id {
+    clauses.push("project = ?");
+    args.push(project_id.clone().into());
+  }
+  for (clause, value) in [
+    ("completed = ?", filter.completed),
+    ("deleted = ?", filter.deleted),
+    ("abandoned = ?", filter.abandoned),
+    ("flagged = ?", filter.flagged),
+  ] {
+    if let Some(value) = value {
+      clauses.push(clause);
+      args.push(i64::from(value).into());
+    }
+  }
+  if let Some(user_id) = &filter.user_id {
+    clauses.push("user_id = ?");
+    args.push(user_id.clone().into());
+  }
+
+  let mut sql = format!("select {TASK_COLUMNS} from tasks");
+  if !clauses.is_empty() {
+    sql.push_str(" where ");
+    sql.push_str(&clauses.join(" and "));
+  }
+  if !sort.is_empty() {
+    let order: Vec<String> = sort
+      .iter()
+      .map(|s| {
+        format!(
+          "{} {} nulls {}",
+          s.field.column(),
+          match s.direction {
+            SortDirection::Asc => "asc",
+            SortDirection::Desc => "desc",
+          },
+          if s.nulls_first { "first" } else { "last" }
+        )
+      })
+      .collect();
+    sql.push_str(" order by ");
+    sql.push_str(&order.join(", "));
+  }
+
+  let mut stmt = conn.prepare(&sql)?;
+  let tasks = stmt
+    .query_map(params_from_iter(args), task_from_row)?
+    .collect::<rusqlite::Result<Vec<_>>>()?;
+  Ok(tasks)
+}
+
+impl Store {
+  pub fn get_tasks(&self, filter: &TaskFilter, sort: &[SortOptions]) -> Result<Vec<Task>> {
+    query_tasks(&self.conn(), filter, sort)
+  }
+
+  pub fn get_task_by_id(&self, id: &str) -> Result<Option<Task>> {
+    read_task(&self.conn(), id)
+  }
+
+  /// Create a task; a subtask takes its parent's project and, without a
+  /// `sort_order`, goes first among its siblings.
+  pub fn create_task(&self, input: Task) -> Result<Task> {
+    let now = now_iso();
+    let mut task = Task {
+      id: new_id(),
+      created_at: input.created_at.clone().or_else(|| Some(now.clone())),
+      updated_at: input.updated_at.clone().or(Some(now)),
+      ..input
+    };
+    let conn = self.conn();
+    if task.parent_id.is_some() {
+      let reorder = task.sort_order.is_none();
+      hierarchy::place(&conn, &mut task, reorder)?;
+    }
+    write_task(&conn, &task)?;
+    Ok(task)
+  }
+
+  pub fn update_task(&self, id: &str, updates: &Map<String, Value>) -> Result<Option<Task>> {
+    let mut conn = self.conn();
+    let tx = conn.transaction()?;
+    let Some(existing) = read_task(&tx, id)? else {
+      return Ok(None);
+    };
+    let mut task = merge_patch(&existing, updates)?;
+    task.id = id.to_string();
+    task.updated_at = Some(now_iso());
+    hierarchy::rehome(
+      &tx,
+      &existing,
+      &mut task,
+      !updates.contains_key("sort_order"),
+    )?;
+    write_task(&tx, &task)?;
+    if task.completed && !existing.completed {
+      recurrence::advance(&tx, &task, Local::now().date_naive())?;
+    }
+    tx.commit()?;
+    Ok(Some(task))
+  }
+
+  /// Permanently delete a task and its subtasks; their tag links go with
+  /// them through the foreign key.
+  pub fn delete_task(&self, id: &str) -> Result<bool> {
+    self.transaction(|tx| {
+      for id in hierarchy::subtree_ids(tx, id)? {
+        tx.execute("delete from tasks where id = ?1", [&id])?;
+        search::remove_task(tx, &id)?;
+      }
+      Ok(true)
+    })
+  }
+
+  pub fn batch_update_sort_order(&self, updates: &[SortOrderUpdate]) -> Result<bool> {
+    let mut conn = self.conn();
+    let tx = conn.transaction()?;
+    {
+      let mut stmt = tx.prepare("update tasks set sort_order = ?1 where id = ?2")?;
+      for update in updates {
+        stmt.execute(params![update.sort_order, update.id])?;
+      }
+    }
+    tx.commit()?;
+    Ok(true)
+  }
+
+  /// Open tasks due on `today` or overdue, the same count as the sidebar's
+  /// "Today" entry.
+  pub fn count_due_today(&self, today: NaiveDate) -> Result<usize> {
+    let filter = TaskFilter {
+      completed: Some(false),
+      deleted: Some(false),
+      abandoned: Some(false),
+      ..Default::default()
+    };
+    Ok(
+      query_tasks(&self.conn(), &filter, &[])?
+        .iter()
+        .filter_map(|task| task.date.as_deref().and_then(due_day))
+        .filter(|day| *day <= today)
+        .count(),
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::storage::models::SortField;
+  use serde_json::json;
+
+  fn task(title: &str, sort_order: Option<f64>) -> Task {
+    Task {
+      title: title.into(),
+      sort_order,
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn filters_and_sorts_with_nulls_last() {
+    let store = Store::open_in_memory().unwrap();
+    store.create_task(task("b", Some(2.0))).unwrap();
+    store.create_task(task("none", None)).unwrap();
+    let a = store.create_task(task("a", Some(1.0))).unwrap();
+    store
+      .update_task(&a.id, json!({ "flagged": true }).as_object().unwrap())
+      .unwrap();
+
+    let sort = [SortOptions {
+      field: SortField::SortOrder,
+      direction: SortDirection::Asc,
+      nulls_first: false,
+    }];
+    let titles: Vec<_> = store
+      .get_tasks(&TaskFilter::default(), &sort)
+      .unwrap()
+      .into_iter()
+      .map(|t| t.title)
+      .collect();
+    assert_eq!(titles, ["a", "b", "none"]);
+
+    let flagged = store
+      .get_tasks(
+        &TaskFilter {
+          flagged: Some(true),
+          ..Default::default()
+        },
+        &[],
+      )
+      .unwrap();
+    assert_eq!(flagged.len(), 1);
+    assert_eq!(flagged[0].id, a.id);
+  }
+
+  #[test]
+  fn updates_keep_tag_links() {
+    let store = Store::open_in_memory().unwrap();
+    let tag = store.create_tag("home", None).unwrap();
+    let t = store.create_task(task("t", None)).unwrap();
+    store.attach_tag_to_task(&t.id, &tag.id).unwrap();
+    store
+      .update_task(&t.id, json!({ "title": "renamed" }).as_object().unwrap())
+      .unwrap();
+    let tags = store
+      .get_tags_by_task_ids(std::slice::from_ref(&t.id))
+      .unwrap();
+    assert_eq!(tags[&t.id].len(), 1);
+  }
+
+  #[test]
+  fn counts_open_tasks_due_today_or_overdue() {
+    let store = Store::open_in_memory().unwrap();
+    let today = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+    for (title, date, completed) in [
+      ("today", Some("2026-10-18"), false),
+      ("overdue", Some("2026-10-01"), false),
+      ("done", Some("2026-10-18"), true),
+      ("later", Some("2026-10-25"), false),
+      ("undated", None, false),
+    ] {
+      store
+        .create_task(Task {
+          date: date.map(Into::into),
+          completed,
+          ..task(title, None)
+        })
+        .unwrap();
+    }
+    assert_eq!(store.count_due_today(today).unwrap(), 2);
+  }
+}
@@ -0,0 +1,628 @@
+//! Time tracking: per-task time entries and tracked-versus-estimated totals.
+//!
+//! An entry is either a start/stop timer (`ended_at` is `None` while it
+//! runs) or a span entered by hand. At most one timer runs at a time; the
+//! schema enforces it too. Completed focus pomodoros linked to a task count
+//! as tracked time on that task, so the totals include them without
+//! copying them into `time_entries`.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::tasks::read_task;
+use super::{merge_patch, new_id, now_iso, Store};
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntrySource {
+  #[default]
+  Timer,
+  Manual,
+  /// A focus session linked to the task; only produced by
+  /// [`Store::time_entries_between`], never stored.
+  Pomodoro,
+}
+
+impl EntrySource {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      EntrySource::Timer => "timer",
+      EntrySource::Manual => "manual",
+      EntrySource::Pomodoro => "pomodoro",
+    }
+  }
+}
+
+impl rusqlite::types::FromSql for EntrySource {
+  fn column_result(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+    match value.as_str()? {
+      "manual" => Ok(EntrySource::Manual),
+      "pomodoro" => Ok(EntrySource::Pomodoro),
+      _ => Ok(EntrySource::Timer),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimeEntry {
+  #[serde(default)]
+  pub id: String,
+  pub task_id: String,
+  pub started_at: String,
+  /// `None` while the timer runs.
+  #[serde(default)]
+  pub ended_at: Option<String>,
+  #[serde(default)]
+  pub source: EntrySource,
+  #[serde(default)]
+  pub note: Option<String>,
+  #[serde(default)]
+  pub created_at: String,
+  #[serde(default)]
+  pub updated_at: String,
+}
+
+impl TimeEntry {
+  /// Whole minutes between start and end, or up to `now` while running.
+  pub fn minutes(&self, now: DateTime<Utc>) -> i64 {
+    let Some(start) = parse_time(&self.started_at) else {
+      return 0;
+    };
+    let end = self.ended_at.as_deref().and_then(parse_time).unwrap_or(now);
+    ((end - start).num_seconds().max(0) as f64 / 60.0).round() as i64
+  }
+}
+
+/// Tracked and estimated minutes of one task, project or tag.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeRollup {
+  /// Task, project or tag id; empty for tasks without a project.
+  pub key: String,
+  pub label: String,
+  /// Timer and manual entries.
+  pub tracked_minutes: i64,
+  /// Completed focus pomodoros linked to the task.
+  pub pomodoro_minutes: i64,
+  pub estimated_minutes: i64,
+}
+
+impl TimeRollup {
+  fn add(&mut self, other: &TimeRollup) {
+    self.tracked_minutes += other.tracked_minutes;
+    self.pomodoro_minutes += other.pomodoro_minutes;
+    self.estimated_minutes += other.estimated_minutes;
+  }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeSummary {
+  /// Tasks with tracked time or an estimate.
+  pub tasks: Vec<TimeRollup>,
+  pub projects: Vec<TimeRollup>,
+  pub tags: Vec<TimeRollup>,
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+  DateTime::parse_from_rfc3339(value)
+    .ok()
+    .map(|t| t.with_timezone(&Utc))
+}
+
+fn entry_from_row(row: &Row<'_>) -> rusqlite::Result<TimeEntry> {
+  Ok(TimeEntry {
+    id: row.get("id")?,
+    task_id: row.get("task_id")?,
+    started_at: row.get("started_at")?,
+    ended_at: row.get("ended_at")?,
+    source: row.get("source")?,
+    note: row.get("note")?,
+    created_at: row.get("created_at")?,
+    updated_at: row.get("updated_at")?,
+  })
+}
+
+fn write_entry(conn: &Connection, entry: &TimeEntry) -> Result<()> {
+  conn.execute(
+    "insert into time_entries \
+     (id, task_id, started_at, ended_at, source, note, created_at, updated_at) \
+     values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8) \
+     on conflict (id) do update set task_id = excluded.task_id, \
+     started_at = excluded.started_at, ended_at = excluded.ended_at, \
+     note = excluded.note, updated_at = excluded.updated_at",
+    params![
+      entry.id,
+      entry.task_id,
+      entry.started_at,
+      entry.ended_at,
+      entry.source.as_str(),
+      entry.note,
+      entry.created_at,
+      entry.updated_at,
+    ],
+  )?;
+  Ok(())
+}
+
+fn read_entry(conn: &Connection, id: &str) -> Result<Option<TimeEntry>> {
+  Ok(
+    conn
+      .query_row(
+        "select * from time_entries where id = ?1",
+        [id],
+        entry_from_row,
+      )
+      .optional()?,
+  )
+}
+
+fn running_entry(conn: &Connection) -> Result<Option<TimeEntry>> {
+  Ok(
+    conn
+      .query_row(
+        "select * from time_entries where ended_at is null",
+        [],
+        entry_from_row,
+      )
+      .optional()?,
+  )
+}
+
+/// A finished span must end after it starts; only timers may be open.
+fn validate(entry: &TimeEntry) -> Result<()> {
+  let start = parse_time(&entry.started_at)
+    .ok_or_else(|| Error::InvalidInput(format!("invalid start time: {}", entry.started_at)))?;
+  match entry.ended_at.as_deref() {
+    Some(end) => {
+      let end =
+        parse_time(end).ok_or_else(|| Error::InvalidInput(format!("invalid end time: {end}")))?;
+      if end <= start {
+        return Err(Error::InvalidInput(
+          "time entry ends before it starts".into(),
+        ));
+      }
+    }
+    None if entry.source != EntrySource::Timer => {
+      return Err(Error::InvalidInput(
+        "a manual time entry needs an end time".into(),
+      ));
+    }
+    None => {}
+  }
+  Ok(())
+}
+
+/// Completed focus sessions linked to a task, as entries.
+fn pomodoro_entries(conn: &Connection) -> Result<Vec<TimeEntry>> {
+  let mut stmt = conn.prepare(
+    "select id, task_id, started_at, completed_at, title, created_at from pomodoro_sessions \
+     where type = 'work' and task_id is not null and completed_at is not null \
+     order by started_at",
+  )?;
+  let entries = stmt
+    .query_map([], |row| {
+      Ok(TimeEntry {
+        id: row.get(0)?,
+        task_id: row.get(1)?,
+        started_at: row.get(2)?,
+        ended_at: row.get(3)?,
+        source: EntrySource::Pomodoro,
+        note: row.get(4)?,
+        created_at: row.get(5)?,
+        updated_at: row.get(5)?,
+      })
+    })?
+    .collect::<rusqlite::Result<Vec<_>>>()?;
+  Ok(entries)
+}
+
+fn sorted(rollups: HashMap<String, TimeRollup>) -> Vec<TimeRollup> {
+  let mut rollups: Vec<TimeRollup> = rollups.into_values().collect();
+  rollups.sort_by(|a, b| {
+    (b.tracked_minutes + b.pomodoro_minutes)
+      .cmp(&(a.tracked_minutes + a.pomodoro_minutes))
+      .then_with(|| a.label.cmp(&b.label))
+  });
+  rollups
+}
+
+impl Store {
+  /// Start a timer on `task_id`. Fails while another timer is running.
+  pub fn start_timer(&self, task_id: &str, note: Option<String>) -> Result<TimeEntry> {
+    self.transaction(|tx| {
+      let task =
+        read_task(tx, task_id)?.ok_or_else(|| Error::NotFound("task", task_id.to_string()))?;
+      if let Some(running) = running_entry(tx)? {
+        let title = read_task(tx, &running.task_id)?.map_or(running.task_id, |t| t.title);
+        return Err(Error::InvalidInput(format!(
+          "a timer is already running on \"{title}\""
+        )));
+      }
+      let now = now_iso();
+      let entry = TimeEntry {
+        id: new_id(),
+        task_id: task.id,
+        started_at: now.clone(),
+        ended_at: None,
+        source: EntrySource::Timer,
+        note,
+        created_at: now.clone(),
+        updated_at: now,
+      };
+      write_entry(tx, &entry)?;
+      Ok(entry)
+    })
+  }
+
+  /// Stop the running timer, if any.
+  pub fn stop_timer(&self) -> Result<Option<TimeEntry>> {
+    let conn = self.conn();
+    let Some(mut entry) = running_entry(&conn)? else {
+      return Ok(None);
+    };
+    let now = now_iso();
+    entry.ended_at = Some(now.clone());
+    entry.updated_at = now;
+    write_entry(&conn, &entry)?;
+    Ok(Some(entry))
+  }
+
+  pub fn running_timer(&self) -> Result<Option<TimeEntry>> {
+    running_entry(&self.conn())
+  }
+
+  /// Log a finished span by hand.
+  pub fn add_time_entry(&self, input: TimeEntry) -> Result<TimeEntry> {
+    let now = now_iso();
+    let entry = TimeEntry {
+      id: new_id(),
+      source: EntrySource::Manual,
+      created_at: now.clone(),
+      updated_at: now,
+      ..input
+    };
+    validate(&entry)?;
+    let conn = self.conn();
+    if read_task(&conn, &entry.task_id)?.is_none() {
+      return Err(Error::NotFound("task", entry.task_id));
+    }
+    write_entry(&conn, &entry)?;
+    Ok(entry)
+  }
+
+  pub fn update_time_entry(
+    &self,
+    id: &str,
+    updates: &Map<String, Value>,
+  ) -> Result<Option<TimeEntry>> {
+    let conn = self.conn();
+    let Some(existing) = read_entry(&conn, id)? else {
+      return Ok(None);
+    };
+    let mut entry: TimeEntry = merge_patch(&existing, updates)?;
+    entry.id = existing.id;
+    entry.source = existing.source;
+    entry.created_at = existing.created_at;
+    entry.updated_at = now_iso();
+    validate(&entry)?;
+    if existing.ended_at.is_some() && entry.ended_at.is_none() {
+      return Err(Error::InvalidInput(
+        "a stopped timer cannot be reopened".into(),
+      ));
+    }
+    write_entry(&conn, &entry)?;
+    Ok(Some(entry))
+  }
+
+  pub fn delete_time_entry(&self, id: &str) -> Result<bool> {
+    let removed = self
+      .conn()
+      .execute("delete from time_entries where id = ?1", [id])?;
+    Ok(removed > 0)
+  }
+
+  /// Entries of one task, oldest first, including its focus pomodoros.
+  pub fn get_time_entries(&self, task_id: &str) -> Result<Vec<TimeEntry>> {
+    let conn = self.conn();
+    let mut stmt =
+      conn.prepare("select * from time_entries where task_id = ?1 order by started_at")?;
+    let mut entries = stmt
+      .query_map([task_id], entry_from_row)?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    entries.extend(
+      pomodoro_entries(&conn)?
+        .into_iter()
+        .filter(|e| e.task_id == task_id),
+    );
+    entries.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+    Ok(entries)
+  }
+
+  /// Finished entries, pomodoros included, that started in `[from, to)`.
+  pub fn time_entries_between(
+    &self,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+  ) -> Result<Vec<TimeEntry>> {
+    let conn = self.conn();
+    let mut stmt = conn.prepare("select * from time_entries where ended_at is not null")?;
+    let mut entries = stmt
+      .query_map([], entry_from_row)?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    entries.extend(pomodoro_entries(&conn)?);
+    entries.retain(|e| parse_time(&e.started_at).is_some_and(|t| t >= from && t < to));
+    entries.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+    Ok(entries)
+  }
+
+  /// Tracked against estimated minutes per task, project and tag, over
+  /// tasks that are not in the trash. A running timer counts up to `now`.
+  pub fn time_summary(&self, now: DateTime<Utc>) -> Result<TimeSummary> {
+    let conn = self.conn();
+    let mut per_task: HashMap<String, TimeRollup> = HashMap::new();
+    let mut tasks = conn.prepare(
+      "select tasks.id, tasks.title, tasks.project, tasks.estimate_minutes, projects.name \
+       from tasks left join projects on projects.id = tasks.project where tasks.deleted = 0",
+    )?;
+    let rows = tasks
+      .query_map([], |row| {
+        Ok((
+          row.get::<_, String>(0)?,
+          row.get::<_, String>(1)?,
+          row.get::<_, Option<String>>(2)?,
+          row.get::<_, Option<i64>>(3)?,
+          row.get::<_, Option<String>>(4)?,
+        ))
+      })?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    let mut projects_of: HashMap<String, (String, String)> = HashMap::new();
+    for (id, title, project, estimate, project_name) in rows {
+      let project_key = project.clone().unwrap_or_default();
+      let project_label = project_name
+        .or(project)
+        .unwrap_or_else(|| crate::analytics::NO_PROJECT.to_string());
+      projects_of.insert(id.clone(), (project_key, project_label));
+      per_task.insert(
+        id.clone(),
+        TimeRollup {
+          key: id,
+          label: title,
+          estimated_minutes: estimate.unwrap_or(0),
+          ..Default::default()
+        },
+      );
+    }
+
+    let mut stmt = conn.prepare("select * from time_entries")?;
+    let entries = stmt
+      .query_map([], entry_from_row)?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    for entry in entries.iter().chain(pomodoro_entries(&conn)?.iter()) {
+      let Some(task) = per_task.get_mut(&entry.task_id) else {
+        continue;
+      };
+      match entry.source {
+        EntrySource::Pomodoro => task.pomodoro_minutes += entry.minutes(now),
+        _ => task.tracked_minutes += entry.minutes(now),
+      }
+    }
+
+    let mut tag_stmt = conn.prepare(
+      "select task_tags.task_id, tags.id, tags.name from task_tags \
+       join tags on tags.id = task_tags.tag_id",
+    )?;
+    let task_tags = tag_stmt
+      .query_map([], |row| {
+        Ok((
+          row.get::<_, String>(0)?,
+          row.get::<_, String>(1)?,
+          row.get::<_, String>(2)?,
+        ))
+      })?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    per_task.retain(|_, t| t.tracked_minutes + t.pomodoro_minutes + t.estimated_minutes > 0);
+    let mut projects: HashMap<String, TimeRollup> = HashMap::new();
+    for (id, task) in &per_task {
+      let (key, label) = projects_of[id].clone();
+      projects
+        .entry(key.clone())
+        .or_insert_with(|| TimeRollup {
+          key,
+          label,
+          ..Default::default()
+        })
+        .add(task);
+    }
+    let mut tags: HashMap<String, TimeRollup> = HashMap::new();
+    for (task_id, tag_id, name) in task_tags {
+      let Some(task) = per_task.get(&task_id) else {
+        continue;
+      };
+      tags
+        .entry(tag_id.clone())
+        .or_insert_with(|| TimeRollup {
+          key: tag_id,
+          label: name,
+          ..Default::default()
+        })
+        .add(task);
+    }
+
+    Ok(TimeSummary {
+      tasks: sorted(per_task),
+      projects: sorted(projects),
+      tags: sorted(tags),
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::storage::models::{PomodoroSession, Project, Task};
+  use serde_json::json;
+
+  fn manual(task_id: &str, start: &str, end: &str) -> TimeEntry {
+    TimeEntry {
+      task_id: task_id.into(),
+      started_at: start.into(),
+      ended_at: Some(end.into()),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn one_timer_at_a_time_and_manual_entries_validate() {
+    let store = Store::open_in_memory().unwrap();
+    let add = |title: &str| {
+      store
+        .create_task(Task {
+          title: title.into(),
+          ..Default::default()
+        })
+        .unwrap()
+    };
+    let write = add("write");
+    let review = add("review");
+
+    let running = store.start_timer(&write.id, None).unwrap();
+    let err = store.start_timer(&review.id, None).unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "invalid input: a timer is already running on \"write\""
+    );
+    assert_eq!(store.running_timer().unwrap().unwrap().id, running.id);
+    let stopped = store.stop_timer().unwrap().unwrap();
+    assert!(stopped.ended_at.is_some());
+    assert!(store.stop_timer().unwrap().is_none());
+    store.start_timer(&review.id, None).unwrap();
+
+    assert!(store
+      .add_time_entry(manual(
+        &write.id,
+        "2026-10-01T10:00:00Z",
+        "2026-10-01T09:00:00Z"
+      ))
+      .is_err());
+    assert!(store
+      .add_time_entry(TimeEntry {
+        ended_at: None,
+        ..manual(&write.id, "2026-10-01T10:00:00Z", "")
+      })
+      .is_err());
+    assert!(store
+      .add_time_entry(manual(
+        "missing",
+        "2026-10-01T09:00:00Z",
+        "2026-10-01T10:00:00Z"
+      ))
+      .is_err());
+
+    let entry = store
+      .add_time_entry(manual(
+        &write.id,
+        "2026-10-01T09:00:00Z",
+        "2026-10-01T10:30:00Z",
+      ))
+      .unwrap();
+    assert_eq!(entry.source, EntrySource::Manual);
+    let patch = json!({ "ended_at": "2026-10-01T10:00:00Z", "note": "draft" });
+    let updated = store
+      .update_time_entry(&entry.id, patch.as_object().unwrap())
+      .unwrap()
+      .unwrap();
+    assert_eq!(updated.minutes(Utc::now()), 60);
+    let reopen = json!({ "ended_at": null });
+    assert!(store
+      .update_time_entry(&stopped.id, reopen.as_object().unwrap())
+      .is_err());
+    assert!(store.delete_time_entry(&entry.id).unwrap());
+  }
+
+  #[test]
+  fn rolls_up_tracked_pomodoro_and_estimated_minutes() {
+    let store = Store::open_in_memory().unwrap();
+    let work = store
+      .create_project(Project {
+        name: "Work".into(),
+        ..Default::default()
+      })
+      .unwrap();
+    let report = store
+      .create_task(Task {
+        title: "report".into(),
+        project: Some(work.id.clone()),
+        estimate_minutes: Some(120),
+        ..Default::default()
+      })
+      .unwrap();
+    let chores = store
+      .create_task(Task {
+        title: "chores".into(),
+        estimate_minutes: Some(30),
+        ..Default::default()
+      })
+      .unwrap();
+    let tag = store.create_tag("deep", None).unwrap();
+    store.attach_tag_to_task(&report.id, &tag.id).unwrap();
+
+    store
+      .add_time_entry(manual(
+        &report.id,
+        "2026-10-01T09:00:00Z",
+        "2026-10-01T10:00:00Z",
+      ))
+      .unwrap();
+    store
+      .add_time_entry(manual(
+        &chores.id,
+        "2026-10-02T09:00:00Z",
+        "2026-10-02T09:15:00Z",
+      ))
+      .unwrap();
+    let session = store
+      .create_pomodoro_session(PomodoroSession {
+        task_id: Some(report.id.clone()),
+        duration: 25,
+        kind: "work".into(),
+        started_at: "2026-10-01T11:00:00Z".into(),
+        ..Default::default()
+      })
+      .unwrap();
+    let done = json!({ "completed_at": "2026-10-01T11:25:00Z" });
+    store
+      .update_pomodoro_session(&session.id, done.as_object().unwrap())
+      .unwrap();
+
+    let summary = store.time_summary(Utc::now()).unwrap();
+    let first = &summary.tasks[0];
+    assert_eq!(first.label, "report");
+    assert_eq!(
+      (
+        first.tracked_minutes,
+        first.pomodoro_minutes,
+        first.estimated_minutes
+      ),
+      (60, 25, 120)
+    );
+    assert_eq!(summary.projects[0].label, "Work");
+    assert_eq!(summary.projects[1].key, "");
+    assert_eq!(summary.projects[1].estimated_minutes, 30);
+    assert_eq!(summary.tags.len(), 1);
+    assert_eq!(summary.tags[0].tracked_minutes, 60);
+
+    assert_eq!(store.get_time_entries(&report.id).unwrap().len(), 2);
+    let from = "2026-10-01T00:00:00Z".parse().unwrap();
+    let to = "2026-10-02T00:00:00Z".parse().unwrap();
+    let day = store.time_entries_between(from, to).unwrap();
+    assert_eq!(
+      day.iter().map(|e| e.source).collect::<Vec<_>>(),
+      [EntrySource::Manual, EntrySource::Pomodoro]
+    );
+  }
+}
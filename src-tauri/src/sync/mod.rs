@@ -0,0 +1,1852 @@
+//! Offline-first sync between the local store and Supabase.
+//!
+//! Every write lands in SQLite first: on desktop the webview reads and
+//! writes only the local store, in both storage modes, and this engine is
+//! the only thing that talks to Supabase. While sync is enabled, triggers
+//! log each change to the synced tables in `sync_outbox`, so writes made
+//! offline (or by the CLI, or over CalDAV) survive restarts until they are
+//! pushed. A sync run:
+//!
+//! 1. pulls remote rows changed since the table's `(cursor, id)` position,
+//!    the cursor being `updated_at`, or `created_at` for the insert-only
+//!    history tables (`tags` and `task_tags` have neither and are pulled
+//!    whole); every [`LIST_IDS_EVERY`] it also lists the remote ids, to
+//!    carry hard deletions;
+//! 2. settles rows changed on both sides: tasks merge field by field through
+//!    their [`crate::crdt`] state, kept remotely in a `crdt` column, and
+//!    other rows by their cursor, the newer write winning; either way the
+//!    row is reported as a [`SyncConflict`];
+//! 3. pushes the latest state of every row still marked `push` in the
+//!    outbox, upserting parents before children and deleting children first.
+//!
+//! Rows applied from the remote are logged unmarked, so they are not pushed
+//! back; LAN peers ([`crate::peer`]) read the same log, and a change stays in
+//! it until Supabase and every peer have it.
+
+pub mod postgrest;
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use rusqlite::types::{Value as SqlValue, ValueRef};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::crdt::{self, Lww, Stamp, TaskState};
+use crate::error::{Error, Result};
+use crate::storage::models::Task;
+use crate::storage::{now_iso, search, Store, OFFLINE_USER_ID};
+use postgrest::Postgrest;
+
+/// Rows per pull request.
+const PAGE: usize = 500;
+/// Rows per upsert request.
+const PUSH_BATCH: usize = 200;
+/// How often the remote ids of an incremental table are listed to find
+/// rows deleted there.
+const LIST_IDS_EVERY: TimeDelta = TimeDelta::hours(6);
+
+/// Where to sync, from the webview's Supabase session.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncConfig {
+  /// Project URL, e.g. `https://xyz.supabase.co`.
+  pub url: String,
+  pub anon_key: String,
+  /// The signed-in user's JWT; row level security scopes every request.
+  pub access_token: String,
+  /// Written as `user_id` on pushed rows created on this device.
+  pub user_id: String,
+}
+
+/// A synced table.
+pub(crate) struct SyncTable {
+  name: &'static str,
+  /// Primary key columns; a row id joins their values with `/`.
+  key: &'static [&'static str],
+  /// Columns shared with the Supabase table.
+  columns: &'static [&'static str],
+  /// Stored as 0/1 locally, booleans remotely.
+  booleans: &'static [&'static str],
+  /// Stored as text locally, `jsonb` remotely.
+  json: &'static [&'static str],
+  /// Column pulled incrementally by, together with `id`; tables without
+  /// one are pulled as a snapshot.
+  cursor: Option<&'static str>,
+  /// Has a `user_id` column, filled in on push for rows without an owner.
+  owned: bool,
+  /// Reshapes a local row for Supabase, and a pulled row back, where the
+  /// two schemas differ.
+  to_remote: fn(&mut Map<String, Value>),
+  from_remote: fn(&mut Map<String, Value>),
+}
+
+fn as_is(_: &mut Map<String, Value>) {}
+
+fn rename(row: &mut Map<String, Value>, from: &str, to: &str) {
+  if let Some(value) = row.remove(from) {
+    row.insert(to.into(), value);
+  }
+}
+
+/// Supabase keeps `start_time`/`end_time` with a `completed` flag and
+/// calls focus sessions `focus`.
+fn pomodoro_to_remote(row: &mut Map<String, Value>) {
+  rename(row, "started_at", "start_time");
+  let completed = row.remove("completed_at").unwrap_or(Value::Null);
+  let cancelled = row.get("cancelled_at").cloned().unwrap_or(Value::Null);
+  row.insert("completed".into(), Value::Bool(!completed.is_null()));
+  row.insert(
+    "end_time".into(),
+    if completed.is_null() {
+      cancelled
+    } else {
+      completed
+    },
+  );
+  if row.get("type").and_then(Value::as_str) == Some("work") {
+    row.insert("type".into(), "focus".into());
+  }
+}
+
+fn pomodoro_from_remote(row: &mut Map<String, Value>) {
+  rename(row, "start_time", "started_at");
+  let end = row.remove("end_time").unwrap_or(Value::Null);
+  if row.remove("completed") == Some(Value::Bool(true)) {
+    row.insert("completed_at".into(), end);
+  } else {
+    row.insert("completed_at".into(), Value::Null);
+    // Sessions ended on the web without finishing only have `end_time`.
+    if row.get("cancelled_at").map_or(true, Value::is_null) {
+      row.insert("cancelled_at".into(), end);
+    }
+  }
+  if row.get("type").and_then(Value::as_str) == Some("focus") {
+    row.insert("type".into(), "work".into());
+  }
+}
+
+/// Parents first: the order to upsert in, reversed to delete.
+const TABLES: [SyncTable; 7] = [
+  SyncTable {
+    name: "projects",
+    key: &["id"],
+    columns: &[
+      "id",
+      "name",
+      "icon",
+      "color",
+      "view_type",
+      "created_at",
+      "updated_at",
+      "sort_order",
+      "user_id",
+      "is_shared",
+      "original_owner_id",
+    ],
+    booleans: &["is_shared"],
+    json: &[],
+    cursor: Some("updated_at"),
+    owned: true,
+    to_remote: as_is,
+    from_remote: as_is,
+  },
+  SyncTable {
+    name: "tags",
+    key: &["id"],
+    columns: &["id", "name", "user_id", "project_id", "created_at"],
+    booleans: &[],
+    json: &[],
+    cursor: None,
+    owned: true,
+    to_remote: as_is,
+    from_remote: as_is,
+  },
+  SyncTable {
+    name: "tasks",
+    key: &["id"],
+    columns: &[
+      "id",
+      "title",
+      "completed",
+      "date",
+      "project",
+      "description",
+      "icon",
+      "completed_at",
+      "created_at",
+      "updated_at",
+      "user_id",
+      "sort_order",
+      "deleted",
+      "deleted_at",
+      "abandoned",
+      "abandoned_at",
+      "flagged",
+      "attachments",
+      "parent_id",
+      "estimate_minutes",
+    ],
+    booleans: &["completed", "deleted", "abandoned", "flagged"],
+    json: &["attachments"],
+    cursor: Some("updated_at"),
+    owned: true,
+    to_remote: as_is,
+    from_remote: as_is,
+  },
+  SyncTable {
+    name: "task_tags",
+    key: &["task_id", "tag_id"],
+    columns: &["task_id", "tag_id", "created_at"],
+    booleans: &[],
+    json: &[],
+    cursor: None,
+    owned: false,
+    to_remote: as_is,
+    from_remote: as_is,
+  },
+  SyncTable {
+    name: "pomodoro_sessions",
+    key: &["id"],
+    columns: &[
+      "id",
+      "task_id",
+      "user_id",
+      "duration",
+      "type",
+      "started_at",
+      "completed_at",
+      "cancelled_at",
+      "created_at",
+      "updated_at",
+      "notes",
+      "title",
+    ],
+    booleans: &[],
+    json: &[],
+    cursor: Some("updated_at"),
+    owned: true,
+    to_remote: pomodoro_to_remote,
+    from_remote: pomodoro_from_remote,
+  },
+  SyncTable {
+    name: "task_activities",
+    key: &["id"],
+    columns: &[
+      "id",
+      "task_id",
+      "user_id",
+      "action",
+      "metadata",
+      "created_at",
+    ],
+    booleans: &[],
+    json: &["metadata"],
+    cursor: Some("created_at"),
+    owned: true,
+    to_remote: as_is,
+    from_remote: as_is,
+  },
+  SyncTable {
+    name: "checkin_records",
+    key: &["id"],
+    columns: &["id", "user_id", "check_in_time", "note", "created_at"],
+    booleans: &[],
+    json: &[],
+    cursor: Some("created_at"),
+    owned: true,
+    to_remote: as_is,
+    from_remote: as_is,
+  },
+];
+
+/// One logged local change.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncChange {
+  pub seq: i64,
+  pub table: String,
+  pub row_id: String,
+  /// `upsert` or `delete`.
+  pub op: String,
+  pub changed_at: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+  Local,
+  Remote,
+  /// Both sides' edits were kept, field by field.
+  Merged,
+}
+
+/// A row changed both locally and remotely since the last sync.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncConflict {
+  pub table: String,
+  pub row_id: String,
+  pub local_updated_at: Option<String>,
+  pub remote_updated_at: Option<String>,
+  /// Whose version was kept.
+  pub winner: Side,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncReport {
+  /// Remote rows written locally.
+  pub pulled: usize,
+  /// Local rows upserted or deleted remotely.
+  pub pushed: usize,
+  pub conflicts: Vec<SyncConflict>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncStatus {
+  pub enabled: bool,
+  /// Rows waiting in the outbox.
+  pub pending: usize,
+  pub last_synced_at: Option<String>,
+  pub last_error: Option<String>,
+}
+
+/// Pull position of an incremental table: `(cursor, id)` of the last row
+/// pulled, in the order rows are pulled in.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Watermark {
+  at: Option<String>,
+  /// Missing from positions saved before the id was part of the cursor.
+  #[serde(default)]
+  id: Option<String>,
+}
+
+impl Watermark {
+  /// PostgREST filter for the rows after this position by `cursor`.
+  fn filter(&self, cursor: &'static str) -> Option<(&'static str, String)> {
+    let at = self.at.as_deref()?;
+    Some(match self.id.as_deref() {
+      Some(id) => (
+        "or",
+        format!("({cursor}.gt.\"{at}\",and({cursor}.eq.\"{at}\",id.gt.\"{id}\"))"),
+      ),
+      None => (cursor, format!("gte.{at}")),
+    })
+  }
+}
+
+/// The synced table called `name`.
+pub(crate) fn table(name: &str) -> Option<&'static SyncTable> {
+  TABLES.iter().find(|t| t.name == name)
+}
+
+fn row_id(table: &SyncTable, row: &Map<String, Value>) -> Option<String> {
+  let parts: Option<Vec<&str>> = table
+    .key
+    .iter()
+    .map(|k| row.get(*k).and_then(Value::as_str))
+    .collect();
+  Some(parts?.join("/"))
+}
+
+/// `(column, "eq.<value>")` filters selecting `row_id`.
+fn key_filters(table: &SyncTable, row_id: &str) -> Vec<(&'static str, String)> {
+  table
+    .key
+    .iter()
+    .zip(row_id.splitn(table.key.len(), '/'))
+    .map(|(k, v)| (*k, format!("eq.{v}")))
+    .collect()
+}
+
+fn key_clause(table: &SyncTable) -> String {
+  table
+    .key
+    .iter()
+    .enumerate()
+    .map(|(i, k)| format!("{k} = ?{}", i + 1))
+    .collect::<Vec<_>>()
+    .join(" and ")
+}
+
+/// Whether timestamp `a` is later than `b`; a missing one is oldest.
+pub(crate) fn later(a: Option<&str>, b: Option<&str>) -> bool {
+  match (a, b) {
+    (Some(a), Some(b)) => match (
+      DateTime::parse_from_rfc3339(a),
+      DateTime::parse_from_rfc3339(b),
+    ) {
+      (Ok(a), Ok(b)) => a > b,
+      _ => a > b,
+    },
+    (Some(_), None) => true,
+    (None, _) => false,
+  }
+}
+
+fn state(conn: &Connection, key: &str) -> Result<Option<String>> {
+  Ok(
+    conn
+      .query_row(
+        "select value from sync_state where key = ?1",
+        [key],
+        |row| row.get(0),
+      )
+      .optional()?,
+  )
+}
+
+fn set_state(conn: &Connection, key: &str, value: Option<&str>) -> Result<()> {
+  match value {
+    Some(value) => conn.execute(
+      "insert or replace into sync_state (key, value) values (?1, ?2)",
+      params![key, value],
+    )?,
+    None => conn.execute("delete from sync_state where key = ?1", [key])?,
+  };
+  Ok(())
+}
+
+fn is_pending(conn: &Connection, table: &str, row_id: &str) -> Result<bool> {
+  Ok(
+    conn
+      .query_row(
+        "select 1 from sync_outbox where table_name = ?1 and row_id = ?2 and push = 1",
+        params![table, row_id],
+        |_| Ok(()),
+      )
+      .optional()?
+      .is_some(),
+  )
+}
+
+/// When the row was deleted locally, if that is its pending change.
+fn deleted_at(conn: &Connection, table: &str, row_id: &str) -> Result<Option<String>> {
+  Ok(
+    conn
+      .query_row(
+        "select changed_at from sync_outbox
+         where table_name = ?1 and row_id = ?2 and op = 'delete' and push = 1
+         order by seq desc limit 1",
+        params![table, row_id],
+        |row| row.get(0),
+      )
+      .optional()?,
+  )
+}
+
+fn forget_pending(conn: &Connection, table: &str, row_id: &str) -> Result<()> {
+  conn.execute(
+    "update sync_outbox set push = 0 where table_name = ?1 and row_id = ?2",
+    params![table, row_id],
+  )?;
+  Ok(())
+}
+
+fn changes(conn: &Connection, filter: &str, after: i64) -> Result<Vec<SyncChange>> {
+  let mut stmt = conn.prepare(&format!(
+    "select seq, table_name, row_id, op, changed_at from sync_outbox
+     where {filter} order by seq"
+  ))?;
+  let changes = stmt
+    .query_map([after], |row| {
+      Ok(SyncChange {
+        seq: row.get(0)?,
+        table: row.get(1)?,
+        row_id: row.get(2)?,
+        op: row.get(3)?,
+        changed_at: row.get(4)?,
+      })
+    })?
+    .collect::<rusqlite::Result<_>>()?;
+  Ok(changes)
+}
+
+/// Drop the changes every consumer has: pushed (or not for Supabase) and
+/// sent to every peer. A peer that has never synced holds back the log
+/// from pairing on, so its first sync still sees the deletions.
+pub(crate) fn prune_outbox(conn: &Connection) -> Result<()> {
+  conn.execute(
+    "delete from sync_outbox where push = 0 and seq <= coalesce(
+       (select min(coalesce(sent_seq, 0)) from peers),
+       (select max(seq) from sync_outbox)
+     )",
+    [],
+  )?;
+  Ok(())
+}
+
+/// Whether two column values are the same. Postgres and the app format
+/// the same instant differently, and numbers may lose their fraction.
+fn same_value(a: &Value, b: &Value) -> bool {
+  match (a, b) {
+    (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+    (Value::String(a), Value::String(b)) if a != b => matches!(
+      (DateTime::parse_from_rfc3339(a), DateTime::parse_from_rfc3339(b)),
+      (Ok(a), Ok(b)) if a == b
+    ),
+    _ => a == b,
+  }
+}
+
+/// Whether `local` already holds `remote`'s values. `user_id` is ignored:
+/// rows written offline keep the offline user locally.
+pub(crate) fn same_row(
+  table: &SyncTable,
+  local: &Map<String, Value>,
+  remote: &Map<String, Value>,
+) -> bool {
+  table.columns.iter().filter(|c| **c != "user_id").all(|c| {
+    same_value(
+      local.get(*c).unwrap_or(&Value::Null),
+      remote.get(*c).unwrap_or(&Value::Null),
+    )
+  })
+}
+
+/// A remote task as a mergeable state. Columns still holding the value
+/// recorded in its `crdt` column keep their clocks; the rest were changed
+/// by a client that keeps none (the web app) and count as written at the
+/// row's `updated_at`.
+fn remote_state(id: &str, row: &Map<String, Value>) -> Result<TaskState> {
+  let recorded: Option<TaskState> = row
+    .get("crdt")
+    .filter(|v| !v.is_null())
+    .and_then(|v| serde_json::from_value(v.clone()).ok());
+  // Through `Task`, so values read the way `crdt::read_state` reads them.
+  let present: Map<String, Value> = row
+    .iter()
+    .filter(|(k, v)| !v.is_null() && *k != "crdt")
+    .map(|(k, v)| (k.clone(), v.clone()))
+    .collect();
+  let task: Task = serde_json::from_value(Value::Object(present))
+    .map_err(|e| Error::InvalidInput(format!("task {id}: {e}")))?;
+  let Value::Object(values) = serde_json::to_value(&task)? else {
+    unreachable!("a task serializes to an object");
+  };
+  let edited = Stamp {
+    at: task
+      .updated_at
+      .as_deref()
+      .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+      .map_or(0, |at| at.timestamp_millis()),
+    replica: "supabase".into(),
+  };
+  let fields = crdt::FIELDS
+    .iter()
+    .map(|field| {
+      let value = values.get(*field).cloned().unwrap_or(Value::Null);
+      let register = recorded
+        .as_ref()
+        .and_then(|r| r.fields.get(*field))
+        .filter(|r| same_value(&r.value, &value))
+        .cloned()
+        .unwrap_or(Lww {
+          value,
+          stamp: edited.clone(),
+        });
+      (field.to_string(), register)
+    })
+    .collect();
+  Ok(TaskState {
+    id: id.to_string(),
+    fields,
+    tags: recorded.map(|r| r.tags).unwrap_or_default(),
+  })
+}
+
+/// Merge a remote task into the local one. The result is pushed back
+/// unless the remote already holds it; a merge that took something from
+/// both sides' unpushed edits is reported.
+fn merge_task(
+  conn: &Connection,
+  id: &str,
+  row: &Map<String, Value>,
+  report: &mut SyncReport,
+) -> Result<()> {
+  let remote = remote_state(id, row)?;
+  let local_at = crdt::read_state(conn, id)?.and_then(|s| {
+    s.fields
+      .get("updated_at")
+      .and_then(|r| r.value.as_str().map(str::to_string))
+  });
+  let pending = is_pending(conn, "tasks", id)?;
+  let changed = crdt::merge_state(conn, &remote)?.is_some_and(|(_, changed)| changed);
+  // The owner is not merged; keep the one Supabase has.
+  conn.execute(
+    "update tasks set user_id = ?2 where id = ?1 and user_id is not ?2",
+    params![id, row.get("user_id").and_then(Value::as_str)],
+  )?;
+  let merged = crdt::read_state(conn, id)?;
+  if merged.as_ref() == Some(&remote) {
+    forget_pending(conn, "tasks", id)?;
+  } else if !pending {
+    conn.execute(
+      "insert into sync_outbox (table_name, row_id, op, changed_at) values ('tasks', ?1, 'upsert', ?2)",
+      params![id, now_iso()],
+    )?;
+  }
+  if changed {
+    report.pulled += 1;
+    if pending {
+      report.conflicts.push(SyncConflict {
+        table: "tasks".into(),
+        row_id: id.to_string(),
+        local_updated_at: local_at,
+        remote_updated_at: row
+          .get("updated_at")
+          .and_then(Value::as_str)
+          .map(str::to_string),
+        winner: if merged.as_ref() == Some(&remote) {
+          Side::Remote
+        } else {
+          Side::Merged
+        },
+      });
+    }
+  }
+  Ok(())
+}
+
+/// The local row as Supabase expects it, `None` if it no longer exists.
+pub(crate) fn read_row(
+  conn: &Connection,
+  table: &SyncTable,
+  row_id: &str,
+) -> Result<Option<Map<String, Value>>> {
+  let sql = format!(
+    "select {} from {} where {}",
+    table.columns.join(", "),
+    table.name,
+    key_clause(table)
+  );
+  let keys: Vec<&str> = row_id.splitn(table.key.len(), '/').collect();
+  let row = conn
+    .query_row(&sql, params_from_iter(keys), |row| {
+      let mut map = Map::new();
+      for (i, column) in table.columns.iter().enumerate() {
+        let value = match row.get_ref(i)? {
+          ValueRef::Null => Value::Null,
+          ValueRef::Integer(n) if table.booleans.contains(column) => Value::Bool(n != 0),
+          ValueRef::Integer(n) => n.into(),
+          ValueRef::Real(f) => f.into(),
+          ValueRef::Text(t) => {
+            let text = String::from_utf8_lossy(t).into_owned();
+            if table.json.contains(column) {
+              serde_json::from_str(&text).unwrap_or(Value::String(text))
+            } else {
+              Value::String(text)
+            }
+          }
+          ValueRef::Blob(_) => Value::Null,
+        };
+        map.insert(column.to_string(), value);
+      }
+      Ok(map)
+    })
+    .optional()?;
+  Ok(row)
+}
+
+/// Insert or update a remote row locally, leaving local-only columns alone.
+pub(crate) fn write_row(
+  conn: &Connection,
+  table: &SyncTable,
+  row: &Map<String, Value>,
+) -> Result<()> {
+  let columns: Vec<&str> = table
+    .columns
+    .iter()
+    .copied()
+    .filter(|c| row.contains_key(*c))
+    .collect();
+  let values: Vec<SqlValue> = columns
+    .iter()
+    .map(|c| match &row[*c] {
+      Value::Null => SqlValue::Null,
+      Value::Bool(b) => SqlValue::Integer(i64::from(*b)),
+      Value::Number(n) => n
+        .as_i64()
+        .map(SqlValue::Integer)
+        .or_else(|| n.as_f64().map(SqlValue::Real))
+        .unwrap_or(SqlValue::Null),
+      Value::String(s) => SqlValue::Text(s.clone()),
+      other => SqlValue::Text(other.to_string()),
+    })
+    .collect();
+  let updates: Vec<String> = columns
+    .iter()
+    .filter(|c| !table.key.contains(c))
+    .map(|c| format!("{c} = excluded.{c}"))
+    .collect();
+  let on_conflict = if updates.is_empty() {
+    "do nothing".to_string()
+  } else {
+    format!("do update set {}", updates.join(", "))
+  };
+  let placeholders: Vec<String> = (1..=columns.len()).map(|i| format!("?{i}")).collect();
+  conn.execute(
+    &format!(
+      "insert into {} ({}) values ({}) on conflict ({}) {on_conflict}",
+      table.name,
+      columns.join(", "),
+      placeholders.join(", "),
+      table.key.join(", ")
+    ),
+    params_from_iter(values),
+  )?;
+  Ok(())
+}
+
+pub(crate) fn delete_row(conn: &Connection, table: &SyncTable, row_id: &str) -> Result<()> {
+  let keys: Vec<&str> = row_id.splitn(table.key.len(), '/').collect();
+  conn.execute(
+    &format!("delete from {} where {}", table.name, key_clause(table)),
+    params_from_iter(keys),
+  )?;
+  Ok(())
+}
+
+fn local_ids(conn: &Connection, table: &SyncTable) -> Result<Vec<String>> {
+  let mut stmt = conn.prepare(&format!(
+    "select {} from {}",
+    table.key.join(" || '/' || "),
+    table.name
+  ))?;
+  let ids = stmt
+    .query_map([], |row| row.get(0))?
+    .collect::<rusqlite::Result<_>>()?;
+  Ok(ids)
+}
+
+/// A local tag that duplicates remote tag `row` under another id, which
+/// Supabase's unique index on the name would refuse on push.
+fn duplicate_tag(conn: &Connection, row: &Map<String, Value>) -> Result<Option<String>> {
+  Ok(
+    conn
+      .query_row(
+        "select id from tags
+         where name = ?1 and coalesce(project_id, '') = coalesce(?2, '') and id <> ?3",
+        params![
+          row.get("name").and_then(Value::as_str),
+          row.get("project_id").and_then(Value::as_str),
+          row.get("id").and_then(Value::as_str),
+        ],
+        |row| row.get(0),
+      )
+      .optional()?,
+  )
+}
+
+/// Move the links of local tag `from` onto `to` and drop `from`; the moved
+/// links are logged so they get pushed.
+fn merge_tag(conn: &Connection, from: &str, to: &str) -> Result<Vec<String>> {
+  let tasks: Vec<String> = {
+    let mut stmt = conn.prepare("select task_id from task_tags where tag_id = ?1")?;
+    let rows = stmt
+      .query_map([from], |row| row.get(0))?
+      .collect::<rusqlite::Result<_>>()?;
+    rows
+  };
+  for task_id in &tasks {
+    conn.execute(
+      "insert or ignore into task_tags (task_id, tag_id, created_at) values (?1, ?2, ?3)",
+      params![task_id, to, now_iso()],
+    )?;
+    conn.execute(
+      "insert into sync_outbox (table_name, row_id, op, changed_at) values ('task_tags', ?1, 'upsert', ?2)",
+      params![format!("{task_id}/{to}"), now_iso()],
+    )?;
+  }
+  conn.execute("delete from tags where id = ?1", [from])?;
+  forget_pending(conn, "tags", from)?;
+  Ok(tasks)
+}
+
+impl Store {
+  /// Start logging changes, queueing every existing row for the first
+  /// push. Returns `false` if sync was already enabled.
+  pub fn enable_sync(&self) -> Result<bool> {
+    self.transaction(|tx| {
+      if state(tx, "enabled")?.is_some() {
+        return Ok(false);
+      }
+      let now = now_iso();
+      for table in &TABLES {
+        tx.execute(
+          &format!(
+            "insert into sync_outbox (table_name, row_id, op, changed_at)
+             select ?1, {}, 'upsert', ?2 from {}",
+            table.key.join(" || '/' || "),
+            table.name
+          ),
+          params![table.name, now],
+        )?;
+      }
+      set_state(tx, "enabled", Some(&now))?;
+      Ok(true)
+    })
+  }
+
+  /// Stop logging and forget the pending pushes and watermarks; enabling
+  /// again starts over with a full push and pull.
+  pub fn disable_sync(&self) -> Result<()> {
+    let conn = self.conn();
+    conn.execute("update sync_outbox set push = 0", [])?;
+    conn.execute("delete from sync_state", [])?;
+    prune_outbox(&conn)
+  }
+
+  pub fn sync_status(&self) -> Result<SyncStatus> {
+    let conn = self.conn();
+    let pending: i64 = conn.query_row(
+      "select count(distinct table_name || ':' || row_id) from sync_outbox where push = 1",
+      [],
+      |row| row.get(0),
+    )?;
+    Ok(SyncStatus {
+      enabled: state(&conn, "enabled")?.is_some(),
+      pending: pending as usize,
+      last_synced_at: state(&conn, "last_synced_at")?,
+      last_error: state(&conn, "last_error")?,
+    })
+  }
+
+  /// Logged changes after `seq`, oldest first, whoever they are for.
+  pub fn sync_changes(&self, after: i64) -> Result<Vec<SyncChange>> {
+    changes(&self.conn(), "seq > ?1", after)
+  }
+
+  /// Pull, settle conflicts, then push. The outcome is kept for
+  /// [`Store::sync_status`].
+  pub fn sync(&self, config: &SyncConfig) -> Result<SyncReport> {
+    self.enable_sync()?;
+    let remote = Postgrest::new(config);
+    let mut report = SyncReport::default();
+    let result = self
+      .pull(&remote, &mut report)
+      .and_then(|()| self.push(&remote, &config.user_id, &mut report));
+    let conn = self.conn();
+    match &result {
+      Ok(()) => {
+        set_state(&conn, "last_synced_at", Some(&now_iso()))?;
+        set_state(&conn, "last_error", None)?;
+      }
+      Err(e) => set_state(&conn, "last_error", Some(&e.to_string()))?,
+    }
+    result.map(|()| report)
+  }
+
+  fn pull(&self, remote: &Postgrest, report: &mut SyncReport) -> Result<()> {
+    for table in &TABLES {
+      match table.cursor {
+        Some(cursor) => self.pull_incremental(remote, table, cursor, report)?,
+        None => self.pull_snapshot(remote, table, report)?,
+      }
+    }
+    Ok(())
+  }
+
+  fn pull_incremental(
+    &self,
+    remote: &Postgrest,
+    table: &SyncTable,
+    cursor: &'static str,
+    report: &mut SyncReport,
+  ) -> Result<()> {
+    let key = format!("watermark:{}", table.name);
+    let mut watermark: Watermark = match state(&self.conn(), &key)? {
+      Some(saved) => serde_json::from_str(&saved)?,
+      None => Watermark::default(),
+    };
+    loop {
+      let mut query = vec![
+        ("select", "*".to_string()),
+        ("order", format!("{cursor}.asc,id.asc")),
+        ("limit", PAGE.to_string()),
+      ];
+      query.extend(watermark.filter(cursor));
+      let rows = self.select(remote, table, &query)?;
+      let refs: Vec<&Map<String, Value>> = rows.iter().collect();
+      self.apply(table, &refs, report)?;
+      if let Some((at, id)) = rows.iter().rev().find_map(|row| {
+        Some((
+          row.get(cursor).and_then(Value::as_str)?,
+          row_id(table, row)?,
+        ))
+      }) {
+        watermark = Watermark {
+          at: Some(at.to_string()),
+          id: Some(id),
+        };
+        set_state(
+          &self.conn(),
+          &key,
+          Some(&serde_json::to_string(&watermark)?),
+        )?;
+      }
+      if rows.len() < PAGE {
+        break;
+      }
+    }
+
+    // A hard delete leaves nothing behind to pull; compare the ids instead,
+    // now and then, as that lists the whole table.
+    let listed = format!("listed_at:{}", table.name);
+    let due = state(&self.conn(), &listed)?
+      .and_then(|at| DateTime::parse_from_rfc3339(&at).ok())
+      .map_or(true, |at| {
+        Utc::now() - at.with_timezone(&Utc) >= LIST_IDS_EVERY
+      });
+    if !due {
+      return Ok(());
+    }
+    let mut remote_ids = HashSet::new();
+    let mut after: Option<String> = None;
+    loop {
+      let mut query = vec![
+        ("select", table.key.join(",")),
+        ("order", "id.asc".to_string()),
+        ("limit", PAGE.to_string()),
+      ];
+      if let Some(after) = &after {
+        query.push(("id", format!("gt.{after}")));
+      }
+      let page = remote.select(table.name, &query)?;
+      after = page.last().and_then(|row| row_id(table, row));
+      let done = page.len() < PAGE;
+      remote_ids.extend(page.iter().filter_map(|row| row_id(table, row)));
+      if done {
+        break;
+      }
+    }
+    self.drop_missing(table, &remote_ids, report)?;
+    set_state(&self.conn(), &listed, Some(&now_iso()))
+  }
+
+  fn pull_snapshot(
+    &self,
+    remote: &Postgrest,
+    table: &SyncTable,
+    report: &mut SyncReport,
+  ) -> Result<()> {
+    let mut rows = Vec::new();
+    loop {
+      let page = self.select(
+        remote,
+        table,
+        &[
+          ("select", "*".to_string()),
+          (
+            "order",
+            table
+              .key
+              .iter()
+              .map(|k| format!("{k}.asc"))
+              .collect::<Vec<_>>()
+              .join(","),
+          ),
+          ("limit", PAGE.to_string()),
+          ("offset", rows.len().to_string()),
+        ],
+      )?;
+      let done = page.len() < PAGE;
+      rows.extend(page);
+      if done {
+        break;
+      }
+    }
+    let remote_ids: HashSet<String> = rows.iter().filter_map(|r| row_id(table, r)).collect();
+    let refs: Vec<&Map<String, Value>> = rows.iter().collect();
+    self.apply(table, &refs, report)?;
+    self.drop_missing(table, &remote_ids, report)
+  }
+
+  /// Rows gone remotely go locally too, unless changed here since.
+  fn drop_missing(
+    &self,
+    table: &SyncTable,
+    remote_ids: &HashSet<String>,
+    report: &mut SyncReport,
+  ) -> Result<()> {
+    let mut reindex = Vec::new();
+    self.transaction(|tx| {
+      set_state(tx, "applying_remote", Some("1"))?;
+      for id in local_ids(tx, table)? {
+        if remote_ids.contains(&id) || is_pending(tx, table.name, &id)? {
+          continue;
+        }
+        match table.name {
+          "tasks" => reindex.push(id.clone()),
+          "task_tags" => reindex.extend(id.split('/').next().map(str::to_string)),
+          "tags" => reindex.extend(search::tasks_tagged(tx, "id", &id)?),
+          _ => {}
+        }
+        delete_row(tx, table, &id)?;
+        report.pulled += 1;
+      }
+      for task_id in &reindex {
+        search::reindex_task(tx, task_id)?;
+      }
+      set_state(tx, "applying_remote", None)
+    })
+  }
+
+  /// Write remote `rows` of `table` locally, settling conflicts with rows
+  /// changed here since the last sync.
+  fn apply(
+    &self,
+    table: &SyncTable,
+    rows: &[&Map<String, Value>],
+    report: &mut SyncReport,
+  ) -> Result<()> {
+    if rows.is_empty() {
+      return Ok(());
+    }
+    self.transaction(|tx| {
+      set_state(tx, "applying_remote", Some("1"))?;
+      let mut reindex: Vec<String> = Vec::new();
+      for row in rows {
+        let Some(id) = row_id(table, row) else {
+          continue;
+        };
+        let local = read_row(tx, table, &id)?;
+        if table.name == "tasks" && local.is_some() {
+          merge_task(tx, &id, row, report)?;
+          continue;
+        }
+        if local
+          .as_ref()
+          .is_some_and(|local| same_row(table, local, row))
+        {
+          // Already here, e.g. our own push coming back.
+          forget_pending(tx, table.name, &id)?;
+          continue;
+        }
+        if table.name == "tasks" && crdt::tombstoned(tx, &id)? {
+          // Deleted here, which wins over whatever was edited elsewhere;
+          // the delete is pushed again to take the remote row down too.
+          if is_pending(tx, table.name, &id)? {
+            report.conflicts.push(SyncConflict {
+              table: table.name.to_string(),
+              row_id: id.clone(),
+              local_updated_at: deleted_at(tx, table.name, &id)?,
+              remote_updated_at: row
+                .get("updated_at")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+              winner: Side::Local,
+            });
+          } else {
+            tx.execute(
+              "insert into sync_outbox (table_name, row_id, op, changed_at) values ('tasks', ?1, 'delete', ?2)",
+              params![id, now_iso()],
+            )?;
+          }
+          continue;
+        }
+        if is_pending(tx, table.name, &id)? {
+          let Some(cursor) = table.cursor else {
+            // No timestamps to compare; the local change is pushed as is.
+            continue;
+          };
+          // A deleted row has no timestamp; the delete itself counts.
+          let local_at = match &local {
+            Some(local) => local
+              .get(cursor)
+              .and_then(Value::as_str)
+              .map(str::to_string),
+            None => deleted_at(tx, table.name, &id)?,
+          };
+          let remote_at = row.get(cursor).and_then(Value::as_str).map(str::to_string);
+          let winner = if later(local_at.as_deref(), remote_at.as_deref()) {
+            Side::Local
+          } else {
+            Side::Remote
+          };
+          report.conflicts.push(SyncConflict {
+            table: table.name.to_string(),
+            row_id: id.clone(),
+            local_updated_at: local_at,
+            remote_updated_at: remote_at,
+            winner,
+          });
+          if winner == Side::Local {
+            continue;
+          }
+          forget_pending(tx, table.name, &id)?;
+        }
+        match table.name {
+          "tags" => {
+            if let Some(local) = duplicate_tag(tx, row)? {
+              reindex.extend(merge_tag(tx, &local, &id)?);
+            }
+          }
+          "task_tags" => {
+            // Links to rows this device does not have (yet) are skipped.
+            let present: bool = tx.query_row(
+              "select exists (select 1 from tasks where id = ?1)
+                 and exists (select 1 from tags where id = ?2)",
+              params![
+                row.get("task_id").and_then(Value::as_str),
+                row.get("tag_id").and_then(Value::as_str)
+              ],
+              |r| r.get(0),
+            )?;
+            if !present {
+              continue;
+            }
+          }
+          _ => {}
+        }
+        if table.name == "tasks" {
+          crdt::merge_state(tx, &remote_state(&id, row)?)?;
+          tx.execute(
+            "update tasks set user_id = ?2 where id = ?1",
+            params![id, row.get("user_id").and_then(Value::as_str)],
+          )?;
+        } else {
+          write_row(tx, table, row)?;
+        }
+        report.pulled += 1;
+        match table.name {
+          "tasks" => reindex.push(id),
+          "task_tags" => reindex.extend(id.split('/').next().map(str::to_string)),
+          "tags" => reindex.extend(search::tasks_tagged(tx, "id", &id)?),
+          _ => {}
+        }
+      }
+      for task_id in &reindex {
+        search::reindex_task(tx, task_id)?;
+      }
+      set_state(tx, "applying_remote", None)
+    })
+  }
+
+  /// Remote rows of `table`, in the local shape.
+  fn select(
+    &self,
+    remote: &Postgrest,
+    table: &SyncTable,
+    query: &[(&str, String)],
+  ) -> Result<Vec<Map<String, Value>>> {
+    let mut rows = remote.select(table.name, query)?;
+    rows.iter_mut().for_each(table.from_remote);
+    Ok(rows)
+  }
+
+  fn push(&self, remote: &Postgrest, user_id: &str, report: &mut SyncReport) -> Result<()> {
+    let changes = changes(&self.conn(), "push = 1 and seq > ?1", 0)?;
+    let Some(last) = changes.last().map(|c| c.seq) else {
+      return Ok(());
+    };
+    // Only the latest state of each row matters.
+    let mut rows: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut seen = HashSet::new();
+    for change in changes.iter().rev() {
+      if seen.insert((change.table.as_str(), change.row_id.as_str())) {
+        rows
+          .entry(change.table.as_str())
+          .or_default()
+          .push(change.row_id.as_str());
+      }
+    }
+
+    let mut deletes: Vec<(&SyncTable, &str)> = Vec::new();
+    for table in &TABLES {
+      let mut upserts = Vec::new();
+      for id in rows.get(table.name).into_iter().flatten() {
+        let row = read_row(&self.conn(), table, id)?;
+        match row {
+          Some(mut row) => {
+            // Rows made on this device have no owner yet (or the offline
+            // one); the rest keep theirs, e.g. tasks in a list shared with us.
+            let unowned = row
+              .get("user_id")
+              .and_then(Value::as_str)
+              .map_or(true, |owner| owner.is_empty() || owner == OFFLINE_USER_ID);
+            if table.owned && unowned {
+              row.insert("user_id".into(), user_id.into());
+            }
+            if table.name == "tasks" {
+              let state = crdt::read_state(&self.conn(), id)?;
+              row.insert("crdt".into(), serde_json::to_value(state)?);
+            }
+            (table.to_remote)(&mut row);
+            upserts.push(Value::Object(row));
+          }
+          None => deletes.push((table, id)),
+        }
+      }
+      for batch in upserts.chunks(PUSH_BATCH) {
+        remote.upsert(table.name, &table.key.join(","), batch)?;
+        report.pushed += batch.len();
+      }
+    }
+    for (table, id) in deletes.iter().rev() {
+      remote.delete(table.name, &key_filters(table, id))?;
+      report.pushed += 1;
+    }
+    let conn = self.conn();
+    conn.execute(
+      "update sync_outbox set push = 0 where seq <= ?1 and push = 1",
+      [last],
+    )?;
+    prune_outbox(&conn)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::storage::models::{PomodoroSession, Project, Task, TaskActivity};
+  use percent_encoding::percent_decode_str;
+  use serde_json::json;
+  use std::cmp::Ordering;
+  use std::sync::{Arc, Mutex};
+  use std::thread;
+
+  type Tables = Arc<Mutex<HashMap<String, Vec<Map<String, Value>>>>>;
+
+  const KEY: &str = "anon";
+
+  fn text(value: Option<&Value>) -> String {
+    match value {
+      Some(Value::String(s)) => s.clone(),
+      Some(Value::Null) | None => String::new(),
+      Some(other) => other.to_string(),
+    }
+  }
+
+  fn compare(row: &Map<String, Value>, column: &str, filter: &str) -> bool {
+    let value = text(row.get(column));
+    match filter.split_once('.') {
+      Some((op, v)) => {
+        let v = v.trim_matches('"');
+        match op {
+          "eq" => value == v,
+          "gt" => value.as_str() > v,
+          "gte" => value.as_str() >= v,
+          _ => panic!("unsupported filter {filter}"),
+        }
+      }
+      None => panic!("unsupported filter {filter}"),
+    }
+  }
+
+  /// Split a logical filter's `(a,b,…)` list at its top-level commas.
+  fn operands(list: &str) -> Vec<&str> {
+    let list = list.strip_prefix('(').unwrap().strip_suffix(')').unwrap();
+    let (mut parts, mut depth, mut quoted, mut start) = (Vec::new(), 0, false, 0);
+    for (i, c) in list.char_indices() {
+      match c {
+        '"' => quoted = !quoted,
+        '(' if !quoted => depth += 1,
+        ')' if !quoted => depth -= 1,
+        ',' if !quoted && depth == 0 => {
+          parts.push(&list[start..i]);
+          start = i + 1;
+        }
+        _ => {}
+      }
+    }
+    parts.push(&list[start..]);
+    parts
+  }
+
+  fn condition(row: &Map<String, Value>, expr: &str) -> bool {
+    if let Some(list) = expr.strip_prefix("and") {
+      operands(list).iter().all(|e| condition(row, e))
+    } else if let Some(list) = expr.strip_prefix("or") {
+      operands(list).iter().any(|e| condition(row, e))
+    } else {
+      let (column, filter) = expr.split_once('.').unwrap();
+      compare(row, column, filter)
+    }
+  }
+
+  fn matches(row: &Map<String, Value>, filters: &[(String, String)]) -> bool {
+    filters
+      .iter()
+      .all(|(column, filter)| match column.as_str() {
+        "or" => operands(filter).iter().any(|e| condition(row, e)),
+        column => compare(row, column, filter),
+      })
+  }
+
+  /// Answer one PostgREST request against `tables`.
+  fn serve(tables: &Tables, request: &mut tiny_http::Request) -> (u16, String) {
+    let header = |name: &'static str| {
+      request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv(name))
+        .map(|h| h.value.to_string())
+    };
+    let authorized = header("apikey").as_deref() == Some(KEY)
+      && header("Authorization").as_deref() == Some("Bearer token");
+    if !authorized {
+      return (401, r#"{"message":"no api key"}"#.into());
+    }
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+    let table = path.strip_prefix("/rest/v1/").unwrap().to_string();
+    let mut params: Vec<(String, String)> = query
+      .split('&')
+      .filter_map(|pair| pair.split_once('='))
+      .map(|(k, v)| {
+        let decode = |s: &str| percent_decode_str(s).decode_utf8_lossy().into_owned();
+        (decode(k), decode(v))
+      })
+      .collect();
+    let mut take = |name: &str| {
+      let at = params.iter().position(|(k, _)| k == name)?;
+      Some(params.remove(at).1)
+    };
+    let (order, limit, offset, on_conflict) = (
+      take("order"),
+      take("limit"),
+      take("offset"),
+      take("on_conflict"),
+    );
+    take("select");
+    let mut tables = tables.lock().unwrap();
+    let rows = tables.entry(table).or_default();
+    match request.method().as_str() {
+      "GET" => {
+        let mut found: Vec<Map<String, Value>> = rows
+          .iter()
+          .filter(|r| matches(r, &params))
+          .cloned()
+          .collect();
+        if let Some(order) = order {
+          found.sort_by(|a, b| {
+            order
+              .split(',')
+              .map(|o| o.trim_end_matches(".asc"))
+              .map(|c| text(a.get(c)).cmp(&text(b.get(c))))
+              .find(|o| *o != Ordering::Equal)
+              .unwrap_or(Ordering::Equal)
+          });
+        }
+        let offset = offset.map_or(0, |o| o.parse().unwrap());
+        let limit = limit.map_or(usize::MAX, |l| l.parse().unwrap());
+        let page: Vec<_> = found.into_iter().skip(offset).take(limit).collect();
+        (200, serde_json::to_string(&page).unwrap())
+      }
+      "POST" => {
+        let mut body = String::new();
+        request.as_reader().read_to_string(&mut body).unwrap();
+        let keys: Vec<String> = on_conflict
+          .unwrap()
+          .split(',')
+          .map(str::to_string)
+          .collect();
+        let incoming: Vec<Map<String, Value>> = serde_json::from_str(&body).unwrap();
+        for row in incoming {
+          let same = |r: &Map<String, Value>| keys.iter().all(|k| r.get(k) == row.get(k));
+          match rows.iter_mut().find(|r| same(r)) {
+            Some(existing) => existing.extend(row),
+            None => rows.push(row),
+          }
+        }
+        (201, String::new())
+      }
+      "DELETE" => {
+        rows.retain(|r| !matches(r, &params));
+        (204, String::new())
+      }
+      method => panic!("unexpected {method}"),
+    }
+  }
+
+  /// In-memory PostgREST stand-in; returns its URL and tables.
+  fn stand_in() -> (String, Tables) {
+    let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", server.server_addr().to_ip().unwrap());
+    let tables = Tables::default();
+    let shared = tables.clone();
+    thread::spawn(move || {
+      for mut request in server.incoming_requests() {
+        let (status, body) = serve(&shared, &mut request);
+        request
+          .respond(tiny_http::Response::from_string(body).with_status_code(status))
+          .unwrap();
+      }
+    });
+    (url, tables)
+  }
+
+  fn config(url: &str) -> SyncConfig {
+    SyncConfig {
+      url: url.into(),
+      anon_key: KEY.into(),
+      access_token: "token".into(),
+      user_id: "user-1".into(),
+    }
+  }
+
+  fn remote_row(tables: &Tables, table: &str, id: &str) -> Option<Map<String, Value>> {
+    tables.lock().unwrap()[table]
+      .iter()
+      .find(|r| r.get("id").and_then(Value::as_str) == Some(id))
+      .cloned()
+  }
+
+  fn edit_remote(tables: &Tables, table: &str, id: &str, changes: Value) {
+    let mut tables = tables.lock().unwrap();
+    let row = tables
+      .get_mut(table)
+      .unwrap()
+      .iter_mut()
+      .find(|r| r.get("id").and_then(Value::as_str) == Some(id))
+      .unwrap();
+    row.extend(changes.as_object().unwrap().clone());
+  }
+
+  fn task(store: &Store, title: &str, project: &str) -> Task {
+    store
+      .create_task(Task {
+        title: title.into(),
+        project: Some(project.into()),
+        ..Default::default()
+      })
+      .unwrap()
+  }
+
+  /// A store with one project, two tasks and a tag, synced once.
+  fn synced_store(url: &str) -> (Store, Task, Task, String) {
+    let store = Store::open_in_memory().unwrap();
+    let project = store
+      .create_project(Project {
+        name: "Work".into(),
+        ..Default::default()
+      })
+      .unwrap();
+    let first = task(&store, "first", &project.id);
+    let second = task(&store, "second", &project.id);
+    let tag = store.create_tag("urgent", None).unwrap();
+    store.attach_tag_to_task(&first.id, &tag.id).unwrap();
+
+    let report = store.sync(&config(url)).unwrap();
+    assert_eq!(report.pushed, 5);
+    assert!(report.conflicts.is_empty());
+    (store, first, second, tag.id)
+  }
+
+  #[test]
+  fn pushes_local_rows_then_pulls_remote_edits_without_echo() {
+    let (url, tables) = stand_in();
+    let (store, first, _, tag) = synced_store(&url);
+    assert_eq!(store.sync_status().unwrap().pending, 0);
+    let pushed = remote_row(&tables, "tasks", &first.id).unwrap();
+    assert_eq!(pushed["title"], "first");
+    assert_eq!(pushed["completed"], false);
+    assert_eq!(pushed["user_id"], "user-1");
+    assert_eq!(tables.lock().unwrap()["task_tags"].len(), 1);
+
+    // Our own rows coming back are not news.
+    let again = store.sync(&config(&url)).unwrap();
+    assert_eq!((again.pulled, again.pushed), (0, 0));
+
+    edit_remote(
+      &tables,
+      "tasks",
+      &first.id,
+      json!({ "title": "renamed on the web", "completed": true, "updated_at": "2099-01-01T00:00:00Z" }),
+    );
+    let report = store.sync(&config(&url)).unwrap();
+    assert_eq!((report.pulled, report.pushed), (1, 0));
+    let local = store.get_task_by_id(&first.id).unwrap().unwrap();
+    assert_eq!(local.title, "renamed on the web");
+    assert!(local.completed);
+    assert_eq!(store.sync_status().unwrap().pending, 0);
+    assert_eq!(
+      store
+        .get_tags_by_task_ids(std::slice::from_ref(&first.id))
+        .unwrap()[&first.id][0]
+        .id,
+      tag
+    );
+  }
+
+  #[test]
+  fn concurrent_task_edits_merge_per_field_and_are_reported() {
+    let (url, tables) = stand_in();
+    let (store, first, second, _) = synced_store(&url);
+    for task in [&first, &second] {
+      store
+        .update_task(&task.id, json!({ "title": "local" }).as_object().unwrap())
+        .unwrap();
+    }
+    // The web app edits columns without touching `crdt`.
+    edit_remote(
+      &tables,
+      "tasks",
+      &first.id,
+      json!({ "description": "remote", "updated_at": "2099-01-01T00:00:00Z" }),
+    );
+    edit_remote(
+      &tables,
+      "tasks",
+      &second.id,
+      json!({ "title": "remote", "updated_at": "2099-01-01T00:00:00Z" }),
+    );
+
+    let report = store.sync(&config(&url)).unwrap();
+    let winners: HashMap<&str, Side> = report
+      .conflicts
+      .iter()
+      .map(|c| (c.row_id.as_str(), c.winner))
+      .collect();
+    assert_eq!(winners.len(), 2);
+    assert_eq!(winners[first.id.as_str()], Side::Merged);
+    assert_eq!(winners[second.id.as_str()], Side::Remote);
+
+    let merged = store.get_task_by_id(&first.id).unwrap().unwrap();
+    assert_eq!(merged.title, "local");
+    assert_eq!(merged.description.as_deref(), Some("remote"));
+    let pushed = remote_row(&tables, "tasks", &first.id).unwrap();
+    assert_eq!(
+      (&pushed["title"], &pushed["description"]),
+      (&json!("local"), &json!("remote"))
+    );
+    assert_eq!(
+      store.get_task_by_id(&second.id).unwrap().unwrap().title,
+      "remote"
+    );
+    assert_eq!(
+      remote_row(&tables, "tasks", &second.id).unwrap()["title"],
+      "remote"
+    );
+    assert_eq!(store.sync_status().unwrap().pending, 0);
+  }
+
+  #[test]
+  fn two_replicas_converge_through_supabase() {
+    let (url, tables) = stand_in();
+    let (here, first, _, tag) = synced_store(&url);
+    let there = Store::open_in_memory().unwrap();
+    there.sync(&config(&url)).unwrap();
+    assert_eq!(
+      there.get_task_by_id(&first.id).unwrap().unwrap().title,
+      "first"
+    );
+
+    // Offline on both, each edits something else of the same task.
+    here
+      .update_task(
+        &first.id,
+        json!({ "title": "renamed here" }).as_object().unwrap(),
+      )
+      .unwrap();
+    here.detach_tag_from_task(&first.id, &tag).unwrap();
+    there
+      .update_task(&first.id, json!({ "completed": true }).as_object().unwrap())
+      .unwrap();
+    let moved = there
+      .place_task_between(&first.id, None, None)
+      .unwrap()
+      .remove(0);
+
+    here.sync(&config(&url)).unwrap();
+    let report = there.sync(&config(&url)).unwrap();
+    assert_eq!(report.conflicts.len(), 1);
+    assert_eq!(report.conflicts[0].winner, Side::Merged);
+    here.sync(&config(&url)).unwrap();
+
+    for store in [&here, &there] {
+      let task = store.get_task_by_id(&first.id).unwrap().unwrap();
+      assert_eq!(task.title, "renamed here");
+      assert!(task.completed);
+      assert_eq!(task.sort_order, moved.sort_order);
+      assert!(store
+        .get_tags_by_task_ids(std::slice::from_ref(&first.id))
+        .unwrap()
+        .get(&first.id)
+        .map_or(true, Vec::is_empty));
+    }
+    assert_eq!(
+      here.task_state(&first.id).unwrap(),
+      there.task_state(&first.id).unwrap()
+    );
+    let pushed = remote_row(&tables, "tasks", &first.id).unwrap();
+    assert_eq!(
+      (&pushed["title"], &pushed["completed"]),
+      (&json!("renamed here"), &json!(true))
+    );
+    assert!(tables.lock().unwrap()["task_tags"].is_empty());
+  }
+
+  #[test]
+  fn deletions_travel_both_ways() {
+    let (url, tables) = stand_in();
+    let (store, first, second, tag) = synced_store(&url);
+
+    store.delete_task(&second.id).unwrap();
+    tables.lock().unwrap().get_mut("task_tags").unwrap().clear();
+    store.sync(&config(&url)).unwrap();
+
+    assert!(remote_row(&tables, "tasks", &second.id).is_none());
+    assert!(remote_row(&tables, "tasks", &first.id).is_some());
+    assert!(store
+      .get_tags_by_task_ids(std::slice::from_ref(&first.id))
+      .unwrap()
+      .get(&first.id)
+      .map_or(true, Vec::is_empty));
+    assert!(store.get_tag_by_id(&tag).unwrap().is_some());
+    assert_eq!(store.sync_status().unwrap().pending, 0);
+  }
+
+  #[test]
+  fn a_task_deleted_here_stays_deleted_when_pushed_back_edited() {
+    let (url, tables) = stand_in();
+    let (here, first, _, _) = synced_store(&url);
+    let there = Store::open_in_memory().unwrap();
+    there.sync(&config(&url)).unwrap();
+
+    here.delete_task(&first.id).unwrap();
+    here.sync(&config(&url)).unwrap();
+    // Not told yet, the other device edits the task and pushes it back.
+    there
+      .update_task(
+        &first.id,
+        json!({ "title": "edited there" }).as_object().unwrap(),
+      )
+      .unwrap();
+    there.sync(&config(&url)).unwrap();
+    assert!(remote_row(&tables, "tasks", &first.id).is_some());
+
+    let report = here.sync(&config(&url)).unwrap();
+    assert_eq!((report.pulled, report.pushed), (0, 1));
+    assert!(here.get_task_by_id(&first.id).unwrap().is_none());
+    assert!(remote_row(&tables, "tasks", &first.id).is_none());
+  }
+
+  #[test]
+  fn remote_hard_deletes_are_pulled() {
+    let (url, tables) = stand_in();
+    let (store, first, second, _) = synced_store(&url);
+    let errands = store
+      .create_project(Project {
+        name: "Errands".into(),
+        ..Default::default()
+      })
+      .unwrap();
+    store.sync(&config(&url)).unwrap();
+
+    {
+      let mut tables = tables.lock().unwrap();
+      let gone = [second.id.as_str(), errands.id.as_str()];
+      for table in ["tasks", "projects"] {
+        tables
+          .get_mut(table)
+          .unwrap()
+          .retain(|r| !gone.contains(&text(r.get("id")).as_str()));
+      }
+    }
+    let unsynced = task(&store, "not pushed yet", &first.project.clone().unwrap());
+    // The ids were listed a moment ago, by the first sync.
+    assert_eq!(store.sync(&config(&url)).unwrap().pulled, 0);
+    assert!(store.get_task_by_id(&second.id).unwrap().is_some());
+
+    let listed = (Utc::now() - LIST_IDS_EVERY).to_rfc3339();
+    for table in ["tasks", "projects"] {
+      set_state(&store.conn(), &format!("listed_at:{table}"), Some(&listed)).unwrap();
+    }
+    let report = store.sync(&config(&url)).unwrap();
+    assert_eq!(report.pulled, 2);
+
+    assert!(store.get_task_by_id(&second.id).unwrap().is_none());
+    assert!(store.get_project_by_id(&errands.id).unwrap().is_none());
+    assert!(store.get_task_by_id(&first.id).unwrap().is_some());
+    assert!(remote_row(&tables, "tasks", &unsynced.id).is_some());
+  }
+
+  #[test]
+  fn pomodoro_sessions_activities_and_check_ins_sync_in_the_remote_shape() {
+    let (url, tables) = stand_in();
+    let (store, first, _, _) = synced_store(&url);
+    let session = store
+      .create_pomodoro_session(PomodoroSession {
+        task_id: Some(first.id.clone()),
+        duration: 25,
+        kind: "work".into(),
+        ..Default::default()
+      })
+      .unwrap();
+    let finished = "2026-10-18T10:25:00.000Z";
+    store
+      .update_pomodoro_session(
+        &session.id,
+        json!({ "completed_at": finished }).as_object().unwrap(),
+      )
+      .unwrap();
+    let activity = store
+      .create_task_activity(TaskActivity {
+        task_id: first.id.clone(),
+        action: "title_updated".into(),
+        metadata: json!({ "from": "draft" }).as_object().cloned(),
+        ..Default::default()
+      })
+      .unwrap();
+    let check_in = store.create_check_in(Some("morning".into())).unwrap();
+    assert_eq!(store.sync(&config(&url)).unwrap().pushed, 3);
+
+    let pushed = remote_row(&tables, "pomodoro_sessions", &session.id).unwrap();
+    assert_eq!(pushed["type"], "focus");
+    assert_eq!(pushed["start_time"], session.started_at.as_str());
+    assert_eq!(
+      (&pushed["completed"], &pushed["end_time"]),
+      (&json!(true), &json!(finished))
+    );
+    assert_eq!(pushed["user_id"], "user-1");
+    assert!(pushed.get("started_at").is_none() && pushed.get("completed_at").is_none());
+    assert_eq!(
+      remote_row(&tables, "task_activities", &activity.id).unwrap()["metadata"],
+      json!({ "from": "draft" })
+    );
+    assert_eq!(
+      remote_row(&tables, "checkin_records", &check_in.id).unwrap()["note"],
+      "morning"
+    );
+    let again = store.sync(&config(&url)).unwrap();
+    assert_eq!((again.pulled, again.pushed), (0, 0));
+
+    // A break the web app ended early, and another device pulling it all.
+    let mut web = pushed.clone();
+    web.extend(
+      json!({
+        "id": "web-session",
+        "type": "short_break",
+        "completed": false,
+        "end_time": "2026-10-18T10:28:00.000Z",
+        "updated_at": "2099-01-01T00:00:00.000Z",
+      })
+      .as_object()
+      .unwrap()
+      .clone(),
+    );
+    web.remove("cancelled_at");
+    tables
+      .lock()
+      .unwrap()
+      .get_mut("pomodoro_sessions")
+      .unwrap()
+      .push(web);
+    assert_eq!(store.sync(&config(&url)).unwrap().pulled, 1);
+    let there = Store::open_in_memory().unwrap();
+    there.sync(&config(&url)).unwrap();
+    for store in [&store, &there] {
+      let sessions = store.get_pomodoro_sessions(Some(&first.id)).unwrap();
+      assert_eq!(sessions.len(), 2);
+      let done = sessions.iter().find(|s| s.id == session.id).unwrap();
+      assert_eq!(
+        (done.kind.as_str(), done.completed_at.as_deref()),
+        ("work", Some(finished))
+      );
+      let early = sessions.iter().find(|s| s.id == "web-session").unwrap();
+      assert_eq!(early.completed_at, None);
+      assert_eq!(
+        early.cancelled_at.as_deref(),
+        Some("2026-10-18T10:28:00.000Z")
+      );
+    }
+    assert_eq!(
+      there.get_task_activities(&first.id).unwrap(),
+      vec![TaskActivity {
+        user_id: Some("user-1".into()),
+        ..activity
+      }]
+    );
+    assert!(there.has_checked_in_today().unwrap());
+  }
+
+  #[test]
+  fn pulls_every_row_of_a_page_sharing_one_timestamp() {
+    let (url, tables) = stand_in();
+    let (store, first, _, _) = synced_store(&url);
+    let mut template = remote_row(&tables, "tasks", &first.id).unwrap();
+    template.remove("crdt");
+    let at = "2099-01-01T00:00:00.000Z";
+    {
+      let mut tables = tables.lock().unwrap();
+      let rows = tables.get_mut("tasks").unwrap();
+      for i in 0..PAGE + 20 {
+        let mut row = template.clone();
+        row.insert("id".into(), format!("bulk-{i:04}").into());
+        row.insert("title".into(), format!("bulk {i}").into());
+        row.insert("updated_at".into(), at.into());
+        rows.push(row);
+      }
+    }
+
+    let report = store.sync(&config(&url)).unwrap();
+    assert_eq!(report.pulled, PAGE + 20);
+    for i in [0, PAGE - 1, PAGE, PAGE + 19] {
+      assert!(store
+        .get_task_by_id(&format!("bulk-{i:04}"))
+        .unwrap()
+        .is_some());
+    }
+    edit_remote(
+      &tables,
+      "tasks",
+      "bulk-0003",
+      json!({ "title": "later", "updated_at": "2099-01-02T00:00:00.000Z" }),
+    );
+    assert_eq!(store.sync(&config(&url)).unwrap().pulled, 1);
+  }
+
+  #[test]
+  fn pushes_keep_the_owner_of_rows_shared_with_us() {
+    let (url, tables) = stand_in();
+    let (store, first, _, _) = synced_store(&url);
+    edit_remote(
+      &tables,
+      "tasks",
+      &first.id,
+      json!({ "user_id": "user-2", "updated_at": "2099-01-01T00:00:00.000Z" }),
+    );
+    store.sync(&config(&url)).unwrap();
+
+    store
+      .update_task(
+        &first.id,
+        json!({ "title": "edited here" }).as_object().unwrap(),
+      )
+      .unwrap();
+    store.sync(&config(&url)).unwrap();
+    let pushed = remote_row(&tables, "tasks", &first.id).unwrap();
+    assert_eq!(pushed["title"], "edited here");
+    assert_eq!(pushed["user_id"], "user-2");
+  }
+
+  #[test]
+  fn changes_wait_in_the_outbox_while_offline() {
+    let store = Store::open_in_memory().unwrap();
+    store
+      .create_project(Project {
+        name: "Inbox".into(),
+        ..Default::default()
+      })
+      .unwrap();
+    assert!(store.sync(&config("http://127.0.0.1:1")).is_err());
+    let status = store.sync_status().unwrap();
+    assert!(status.enabled);
+    assert_eq!(status.pending, 1);
+    assert!(status.last_error.is_some());
+
+    let (url, tables) = stand_in();
+    let mut wrong = config(&url);
+    wrong.anon_key = "other".into();
+    wrong.access_token = "other".into();
+    let error = store.sync(&wrong).unwrap_err().to_string();
+    assert!(error.contains("HTTP 401"), "{error}");
+
+    store.sync(&config(&url)).unwrap();
+    assert_eq!(tables.lock().unwrap()["projects"].len(), 1);
+    let status = store.sync_status().unwrap();
+    assert_eq!((status.pending, status.last_error), (0, None));
+    assert!(status.last_synced_at.is_some());
+  }
+}
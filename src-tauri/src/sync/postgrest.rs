@@ -0,0 +1,87 @@
+//! The slice of the PostgREST API (Supabase's `/rest/v1`) the sync engine
+//! uses: filtered selects, upserts and deletes.
+
+use std::time::Duration;
+
+use serde_json::{Map, Value};
+
+use super::SyncConfig;
+use crate::error::{Error, Result};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub struct Postgrest {
+  base: String,
+  anon_key: String,
+  access_token: String,
+  agent: ureq::Agent,
+}
+
+impl Postgrest {
+  pub fn new(config: &SyncConfig) -> Self {
+    Self {
+      base: format!("{}/rest/v1", config.url.trim_end_matches('/')),
+      anon_key: config.anon_key.clone(),
+      access_token: config.access_token.clone(),
+      agent: ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build(),
+    }
+  }
+
+  fn request(&self, method: &str, table: &str, query: &[(&str, String)]) -> ureq::Request {
+    let mut request = self
+      .agent
+      .request(method, &format!("{}/{table}", self.base))
+      .set("apikey", &self.anon_key)
+      .set("Authorization", &format!("Bearer {}", self.access_token))
+      .set("User-Agent", "SnailTodoList");
+    for (name, value) in query {
+      request = request.query(name, value);
+    }
+    request
+  }
+
+  /// Rows of `table` matching `query`, e.g. `[("updated_at", "gte.2026-..")]`.
+  pub fn select(&self, table: &str, query: &[(&str, String)]) -> Result<Vec<Map<String, Value>>> {
+    let response = self
+      .request("GET", table, query)
+      .call()
+      .map_err(|e| failure(table, e))?;
+    let body = response.into_string()?;
+    serde_json::from_str(&body)
+      .map_err(|e| Error::Sync(format!("{table}: unreadable response: {e}")))
+  }
+
+  /// Insert `rows`, updating the ones whose `on_conflict` columns exist.
+  pub fn upsert(&self, table: &str, on_conflict: &str, rows: &[Value]) -> Result<()> {
+    if rows.is_empty() {
+      return Ok(());
+    }
+    self
+      .request("POST", table, &[("on_conflict", on_conflict.to_string())])
+      .set("Content-Type", "application/json")
+      .set("Prefer", "resolution=merge-duplicates,return=minimal")
+      .send_string(&Value::Array(rows.to_vec()).to_string())
+      .map_err(|e| failure(table, e))?;
+    Ok(())
+  }
+
+  /// Delete the rows matching `filters`, e.g. `[("id", "eq.<id>")]`.
+  pub fn delete(&self, table: &str, filters: &[(&str, String)]) -> Result<()> {
+    self
+      .request("DELETE", table, filters)
+      .set("Prefer", "return=minimal")
+      .call()
+      .map_err(|e| failure(table, e))?;
+    Ok(())
+  }
+}
+
+fn failure(table: &str, e: ureq::Error) -> Error {
+  match e {
+    ureq::Error::Status(code, response) => {
+      let body = response.into_string().unwrap_or_default();
+      Error::Sync(format!("{table}: HTTP {code} {}", body.trim()))
+    }
+    e => Error::Sync(format!("{table}: {e}")),
+  }
+}
@@ -0,0 +1,179 @@
+//! System tray: today's open-task count, the pomodoro countdown and quick
+//! actions.
+//!
+//! The tray keeps the app reachable while the main window is hidden; closing
+//! the main window only hides it (see `lib.rs`), and "退出" is the way out.
+//!
+//! The count is read from the local store, as is everything the main window
+//! shows: on desktop the webview uses SQLite in both storage modes, and
+//! Supabase changes arrive through [`crate::sync`], which refreshes the tray
+//! whenever a run pulls something.
+
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use chrono::Local;
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Manager, Wry};
+
+use crate::capture;
+use crate::commands;
+use crate::storage::Store;
+
+const TRAY_ID: &str = "main";
+pub(crate) const MAIN_WINDOW: &str = "main";
+
+/// Asks the main window to open the pomodoro page and start a session.
+pub const START_POMODORO_EVENT: &str = "tray://start-pomodoro";
+
+/// The count is refreshed after every task write and at least this often,
+/// so it also rolls over at midnight.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Menu entries whose text changes at runtime, and what the title shows.
+struct TrayMenu {
+  today: MenuItem<Wry>,
+  status: Mutex<TrayStatus>,
+}
+
+#[derive(Default)]
+struct TrayStatus {
+  count: usize,
+  /// Remaining time of the open pomodoro session, if any.
+  pomodoro: Option<String>,
+}
+
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+  let today = MenuItem::with_id(app, "today", today_label(0), false, None::<&str>)?;
+  let menu = Menu::with_items(
+    app,
+    &[
+      &today,
+      &PredefinedMenuItem::separator(app)?,
+      &MenuItem::with_id(app, "quick_add", "快速添加任务", true, None::<&str>)?,
+      &MenuItem::with_id(app, "start_pomodoro", "开始番茄钟", true, None::<&str>)?,
+      &MenuItem::with_id(app, "show", "显示窗口", true, None::<&str>)?,
+      &PredefinedMenuItem::separator(app)?,
+      &MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?,
+    ],
+  )?;
+
+  let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+    .menu(&menu)
+    .show_menu_on_left_click(false)
+    .on_menu_event(on_menu_event)
+    .on_tray_icon_event(on_tray_icon_event);
+  if let Some(icon) = app.default_window_icon() {
+    builder = builder.icon(icon.clone());
+  }
+  builder.build(app)?;
+  app.manage(TrayMenu {
+    today,
+    status: Mutex::default(),
+  });
+
+  refresh(app);
+  let handle = app.clone();
+  thread::spawn(move || loop {
+    thread::sleep(REFRESH_INTERVAL);
+    refresh(&handle);
+  });
+  Ok(())
+}
+
+fn today_label(count: usize) -> String {
+  format!("今天 {count} 个待办")
+}
+
+/// Recount today's open tasks and update the tray.
+pub fn refresh(app: &AppHandle) {
+  let Some(store) = app.try_state::<Store>() else {
+    return;
+  };
+  let count = match store.count_due_today(Local::now().date_naive()) {
+    Ok(count) => count,
+    Err(e) => {
+      log::warn!("failed to count today's tasks: {e}");
+      return;
+    }
+  };
+  if let Some(menu) = app.try_state::<TrayMenu>() {
+    let _ = menu.today.set_text(today_label(count));
+    menu.status.lock().unwrap_or_else(|e| e.into_inner()).count = count;
+  }
+  update(app);
+}
+
+/// Show the pomodoro countdown in place of the count; `None` clears it.
+pub fn set_pomodoro(app: &AppHandle, label: Option<String>) {
+  let Some(menu) = app.try_state::<TrayMenu>() else {
+    return;
+  };
+  {
+    let mut status = menu.status.lock().unwrap_or_else(|e| e.into_inner());
+    if status.pomodoro == label {
+      return;
+    }
+    status.pomodoro = label;
+  }
+  update(app);
+}
+
+fn update(app: &AppHandle) {
+  let (Some(menu), Some(tray)) = (app.try_state::<TrayMenu>(), app.tray_by_id(TRAY_ID)) else {
+    return;
+  };
+  let status = menu.status.lock().unwrap_or_else(|e| e.into_inner());
+  let label = today_label(status.count);
+  let tooltip = match &status.pomodoro {
+    Some(pomodoro) => format!("蜗牛待办 · {pomodoro} · {label}"),
+    None => format!("蜗牛待办 · {label}"),
+  };
+  let _ = tray.set_tooltip(Some(tooltip));
+  // macOS 在图标旁显示番茄钟倒计时或数字，其他平台忽略
+  let title = status
+    .pomodoro
+    .clone()
+    .or_else(|| (status.count > 0).then(|| status.count.to_string()));
+  let _ = tray.set_title(title);
+}
+
+pub fn show_main_window(app: &AppHandle) {
+  if let Some(window) = app.get_webview_window(MAIN_WINDOW) {
+    let _ = window.unminimize();
+    let _ = window.show();
+    let _ = window.set_focus();
+  }
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent) {
+  match event.id().as_ref() {
+    "quick_add" => {
+      if let Err(e) = capture::show(app) {
+        log::warn!("failed to open quick add window: {e}");
+      }
+    }
+    "start_pomodoro" => {
+      // 计时在 Rust 侧开始，主窗口只负责切到番茄钟页面
+      commands::pomodoro::start_from_tray(app);
+      show_main_window(app);
+      let _ = app.emit_to(MAIN_WINDOW, START_POMODORO_EVENT, ());
+    }
+    "show" => show_main_window(app),
+    "quit" => app.exit(0),
+    _ => {}
+  }
+}
+
+fn on_tray_icon_event(tray: &TrayIcon, event: TrayIconEvent) {
+  if let TrayIconEvent::Click {
+    button: MouseButton::Left,
+    button_state: MouseButtonState::Up,
+    ..
+  } = event
+  {
+    show_main_window(tray.app_handle());
+  }
+}
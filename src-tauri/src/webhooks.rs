@@ -0,0 +1,850 @@
+//! Deadline webhooks.
+//!
+//! Fills in the `webhook_enabled` TODO in `deadlineService.checkAndNotify`:
+//! upcoming and overdue tasks are queued in `webhook_outbox` and POSTed to
+//! `webhook_url`, retrying transient failures with exponential backoff.
+//! Queued deliveries survive restarts. Bodies are queued unsigned; when
+//! `webhook_secret` is set each request is signed at send time the way its
+//! receiver checks it:
+//!
+//! - Feishu: `timestamp` (unix seconds) and `sign` fields in the JSON body
+//! - DingTalk: `timestamp` (unix milliseconds) and `sign` query parameters
+//! - custom: `X-Snail-Timestamp` (unix seconds) and `X-Snail-Signature`
+//!   (`sha256=` + hex HMAC-SHA256 of `"<timestamp>.<body>"`) headers, so
+//!   receivers can check the sender and reject replays
+
+use std::time::Duration;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, Local, TimeDelta, Utc};
+use hmac::{Hmac, Mac};
+use rusqlite::{params, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+use crate::error::{Error, Result};
+use crate::reminders::{deadline_of, upcoming, ReminderPolicy};
+use crate::storage::models::{Task, TaskFilter, UserSettings};
+use crate::storage::{new_id, now_iso, Store};
+
+pub const TIMESTAMP_HEADER: &str = "X-Snail-Timestamp";
+pub const SIGNATURE_HEADER: &str = "X-Snail-Signature";
+
+/// Deliveries are abandoned (status `failed`) after this many attempts, or
+/// at once when the receiver rejects them for good.
+pub const MAX_ATTEMPTS: u32 = 8;
+const FIRST_RETRY: TimeDelta = TimeDelta::seconds(30);
+const MAX_RETRY: TimeDelta = TimeDelta::hours(1);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Only deadlines missed within this window are reported as overdue, so
+/// turning webhooks on does not replay the whole backlog.
+const OVERDUE_WINDOW: TimeDelta = TimeDelta::days(1);
+
+/// Bot error codes that only ask the sender to slow down: Feishu's
+/// frequency limit and DingTalk's "send too fast".
+const BOT_RATE_LIMITED: [i64; 2] = [11232, 130101];
+
+/// `webhook_type` from the notification settings page. Feishu and DingTalk
+/// bots only accept their own text message shape.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookKind {
+  #[default]
+  Feishu,
+  Dingtalk,
+  Custom,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookConfig {
+  #[serde(default)]
+  pub kind: WebhookKind,
+  pub url: String,
+  #[serde(default)]
+  pub secret: Option<String>,
+}
+
+impl WebhookConfig {
+  /// Reads `webhook_enabled`, `webhook_type`, `webhook_url` and
+  /// `webhook_secret`; `None` unless enabled with a URL.
+  pub fn from_settings(settings: &UserSettings) -> Option<Self> {
+    if !settings.bool("webhook_enabled").unwrap_or(false) {
+      return None;
+    }
+    let url = settings.str("webhook_url")?.trim();
+    if url.is_empty() {
+      return None;
+    }
+    Some(Self {
+      kind: match settings.str("webhook_type") {
+        Some("dingtalk") => WebhookKind::Dingtalk,
+        Some("custom") => WebhookKind::Custom,
+        _ => WebhookKind::Feishu,
+      },
+      url: url.to_string(),
+      secret: settings
+        .str("webhook_secret")
+        .filter(|s| !s.is_empty())
+        .map(str::to_string),
+    })
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookEvent {
+  Upcoming,
+  Overdue,
+  Test,
+}
+
+impl WebhookEvent {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      WebhookEvent::Upcoming => "task.upcoming",
+      WebhookEvent::Overdue => "task.overdue",
+      WebhookEvent::Test => "webhook.test",
+    }
+  }
+
+  fn text(self, task: Option<(&Task, DateTime<Utc>)>) -> String {
+    let Some((task, deadline)) = task else {
+      return "这是一条来自蜗牛待办的测试消息".to_string();
+    };
+    let deadline = deadline.with_timezone(&Local).format("%m/%d %H:%M");
+    match self {
+      WebhookEvent::Overdue => format!("任务已逾期：{}（截止时间 {deadline}）", task.title),
+      _ => format!("任务即将截止：{}（截止时间 {deadline}）", task.title),
+    }
+  }
+}
+
+/// Request body for `event`; `task` is `None` for test messages.
+pub fn build_body(
+  kind: WebhookKind,
+  event: WebhookEvent,
+  task: Option<(&Task, DateTime<Utc>)>,
+) -> String {
+  let text = event.text(task);
+  let body = match kind {
+    WebhookKind::Feishu => json!({ "msg_type": "text", "content": { "text": text } }),
+    WebhookKind::Dingtalk => json!({ "msgtype": "text", "text": { "content": text } }),
+    WebhookKind::Custom => json!({
+      "event": event.as_str(),
+      "text": text,
+      "created_at": now_iso(),
+      "task": task.map(|(task, deadline)| json!({
+        "id": task.id,
+        "title": task.title,
+        "date": task.date,
+        "deadline": deadline,
+        "project": task.project,
+        "flagged": task.flagged,
+      })),
+    }),
+  };
+  body.to_string()
+}
+
+/// Hex HMAC-SHA256 of `"<timestamp>.<body>"`, without the `sha256=` prefix.
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+  let mut mac =
+    Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+  mac.update(timestamp.to_string().as_bytes());
+  mac.update(b".");
+  mac.update(body.as_bytes());
+  hex::encode(mac.finalize().into_bytes())
+}
+
+/// Feishu bot signature: base64 HMAC-SHA256 keyed with
+/// `"<timestamp>\n<secret>"` over an empty message; `timestamp` in seconds.
+pub fn feishu_sign(secret: &str, timestamp: i64) -> String {
+  let mac = Hmac::<Sha256>::new_from_slice(format!("{timestamp}\n{secret}").as_bytes())
+    .expect("HMAC accepts keys of any length");
+  STANDARD.encode(mac.finalize().into_bytes())
+}
+
+/// DingTalk bot signature: base64 HMAC-SHA256 of `"<timestamp>\n<secret>"`
+/// keyed with the secret; `timestamp` in milliseconds.
+pub fn dingtalk_sign(secret: &str, timestamp: i64) -> String {
+  let mut mac =
+    Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+  mac.update(format!("{timestamp}\n{secret}").as_bytes());
+  STANDARD.encode(mac.finalize().into_bytes())
+}
+
+/// A delivery as it goes on the wire.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedRequest {
+  pub url: String,
+  pub body: String,
+  pub headers: Vec<(&'static str, String)>,
+}
+
+/// Sign a queued `body` for `kind` at `now`; without a secret it goes out
+/// unchanged.
+pub fn sign_request(
+  kind: WebhookKind,
+  url: &str,
+  body: &str,
+  secret: Option<&str>,
+  now: DateTime<Utc>,
+) -> Result<SignedRequest> {
+  let mut request = SignedRequest {
+    url: url.to_string(),
+    body: body.to_string(),
+    headers: Vec::new(),
+  };
+  let Some(secret) = secret else {
+    return Ok(request);
+  };
+  match kind {
+    WebhookKind::Feishu => {
+      let timestamp = now.timestamp();
+      let mut value: Value = serde_json::from_str(body)?;
+      let object = value
+        .as_object_mut()
+        .ok_or_else(|| Error::InvalidInput("webhook body is not a JSON object".into()))?;
+      object.insert("timestamp".into(), json!(timestamp.to_string()));
+      object.insert("sign".into(), json!(feishu_sign(secret, timestamp)));
+      request.body = value.to_string();
+    }
+    WebhookKind::Dingtalk => {
+      let timestamp = now.timestamp_millis();
+      let mut parsed =
+        url::Url::parse(url).map_err(|e| Error::InvalidInput(format!("webhook url: {e}")))?;
+      parsed
+        .query_pairs_mut()
+        .append_pair("timestamp", &timestamp.to_string())
+        .append_pair("sign", &dingtalk_sign(secret, timestamp));
+      request.url = parsed.into();
+    }
+    WebhookKind::Custom => {
+      let timestamp = now.timestamp();
+      request.headers = vec![
+        (TIMESTAMP_HEADER, timestamp.to_string()),
+        (
+          SIGNATURE_HEADER,
+          format!("sha256={}", sign(secret, timestamp, body)),
+        ),
+      ];
+    }
+  }
+  Ok(request)
+}
+
+/// Why a delivery attempt failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Failure {
+  /// Worth retrying: network errors, 5xx, 408, 429 and bot rate limits.
+  Transient(String),
+  /// Retrying cannot help: other 4xx and messages the bot rejected.
+  Permanent(String),
+}
+
+impl From<Failure> for Error {
+  fn from(failure: Failure) -> Self {
+    match failure {
+      Failure::Transient(message) | Failure::Permanent(message) => Error::Webhook(message),
+    }
+  }
+}
+
+fn status_failure(code: u16) -> Failure {
+  let message = format!("HTTP {code}");
+  match code {
+    408 | 429 => Failure::Transient(message),
+    400..=499 => Failure::Permanent(message),
+    _ => Failure::Transient(message),
+  }
+}
+
+/// Feishu and DingTalk answer HTTP 200 even when they reject a message
+/// (bad signature, keyword filter, ...); the error is in `code`/`errcode`.
+fn check_reply(kind: WebhookKind, response: ureq::Response) -> Result<(), Failure> {
+  if kind == WebhookKind::Custom {
+    return Ok(());
+  }
+  let Some(reply) = response
+    .into_string()
+    .ok()
+    .and_then(|text| serde_json::from_str::<Value>(&text).ok())
+  else {
+    return Ok(());
+  };
+  let code = ["code", "errcode"]
+    .iter()
+    .find_map(|key| reply.get(*key)?.as_i64())
+    .unwrap_or(0);
+  if code == 0 {
+    return Ok(());
+  }
+  let message = ["msg", "errmsg"]
+    .iter()
+    .find_map(|key| reply.get(*key)?.as_str())
+    .unwrap_or_default();
+  let message = format!("error {code}: {message}");
+  if BOT_RATE_LIMITED.contains(&code) {
+    Err(Failure::Transient(message))
+  } else {
+    Err(Failure::Permanent(message))
+  }
+}
+
+/// POST a queued `body` to `url`, signed for `kind` if `secret` is given.
+pub fn send(
+  kind: WebhookKind,
+  url: &str,
+  body: &str,
+  secret: Option<&str>,
+  now: DateTime<Utc>,
+) -> Result<(), Failure> {
+  let signed =
+    sign_request(kind, url, body, secret, now).map_err(|e| Failure::Permanent(e.to_string()))?;
+  let agent = ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build();
+  let mut request = agent
+    .post(&signed.url)
+    .set("Content-Type", "application/json")
+    .set("User-Agent", "SnailTodoList");
+  for (name, value) in &signed.headers {
+    request = request.set(name, value);
+  }
+  match request.send_string(&signed.body) {
+    Ok(response) => check_reply(kind, response),
+    Err(ureq::Error::Status(code, _)) => Err(status_failure(code)),
+    Err(e) => Err(Failure::Transient(e.to_string())),
+  }
+}
+
+/// Delay before retry number `attempts` (1-based): 30s, 1m, 2m, ... capped at 1h.
+pub fn backoff(attempts: u32) -> TimeDelta {
+  let factor = 1i32 << attempts.saturating_sub(1).min(16);
+  (FIRST_RETRY * factor).min(MAX_RETRY)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboxEntry {
+  pub id: String,
+  pub event: String,
+  pub url: String,
+  pub body: String,
+  pub status: String,
+  pub attempts: u32,
+  pub next_attempt_at: String,
+  pub last_error: Option<String>,
+}
+
+fn entry_from_row(row: &Row<'_>) -> rusqlite::Result<OutboxEntry> {
+  Ok(OutboxEntry {
+    id: row.get("id")?,
+    event: row.get("event")?,
+    url: row.get("url")?,
+    body: row.get("body")?,
+    status: row.get("status")?,
+    attempts: row.get("attempts")?,
+    next_attempt_at: row.get("next_attempt_at")?,
+    last_error: row.get("last_error")?,
+  })
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeliveryReport {
+  pub delivered: usize,
+  pub retrying: usize,
+  pub failed: usize,
+}
+
+fn iso(t: DateTime<Utc>) -> String {
+  t.to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+}
+
+impl Store {
+  /// Queue a delivery; returns `false` if `dedup_key` was queued before.
+  pub fn enqueue_webhook(
+    &self,
+    event: WebhookEvent,
+    dedup_key: Option<&str>,
+    url: &str,
+    body: &str,
+    now: DateTime<Utc>,
+  ) -> Result<bool> {
+    let inserted = self.conn().execute(
+      "insert or ignore into webhook_outbox
+         (id, event, dedup_key, url, body, next_attempt_at, created_at)
+       values (?1, ?2, ?3, ?4, ?5, ?6, ?6)",
+      params![new_id(), event.as_str(), dedup_key, url, body, iso(now)],
+    )?;
+    Ok(inserted > 0)
+  }
+
+  /// Queue webhooks for `tasks` due within `lead` and tasks that became
+  /// overdue recently. Returns how many were newly queued.
+  pub fn enqueue_deadline_webhooks(
+    &self,
+    config: &WebhookConfig,
+    tasks: &[Task],
+    lead: TimeDelta,
+    now: DateTime<Utc>,
+  ) -> Result<usize> {
+    let mut due: Vec<(WebhookEvent, &Task, DateTime<Utc>)> = Vec::new();
+    for reminder in upcoming(tasks, lead, now) {
+      if let Some(task) = tasks.iter().find(|t| t.id == reminder.task_id) {
+        due.push((WebhookEvent::Upcoming, task, reminder.deadline));
+      }
+    }
+    for task in tasks
+      .iter()
+      .filter(|t| !t.completed && !t.deleted && !t.abandoned)
+    {
+      let Some(deadline) = task.date.as_deref().and_then(deadline_of) else {
+        continue;
+      };
+      if deadline <= now && deadline > now - OVERDUE_WINDOW {
+        due.push((WebhookEvent::Overdue, task, deadline));
+      }
+    }
+
+    let mut queued = 0;
+    for (event, task, deadline) in due {
+      let key = format!(
+        "{}:{}:{}",
+        event.as_str(),
+        task.id,
+        task.date.as_deref().unwrap_or_default()
+      );
+      let body = build_body(config.kind, event, Some((task, deadline)));
+      if self.enqueue_webhook(event, Some(&key), &config.url, &body, now)? {
+        queued += 1;
+      }
+    }
+    Ok(queued)
+  }
+
+  /// Pending deliveries whose next attempt is due, oldest first.
+  pub fn due_webhooks(&self, now: DateTime<Utc>) -> Result<Vec<OutboxEntry>> {
+    let conn = self.conn();
+    let mut stmt = conn.prepare(
+      "select * from webhook_outbox
+       where status = 'pending' and next_attempt_at <= ?1
+       order by created_at",
+    )?;
+    let entries = stmt
+      .query_map([iso(now)], entry_from_row)?
+      .collect::<rusqlite::Result<_>>()?;
+    Ok(entries)
+  }
+
+  pub fn webhook_outbox(&self) -> Result<Vec<OutboxEntry>> {
+    let conn = self.conn();
+    let mut stmt = conn.prepare("select * from webhook_outbox order by created_at desc")?;
+    let entries = stmt
+      .query_map([], entry_from_row)?
+      .collect::<rusqlite::Result<_>>()?;
+    Ok(entries)
+  }
+
+  fn record_attempt(
+    &self,
+    entry: &OutboxEntry,
+    outcome: &Result<(), Failure>,
+    now: DateTime<Utc>,
+  ) -> Result<&'static str> {
+    let attempts = entry.attempts + 1;
+    let (status, next, error) = match outcome {
+      Ok(()) => ("delivered", now, None),
+      Err(failure) => {
+        let error = Some(Error::from(failure.clone()).to_string());
+        match failure {
+          Failure::Transient(_) if attempts < MAX_ATTEMPTS => {
+            ("pending", now + backoff(attempts), error)
+          }
+          _ => ("failed", now, error),
+        }
+      }
+    };
+    self.conn().execute(
+      "update webhook_outbox
+       set status = ?2, attempts = ?3, next_attempt_at = ?4, last_error = ?5,
+           delivered_at = case when ?2 = 'delivered' then ?6 else delivered_at end
+       where id = ?1",
+      params![entry.id, status, attempts, iso(next), error, iso(now)],
+    )?;
+    Ok(status)
+  }
+
+  /// Try every due delivery once.
+  pub fn deliver_webhooks(
+    &self,
+    config: &WebhookConfig,
+    now: DateTime<Utc>,
+  ) -> Result<DeliveryReport> {
+    let mut report = DeliveryReport::default();
+    for entry in self.due_webhooks(now)? {
+      let outcome = send(
+        config.kind,
+        &entry.url,
+        &entry.body,
+        config.secret.as_deref(),
+        now,
+      );
+      if let Err(e) = &outcome {
+        log::warn!(
+          "webhook {} attempt {} failed: {e:?}",
+          entry.id,
+          entry.attempts + 1
+        );
+      }
+      match self.record_attempt(&entry, &outcome, now)? {
+        "delivered" => report.delivered += 1,
+        "failed" => report.failed += 1,
+        _ => report.retrying += 1,
+      }
+    }
+    Ok(report)
+  }
+}
+
+/// One dispatcher tick when tasks and settings live in this database: queue
+/// new deadline webhooks and deliver what is due.
+pub fn run_due(store: &Store, now: DateTime<Utc>) -> Result<DeliveryReport> {
+  let settings = store.get_user_settings()?;
+  let Some(config) = WebhookConfig::from_settings(&settings) else {
+    return Ok(DeliveryReport::default());
+  };
+  let lead = ReminderPolicy::from_settings(&settings).lead;
+  let filter = TaskFilter {
+    completed: Some(false),
+    deleted: Some(false),
+    abandoned: Some(false),
+    ..Default::default()
+  };
+  let tasks = store.get_tasks(&filter, &[])?;
+  store.enqueue_deadline_webhooks(&config, &tasks, lead, now)?;
+  store.deliver_webhooks(&config, now)
+}
+
+/// Same as [`run_due`] for tasks and settings the webview keeps outside this
+/// database (IndexedDB or Supabase) and hands over itself.
+pub fn run_with(
+  store: &Store,
+  config: &WebhookConfig,
+  tasks: &[Task],
+  lead: TimeDelta,
+  now: DateTime<Utc>,
+) -> Result<DeliveryReport> {
+  store.enqueue_deadline_webhooks(config, tasks, lead, now)?;
+  store.deliver_webhooks(config, now)
+}
+
+/// Send a test message right away, bypassing the outbox.
+pub fn send_test(config: &WebhookConfig) -> Result<()> {
+  let body = build_body(config.kind, WebhookEvent::Test, None);
+  send(
+    config.kind,
+    &config.url,
+    &body,
+    config.secret.as_deref(),
+    Utc::now(),
+  )?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::Map;
+  use std::sync::mpsc;
+  use std::thread;
+
+  struct Received {
+    /// Path and query.
+    url: String,
+    body: String,
+    timestamp: Option<String>,
+    signature: Option<String>,
+  }
+
+  fn header(request: &tiny_http::Request, name: &'static str) -> Option<String> {
+    request
+      .headers()
+      .iter()
+      .find(|h| h.field.equiv(name))
+      .map(|h| h.value.to_string())
+  }
+
+  /// Local HTTP stand-in answering with `statuses` in turn.
+  fn stand_in(statuses: Vec<u16>) -> (String, mpsc::Receiver<Received>) {
+    stand_in_replying(statuses.into_iter().map(|s| (s, "")).collect())
+  }
+
+  /// Local HTTP stand-in answering with `(status, body)` in turn.
+  fn stand_in_replying(replies: Vec<(u16, &'static str)>) -> (String, mpsc::Receiver<Received>) {
+    let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/hook", server.server_addr().to_ip().unwrap());
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+      for (status, reply) in replies {
+        let mut request = server.recv().unwrap();
+        let (timestamp, signature) = (
+          header(&request, TIMESTAMP_HEADER),
+          header(&request, SIGNATURE_HEADER),
+        );
+        let mut body = String::new();
+        request.as_reader().read_to_string(&mut body).unwrap();
+        tx.send(Received {
+          url: request.url().to_string(),
+          body,
+          timestamp,
+          signature,
+        })
+        .unwrap();
+        let response = tiny_http::Response::from_string(reply).with_status_code(status);
+        request.respond(response).unwrap();
+      }
+    });
+    (url, rx)
+  }
+
+  fn enabled_store(kind: &str, url: &str, secret: Option<&str>) -> Store {
+    let store = Store::open_in_memory().unwrap();
+    let settings = json!({
+      "webhook_enabled": true,
+      "webhook_type": kind,
+      "webhook_url": url,
+      "webhook_secret": secret,
+    });
+    store
+      .save_user_settings(UserSettings(
+        serde_json::from_value::<Map<String, Value>>(settings).unwrap(),
+      ))
+      .unwrap();
+    store
+  }
+
+  fn task_due(store: &Store, title: &str, deadline: DateTime<Utc>) -> Task {
+    store
+      .create_task(Task {
+        title: title.into(),
+        date: Some(deadline.to_rfc3339()),
+        ..Default::default()
+      })
+      .unwrap()
+  }
+
+  #[test]
+  fn backoff_doubles_up_to_an_hour() {
+    assert_eq!(backoff(1), TimeDelta::seconds(30));
+    assert_eq!(backoff(2), TimeDelta::seconds(60));
+    assert_eq!(backoff(4), TimeDelta::seconds(240));
+    assert_eq!(backoff(20), TimeDelta::hours(1));
+  }
+
+  #[test]
+  fn delivers_signed_payloads_for_upcoming_and_overdue_tasks() {
+    let (url, received) = stand_in(vec![200, 200]);
+    let store = enabled_store("custom", &url, Some("s3cret"));
+    let now = Utc::now();
+    let soon = task_due(&store, "soon", now + TimeDelta::minutes(10));
+    let late = task_due(&store, "late", now - TimeDelta::hours(2));
+    task_due(&store, "ancient", now - TimeDelta::days(30));
+
+    let report = run_due(&store, now).unwrap();
+    assert_eq!(report.delivered, 2);
+
+    let mut events = Vec::new();
+    for _ in 0..2 {
+      let request = received.recv().unwrap();
+      let timestamp: i64 = request.timestamp.unwrap().parse().unwrap();
+      assert_eq!(
+        request.signature.unwrap(),
+        format!("sha256={}", sign("s3cret", timestamp, &request.body))
+      );
+      let body: Value = serde_json::from_str(&request.body).unwrap();
+      events.push((
+        body["event"].as_str().unwrap().to_string(),
+        body["task"]["id"].as_str().unwrap().to_string(),
+      ));
+    }
+    events.sort();
+    assert_eq!(
+      events,
+      [
+        ("task.overdue".to_string(), late.id),
+        ("task.upcoming".to_string(), soon.id)
+      ]
+    );
+
+    // Already queued: a second tick sends nothing.
+    assert_eq!(run_due(&store, now).unwrap(), DeliveryReport::default());
+  }
+
+  #[test]
+  fn failed_deliveries_stay_queued_and_retry_after_backoff() {
+    let (url, received) = stand_in(vec![500, 204]);
+    let store = enabled_store("custom", &url, None);
+    let now = Utc::now();
+    task_due(&store, "soon", now + TimeDelta::minutes(10));
+
+    let first = run_due(&store, now).unwrap();
+    assert_eq!(first.retrying, 1);
+    let unsigned = received.recv().unwrap();
+    assert!(unsigned.signature.is_none());
+    let entry = &store.webhook_outbox().unwrap()[0];
+    assert_eq!(entry.attempts, 1);
+    assert_eq!(
+      entry.last_error.as_deref(),
+      Some("webhook delivery failed: HTTP 500")
+    );
+
+    // Not due again until the backoff has passed.
+    assert_eq!(run_due(&store, now).unwrap(), DeliveryReport::default());
+    let later = now + backoff(1);
+    assert_eq!(run_due(&store, later).unwrap().delivered, 1);
+    received.recv().unwrap();
+    assert_eq!(store.webhook_outbox().unwrap()[0].status, "delivered");
+  }
+
+  #[test]
+  fn test_message_uses_the_bot_text_format() {
+    let (url, received) = stand_in(vec![200]);
+    send_test(&WebhookConfig {
+      kind: WebhookKind::Dingtalk,
+      url,
+      secret: None,
+    })
+    .unwrap();
+    let body: Value = serde_json::from_str(&received.recv().unwrap().body).unwrap();
+    assert_eq!(body["msgtype"], "text");
+    assert!(body["text"]["content"]
+      .as_str()
+      .unwrap()
+      .contains("测试消息"));
+  }
+
+  #[test]
+  fn bot_signatures_match_the_documented_algorithms() {
+    assert_eq!(
+      feishu_sign("SEC123", 1_700_000_000),
+      "j/tImR0k8vYXRsYw0+GHVQkV1v/J/8obOuMU7PE/KDo="
+    );
+    assert_eq!(
+      dingtalk_sign("SEC123", 1_700_000_000_000),
+      "lkcPI1uoxBY1gUnCnnPH1Kkru0Hqjo7rFpA3haIVhEQ="
+    );
+  }
+
+  #[test]
+  fn feishu_requests_carry_timestamp_and_sign_in_the_body() {
+    let (url, received) = stand_in_replying(vec![(200, r#"{"code":0,"msg":"success"}"#)]);
+    send_test(&WebhookConfig {
+      kind: WebhookKind::Feishu,
+      url,
+      secret: Some("SEC123".into()),
+    })
+    .unwrap();
+
+    let request = received.recv().unwrap();
+    assert_eq!(request.url, "/hook");
+    assert!(request.timestamp.is_none() && request.signature.is_none());
+    let body: Value = serde_json::from_str(&request.body).unwrap();
+    assert_eq!(body["msg_type"], "text");
+    let timestamp = body["timestamp"].as_str().unwrap();
+    assert!((Utc::now().timestamp() - timestamp.parse::<i64>().unwrap()).abs() < 60);
+    assert_eq!(
+      body["sign"],
+      feishu_sign("SEC123", timestamp.parse().unwrap())
+    );
+  }
+
+  #[test]
+  fn dingtalk_requests_carry_timestamp_and_sign_in_the_query() {
+    let (url, received) = stand_in_replying(vec![(200, r#"{"errcode":0,"errmsg":"ok"}"#)]);
+    send_test(&WebhookConfig {
+      kind: WebhookKind::Dingtalk,
+      url: format!("{url}?access_token=abc"),
+      secret: Some("SEC123".into()),
+    })
+    .unwrap();
+
+    let request = received.recv().unwrap();
+    assert!(request.timestamp.is_none() && request.signature.is_none());
+    let sent = url::Url::parse(&format!("http://stand-in{}", request.url)).unwrap();
+    assert_eq!(sent.path(), "/hook");
+    let query: Vec<(String, String)> = sent.query_pairs().into_owned().collect();
+    assert_eq!(query[0], ("access_token".to_string(), "abc".to_string()));
+    let timestamp: i64 = query[1].1.parse().unwrap();
+    assert_eq!(query[1].0, "timestamp");
+    assert!((Utc::now().timestamp_millis() - timestamp).abs() < 60_000);
+    assert_eq!(
+      query[2],
+      ("sign".to_string(), dingtalk_sign("SEC123", timestamp))
+    );
+    let body: Value = serde_json::from_str(&request.body).unwrap();
+    assert_eq!(body["msgtype"], "text");
+    assert!(body.get("timestamp").is_none() && body.get("sign").is_none());
+  }
+
+  #[test]
+  fn client_errors_and_rejected_messages_are_not_retried() {
+    let (url, received) = stand_in(vec![404]);
+    let store = enabled_store("custom", &url, None);
+    let now = Utc::now();
+    task_due(&store, "soon", now + TimeDelta::minutes(10));
+    assert_eq!(run_due(&store, now).unwrap().failed, 1);
+    received.recv().unwrap();
+    let entry = &store.webhook_outbox().unwrap()[0];
+    assert_eq!((entry.status.as_str(), entry.attempts), ("failed", 1));
+
+    let (url, received) = stand_in_replying(vec![
+      (200, r#"{"code":19021,"msg":"sign match fail"}"#),
+      (200, r#"{"errcode":130101,"errmsg":"send too fast"}"#),
+    ]);
+    let store = enabled_store("feishu", &url, Some("SEC123"));
+    task_due(&store, "soon", now + TimeDelta::minutes(10));
+    assert_eq!(run_due(&store, now).unwrap().failed, 1);
+    received.recv().unwrap();
+    assert_eq!(
+      store.webhook_outbox().unwrap()[0].last_error.as_deref(),
+      Some("webhook delivery failed: error 19021: sign match fail")
+    );
+
+    // Rate limits are worth another try.
+    let store = enabled_store("dingtalk", &url, None);
+    task_due(&store, "soon", now + TimeDelta::minutes(10));
+    assert_eq!(run_due(&store, now).unwrap().retrying, 1);
+    received.recv().unwrap();
+  }
+
+  #[test]
+  fn delivers_for_tasks_the_webview_hands_over() {
+    let (url, received) = stand_in(vec![200]);
+    let store = Store::open_in_memory().unwrap();
+    let now = Utc::now();
+    let config = WebhookConfig {
+      kind: WebhookKind::Custom,
+      url,
+      secret: None,
+    };
+    let task = |id: &str, completed: bool| Task {
+      id: id.into(),
+      title: id.into(),
+      date: Some((now + TimeDelta::minutes(10)).to_rfc3339()),
+      completed,
+      ..Default::default()
+    };
+    let tasks = [task("open", false), task("done", true)];
+
+    let report = run_with(&store, &config, &tasks, TimeDelta::minutes(30), now).unwrap();
+    assert_eq!(report.delivered, 1);
+    let body: Value = serde_json::from_str(&received.recv().unwrap().body).unwrap();
+    assert_eq!(body["task"]["id"], "open");
+    // The webview's tasks are never written here.
+    assert!(store
+      .get_tasks(&TaskFilter::default(), &[])
+      .unwrap()
+      .is_empty());
+  }
+}
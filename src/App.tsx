@@ -22,6 +22,8 @@ import { AuthProvider } from "@/contexts/AuthContext";
 import Auth from "@/pages/Auth";
 import AuthRoute from "@/components/AuthRoute";
 import AuthCallback from "@/pages/AuthCallback";
+import DeepLinkHandler from "@/components/DeepLinkHandler";
+import QuickCaptureHandler from "@/components/QuickCaptureHandler";
 
 // Create a client
 const queryClient = new QueryClient({
@@ -44,6 +46,8 @@ const App = () => {
                 <SidebarProvider>
                   <Toaster />
                   <Sonner />
+                  <DeepLinkHandler />
+                  <QuickCaptureHandler />
                   <Routes>
                     {/* 分享链接加入页，放在保护路由之外，页面内部处理未登录跳转 */}
                     <Route path="/join/:code" element={<JoinSharedProject />} />
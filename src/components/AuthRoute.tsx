@@ -3,7 +3,7 @@ import React from "react";
 import { Navigate, Outlet } from "react-router-dom";
 import { useAuth } from "@/contexts/AuthContext";
 import { Loader2 } from "lucide-react";
-import { isOfflineMode } from "@/storage";
+import { useStorageMode } from "@/hooks/useStorageMode";
 
 interface AuthRouteProps {
   children?: React.ReactNode;
@@ -11,9 +11,10 @@ interface AuthRouteProps {
 
 const AuthRoute: React.FC<AuthRouteProps> = ({ children }) => {
   const { user, loading, isGuest } = useAuth();
+  const mode = useStorageMode();
 
   // In offline mode, always allow access (no auth required)
-  if (isOfflineMode) {
+  if (mode === "offline") {
     return children ? <>{children}</> : <Outlet />;
   }
 
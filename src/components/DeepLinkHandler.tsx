@@ -0,0 +1,92 @@
+import { useCallback, useEffect, useRef } from "react";
+import { useNavigate } from "react-router-dom";
+import { supabase } from "@/integrations/supabase/client";
+import { useTaskContext } from "@/contexts/task";
+import { useToast } from "@/hooks/use-toast";
+import { invokeTauri, isTauriRuntime, listenTauriEvent } from "@/utils/runtime";
+
+/** Mirrors `deep_link::DeepLink` on the Rust side. */
+type DeepLink =
+  | { type: "authCallback"; params: Record<string, string> }
+  | { type: "task"; id: string }
+  | { type: "project"; id: string }
+  | { type: "join"; code: string };
+
+/**
+ * 处理桌面端的 snailtodo:// 链接。
+ * 链接先在 Rust 侧排队（应用可能正是被链接唤起的，此时页面还没加载），
+ * 挂载时以及收到 deep-link://open 事件时取出并逐个处理。
+ */
+const DeepLinkHandler = () => {
+  const navigate = useNavigate();
+  const { toast } = useToast();
+  const { tasks, selectTask, selectProject } = useTaskContext();
+
+  // 事件回调里总是使用最新的任务列表
+  const tasksRef = useRef(tasks);
+  tasksRef.current = tasks;
+
+  const finishSignIn = useCallback(async (params: Record<string, string>) => {
+    const { error } = params.code
+      ? await supabase.auth.exchangeCodeForSession(params.code)
+      : params.access_token
+        ? await supabase.auth.setSession({
+            access_token: params.access_token,
+            refresh_token: params.refresh_token || "",
+          })
+        : { error: new Error(params.error_description || "未找到有效的认证信息") };
+
+    if (error) {
+      toast({ title: "登录失败", description: error.message, variant: "destructive" });
+      navigate("/auth", { replace: true });
+      return;
+    }
+
+    let target: string | null = null;
+    try { target = localStorage.getItem("post_login_redirect"); } catch {}
+    if (target) {
+      try { localStorage.removeItem("post_login_redirect"); } catch {}
+    }
+    navigate(target || "/", { replace: true });
+  }, [navigate, toast]);
+
+  const open = useCallback((link: DeepLink) => {
+    switch (link.type) {
+      case "authCallback":
+        void finishSignIn(link.params);
+        break;
+      case "task": {
+        const task = tasksRef.current.find((t) => t.id === link.id);
+        navigate("/");
+        if (task?.project) selectProject(task.project);
+        selectTask(link.id);
+        break;
+      }
+      case "project":
+        navigate("/");
+        selectProject(link.id);
+        break;
+      case "join":
+        // 与网页分享链接 /join/:code 走同一个页面
+        navigate(`/join/${encodeURIComponent(link.code)}`);
+        break;
+    }
+  }, [finishSignIn, navigate, selectProject, selectTask]);
+
+  useEffect(() => {
+    if (!isTauriRuntime()) return;
+
+    const drain = () => {
+      invokeTauri<DeepLink[]>("take_pending_deep_links")
+        .then((links) => links.forEach(open))
+        .catch((e) => console.error("Failed to read deep links:", e));
+    };
+
+    drain();
+    return listenTauriEvent("deep-link://open", drain);
+  }, [open]);
+
+  return null;
+};
+
+export default DeepLinkHandler;
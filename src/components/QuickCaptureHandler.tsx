@@ -0,0 +1,60 @@
+import { useEffect, useRef } from "react";
+import { useTaskContext } from "@/contexts/task";
+import { useProjectContext } from "@/contexts/ProjectContext";
+import { useToast } from "@/hooks/use-toast";
+import {
+  CAPTURED_EVENT,
+  QuickAddOps,
+  publishCaptureProjects,
+  saveCapturedTask,
+  takeCapturedTasks,
+} from "@/services/quickAddService";
+import { isTauriRuntime, listenTauriEvent } from "@/utils/runtime";
+
+/**
+ * 保存桌面端快速添加窗口提交的任务。
+ * 任务先在 Rust 侧排队（主窗口可能还没加载完），挂载时以及收到 capture://task 事件时取出，
+ * 经当前存储模式的适配器保存，与在主窗口输入框中添加的任务一样出现在列表里。
+ */
+const QuickCaptureHandler = () => {
+  const { toast } = useToast();
+  const { addTask, listAllTags, createTag, attachTagToTask } = useTaskContext();
+  const { projects } = useProjectContext();
+
+  // 事件回调里总是使用最新的任务操作
+  const opsRef = useRef<QuickAddOps>({ addTask, listAllTags, createTag, attachTagToTask });
+  opsRef.current = { addTask, listAllTags, createTag, attachTagToTask };
+
+  useEffect(() => {
+    if (!isTauriRuntime()) return;
+    publishCaptureProjects(projects).catch((e) => console.error("Failed to publish projects:", e));
+  }, [projects]);
+
+  useEffect(() => {
+    if (!isTauriRuntime()) return;
+
+    // 逐个保存，避免并发取出时重复
+    let draining: Promise<void> = Promise.resolve();
+    const drain = () => {
+      draining = draining.then(async () => {
+        try {
+          for (const captured of await takeCapturedTasks()) {
+            const task = await saveCapturedTask(captured, opsRef.current);
+            if (!task) {
+              toast({ title: "快速添加失败", description: captured.title, variant: "destructive" });
+            }
+          }
+        } catch (e) {
+          console.error("Failed to save captured tasks:", e);
+        }
+      });
+    };
+
+    drain();
+    return listenTauriEvent(CAPTURED_EVENT, drain);
+  }, [toast]);
+
+  return null;
+};
+
+export default QuickCaptureHandler;
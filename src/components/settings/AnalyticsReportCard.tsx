@@ -0,0 +1,107 @@
+import { useState } from "react";
+import { format, startOfMonth, subMonths, endOfMonth } from "date-fns";
+import { BarChart3, Loader2 } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "@/components/ui/card";
+import { toast } from "@/hooks/use-toast";
+import { downloadAnalyticsReport } from "@/services/analyticsService";
+import { downloadTimesheet } from "@/services/timeTrackingService";
+import { ReportFormat } from "@/types/analytics";
+
+const DAY_FORMAT = "yyyy-MM-dd";
+
+// "timesheet" 是逐条列出时间记录的时间表，其余为汇总报告的格式
+type ExportKind = ReportFormat | "timesheet";
+
+const FORMATS: { value: ExportKind; label: string }[] = [
+  { value: "html", label: "HTML 报告" },
+  { value: "csv", label: "CSV" },
+  { value: "json", label: "JSON" },
+  { value: "timesheet", label: "时间表 CSV" },
+];
+
+// 默认选中上个月，方便月度复盘
+const lastMonth = () => {
+  const month = subMonths(new Date(), 1);
+  return { from: format(startOfMonth(month), DAY_FORMAT), to: format(endOfMonth(month), DAY_FORMAT) };
+};
+
+// 效率报告导出，仅桌面端
+const AnalyticsReportCard = () => {
+  const [range, setRange] = useState(lastMonth);
+  const [busy, setBusy] = useState<ExportKind | null>(null);
+
+  const handleExport = async (kind: ExportKind) => {
+    setBusy(kind);
+    try {
+      const fileName =
+        kind === "timesheet"
+          ? await downloadTimesheet(range.from, range.to)
+          : await downloadAnalyticsReport(range.from, range.to, kind);
+      toast({ title: "导出成功", description: fileName });
+    } catch (error) {
+      toast({
+        title: "导出失败",
+        description: error instanceof Error ? error.message : String(error),
+        variant: "destructive",
+      });
+    } finally {
+      setBusy(null);
+    }
+  };
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="flex items-center gap-2">
+          <BarChart3 className="h-5 w-5" />
+          效率报告
+        </CardTitle>
+        <CardDescription>
+          按项目、标签、星期和时段汇总番茄、完成任务与打卡，可用于月度复盘；时间表逐条列出任务计时
+        </CardDescription>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <div className="flex flex-wrap items-end gap-3">
+          <div className="space-y-1">
+            <Label htmlFor="report-from">开始日期</Label>
+            <Input
+              id="report-from"
+              type="date"
+              value={range.from}
+              max={range.to}
+              onChange={(e) => setRange((prev) => ({ ...prev, from: e.target.value }))}
+            />
+          </div>
+          <div className="space-y-1">
+            <Label htmlFor="report-to">结束日期</Label>
+            <Input
+              id="report-to"
+              type="date"
+              value={range.to}
+              min={range.from}
+              onChange={(e) => setRange((prev) => ({ ...prev, to: e.target.value }))}
+            />
+          </div>
+        </div>
+        <div className="flex flex-wrap gap-2">
+          {FORMATS.map((item) => (
+            <Button
+              key={item.value}
+              variant={item.value === "html" ? "default" : "outline"}
+              disabled={busy !== null || !range.from || !range.to}
+              onClick={() => handleExport(item.value)}
+            >
+              {busy === item.value && <Loader2 className="mr-2 h-4 w-4 animate-spin" />}
+              {item.label}
+            </Button>
+          ))}
+        </div>
+      </CardContent>
+    </Card>
+  );
+};
+
+export default AnalyticsReportCard;
@@ -0,0 +1,124 @@
+import { useRef, useState } from "react";
+import { CalendarDays, Loader2 } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { Label } from "@/components/ui/label";
+import { Switch } from "@/components/ui/switch";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "@/components/ui/card";
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "@/components/ui/select";
+import { useProjectContext } from "@/contexts/ProjectContext";
+import { toast } from "@/hooks/use-toast";
+import { downloadIcs, importIcsFile } from "@/services/icalService";
+
+// Select 不允许空字符串作为值
+const ALL_PROJECTS = "__all__";
+
+// iCalendar 导出与导入，仅桌面端
+const CalendarExchangeCard = () => {
+  const { projects, refreshProjects } = useProjectContext();
+  const [projectId, setProjectId] = useState(ALL_PROJECTS);
+  const [events, setEvents] = useState(false);
+  const [busy, setBusy] = useState<"export" | "import" | null>(null);
+  const fileInputRef = useRef<HTMLInputElement>(null);
+  const selectedProject = projectId === ALL_PROJECTS ? null : projectId;
+
+  const fail = (title: string, error: unknown) =>
+    toast({
+      title,
+      description: error instanceof Error ? error.message : String(error),
+      variant: "destructive",
+    });
+
+  const handleExport = async () => {
+    setBusy("export");
+    try {
+      const fileName = await downloadIcs({ project_id: selectedProject, events });
+      toast({ title: "导出成功", description: fileName });
+    } catch (error) {
+      fail("导出失败", error);
+    } finally {
+      setBusy(null);
+    }
+  };
+
+  const handleImport = async (event: React.ChangeEvent<HTMLInputElement>) => {
+    const file = event.target.files?.[0];
+    event.target.value = "";
+    if (!file) return;
+    setBusy("import");
+    try {
+      const summary = await importIcsFile(file, selectedProject);
+      await refreshProjects();
+      toast({
+        title: "导入完成",
+        description: `新建 ${summary.created} 个、更新 ${summary.updated} 个任务${
+          summary.skipped > 0 ? `，跳过 ${summary.skipped} 个无标题条目` : ""
+        }`,
+      });
+    } catch (error) {
+      fail("导入失败", error);
+    } finally {
+      setBusy(null);
+    }
+  };
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="flex items-center gap-2">
+          <CalendarDays className="h-5 w-5" />
+          日历（iCalendar）
+        </CardTitle>
+        <CardDescription>
+          导出为 .ics 后可在系统日历、Thunderbird 等应用中查看截止日期；导入 .ics 中的待办与日程，重复导入同一文件会更新已有任务
+        </CardDescription>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <div className="flex flex-wrap items-end gap-4">
+          <div className="space-y-1 min-w-48">
+            <Label>清单</Label>
+            <Select value={projectId} onValueChange={setProjectId}>
+              <SelectTrigger>
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                <SelectItem value={ALL_PROJECTS}>全部清单</SelectItem>
+                {projects.map((project) => (
+                  <SelectItem key={project.id} value={project.id}>
+                    {project.name}
+                  </SelectItem>
+                ))}
+              </SelectContent>
+            </Select>
+          </div>
+          <div className="flex items-center gap-2 pb-2">
+            <Switch id="ics-events" checked={events} onCheckedChange={setEvents} />
+            <Label htmlFor="ics-events">导出为日程（适用于不显示待办的日历）</Label>
+          </div>
+        </div>
+        <div className="flex flex-wrap gap-2">
+          <Button disabled={busy !== null} onClick={handleExport}>
+            {busy === "export" && <Loader2 className="mr-2 h-4 w-4 animate-spin" />}
+            导出 .ics
+          </Button>
+          <Button
+            variant="outline"
+            disabled={busy !== null}
+            onClick={() => fileInputRef.current?.click()}
+          >
+            {busy === "import" && <Loader2 className="mr-2 h-4 w-4 animate-spin" />}
+            导入 .ics
+          </Button>
+          <input
+            ref={fileInputRef}
+            type="file"
+            accept=".ics,text/calendar"
+            className="hidden"
+            onChange={handleImport}
+          />
+        </div>
+      </CardContent>
+    </Card>
+  );
+};
+
+export default CalendarExchangeCard;
@@ -0,0 +1,216 @@
+import { useEffect, useState } from "react";
+import { format } from "date-fns";
+import { Copy, KeyRound, Rss, Trash2 } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { Switch } from "@/components/ui/switch";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "@/components/ui/card";
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "@/components/ui/select";
+import { useProjectContext } from "@/contexts/ProjectContext";
+import { toast } from "@/hooks/use-toast";
+import { CalendarFeedOverview } from "@/types/calendarFeed";
+import {
+  caldavUrl,
+  calendarFeedUrl,
+  createCalendarFeed,
+  deleteCalendarFeed,
+  fetchCalendarFeeds,
+  saveCalendarFeedSettings,
+} from "@/services/calendarFeedService";
+
+// Select 不允许空字符串作为值
+const ALL_PROJECTS = "__all__";
+
+// 本地日历订阅（webcal）与 CalDAV，仅桌面端
+const CalendarFeedCard = () => {
+  const { projects } = useProjectContext();
+  const [overview, setOverview] = useState<CalendarFeedOverview | null>(null);
+  const [port, setPort] = useState("");
+  const [name, setName] = useState("");
+  const [projectId, setProjectId] = useState(ALL_PROJECTS);
+  const [events, setEvents] = useState(false);
+
+  const fail = (title: string, error: unknown) =>
+    toast({
+      title,
+      description: error instanceof Error ? error.message : String(error),
+      variant: "destructive",
+    });
+
+  const apply = async (action: () => Promise<CalendarFeedOverview>, failure: string) => {
+    try {
+      const next = await action();
+      setOverview(next);
+      setPort(String(next.settings.port));
+      return next;
+    } catch (error) {
+      fail(failure, error);
+      return null;
+    }
+  };
+
+  useEffect(() => {
+    void apply(fetchCalendarFeeds, "读取订阅设置失败");
+  }, []);
+
+  if (!overview) return null;
+  const { settings, runningPort, host, feeds } = overview;
+
+  const toggle = async (enabled: boolean) => {
+    const next = await apply(() => saveCalendarFeedSettings({ ...settings, enabled }), "保存失败");
+    if (next && enabled && next.runningPort === null) {
+      fail("订阅服务未能启动", `端口 ${next.settings.port} 可能已被占用`);
+    }
+  };
+
+  const savePort = () => {
+    const value = Number.parseInt(port, 10);
+    if (value === settings.port) return;
+    if (!Number.isInteger(value) || value < 1024 || value > 65535) {
+      fail("端口无效", "请输入 1024 到 65535 之间的端口");
+      setPort(String(settings.port));
+      return;
+    }
+    void apply(() => saveCalendarFeedSettings({ ...settings, port: value }), "保存失败");
+  };
+
+  const addFeed = async () => {
+    if (await apply(() => createCalendarFeed(name.trim() || "日历"), "创建订阅失败")) setName("");
+  };
+
+  const copy = (text: string, title: string, description?: string) => {
+    navigator.clipboard.writeText(text).then(() => {
+      toast({ title, description });
+    });
+  };
+
+  const copyUrl = (token: string) => {
+    const url = calendarFeedUrl(host, runningPort ?? settings.port, token, {
+      project: projectId === ALL_PROJECTS ? null : projectId,
+      events,
+    });
+    copy(url, "订阅链接已复制", "在日历应用中选择“通过 URL 订阅”并粘贴");
+  };
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="flex items-center gap-2">
+          <Rss className="h-5 w-5" />
+          日历订阅
+        </CardTitle>
+        <CardDescription>
+          在本机提供只读的 webcal 订阅，系统日历、Thunderbird 等会定期拉取有截止日期的任务；每个订阅有独立的访问令牌，删除后链接立即失效。
+          支持 CalDAV 的客户端可双向同步：服务器地址填写 {caldavUrl(host, runningPort ?? settings.port)}，用户名任意，密码为订阅令牌。
+          默认仅本机可访问，手机等设备需开启局域网访问
+        </CardDescription>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <div className="flex flex-wrap items-center gap-4">
+          <div className="flex items-center gap-2">
+            <Switch id="calendar-feed-enabled" checked={settings.enabled} onCheckedChange={toggle} />
+            <Label htmlFor="calendar-feed-enabled">
+              {settings.enabled ? (runningPort !== null ? `运行中（端口 ${runningPort}）` : "未能启动") : "已关闭"}
+            </Label>
+          </div>
+          <div className="flex items-center gap-2">
+            <Label htmlFor="calendar-feed-port">端口</Label>
+            <Input
+              id="calendar-feed-port"
+              type="number"
+              min={1024}
+              max={65535}
+              value={port}
+              onChange={(e) => setPort(e.target.value)}
+              onBlur={savePort}
+              onKeyDown={(e) => e.key === "Enter" && savePort()}
+              className="h-8 w-28"
+            />
+          </div>
+          <div className="flex items-center gap-2">
+            <Switch
+              id="calendar-feed-lan"
+              checked={settings.lan}
+              onCheckedChange={(lan) => apply(() => saveCalendarFeedSettings({ ...settings, lan }), "保存失败")}
+            />
+            <Label htmlFor="calendar-feed-lan">局域网访问</Label>
+          </div>
+        </div>
+        {settings.lan && (
+          <p className="text-sm text-destructive">
+            局域网访问使用未加密的 HTTP：同一网络中的他人可截获订阅令牌和任务内容，请只在可信网络（如家庭网络）中开启
+          </p>
+        )}
+        <div className="flex flex-wrap items-end gap-4">
+          <div className="space-y-1 min-w-48">
+            <Label>链接筛选</Label>
+            <Select value={projectId} onValueChange={setProjectId}>
+              <SelectTrigger>
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                <SelectItem value={ALL_PROJECTS}>全部清单</SelectItem>
+                {projects.map((project) => (
+                  <SelectItem key={project.id} value={project.id}>
+                    {project.name}
+                  </SelectItem>
+                ))}
+              </SelectContent>
+            </Select>
+          </div>
+          <div className="flex items-center gap-2 pb-2">
+            <Switch id="calendar-feed-events" checked={events} onCheckedChange={setEvents} />
+            <Label htmlFor="calendar-feed-events">以日程发布</Label>
+          </div>
+        </div>
+        {feeds.length > 0 && (
+          <div className="divide-y rounded-md border">
+            {feeds.map((feed) => (
+              <div key={feed.id} className="flex items-center gap-2 px-3 py-2 text-sm">
+                <span className="flex-1 truncate">{feed.name}</span>
+                <span className="text-xs text-muted-foreground">
+                  {feed.last_fetched_at
+                    ? `最近拉取 ${format(new Date(feed.last_fetched_at), "MM-dd HH:mm")}`
+                    : "尚未拉取"}
+                </span>
+                <Button size="sm" variant="ghost" onClick={() => copyUrl(feed.token)} title="复制订阅链接">
+                  <Copy className="h-4 w-4" />
+                </Button>
+                <Button
+                  size="sm"
+                  variant="ghost"
+                  onClick={() => copy(feed.token, "CalDAV 密码已复制", "用户名可任意填写")}
+                  title="复制 CalDAV 密码"
+                >
+                  <KeyRound className="h-4 w-4" />
+                </Button>
+                <Button
+                  size="sm"
+                  variant="ghost"
+                  onClick={() => apply(() => deleteCalendarFeed(feed.id), "删除订阅失败")}
+                  title="删除订阅"
+                >
+                  <Trash2 className="h-4 w-4" />
+                </Button>
+              </div>
+            ))}
+          </div>
+        )}
+        <div className="flex gap-2">
+          <Input
+            value={name}
+            onChange={(e) => setName(e.target.value)}
+            placeholder="订阅名称，如 Thunderbird"
+            className="max-w-64"
+          />
+          <Button variant="outline" onClick={addFeed}>
+            新建订阅
+          </Button>
+        </div>
+      </CardContent>
+    </Card>
+  );
+};
+
+export default CalendarFeedCard;
@@ -13,10 +13,17 @@ import ModeSwitchDialog from "./ModeSwitchDialog";
 import { useProjectContext } from "@/contexts/ProjectContext";
 import { useQueryClient } from "@tanstack/react-query";
 import { toast } from "@/hooks/use-toast";
-import { getStorageConfig, setStorageMode, type StorageMode } from "@/config/storage";
+import { setStorageMode, type StorageMode } from "@/config/storage";
 import { useAuth } from "@/contexts/AuthContext";
+import { useStorageMode } from "@/hooks/useStorageMode";
+import { useNavigate } from "react-router-dom";
 import { Cloud, HardDrive } from "lucide-react";
-import { navigateWithReload } from "@/utils/runtime";
+import { isTauriRuntime, navigateWithReload } from "@/utils/runtime";
+import AnalyticsReportCard from "./AnalyticsReportCard";
+import CalendarExchangeCard from "./CalendarExchangeCard";
+import CalendarFeedCard from "./CalendarFeedCard";
+import SyncSettingsCard from "./SyncSettingsCard";
+import PeerSyncCard from "./PeerSyncCard";
 
 const DataManagementSettings = () => {
   const fileInputRef = useRef<HTMLInputElement>(null);
@@ -24,8 +31,11 @@ const DataManagementSettings = () => {
   const queryClient = useQueryClient();
   const { signOut } = useAuth();
 
+  const navigate = useNavigate();
+
   // Storage mode state
-  const currentConfig = getStorageConfig();
+  const mode = useStorageMode();
+  const desktop = isTauriRuntime();
   const [showModeSwitchDialog, setShowModeSwitchDialog] = useState(false);
   const [targetMode, setTargetMode] = useState<StorageMode>("supabase");
 
@@ -145,7 +155,7 @@ const DataManagementSettings = () => {
   };
 
   const handleModeChange = (newMode: string) => {
-    if (newMode === currentConfig.mode) return;
+    if (newMode === mode) return;
     setTargetMode(newMode as StorageMode);
     setShowModeSwitchDialog(true);
   };
@@ -154,20 +164,27 @@ const DataManagementSettings = () => {
     setShowModeSwitchDialog(false);
     
     // If switching from online to offline, sign out first
-    if (currentConfig.mode === "supabase" && targetMode === "offline") {
+    if (mode === "supabase" && targetMode === "offline") {
       await signOut();
     }
     
     // Set the new mode
     setStorageMode(targetMode);
-    
-    // Show toast and reload
+
+    // 桌面端两种模式都读写本地库，切换即生效；浏览器中需重新加载以换用另一个存储适配器
+    if (desktop) {
+      toast({
+        title: "存储模式已切换",
+        description: targetMode === "offline" ? "已停止与云端同步" : "登录后将与云端同步",
+      });
+      navigate("/");
+      return;
+    }
+
     toast({
       title: "存储模式已切换",
       description: "页面将重新加载以应用新设置",
     });
-    
-    // Reload to apply new storage mode
     setTimeout(() => {
       navigateWithReload("/");
     }, 500);
@@ -181,7 +198,7 @@ const DataManagementSettings = () => {
       <Card>
         <CardHeader>
           <CardTitle className="flex items-center gap-2">
-            {currentConfig.isOfflineMode ? (
+            {mode === "offline" ? (
               <HardDrive className="h-5 w-5" />
             ) : (
               <Cloud className="h-5 w-5" />
@@ -189,12 +206,14 @@ const DataManagementSettings = () => {
             存储模式
           </CardTitle>
           <CardDescription>
-            选择数据存储位置。切换模式后数据不会自动迁移。
+            {desktop
+              ? "桌面端的数据始终保存在本机，在线模式登录后与云端同步。"
+              : "选择数据存储位置。切换模式后数据不会自动迁移。"}
           </CardDescription>
         </CardHeader>
         <CardContent>
           <RadioGroup
-            value={currentConfig.mode}
+            value={mode}
             onValueChange={handleModeChange}
             className="space-y-3"
           >
@@ -205,7 +224,7 @@ const DataManagementSettings = () => {
                 <div>
                   <div className="font-medium">在线模式</div>
                   <div className="text-sm text-muted-foreground">
-                    数据存储在云端，支持多设备同步
+                    {desktop ? "数据保存在本机，登录后与云端双向同步" : "数据存储在云端，支持多设备同步"}
                   </div>
                 </div>
               </Label>
@@ -217,7 +236,7 @@ const DataManagementSettings = () => {
                 <div>
                   <div className="font-medium">离线模式</div>
                   <div className="text-sm text-muted-foreground">
-                    数据存储在本地浏览器，无需登录
+                    {desktop ? "数据只保存在本机，无需登录，不同步" : "数据存储在本地浏览器，无需登录"}
                   </div>
                 </div>
               </Label>
@@ -262,6 +281,16 @@ const DataManagementSettings = () => {
         </CardContent>
       </Card>
 
+      {isTauriRuntime() && <AnalyticsReportCard />}
+
+      {isTauriRuntime() && <CalendarExchangeCard />}
+
+      {isTauriRuntime() && <CalendarFeedCard />}
+
+      {isTauriRuntime() && <SyncSettingsCard />}
+
+      {isTauriRuntime() && <PeerSyncCard />}
+
       {/* Import Section */}
       <Card>
         <CardHeader>
@@ -9,6 +9,7 @@ import {
   AlertDialogTitle,
 } from "@/components/ui/alert-dialog";
 import type { StorageMode } from "@/config/storage";
+import { isTauriRuntime } from "@/utils/runtime";
 
 interface ModeSwitchDialogProps {
   open: boolean;
@@ -32,17 +33,27 @@ const ModeSwitchDialog = ({
         <AlertDialogHeader>
           <AlertDialogTitle>切换到{targetModeLabel}？</AlertDialogTitle>
           <AlertDialogDescription className="space-y-2">
-            <p>
-              切换存储模式后，您的数据<strong>不会自动迁移</strong>。
-            </p>
-            <p>
-              {isToOffline
-                ? "离线模式的数据存储在本地浏览器中，与云端数据完全隔离。"
-                : "在线模式的数据存储在云端，与本地数据完全隔离。"}
-            </p>
-            <p className="text-amber-600 dark:text-amber-400">
-              建议：切换前先导出当前数据，切换后可通过导入功能恢复。
-            </p>
+            {isTauriRuntime() ? (
+              <p>
+                {isToOffline
+                  ? "本机数据保持不变，但不再与云端同步。"
+                  : "登录后，本机数据与云端账户双向同步。"}
+              </p>
+            ) : (
+              <>
+                <p>
+                  切换存储模式后，您的数据<strong>不会自动迁移</strong>。
+                </p>
+                <p>
+                  {isToOffline
+                    ? "离线模式的数据存储在本地浏览器中，与云端数据完全隔离。"
+                    : "在线模式的数据存储在云端，与本地数据完全隔离。"}
+                </p>
+                <p className="text-amber-600 dark:text-amber-400">
+                  建议：切换前先导出当前数据，切换后可通过导入功能恢复。
+                </p>
+              </>
+            )}
             {!isToOffline && (
               <p className="text-muted-foreground">
                 切换到在线模式后，您需要重新登录。
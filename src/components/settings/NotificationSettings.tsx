@@ -11,6 +11,7 @@ import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "@/com
 import { Tabs, TabsContent, TabsList, TabsTrigger } from "@/components/ui/tabs";
 import { getDeadlineConfig, saveDeadlineConfig, requestNotificationPermission } from "@/services/deadlineService";
 import * as storageOps from "@/storage/operations";
+import { invokeTauri, isTauriRuntime } from "@/utils/runtime";
 
 type WebhookType = "feishu" | "dingtalk" | "custom";
 
@@ -101,9 +102,20 @@ const NotificationSettings = () => {
     setIsTesting(true);
 
     try {
-      // Here we would typically call a backend API to send a test message
-      // For now, we'll simulate an API call with a timeout
-      await new Promise(resolve => setTimeout(resolve, 1000));
+      if (isTauriRuntime()) {
+        // 桌面端由 Rust 进程直接发送，失败时会带回具体原因
+        await invokeTauri("send_test_webhook", {
+          config: {
+            kind: webhookConfig.type,
+            url: webhookConfig.url,
+            secret: webhookConfig.secret || null,
+          },
+        });
+      } else {
+        // Here we would typically call a backend API to send a test message
+        // For now, we'll simulate an API call with a timeout
+        await new Promise(resolve => setTimeout(resolve, 1000));
+      }
 
       toast({
         title: "测试消息已发送",
@@ -112,7 +124,7 @@ const NotificationSettings = () => {
     } catch (error) {
       toast({
         title: "测试失败",
-        description: "发送测试消息时出错",
+        description: typeof error === "string" ? error : "发送测试消息时出错",
         variant: "destructive",
       });
     } finally {
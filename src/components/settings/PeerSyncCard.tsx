@@ -0,0 +1,253 @@
+import { useEffect, useState } from "react";
+import { format } from "date-fns";
+import { KeyRound, Network, RefreshCw, Search, Trash2 } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { Switch } from "@/components/ui/switch";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "@/components/ui/card";
+import { toast } from "@/hooks/use-toast";
+import { DiscoveredPeer, PairingCode, PeerOverview } from "@/types/peer";
+import {
+  createPairingCode,
+  discoverPeers,
+  fetchPeers,
+  onPeersSynced,
+  pairPeer,
+  savePeerSettings,
+  syncPeers,
+  unpairPeer,
+} from "@/services/peerSyncService";
+
+// 局域网同步（不经 Supabase），仅桌面端
+const PeerSyncCard = () => {
+  const [overview, setOverview] = useState<PeerOverview | null>(null);
+  const [port, setPort] = useState("");
+  const [name, setName] = useState("");
+  const [pairing, setPairing] = useState<PairingCode | null>(null);
+  const [discovered, setDiscovered] = useState<DiscoveredPeer[] | null>(null);
+  const [address, setAddress] = useState("");
+  const [code, setCode] = useState("");
+  const [busy, setBusy] = useState(false);
+
+  const fail = (title: string, error: unknown) =>
+    toast({
+      title,
+      description: error instanceof Error ? error.message : String(error),
+      variant: "destructive",
+    });
+
+  const show = (next: PeerOverview) => {
+    setOverview(next);
+    setPort(String(next.settings.port));
+    setName(next.settings.name);
+  };
+
+  const apply = async (action: () => Promise<PeerOverview>, failure: string) => {
+    setBusy(true);
+    try {
+      const next = await action();
+      show(next);
+      return next;
+    } catch (error) {
+      fail(failure, error);
+      return null;
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  useEffect(() => {
+    void apply(fetchPeers, "读取局域网同步设置失败");
+    return onPeersSynced(show);
+  }, []);
+
+  if (!overview) return null;
+  const { settings, runningPort, peers } = overview;
+  const paired = new Set(peers.map((peer) => peer.id));
+
+  const toggle = async (enabled: boolean) => {
+    const next = await apply(() => savePeerSettings({ ...settings, enabled }), "保存失败");
+    if (next && enabled && next.runningPort === null) {
+      fail("局域网同步未能启动", `端口 ${next.settings.port} 可能已被占用`);
+    }
+    if (!enabled) setPairing(null);
+  };
+
+  const saveSettings = () => {
+    const value = Number.parseInt(port, 10);
+    if (value === settings.port && name.trim() === settings.name) return;
+    if (!Number.isInteger(value) || value < 1024 || value > 65535) {
+      fail("端口无效", "请输入 1024 到 65535 之间的端口");
+      setPort(String(settings.port));
+      return;
+    }
+    void apply(() => savePeerSettings({ ...settings, port: value, name: name.trim() }), "保存失败");
+  };
+
+  const newCode = async () => {
+    try {
+      setPairing(await createPairingCode());
+    } catch (error) {
+      fail("生成配对码失败", error);
+    }
+  };
+
+  const discover = async () => {
+    setBusy(true);
+    try {
+      const found = await discoverPeers();
+      setDiscovered(found);
+      if (found.length > 0 && !address) setAddress(found[0].addresses[0] ?? "");
+    } catch (error) {
+      fail("查找设备失败", error);
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const pair = async () => {
+    const next = await apply(() => pairPeer(address.trim(), code.trim()), "配对失败");
+    if (next) {
+      setCode("");
+      toast({ title: "配对成功", description: "已完成首次同步" });
+    }
+  };
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="flex items-center gap-2">
+          <Network className="h-5 w-5" />
+          局域网同步
+        </CardTitle>
+        <CardDescription>
+          不经过云端，在同一网络中的设备之间直接同步清单、标签和任务。在一台设备上生成配对码，在另一台设备上输入即可配对；
+          配对码只能使用一次，之后的数据经加密通道传输
+        </CardDescription>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <div className="flex flex-wrap items-center gap-4">
+          <div className="flex items-center gap-2">
+            <Switch id="peer-sync-enabled" checked={settings.enabled} onCheckedChange={toggle} disabled={busy} />
+            <Label htmlFor="peer-sync-enabled">
+              {settings.enabled ? (runningPort !== null ? `运行中（端口 ${runningPort}）` : "未能启动") : "已关闭"}
+            </Label>
+          </div>
+          <div className="flex items-center gap-2">
+            <Label htmlFor="peer-sync-port">端口</Label>
+            <Input
+              id="peer-sync-port"
+              type="number"
+              min={1024}
+              max={65535}
+              value={port}
+              onChange={(e) => setPort(e.target.value)}
+              onBlur={saveSettings}
+              onKeyDown={(e) => e.key === "Enter" && saveSettings()}
+              className="h-8 w-28"
+            />
+          </div>
+          <div className="flex items-center gap-2">
+            <Label htmlFor="peer-sync-name">设备名称</Label>
+            <Input
+              id="peer-sync-name"
+              value={name}
+              placeholder={overview.name}
+              onChange={(e) => setName(e.target.value)}
+              onBlur={saveSettings}
+              onKeyDown={(e) => e.key === "Enter" && saveSettings()}
+              className="h-8 w-40"
+            />
+          </div>
+        </div>
+
+        {settings.enabled && runningPort !== null && (
+          <div className="flex flex-wrap items-center gap-3">
+            <Button variant="outline" size="sm" onClick={newCode}>
+              <KeyRound className="mr-2 h-4 w-4" />
+              生成配对码
+            </Button>
+            {pairing && (
+              <span className="text-sm text-muted-foreground">
+                在另一台设备上输入 <span className="font-mono text-base text-foreground">{pairing.code}</span>
+                ，{Math.round(pairing.expiresInSeconds / 60)} 分钟内有效（端口 {pairing.port}）
+              </span>
+            )}
+          </div>
+        )}
+
+        <div className="space-y-2">
+          <div className="flex flex-wrap items-center gap-2">
+            <Button variant="outline" size="sm" onClick={discover} disabled={busy}>
+              <Search className="mr-2 h-4 w-4" />
+              查找设备
+            </Button>
+            {discovered?.length === 0 && <span className="text-sm text-muted-foreground">未发现其他设备</span>}
+            {discovered
+              ?.filter((peer) => !paired.has(peer.replica))
+              .map((peer) => (
+                <Button
+                  key={peer.replica}
+                  size="sm"
+                  variant={address === peer.addresses[0] ? "secondary" : "ghost"}
+                  onClick={() => setAddress(peer.addresses[0] ?? "")}
+                >
+                  {peer.name}
+                </Button>
+              ))}
+          </div>
+          <div className="flex flex-wrap gap-2">
+            <Input
+              value={address}
+              onChange={(e) => setAddress(e.target.value)}
+              placeholder="地址，如 192.168.1.20:47614"
+              className="max-w-56"
+            />
+            <Input
+              value={code}
+              onChange={(e) => setCode(e.target.value)}
+              placeholder="6 位配对码"
+              inputMode="numeric"
+              maxLength={6}
+              className="w-32"
+            />
+            <Button variant="outline" onClick={pair} disabled={busy || !address.trim() || code.trim().length !== 6}>
+              配对
+            </Button>
+          </div>
+        </div>
+
+        {peers.length > 0 && (
+          <>
+            <div className="divide-y rounded-md border">
+              {peers.map((peer) => (
+                <div key={peer.id} className="flex items-center gap-2 px-3 py-2 text-sm">
+                  <span className="flex-1 truncate">{peer.name}</span>
+                  <span className="text-xs text-muted-foreground">{peer.address ?? ""}</span>
+                  <span className="text-xs text-muted-foreground">
+                    {peer.lastSyncedAt ? `最近同步 ${format(new Date(peer.lastSyncedAt), "MM-dd HH:mm")}` : "尚未同步"}
+                  </span>
+                  <Button
+                    size="sm"
+                    variant="ghost"
+                    onClick={() => apply(() => unpairPeer(peer.id), "取消配对失败")}
+                    title="取消配对"
+                  >
+                    <Trash2 className="h-4 w-4" />
+                  </Button>
+                </div>
+              ))}
+            </div>
+            <Button variant="outline" size="sm" onClick={() => apply(syncPeers, "同步失败")} disabled={busy}>
+              <RefreshCw className="mr-2 h-4 w-4" />
+              立即同步
+            </Button>
+          </>
+        )}
+      </CardContent>
+    </Card>
+  );
+};
+
+export default PeerSyncCard;
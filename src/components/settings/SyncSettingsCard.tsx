@@ -0,0 +1,146 @@
+import { useEffect, useState } from "react";
+import { format } from "date-fns";
+import { RefreshCw } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { Label } from "@/components/ui/label";
+import { Switch } from "@/components/ui/switch";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "@/components/ui/card";
+import { useAuth } from "@/contexts/AuthContext";
+import { getStorageConfig } from "@/config/storage";
+import { toast } from "@/hooks/use-toast";
+import { SyncConflict, SyncStatus } from "@/types/sync";
+import {
+  disableSync,
+  enableSync,
+  fetchSyncStatus,
+  isDesktopSyncEnabled,
+  onSyncConflict,
+  onSyncStatus,
+  syncNow,
+} from "@/services/syncService";
+
+const TABLE_NAMES: Record<SyncConflict["table"], string> = {
+  projects: "清单",
+  tags: "标签",
+  tasks: "任务",
+  task_tags: "任务标签",
+};
+
+const WINNER_NOTES: Record<SyncConflict["winner"], string> = {
+  local: "已保留本机的修改",
+  remote: "已采用云端的修改",
+  merged: "已合并两端的修改",
+};
+
+// 本地数据库与云端的后台同步，仅桌面端
+const SyncSettingsCard = () => {
+  const { session } = useAuth();
+  const [status, setStatus] = useState<SyncStatus | null>(null);
+  const [enabled, setEnabled] = useState(isDesktopSyncEnabled);
+  const [syncing, setSyncing] = useState(false);
+
+  const fail = (title: string, error: unknown) =>
+    toast({
+      title,
+      description: error instanceof Error ? error.message : String(error),
+      variant: "destructive",
+    });
+
+  useEffect(() => {
+    fetchSyncStatus()
+      .then(setStatus)
+      .catch((error) => fail("读取同步状态失败", error));
+    const stopStatus = onSyncStatus(setStatus);
+    const stopConflicts = onSyncConflict((conflict) =>
+      toast({
+        title: `${TABLE_NAMES[conflict.table]}在两端都被修改`,
+        description: WINNER_NOTES[conflict.winner],
+      }),
+    );
+    return () => {
+      stopStatus();
+      stopConflicts();
+    };
+  }, []);
+
+  if (!status) return null;
+  const offline = getStorageConfig().isOfflineMode;
+
+  const toggle = async (next: boolean) => {
+    setEnabled(next);
+    try {
+      if (next) {
+        await enableSync(session);
+        setStatus(await fetchSyncStatus());
+      } else {
+        setStatus(await disableSync());
+      }
+    } catch (error) {
+      fail(next ? "同步失败" : "关闭同步失败", error);
+    }
+  };
+
+  const runNow = async () => {
+    setSyncing(true);
+    try {
+      const report = await syncNow();
+      toast({ title: "同步完成", description: `拉取 ${report.pulled} 项，推送 ${report.pushed} 项` });
+    } catch (error) {
+      fail("同步失败", error);
+    } finally {
+      setSyncing(false);
+    }
+  };
+
+  const summary = () => {
+    if (offline) return "离线模式下不同步";
+    if (!session) return "登录后开始同步";
+    if (!status.enabled) return "已关闭";
+    const last = status.last_synced_at
+      ? `上次同步 ${format(new Date(status.last_synced_at), "MM-dd HH:mm")}`
+      : "尚未同步";
+    return status.pending > 0 ? `${last}，${status.pending} 项待推送` : last;
+  };
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="flex items-center gap-2">
+          <RefreshCw className="h-5 w-5" />
+          后台同步
+        </CardTitle>
+        <CardDescription>
+          本机的修改（包括命令行、托盘与 CalDAV 写入的任务）先保存在本地，联网后自动推送到云端，并每分钟拉取其他设备的修改；
+          同一项在两端都被修改时保留较新的版本，并提示冲突
+        </CardDescription>
+      </CardHeader>
+      <CardContent className="space-y-3">
+        <div className="flex flex-wrap items-center gap-4">
+          <div className="flex items-center gap-2">
+            <Switch
+              id="desktop-sync-enabled"
+              checked={enabled && !offline}
+              disabled={offline}
+              onCheckedChange={toggle}
+            />
+            <Label htmlFor="desktop-sync-enabled">{summary()}</Label>
+          </div>
+          <Button
+            variant="outline"
+            size="sm"
+            onClick={runNow}
+            disabled={syncing || offline || !session || !enabled}
+          >
+            <RefreshCw className={`mr-2 h-4 w-4 ${syncing ? "animate-spin" : ""}`} />
+            立即同步
+          </Button>
+        </div>
+        {status.last_error && enabled && (
+          <p className="text-sm text-destructive break-all">上次同步出错：{status.last_error}</p>
+        )}
+      </CardContent>
+    </Card>
+  );
+};
+
+export default SyncSettingsCard;
@@ -15,6 +15,7 @@ import UserMenu from "@/components/UserMenu";
 import { useAuth } from "@/contexts/AuthContext";
 import { Task } from "@/types/task";
 import { Skeleton } from "@/components/ui/skeleton";
+import { listenTauriEvent } from "@/utils/runtime";
 
 const AppSidebar = () => {
   const [collapsed, setCollapsed] = useState(false);
@@ -23,6 +24,13 @@ const AppSidebar = () => {
   const navigate = useNavigate();
   const { tasks } = useTaskContext();
 
+  // 托盘菜单「开始番茄钟」：切到番茄钟页面并自动开始计时
+  useEffect(() => {
+    return listenTauriEvent("tray://start-pomodoro", () => {
+      navigate("/pomodoro?autostart=1");
+    });
+  }, [navigate]);
+
   // 简化搜索实现
   const [searchQuery, setSearchQuery] = useState("");
   const [searchLoading, setSearchLoading] = useState(false);
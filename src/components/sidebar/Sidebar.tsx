@@ -11,6 +11,7 @@ import { useSidebar } from "@/contexts/SidebarContext";
 import { DragDropContext, Droppable, DropResult } from "@hello-pangea/dnd";
 import { Project } from "@/types/project";
 import CheckInButton from "@/components/sidebar/CheckInButton";
+import SmartListSection from "@/components/sidebar/SmartListSection";
 import { useToast } from "@/components/ui/use-toast";
 import { isToday, isBefore, startOfDay, addDays, parseISO, isValid, isWithinInterval } from 'date-fns';
 import { Skeleton } from "@/components/ui/skeleton";
@@ -165,6 +166,8 @@ const Sidebar: React.FC = () => {
 
       {!collapsed && (
         <div className="flex-grow flex flex-col">
+          <SmartListSection />
+
           <div className="px-3 py-2 flex items-center justify-between mb-2 border-t border-gray-200 dark:border-gray-600 pt-3">
             <span className="text-xs uppercase font-medium text-gray-500 dark:text-gray-400">清单</span>
             <div className="flex space-x-1">
@@ -0,0 +1,132 @@
+import React, { useEffect, useState } from "react";
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogFooter,
+  DialogHeader,
+  DialogTitle,
+} from "@/components/ui/dialog";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { SmartList } from "@/types/smartList";
+import { createSmartList, updateSmartList, validateQuery } from "@/services/smartListService";
+import { useSmartListStore } from "@/store/smartListStore";
+
+interface SmartListDialogProps {
+  open: boolean;
+  onOpenChange: (open: boolean) => void;
+  list: SmartList | null;
+  onSaved?: (list: SmartList) => void;
+}
+
+const QUERY_EXAMPLES = [
+  "project:工作 tag:紧急 due:<7d !completed",
+  "overdue OR (flagged due:today)",
+  "has:attachments sort:-updated limit:20",
+];
+
+const SmartListDialog: React.FC<SmartListDialogProps> = ({ open, onOpenChange, list, onSaved }) => {
+  const upsertList = useSmartListStore((state) => state.upsertList);
+  const [name, setName] = useState("");
+  const [query, setQuery] = useState("");
+  const [error, setError] = useState<string | null>(null);
+  const [saving, setSaving] = useState(false);
+
+  useEffect(() => {
+    if (open) {
+      setName(list?.name || "");
+      setQuery(list?.query || "");
+      setError(null);
+    }
+  }, [open, list]);
+
+  // 输入时实时校验查询语句
+  useEffect(() => {
+    if (!open || !query.trim()) {
+      setError(null);
+      return;
+    }
+    let cancelled = false;
+    const timer = setTimeout(async () => {
+      const message = await validateQuery(query);
+      if (!cancelled) setError(message);
+    }, 300);
+    return () => {
+      cancelled = true;
+      clearTimeout(timer);
+    };
+  }, [open, query]);
+
+  const handleSubmit = async (e: React.FormEvent) => {
+    e.preventDefault();
+    if (!name.trim() || error) return;
+    try {
+      setSaving(true);
+      const saved = list
+        ? await updateSmartList(list.id, { name: name.trim(), query })
+        : await createSmartList(name.trim(), query);
+      if (saved) {
+        upsertList(saved);
+        onSaved?.(saved);
+      }
+      onOpenChange(false);
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  return (
+    <Dialog open={open} onOpenChange={onOpenChange}>
+      <DialogContent className="sm:max-w-[500px]">
+        <DialogHeader>
+          <DialogTitle>{list ? "修改智能清单" : "新建智能清单"}</DialogTitle>
+          <DialogDescription>用查询语句筛选任务，结果会随任务变化自动更新。</DialogDescription>
+        </DialogHeader>
+        <form onSubmit={handleSubmit} className="space-y-4">
+          <div className="space-y-2">
+            <Label htmlFor="smart-list-name">名称</Label>
+            <Input
+              id="smart-list-name"
+              value={name}
+              onChange={(e) => setName(e.target.value)}
+              placeholder="智能清单名称"
+            />
+          </div>
+          <div className="space-y-2">
+            <Label htmlFor="smart-list-query">查询</Label>
+            <Input
+              id="smart-list-query"
+              value={query}
+              onChange={(e) => setQuery(e.target.value)}
+              placeholder={QUERY_EXAMPLES[0]}
+              className="font-mono text-sm"
+            />
+            {error ? (
+              <p className="text-xs text-destructive break-all">{error}</p>
+            ) : (
+              <div className="text-xs text-muted-foreground space-y-0.5">
+                {QUERY_EXAMPLES.map((example) => (
+                  <p key={example} className="font-mono">{example}</p>
+                ))}
+              </div>
+            )}
+          </div>
+          <DialogFooter>
+            <Button type="button" variant="outline" onClick={() => onOpenChange(false)}>
+              取消
+            </Button>
+            <Button type="submit" disabled={saving || !name.trim() || !!error}>
+              保存
+            </Button>
+          </DialogFooter>
+        </form>
+      </DialogContent>
+    </Dialog>
+  );
+};
+
+export default SmartListDialog;
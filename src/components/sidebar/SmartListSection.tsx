@@ -0,0 +1,69 @@
+import React, { useEffect, useState } from "react";
+import { Plus } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import ProjectItem from "@/components/sidebar/ProjectItem";
+import SmartListDialog from "@/components/sidebar/SmartListDialog";
+import { useTaskContext } from "@/contexts/task";
+import { useSmartListStore } from "@/store/smartListStore";
+import { Project } from "@/types/project";
+import { smartListProjectId } from "@/types/smartList";
+import { isTauriRuntime } from "@/utils/runtime";
+
+// 智能清单只在桌面端可用（查询在 Rust 侧执行）
+const SmartListSection: React.FC = () => {
+  const { selectProject } = useTaskContext();
+  const { lists, hasLoaded, load } = useSmartListStore();
+  const [dialogOpen, setDialogOpen] = useState(false);
+  const enabled = isTauriRuntime();
+
+  useEffect(() => {
+    if (enabled && !hasLoaded) {
+      load();
+    }
+  }, [enabled, hasLoaded, load]);
+
+  if (!enabled) return null;
+
+  const projects: Project[] = lists.map((list) => ({
+    id: smartListProjectId(list.id),
+    name: list.name,
+    icon: list.icon || "filter",
+    count: 0,
+    isFixed: true,
+  }));
+
+  return (
+    <div className="mb-2">
+      <div className="px-3 py-2 flex items-center justify-between border-t border-gray-200 dark:border-gray-600 pt-3">
+        <span className="text-xs uppercase font-medium text-gray-500 dark:text-gray-400">智能清单</span>
+        <Button
+          variant="ghost"
+          size="icon"
+          onClick={() => setDialogOpen(true)}
+          className="h-6 w-6 text-gray-500 hover:text-gray-700 dark:text-gray-400 dark:hover:text-gray-200"
+          title="新建智能清单"
+        >
+          <Plus className="h-4 w-4" />
+        </Button>
+      </div>
+      <div className="space-y-1 px-2">
+        {projects.map((project) => (
+          <ProjectItem
+            key={project.id}
+            project={project}
+            isDraggable={false}
+          />
+        ))}
+      </div>
+
+      <SmartListDialog
+        open={dialogOpen}
+        onOpenChange={setDialogOpen}
+        list={null}
+        onSaved={(list) => selectProject(smartListProjectId(list.id))}
+      />
+    </div>
+  );
+};
+
+export default SmartListSection;
@@ -1,4 +1,4 @@
-import React, { useState, useRef } from "react";
+import React, { useState, useRef, useEffect } from "react";
 import { Button } from "@/components/ui/button";
 import { Input } from "@/components/ui/input";
 import { Plus, Calendar, Loader2 } from "lucide-react";
@@ -7,16 +7,78 @@ import { isValid, isBefore, startOfDay } from "date-fns";
 import { formatDateText } from "@/utils/taskUtils";
 import { cn } from "@/lib/utils";
 import DueDatePickerContent from "./DueDatePickerContent";
+import { QuickAddResult, QuickAddSpanKind } from "@/types/quickAdd";
+import { parseQuickAdd } from "@/services/quickAddService";
+import { isTauriRuntime } from "@/utils/runtime";
 
 interface AddTaskFormProps {
-  onAddTask: (title: string, date?: Date) => Promise<void>;
+  /** 桌面端会附带自然语言解析结果 */
+  onAddTask: (title: string, date?: Date, parsed?: QuickAddResult) => Promise<void>;
   isSubmitting: boolean;
 }
 
+const SPAN_STYLES: Record<QuickAddSpanKind, string> = {
+  date: "bg-orange-100 text-orange-700",
+  time: "bg-orange-100 text-orange-700",
+  repeat: "bg-purple-100 text-purple-700",
+  project: "bg-blue-100 text-blue-700",
+  tag: "bg-green-100 text-green-700",
+  flag: "bg-rose-100 text-rose-700",
+};
+
+const parseSafely = (text: string): Promise<QuickAddResult | null> =>
+  parseQuickAdd(text).catch((error) => {
+    console.error("Failed to parse quick add:", error);
+    return null;
+  });
+
+// 高亮显示输入中被识别的日期、项目、标签等片段
+const QuickAddPreview: React.FC<{ text: string; parsed: QuickAddResult }> = ({ text, parsed }) => {
+  const parts: React.ReactNode[] = [];
+  let from = 0;
+  parsed.spans.forEach((span, index) => {
+    parts.push(text.slice(from, span.start));
+    parts.push(
+      <mark key={index} className={cn("rounded px-0.5", SPAN_STYLES[span.kind])}>
+        {text.slice(span.start, span.end)}
+      </mark>
+    );
+    from = span.end;
+  });
+  parts.push(text.slice(from));
+  return (
+    <div className="px-9 pt-1 text-xs text-muted-foreground truncate">
+      {parts}
+      {parsed.project && !parsed.project_id && (
+        <span className="ml-2 text-red-500">没有名为「{parsed.project}」的项目</span>
+      )}
+    </div>
+  );
+};
+
 const AddTaskForm: React.FC<AddTaskFormProps> = ({ onAddTask, isSubmitting }) => {
   const [newTaskTitle, setNewTaskTitle] = useState("");
   const [newTaskDate, setNewTaskDate] = useState<Date | undefined>(undefined);
   const isComposingRef = useRef(false);
+  const [preview, setPreview] = useState<{ text: string; parsed: QuickAddResult } | null>(null);
+  const quickAddEnabled = isTauriRuntime();
+
+  useEffect(() => {
+    if (!quickAddEnabled || !newTaskTitle.trim()) {
+      setPreview(null);
+      return;
+    }
+    let cancelled = false;
+    const timer = setTimeout(async () => {
+      const text = newTaskTitle;
+      const parsed = await parseSafely(text);
+      if (!cancelled) setPreview(parsed ? { text, parsed } : null);
+    }, 150);
+    return () => {
+      cancelled = true;
+      clearTimeout(timer);
+    };
+  }, [newTaskTitle, quickAddEnabled]);
 
   const handleAddTask = async (e: React.FormEvent) => {
     e.preventDefault();
@@ -24,7 +86,8 @@ const AddTaskForm: React.FC<AddTaskFormProps> = ({ onAddTask, isSubmitting }) =>
       return;
     }
     if (newTaskTitle.trim() && !isSubmitting) {
-      await onAddTask(newTaskTitle, newTaskDate);
+      const result = quickAddEnabled ? await parseSafely(newTaskTitle) : null;
+      await onAddTask(newTaskTitle, newTaskDate, result ?? undefined);
       setNewTaskTitle("");
       setNewTaskDate(undefined);
     }
@@ -61,7 +124,7 @@ const AddTaskForm: React.FC<AddTaskFormProps> = ({ onAddTask, isSubmitting }) =>
           onKeyDown={handleKeyDown}
           onCompositionStart={() => { isComposingRef.current = true; }}
           onCompositionEnd={() => { isComposingRef.current = false; }}
-          placeholder="添加任务"
+          placeholder={quickAddEnabled ? "添加任务，例如：明天下午三点 写周报 #工作 @紧急" : "添加任务"}
           className="h-6 border-0 bg-transparent focus-visible:ring-0 focus-visible:ring-offset-0 text-sm font-medium px-0"
           disabled={isSubmitting}
         />
@@ -92,6 +155,7 @@ const AddTaskForm: React.FC<AddTaskFormProps> = ({ onAddTask, isSubmitting }) =>
           </PopoverContent>
         </Popover>
       </form>
+      {preview && preview.parsed.spans.length > 0 && <QuickAddPreview text={preview.text} parsed={preview.parsed} />}
     </div>
   );
 };
@@ -0,0 +1,88 @@
+import React, { useState } from "react";
+import { Button } from "@/components/ui/button";
+import { Icon } from "@/components/ui/icon-park";
+import {
+  DropdownMenu,
+  DropdownMenuContent,
+  DropdownMenuItem,
+  DropdownMenuTrigger,
+} from "@/components/ui/dropdown-menu";
+import {
+  AlertDialog,
+  AlertDialogAction,
+  AlertDialogCancel,
+  AlertDialogContent,
+  AlertDialogDescription,
+  AlertDialogFooter,
+  AlertDialogHeader,
+  AlertDialogTitle,
+} from "@/components/ui/alert-dialog";
+import SmartListDialog from "@/components/sidebar/SmartListDialog";
+import { useTaskContext } from "@/contexts/task";
+import { deleteSmartList } from "@/services/smartListService";
+import { useSmartListStore } from "@/store/smartListStore";
+import { SmartList } from "@/types/smartList";
+
+interface SmartListActionsProps {
+  list: SmartList;
+}
+
+// 智能清单视图右上角的菜单：修改查询、删除
+const SmartListActions: React.FC<SmartListActionsProps> = ({ list }) => {
+  const { selectProject } = useTaskContext();
+  const removeList = useSmartListStore((state) => state.removeList);
+  const [editOpen, setEditOpen] = useState(false);
+  const [confirmDelete, setConfirmDelete] = useState(false);
+
+  const handleDelete = async () => {
+    try {
+      await deleteSmartList(list.id);
+      removeList(list.id);
+      selectProject("today");
+    } catch (error) {
+      console.error("Error deleting smart list:", error);
+    }
+    setConfirmDelete(false);
+  };
+
+  return (
+    <>
+      <DropdownMenu>
+        <DropdownMenuTrigger asChild>
+          <Button variant="ghost" size="sm" className="h-8 px-2">
+            <Icon icon="more" size="16" className="h-4 w-4" />
+          </Button>
+        </DropdownMenuTrigger>
+        <DropdownMenuContent align="end">
+          <DropdownMenuItem onClick={() => setEditOpen(true)}>
+            <Icon icon="edit" size="16" className="mr-2 h-4 w-4" />
+            <span>修改智能清单</span>
+          </DropdownMenuItem>
+          <DropdownMenuItem onClick={() => setConfirmDelete(true)}>
+            <Icon icon="delete" size="16" className="mr-2 h-4 w-4" />
+            <span>删除智能清单</span>
+          </DropdownMenuItem>
+        </DropdownMenuContent>
+      </DropdownMenu>
+
+      <SmartListDialog open={editOpen} onOpenChange={setEditOpen} list={list} />
+
+      <AlertDialog open={confirmDelete} onOpenChange={setConfirmDelete}>
+        <AlertDialogContent>
+          <AlertDialogHeader>
+            <AlertDialogTitle>确认删除智能清单？</AlertDialogTitle>
+            <AlertDialogDescription>
+              只删除保存的查询，不会影响任何任务。
+            </AlertDialogDescription>
+          </AlertDialogHeader>
+          <AlertDialogFooter>
+            <AlertDialogCancel>取消</AlertDialogCancel>
+            <AlertDialogAction onClick={handleDelete}>确认删除</AlertDialogAction>
+          </AlertDialogFooter>
+        </AlertDialogContent>
+      </AlertDialog>
+    </>
+  );
+};
+
+export default SmartListActions;
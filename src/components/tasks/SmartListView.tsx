@@ -0,0 +1,81 @@
+import React, { useCallback, useEffect, useState } from "react";
+import TaskItem from "@/components/tasks/TaskItem";
+import { ScrollArea } from "@/components/ui/scroll-area";
+import { useTaskContext } from "@/contexts/task";
+import { useProjectContext } from "@/contexts/ProjectContext";
+import { runSmartList } from "@/services/smartListService";
+import { useSmartListStore } from "@/store/smartListStore";
+import { Task } from "@/types/task";
+import { listenTauriEvent } from "@/utils/runtime";
+
+interface SmartListViewProps {
+  listId: string;
+}
+
+const SmartListView: React.FC<SmartListViewProps> = ({ listId }) => {
+  const { tasks } = useTaskContext();
+  const { projects } = useProjectContext();
+  const list = useSmartListStore((state) => state.lists.find((l) => l.id === listId));
+  const [results, setResults] = useState<Task[]>([]);
+  const [loading, setLoading] = useState(true);
+  const [error, setError] = useState<string | null>(null);
+
+  const refresh = useCallback(async () => {
+    try {
+      setResults(await runSmartList(listId));
+      setError(null);
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+    } finally {
+      setLoading(false);
+    }
+  }, [listId]);
+
+  // 查询语句变化或任务变化时重新执行
+  useEffect(() => {
+    refresh();
+  }, [refresh, list?.query, tasks]);
+
+  useEffect(() => listenTauriEvent("tasks://changed", () => refresh()), [refresh]);
+
+  if (loading) {
+    return (
+      <div className="flex-1 flex items-center justify-center text-muted-foreground">
+        正在执行查询...
+      </div>
+    );
+  }
+
+  if (error) {
+    return (
+      <div className="flex-1 flex items-center justify-center text-destructive text-sm px-6 text-center">
+        {error}
+      </div>
+    );
+  }
+
+  if (results.length === 0) {
+    return (
+      <div className="flex-1 flex items-center justify-center text-muted-foreground">
+        没有符合条件的任务
+      </div>
+    );
+  }
+
+  return (
+    <ScrollArea className="h-full">
+      <div className="space-y-1.5 p-4">
+        {results.map((task) => (
+          <TaskItem
+            key={task.id}
+            task={task}
+            showProject={true}
+            projectName={projects.find(p => p.id === task.project)?.name}
+          />
+        ))}
+      </div>
+    </ScrollArea>
+  );
+};
+
+export default SmartListView;
@@ -64,6 +64,10 @@ const describeActivity = (activity: TaskActivity): string => {
       return "将任务标记为已放弃";
     case "task_reactivated":
       return "重新激活了任务";
+    case "recurrence_next_created":
+      return `完成后生成了下一次重复（第 ${metadata.occurrence ?? "?"} 次，截止 ${formatDateLabel(metadata.next_date ?? null)}）`;
+    case "recurrence_created":
+      return `由上一次重复任务完成后生成（第 ${metadata.occurrence ?? "?"} 次）`;
     default:
       return "更新了任务";
   }
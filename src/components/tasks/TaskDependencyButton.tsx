@@ -0,0 +1,151 @@
+import React, { useCallback, useEffect, useMemo, useState } from "react";
+import { Link2, X } from "lucide-react";
+import { Popover, PopoverContent, PopoverTrigger } from "@/components/ui/popover";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { cn } from "@/lib/utils";
+import { Task } from "@/types/task";
+import { TaskDependencies } from "@/types/dependency";
+import {
+  addTaskDependency,
+  fetchTaskDependencies,
+  removeTaskDependency,
+  TASKS_UNBLOCKED_EVENT,
+} from "@/services/dependencyService";
+import { useTaskStore } from "@/store/taskStore";
+import { isTauriRuntime, listenTauriEvent } from "@/utils/runtime";
+
+interface TaskDependencyButtonProps {
+  taskId: string;
+  disabled?: boolean;
+}
+
+const MAX_CANDIDATES = 8;
+
+const EMPTY: TaskDependencies = { blocked_by: [], blocking: [], blocked: false };
+
+// 依赖关系只在桌面端可用（环检测与阻塞状态由 Rust 侧计算）
+const TaskDependencyButton: React.FC<TaskDependencyButtonProps> = ({ taskId, disabled }) => {
+  const tasks = useTaskStore((state) => state.tasks);
+  const [dependencies, setDependencies] = useState<TaskDependencies>(EMPTY);
+  const [keyword, setKeyword] = useState("");
+  const [error, setError] = useState<string | null>(null);
+  const [open, setOpen] = useState(false);
+  const enabled = isTauriRuntime();
+
+  const load = useCallback(async () => {
+    try {
+      setDependencies(await fetchTaskDependencies(taskId));
+    } catch (err) {
+      console.error("Error loading dependencies:", err);
+    }
+  }, [taskId]);
+
+  useEffect(() => {
+    if (enabled) load();
+  }, [enabled, load]);
+
+  // 前置任务被完成后刷新阻塞状态
+  useEffect(() => {
+    if (!enabled) return;
+    return listenTauriEvent(TASKS_UNBLOCKED_EVENT, () => load());
+  }, [enabled, load]);
+
+  const candidates = useMemo(() => {
+    const linked = new Set(dependencies.blocked_by.map((t) => t.id));
+    const query = keyword.trim().toLowerCase();
+    return tasks
+      .filter((t) => t.id !== taskId && !linked.has(t.id) && !t.completed)
+      .filter((t) => !query || t.title.toLowerCase().includes(query))
+      .slice(0, MAX_CANDIDATES);
+  }, [tasks, taskId, dependencies.blocked_by, keyword]);
+
+  if (!enabled) return null;
+
+  const run = async (action: () => Promise<unknown>) => {
+    try {
+      setError(null);
+      await action();
+      await load();
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+    }
+  };
+
+  const renderTask = (task: Task, onRemove?: () => void) => (
+    <div key={task.id} className="flex items-center gap-2 text-xs py-1">
+      <span className={cn("flex-1 truncate", task.completed && "line-through text-muted-foreground")}>
+        {task.title}
+      </span>
+      {onRemove && (
+        <button className="text-muted-foreground hover:text-foreground" onClick={onRemove} title="移除">
+          <X className="h-3 w-3" />
+        </button>
+      )}
+    </div>
+  );
+
+  const linkCount = dependencies.blocked_by.length;
+
+  return (
+    <Popover open={open} onOpenChange={setOpen}>
+      <PopoverTrigger asChild>
+        <button
+          className={cn(
+            "text-xs flex items-center gap-1 px-2 py-1 rounded-md transition-colors",
+            "disabled:cursor-not-allowed disabled:opacity-50",
+            dependencies.blocked
+              ? "text-amber-700 bg-amber-50 hover:bg-amber-100 dark:bg-amber-900/20 dark:hover:bg-amber-900/30"
+              : linkCount > 0
+                ? "text-foreground bg-muted hover:bg-muted/80"
+                : "text-muted-foreground hover:text-foreground hover:bg-muted"
+          )}
+          disabled={disabled}
+          title="依赖"
+        >
+          <Link2 className="h-3 w-3" />
+          {dependencies.blocked ? "被阻塞" : linkCount > 0 ? `依赖 ${linkCount}` : "依赖"}
+        </button>
+      </PopoverTrigger>
+      <PopoverContent className="w-72 p-2 space-y-2" align="start">
+        <div>
+          <div className="text-xs text-muted-foreground mb-1">被以下任务阻塞</div>
+          {linkCount === 0 && <div className="text-xs text-muted-foreground py-1">暂无</div>}
+          {dependencies.blocked_by.map((task) =>
+            renderTask(task, () => run(() => removeTaskDependency(taskId, task.id)))
+          )}
+        </div>
+        {dependencies.blocking.length > 0 && (
+          <div className="border-t pt-2">
+            <div className="text-xs text-muted-foreground mb-1">正在阻塞</div>
+            {dependencies.blocking.map((task) => renderTask(task))}
+          </div>
+        )}
+        <div className="border-t pt-2 space-y-1">
+          <Input
+            value={keyword}
+            onChange={(e) => setKeyword(e.target.value)}
+            placeholder="搜索要等待的任务"
+            className="h-8 text-xs"
+          />
+          <div className="flex flex-col">
+            {candidates.map((task) => (
+              <Button
+                key={task.id}
+                variant="ghost"
+                size="sm"
+                className="justify-start truncate"
+                onClick={() => run(() => addTaskDependency(taskId, task.id))}
+              >
+                {task.title}
+              </Button>
+            ))}
+          </div>
+        </div>
+        {error && <p className="text-xs text-destructive break-all">{error}</p>}
+      </PopoverContent>
+    </Popover>
+  );
+};
+
+export default TaskDependencyButton;
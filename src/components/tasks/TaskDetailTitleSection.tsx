@@ -10,6 +10,9 @@ import { cn } from "@/lib/utils";
 import { formatDateText } from "@/utils/taskUtils";
 import TagSelector from "./TagSelector";
 import DueDatePickerContent from "./DueDatePickerContent";
+import TaskRecurrenceButton from "./TaskRecurrenceButton";
+import TaskDependencyButton from "./TaskDependencyButton";
+import TaskTimeTrackingButton from "./TaskTimeTrackingButton";
 import type { Task } from "@/types/task";
 
 export interface TaskDetailTitleSectionProps {
@@ -172,6 +175,25 @@ const TaskDetailTitleSection: React.FC<TaskDetailTitleSectionProps> = ({
           </Popover>
         )}
 
+        {/* 重复（需要先设置截止日期） */}
+        {!isTaskInTrash && selectedDate && (
+          <TaskRecurrenceButton
+            taskId={selectedTask.id}
+            selectedDate={selectedDate}
+            disabled={isCompletionLoading}
+          />
+        )}
+
+        {/* 依赖（被哪些任务阻塞） */}
+        {!isTaskInTrash && (
+          <TaskDependencyButton taskId={selectedTask.id} disabled={isCompletionLoading} />
+        )}
+
+        {/* 时间记录（计时、补录与预估） */}
+        {!isTaskInTrash && (
+          <TaskTimeTrackingButton task={selectedTask} disabled={isCompletionLoading} />
+        )}
+
         {/* 标记按钮 */}
         {!isTaskInTrash && (
           <button
@@ -1,4 +1,4 @@
-import React, { useState, useRef, useEffect } from "react";
+import React, { useState, useRef, useEffect, useMemo } from "react";
 import { useTaskContext } from "@/contexts/task";
 import { useProjectContext } from "@/contexts/ProjectContext";
 import { cn } from "@/lib/utils";
@@ -26,6 +26,8 @@ import { Draggable } from "@hello-pangea/dnd";
 import { Loader2 } from "lucide-react";
 import { useTaskOperation } from "@/hooks/useTaskOperation";
 import TagSelector from "./TagSelector";
+import { useTaskStore } from "@/store/taskStore";
+import { collectSubtree, computeProgress } from "@/storage/hierarchy";
 
 interface TaskItemProps {
   task: Task;
@@ -49,6 +51,13 @@ const TaskItem: React.FC<TaskItemProps> = ({ task, showProject = false, projectN
   const [isContextMenuOpen, setIsContextMenuOpen] = useState(false);
   const { operationState, startOperation } = useTaskOperation();
 
+  // 子任务完成进度，逐层汇总到父任务
+  const allTasks = useTaskStore((state) => state.tasks);
+  const subtaskProgress = useMemo(() => {
+    if (!allTasks.some((t) => t.parent_id === task.id)) return null;
+    return computeProgress(collectSubtree(allTasks, task.id));
+  }, [allTasks, task.id]);
+
 
 
   useEffect(() => {
@@ -634,6 +643,15 @@ const TaskItem: React.FC<TaskItemProps> = ({ task, showProject = false, projectN
                   <span>截止: {formatDateText(task.date)}</span>
                 </div>
               )}
+              {subtaskProgress && subtaskProgress.total > 0 && (
+                <div
+                  className="flex items-center gap-1"
+                  title={`子任务已完成 ${subtaskProgress.percent}%`}
+                >
+                  <Icon icon="check-one" size="12" className="h-3 w-3" />
+                  <span>{subtaskProgress.completed}/{subtaskProgress.total}</span>
+                </div>
+              )}
               {task.completed && task.completed_at && (
                 <div className="text-gray-400 text-xs ml-2">
                   {format(parseISO(task.completed_at), "M月d日完成", { locale: zhCN })}
@@ -2,6 +2,8 @@ import React, { useState, useEffect } from "react";
 import { useTaskContext } from "@/contexts/task";
 import { useProjectContext } from "@/contexts/ProjectContext";
 import { Task } from "@/types/task";
+import { QuickAddResult } from "@/types/quickAdd";
+import { applyQuickAddExtras } from "@/services/quickAddService";
 import TaskItem from "@/components/tasks/TaskItem";
 import { DragDropContext, Droppable, DropResult } from "@hello-pangea/dnd";
 import { useSidebar } from "@/contexts/SidebarContext";
@@ -25,7 +27,7 @@ import EmptyStateGuide from "./EmptyStateGuide";
 import EditProjectDialog from "@/components/projects/EditProjectDialog";
 
 const TaskList: React.FC = () => {
-  const { tasks, loading, selectedProject, addTask, reorderTasks } = useTaskContext();
+  const { tasks, loading, selectedProject, addTask, reorderTasks, listAllTags, createTag, attachTagToTask } = useTaskContext();
   const { projects, createProject } = useProjectContext();
   const [isSubmitting, setIsSubmitting] = useState(false);
   const { collapsed, setCollapsed } = useSidebar();
@@ -125,18 +127,23 @@ const TaskList: React.FC = () => {
     return projectId;
   };
 
-  const handleAddTask = async (title: string, date?: Date) => {
+  const handleAddTask = async (title: string, date?: Date, parsed?: QuickAddResult) => {
     setIsSubmitting(true);
 
     try {
       const dateString = date ? date.toISOString() : undefined;
 
-      await addTask({
-        title: title,
+      // 文字里识别出的日期、#项目 优先于日期选择器和当前项目
+      const task = await addTask({
+        title: parsed?.title || title,
         completed: false,
-        project: selectedProject,
-        date: dateString,
+        project: parsed?.project_id || selectedProject,
+        date: parsed?.date || dateString,
+        flagged: parsed?.flagged || undefined,
       });
+      if (task && parsed) {
+        await applyQuickAddExtras(task, parsed, { listAllTags, createTag, attachTagToTask });
+      }
     } catch (error) {
       console.error("Failed to add task:", error);
     } finally {
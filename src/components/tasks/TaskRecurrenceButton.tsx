@@ -0,0 +1,163 @@
+import React, { useCallback, useEffect, useState } from "react";
+import { Repeat } from "lucide-react";
+import { format, parseISO } from "date-fns";
+import { Popover, PopoverContent, PopoverTrigger } from "@/components/ui/popover";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { cn } from "@/lib/utils";
+import { TaskRecurrence } from "@/types/recurrence";
+import {
+  clearTaskRecurrence,
+  fetchTaskRecurrence,
+  fetchUpcomingOccurrences,
+  setTaskRecurrence,
+} from "@/services/recurrenceService";
+import { formatDateText } from "@/utils/taskUtils";
+import { isTauriRuntime } from "@/utils/runtime";
+
+interface TaskRecurrenceButtonProps {
+  taskId: string;
+  selectedDate: Date;
+  disabled?: boolean;
+}
+
+const WEEKDAY_CODES = ["SU", "MO", "TU", "WE", "TH", "FR", "SA"];
+const WEEKDAY_NAMES = ["日", "一", "二", "三", "四", "五", "六"];
+
+const presetsFor = (date: Date) => [
+  { label: "每天", rrule: "FREQ=DAILY" },
+  { label: "每个工作日", rrule: "FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR" },
+  { label: `每周${WEEKDAY_NAMES[date.getDay()]}`, rrule: `FREQ=WEEKLY;BYDAY=${WEEKDAY_CODES[date.getDay()]}` },
+  { label: `每月${date.getDate()}日`, rrule: `FREQ=MONTHLY;BYMONTHDAY=${date.getDate()}` },
+  { label: "每月最后一个工作日", rrule: "FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1" },
+  { label: `每年${format(date, "M月d日")}`, rrule: "FREQ=YEARLY" },
+];
+
+const describe = (rrule: string, date: Date) =>
+  presetsFor(date).find((preset) => preset.rrule === rrule)?.label ?? "自定义重复";
+
+// 重复设置只在桌面端可用（完成后由 Rust 侧生成下一次任务）
+const TaskRecurrenceButton: React.FC<TaskRecurrenceButtonProps> = ({ taskId, selectedDate, disabled }) => {
+  const [recurrence, setRecurrence] = useState<TaskRecurrence | null>(null);
+  const [upcoming, setUpcoming] = useState<string[]>([]);
+  const [custom, setCustom] = useState("");
+  const [error, setError] = useState<string | null>(null);
+  const [open, setOpen] = useState(false);
+  const enabled = isTauriRuntime();
+
+  const load = useCallback(async () => {
+    try {
+      const current = await fetchTaskRecurrence(taskId);
+      setRecurrence(current);
+      setCustom(current?.rrule ?? "");
+      setUpcoming(current ? await fetchUpcomingOccurrences(taskId) : []);
+    } catch (err) {
+      console.error("Error loading recurrence:", err);
+    }
+  }, [taskId]);
+
+  useEffect(() => {
+    if (enabled) load();
+  }, [enabled, load, selectedDate]);
+
+  if (!enabled) return null;
+
+  const apply = async (rrule: string | null) => {
+    try {
+      setError(null);
+      if (rrule) {
+        await setTaskRecurrence(taskId, rrule, recurrence?.exdates ?? []);
+      } else {
+        await clearTaskRecurrence(taskId);
+      }
+      await load();
+      setOpen(false);
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+    }
+  };
+
+  const skipNext = async () => {
+    if (!recurrence || upcoming.length === 0) return;
+    try {
+      const day = format(parseISO(upcoming[0]), "yyyy-MM-dd");
+      await setTaskRecurrence(taskId, recurrence.rrule, [...recurrence.exdates, day]);
+      await load();
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+    }
+  };
+
+  return (
+    <Popover open={open} onOpenChange={setOpen}>
+      <PopoverTrigger asChild>
+        <button
+          className={cn(
+            "text-xs flex items-center gap-1 px-2 py-1 rounded-md transition-colors",
+            "disabled:cursor-not-allowed disabled:opacity-50",
+            recurrence
+              ? "text-foreground bg-muted hover:bg-muted/80"
+              : "text-muted-foreground hover:text-foreground hover:bg-muted"
+          )}
+          disabled={disabled}
+          title="重复"
+        >
+          <Repeat className="h-3 w-3" />
+          {recurrence ? describe(recurrence.rrule, selectedDate) : "重复"}
+        </button>
+      </PopoverTrigger>
+      <PopoverContent className="w-72 p-2 space-y-2" align="start">
+        <div className="flex flex-col">
+          {presetsFor(selectedDate).map((preset) => (
+            <Button
+              key={preset.rrule}
+              variant="ghost"
+              size="sm"
+              className={cn("justify-start", recurrence?.rrule === preset.rrule && "bg-muted")}
+              onClick={() => apply(preset.rrule)}
+            >
+              {preset.label}
+            </Button>
+          ))}
+          {recurrence && (
+            <Button
+              variant="ghost"
+              size="sm"
+              className="justify-start text-red-500 hover:text-red-600"
+              onClick={() => apply(null)}
+            >
+              不再重复
+            </Button>
+          )}
+        </div>
+        <div className="flex gap-1 border-t pt-2">
+          <Input
+            value={custom}
+            onChange={(e) => setCustom(e.target.value)}
+            placeholder="FREQ=WEEKLY;INTERVAL=2;BYDAY=MO"
+            className="h-8 font-mono text-xs"
+          />
+          <Button size="sm" className="h-8" onClick={() => apply(custom)} disabled={!custom.trim()}>
+            应用
+          </Button>
+        </div>
+        {error && <p className="text-xs text-destructive break-all">{error}</p>}
+        {upcoming.length > 0 && (
+          <div className="border-t pt-2 text-xs text-muted-foreground space-y-1">
+            <div className="flex items-center justify-between">
+              <span>之后的日期</span>
+              <button className="hover:text-foreground" onClick={skipNext}>
+                跳过下一次
+              </button>
+            </div>
+            {upcoming.map((date) => (
+              <div key={date}>{formatDateText(parseISO(date))}</div>
+            ))}
+          </div>
+        )}
+      </PopoverContent>
+    </Popover>
+  );
+};
+
+export default TaskRecurrenceButton;
@@ -0,0 +1,231 @@
+import React, { useCallback, useEffect, useState } from "react";
+import { format } from "date-fns";
+import { Play, Square, Timer, X } from "lucide-react";
+import { Popover, PopoverContent, PopoverTrigger } from "@/components/ui/popover";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { cn } from "@/lib/utils";
+import { Task } from "@/types/task";
+import { TimeEntry } from "@/types/timeTracking";
+import { useTaskContext } from "@/contexts/task";
+import {
+  addTimeEntry,
+  deleteTimeEntry,
+  fetchRunningTimer,
+  fetchTimeEntries,
+  formatMinutes,
+  startTaskTimer,
+  stopTaskTimer,
+} from "@/services/timeTrackingService";
+import { isTauriRuntime } from "@/utils/runtime";
+
+interface TaskTimeTrackingButtonProps {
+  task: Task;
+  disabled?: boolean;
+}
+
+const SOURCE_LABELS: Record<TimeEntry["source"], string> = {
+  timer: "计时",
+  manual: "手动",
+  pomodoro: "番茄",
+};
+
+const entryMinutes = (entry: TimeEntry, now: number) => {
+  const end = entry.ended_at ? new Date(entry.ended_at).getTime() : now;
+  return Math.max(0, Math.round((end - new Date(entry.started_at).getTime()) / 60000));
+};
+
+const today = () => format(new Date(), "yyyy-MM-dd");
+
+// 时间记录只在桌面端可用（同一时间只能有一个计时器，由 Rust 侧保证）
+const TaskTimeTrackingButton: React.FC<TaskTimeTrackingButtonProps> = ({ task, disabled }) => {
+  const { updateTask } = useTaskContext();
+  const [entries, setEntries] = useState<TimeEntry[]>([]);
+  const [running, setRunning] = useState<TimeEntry | null>(null);
+  const [now, setNow] = useState(Date.now());
+  const [estimate, setEstimate] = useState("");
+  const [manual, setManual] = useState({ day: today(), start: "09:00", end: "10:00" });
+  const [error, setError] = useState<string | null>(null);
+  const [open, setOpen] = useState(false);
+  const enabled = isTauriRuntime();
+
+  const load = useCallback(async () => {
+    try {
+      const [list, timer] = await Promise.all([fetchTimeEntries(task.id), fetchRunningTimer()]);
+      setEntries(list);
+      setRunning(timer);
+    } catch (err) {
+      console.error("Error loading time entries:", err);
+    }
+  }, [task.id]);
+
+  useEffect(() => {
+    if (enabled) load();
+  }, [enabled, load]);
+
+  useEffect(() => {
+    setEstimate(task.estimate_minutes ? String(task.estimate_minutes) : "");
+  }, [task.estimate_minutes]);
+
+  const runningHere = running?.task_id === task.id;
+
+  // 计时中每 30 秒刷新一次显示
+  useEffect(() => {
+    if (!runningHere) return;
+    const timer = window.setInterval(() => setNow(Date.now()), 30000);
+    return () => window.clearInterval(timer);
+  }, [runningHere]);
+
+  if (!enabled) return null;
+
+  const run = async (action: () => Promise<unknown>) => {
+    try {
+      setError(null);
+      await action();
+      setNow(Date.now());
+      await load();
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+    }
+  };
+
+  const saveEstimate = () => {
+    const minutes = estimate.trim() === "" ? null : Number.parseInt(estimate, 10);
+    if (minutes !== null && (!Number.isFinite(minutes) || minutes < 0)) {
+      setError("预估时长需为非负整数分钟");
+      return;
+    }
+    if (minutes === (task.estimate_minutes ?? null)) return;
+    void run(() => updateTask(task.id, { estimate_minutes: minutes }));
+  };
+
+  const addManual = () =>
+    run(() =>
+      addTimeEntry({
+        task_id: task.id,
+        started_at: new Date(`${manual.day}T${manual.start}`).toISOString(),
+        ended_at: new Date(`${manual.day}T${manual.end}`).toISOString(),
+      })
+    );
+
+  const tracked = entries.reduce((sum, entry) => sum + entryMinutes(entry, now), 0);
+  const estimated = task.estimate_minutes ?? 0;
+  const over = estimated > 0 && tracked > estimated;
+  const label = runningHere
+    ? `计时中 ${formatMinutes(tracked)}`
+    : tracked > 0 || estimated > 0
+      ? `${formatMinutes(tracked)}${estimated > 0 ? ` / ${formatMinutes(estimated)}` : ""}`
+      : "计时";
+
+  return (
+    <Popover open={open} onOpenChange={setOpen}>
+      <PopoverTrigger asChild>
+        <button
+          className={cn(
+            "text-xs flex items-center gap-1 px-2 py-1 rounded-md transition-colors",
+            "disabled:cursor-not-allowed disabled:opacity-50",
+            runningHere
+              ? "text-emerald-700 bg-emerald-50 hover:bg-emerald-100 dark:bg-emerald-900/20 dark:hover:bg-emerald-900/30"
+              : over
+                ? "text-amber-700 bg-amber-50 hover:bg-amber-100 dark:bg-amber-900/20 dark:hover:bg-amber-900/30"
+                : tracked > 0 || estimated > 0
+                  ? "text-foreground bg-muted hover:bg-muted/80"
+                  : "text-muted-foreground hover:text-foreground hover:bg-muted"
+          )}
+          disabled={disabled}
+          title="时间记录"
+        >
+          <Timer className="h-3 w-3" />
+          {label}
+        </button>
+      </PopoverTrigger>
+      <PopoverContent className="w-80 p-2 space-y-2" align="start">
+        <div className="flex items-center gap-2">
+          {runningHere ? (
+            <Button size="sm" variant="destructive" onClick={() => run(stopTaskTimer)}>
+              <Square className="h-3 w-3 mr-1" />
+              停止计时
+            </Button>
+          ) : (
+            <Button size="sm" onClick={() => run(() => startTaskTimer(task.id))}>
+              <Play className="h-3 w-3 mr-1" />
+              开始计时
+            </Button>
+          )}
+          <span className={cn("text-xs", over ? "text-amber-700" : "text-muted-foreground")}>
+            已用 {formatMinutes(tracked)}
+            {estimated > 0 && ` / 预估 ${formatMinutes(estimated)}`}
+          </span>
+        </div>
+        {running && !runningHere && (
+          <p className="text-xs text-muted-foreground">另一个任务正在计时，停止后才能开始新的计时</p>
+        )}
+        <div className="border-t pt-2 flex items-center gap-2">
+          <span className="text-xs text-muted-foreground shrink-0">预估（分钟）</span>
+          <Input
+            type="number"
+            min={0}
+            value={estimate}
+            onChange={(e) => setEstimate(e.target.value)}
+            onBlur={saveEstimate}
+            onKeyDown={(e) => e.key === "Enter" && saveEstimate()}
+            placeholder="未设置"
+            className="h-8 text-xs"
+          />
+        </div>
+        <div className="border-t pt-2 space-y-1">
+          <div className="text-xs text-muted-foreground">手动补录</div>
+          <div className="flex items-center gap-1">
+            <Input
+              type="date"
+              value={manual.day}
+              onChange={(e) => setManual((prev) => ({ ...prev, day: e.target.value }))}
+              className="h-8 text-xs"
+            />
+            <Input
+              type="time"
+              value={manual.start}
+              onChange={(e) => setManual((prev) => ({ ...prev, start: e.target.value }))}
+              className="h-8 text-xs w-24"
+            />
+            <Input
+              type="time"
+              value={manual.end}
+              onChange={(e) => setManual((prev) => ({ ...prev, end: e.target.value }))}
+              className="h-8 text-xs w-24"
+            />
+          </div>
+          <Button size="sm" variant="outline" className="w-full" onClick={addManual}>
+            添加记录
+          </Button>
+        </div>
+        {entries.length > 0 && (
+          <div className="border-t pt-2 max-h-48 overflow-y-auto">
+            {[...entries].reverse().map((entry) => (
+              <div key={entry.id} className="flex items-center gap-2 text-xs py-1">
+                <span className="text-muted-foreground w-8 shrink-0">{SOURCE_LABELS[entry.source]}</span>
+                <span className="flex-1 truncate">
+                  {format(new Date(entry.started_at), "MM-dd HH:mm")}
+                  {entry.ended_at ? ` - ${format(new Date(entry.ended_at), "HH:mm")}` : " 起"}
+                </span>
+                <span>{formatMinutes(entryMinutes(entry, now))}</span>
+                {entry.source !== "pomodoro" && entry.ended_at && (
+                  <button
+                    className="text-muted-foreground hover:text-foreground"
+                    onClick={() => run(() => deleteTimeEntry(entry.id))}
+                    title="删除"
+                  >
+                    <X className="h-3 w-3" />
+                  </button>
+                )}
+              </div>
+            ))}
+          </div>
+        )}
+        {error && <p className="text-xs text-destructive break-all">{error}</p>}
+      </PopoverContent>
+    </Popover>
+  );
+};
+
+export default TaskTimeTrackingButton;
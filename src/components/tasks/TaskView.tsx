@@ -6,6 +6,10 @@ import TaskList from "./TaskList";
 import CompletedTasksView from "./CompletedTasksView";
 import AbandonedTasksView from "./AbandonedTasksView";
 import TrashView from "./TrashView";
+import SmartListView from "./SmartListView";
+import SmartListActions from "./SmartListActions";
+import { useSmartListStore } from "@/store/smartListStore";
+import { SMART_LIST_PREFIX, isSmartListProject } from "@/types/smartList";
 import { useSidebar } from "@/contexts/SidebarContext";
 import { useState, useEffect } from "react";
 import { Button } from "@/components/ui/button";
@@ -43,13 +47,24 @@ const TaskView: React.FC = () => {
   const [showTopHeader, setShowTopHeader] = useState(false);
   const [exportDialogOpen, setExportDialogOpen] = useState(false);
   const [confirmDeleteAll, setConfirmDeleteAll] = useState(false);
+  const smartListId = isSmartListProject(selectedProject)
+    ? selectedProject.slice(SMART_LIST_PREFIX.length)
+    : null;
+  const smartList = useSmartListStore((state) =>
+    smartListId ? state.lists.find((list) => list.id === smartListId) : undefined
+  );
 
   useEffect(() => {
-    setShowTopHeader(["completed", "abandoned", "trash"].includes(selectedProject));
+    setShowTopHeader(
+      ["completed", "abandoned", "trash"].includes(selectedProject) || isSmartListProject(selectedProject)
+    );
   }, [selectedProject]);
 
   const renderContent = () => {
     console.log('selectedProject', selectedProject);
+    if (smartListId) {
+      return <SmartListView listId={smartListId} />;
+    }
     switch (selectedProject) {
       case "completed":
         return (
@@ -88,6 +103,9 @@ const TaskView: React.FC = () => {
 
   // 获取当前视图的标题和图标
   const getViewInfo = () => {
+    if (smartListId) {
+      return { name: smartList?.name || "智能清单", icon: smartList?.icon || "filter" };
+    }
     switch (selectedProject) {
       case "recent":
         return { name: "最近7天", icon: "calendar" };
@@ -123,6 +141,9 @@ const TaskView: React.FC = () => {
 
   // Render the appropriate actions for each view
   const renderHeaderActions = () => {
+    if (smartList) {
+      return <SmartListActions list={smartList} />;
+    }
     if (selectedProject === "completed") {
       return (
         <DropdownMenu>
@@ -159,4 +159,40 @@ describe('Storage Configuration', () => {
       );
     });
   });
+
+  describe('Backend', () => {
+    it('should always use the desktop database under Tauri', async () => {
+      const { getStorageConfig } = await import('./storage');
+      localStorage.setItem('snail_storage_mode', 'offline');
+      expect(getStorageConfig().backend).toBe('indexeddb');
+      localStorage.setItem('snail_storage_mode', 'supabase');
+      expect(getStorageConfig().backend).toBe('supabase');
+
+      (window as unknown as { __TAURI_INTERNALS__?: unknown }).__TAURI_INTERNALS__ = {};
+      try {
+        expect(getStorageConfig().backend).toBe('tauri');
+        localStorage.setItem('snail_storage_mode', 'offline');
+        expect(getStorageConfig().backend).toBe('tauri');
+      } finally {
+        delete (window as unknown as { __TAURI_INTERNALS__?: unknown }).__TAURI_INTERNALS__;
+      }
+    });
+  });
+
+  describe('Mode switch', () => {
+    it('should take effect without a reload', async () => {
+      const storage = await import('./storage');
+      const seen: string[] = [];
+      const unsubscribe = storage.onStorageModeChange((mode) => seen.push(mode));
+
+      storage.setStorageMode('offline');
+      expect(storage.STORAGE_MODE).toBe('offline');
+      expect(storage.isOfflineMode).toBe(true);
+
+      unsubscribe();
+      storage.setStorageMode('supabase');
+      expect(storage.isOfflineMode).toBe(false);
+      expect(seen).toEqual(['offline']);
+    });
+  });
 });
@@ -1,3 +1,5 @@
+import { isTauriRuntime } from '@/utils/runtime';
+
 /**
  * Storage configuration module
  * Determines which storage backend to use based on user preference (localStorage)
@@ -6,17 +8,41 @@
 
 export type StorageMode = 'supabase' | 'offline';
 
+/**
+ * Where the webview reads and writes: always the desktop database under Tauri,
+ * otherwise Supabase online and IndexedDB offline
+ */
+export type StorageBackend = 'supabase' | 'indexeddb' | 'tauri';
+
 export interface StorageConfig {
   mode: StorageMode;
+  backend: StorageBackend;
   isOfflineMode: boolean;
 }
 
 /** localStorage key for user's storage mode preference */
 export const STORAGE_MODE_KEY = 'snail_storage_mode';
 
+type StorageModeListener = (mode: StorageMode) => void;
+
+const modeListeners = new Set<StorageModeListener>();
+
+/**
+ * Subscribe to storage mode switches
+ * Returns the unsubscribe function
+ */
+export function onStorageModeChange(listener: StorageModeListener): () => void {
+  modeListeners.add(listener);
+  return () => {
+    modeListeners.delete(listener);
+  };
+}
+
 /**
  * Set storage mode preference in localStorage
- * Requires page reload to take effect (storage adapter is singleton)
+ * Takes effect at once: STORAGE_MODE/isOfflineMode follow it and listeners are told.
+ * On desktop the adapter stays the same, only sign-in and sync follow the mode;
+ * in the browser it picks another adapter, so callers reload the page there
  */
 export function setStorageMode(mode: StorageMode): void {
   try {
@@ -24,6 +50,9 @@ export function setStorageMode(mode: StorageMode): void {
   } catch {
     console.warn('Failed to save storage mode preference');
   }
+  STORAGE_MODE = mode;
+  isOfflineMode = mode === 'offline';
+  modeListeners.forEach((listener) => listener(mode));
 }
 
 /**
@@ -41,6 +70,10 @@ export function clearStorageMode(): void {
 /**
  * Get storage configuration
  * Priority: localStorage > environment variable > default ('supabase')
+ *
+ * On desktop the webview always uses the local database, in both modes: with
+ * mode 'supabase' the Rust side is the only one that pushes to and pulls from
+ * Supabase (see services/syncService.ts); offline it never syncs
  */
 export const getStorageConfig = (): StorageConfig => {
   let mode: StorageMode = 'supabase';
@@ -65,14 +98,20 @@ export const getStorageConfig = (): StorageConfig => {
     }
   }
   
+  let backend: StorageBackend = mode === 'offline' ? 'indexeddb' : 'supabase';
+  if (isTauriRuntime()) {
+    backend = 'tauri';
+  }
+
   return {
     mode,
+    backend,
     isOfflineMode: mode === 'offline',
   };
 };
 
-// Export singleton config values for easy access throughout the application
+// Export config values for easy access throughout the application; setStorageMode keeps them current
 const config = getStorageConfig();
 
-export const STORAGE_MODE = config.mode;
-export const isOfflineMode = config.isOfflineMode;
+export let STORAGE_MODE = config.mode;
+export let isOfflineMode = config.isOfflineMode;
@@ -5,7 +5,8 @@ import { supabase } from "@/integrations/supabase/client";
 import { isTauriRuntime } from "@/utils/runtime";
 import { toast } from "@/hooks/use-toast";
 import { useNavigate } from "react-router-dom";
-import { isOfflineMode } from "@/storage";
+import { useStorageMode } from "@/hooks/useStorageMode";
+import { configureSync } from "@/services/syncService";
 
 // 游客ID本地存储key
 const GUEST_ID_KEY = "snail_guest_id";
@@ -44,10 +45,11 @@ export const AuthProvider: React.FC<{ children: React.ReactNode }> = ({ children
 
   // Track if we've already shown the login toast
   const [hasShownLoginToast, setHasShownLoginToast] = useState(false);
+  const offline = useStorageMode() === "offline";
 
   useEffect(() => {
     // In offline mode, skip Supabase auth entirely
-    if (isOfflineMode) {
+    if (offline) {
       // Create a mock offline user
       const offlineUser = {
         id: 'offline-user',
@@ -112,7 +114,13 @@ export const AuthProvider: React.FC<{ children: React.ReactNode }> = ({ children
     return () => {
       subscription.unsubscribe();
     };
-  }, [navigate, hasShownLoginToast]);
+  }, [navigate, hasShownLoginToast, offline]);
+
+  // 桌面端：会话（含令牌刷新）交给 Rust 同步引擎，退出登录时停止同步
+  useEffect(() => {
+    if (!isTauriRuntime() || loading) return;
+    configureSync(session).catch((error) => console.warn("Failed to configure sync:", error));
+  }, [session, loading]);
 
   // 将游客数据迁移到正式用户账户
   const migrateGuestDataToUser = async (guestId: string, userId: string) => {
@@ -270,7 +278,7 @@ export const AuthProvider: React.FC<{ children: React.ReactNode }> = ({ children
 
   // Refresh user data from Supabase
   const refreshUser = async () => {
-    if (isOfflineMode || isGuest) return;
+    if (offline || isGuest) return;
     
     const { data: { user: freshUser } } = await supabase.auth.getUser();
     if (freshUser) {
@@ -6,6 +6,8 @@ import { toast } from "@/hooks/use-toast";
 import { useAuth } from "@/contexts/AuthContext";
 import { useProjectStore } from "@/store/projectStore";
 import { isOfflineMode } from "@/storage";
+import { getStorageConfig } from "@/config/storage";
+import { listenTauriEvent } from "@/utils/runtime";
 import * as storageOps from "@/storage/operations";
 import { canPerformOperation, requiresAuth } from "@/storage/operations";
 
@@ -40,8 +42,8 @@ export const ProjectProvider: React.FC<{ children: React.ReactNode }> = ({ child
     try {
       setLoading(true);
       
-      // Use unified storage operations for offline mode
-      if (isOfflineMode) {
+      // Use unified storage operations for offline mode and on desktop (local database, synced by Rust)
+      if (isOfflineMode || getStorageConfig().backend === "tauri") {
         const projects = await storageOps.getProjects();
         setProjects(projects);
         setHasLoaded(true);
@@ -168,8 +170,8 @@ export const ProjectProvider: React.FC<{ children: React.ReactNode }> = ({ child
 
   // Realtime: refresh on project membership changes (filtered)
   useEffect(() => {
-    // Skip realtime subscriptions in offline mode
-    if (isOfflineMode) return;
+    // Skip realtime subscriptions in offline mode and on desktop
+    if (getStorageConfig().backend !== "supabase") return;
     if (!user) return;
     const ownedIds = (projects || []).filter(p => p.user_id === user.id).map(p => p.id);
     const channel = supabase.channel(`projects:members:${user.id}`);
@@ -193,6 +195,13 @@ export const ProjectProvider: React.FC<{ children: React.ReactNode }> = ({ child
     };
   }, [user, projects, fetchProjects]);
 
+  // 桌面端：同步拉取或 Rust 侧写入后重新读取本地清单
+  useEffect(() => {
+    return listenTauriEvent("tasks://changed", () => {
+      fetchProjects(true);
+    });
+  }, [fetchProjects]);
+
   // Listen for task count updates from TaskProvider
   useEffect(() => {
     const handleTaskCountsUpdate = (event: CustomEvent<{projectCounts: Record<string, number>}>) => {
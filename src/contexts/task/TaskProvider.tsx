@@ -16,9 +16,13 @@ import { tagKeys, tagQueries } from "@/queries/tagQueries";
 import { taskActivityKeys } from "@/queries/taskActivityQueries";
 import type { TaskActivityAction, TaskActivityInput } from "@/types/taskActivity";
 import { useProjectContext } from "@/contexts/ProjectContext";
-import { isOfflineMode } from "@/storage";
 import * as storageOps from "@/storage/operations";
 import { canPerformOperation, requiresAuth } from "@/storage/operations";
+import { listenTauriEvent } from "@/utils/runtime";
+import { isSmartListProject } from "@/types/smartList";
+import { TASKS_UNBLOCKED_EVENT } from "@/services/dependencyService";
+import { placeTaskBetween } from "@/services/taskOrderService";
+import { getStorageConfig } from "@/config/storage";
 
 const hasProp = <K extends keyof Partial<Task>>(obj: Partial<Task>, key: K): boolean =>
   Object.prototype.hasOwnProperty.call(obj, key);
@@ -164,7 +168,7 @@ export const TaskProvider: React.FC<TaskProviderProps> = ({ children }) => {
   useEffect(() => {
     if (!user) return;
     if (projectsLoading) return;
-    const isBuiltin = builtinScopes.has(selectedProject);
+    const isBuiltin = builtinScopes.has(selectedProject) || isSmartListProject(selectedProject);
     const existsInProjects = (projects || []).some(p => p.id === selectedProject);
     if (!isBuiltin && !existsInProjects) {
       localStorage.setItem(SELECTED_PROJECT_KEY, "today");
@@ -248,6 +252,24 @@ export const TaskProvider: React.FC<TaskProviderProps> = ({ children }) => {
     setLoading(isActivePending);
   }, [isActivePending, setLoading]);
 
+  // 托盘或快速添加窗口在 Rust 侧写入任务后，重新拉取任务列表
+  useEffect(() => {
+    return listenTauriEvent("tasks://changed", () => {
+      queryClient.invalidateQueries({ queryKey: taskKeys.all });
+    });
+  }, [queryClient]);
+
+  // 完成前置任务后，提示哪些任务已经可以开始
+  useEffect(() => {
+    return listenTauriEvent<Task[]>(TASKS_UNBLOCKED_EVENT, (unblocked) => {
+      if (!unblocked?.length) return;
+      toast({
+        title: "任务已解除阻塞",
+        description: unblocked.map((t) => t.title).join("、"),
+      });
+    });
+  }, [toast]);
+
   useEffect(() => {
     if (!isActiveSuccess) return;
     // Avoid overriding local manual order while saving or shortly after a manual reorder
@@ -314,7 +336,7 @@ export const TaskProvider: React.FC<TaskProviderProps> = ({ children }) => {
         description: "您需要登录才能添加任务",
         variant: "destructive"
       });
-      return;
+      return null;
     }
 
     // 生成临时 ID 用于乐观更新
@@ -354,6 +376,7 @@ export const TaskProvider: React.FC<TaskProviderProps> = ({ children }) => {
 
       await recordTaskActivity(newTask.id, "task_created", { title: newTask.title });
       queryClient.invalidateQueries({ queryKey: taskKeys.active() });
+      return newTask;
     } catch (error) {
       // 回滚：移除乐观任务
       setTasks((current) => current.filter((t) => t.id !== tempId));
@@ -906,28 +929,20 @@ export const TaskProvider: React.FC<TaskProviderProps> = ({ children }) => {
         return;
       }
 
-      const success = await storageOps.moveToTrash(id);
+      const trashed = await storageOps.moveToTrash(id);
 
-      if (!success) {
+      if (trashed.length === 0) {
         throw new Error("move to trash failed");
       }
 
+      // 子任务随父任务一起进入垃圾桶
+      const trashedIds = new Set(trashed.map(task => task.id));
       const { tasks: currentTasks, trashedTasks: currentTrashed } = useTaskStore.getState();
-      const taskToTrash = currentTasks.find(task => task.id === id);
-
-      setTasks(currentTasks.filter((task) => task.id !== id));
-
-        if (taskToTrash) {
-          const trashedTask = {
-            ...taskToTrash,
-            deleted: true,
-            deleted_at: new Date().toISOString()
-          };
-        setTrashedTasks([trashedTask, ...currentTrashed]);
-        }
+      setTasks(currentTasks.filter((task) => !trashedIds.has(task.id)));
+      setTrashedTasks([...trashed, ...currentTrashed.filter((task) => !trashedIds.has(task.id))]);
 
-        // Clear selection if the trashed task was selected
-      if (selectedTaskId === id) {
+      // Clear selection if the trashed task (or one of its subtasks) was selected
+      if (selectedTaskId && trashedIds.has(selectedTaskId)) {
         setSelectedTaskId(null);
       }
 
@@ -952,28 +967,17 @@ export const TaskProvider: React.FC<TaskProviderProps> = ({ children }) => {
         return;
       }
 
-      const success = await storageOps.restoreFromTrash(id);
+      const restored = await storageOps.restoreFromTrash(id);
 
-      if (!success) {
+      if (restored.length === 0) {
         throw new Error("restore from trash failed");
       }
 
-        // Find the task before removing it from the trashed tasks list
+      // 一起被删除的子任务随父任务一起恢复
+      const restoredIds = new Set(restored.map(task => task.id));
       const { trashedTasks: currentTrashed, tasks: currentTasks } = useTaskStore.getState();
-      const taskToRestore = currentTrashed.find(task => task.id === id);
-
-        // Remove from trashed tasks
-      setTrashedTasks(currentTrashed.filter((task) => task.id !== id));
-
-        // Add to regular tasks if found
-        if (taskToRestore) {
-          const restoredTask = {
-            ...taskToRestore,
-            deleted: false,
-            deleted_at: undefined
-          };
-        setTasks([restoredTask, ...currentTasks]);
-      }
+      setTrashedTasks(currentTrashed.filter((task) => !restoredIds.has(task.id)));
+      setTasks([...restored, ...currentTasks.filter((task) => !restoredIds.has(task.id))]);
 
       await recordTaskActivity(id, "task_restored");
       queryClient.invalidateQueries({ queryKey: taskKeys.active() });
@@ -1033,7 +1037,7 @@ export const TaskProvider: React.FC<TaskProviderProps> = ({ children }) => {
   // Supabase Realtime: tasks changes（按用户 + 可见清单集合过滤；大量项目时分片；若选中具体清单则优先只订阅该清单）
   const visibleProjectIds = useMemo(() => (projects || []).map(p => p.id), [projects]);
   const narrowedProjectIds = useMemo(() => {
-    if (selectedProject && !builtinScopes.has(selectedProject)) {
+    if (selectedProject && !builtinScopes.has(selectedProject) && !isSmartListProject(selectedProject)) {
       // 当前选中为具体清单，则仅订阅该清单
       return visibleProjectIds.includes(selectedProject) ? [selectedProject] : [];
     }
@@ -1041,8 +1045,8 @@ export const TaskProvider: React.FC<TaskProviderProps> = ({ children }) => {
   }, [selectedProject, builtinScopes, visibleProjectIds]);
 
   useEffect(() => {
-    // Skip realtime subscriptions in offline mode
-    if (isOfflineMode) return;
+    // Skip realtime subscriptions in offline mode and on desktop, where the sync pull emits tasks://changed
+    if (getStorageConfig().backend !== "supabase") return;
     if (!user) return;
     const uid = user.id;
     const invalidate = () => queryClient.invalidateQueries({ queryKey: taskKeys.active() });
@@ -1170,6 +1174,17 @@ export const TaskProvider: React.FC<TaskProviderProps> = ({ children }) => {
       ...otherTasks,
     ];
 
+    // 桌面本地库交给 Rust 计算位置（必要时重排同级任务），其余后端只保存被移动任务的 sort_order
+    const persistOrder = async (movedId: string, prevId: string | undefined, nextId: string | undefined, order: number) => {
+      if (getStorageConfig().backend !== "tauri") {
+        return Boolean(await storageOps.updateTask(movedId, { sort_order: order }));
+      }
+      const changed = await placeTaskBetween(movedId, prevId, nextId);
+      const byId = new Map(changed.map((t) => [t.id, t]));
+      setTasks(useTaskStore.getState().tasks.map((t) => byId.get(t.id) ?? t));
+      return changed.length > 0;
+    };
+
     // Optimistically update the local store
     setTasks(nextTasks);
     lastManualOrderAtRef.current = Date.now();
@@ -1189,7 +1204,7 @@ export const TaskProvider: React.FC<TaskProviderProps> = ({ children }) => {
     try {
       savingSortRef.current = true;
       // 保存排序 - 使用统一的 storage operations
-      const saved = await storageOps.updateTask(movedUpdated.id, { sort_order: newOrder });
+      const saved = await persistOrder(movedUpdated.id, prev?.id, next?.id, newOrder);
       if (!saved) throw new Error("Failed to persist updated sort order");
 
       // Sync query cache without triggering refetch to avoid flicker
@@ -1245,7 +1260,7 @@ export const TaskProvider: React.FC<TaskProviderProps> = ({ children }) => {
         try {
           savingSortRef.current = true;
           // 保存排序 - 使用统一的 storage operations
-          const saved2 = await storageOps.updateTask(job.movedId, { sort_order: newOrder2 });
+          const saved2 = await persistOrder(job.movedId, job.prevId, job.nextId, newOrder2);
           if (!saved2) throw new Error("Failed to persist updated sort order");
           queryClient.setQueryData(taskKeys.active(), useTaskStore.getState().tasks);
           toast({ title: "已保存排序" });
@@ -24,7 +24,7 @@ export interface TaskContextType {
   abandonedLoaded: boolean;
   selectedTask: Task | null;
   selectedProject: string;
-  addTask: (task: Omit<Task, "id">) => Promise<void>;
+  addTask: (task: Omit<Task, "id">) => Promise<Task | null>;
   updateTask: (id: string, task: Partial<Task>) => Promise<void>;
   moveToTrash: (id: string) => Promise<void>;
   restoreFromTrash: (id: string) => Promise<void>;
@@ -0,0 +1,155 @@
+import { useCallback, useEffect, useMemo, useRef, useState } from "react";
+import { mapSessionToPublic } from "@/storage/operations";
+import type { PomodoroSessionType } from "@/storage/operations";
+import { PomodoroEngineState, PomodoroPhaseChange } from "@/types/pomodoro";
+import {
+  fetchPomodoroState,
+  pausePomodoro,
+  POMODORO_PHASE_EVENT,
+  POMODORO_TICK_EVENT,
+  resetPomodoro,
+  selectPomodoroPhase,
+  skipPomodoro,
+  startPomodoro,
+  syncPomodoroSettings,
+} from "@/services/pomodoroEngineService";
+import { listenTauriEvent } from "@/utils/runtime";
+import { playCompletionChime } from "@/utils/pomodoroChime";
+import type { PomodoroSettings } from "./usePomodoroSettings";
+import type { PomodoroTimerState } from "./usePomodoroTimer";
+
+const getDurationForMode = (mode: PomodoroSessionType, settings: PomodoroSettings): number => {
+  switch (mode) {
+    case "focus":
+      return settings.focusDuration;
+    case "long_break":
+      return settings.longBreakDuration;
+    default:
+      return settings.shortBreakDuration;
+  }
+};
+
+/**
+ * 桌面端番茄钟：状态机与计时都在 Rust 侧，这里只负责展示和转发操作。
+ * 窗口隐藏时 Rust 侧继续计时并在托盘显示剩余时间，完成时发送系统通知。
+ */
+export const useNativePomodoroTimer = (
+  settings: PomodoroSettings,
+  ready = true
+): PomodoroTimerState => {
+  const [state, setState] = useState<PomodoroEngineState | null>(null);
+  const [version, setVersion] = useState(0);
+  const [focusTitle, setFocusTitleState] = useState("");
+  const soundEnabledRef = useRef(settings.soundEnabled);
+  soundEnabledRef.current = settings.soundEnabled;
+
+  const run = useCallback(async (action: () => Promise<PomodoroEngineState>) => {
+    try {
+      setState(await action());
+    } catch (error) {
+      console.error("Pomodoro action failed:", error);
+    }
+  }, []);
+
+  // 设置仍保存在本地，读取完成后及每次修改时同步给 Rust 侧
+  useEffect(() => {
+    if (!ready) return;
+    void run(() => syncPomodoroSettings(settings));
+  }, [settings, ready, run]);
+
+  useEffect(() => {
+    let mounted = true;
+    fetchPomodoroState()
+      .then((current) => {
+        if (!mounted) return;
+        setState(current);
+        if (current.session?.type === "work" && current.session.title) {
+          setFocusTitleState(current.session.title);
+        }
+      })
+      .catch((error) => console.error("Failed to load pomodoro state:", error));
+    return () => {
+      mounted = false;
+    };
+  }, []);
+
+  useEffect(() => listenTauriEvent<PomodoroEngineState>(POMODORO_TICK_EVENT, setState), []);
+
+  useEffect(
+    () =>
+      listenTauriEvent<PomodoroPhaseChange>(POMODORO_PHASE_EVENT, (change) => {
+        if (change.completed && soundEnabledRef.current) {
+          playCompletionChime();
+        }
+        setFocusTitleState("");
+        setVersion((prev) => prev + 1);
+      }),
+    []
+  );
+
+  const mode = state?.phase ?? "focus";
+  const session = useMemo(
+    () => (state?.session ? mapSessionToPublic(state.session) : null),
+    [state?.session]
+  );
+  const isRunning = state?.running ?? false;
+  const totalSeconds = state?.totalSeconds ?? getDurationForMode(mode, settings) * 60;
+  const remainingSeconds = state?.remainingSeconds ?? totalSeconds;
+
+  const progress = useMemo(() => {
+    if (!session && !isRunning) return 0;
+    if (totalSeconds <= 0) return 0;
+    return Math.min(1, Math.max(0, 1 - remainingSeconds / totalSeconds));
+  }, [remainingSeconds, totalSeconds, session, isRunning]);
+
+  // 结束进行中的记录后刷新历史并清空标题；跳过由阶段事件处理
+  const finish = useCallback(
+    async (action: () => Promise<PomodoroEngineState>) => {
+      const hadSession = !!state?.session;
+      await run(action);
+      if (hadSession) {
+        setFocusTitleState("");
+        setVersion((prev) => prev + 1);
+      }
+    },
+    [state?.session, run]
+  );
+
+  const start = useCallback(async () => {
+    if (isRunning) return;
+    await run(() => startPomodoro(focusTitle));
+  }, [isRunning, focusTitle, run]);
+
+  const pause = useCallback(() => {
+    void run(pausePomodoro);
+  }, [run]);
+
+  const reset = useCallback(() => finish(resetPomodoro), [finish]);
+
+  const skip = useCallback(() => run(skipPomodoro), [run]);
+
+  const selectMode = useCallback(
+    (target: PomodoroSessionType) => finish(() => selectPomodoroPhase(target)),
+    [finish]
+  );
+
+  return {
+    mode,
+    isRunning,
+    remainingSeconds,
+    totalSeconds,
+    progress,
+    focusStreak: state?.focusStreak ?? 0,
+    focusTarget: state?.focusTarget ?? settings.cyclesBeforeLongBreak,
+    upcomingMode: state?.upcomingPhase ?? "short_break",
+    session,
+    version,
+    focusTitle,
+    setFocusTitle: setFocusTitleState,
+    start,
+    pause,
+    reset,
+    skip,
+    selectMode,
+  };
+};
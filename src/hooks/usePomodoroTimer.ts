@@ -3,6 +3,9 @@ import * as storageOps from "@/storage/operations";
 import type { PomodoroSessionPublic, PomodoroSessionType } from "@/storage/operations";
 import { POMODORO_CYCLE_PROGRESS_KEY, POMODORO_FOCUS_TITLE_KEY } from "@/constants/storage-keys";
 import { PomodoroSettings } from "./usePomodoroSettings";
+import { useNativePomodoroTimer } from "./useNativePomodoroTimer";
+import { playCompletionChime } from "@/utils/pomodoroChime";
+import { isTauriRuntime } from "@/utils/runtime";
 
 type TransitionReason = "complete" | "skip" | "reset";
 
@@ -100,32 +103,6 @@ const computeNextState = (
   return { nextMode: "focus", nextFocusStreak: focusStreak };
 };
 
-const playCompletionChime = () => {
-  try {
-    const audioContext = new AudioContext();
-    const oscillator = audioContext.createOscillator();
-    const gainNode = audioContext.createGain();
-
-    oscillator.type = "sine";
-    oscillator.frequency.value = 880;
-    oscillator.connect(gainNode);
-    gainNode.connect(audioContext.destination);
-
-    gainNode.gain.setValueAtTime(0.15, audioContext.currentTime);
-    gainNode.gain.exponentialRampToValueAtTime(0.00001, audioContext.currentTime + 1.2);
-
-    oscillator.start(audioContext.currentTime);
-    oscillator.stop(audioContext.currentTime + 1.3);
-    oscillator.onended = () => {
-      audioContext.close().catch((error) => {
-        console.error("Failed to close pomodoro chime audio context:", error);
-      });
-    };
-  } catch (error) {
-    console.error("Unable to play pomodoro completion chime:", error);
-  }
-};
-
 export interface PomodoroTimerState {
   mode: PomodoroSessionType;
   isRunning: boolean;
@@ -146,7 +123,7 @@ export interface PomodoroTimerState {
   selectMode: (mode: PomodoroSessionType) => Promise<void>;
 }
 
-export const usePomodoroTimer = (settings: PomodoroSettings): PomodoroTimerState => {
+const useWebPomodoroTimer = (settings: PomodoroSettings): PomodoroTimerState => {
   const [mode, setMode] = useState<PomodoroSessionType>("focus");
   const [session, setSession] = useState<PomodoroSessionPublic | null>(null);
   const [isRunning, setIsRunning] = useState(false);
@@ -471,4 +448,6 @@ export const usePomodoroTimer = (settings: PomodoroSettings): PomodoroTimerState
   };
 };
 
-
+// 桌面端由 Rust 侧计时（窗口隐藏时也准确），Web 端仍在页面里计时
+export const usePomodoroTimer: (settings: PomodoroSettings, ready?: boolean) => PomodoroTimerState =
+  isTauriRuntime() ? useNativePomodoroTimer : useWebPomodoroTimer;
@@ -0,0 +1,7 @@
+import { useSyncExternalStore } from "react";
+import { onStorageModeChange, STORAGE_MODE, type StorageMode } from "@/config/storage";
+
+/** 当前存储模式，切换后（setStorageMode）无需刷新页面即重新渲染 */
+export const useStorageMode = (): StorageMode => {
+  return useSyncExternalStore(onStorageModeChange, () => STORAGE_MODE);
+};
@@ -216,8 +216,10 @@ export type Database = {
           deleted: boolean | null
           deleted_at: string | null
           description: string | null
+          estimate_minutes: number | null
           icon: string | null
           id: string
+          parent_id: string | null
           project: string | null
           sort_order: number | null
           title: string
@@ -234,8 +236,10 @@ export type Database = {
           deleted?: boolean | null
           deleted_at?: string | null
           description?: string | null
+          estimate_minutes?: number | null
           icon?: string | null
           id?: string
+          parent_id?: string | null
           project?: string | null
           sort_order?: number | null
           title: string
@@ -252,8 +256,10 @@ export type Database = {
           deleted?: boolean | null
           deleted_at?: string | null
           description?: string | null
+          estimate_minutes?: number | null
           icon?: string | null
           id?: string
+          parent_id?: string | null
           project?: string | null
           sort_order?: number | null
           title?: string
@@ -286,6 +292,10 @@ export type Database = {
       }
     }
     Functions: {
+      get_task_subtree: {
+        Args: { root_id: string }
+        Returns: Database["public"]["Tables"]["tasks"]["Row"][]
+      }
       join_shared_project: {
         Args: { input_share_code: string; joining_user_id: string }
         Returns: string
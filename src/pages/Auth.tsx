@@ -9,7 +9,7 @@ import { useAuth } from "@/contexts/AuthContext";
 import { Label } from "@/components/ui/label";
 import { WifiOff } from "lucide-react";
 import { setStorageMode } from "@/config/storage";
-import { navigateWithReload } from "@/utils/runtime";
+import { isTauriRuntime, navigateWithReload } from "@/utils/runtime";
 
 const Auth = () => {
   const [email, setEmail] = useState("");
@@ -67,7 +67,11 @@ const Auth = () => {
 
   const handleOfflineMode = () => {
     setStorageMode("offline");
-    // Reload the page to reinitialize all contexts with new storage mode
+    // 桌面端始终使用本地库，直接进入；浏览器需重新加载以换用 IndexedDB
+    if (isTauriRuntime()) {
+      navigate("/", { replace: true });
+      return;
+    }
     navigateWithReload("/");
   };
 
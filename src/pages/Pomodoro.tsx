@@ -11,6 +11,7 @@ import {
   TimerReset,
 } from "lucide-react";
 import { format } from "date-fns";
+import { useSearchParams } from "react-router-dom";
 import { Button } from "@/components/ui/button";
 import { Switch } from "@/components/ui/switch";
 import { Label } from "@/components/ui/label";
@@ -50,7 +51,7 @@ const formatTimer = (seconds: number) => {
 
 const Pomodoro = () => {
   const { settings, updateSettings, resetSettings, isReady } = usePomodoroSettings();
-  const timer = usePomodoroTimer(settings);
+  const timer = usePomodoroTimer(settings, isReady);
   const {
     today: todayStats,
     recentSessions,
@@ -59,6 +60,16 @@ const Pomodoro = () => {
   } = usePomodoroHistory({ includeHeatmap: false });
   const [settingsDirty, setSettingsDirty] = useState(false);
   const [panel, setPanel] = useState<"none" | "stats" | "settings">("none");
+  const [searchParams, setSearchParams] = useSearchParams();
+
+  // 从托盘菜单进入时自动开始
+  useEffect(() => {
+    if (!isReady || searchParams.get("autostart") !== "1") return;
+    setSearchParams({}, { replace: true });
+    if (!timer.isRunning) {
+      void timer.start();
+    }
+  }, [isReady, searchParams, setSearchParams, timer]);
 
   useEffect(() => {
     if (timer.version > 0) {
@@ -0,0 +1,35 @@
+import { AnalyticsReport, RenderedReport, ReportFormat } from "@/types/analytics";
+import { invokeTauri } from "@/utils/runtime";
+
+/**
+ * Analytics Service - 仅桌面端
+ * 汇总在 Rust 侧（src-tauri/src/analytics），按本地时间统计番茄、完成任务与打卡
+ */
+
+export const fetchAnalyticsReport = (from: string, to: string): Promise<AnalyticsReport> =>
+  invokeTauri<AnalyticsReport>("get_analytics_report", { from, to });
+
+const MIME_TYPES: Record<ReportFormat, string> = {
+  csv: "text/csv;charset=utf-8",
+  json: "application/json",
+  html: "text/html;charset=utf-8",
+};
+
+/** 生成报告并作为文件下载；CSV 加 BOM 以便 Excel 正确识别中文 */
+export async function downloadAnalyticsReport(
+  from: string,
+  to: string,
+  format: ReportFormat
+): Promise<string> {
+  const report = await invokeTauri<RenderedReport>("export_analytics_report", { from, to, format });
+  const content = format === "csv" ? `\uFEFF${report.content}` : report.content;
+  const url = URL.createObjectURL(new Blob([content], { type: MIME_TYPES[format] }));
+  const link = document.createElement("a");
+  link.href = url;
+  link.download = report.file_name;
+  document.body.appendChild(link);
+  link.click();
+  document.body.removeChild(link);
+  URL.revokeObjectURL(url);
+  return report.file_name;
+}
@@ -0,0 +1,44 @@
+import { CalendarFeedFilter, CalendarFeedOverview, CalendarFeedSettings } from "@/types/calendarFeed";
+import { invokeTauri } from "@/utils/runtime";
+
+/**
+ * Calendar Feed Service - 仅桌面端
+ * 在 127.0.0.1 上提供只读的 .ics 订阅（src-tauri/src/feed.rs），只包含有截止日期的任务；
+ * 每个订阅有独立的令牌，删除后对应链接立即失效。
+ * 同一端口还提供 CalDAV（src-tauri/src/caldav.rs），每个清单是一个待办日历，用户名任意、密码为订阅令牌。
+ * 默认只有本机可访问；开启局域网访问后手机等设备也能连接，但走明文 HTTP，令牌与任务内容可被同一网络中的他人截获
+ */
+
+export const FEED_PATH = "/calendar.ics";
+export const CALDAV_PATH = "/dav/";
+
+export async function fetchCalendarFeeds(): Promise<CalendarFeedOverview> {
+  return invokeTauri<CalendarFeedOverview>("get_calendar_feeds");
+}
+
+/** 保存设置并按新设置重启服务 */
+export async function saveCalendarFeedSettings(settings: CalendarFeedSettings): Promise<CalendarFeedOverview> {
+  return invokeTauri<CalendarFeedOverview>("set_calendar_feed_settings", { settings });
+}
+
+export async function createCalendarFeed(name: string): Promise<CalendarFeedOverview> {
+  return invokeTauri<CalendarFeedOverview>("create_calendar_feed", { name });
+}
+
+export async function deleteCalendarFeed(id: string): Promise<CalendarFeedOverview> {
+  return invokeTauri<CalendarFeedOverview>("delete_calendar_feed", { id });
+}
+
+/** webcal:// 订阅链接，筛选参数追加在令牌之后 */
+export function calendarFeedUrl(host: string, port: number, token: string, filter: CalendarFeedFilter = {}): string {
+  const params = new URLSearchParams({ token });
+  if (filter.project) params.set("project", filter.project);
+  if (filter.tag) params.set("tag", filter.tag);
+  if (filter.events) params.set("type", "event");
+  return `webcal://${host}:${port}${FEED_PATH}?${params.toString()}`;
+}
+
+/** CalDAV 服务器地址，在支持 CalDAV 的客户端中作为账户地址填写 */
+export function caldavUrl(host: string, port: number): string {
+  return `http://${host}:${port}${CALDAV_PATH}`;
+}
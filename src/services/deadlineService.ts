@@ -2,6 +2,8 @@ import { Task } from "@/types/task";
 import { toast } from "@/hooks/use-toast";
 import { ensureNotificationPermission, sendNotification as sendUnifiedNotification } from "@/utils/notifications";
 import * as storageOps from "@/storage/operations";
+import { getStorageConfig } from "@/config/storage";
+import { invokeTauri, isTauriRuntime } from "@/utils/runtime";
 
 interface DeadlineNotificationConfig {
   enabled: boolean;
@@ -27,7 +29,9 @@ export const getDeadlineConfig = async (): Promise<DeadlineNotificationConfig> =
       enabled: settings.deadline_notification_enabled ?? DEFAULT_CONFIG.enabled,
       reminderMinutes: settings.deadline_notification_days ?? DEFAULT_CONFIG.reminderMinutes,
       webhookEnabled: settings.webhook_enabled ?? DEFAULT_CONFIG.webhookEnabled,
-      browserNotificationEnabled: DEFAULT_CONFIG.browserNotificationEnabled,
+      // 数据存在桌面端本地数据库时由 Rust 进程发送系统通知（src-tauri/src/reminders.rs），这里不再重复发送；
+      // 其他存储模式下 Rust 侧读不到任务和设置，仍由页面提醒
+      browserNotificationEnabled: DEFAULT_CONFIG.browserNotificationEnabled && getStorageConfig().backend !== "tauri",
     };
   } catch (error) {
     console.error("Error getting deadline config:", error);
@@ -133,6 +137,30 @@ export const formatTimeUntilDeadline = (deadline: string): string => {
   }
 };
 
+// 把 Webhook 配置和任务交给桌面端的发件箱，Webhook 关闭时传 null
+const queueDesktopWebhooks = async (tasks: Task[], reminderMinutes: number): Promise<void> => {
+  try {
+    const settings = await storageOps.getUserSettings();
+    const url = settings.webhook_url?.trim();
+    const config = settings.webhook_enabled && url
+      ? {
+          kind: settings.webhook_type || "feishu",
+          url,
+          secret: settings.webhook_secret || null,
+        }
+      : null;
+    await invokeTauri("queue_deadline_webhooks", {
+      config,
+      tasks: tasks.map(({ id, title, date, project, flagged, completed, deleted, abandoned }) => ({
+        id, title, date, project, flagged, completed, deleted, abandoned,
+      })),
+      reminderMinutes,
+    });
+  } catch (error) {
+    console.error("Error queueing webhooks:", error);
+  }
+};
+
 // 批量检查并发送通知
 export const checkAndNotify = async (tasks: Task[]): Promise<void> => {
   try {
@@ -142,6 +170,12 @@ export const checkAndNotify = async (tasks: Task[]): Promise<void> => {
       return;
     }
 
+    // 桌面端的 Webhook 由 Rust 进程经发件箱推送（带签名和重试）。数据在本地数据库时 Rust 侧自行读取，
+    // 其他存储模式下由这里把任务和配置交给它（逾期任务也要推送，所以放在即将截止的判断之前）
+    if (isTauriRuntime() && getStorageConfig().backend !== "tauri") {
+      await queueDesktopWebhooks(tasks, config.reminderMinutes);
+    }
+
     const upcomingTasks = checkUpcomingDeadlines(tasks, config.reminderMinutes);
     
     if (upcomingTasks.length === 0) {
@@ -173,8 +207,8 @@ export const checkAndNotify = async (tasks: Task[]): Promise<void> => {
       });
     }
 
-    // TODO: 如果启用了 Webhook，这里可以调用云函数发送通知
-    if (config.webhookEnabled) {
+    // TODO: Web 端如果启用了 Webhook，这里可以调用云函数发送通知
+    if (config.webhookEnabled && !isTauriRuntime()) {
       console.log("Webhook notifications would be sent for:", upcomingTasks);
       // 未来可以通过 Supabase Functions 实现 Webhook 通知
     }
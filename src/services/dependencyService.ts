@@ -0,0 +1,26 @@
+import { NextAction, TaskDependencies } from "@/types/dependency";
+import { invokeTauri } from "@/utils/runtime";
+
+/**
+ * Dependency Service - 仅桌面端
+ * 环检测、阻塞状态与拓扑排序都在 Rust 侧（src-tauri/src/graph.rs）
+ */
+
+/** 完成前置任务后，Rust 侧推送因此解除阻塞的任务 */
+export const TASKS_UNBLOCKED_EVENT = "tasks://unblocked";
+
+export const fetchTaskDependencies = (taskId: string): Promise<TaskDependencies> =>
+  invokeTauri<TaskDependencies>("get_task_dependencies", { taskId });
+
+/** 形成循环依赖时会被拒绝，错误信息里带有环上的任务标题 */
+export const addTaskDependency = (taskId: string, blockerId: string): Promise<TaskDependencies> =>
+  invokeTauri<TaskDependencies>("add_task_dependency", { taskId, blockerId });
+
+export const removeTaskDependency = (taskId: string, blockerId: string): Promise<boolean> =>
+  invokeTauri<boolean>("remove_task_dependency", { taskId, blockerId });
+
+export const fetchBlockedTaskIds = (): Promise<string[]> =>
+  invokeTauri<string[]>("get_blocked_task_ids");
+
+export const fetchNextActions = (projectId?: string | null): Promise<NextAction[]> =>
+  invokeTauri<NextAction[]>("get_next_actions", { projectId: projectId ?? null });
@@ -0,0 +1,28 @@
+import { IcsExportOptions, IcsImportSummary } from "@/types/ical";
+import { RenderedReport } from "@/types/analytics";
+import { invokeTauri } from "@/utils/runtime";
+
+/**
+ * iCalendar Service - 仅桌面端
+ * 序列化与解析在 Rust 侧（src-tauri/src/ical），任务导出为 VTODO，UID 在导入时保留，重复导入会更新而不是重复创建
+ */
+
+/** 导出 .ics 并下载，返回文件名 */
+export async function downloadIcs(options: IcsExportOptions): Promise<string> {
+  const calendar = await invokeTauri<RenderedReport>("export_ics", { options });
+  const url = URL.createObjectURL(new Blob([calendar.content], { type: "text/calendar;charset=utf-8" }));
+  const link = document.createElement("a");
+  link.href = url;
+  link.download = calendar.file_name;
+  document.body.appendChild(link);
+  link.click();
+  document.body.removeChild(link);
+  URL.revokeObjectURL(url);
+  return calendar.file_name;
+}
+
+/** 导入 .ics 文件；指定 projectId 时全部放入该清单 */
+export async function importIcsFile(file: File, projectId?: string | null): Promise<IcsImportSummary> {
+  const content = await file.text();
+  return invokeTauri<IcsImportSummary>("import_ics", { content, projectId: projectId ?? null });
+}
@@ -0,0 +1,47 @@
+import { DiscoveredPeer, PairingCode, PeerOverview, PeerSettings } from "@/types/peer";
+import { invokeTauri, listenTauriEvent } from "@/utils/runtime";
+
+/**
+ * Peer Sync Service - 仅桌面端
+ * 不经 Supabase，在局域网内的 Snail 实例之间同步（src-tauri/src/peer）：通过 mDNS 发现彼此，
+ * 用对方显示的一次性配对码配对（配对码本身不经网络传输，输错即作废），之后的变更经加密通道交换。
+ * 变更取自与云同步相同的发件箱，任务按字段合并；开启后后台每分钟与已配对设备同步一次
+ */
+
+export const PEERS_SYNCED_EVENT = "peers://synced";
+
+export async function fetchPeers(): Promise<PeerOverview> {
+  return invokeTauri<PeerOverview>("get_peers");
+}
+
+/** 保存设置并按新设置重启服务 */
+export async function savePeerSettings(settings: PeerSettings): Promise<PeerOverview> {
+  return invokeTauri<PeerOverview>("set_peer_settings", { settings });
+}
+
+export async function createPairingCode(): Promise<PairingCode> {
+  return invokeTauri<PairingCode>("create_pairing_code");
+}
+
+/** 监听几秒钟，返回局域网中的其他实例 */
+export async function discoverPeers(): Promise<DiscoveredPeer[]> {
+  return invokeTauri<DiscoveredPeer[]>("discover_peers");
+}
+
+/** 用对方显示的配对码配对，成功后立即同步一次 */
+export async function pairPeer(address: string, code: string): Promise<PeerOverview> {
+  return invokeTauri<PeerOverview>("pair_peer", { address, code });
+}
+
+export async function syncPeers(): Promise<PeerOverview> {
+  return invokeTauri<PeerOverview>("sync_peers");
+}
+
+export async function unpairPeer(id: string): Promise<PeerOverview> {
+  return invokeTauri<PeerOverview>("unpair_peer", { id });
+}
+
+/** 每次与其他设备同步后（无论哪一方发起）收到最新概览 */
+export function onPeersSynced(handler: (overview: PeerOverview) => void): () => void {
+  return listenTauriEvent<PeerOverview>(PEERS_SYNCED_EVENT, handler);
+}
@@ -0,0 +1,36 @@
+import { PomodoroEngineState } from "@/types/pomodoro";
+import type { PomodoroSessionType } from "@/storage/operations";
+import type { PomodoroSettings } from "@/hooks/usePomodoroSettings";
+import { invokeTauri } from "@/utils/runtime";
+
+/**
+ * Pomodoro Engine Service - 仅桌面端
+ * 计时状态机在 Rust 侧（src-tauri/src/pomodoro.rs），窗口隐藏或被节流时也不会变慢
+ */
+
+/** 每秒推送一次 PomodoroEngineState */
+export const POMODORO_TICK_EVENT = "pomodoro://tick";
+/** 阶段完成或被跳过时推送 PomodoroPhaseChange */
+export const POMODORO_PHASE_EVENT = "pomodoro://phase";
+
+export const fetchPomodoroState = (): Promise<PomodoroEngineState> =>
+  invokeTauri<PomodoroEngineState>("get_pomodoro_state");
+
+/** 开始当前阶段，或继续已暂停的计时；标题只用于专注 */
+export const startPomodoro = (title?: string): Promise<PomodoroEngineState> =>
+  invokeTauri<PomodoroEngineState>("pomodoro_start", { title: title ?? null });
+
+export const pausePomodoro = (): Promise<PomodoroEngineState> =>
+  invokeTauri<PomodoroEngineState>("pomodoro_pause");
+
+export const resetPomodoro = (): Promise<PomodoroEngineState> =>
+  invokeTauri<PomodoroEngineState>("pomodoro_reset");
+
+export const skipPomodoro = (): Promise<PomodoroEngineState> =>
+  invokeTauri<PomodoroEngineState>("pomodoro_skip");
+
+export const selectPomodoroPhase = (phase: PomodoroSessionType): Promise<PomodoroEngineState> =>
+  invokeTauri<PomodoroEngineState>("pomodoro_select", { phase });
+
+export const syncPomodoroSettings = (settings: PomodoroSettings): Promise<PomodoroEngineState> =>
+  invokeTauri<PomodoroEngineState>("set_pomodoro_settings", { settings });
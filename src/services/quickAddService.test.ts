@@ -0,0 +1,62 @@
+/**
+ * Tests for saving tasks from the quick-capture window
+ * A captured task is saved through the storage adapter and read back the way the main window reads tasks
+ */
+
+import { describe, it, expect, vi } from 'vitest';
+
+vi.mock('@/storage/index', async () => {
+  const { IndexedDBAdapter } = await import('@/storage/indexeddb/IndexedDBAdapter');
+  const adapter = new IndexedDBAdapter();
+  return {
+    isOfflineMode: true,
+    getStorage: () => adapter,
+    initializeStorage: async () => {
+      if (!adapter.isReady()) {
+        await adapter.initialize();
+      }
+    },
+  };
+});
+
+import * as operations from '@/storage/operations';
+import { QuickAddOps, saveCapturedTask } from './quickAddService';
+
+const ops: QuickAddOps = {
+  addTask: operations.addTask,
+  listAllTags: operations.fetchAllTags,
+  createTag: operations.createTag,
+  attachTagToTask: operations.attachTagToTask,
+};
+
+describe('saveCapturedTask', () => {
+  it('saves a captured task where the main window reads tasks', async () => {
+    const project = await operations.createProject({ name: '工作', icon: 'folder' });
+    const existing = await operations.createTag('紧急');
+
+    const saved = await saveCapturedTask(
+      {
+        title: '写周报',
+        project: project!.id,
+        date: '2025-01-03T07:00:00.000Z',
+        tags: ['紧急', '周报'],
+        flagged: true,
+        rrule: null,
+      },
+      ops
+    );
+    expect(saved).not.toBeNull();
+
+    const task = (await operations.fetchTasks()).find((t) => t.id === saved!.id);
+    expect(task).toMatchObject({
+      title: '写周报',
+      project: project!.id,
+      date: '2025-01-03T07:00:00.000Z',
+      flagged: true,
+      completed: false,
+    });
+    const tags = (await operations.getTagsByTaskIds([saved!.id]))[saved!.id];
+    expect(tags.map((t) => t.name).sort()).toEqual(['周报', '紧急']);
+    expect(tags.find((t) => t.name === '紧急')?.id).toBe(existing!.id);
+  });
+});
@@ -0,0 +1,73 @@
+import { Task } from "@/types/task";
+import { Tag } from "@/types/tag";
+import { Project } from "@/types/project";
+import { CapturedTask, QuickAddResult } from "@/types/quickAdd";
+import { setTaskRecurrence } from "@/services/recurrenceService";
+import { invokeTauri } from "@/utils/runtime";
+
+/**
+ * Quick Add Service - 仅桌面端
+ * 中英文日期、#项目、@标签、!flag 的识别在 Rust 侧（src-tauri/src/quick_add.rs）
+ * 快速添加窗口（public/quick-add.html）不直接写库，提交的任务排队等主窗口按当前存储模式保存
+ */
+
+export const CAPTURED_EVENT = "capture://task";
+
+export const parseQuickAdd = (text: string): Promise<QuickAddResult> =>
+  invokeTauri<QuickAddResult>("parse_quick_add", { text });
+
+/** 取出快速添加窗口提交、尚未保存的任务 */
+export const takeCapturedTasks = (): Promise<CapturedTask[]> =>
+  invokeTauri<CapturedTask[]>("take_captured_tasks");
+
+/** 把主窗口的清单告诉快速添加窗口，#项目 也按这份清单识别 */
+export const publishCaptureProjects = (projects: Project[]): Promise<void> =>
+  invokeTauri<void>("set_capture_projects", {
+    projects: projects.map(({ id, name }) => ({ id, name })),
+  });
+
+/** 保存任务所用的操作，主窗口传入任务上下文里的同名方法 */
+export interface QuickAddOps {
+  addTask: (task: Omit<Task, "id">) => Promise<Task | null>;
+  listAllTags: (projectId?: string | null) => Promise<Tag[]>;
+  createTag: (name: string, projectId?: string | null) => Promise<Tag | null>;
+  attachTagToTask: (taskId: string, tagId: string, tagData?: Tag) => Promise<unknown>;
+}
+
+/** 快速添加里的 @标签 和重复规则要在任务创建后再写入 */
+export async function applyQuickAddExtras(
+  task: Task,
+  extras: { tags: string[]; rrule?: string | null },
+  ops: Pick<QuickAddOps, "listAllTags" | "createTag" | "attachTagToTask">
+): Promise<void> {
+  if (extras.tags.length > 0) {
+    const existing = await ops.listAllTags();
+    for (const name of extras.tags) {
+      const found = existing.find(
+        (tag) =>
+          tag.name.toLowerCase() === name.toLowerCase() &&
+          (!tag.project_id || tag.project_id === task.project)
+      );
+      const tag = found ?? (await ops.createTag(name, null));
+      if (tag) await ops.attachTagToTask(task.id, tag.id, tag);
+    }
+  }
+  if (extras.rrule && task.date) {
+    await setTaskRecurrence(task.id, extras.rrule);
+  }
+}
+
+/** 保存快速添加窗口提交的任务，与在主窗口输入框中添加的走同一条路径 */
+export async function saveCapturedTask(captured: CapturedTask, ops: QuickAddOps): Promise<Task | null> {
+  const task = await ops.addTask({
+    title: captured.title,
+    completed: false,
+    project: captured.project || undefined,
+    date: captured.date || undefined,
+    flagged: captured.flagged || undefined,
+  });
+  if (task) {
+    await applyQuickAddExtras(task, captured, ops);
+  }
+  return task;
+}
@@ -0,0 +1,23 @@
+import { TaskRecurrence } from "@/types/recurrence";
+import { invokeTauri } from "@/utils/runtime";
+
+/**
+ * Recurrence Service - 仅桌面端
+ * 规则展开与完成后生成下一次任务都在 Rust 侧（src-tauri/src/storage/recurrence.rs）
+ */
+
+export const fetchTaskRecurrence = (taskId: string): Promise<TaskRecurrence | null> =>
+  invokeTauri<TaskRecurrence | null>("get_task_recurrence", { taskId });
+
+export const setTaskRecurrence = (
+  taskId: string,
+  rrule: string,
+  exdates: string[] = []
+): Promise<TaskRecurrence> =>
+  invokeTauri<TaskRecurrence>("set_task_recurrence", { taskId, rrule, exdates });
+
+export const clearTaskRecurrence = (taskId: string): Promise<boolean> =>
+  invokeTauri<boolean>("clear_task_recurrence", { taskId });
+
+export const fetchUpcomingOccurrences = (taskId: string, limit = 3): Promise<string[]> =>
+  invokeTauri<string[]>("get_upcoming_occurrences", { taskId, limit });
@@ -0,0 +1,36 @@
+import { SmartList } from "@/types/smartList";
+import { Task } from "@/types/task";
+import { invokeTauri } from "@/utils/runtime";
+
+/**
+ * Smart List Service - 仅桌面端
+ * 查询语言的解析与执行都在 Rust 侧（src-tauri/src/query）
+ */
+
+export const fetchSmartLists = (): Promise<SmartList[]> =>
+  invokeTauri<SmartList[]>("get_smart_lists");
+
+export const createSmartList = (name: string, query: string): Promise<SmartList> =>
+  invokeTauri<SmartList>("create_smart_list", { list: { name, query } });
+
+export const updateSmartList = (
+  id: string,
+  updates: Partial<Pick<SmartList, "name" | "query" | "icon" | "sort_order">>
+): Promise<SmartList | null> =>
+  invokeTauri<SmartList | null>("update_smart_list", { id, updates });
+
+export const deleteSmartList = (id: string): Promise<boolean> =>
+  invokeTauri<boolean>("delete_smart_list", { id });
+
+export const runSmartList = (id: string): Promise<Task[]> =>
+  invokeTauri<Task[]>("run_smart_list", { id });
+
+/** 校验查询语句，返回错误信息；合法时返回 null */
+export const validateQuery = async (query: string): Promise<string | null> => {
+  try {
+    await invokeTauri<void>("validate_query", { query });
+    return null;
+  } catch (error) {
+    return error instanceof Error ? error.message : String(error);
+  }
+};
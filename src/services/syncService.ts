@@ -0,0 +1,78 @@
+import type { Session } from "@supabase/supabase-js";
+import { ENV_CONFIG } from "@/config/env";
+import { getStorageConfig } from "@/config/storage";
+import { SyncConflict, SyncReport, SyncStatus } from "@/types/sync";
+import { invokeTauri, isTauriRuntime, listenTauriEvent } from "@/utils/runtime";
+
+/**
+ * Sync Service - 仅桌面端
+ * 桌面端界面在两种模式下都只读写本地数据库（见 getStorageConfig），与 Supabase 之间的推送和拉取全部由 Rust 侧
+ * （src-tauri/src/sync）完成：在线模式登录后，把会话交给它在后台双向同步。本地库的每次变更记入发件箱，
+ * 按 (updated_at, id) 游标拉取云端变更，再推送发件箱；两边都改过的任务逐字段合并，其余行以较新的为准，并通过事件通知。
+ * 离线模式没有会话，从不同步
+ */
+
+export const SYNC_CONFLICT_EVENT = "sync://conflict";
+export const SYNC_STATUS_EVENT = "sync://status";
+
+/** localStorage key，用户在设置中关闭同步后不再随登录自动开启 */
+const SYNC_DISABLED_KEY = "snail_desktop_sync_disabled";
+
+export function isDesktopSyncEnabled(): boolean {
+  try {
+    return localStorage.getItem(SYNC_DISABLED_KEY) !== "1";
+  } catch {
+    return true;
+  }
+}
+
+/** 把当前会话交给 Rust 侧并立即同步一次；退出登录时传 null */
+export async function configureSync(session: Session | null): Promise<SyncReport | null> {
+  if (!isTauriRuntime()) return null;
+  const usable = session && !getStorageConfig().isOfflineMode && isDesktopSyncEnabled();
+  const config = usable
+    ? {
+        url: ENV_CONFIG.SUPABASE_URL,
+        anonKey: ENV_CONFIG.SUPABASE_ANON_KEY,
+        accessToken: session.access_token,
+        userId: session.user.id,
+      }
+    : null;
+  return invokeTauri<SyncReport | null>("configure_sync", { config });
+}
+
+export async function syncNow(): Promise<SyncReport> {
+  return invokeTauri<SyncReport>("sync_now");
+}
+
+export async function fetchSyncStatus(): Promise<SyncStatus> {
+  return invokeTauri<SyncStatus>("get_sync_status");
+}
+
+/** 重新开启：记住偏好，并用当前会话同步（首次会推送全部本地数据） */
+export async function enableSync(session: Session | null): Promise<SyncReport | null> {
+  try {
+    localStorage.removeItem(SYNC_DISABLED_KEY);
+  } catch {
+    console.warn("Failed to save sync preference");
+  }
+  return configureSync(session);
+}
+
+/** 关闭同步并清空发件箱，再次开启时从头推送与拉取 */
+export async function disableSync(): Promise<SyncStatus> {
+  try {
+    localStorage.setItem(SYNC_DISABLED_KEY, "1");
+  } catch {
+    console.warn("Failed to save sync preference");
+  }
+  return invokeTauri<SyncStatus>("disable_sync");
+}
+
+export function onSyncConflict(handler: (conflict: SyncConflict) => void): () => void {
+  return listenTauriEvent<SyncConflict>(SYNC_CONFLICT_EVENT, handler);
+}
+
+export function onSyncStatus(handler: (status: SyncStatus) => void): () => void {
+  return listenTauriEvent<SyncStatus>(SYNC_STATUS_EVENT, handler);
+}
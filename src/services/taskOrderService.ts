@@ -0,0 +1,14 @@
+import { Task } from "@/types/task";
+import { invokeTauri } from "@/utils/runtime";
+
+/**
+ * Task Order Service - 仅桌面端本地数据库（backend 为 tauri）
+ * 位置由 Rust 侧（src-tauri/src/crdt.rs）取前后两项的中点；中点挤不进去时会先重排同级任务，
+ * 因此返回所有位置变化了的任务，被移动的任务在最前
+ */
+export const placeTaskBetween = (
+  id: string,
+  before?: string | null,
+  after?: string | null
+): Promise<Task[]> =>
+  invokeTauri<Task[]>("place_task_between", { id, before: before ?? null, after: after ?? null });
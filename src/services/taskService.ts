@@ -120,7 +120,7 @@ export const fetchTasks = async (includeDeleted: boolean = false, isGuest: boole
 };
 
 // 统一的Task数据映射函数
-const mapTaskData = (item: any): Task => {
+export const mapTaskData = (item: any): Task => {
   // Normalize attachments: database may return JSON string or array
   let normalizedAttachments: any[] = [];
   const raw = item.attachments;
@@ -176,6 +176,8 @@ const mapTaskData = (item: any): Task => {
     abandoned: item.abandoned || false,
     abandoned_at: item.abandoned_at || undefined,
     flagged: item.flagged ?? false,
+    parent_id: item.parent_id || undefined,
+    estimate_minutes: item.estimate_minutes ?? undefined,
     // Note: anonymous_id is used in guest mode flows but not in Task type; omit
     attachments: normalizedAttachments
   };
@@ -275,6 +277,8 @@ export const addTask = async (task: Omit<Task, "id">, isGuest: boolean = false):
         .select("sort_order")
         .eq("anonymous_id", guestId)
         .eq("completed", task.completed)
+        // 排序值只在同一父任务的子任务之间比较
+        .filter("parent_id", task.parent_id ? "eq" : "is", task.parent_id ?? null)
         .order("sort_order", { ascending: true })
         .limit(1);
       
@@ -376,6 +380,8 @@ export const addTask = async (task: Omit<Task, "id">, isGuest: boolean = false):
       .select("sort_order")
       .eq("project", task.project)
       .eq("completed", task.completed)
+      // sort_order is scoped per parent: subtasks only compete with their siblings
+      .filter("parent_id", task.parent_id ? "eq" : "is", task.parent_id ?? null)
       .order("sort_order", { ascending: true })
       .limit(1);
 
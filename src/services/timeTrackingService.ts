@@ -0,0 +1,60 @@
+import { ManualTimeEntryInput, TimeEntry, TimeSummary } from "@/types/timeTracking";
+import { RenderedReport } from "@/types/analytics";
+import { invokeTauri } from "@/utils/runtime";
+
+/**
+ * Time Tracking Service - 仅桌面端
+ * 计时与汇总在 Rust 侧（src-tauri/src/storage/time_tracking.rs），同一时间只能有一个计时器在运行
+ */
+
+/** 已有计时器在运行时会被拒绝，错误信息里带有正在计时的任务标题 */
+export const startTaskTimer = (taskId: string, note?: string): Promise<TimeEntry> =>
+  invokeTauri<TimeEntry>("start_task_timer", { taskId, note: note ?? null });
+
+export const stopTaskTimer = (): Promise<TimeEntry | null> =>
+  invokeTauri<TimeEntry | null>("stop_task_timer");
+
+export const fetchRunningTimer = (): Promise<TimeEntry | null> =>
+  invokeTauri<TimeEntry | null>("get_running_timer");
+
+/** 任务的全部时间记录（含关联的专注番茄），按开始时间排序 */
+export const fetchTimeEntries = (taskId: string): Promise<TimeEntry[]> =>
+  invokeTauri<TimeEntry[]>("get_time_entries", { taskId });
+
+export const addTimeEntry = (entry: ManualTimeEntryInput): Promise<TimeEntry> =>
+  invokeTauri<TimeEntry>("add_time_entry", { entry });
+
+export const updateTimeEntry = (
+  id: string,
+  updates: Partial<Pick<TimeEntry, "started_at" | "ended_at" | "note">>
+): Promise<TimeEntry | null> => invokeTauri<TimeEntry | null>("update_time_entry", { id, updates });
+
+export const deleteTimeEntry = (id: string): Promise<boolean> =>
+  invokeTauri<boolean>("delete_time_entry", { id });
+
+/** 按任务、项目、标签汇总的实际用时与预估用时 */
+export const fetchTimeSummary = (): Promise<TimeSummary> =>
+  invokeTauri<TimeSummary>("get_time_summary");
+
+/** 导出时间表 CSV 并下载；加 BOM 以便 Excel 正确识别中文 */
+export async function downloadTimesheet(from: string, to: string): Promise<string> {
+  const sheet = await invokeTauri<RenderedReport>("export_timesheet", { from, to });
+  const blob = new Blob([`\uFEFF${sheet.content}`], { type: "text/csv;charset=utf-8" });
+  const url = URL.createObjectURL(blob);
+  const link = document.createElement("a");
+  link.href = url;
+  link.download = sheet.file_name;
+  document.body.appendChild(link);
+  link.click();
+  document.body.removeChild(link);
+  URL.revokeObjectURL(url);
+  return sheet.file_name;
+}
+
+/** 90 -> "1小时30分" */
+export const formatMinutes = (minutes: number): string => {
+  const hours = Math.floor(minutes / 60);
+  const rest = minutes % 60;
+  if (hours === 0) return `${rest}分`;
+  return rest === 0 ? `${hours}小时` : `${hours}小时${rest}分`;
+};
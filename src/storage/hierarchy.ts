@@ -0,0 +1,203 @@
+/**
+ * Task Hierarchy
+ * Pure helpers for subtasks, shared by the storage adapters and the UI
+ *
+ * Rules (the same as the desktop store in src-tauri/src/storage/hierarchy.rs):
+ * - a subtask always lives in its parent's project
+ * - sort_order only orders a task among its siblings; new and moved tasks go first
+ * - a project change is carried down to every subtask
+ * - moving a subtask to another project on its own takes it out of its parent
+ */
+
+import { Task } from '@/types/task';
+import { CreateTaskInput, MoveTaskTarget, TaskProgress } from './types';
+
+/**
+ * Gap between neighbouring sort_order values
+ */
+export const SORT_ORDER_STEP = 1000;
+
+// parent_id / project: undefined and null both mean "none"
+const same = (a?: string | null, b?: string | null) => (a ?? null) === (b ?? null);
+
+/**
+ * Compare sibling tasks: sort_order ascending, missing values last
+ */
+const bySiblingOrder = (a: Task, b: Task): number => {
+  const aOrder = a.sort_order ?? Number.POSITIVE_INFINITY;
+  const bOrder = b.sort_order ?? Number.POSITIVE_INFINITY;
+  return aOrder === bOrder ? 0 : aOrder - bOrder;
+};
+
+/**
+ * A task followed by all of its subtasks, depth first with siblings in list order.
+ * Empty if the root is not in `tasks`.
+ */
+export function collectSubtree(tasks: Task[], rootId: string): Task[] {
+  const root = tasks.find((t) => t.id === rootId);
+  if (!root) return [];
+
+  const children = new Map<string, Task[]>();
+  for (const task of tasks) {
+    if (!task.parent_id || task.id === rootId) continue;
+    const siblings = children.get(task.parent_id) ?? [];
+    siblings.push(task);
+    children.set(task.parent_id, siblings);
+  }
+
+  const result: Task[] = [];
+  const seen = new Set<string>();
+  const visit = (task: Task) => {
+    // 防御损坏数据中的循环引用
+    if (seen.has(task.id)) return;
+    seen.add(task.id);
+    result.push(task);
+    for (const child of (children.get(task.id) ?? []).sort(bySiblingOrder)) {
+      visit(child);
+    }
+  };
+  visit(root);
+  return result;
+}
+
+/**
+ * Completion of a task's subtasks, at every depth.
+ * `subtree` is root first, as returned by collectSubtree / getSubtree.
+ * Trashed and abandoned subtasks are left out; a task without subtasks counts as 0 or 100 by itself.
+ */
+export function computeProgress(subtree: Task[]): TaskProgress {
+  const [root, ...descendants] = subtree;
+  if (!root) return { total: 0, completed: 0, percent: 0 };
+
+  const counted = descendants.filter((t) => !t.deleted && !t.abandoned);
+  const total = counted.length;
+  const completed = counted.filter((t) => t.completed).length;
+  const percent = total === 0 ? (root.completed ? 100 : 0) : Math.floor((completed * 100) / total);
+  return { total, completed, percent };
+}
+
+/**
+ * sort_order that puts `task` first among its siblings
+ */
+export function firstSortOrder(tasks: Task[], task: Pick<Task, 'id' | 'parent_id' | 'project'>): number {
+  const orders = tasks
+    .filter(
+      (t) =>
+        t.id !== task.id &&
+        same(t.parent_id, task.parent_id) &&
+        same(t.project, task.project) &&
+        typeof t.sort_order === 'number'
+    )
+    .map((t) => t.sort_order as number);
+  const min = orders.length > 0 ? Math.min(...orders) : SORT_ORDER_STEP;
+  return min - SORT_ORDER_STEP;
+}
+
+/**
+ * Check a new task's parent: a subtask takes its parent's project and,
+ * without a sort_order, goes first among its siblings.
+ * Throws when the parent does not exist.
+ */
+export function placeNewTask(tasks: Task[], task: CreateTaskInput): CreateTaskInput {
+  if (!task.parent_id) return task;
+  const parent = tasks.find((t) => t.id === task.parent_id);
+  if (!parent) {
+    throw new Error(`Parent task not found: ${task.parent_id}`);
+  }
+  const placed = { ...task, project: parent.project };
+  if (placed.sort_order === undefined || placed.sort_order === null) {
+    placed.sort_order = firstSortOrder(tasks, { id: '', parent_id: parent.id, project: parent.project });
+  }
+  return placed;
+}
+
+/**
+ * Keep the tree consistent when `next` replaces `existing`.
+ * With `reorder`, a task that changed parent goes first among its new siblings.
+ *
+ * Returns the task to write followed by the subtasks whose project changed.
+ * Throws when the new parent is missing, the task itself or one of its subtasks.
+ */
+export function rehomeTask(tasks: Task[], existing: Task, next: Task, reorder: boolean): Task[] {
+  let task = { ...next };
+  if (task.parent_id && same(task.parent_id, existing.parent_id) && !same(task.project, existing.project)) {
+    task.parent_id = null;
+  }
+  if (same(task.parent_id, existing.parent_id) && same(task.project, existing.project)) {
+    return [task];
+  }
+
+  const subtree = collectSubtree(tasks, existing.id);
+  if (task.parent_id) {
+    if (subtree.some((t) => t.id === task.parent_id)) {
+      throw new Error('A task cannot be moved under itself or one of its subtasks');
+    }
+    const parent = tasks.find((t) => t.id === task.parent_id);
+    if (!parent) {
+      throw new Error(`Parent task not found: ${task.parent_id}`);
+    }
+    task = { ...task, project: parent.project };
+  }
+  if (reorder && !same(task.parent_id, existing.parent_id)) {
+    task.sort_order = firstSortOrder(tasks, task);
+  }
+
+  if (same(task.project, existing.project)) {
+    return [task];
+  }
+  const descendants = subtree.slice(1).map((t) => ({ ...t, project: task.project, updated_at: task.updated_at }));
+  return [task, ...descendants];
+}
+
+/**
+ * Work out a move for StorageAdapter.moveTask.
+ * Returns the moved task followed by the subtasks whose project changed.
+ */
+export function planMove(tasks: Task[], existing: Task, target: MoveTaskTarget): Task[] {
+  const next: Task = {
+    ...existing,
+    parent_id: target.parentId,
+    project: target.project ?? undefined,
+    sort_order: target.sortOrder,
+    updated_at: new Date().toISOString(),
+  };
+  const [task, ...descendants] = rehomeTask(tasks, existing, next, target.sortOrder === undefined);
+  if (task.sort_order === undefined) {
+    task.sort_order = firstSortOrder(tasks, task);
+  }
+  return [task, ...descendants];
+}
+
+/**
+ * Tasks of a subtree to move to the trash together; they share one deleted_at
+ */
+export function planTrash(subtree: Task[]): Task[] {
+  const now = new Date().toISOString();
+  return subtree
+    .filter((t) => !t.deleted)
+    .map((t) => ({ ...t, deleted: true, deleted_at: now, updated_at: now }));
+}
+
+/**
+ * Tasks to bring back with the subtree's root: those trashed along with it.
+ * If the root's parent is missing or still in the trash, the root comes back at the top level.
+ */
+export function planRestore(tasks: Task[], subtree: Task[]): Task[] {
+  const [root] = subtree;
+  if (!root || !root.deleted) return [];
+
+  const now = new Date().toISOString();
+  const parent = root.parent_id ? tasks.find((t) => t.id === root.parent_id) : undefined;
+  const orphaned = !!root.parent_id && (!parent || !!parent.deleted);
+
+  return subtree
+    .filter((t, i) => i === 0 || (t.deleted && t.deleted_at === root.deleted_at))
+    .map((t, i) => {
+      const restored: Task = { ...t, deleted: false, deleted_at: undefined, updated_at: now };
+      if (i === 0 && orphaned) {
+        restored.parent_id = null;
+        restored.sort_order = firstSortOrder(tasks, restored);
+      }
+      return restored;
+    });
+}
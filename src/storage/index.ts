@@ -1,11 +1,14 @@
 /**
  * Storage Factory Module
  * Provides a singleton storage instance based on configuration
+ * The desktop always uses its own database (synced by the Rust side when online);
+ * the browser uses Supabase online and IndexedDB offline
  */
 
 import { StorageAdapter } from './types';
 import { IndexedDBAdapter } from './indexeddb';
 import { SupabaseAdapter } from './supabase';
+import { TauriAdapter } from './tauri';
 import { isOfflineMode, getStorageConfig } from '@/config/storage';
 
 let storageInstance: StorageAdapter | null = null;
@@ -18,7 +21,9 @@ export function getStorage(): StorageAdapter {
   if (!storageInstance) {
     const config = getStorageConfig();
     
-    if (config.mode === 'offline') {
+    if (config.backend === 'tauri') {
+      storageInstance = new TauriAdapter();
+    } else if (config.backend === 'indexeddb') {
       storageInstance = new IndexedDBAdapter();
     } else {
       storageInstance = new SupabaseAdapter();
@@ -8,6 +8,7 @@ import fc from 'fast-check';
 import { IndexedDBAdapter } from './IndexedDBAdapter';
 import { Task } from '@/types/task';
 import { CreateTaskInput, TaskFilter, SortOptions } from '../types';
+import { computeProgress } from '../hierarchy';
 
 // Helper to create a fresh adapter for each test
 const createAdapter = async (): Promise<IndexedDBAdapter> => {
@@ -878,3 +879,141 @@ describe('IndexedDB Adapter - Activity Operations', () => {
     });
   });
 });
+
+// Arbitrary for a task tree: entry i is the parent index of task i + 1 (task 0 is the root)
+const treeShapeArb = fc
+  .array(fc.nat(), { minLength: 1, maxLength: 12 })
+  .map((picks) => picks.map((pick, i) => pick % (i + 1)));
+
+describe('IndexedDB Adapter - Task Hierarchy', () => {
+  let adapter: IndexedDBAdapter;
+
+  beforeEach(async () => {
+    adapter = await createAdapter();
+    await clearDatabase(adapter);
+  });
+
+  afterEach(async () => {
+    await clearDatabase(adapter);
+  });
+
+  // Create a tree of tasks in one project; returns them in creation order
+  const createTree = async (parents: number[], project: string): Promise<Task[]> => {
+    const root = await adapter.createTask({ title: 'root', completed: false, project });
+    const tasks = [root];
+    for (const [i, parentIndex] of parents.entries()) {
+      tasks.push(
+        await adapter.createTask({
+          title: `task ${i + 1}`,
+          completed: false,
+          parent_id: tasks[parentIndex].id,
+        })
+      );
+    }
+    return tasks;
+  };
+
+  describe('Property 11: Subtrees Move As One Piece', () => {
+    /**
+     * getSubtree SHALL return the root first and every descendant exactly once,
+     * each after its parent, with subtasks in their parent's project.
+     */
+    it('should return a whole subtree, parents before children', async () => {
+      await fc.assert(
+        fc.asyncProperty(treeShapeArb, fc.uuid(), async (parents, project) => {
+          const tasks = await createTree(parents, project);
+          const subtree = await adapter.getSubtree(tasks[0].id);
+
+          expect(subtree.length).toBe(tasks.length);
+          expect(subtree[0].id).toBe(tasks[0].id);
+          const seen = new Set<string>();
+          for (const task of subtree) {
+            if (task.parent_id) expect(seen.has(task.parent_id)).toBe(true);
+            expect(task.project).toBe(project);
+            seen.add(task.id);
+          }
+
+          await adapter.deleteTask(tasks[0].id);
+          expect(await adapter.getTasks()).toHaveLength(0);
+        }),
+        { numRuns: 30 }
+      );
+    });
+
+    /**
+     * Moving a parent SHALL carry its subtasks into the new project, and moving
+     * a task under one of its own subtasks SHALL be rejected.
+     */
+    it('should cascade moves and reject cycles', async () => {
+      await fc.assert(
+        fc.asyncProperty(treeShapeArb, fc.uuid(), fc.uuid(), async (parents, from, to) => {
+          const tasks = await createTree(parents, from);
+          const leaf = tasks[tasks.length - 1];
+
+          await expect(adapter.moveTask(tasks[0].id, { parentId: leaf.id })).rejects.toThrow();
+
+          const moved = await adapter.moveTask(tasks[0].id, { parentId: null, project: to });
+          expect(moved!.project).toBe(to);
+          for (const task of await adapter.getSubtree(tasks[0].id)) {
+            expect(task.project).toBe(to);
+          }
+
+          await adapter.deleteTask(tasks[0].id);
+        }),
+        { numRuns: 20 }
+      );
+    });
+
+    /**
+     * Trashing a parent SHALL trash its subtree, and restoring it SHALL bring back
+     * exactly the tasks trashed with it.
+     */
+    it('should trash and restore subtrees together', async () => {
+      await fc.assert(
+        fc.asyncProperty(treeShapeArb, async (parents) => {
+          const tasks = await createTree(parents, 'inbox');
+          const trashed = await adapter.trashTask(tasks[0].id);
+          expect(trashed.length).toBe(tasks.length);
+          expect(await adapter.getTasks({ deleted: false })).toHaveLength(0);
+
+          const restored = await adapter.restoreTask(tasks[0].id);
+          expect(restored.length).toBe(tasks.length);
+          expect(await adapter.getTasks({ deleted: true })).toHaveLength(0);
+
+          await adapter.deleteTask(tasks[0].id);
+        }),
+        { numRuns: 20 }
+      );
+    });
+  });
+
+  describe('Property 12: Progress Roll-Up', () => {
+    /**
+     * A parent's progress SHALL count completed subtasks at every depth.
+     */
+    it('should roll completion up to the root', async () => {
+      await fc.assert(
+        fc.asyncProperty(
+          treeShapeArb.chain((parents) =>
+            fc.tuple(fc.constant(parents), fc.array(fc.boolean(), { minLength: parents.length, maxLength: parents.length }))
+          ),
+          async ([parents, done]) => {
+            const tasks = await createTree(parents, 'inbox');
+            for (const [i, completed] of done.entries()) {
+              if (completed) await adapter.updateTask(tasks[i + 1].id, { completed: true });
+            }
+
+            const progress = computeProgress(await adapter.getSubtree(tasks[0].id));
+            const completed = done.filter(Boolean).length;
+            expect(progress.total).toBe(parents.length);
+            expect(progress.completed).toBe(completed);
+            expect(progress.percent).toBe(Math.floor((completed * 100) / parents.length));
+
+            await adapter.deleteTask(tasks[0].id);
+          }
+        ),
+        { numRuns: 20 }
+      );
+    });
+  });
+});
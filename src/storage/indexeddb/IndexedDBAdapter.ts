@@ -21,12 +21,15 @@ import {
   DB_STORES,
   UserProfile,
   LocalAttachment,
+  LocalDataExport,
   FileUploadResult,
   SearchOptions,
   SearchResult,
   UserSettings,
   AppInfo,
+  MoveTaskTarget,
 } from '../types';
+import { collectSubtree, placeNewTask, planMove, planRestore, planTrash, rehomeTask } from '../hierarchy';
 
 const DB_NAME = 'snail_todo_db';
 const DB_VERSION = 3; // Increment version for schema change (added user_profile and attachments)
@@ -210,6 +213,23 @@ export class IndexedDBAdapter implements StorageAdapter {
     return record;
   }
 
+  /**
+   * Helper to put several records in one transaction
+   */
+  private async putRecords<T>(storeName: string, records: T[]): Promise<T[]> {
+    if (records.length === 0) return records;
+    const db = this.getDB();
+    const transaction = db.transaction(storeName, 'readwrite');
+    const store = transaction.objectStore(storeName);
+    for (const record of records) {
+      store.put(record);
+    }
+    return new Promise((resolve, reject) => {
+      transaction.oncomplete = () => resolve(records);
+      transaction.onerror = () => reject(transaction.error);
+    });
+  }
+
   /**
    * Helper to delete a record by key
    */
@@ -306,6 +326,9 @@ export class IndexedDBAdapter implements StorageAdapter {
 
   async createTask(task: CreateTaskInput): Promise<Task> {
     const now = new Date().toISOString();
+    if (task.parent_id) {
+      task = placeNewTask(await this.getAllFromStore<Task>(DB_STORES.TASKS), task);
+    }
     const newTask: Task = {
       ...task,
       id: uuidv4(),
@@ -338,21 +361,38 @@ export class IndexedDBAdapter implements StorageAdapter {
       updated_at: new Date().toISOString(),
     };
 
+    // Moving to another parent or project carries the subtasks along
+    if (updates.parent_id !== undefined || updates.project !== undefined) {
+      const tasks = await this.getAllFromStore<Task>(DB_STORES.TASKS);
+      const [task, ...descendants] = rehomeTask(tasks, existing, updatedTask, updates.sort_order === undefined);
+      await this.putRecords(DB_STORES.TASKS, [task, ...descendants]);
+      return task;
+    }
+
     await this.putRecord(DB_STORES.TASKS, updatedTask);
     return updatedTask;
   }
 
   async deleteTask(id: string): Promise<boolean> {
+    // Subtasks go with their parent
+    const subtree = collectSubtree(await this.getAllFromStore<Task>(DB_STORES.TASKS), id);
+    const ids = subtree.length > 0 ? subtree.map((t) => t.id) : [id];
+
     // Also delete associated task-tags
-    const taskTags = await this.getByIndex<TaskTagRecord>(DB_STORES.TASK_TAGS, 'task_id', id);
+    const taskTags: TaskTagRecord[] = [];
+    for (const taskId of ids) {
+      taskTags.push(...(await this.getByIndex<TaskTagRecord>(DB_STORES.TASK_TAGS, 'task_id', taskId)));
+    }
     const db = this.getDB();
     const transaction = db.transaction([DB_STORES.TASKS, DB_STORES.TASK_TAGS], 'readwrite');
 
     const taskStore = transaction.objectStore(DB_STORES.TASKS);
     const taskTagStore = transaction.objectStore(DB_STORES.TASK_TAGS);
 
-    // Delete task
-    taskStore.delete(id);
+    // Delete tasks
+    for (const taskId of ids) {
+      taskStore.delete(taskId);
+    }
 
     // Delete associated task-tags
     for (const taskTag of taskTags) {
@@ -386,6 +426,35 @@ export class IndexedDBAdapter implements StorageAdapter {
     });
   }
 
+  // ============================================
+  // Task Hierarchy Operations
+  // ============================================
+
+  async getSubtree(rootId: string): Promise<Task[]> {
+    return collectSubtree(await this.getAllFromStore<Task>(DB_STORES.TASKS), rootId);
+  }
+
+  async moveTask(id: string, target: MoveTaskTarget): Promise<Task | null> {
+    const tasks = await this.getAllFromStore<Task>(DB_STORES.TASKS);
+    const existing = tasks.find((t) => t.id === id);
+    if (!existing) {
+      return null;
+    }
+    const [task, ...descendants] = planMove(tasks, existing, target);
+    await this.putRecords(DB_STORES.TASKS, [task, ...descendants]);
+    return task;
+  }
+
+  async trashTask(id: string): Promise<Task[]> {
+    const subtree = await this.getSubtree(id);
+    return this.putRecords(DB_STORES.TASKS, planTrash(subtree));
+  }
+
+  async restoreTask(id: string): Promise<Task[]> {
+    const tasks = await this.getAllFromStore<Task>(DB_STORES.TASKS);
+    return this.putRecords(DB_STORES.TASKS, planRestore(tasks, collectSubtree(tasks, id)));
+  }
+
   // ============================================
   // Project Operations
   // ============================================
@@ -970,6 +1039,24 @@ export class IndexedDBAdapter implements StorageAdapter {
   // Attachment Operations (Offline Mode)
   // ============================================
 
+  /**
+   * Read every record, for moving offline data into the desktop database
+   * Attachment files are left out: tasks carry them as data URLs
+   */
+  async exportAll(): Promise<LocalDataExport> {
+    const [projects, tasks, tags, taskTags, pomodoroSessions, activities, checkIns, profile] = await Promise.all([
+      this.getAllFromStore<Project>(DB_STORES.PROJECTS),
+      this.getAllFromStore<Task>(DB_STORES.TASKS),
+      this.getAllFromStore<Tag>(DB_STORES.TAGS),
+      this.getAllFromStore<TaskTagRecord>(DB_STORES.TASK_TAGS),
+      this.getAllFromStore<PomodoroSession>(DB_STORES.POMODORO_SESSIONS),
+      this.getAllFromStore<TaskActivity>(DB_STORES.TASK_ACTIVITIES),
+      this.getAllFromStore<CheckInRecord>(DB_STORES.CHECKIN_RECORDS),
+      this.getUserProfile(),
+    ]);
+    return { projects, tasks, tags, taskTags, pomodoroSessions, activities, checkIns, profile };
+  }
+
   async getAttachmentsByTaskId(taskId: string): Promise<LocalAttachment[]> {
     return this.getByIndex<LocalAttachment>(DB_STORES.ATTACHMENTS, 'task_id', taskId);
   }
@@ -12,7 +12,7 @@
 import { Task } from '@/types/task';
 import { Tag } from '@/types/tag';
 import { Project } from '@/types/project';
-import { PomodoroSession, TaskActivity, CheckInRecord, CreatePomodoroInput, CreateActivityInput } from './types';
+import { PomodoroSession, TaskActivity, CheckInRecord, CreatePomodoroInput, CreateActivityInput, MoveTaskTarget } from './types';
 import { getStorage, initializeStorage, isOfflineMode } from './index';
 import { toast } from '@/hooks/use-toast';
 import type { User } from '@supabase/supabase-js';
@@ -133,18 +133,21 @@ export async function deleteTask(id: string): Promise<boolean> {
   }
 }
 
-export async function moveToTrash(id: string): Promise<boolean> {
+/**
+ * Move a task and its subtasks to the trash
+ * Returns the tasks that changed; empty on failure
+ */
+export async function moveToTrash(id: string): Promise<Task[]> {
   try {
     const storage = await ensureStorage();
-    const result = await storage.updateTask(id, {
-      deleted: true,
-      deleted_at: new Date().toISOString(),
-    });
-    if (result) {
-      toast({ title: '删除成功', description: '任务已移至垃圾桶' });
-      return true;
+    const trashed = await storage.trashTask(id);
+    if (trashed.length > 0) {
+      const description = trashed.length > 1
+        ? `任务及其 ${trashed.length - 1} 个子任务已移至垃圾桶`
+        : '任务已移至垃圾桶';
+      toast({ title: '删除成功', description });
     }
-    return false;
+    return trashed;
   } catch (error) {
     console.error('Failed to move task to trash:', error);
     toast({
@@ -152,22 +155,22 @@ export async function moveToTrash(id: string): Promise<boolean> {
       description: '无法删除任务，请稍后再试',
       variant: 'destructive',
     });
-    return false;
+    return [];
   }
 }
 
-export async function restoreFromTrash(id: string): Promise<boolean> {
+/**
+ * Restore a task together with the subtasks trashed along with it
+ * Returns the tasks that changed; empty on failure
+ */
+export async function restoreFromTrash(id: string): Promise<Task[]> {
   try {
     const storage = await ensureStorage();
-    const result = await storage.updateTask(id, {
-      deleted: false,
-      deleted_at: undefined,
-    });
-    if (result) {
+    const restored = await storage.restoreTask(id);
+    if (restored.length > 0) {
       toast({ title: '恢复成功', description: '任务已恢复' });
-      return true;
     }
-    return false;
+    return restored;
   } catch (error) {
     console.error('Failed to restore task from trash:', error);
     toast({
@@ -175,7 +178,7 @@ export async function restoreFromTrash(id: string): Promise<boolean> {
       description: '无法恢复任务，请稍后再试',
       variant: 'destructive',
     });
-    return false;
+    return [];
   }
 }
 
@@ -239,6 +242,41 @@ export async function batchUpdateSortOrder(
   }
 }
 
+// ============================================
+// Task Hierarchy Operations
+// ============================================
+
+/**
+ * Get a task and all of its subtasks, root first
+ */
+export async function fetchSubtree(rootId: string): Promise<Task[]> {
+  try {
+    const storage = await ensureStorage();
+    return await storage.getSubtree(rootId);
+  } catch (error) {
+    console.error('Failed to fetch subtree:', error);
+    return [];
+  }
+}
+
+/**
+ * Move a task under another parent or to the top level of a project
+ */
+export async function moveTask(id: string, target: MoveTaskTarget): Promise<Task | null> {
+  try {
+    const storage = await ensureStorage();
+    return await storage.moveTask(id, target);
+  } catch (error) {
+    console.error('Failed to move task:', error);
+    toast({
+      title: '移动失败',
+      description: error instanceof Error ? error.message : '无法移动任务，请稍后再试',
+      variant: 'destructive',
+    });
+    return null;
+  }
+}
+
 // ============================================
 // Tag Operations
 // ============================================
@@ -489,7 +527,7 @@ const mapPublicTypeToInternal = (type: PomodoroSessionType): PomodoroSession['ty
   return type;
 };
 
-const mapSessionToPublic = (session: PomodoroSession): PomodoroSessionPublic => ({
+export const mapSessionToPublic = (session: PomodoroSession): PomodoroSessionPublic => ({
   id: session.id,
   user_id: session.user_id,
   start_time: session.started_at,
@@ -587,7 +625,7 @@ export async function getActivePomodoroSession(): Promise<PomodoroSessionPublic
     const storage = await ensureStorage();
     const sessions = await storage.getPomodoroSessions();
     const active = sessions
-      .filter(s => !s.completed_at)
+      .filter(s => !s.completed_at && !s.cancelled_at)
       .sort((a, b) => b.started_at.localeCompare(a.started_at))[0];
     return active ? mapSessionToPublic(active) : null;
   } catch (error) {
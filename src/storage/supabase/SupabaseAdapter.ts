@@ -27,7 +27,9 @@ import {
   UserSettings,
   UserProfile,
   AppInfo,
+  MoveTaskTarget,
 } from '../types';
+import { collectSubtree, placeNewTask, planMove, planRestore, planTrash, rehomeTask } from '../hierarchy';
 import * as taskService from '@/services/taskService';
 import * as tagService from '@/services/tagService';
 import * as pomodoroService from '@/services/pomodoroService';
@@ -95,6 +97,9 @@ export class SupabaseAdapter implements StorageAdapter {
   }
 
   async createTask(task: CreateTaskInput): Promise<Task> {
+    if (task.parent_id) {
+      task = placeNewTask(await taskService.fetchTasks(true, false), task);
+    }
     const result = await taskService.addTask(task, false);
     if (!result) {
       throw new Error('Failed to create task');
@@ -103,7 +108,29 @@ export class SupabaseAdapter implements StorageAdapter {
   }
 
   async updateTask(id: string, updates: Partial<Task>): Promise<Task | null> {
-    return taskService.updateTask(id, updates, false);
+    if (updates.parent_id === undefined && updates.project === undefined) {
+      return taskService.updateTask(id, updates, false);
+    }
+
+    // Moving to another parent or project carries the subtasks along
+    const tasks = await taskService.fetchTasks(true, false);
+    const existing = tasks.find(t => t.id === id);
+    if (!existing) {
+      return null;
+    }
+    const [task, ...descendants] = rehomeTask(
+      tasks,
+      existing,
+      { ...existing, ...updates, id },
+      updates.sort_order === undefined
+    );
+    const result = await taskService.updateTask(
+      id,
+      { ...updates, parent_id: task.parent_id ?? null, project: task.project ?? null, sort_order: task.sort_order },
+      false
+    );
+    await this.updateSubtaskProjects(descendants);
+    return result;
   }
 
   async deleteTask(id: string): Promise<boolean> {
@@ -114,6 +141,87 @@ export class SupabaseAdapter implements StorageAdapter {
     return taskService.batchUpdateSortOrder(updates, false);
   }
 
+  // ============================================
+  // Task Hierarchy Operations
+  // ============================================
+
+  async getSubtree(rootId: string): Promise<Task[]> {
+    await this.ensureUser();
+
+    const { data, error } = await supabase.rpc('get_task_subtree', { root_id: rootId });
+    if (error) throw error;
+
+    return collectSubtree((data || []).map(taskService.mapTaskData), rootId);
+  }
+
+  async moveTask(id: string, target: MoveTaskTarget): Promise<Task | null> {
+    const tasks = await taskService.fetchTasks(true, false);
+    const existing = tasks.find(t => t.id === id);
+    if (!existing) {
+      return null;
+    }
+
+    const [task, ...descendants] = planMove(tasks, existing, target);
+    const result = await taskService.updateTask(
+      id,
+      { parent_id: task.parent_id ?? null, project: task.project ?? null, sort_order: task.sort_order },
+      false
+    );
+    await this.updateSubtaskProjects(descendants);
+    return result;
+  }
+
+  async trashTask(id: string): Promise<Task[]> {
+    const trashed = planTrash(await this.getSubtree(id));
+    if (trashed.length === 0) return [];
+
+    const { error } = await supabase
+      .from('tasks')
+      .update({ deleted: true, deleted_at: trashed[0].deleted_at })
+      .in('id', trashed.map(t => t.id));
+    if (error) throw error;
+
+    return trashed;
+  }
+
+  async restoreTask(id: string): Promise<Task[]> {
+    const tasks = await taskService.fetchTasks(true, false);
+    const subtree = collectSubtree(tasks, id);
+    const restored = planRestore(tasks, subtree);
+    if (restored.length === 0) return [];
+
+    const [root] = restored;
+    const { error } = await supabase
+      .from('tasks')
+      .update({ deleted: false, deleted_at: null })
+      .in('id', restored.map(t => t.id));
+    if (error) throw error;
+
+    // A subtask whose parent is still in the trash comes back at the top level
+    if (subtree[0].parent_id && !root.parent_id) {
+      const { error: moveError } = await supabase
+        .from('tasks')
+        .update({ parent_id: null, sort_order: root.sort_order })
+        .eq('id', root.id);
+      if (moveError) throw moveError;
+    }
+
+    return restored;
+  }
+
+  /**
+   * Write the new project of subtasks that followed their parent
+   */
+  private async updateSubtaskProjects(descendants: Task[]): Promise<void> {
+    if (descendants.length === 0) return;
+
+    const { error } = await supabase
+      .from('tasks')
+      .update({ project: descendants[0].project ?? null })
+      .in('id', descendants.map(t => t.id));
+    if (error) throw error;
+  }
+
   // ============================================
   // Project Operations
   // ============================================
@@ -0,0 +1,61 @@
+/**
+ * Tests for the Tauri Adapter
+ * Feature: desktop storage, one-time import from IndexedDB
+ */
+
+import { describe, it, expect, beforeEach, afterEach, vi } from 'vitest';
+import { IndexedDBAdapter } from '../indexeddb';
+import { TauriAdapter, INDEXEDDB_IMPORTED_KEY } from './TauriAdapter';
+
+type TauriWindow = { __TAURI__?: { core: { invoke: (cmd: string, args?: Record<string, unknown>) => Promise<unknown> } } };
+
+const invoke = vi.fn(async (_cmd: string, _args?: Record<string, unknown>): Promise<unknown> => null);
+
+describe('TauriAdapter', () => {
+  beforeEach(() => {
+    invoke.mockClear();
+    localStorage.clear();
+    (window as unknown as TauriWindow).__TAURI__ = { core: { invoke } };
+  });
+
+  afterEach(() => {
+    delete (window as unknown as TauriWindow).__TAURI__;
+  });
+
+  it('imports the IndexedDB data once', async () => {
+    const source = new IndexedDBAdapter();
+    await source.initialize();
+    const project = await source.createProject({ name: '工作', icon: 'folder' });
+    const task = await source.createTask({ title: '周报', completed: false, project: project.id });
+    const tag = await source.createTag('urgent');
+    await source.attachTagToTask(task.id, tag.id);
+
+    await new TauriAdapter().initialize();
+    const imports = invoke.mock.calls.filter(([cmd]) => cmd === 'import_webview_data');
+    expect(imports).toHaveLength(1);
+    const data = imports[0][1]?.data as { tasks: { id: string }[]; taskTags: { tag_id: string }[] };
+    expect(data.tasks.map((t) => t.id)).toContain(task.id);
+    expect(data.taskTags.map((l) => l.tag_id)).toContain(tag.id);
+    expect(localStorage.getItem(INDEXEDDB_IMPORTED_KEY)).not.toBeNull();
+
+    const again = new TauriAdapter();
+    await again.initialize();
+    expect(again.isReady()).toBe(true);
+    expect(invoke.mock.calls.filter(([cmd]) => cmd === 'import_webview_data')).toHaveLength(1);
+  });
+
+  it('tells global-only tags apart from all tags', async () => {
+    localStorage.setItem(INDEXEDDB_IMPORTED_KEY, 'done');
+    const adapter = new TauriAdapter();
+    await adapter.initialize();
+
+    await adapter.getTags(null);
+    await adapter.getTags();
+    await adapter.getTags('p1');
+    expect(invoke.mock.calls.map(([, args]) => args)).toEqual([
+      { projectId: null, globalOnly: true },
+      { projectId: null, globalOnly: false },
+      { projectId: 'p1', globalOnly: false },
+    ]);
+  });
+});
@@ -0,0 +1,342 @@
+/**
+ * Tauri Storage Adapter
+ * Implements the StorageAdapter interface over the desktop app's SQLite database
+ * Every method calls the Rust command of the same name in snake_case (src-tauri/src/commands/storage.rs)
+ */
+
+import { Task } from '@/types/task';
+import { Project } from '@/types/project';
+import { Tag } from '@/types/tag';
+import { invokeTauri } from '@/utils/runtime';
+import {
+  StorageAdapter,
+  TaskFilter,
+  SortOptions,
+  PomodoroSession,
+  TaskActivity,
+  CheckInRecord,
+  CreateTaskInput,
+  CreateProjectInput,
+  CreatePomodoroInput,
+  CreateActivityInput,
+  UserProfile,
+  FileUploadResult,
+  SearchOptions,
+  SearchResult,
+  UserSettings,
+  AppInfo,
+  MoveTaskTarget,
+} from '../types';
+import { IndexedDBAdapter } from '../indexeddb';
+
+/** localStorage key set once the IndexedDB data has been copied into the desktop database */
+export const INDEXEDDB_IMPORTED_KEY = 'snail_indexeddb_imported';
+
+/**
+ * A DOM File as the Rust side takes it
+ */
+async function toUploadFile(file: File): Promise<{ name: string; type: string; data: number[] }> {
+  const data = new Uint8Array(await file.arrayBuffer());
+  return { name: file.name, type: file.type, data: Array.from(data) };
+}
+
+export class TauriAdapter implements StorageAdapter {
+  private ready = false;
+  private initPromise: Promise<void> | null = null;
+
+  /**
+   * The database is opened by the Rust process before the window exists;
+   * the first run also copies over what offline mode kept in IndexedDB
+   */
+  async initialize(): Promise<void> {
+    if (this.ready) {
+      return;
+    }
+
+    if (!this.initPromise) {
+      this.initPromise = this.importIndexedDB()
+        .then(() => {
+          this.ready = true;
+        })
+        .finally(() => {
+          this.initPromise = null;
+        });
+    }
+
+    return this.initPromise;
+  }
+
+  isReady(): boolean {
+    return this.ready;
+  }
+
+  /**
+   * Copy the IndexedDB data once. The flag lives in localStorage, which is
+   * cleared together with IndexedDB, so the import never runs over newer data
+   */
+  private async importIndexedDB(): Promise<void> {
+    try {
+      if (localStorage.getItem(INDEXEDDB_IMPORTED_KEY)) {
+        return;
+      }
+    } catch {
+      return;
+    }
+
+    const source = new IndexedDBAdapter();
+    await source.initialize();
+    const data = await source.exportAll();
+    const empty =
+      data.projects.length === 0 &&
+      data.tasks.length === 0 &&
+      data.tags.length === 0 &&
+      data.pomodoroSessions.length === 0 &&
+      data.checkIns.length === 0 &&
+      !data.profile;
+    if (!empty) {
+      await invokeTauri('import_webview_data', { data });
+    }
+
+    try {
+      localStorage.setItem(INDEXEDDB_IMPORTED_KEY, new Date().toISOString());
+    } catch {
+      console.warn('Failed to save IndexedDB import flag');
+    }
+  }
+
+  // ============================================
+  // Task Operations
+  // ============================================
+
+  async getTasks(filter?: TaskFilter, sort?: SortOptions[]): Promise<Task[]> {
+    return invokeTauri<Task[]>('get_tasks', { filter: filter ?? null, sort: sort ?? null });
+  }
+
+  async getTaskById(id: string): Promise<Task | null> {
+    return invokeTauri<Task | null>('get_task_by_id', { id });
+  }
+
+  async createTask(task: CreateTaskInput): Promise<Task> {
+    return invokeTauri<Task>('create_task', { task });
+  }
+
+  async updateTask(id: string, updates: Partial<Task>): Promise<Task | null> {
+    return invokeTauri<Task | null>('update_task', { id, updates });
+  }
+
+  async deleteTask(id: string): Promise<boolean> {
+    return invokeTauri<boolean>('delete_task', { id });
+  }
+
+  async batchUpdateSortOrder(updates: Array<{ id: string; sort_order: number }>): Promise<boolean> {
+    return invokeTauri<boolean>('batch_update_sort_order', { updates });
+  }
+
+  // ============================================
+  // Task Hierarchy Operations
+  // ============================================
+
+  async getSubtree(rootId: string): Promise<Task[]> {
+    return invokeTauri<Task[]>('get_subtree', { rootId });
+  }
+
+  async moveTask(id: string, target: MoveTaskTarget): Promise<Task | null> {
+    return invokeTauri<Task | null>('move_task', { id, target });
+  }
+
+  async trashTask(id: string): Promise<Task[]> {
+    return invokeTauri<Task[]>('trash_task', { id });
+  }
+
+  async restoreTask(id: string): Promise<Task[]> {
+    return invokeTauri<Task[]>('restore_task', { id });
+  }
+
+  // ============================================
+  // Project Operations
+  // ============================================
+
+  async getProjects(): Promise<Project[]> {
+    return invokeTauri<Project[]>('get_projects');
+  }
+
+  async getProjectById(id: string): Promise<Project | null> {
+    return invokeTauri<Project | null>('get_project_by_id', { id });
+  }
+
+  async createProject(project: CreateProjectInput): Promise<Project> {
+    return invokeTauri<Project>('create_project', { project });
+  }
+
+  async updateProject(id: string, updates: Partial<Project>): Promise<Project | null> {
+    return invokeTauri<Project | null>('update_project', { id, updates });
+  }
+
+  async deleteProject(id: string): Promise<boolean> {
+    return invokeTauri<boolean>('delete_project', { id });
+  }
+
+  async batchUpdateProjectSortOrder(updates: Array<{ id: string; sort_order: number }>): Promise<boolean> {
+    return invokeTauri<boolean>('batch_update_project_sort_order', { updates });
+  }
+
+  // ============================================
+  // Tag Operations
+  // ============================================
+
+  async getTags(projectId?: string | null): Promise<Tag[]> {
+    // null (global tags only) and undefined (all tags) look the same once serialized
+    return invokeTauri<Tag[]>('get_tags', { projectId: projectId ?? null, globalOnly: projectId === null });
+  }
+
+  async getTagById(id: string): Promise<Tag | null> {
+    return invokeTauri<Tag | null>('get_tag_by_id', { id });
+  }
+
+  async createTag(name: string, projectId?: string | null): Promise<Tag> {
+    return invokeTauri<Tag>('create_tag', { name, projectId: projectId ?? null });
+  }
+
+  async updateTag(id: string, updates: Partial<Tag>): Promise<Tag | null> {
+    return invokeTauri<Tag | null>('update_tag', { id, updates });
+  }
+
+  async deleteTag(id: string): Promise<boolean> {
+    return invokeTauri<boolean>('delete_tag', { id });
+  }
+
+  // ============================================
+  // Task-Tag Operations
+  // ============================================
+
+  async getTagsByTaskIds(taskIds: string[]): Promise<Record<string, Tag[]>> {
+    return invokeTauri<Record<string, Tag[]>>('get_tags_by_task_ids', { taskIds });
+  }
+
+  async attachTagToTask(taskId: string, tagId: string): Promise<void> {
+    await invokeTauri<void>('attach_tag_to_task', { taskId, tagId });
+  }
+
+  async detachTagFromTask(taskId: string, tagId: string): Promise<void> {
+    await invokeTauri<void>('detach_tag_from_task', { taskId, tagId });
+  }
+
+  // ============================================
+  // Pomodoro Operations
+  // ============================================
+
+  async getPomodoroSessions(taskId?: string): Promise<PomodoroSession[]> {
+    return invokeTauri<PomodoroSession[]>('get_pomodoro_sessions', { taskId: taskId ?? null });
+  }
+
+  async getPomodoroSessionById(id: string): Promise<PomodoroSession | null> {
+    return invokeTauri<PomodoroSession | null>('get_pomodoro_session_by_id', { id });
+  }
+
+  async createPomodoroSession(session: CreatePomodoroInput): Promise<PomodoroSession> {
+    return invokeTauri<PomodoroSession>('create_pomodoro_session', { session });
+  }
+
+  async updatePomodoroSession(id: string, updates: Partial<PomodoroSession>): Promise<PomodoroSession | null> {
+    return invokeTauri<PomodoroSession | null>('update_pomodoro_session', { id, updates });
+  }
+
+  async deletePomodoroSession(id: string): Promise<boolean> {
+    return invokeTauri<boolean>('delete_pomodoro_session', { id });
+  }
+
+  // ============================================
+  // Activity Operations
+  // ============================================
+
+  async getTaskActivities(taskId: string): Promise<TaskActivity[]> {
+    return invokeTauri<TaskActivity[]>('get_task_activities', { taskId });
+  }
+
+  async createTaskActivity(activity: CreateActivityInput): Promise<TaskActivity> {
+    return invokeTauri<TaskActivity>('create_task_activity', { activity });
+  }
+
+  // ============================================
+  // Check-In Operations
+  // ============================================
+
+  async hasCheckedInToday(): Promise<boolean> {
+    return invokeTauri<boolean>('has_checked_in_today');
+  }
+
+  async createCheckIn(note?: string): Promise<CheckInRecord> {
+    return invokeTauri<CheckInRecord>('create_check_in', { note: note ?? null });
+  }
+
+  async getCheckInHistory(page: number = 1, pageSize: number = 10): Promise<{ records: CheckInRecord[]; total: number }> {
+    return invokeTauri<{ records: CheckInRecord[]; total: number }>('get_check_in_history', { page, pageSize });
+  }
+
+  async getCheckInStreak(): Promise<number> {
+    return invokeTauri<number>('get_check_in_streak');
+  }
+
+  // ============================================
+  // File Storage Operations
+  // ============================================
+
+  async uploadAttachment(taskId: string, file: File): Promise<FileUploadResult> {
+    return invokeTauri<FileUploadResult>('upload_attachment', { taskId, file: await toUploadFile(file) });
+  }
+
+  async deleteAttachment(attachmentId: string): Promise<boolean> {
+    return invokeTauri<boolean>('delete_attachment', { attachmentId });
+  }
+
+  async uploadImage(file: File): Promise<FileUploadResult> {
+    return invokeTauri<FileUploadResult>('upload_image', { file: await toUploadFile(file) });
+  }
+
+  async uploadAvatar(file: File): Promise<FileUploadResult> {
+    return invokeTauri<FileUploadResult>('upload_avatar', { file: await toUploadFile(file) });
+  }
+
+  // ============================================
+  // Search Operations
+  // ============================================
+
+  async searchTasks(query: string, options?: SearchOptions): Promise<SearchResult> {
+    return invokeTauri<SearchResult>('search_tasks', { query, options: options ?? null });
+  }
+
+  // ============================================
+  // User Settings Operations
+  // ============================================
+
+  async getUserSettings(): Promise<UserSettings> {
+    return invokeTauri<UserSettings>('get_user_settings');
+  }
+
+  async saveUserSettings(settings: Partial<UserSettings>): Promise<UserSettings> {
+    return invokeTauri<UserSettings>('save_user_settings', { settings });
+  }
+
+  // ============================================
+  // User Profile Operations
+  // ============================================
+
+  async getUserProfile(): Promise<UserProfile | null> {
+    return invokeTauri<UserProfile | null>('get_user_profile');
+  }
+
+  async saveUserProfile(profile: Partial<UserProfile>): Promise<UserProfile> {
+    return invokeTauri<UserProfile>('save_user_profile', { profile });
+  }
+
+  // ============================================
+  // App Info Operations
+  // ============================================
+
+  async getAppInfo(): Promise<AppInfo> {
+    return invokeTauri<AppInfo>('get_app_info');
+  }
+}
+
+// Export singleton instance for convenience
+export const tauriAdapter = new TauriAdapter();
@@ -0,0 +1,5 @@
+/**
+ * Tauri storage module exports
+ */
+
+export { TauriAdapter, tauriAdapter, INDEXEDDB_IMPORTED_KEY } from './TauriAdapter';
@@ -1,7 +1,7 @@
 /**
  * Storage Adapter Interface
  * Defines the common interface for all storage operations
- * Supports both Supabase (online) and IndexedDB (offline) implementations
+ * Supports Supabase (online), IndexedDB (offline) and the desktop database (offline under Tauri)
  */
 
 import { Task } from '@/types/task';
@@ -29,6 +29,28 @@ export interface SortOptions {
   nullsFirst?: boolean;
 }
 
+/**
+ * Where to move a task: under another task, or at the top level of a project
+ */
+export interface MoveTaskTarget {
+  /** New parent task; null for the top level */
+  parentId: string | null;
+  /** Project of a top-level task; a subtask always takes its parent's */
+  project?: string | null;
+  /** Position among the new siblings; first when omitted */
+  sortOrder?: number;
+}
+
+/**
+ * Completion of a task's subtasks, at every depth
+ */
+export interface TaskProgress {
+  total: number;
+  completed: number;
+  /** 0-100; a task without subtasks counts as 0 or 100 by itself */
+  percent: number;
+}
+
 /**
  * Pomodoro session record
  */
@@ -40,6 +62,8 @@ export interface PomodoroSession {
   type: 'work' | 'short_break' | 'long_break';
   started_at: string;
   completed_at?: string | null;
+  /** 重置或跳过时写入（仅桌面端） */
+  cancelled_at?: string | null;
   created_at: string;
   notes?: string | null;
   title?: string | null;
@@ -124,6 +148,27 @@ export interface SearchResult {
   tasks: Task[];
   totalCount: number;
   searchTime: number;
+  /** Highlighting for each of `tasks` (desktop full-text index only) */
+  hits?: SearchHit[];
+}
+
+/**
+ * A piece of highlighted text; matched parts have `highlight` set
+ */
+export interface SearchFragment {
+  text: string;
+  highlight?: boolean;
+}
+
+/**
+ * Ranked search hit returned by the desktop full-text index
+ */
+export interface SearchHit {
+  taskId: string;
+  score: number;
+  title: SearchFragment[];
+  snippet: SearchFragment[];
+  tags: string[];
 }
 
 /**
@@ -134,6 +179,8 @@ export interface UserSettings {
   deadline_notification_days?: number;
   webhook_url?: string;
   webhook_enabled?: boolean;
+  webhook_type?: string;
+  webhook_secret?: string;
   [key: string]: unknown;
 }
 
@@ -198,6 +245,34 @@ export interface StorageAdapter {
    */
   batchUpdateSortOrder(updates: Array<{ id: string; sort_order: number }>): Promise<boolean>;
 
+  // ============================================
+  // Task Hierarchy Operations
+  // ============================================
+
+  /**
+   * Get a task and all of its subtasks in one call
+   * Root first, then depth first with siblings ordered by sort_order; empty if the root does not exist
+   */
+  getSubtree(rootId: string): Promise<Task[]>;
+
+  /**
+   * Move a task under another parent or to the top level of a project
+   * Its subtasks follow it into the new project; moving a task under its own subtask is rejected
+   */
+  moveTask(id: string, target: MoveTaskTarget): Promise<Task | null>;
+
+  /**
+   * Move a task and its subtasks to the trash
+   * Returns the tasks that changed
+   */
+  trashTask(id: string): Promise<Task[]>;
+
+  /**
+   * Restore a task together with the subtasks trashed along with it
+   * Returns the tasks that changed
+   */
+  restoreTask(id: string): Promise<Task[]>;
+
   // ============================================
   // Project Operations
   // ============================================
@@ -449,6 +524,21 @@ export interface UserProfile {
   updated_at: string;
 }
 
+/**
+ * Everything the IndexedDB adapter holds
+ * Read once to move offline data into the desktop database
+ */
+export interface LocalDataExport {
+  projects: Project[];
+  tasks: Task[];
+  tags: Tag[];
+  taskTags: Array<{ task_id: string; tag_id: string; created_at?: string }>;
+  pomodoroSessions: PomodoroSession[];
+  activities: TaskActivity[];
+  checkIns: CheckInRecord[];
+  profile: UserProfile | null;
+}
+
 /**
  * Attachment file stored locally
  */
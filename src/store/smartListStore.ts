@@ -0,0 +1,38 @@
+import { create } from "zustand";
+import { SmartList } from "@/types/smartList";
+import { fetchSmartLists } from "@/services/smartListService";
+
+export interface SmartListStore {
+  lists: SmartList[];
+  hasLoaded: boolean;
+  load: () => Promise<void>;
+  upsertList: (list: SmartList) => void;
+  removeList: (id: string) => void;
+}
+
+export const useSmartListStore = create<SmartListStore>((set) => ({
+  lists: [],
+  hasLoaded: false,
+
+  load: async () => {
+    try {
+      const lists = await fetchSmartLists();
+      set({ lists, hasLoaded: true });
+    } catch (error) {
+      console.error("Error loading smart lists:", error);
+      set({ hasLoaded: true });
+    }
+  },
+
+  upsertList: (list) =>
+    set((state) => ({
+      lists: state.lists.some((l) => l.id === list.id)
+        ? state.lists.map((l) => (l.id === list.id ? list : l))
+        : [...state.lists, list],
+    })),
+
+  removeList: (id) =>
+    set((state) => ({
+      lists: state.lists.filter((list) => list.id !== id),
+    })),
+}));
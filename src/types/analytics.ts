@@ -0,0 +1,36 @@
+/** 报告中的一组统计，对应 Rust 侧 `analytics::Bucket` */
+export interface AnalyticsBucket {
+  key: string;
+  label: string;
+  focus_sessions: number;
+  /** 实际专注分钟（开始到完成），缺失时按计划时长 */
+  focus_minutes: number;
+  break_sessions: number;
+  break_minutes: number;
+  /** 被重置或跳过的番茄 */
+  cancelled_sessions: number;
+  tasks_completed: number;
+  check_ins: number;
+}
+
+export interface AnalyticsReport {
+  /** 包含首尾两天，YYYY-MM-DD */
+  from: string;
+  to: string;
+  generated_at: string;
+  totals: AnalyticsBucket;
+  days: AnalyticsBucket[];
+  projects: AnalyticsBucket[];
+  tags: AnalyticsBucket[];
+  /** 周一到周日 */
+  weekdays: AnalyticsBucket[];
+  /** 0 点到 23 点 */
+  hours: AnalyticsBucket[];
+}
+
+export type ReportFormat = "csv" | "json" | "html";
+
+export interface RenderedReport {
+  file_name: string;
+  content: string;
+}
@@ -0,0 +1,35 @@
+/** 本地日历订阅设置，对应 Rust 侧 `feed::FeedSettings` */
+export interface CalendarFeedSettings {
+  enabled: boolean;
+  port: number;
+  /** 在所有网卡上监听，供手机等局域网设备访问（明文 HTTP） */
+  lan: boolean;
+}
+
+/** 一个订阅（每个日历客户端一个），令牌即访问凭据 */
+export interface CalendarFeed {
+  id: string;
+  name: string;
+  token: string;
+  created_at: string;
+  last_fetched_at: string | null;
+}
+
+export interface CalendarFeedOverview {
+  settings: CalendarFeedSettings;
+  /** 服务实际监听的端口，未运行时为 null */
+  runningPort: number | null;
+  /** 链接中使用的主机：开启局域网访问时为本机局域网地址，否则为 127.0.0.1 */
+  host: string;
+  feeds: CalendarFeed[];
+}
+
+/** 订阅链接上的筛选参数 */
+export interface CalendarFeedFilter {
+  /** 清单 id 或名称 */
+  project?: string | null;
+  /** 标签 id 或名称 */
+  tag?: string | null;
+  /** 以日程（VEVENT）发布截止日期 */
+  events?: boolean;
+}
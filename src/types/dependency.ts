@@ -0,0 +1,19 @@
+import { Task } from "./task";
+
+/** 任务的依赖关系（"被……阻塞"），对应 Rust 侧 `task_dependencies` */
+export interface TaskDependencies {
+  /** 当前任务等待的任务 */
+  blocked_by: Task[];
+  /** 等待当前任务的任务 */
+  blocking: Task[];
+  /** 是否还有未完成的前置任务 */
+  blocked: boolean;
+}
+
+/** "下一步做什么" 列表中的一项 */
+export interface NextAction {
+  task: Task;
+  /** 0 表示现在就能做；n 表示要先完成 n 轮其他任务 */
+  level: number;
+  blocked: boolean;
+}
@@ -0,0 +1,16 @@
+/** iCalendar 导出选项，对应 Rust 侧 `ical::tasks::IcsExportOptions` */
+export interface IcsExportOptions {
+  project_id?: string | null;
+  tag_id?: string | null;
+  /** 把截止日期导出为日程（VEVENT），适用于不显示待办的日历 */
+  events?: boolean;
+  /** 只导出有截止日期的任务 */
+  due_only?: boolean;
+}
+
+export interface IcsImportSummary {
+  created: number;
+  updated: number;
+  /** 缺少标题而被跳过的条目 */
+  skipped: number;
+}
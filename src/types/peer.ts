@@ -0,0 +1,41 @@
+/** 局域网同步设置，对应 Rust 侧 `peer::PeerSettings` */
+export interface PeerSettings {
+  enabled: boolean;
+  port: number;
+  /** 其他设备看到的名称，留空时由副本 id 生成 */
+  name: string;
+}
+
+/** 已配对的设备，id 为对方数据库的副本 id */
+export interface Peer {
+  id: string;
+  name: string;
+  /** 最近一次连上的地址（ip:端口） */
+  address: string | null;
+  pairedAt: string;
+  lastSyncedAt: string | null;
+}
+
+export interface PeerOverview {
+  settings: PeerSettings;
+  /** 本机对外显示的名称 */
+  name: string;
+  /** 服务实际监听的端口，未运行时为 null */
+  runningPort: number | null;
+  peers: Peer[];
+}
+
+/** 一次性配对码，在另一台设备上输入，使用一次或过期后失效 */
+export interface PairingCode {
+  code: string;
+  port: number;
+  expiresInSeconds: number;
+}
+
+/** 通过 mDNS 在局域网中发现的实例 */
+export interface DiscoveredPeer {
+  replica: string;
+  name: string;
+  /** 可尝试连接的 ip:端口，IPv4 在前 */
+  addresses: string[];
+}
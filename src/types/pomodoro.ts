@@ -0,0 +1,27 @@
+import type { PomodoroSession } from "@/storage/types";
+import type { PomodoroSessionType } from "@/storage/operations";
+
+/** Rust 侧番茄钟的当前状态，对应 `src-tauri/src/pomodoro.rs` 的 `PomodoroState` */
+export interface PomodoroEngineState {
+  phase: PomodoroSessionType;
+  running: boolean;
+  remainingSeconds: number;
+  totalSeconds: number;
+  focusStreak: number;
+  focusTarget: number;
+  upcomingPhase: PomodoroSessionType;
+  /** 进行中（含暂停）的记录，空闲时为 null */
+  session: PomodoroSession | null;
+  soundEnabled: boolean;
+}
+
+/** 一个阶段完成或被跳过 */
+export interface PomodoroPhaseChange {
+  from: PomodoroSessionType;
+  to: PomodoroSessionType;
+  /** 跳过时为 false */
+  completed: boolean;
+  session: PomodoroSession | null;
+  /** 下一阶段是否已按设置自动开始 */
+  autoStarted: boolean;
+}
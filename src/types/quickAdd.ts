@@ -0,0 +1,39 @@
+/** 快速添加识别出的片段类型 */
+export type QuickAddSpanKind = "date" | "time" | "repeat" | "project" | "tag" | "flag";
+
+/** 识别出的片段，start/end 为字符串下标（与 JS 字符串一致） */
+export interface QuickAddSpan {
+  kind: QuickAddSpanKind;
+  start: number;
+  end: number;
+  text: string;
+}
+
+/** 自然语言快速添加的解析结果，对应 Rust 侧 `quick_add::QuickAdd` */
+export interface QuickAddResult {
+  /** 去掉识别片段后的标题 */
+  title: string;
+  /** ISO 时间字符串，与日期选择器的存储格式一致 */
+  date?: string | null;
+  has_time: boolean;
+  rrule?: string | null;
+  /** 输入中的项目名 */
+  project?: string | null;
+  /** 与项目名匹配的已有项目 */
+  project_id?: string | null;
+  tags: string[];
+  flagged: boolean;
+  spans: QuickAddSpan[];
+}
+
+/** 快速添加窗口提交、等待主窗口保存的任务，对应 Rust 侧 `capture::CapturedTask` */
+export interface CapturedTask {
+  title: string;
+  project?: string | null;
+  /** ISO 时间字符串 */
+  date?: string | null;
+  /** 标签名，已有的复用，没有的新建为全局标签 */
+  tags: string[];
+  flagged: boolean;
+  rrule?: string | null;
+}
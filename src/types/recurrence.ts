@@ -0,0 +1,14 @@
+/** 重复任务规则，对应 Rust 侧 `task_recurrences` 的一行 */
+export interface TaskRecurrence {
+  task_id: string;
+  series_id: string;
+  /** RFC 5545 RRULE，例如 `FREQ=WEEKLY;BYDAY=FR` */
+  rrule: string;
+  dtstart: string;
+  /** 跳过的日期（YYYY-MM-DD） */
+  exdates: string[];
+  occurrence: number;
+  next_task_id?: string;
+  created_at: string;
+  updated_at: string;
+}
@@ -0,0 +1,16 @@
+/** 智能清单：保存下来的查询，在侧边栏以虚拟清单 `smart:<id>` 出现 */
+export interface SmartList {
+  id: string;
+  name: string;
+  query: string;
+  icon: string;
+  sort_order?: number;
+  created_at: string;
+  updated_at: string;
+}
+
+export const SMART_LIST_PREFIX = "smart:";
+
+export const smartListProjectId = (id: string) => `${SMART_LIST_PREFIX}${id}`;
+
+export const isSmartListProject = (projectId: string) => projectId.startsWith(SMART_LIST_PREFIX);
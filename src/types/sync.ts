@@ -0,0 +1,30 @@
+/** 同步目标，来自当前的 Supabase 会话，对应 Rust 侧 `sync::SyncConfig` */
+export interface SyncConfig {
+  url: string;
+  anonKey: string;
+  accessToken: string;
+  userId: string;
+}
+
+export interface SyncStatus {
+  enabled: boolean;
+  /** 发件箱中等待推送的行数 */
+  pending: number;
+  last_synced_at: string | null;
+  last_error: string | null;
+}
+
+/** 本地与云端都修改过的同一行：任务逐字段合并（merged 表示两边的修改都保留了），其余按 updated_at 较新的一方保留 */
+export interface SyncConflict {
+  table: "projects" | "tags" | "tasks" | "task_tags";
+  row_id: string;
+  local_updated_at: string | null;
+  remote_updated_at: string | null;
+  winner: "local" | "remote" | "merged";
+}
+
+export interface SyncReport {
+  pulled: number;
+  pushed: number;
+  conflicts: SyncConflict[];
+}
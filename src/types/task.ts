@@ -16,6 +16,8 @@ export interface Task {
   abandoned_at?: string; // ISO string format representing when the task was abandoned
   flagged?: boolean; // Whether the task is flagged for quick access
   attachments?: TaskAttachment[]; // File attachments
+  parent_id?: string | null; // Parent task id; empty for top-level tasks
+  estimate_minutes?: number | null; // Estimated duration in minutes
   /** 乐观更新标记：任务正在创建中，尚未持久化 */
   _isPending?: boolean;
   /** 乐观更新时的临时 ID，用于后续替换 */
@@ -0,0 +1,39 @@
+/** 时间记录来源：计时器、手动补录，或关联到任务的专注番茄（只读） */
+export type TimeEntrySource = "timer" | "manual" | "pomodoro";
+
+/** 一条时间记录，对应 Rust 侧 `storage::time_tracking::TimeEntry` */
+export interface TimeEntry {
+  id: string;
+  task_id: string;
+  started_at: string;
+  /** 计时进行中时为空 */
+  ended_at?: string | null;
+  source: TimeEntrySource;
+  note?: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+/** 某个任务、项目或标签的实际用时与预估用时（分钟） */
+export interface TimeRollup {
+  key: string;
+  label: string;
+  /** 计时器与手动记录 */
+  tracked_minutes: number;
+  /** 关联到任务的已完成专注番茄 */
+  pomodoro_minutes: number;
+  estimated_minutes: number;
+}
+
+export interface TimeSummary {
+  tasks: TimeRollup[];
+  projects: TimeRollup[];
+  tags: TimeRollup[];
+}
+
+export interface ManualTimeEntryInput {
+  task_id: string;
+  started_at: string;
+  ended_at: string;
+  note?: string | null;
+}
@@ -34,7 +34,8 @@ export const ensureNotificationPermission = async (): Promise<boolean> => {
   return permission === "granted";
 };
 
-// Unified notification API - currently only browser notifications
+// Unified notification API - browser notifications only; when the desktop app
+// keeps its data in SQLite, deadline reminders are sent natively from Rust instead
 export const sendNotification = async (options: { title: string, body: string, tag?: string }) => {
   const { title, body, tag } = options;
   await sendBrowserNotification(title, body, tag);
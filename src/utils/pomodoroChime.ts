@@ -0,0 +1,25 @@
+export const playCompletionChime = () => {
+  try {
+    const audioContext = new AudioContext();
+    const oscillator = audioContext.createOscillator();
+    const gainNode = audioContext.createGain();
+
+    oscillator.type = "sine";
+    oscillator.frequency.value = 880;
+    oscillator.connect(gainNode);
+    gainNode.connect(audioContext.destination);
+
+    gainNode.gain.setValueAtTime(0.15, audioContext.currentTime);
+    gainNode.gain.exponentialRampToValueAtTime(0.00001, audioContext.currentTime + 1.2);
+
+    oscillator.start(audioContext.currentTime);
+    oscillator.stop(audioContext.currentTime + 1.3);
+    oscillator.onended = () => {
+      audioContext.close().catch((error) => {
+        console.error("Failed to close pomodoro chime audio context:", error);
+      });
+    };
+  } catch (error) {
+    console.error("Unable to play pomodoro completion chime:", error);
+  }
+};
@@ -21,3 +21,47 @@ export function navigateWithReload(path: string): void {
 }
 
 
+
+type TauriEventApi = {
+  event?: {
+    listen: (event: string, handler: (event: { payload: unknown }) => void) => Promise<() => void>;
+  };
+};
+
+/**
+ * Listen to an event emitted by the Tauri process.
+ * Returns an unsubscribe function; a no-op outside Tauri.
+ */
+export function listenTauriEvent<T = unknown>(event: string, handler: (payload: T) => void): () => void {
+  const tauri = (window as unknown as { __TAURI__?: TauriEventApi }).__TAURI__;
+  if (!tauri?.event) return () => {};
+
+  let disposed = false;
+  let unlisten: (() => void) | null = null;
+  tauri.event
+    .listen(event, ({ payload }) => handler(payload as T))
+    .then((fn) => {
+      if (disposed) fn();
+      else unlisten = fn;
+    })
+    .catch((e) => console.error(`Failed to listen to ${event}:`, e));
+
+  return () => {
+    disposed = true;
+    unlisten?.();
+  };
+}
+
+/**
+ * Call a command registered in the Tauri process.
+ * Only valid when `isTauriRuntime()` is true.
+ */
+export function invokeTauri<T>(command: string, args?: Record<string, unknown>): Promise<T> {
+  const tauri = (window as unknown as {
+    __TAURI__?: { core?: { invoke: (cmd: string, args?: Record<string, unknown>) => Promise<unknown> } };
+  }).__TAURI__;
+  if (!tauri?.core) {
+    return Promise.reject(new Error("Tauri runtime is not available"));
+  }
+  return tauri.core.invoke(command, args) as Promise<T>;
+}